@@ -0,0 +1,61 @@
+//! ML-DSA-87 verification throughput: one signature at a time, as a rayon
+//! batch, and as a batch of signatures already in the cache.
+//!
+//! Run with `cargo bench -p synergy-testnet --bench signature_verification`.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use synergy_testnet::crypto::pqc::{self, SignatureAlgorithm, SignatureCache, Signer, VerifyRequest};
+
+const BATCH_SIZES: [usize; 3] = [16, 64, 256];
+const ALGORITHM: SignatureAlgorithm = SignatureAlgorithm::MlDsa87;
+
+struct Signed {
+    public_key: Vec<u8>,
+    message: Vec<u8>,
+    signature: Vec<u8>,
+}
+
+/// `count` signatures by different keys, like the transactions of a block.
+fn signed(count: usize) -> Vec<Signed> {
+    (0..count)
+        .map(|i| {
+            let (public_key, private_key) = ALGORITHM.generate_keypair();
+            let message = format!("transaction {}", i).into_bytes();
+            let signature = ALGORITHM.sign(&private_key, &message).unwrap();
+            Signed { public_key, message, signature }
+        })
+        .collect()
+}
+
+fn requests(signed: &[Signed]) -> Vec<VerifyRequest<'_>> {
+    signed
+        .iter()
+        .map(|s| VerifyRequest { algorithm: ALGORITHM, public_key: &s.public_key, message: &s.message, signature: &s.signature })
+        .collect()
+}
+
+fn verification(c: &mut Criterion) {
+    let mut group = c.benchmark_group("ML-DSA-87 verification");
+    for size in BATCH_SIZES {
+        let signed = signed(size);
+        let requests = requests(&signed);
+        group.throughput(Throughput::Elements(size as u64));
+
+        group.bench_with_input(BenchmarkId::new("sequential", size), &requests, |b, requests| {
+            b.iter(|| requests.iter().map(VerifyRequest::verify).collect::<Vec<_>>())
+        });
+        group.bench_with_input(BenchmarkId::new("batch", size), &requests, |b, requests| {
+            b.iter(|| pqc::verify_batch(requests))
+        });
+
+        let cache = SignatureCache::new(size);
+        assert!(cache.verify_batch(&requests).into_iter().all(|valid| valid));
+        group.bench_with_input(BenchmarkId::new("cached", size), &requests, |b, requests| {
+            b.iter(|| cache.verify_batch(requests))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, verification);
+criterion_main!(benches);
@@ -0,0 +1,247 @@
+//! Generates the client types and methods from `openrpc.json`.
+//!
+//! The spec is produced by the node (`synergy-testnet openrpc`); a node test
+//! fails when the committed copy falls out of date.
+
+use std::env;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+use serde_json::{Map, Value};
+
+const SPEC: &str = "openrpc.json";
+const SCHEMA_PREFIX: &str = "#/components/schemas/";
+
+fn main() {
+    println!("cargo:rerun-if-changed={}", SPEC);
+
+    let contents = fs::read_to_string(SPEC).expect("Failed to read openrpc.json");
+    let spec: Value = serde_json::from_str(&contents).expect("openrpc.json is not valid JSON");
+
+    let mut out = String::new();
+    if let Some(schemas) = spec["components"]["schemas"].as_object() {
+        for (name, schema) in schemas {
+            write_type(&mut out, name, schema);
+        }
+    }
+
+    out.push_str("impl Client {\n");
+    for method in spec["methods"].as_array().expect("openrpc.json has no methods") {
+        write_method(&mut out, method);
+    }
+    out.push_str("}\n");
+
+    let path = Path::new(&env::var("OUT_DIR").unwrap()).join("generated.rs");
+    fs::write(path, out).expect("Failed to write generated client");
+}
+
+fn write_type(out: &mut String, name: &str, schema: &Value) {
+    write_doc(out, "", schema["description"].as_str());
+
+    if let Some(variants) = schema["enum"].as_array() {
+        out.push_str("#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]\n");
+        writeln!(out, "pub enum {} {{", name).unwrap();
+        for variant in variants.iter().filter_map(|v| v.as_str()) {
+            let name = variant_name(variant);
+            if name != variant {
+                writeln!(out, "    #[serde(rename = \"{}\")]", variant).unwrap();
+            }
+            writeln!(out, "    {},", name).unwrap();
+        }
+        out.push_str("}\n\n");
+        return;
+    }
+
+    let empty = Map::new();
+    let properties = schema["properties"].as_object().unwrap_or(&empty);
+    let required: Vec<&str> = schema["required"]
+        .as_array()
+        .map(|r| r.iter().filter_map(|v| v.as_str()).collect())
+        .unwrap_or_default();
+
+    out.push_str("#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]\n");
+    writeln!(out, "pub struct {} {{", name).unwrap();
+    for (property, property_schema) in properties {
+        write_doc(out, "    ", property_schema["description"].as_str());
+
+        let field = snake_case(property);
+        if field != *property {
+            writeln!(out, "    #[serde(rename = \"{}\")]", property).unwrap();
+        }
+
+        let mut ty = rust_type(property_schema);
+        if !required.contains(&property.as_str()) {
+            if !ty.starts_with("Option<") && !has_default(&ty) {
+                ty = format!("Option<{}>", ty);
+            }
+            out.push_str("    #[serde(default)]\n");
+        }
+        writeln!(out, "    pub {}: {},", ident(&field), ty).unwrap();
+    }
+    out.push_str("}\n\n");
+}
+
+fn write_method(out: &mut String, method: &Value) {
+    let name = method["name"].as_str().expect("method without a name");
+    let function = snake_case(name.trim_start_matches("synergy_").replace('.', "_").as_str());
+
+    let mut arguments = Vec::new();
+    let mut values = Vec::new();
+    for param in method["params"].as_array().into_iter().flatten() {
+        let param_name = ident(&snake_case(param["name"].as_str().expect("param without a name")));
+        let mut ty = rust_type(&param["schema"]);
+        if param["required"] != Value::Bool(true) && !ty.starts_with("Option<") {
+            ty = format!("Option<{}>", ty);
+        }
+        arguments.push(format!("{}: {}", param_name, argument_type(&ty)));
+        values.push(param_name);
+    }
+
+    let result = rust_type(&method["result"]["schema"]);
+
+    write_doc(out, "    ", method["summary"].as_str());
+    writeln!(out, "    ///\n    /// Calls `{}`.", name).unwrap();
+    writeln!(
+        out,
+        "    pub async fn {}(&self{}) -> Result<{}, Error> {{",
+        function,
+        arguments.iter().map(|a| format!(", {}", a)).collect::<String>(),
+        result,
+    ).unwrap();
+    writeln!(
+        out,
+        "        self.request(\"{}\", serde_json::json!([{}])).await",
+        name,
+        values.join(", "),
+    ).unwrap();
+    out.push_str("    }\n\n");
+}
+
+/// Maps a JSON Schema to a Rust type. Anything the generator does not
+/// understand becomes `serde_json::Value`.
+fn rust_type(schema: &Value) -> String {
+    if let Some(reference) = schema["$ref"].as_str() {
+        return reference.trim_start_matches(SCHEMA_PREFIX).to_string();
+    }
+
+    if let Some(options) = schema["anyOf"].as_array() {
+        let non_null: Vec<&Value> = options.iter().filter(|o| o["type"] != "null").collect();
+        if non_null.len() == 1 && options.len() == 2 {
+            return format!("Option<{}>", rust_type(non_null[0]));
+        }
+        return "serde_json::Value".to_string();
+    }
+
+    match &schema["type"] {
+        Value::String(ty) => primitive_type(ty, schema),
+        Value::Array(types) => {
+            let non_null: Vec<&str> = types.iter().filter_map(|t| t.as_str()).filter(|t| *t != "null").collect();
+            if non_null.len() == 1 {
+                let inner = primitive_type(non_null[0], schema);
+                if types.len() > 1 {
+                    format!("Option<{}>", inner)
+                } else {
+                    inner
+                }
+            } else {
+                "serde_json::Value".to_string()
+            }
+        }
+        _ => "serde_json::Value".to_string(),
+    }
+}
+
+fn primitive_type(ty: &str, schema: &Value) -> String {
+    match ty {
+        "string" => "String".to_string(),
+        "boolean" => "bool".to_string(),
+        "number" => "f64".to_string(),
+        "integer" => match schema["format"].as_str() {
+            Some("uint8") => "u8",
+            Some("uint16") => "u16",
+            Some("uint32") => "u32",
+            Some("uint64") => "u64",
+            Some("uint") => "usize",
+            Some("int8") => "i8",
+            Some("int16") => "i16",
+            Some("int32") => "i32",
+            Some("int") => "isize",
+            _ => "i64",
+        }.to_string(),
+        "array" => format!("Vec<{}>", rust_type(&schema["items"])),
+        "object" => match schema.get("additionalProperties") {
+            Some(values) if values.is_object() => format!("std::collections::HashMap<String, {}>", rust_type(values)),
+            _ => "serde_json::Value".to_string(),
+        },
+        _ => "serde_json::Value".to_string(),
+    }
+}
+
+/// Types that can fall back to `Default` when a field is missing.
+fn has_default(ty: &str) -> bool {
+    matches!(ty, "String" | "bool" | "f64" | "u8" | "u16" | "u32" | "u64" | "usize" | "i8" | "i16" | "i32" | "i64" | "isize")
+        || ty.starts_with("Vec<")
+        || ty.starts_with("std::collections::HashMap<")
+}
+
+/// Borrowed form of a parameter type for method signatures.
+fn argument_type(ty: &str) -> String {
+    match ty {
+        "String" => "&str".to_string(),
+        "Option<String>" => "Option<&str>".to_string(),
+        _ if has_default(ty) || ty.starts_with("Option<") => ty.to_string(),
+        _ => format!("&{}", ty),
+    }
+}
+
+fn snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut out = String::new();
+    for (i, c) in chars.iter().enumerate() {
+        if c.is_uppercase() {
+            let previous = if i > 0 { Some(chars[i - 1]) } else { None };
+            let next = chars.get(i + 1);
+            let starts_word = match previous {
+                Some(p) if p.is_lowercase() || p.is_ascii_digit() => true,
+                Some(p) if p.is_uppercase() => next.is_some_and(|n| n.is_lowercase()),
+                _ => false,
+            };
+            if starts_word && !out.ends_with('_') {
+                out.push('_');
+            }
+            out.extend(c.to_lowercase());
+        } else {
+            out.push(*c);
+        }
+    }
+    out
+}
+
+/// Enum values that are not type-style identifiers, such as `ML-DSA-87`
+/// or `account`, become `MlDsa87` and `Account`.
+fn variant_name(value: &str) -> String {
+    if value.starts_with(|c: char| c.is_ascii_uppercase()) && value.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return value.to_string();
+    }
+    value
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(|part| part[..1].to_ascii_uppercase() + &part[1..].to_ascii_lowercase())
+        .collect()
+}
+
+fn ident(name: &str) -> String {
+    match name {
+        "type" | "ref" | "move" | "match" | "loop" | "self" | "struct" | "enum" | "fn" | "impl" | "mod" | "use" | "where" => format!("r#{}", name),
+        _ => name.to_string(),
+    }
+}
+
+fn write_doc(out: &mut String, indent: &str, doc: Option<&str>) {
+    if let Some(doc) = doc {
+        for line in doc.lines() {
+            writeln!(out, "{}/// {}", indent, line).unwrap();
+        }
+    }
+}
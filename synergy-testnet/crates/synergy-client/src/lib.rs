@@ -0,0 +1,114 @@
+//! Typed JSON-RPC client for Synergy Network nodes.
+//!
+//! Every type and method here is generated at build time from
+//! `openrpc.json`, the document a node serves at `rpc.discover`. Method
+//! names drop the `synergy_` prefix and use snake case, so
+//! `synergy_getBlockByNumber` becomes [`Client::get_block_by_number`].
+//!
+//! ```no_run
+//! # async fn example() -> Result<(), synergy_client::Error> {
+//! let client = synergy_client::Client::new("http://localhost:8545");
+//! let height = client.block_number().await?;
+//! let block = client.get_block_by_number(height).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+include!(concat!(env!("OUT_DIR"), "/generated.rs"));
+
+/// Error object returned by the node.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+    #[serde(default)]
+    pub data: Option<Value>,
+}
+
+#[derive(Debug)]
+pub enum Error {
+    /// The request could not be sent or the response could not be read.
+    Transport(reqwest::Error),
+    /// The node answered with a JSON-RPC error.
+    Rpc(RpcError),
+    /// The response was not a valid JSON-RPC response for this method.
+    InvalidResponse(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Transport(e) => write!(f, "transport error: {}", e),
+            Error::Rpc(e) => write!(f, "{} ({})", e.message, e.code),
+            Error::InvalidResponse(e) => write!(f, "invalid response: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<reqwest::Error> for Error {
+    fn from(error: reqwest::Error) -> Self {
+        Error::Transport(error)
+    }
+}
+
+pub struct Client {
+    http: reqwest::Client,
+    url: String,
+    token: Option<String>,
+    next_id: AtomicU64,
+}
+
+impl Client {
+    pub fn new(url: impl Into<String>) -> Self {
+        Client {
+            http: reqwest::Client::new(),
+            url: url.into(),
+            token: None,
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    /// Sends `token` as a bearer token, as required for `admin` and `debug`
+    /// methods and for `wallet` methods on a remote node.
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    /// Calls any method by name. The generated methods go through here.
+    pub async fn request<R: DeserializeOwned>(&self, method: &str, params: Value) -> Result<R, Error> {
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": self.next_id.fetch_add(1, Ordering::Relaxed),
+            "method": method,
+            "params": params,
+        });
+
+        let mut request = self.http.post(&self.url).json(&body);
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+        let mut response: Value = request.send().await?.json().await?;
+
+        if let Some(error) = response.get_mut("error") {
+            let error = serde_json::from_value(error.take())
+                .map_err(|e| Error::InvalidResponse(e.to_string()))?;
+            return Err(Error::Rpc(error));
+        }
+
+        let result = response
+            .get_mut("result")
+            .map(Value::take)
+            .ok_or_else(|| Error::InvalidResponse("missing result".to_string()))?;
+        serde_json::from_value(result).map_err(|e| Error::InvalidResponse(e.to_string()))
+    }
+}
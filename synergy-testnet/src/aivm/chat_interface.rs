@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use std::sync::Mutex;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use super::runtime::AIVMExecutionContext;
@@ -21,15 +22,21 @@ pub struct ChatSession {
 
 #[derive(Debug)]
 pub struct ChatInterface {
-    sessions: HashMap<String, ChatSession>,
+    sessions: Mutex<HashMap<String, ChatSession>>,
     model_endpoint: String,
     api_key: Option<String>,
 }
 
+impl Default for ChatInterface {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ChatInterface {
     pub fn new() -> Self {
         ChatInterface {
-            sessions: HashMap::new(),
+            sessions: Mutex::new(HashMap::new()),
             model_endpoint: "http://localhost:8000".to_string(), // Default GPT-OSS endpoint
             api_key: None,
         }
@@ -37,7 +44,7 @@ impl ChatInterface {
 
     pub fn with_endpoint(endpoint: String) -> Self {
         ChatInterface {
-            sessions: HashMap::new(),
+            sessions: Mutex::new(HashMap::new()),
             model_endpoint: endpoint,
             api_key: None,
         }
@@ -110,7 +117,7 @@ impl ChatInterface {
         session.messages.push(ai_message);
 
         // Update session
-        self.sessions.insert(session_id, session);
+        self.sessions.lock().unwrap().insert(session_id, session);
 
         Ok(ai_response)
     }
@@ -118,9 +125,9 @@ impl ChatInterface {
     fn get_or_create_session(
         &self,
         session_id: &str,
-        context: &AIVMExecutionContext,
+        _context: &AIVMExecutionContext,
     ) -> ChatSession {
-        if let Some(session) = self.sessions.get(session_id) {
+        if let Some(session) = self.sessions.lock().unwrap().get(session_id) {
             session.clone()
         } else {
             ChatSession {
@@ -212,7 +219,7 @@ impl ChatInterface {
 
         if let Some(choices) = response.get("choices") {
             if let Some(choices_array) = choices.as_array() {
-                if let Some(first_choice) = choices_array.get(0) {
+                if let Some(first_choice) = choices_array.first() {
                     if let Some(message) = first_choice.get("message") {
                         if let Some(content) = message.get("content") {
                             if let Some(content_str) = content.as_str() {
@@ -227,28 +234,29 @@ impl ChatInterface {
         Err("Unexpected response format from AI model".to_string())
     }
 
-    pub fn get_session(&self, session_id: &str) -> Option<&ChatSession> {
-        self.sessions.get(session_id)
+    pub fn get_session(&self, session_id: &str) -> Option<ChatSession> {
+        self.sessions.lock().unwrap().get(session_id).cloned()
     }
 
-    pub fn get_all_sessions(&self) -> Vec<&ChatSession> {
-        self.sessions.values().collect()
+    pub fn get_all_sessions(&self) -> Vec<ChatSession> {
+        self.sessions.lock().unwrap().values().cloned().collect()
     }
 
-    pub fn clear_session(&mut self, session_id: &str) {
-        self.sessions.remove(session_id);
+    pub fn clear_session(&self, session_id: &str) {
+        self.sessions.lock().unwrap().remove(session_id);
     }
 
-    pub fn clear_all_sessions(&mut self) {
-        self.sessions.clear();
+    pub fn clear_all_sessions(&self) {
+        self.sessions.lock().unwrap().clear();
     }
 
     pub fn get_session_stats(&self) -> HashMap<String, usize> {
+        let sessions = self.sessions.lock().unwrap();
         let mut stats = HashMap::new();
-        stats.insert("total_sessions".to_string(), self.sessions.len());
+        stats.insert("total_sessions".to_string(), sessions.len());
         stats.insert(
             "total_messages".to_string(),
-            self.sessions.values().map(|s| s.messages.len()).sum(),
+            sessions.values().map(|s| s.messages.len()).sum(),
         );
         stats
     }
@@ -1,9 +1,9 @@
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use crate::consensus::consensus_algorithm::ProofOfSynergy;
 use crate::validator::ValidatorManager;
-use crate::block::BlockChain;
+use crate::events::{ComputationStatusChange, NodeEvent, EVENT_BUS};
 use super::model_registry::{AIModel, ModelRegistry};
 use super::chat_interface::ChatInterface;
 
@@ -34,7 +34,7 @@ pub enum ComputationStatus {
     Timeout,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct AIComputationTask {
     pub task_id: String,
     pub computation_id: String,
@@ -49,7 +49,7 @@ pub struct AIComputationTask {
     pub reward_claimed: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 pub enum TaskStatus {
     Assigned,
     InProgress,
@@ -92,17 +92,17 @@ pub enum RewardBasis {
 pub struct DistributedAIProtocol {
     computations: Arc<Mutex<HashMap<String, DistributedAIComputation>>>,
     tasks: Arc<Mutex<HashMap<String, AIComputationTask>>>,
+    #[allow(dead_code)]
     model_shards: Arc<Mutex<HashMap<String, ModelShard>>>,
     reward_distributions: Arc<Mutex<HashMap<String, AIRewardDistribution>>>,
-    consensus_engine: Arc<ProofOfSynergy>,
     validator_manager: Arc<ValidatorManager>,
     model_registry: Arc<ModelRegistry>,
+    #[allow(dead_code)]
     chat_interface: Arc<ChatInterface>,
 }
 
 impl DistributedAIProtocol {
     pub fn new(
-        consensus_engine: Arc<ProofOfSynergy>,
         validator_manager: Arc<ValidatorManager>,
         model_registry: Arc<ModelRegistry>,
         chat_interface: Arc<ChatInterface>,
@@ -112,7 +112,6 @@ impl DistributedAIProtocol {
             tasks: Arc::new(Mutex::new(HashMap::new())),
             model_shards: Arc::new(Mutex::new(HashMap::new())),
             reward_distributions: Arc::new(Mutex::new(HashMap::new())),
-            consensus_engine,
             validator_manager,
             model_registry,
             chat_interface,
@@ -193,6 +192,8 @@ impl DistributedAIProtocol {
             }
         }
 
+        Self::publish_status(&computation);
+
         // Store computation
         if let Ok(mut computations) = self.computations.lock() {
             computations.insert(computation_id.clone(), computation);
@@ -226,26 +227,32 @@ impl DistributedAIProtocol {
         }
 
         // Update task status
+        let mut computation_id = None;
         if let Ok(mut tasks) = self.tasks.lock() {
             if let Some(task) = tasks.get_mut(task_id) {
-                task.status = TaskStatus::Completed;
-                task.completed_at = Some(std::time::SystemTime::now()
+                let completed_at = std::time::SystemTime::now()
                     .duration_since(std::time::UNIX_EPOCH)
                     .unwrap()
-                    .as_secs());
+                    .as_secs();
+                task.status = TaskStatus::Completed;
+                task.completed_at = Some(completed_at);
                 task.partial_result = Some(partial_result.clone());
+                crate::metrics::AI_TASK_DURATION_SECONDS
+                    .observe(completed_at.saturating_sub(task.assigned_at) as f64);
+                computation_id = Some(task.computation_id.clone());
             }
         }
 
         // Update computation
-        if let Ok(mut computations) = self.computations.lock() {
-            if let Some(computation) = computations.get_mut(&task.computation_id) {
+        if let (Some(computation_id), Ok(mut computations)) = (computation_id, self.computations.lock()) {
+            if let Some(computation) = computations.get_mut(&computation_id) {
                 computation.results.insert(validator_address.to_string(), partial_result);
                 computation.current_confirmations += 1;
 
                 // Check if we have enough confirmations
                 if computation.current_confirmations >= computation.required_confirmations {
                     computation.computation_status = ComputationStatus::Aggregating;
+                    Self::publish_status(computation);
                     self.aggregate_results(&computation.computation_id)?;
                 }
             }
@@ -255,8 +262,8 @@ impl DistributedAIProtocol {
     }
 
     fn start_distributed_computation(&self, computation_id: &str) -> Result<(), String> {
-        if let Ok(computations) = self.computations.lock() {
-            if let Some(computation) = computations.get(computation_id) {
+        if let Ok(mut computations) = self.computations.lock() {
+            if let Some(computation) = computations.get_mut(computation_id) {
                 // Notify validators in the cluster to start computation
                 for validator_address in &computation.participating_validators {
                     let task_id = format!("{}_task_{}", computation_id, validator_address);
@@ -273,6 +280,9 @@ impl DistributedAIProtocol {
                         }
                     }
                 }
+
+                computation.computation_status = ComputationStatus::InProgress;
+                Self::publish_status(computation);
             }
         }
 
@@ -292,12 +302,16 @@ impl DistributedAIProtocol {
                 // Update computation status
                 if let Ok(mut computations) = self.computations.lock() {
                     if let Some(comp) = computations.get_mut(computation_id) {
-                        comp.computation_status = ComputationStatus::Completed;
-                        comp.completed_at = Some(std::time::SystemTime::now()
+                        let completed_at = std::time::SystemTime::now()
                             .duration_since(std::time::UNIX_EPOCH)
                             .unwrap()
-                            .as_secs());
+                            .as_secs();
+                        comp.computation_status = ComputationStatus::Completed;
+                        comp.completed_at = Some(completed_at);
+                        crate::metrics::AI_COMPUTATION_DURATION_SECONDS
+                            .observe(completed_at.saturating_sub(comp.created_at) as f64);
                         comp.final_result = Some(final_result.clone());
+                        Self::publish_status(comp);
                     }
                 }
 
@@ -346,12 +360,12 @@ impl DistributedAIProtocol {
         }
     }
 
-    fn select_optimal_cluster_for_ai(&self, model: &AIModel) -> u64 {
+    fn select_optimal_cluster_for_ai(&self, _model: &AIModel) -> u64 {
         // Use synergy scoring to select the best cluster for AI computation
         // This integrates with the existing Proof of Synergy consensus
 
         let active_validators = self.validator_manager.get_active_validators();
-        let clusters = self.consensus_engine.get_validator_clusters();
+        let clusters = self.validator_manager.get_validator_clusters();
 
         // Find cluster with highest average synergy score for AI tasks
         let mut best_cluster_id = 0u64;
@@ -381,7 +395,7 @@ impl DistributedAIProtocol {
 
     fn get_cluster_validators_for_ai(&self, cluster_id: u64) -> Result<Vec<String>, String> {
         let active_validators = self.validator_manager.get_active_validators();
-        let clusters = self.consensus_engine.get_validator_clusters();
+        let clusters = self.validator_manager.get_validator_clusters();
 
         if let Some(cluster) = clusters.get(&cluster_id) {
             Ok(cluster.validators
@@ -513,6 +527,19 @@ impl DistributedAIProtocol {
         stats
     }
 
+    fn publish_status(computation: &DistributedAIComputation) {
+        EVENT_BUS.publish(NodeEvent::ComputationStatus(ComputationStatusChange {
+            computation_id: computation.computation_id.clone(),
+            model_id: computation.model_id.clone(),
+            cluster_id: computation.cluster_id,
+            status: computation.computation_status.clone(),
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+        }));
+    }
+
     pub fn cleanup_expired_computations(&self, max_age_seconds: u64) -> usize {
         let current_time = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
@@ -532,9 +559,11 @@ impl DistributedAIProtocol {
                 .collect();
 
             for id in expired_ids {
-                if let Some(computation) = computations.remove(&id) {
+                if let Some(mut computation) = computations.remove(&id) {
                     // Mark as failed/timeout
                     // In a real implementation, would handle cleanup and refunds
+                    computation.computation_status = ComputationStatus::Timeout;
+                    Self::publish_status(&computation);
                     println!("🧹 Cleaned up expired AI computation: {}", id);
                     cleaned_count += 1;
                 }
@@ -3,7 +3,7 @@ use std::sync::{Arc, Mutex};
 use serde::{Deserialize, Serialize};
 use hex;
 use crate::transaction::Transaction;
-use crate::crypto::pqc::{PQCManager, PQCAlgorithm};
+use crate::crypto::pqc::{Kem, KemAlgorithm, PQCAlgorithm, PQCSignature, SignatureAlgorithm, Signer};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CrossChainMessage {
@@ -23,7 +23,7 @@ pub struct CrossChainMessage {
     pub required_confirmations: u32,
     pub pqc_algorithm: PQCAlgorithm,
     pub security_level: SecurityLevel,
-    pub validator_signatures: Vec<String>,
+    pub validator_signatures: Vec<PQCSignature>,
     pub encryption_key_id: Option<String>,
 }
 
@@ -83,7 +83,7 @@ pub enum ChainStatus {
     Deprecated,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, PartialOrd)]
 pub enum SecurityLevel {
     Basic,
     Enhanced,
@@ -128,19 +128,31 @@ pub enum BridgeStatus {
     Refunded,
 }
 
+/// Signature schemes of `SecurityLevel::Maximum` messages, one per family.
+const MAXIMUM_SIGNATURE_ALGORITHMS: [SignatureAlgorithm; 3] =
+    [SignatureAlgorithm::MlDsa87, SignatureAlgorithm::Falcon1024, SignatureAlgorithm::SlhDsaSha2_256f];
+/// `SecurityLevel::Military` adds SLH-DSA over SHAKE, so no single hash
+/// function underpins every signature.
+const MILITARY_SIGNATURE_ALGORITHMS: [SignatureAlgorithm; 4] = [
+    SignatureAlgorithm::MlDsa87,
+    SignatureAlgorithm::Falcon1024,
+    SignatureAlgorithm::SlhDsaSha2_256f,
+    SignatureAlgorithm::SlhDsaShake256f,
+];
+
 #[derive(Debug)]
 pub struct InteroperabilityLayer {
     supported_chains: Arc<Mutex<HashMap<String, ChainInfo>>>,
     pending_messages: Arc<Mutex<HashMap<String, CrossChainMessage>>>,
     bridge_transactions: Arc<Mutex<HashMap<String, BridgeTransaction>>>,
     message_routing: Arc<Mutex<HashMap<String, String>>>, // message_id -> handler_contract
-    pqc_manager: Arc<PQCManager>,
     security_config: SecurityConfiguration,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SecurityConfiguration {
-    pub default_pqc_algorithm: PQCAlgorithm,
+    pub default_pqc_algorithm: SignatureAlgorithm,
+    pub kem_algorithm: KemAlgorithm,
     pub minimum_security_level: SecurityLevel,
     pub require_validator_attestation: bool,
     pub enable_zero_knowledge_proofs: bool,
@@ -148,18 +160,22 @@ pub struct SecurityConfiguration {
     pub encryption_timeout_seconds: u64,
 }
 
+impl Default for InteroperabilityLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl InteroperabilityLayer {
     pub fn new() -> Self {
-        let pqc_manager = Arc::new(PQCManager::new());
-
         InteroperabilityLayer {
             supported_chains: Arc::new(Mutex::new(HashMap::new())),
             pending_messages: Arc::new(Mutex::new(HashMap::new())),
             bridge_transactions: Arc::new(Mutex::new(HashMap::new())),
             message_routing: Arc::new(Mutex::new(HashMap::new())),
-            pqc_manager: pqc_manager.clone(),
             security_config: SecurityConfiguration {
-                default_pqc_algorithm: PQCAlgorithm::Dilithium,
+                default_pqc_algorithm: SignatureAlgorithm::MlDsa87,
+                kem_algorithm: KemAlgorithm::MlKem1024,
                 minimum_security_level: SecurityLevel::Enhanced,
                 require_validator_attestation: true,
                 enable_zero_knowledge_proofs: true,
@@ -203,46 +219,48 @@ impl InteroperabilityLayer {
         match message.security_level {
             SecurityLevel::Basic => {
                 // Basic security - just hash the message
-                message.pqc_algorithm = PQCAlgorithm::Dilithium;
+                message.pqc_algorithm = self.security_config.default_pqc_algorithm.into();
             },
             SecurityLevel::Enhanced => {
                 // Enhanced security - encrypt payload
-                message.pqc_algorithm = PQCAlgorithm::Kyber;
-                message.encrypted_payload = Some(self.encrypt_message_payload(&message.payload)?);
+                let kem_algorithm = self.security_config.kem_algorithm;
+                message.pqc_algorithm = kem_algorithm.into();
+                message.encrypted_payload = Some(self.encrypt_message_payload(kem_algorithm, &message.payload)?);
             },
             SecurityLevel::Maximum => {
                 // Maximum security - encrypt + sign with multiple algorithms
-                message.pqc_algorithm = PQCAlgorithm::Falcon;
-                message.encrypted_payload = Some(self.encrypt_message_payload(&message.payload)?);
+                message.pqc_algorithm = SignatureAlgorithm::Falcon1024.into();
+                message.encrypted_payload = Some(self.encrypt_message_payload(self.security_config.kem_algorithm, &message.payload)?);
 
                 // Generate multiple signatures for verification
-                let signatures = self.generate_multi_algorithm_signatures(&message.payload)?;
+                let signatures = self.generate_multi_algorithm_signatures(&MAXIMUM_SIGNATURE_ALGORITHMS, &message.payload)?;
                 message.validator_signatures = signatures;
             },
             SecurityLevel::Military => {
                 // Military-grade security - full encryption + zero-knowledge proofs
-                message.pqc_algorithm = PQCAlgorithm::ClassicMcEliece;
-                message.encrypted_payload = Some(self.encrypt_message_payload(&message.payload)?);
+                message.pqc_algorithm = KemAlgorithm::McEliece348864.into();
+                message.encrypted_payload = Some(self.encrypt_message_payload(KemAlgorithm::McEliece348864, &message.payload)?);
 
                 // Generate comprehensive security signatures
-                let signatures = self.generate_military_grade_signatures(&message.payload)?;
+                let signatures = self.generate_multi_algorithm_signatures(&MILITARY_SIGNATURE_ALGORITHMS, &message.payload)?;
                 message.validator_signatures = signatures;
 
                 // Generate zero-knowledge proof of message validity
                 if self.security_config.enable_zero_knowledge_proofs {
-                    let zk_proof = self.generate_zero_knowledge_proof(&message.payload)?;
+                    let _zk_proof = self.generate_zero_knowledge_proof(&message.payload)?;
                     // Store ZK proof in encrypted payload (simplified)
                 }
             },
         }
 
+        // Route message to appropriate handler
+        let handler_contract = format!("bridge_{}", message.destination_chain);
+
         // Store message for processing
         if let Ok(mut messages) = self.pending_messages.lock() {
             messages.insert(message_id.clone(), message);
         }
 
-        // Route message to appropriate handler
-        let handler_contract = format!("bridge_{}", message.destination_chain);
         if let Ok(mut routing) = self.message_routing.lock() {
             routing.insert(message_id.clone(), handler_contract);
         }
@@ -250,49 +268,35 @@ impl InteroperabilityLayer {
         Ok(message_id)
     }
 
-    fn encrypt_message_payload(&self, payload: &[u8]) -> Result<Vec<u8>, String> {
+    fn encrypt_message_payload(&self, algorithm: KemAlgorithm, payload: &[u8]) -> Result<Vec<u8>, String> {
         // Generate encryption keys for the message
-        let (public_key, private_key) = self.pqc_manager.generate_keypair(PQCAlgorithm::Kyber)?;
+        let (public_key, _private_key) = algorithm.generate_keypair();
 
         // Encrypt the payload
-        let (ciphertext, shared_secret) = self.pqc_manager.encapsulate_key(&public_key.key_id)?;
+        let (ciphertext, shared_secret) = algorithm.encapsulate(&public_key)?;
 
         // XOR the payload with the shared secret for encryption
         let encrypted_payload: Vec<u8> = payload.iter()
-            .zip(shared_secret.shared_secret.iter().cycle())
+            .zip(shared_secret.iter().cycle())
             .map(|(a, b)| a ^ b)
             .collect();
 
         // Store the ciphertext and key information
         // In a real implementation, this would be more sophisticated
         let mut encrypted_data = Vec::new();
-        encrypted_data.extend_from_slice(&ciphertext.ciphertext);
+        encrypted_data.extend_from_slice(&ciphertext);
         encrypted_data.extend_from_slice(&encrypted_payload);
 
         Ok(encrypted_data)
     }
 
-    fn generate_multi_algorithm_signatures(&self, message: &[u8]) -> Result<Vec<String>, String> {
-        let mut signatures = Vec::new();
-
-        // Generate signatures with multiple PQC algorithms for enhanced security
-        for algorithm in [PQCAlgorithm::Dilithium, PQCAlgorithm::Falcon, PQCAlgorithm::Sphincs] {
-            let (public_key, private_key) = self.pqc_manager.generate_keypair(algorithm.clone())?;
-            let signature = self.pqc_manager.sign_message(&private_key.public_key_id, message)?;
-            signatures.push(signature.public_key_id);
-        }
-
-        Ok(signatures)
-    }
-
-    fn generate_military_grade_signatures(&self, message: &[u8]) -> Result<Vec<String>, String> {
+    fn generate_multi_algorithm_signatures(&self, algorithms: &[SignatureAlgorithm], message: &[u8]) -> Result<Vec<PQCSignature>, String> {
         let mut signatures = Vec::new();
 
-        // Generate signatures with all 5 NIST PQC algorithms for maximum security
-        for algorithm in self.pqc_manager.get_supported_algorithms() {
-            let (public_key, private_key) = self.pqc_manager.generate_keypair(algorithm.clone())?;
-            let signature = self.pqc_manager.sign_message(&private_key.public_key_id, message)?;
-            signatures.push(signature.public_key_id);
+        // Signatures carry their public key, so any node can verify them
+        for &algorithm in algorithms {
+            let (public_key, private_key) = algorithm.generate_keypair();
+            signatures.push(PQCSignature::sign(algorithm, &public_key, &private_key, message)?);
         }
 
         Ok(signatures)
@@ -348,6 +352,7 @@ impl InteroperabilityLayer {
                         sender: tx.sender.clone(),
                         recipient: tx.receiver.clone(),
                         payload: tx.hash().as_bytes().to_vec(),
+                        encrypted_payload: None,
                         message_type: MessageType::TokenTransfer,
                         timestamp: std::time::SystemTime::now()
                             .duration_since(std::time::UNIX_EPOCH)
@@ -358,6 +363,10 @@ impl InteroperabilityLayer {
                         status: MessageStatus::Pending,
                         confirmations: 0,
                         required_confirmations: 12,
+                        pqc_algorithm: self.security_config.default_pqc_algorithm.into(),
+                        security_level: self.security_config.minimum_security_level.clone(),
+                        validator_signatures: Vec::new(),
+                        encryption_key_id: None,
                     };
 
                     return self.send_cross_chain_message(message);
@@ -679,7 +688,7 @@ impl InteroperabilityLayer {
 
     pub fn get_cross_chain_fees(&self, destination_chain: &str) -> Result<u64, String> {
         if let Ok(chains) = self.supported_chains.lock() {
-            if let Some(chain_info) = chains.get(destination_chain) {
+            if chains.contains_key(destination_chain) {
                 // Base fee calculation (would be more sophisticated in production)
                 let base_fee = 1000000000000000000; // 1 ETH equivalent in wei
                 Ok(base_fee)
@@ -706,7 +715,7 @@ impl InteroperabilityLayer {
         let mut verification = SecurityVerification {
             message_id: message_id.to_string(),
             security_level: message.security_level.clone(),
-            pqc_algorithm: message.pqc_algorithm.clone(),
+            pqc_algorithm: message.pqc_algorithm,
             signatures_valid: true,
             encryption_valid: true,
             zk_proofs_valid: true,
@@ -719,18 +728,10 @@ impl InteroperabilityLayer {
         };
 
         // Verify PQC signatures
-        for signature_id in &message.validator_signatures {
-            match self.pqc_manager.verify_signature(signature_id, &message.payload) {
-                Ok(is_valid) => {
-                    if !is_valid {
-                        verification.signatures_valid = false;
-                        verification.errors.push(format!("Invalid signature: {}", signature_id));
-                    }
-                },
-                Err(e) => {
-                    verification.signatures_valid = false;
-                    verification.errors.push(format!("Signature verification failed for {}: {}", signature_id, e));
-                }
+        for signature in &message.validator_signatures {
+            if !signature.verify(&message.payload) {
+                verification.signatures_valid = false;
+                verification.errors.push(format!("Invalid {} signature by key {}", signature.algorithm, signature.public_key));
             }
         }
 
@@ -789,6 +790,7 @@ impl InteroperabilityLayer {
         Ok(!message.is_empty())
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn create_secure_cross_chain_message(
         &self,
         source_chain: String,
@@ -835,7 +837,7 @@ impl InteroperabilityLayer {
             status: MessageStatus::Pending,
             confirmations: 0,
             required_confirmations: 12, // 67% of 18 validators
-            pqc_algorithm: self.security_config.default_pqc_algorithm.clone(),
+            pqc_algorithm: self.security_config.default_pqc_algorithm.into(),
             security_level,
             validator_signatures: Vec::new(),
             encryption_key_id: None,
@@ -18,7 +18,7 @@ pub struct AIModel {
     pub total_ratings: u64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum ModelType {
     Chat,
     CodeGeneration,
@@ -62,6 +62,12 @@ pub struct ModelRegistry {
     model_usage: Arc<Mutex<HashMap<String, u64>>>,
 }
 
+impl Default for ModelRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ModelRegistry {
     pub fn new() -> Self {
         ModelRegistry {
@@ -86,6 +86,12 @@ pub struct ProviderManager {
     provider_metrics: Arc<Mutex<HashMap<String, ProviderMetrics>>>,
 }
 
+impl Default for ProviderManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ProviderManager {
     pub fn new() -> Self {
         ProviderManager {
@@ -102,11 +108,12 @@ impl ProviderManager {
                 return Err(format!("Provider {} already registered", provider.id));
             }
 
-            providers.insert(provider.id.clone(), provider);
+            let provider_id = provider.id.clone();
+            providers.insert(provider_id.clone(), provider);
 
             // Initialize metrics
             let metrics = ProviderMetrics {
-                provider_id: provider.id.clone(),
+                provider_id: provider_id.clone(),
                 uptime_percentage: 100.0,
                 average_response_time_ms: 0.0,
                 tasks_completed: 0,
@@ -116,10 +123,10 @@ impl ProviderManager {
             };
 
             if let Ok(mut provider_metrics) = self.provider_metrics.lock() {
-                provider_metrics.insert(provider.id.clone(), metrics);
+                provider_metrics.insert(provider_id.clone(), metrics);
             }
 
-            Ok(provider.id)
+            Ok(provider_id)
         } else {
             Err("Failed to acquire providers lock".to_string())
         }
@@ -159,7 +166,7 @@ impl ProviderManager {
         }
     }
 
-    pub fn get_best_provider(&self, model_id: &str, priority: TaskPriority) -> Option<ProviderNode> {
+    pub fn get_best_provider(&self, model_id: &str, _priority: TaskPriority) -> Option<ProviderNode> {
         let available_providers = self.get_available_providers(Some(model_id));
 
         if available_providers.is_empty() {
@@ -329,7 +336,8 @@ impl ProviderManager {
             });
 
             // Process up to 10 tasks
-            for task in queue.drain(..10.min(queue.len())) {
+            let count = 10.min(queue.len());
+            for task in queue.drain(..count) {
                 tasks_to_process.push(task);
             }
 
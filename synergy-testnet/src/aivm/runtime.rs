@@ -1,13 +1,16 @@
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use tokio::runtime::Runtime;
 use hex;
 use crate::transaction::Transaction;
-use crate::block::Block;
 use super::distributed_ai::DistributedAIProtocol;
+use super::provider::ProviderManager;
+use super::model_registry::ModelRegistry;
+use super::chat_interface::ChatInterface;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct AIVMExecutionContext {
     pub transaction_hash: String,
     pub block_height: u64,
@@ -19,7 +22,7 @@ pub struct AIVMExecutionContext {
     pub gas_price: u64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct AIVMExecutionResult {
     pub success: bool,
     pub output: Vec<u8>,
@@ -30,7 +33,7 @@ pub struct AIVMExecutionResult {
     pub ai_responses: Vec<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct AIVMContract {
     pub address: String,
     pub bytecode: Vec<u8>,
@@ -40,7 +43,7 @@ pub struct AIVMContract {
     pub contract_type: ContractType,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 pub enum ContractType {
     Standard,
     AIEnhanced,
@@ -52,24 +55,32 @@ pub enum ContractType {
 pub struct AIVMRuntime {
     contracts: Arc<Mutex<HashMap<String, AIVMContract>>>,
     execution_cache: Arc<Mutex<HashMap<String, AIVMExecutionResult>>>,
+    #[allow(dead_code)]
     model_registry: Arc<ModelRegistry>,
+    #[allow(dead_code)]
     chat_interface: Arc<ChatInterface>,
     distributed_ai: Arc<DistributedAIProtocol>,
+    providers: Arc<ProviderManager>,
+    #[allow(dead_code)]
     runtime: Runtime,
 }
 
+impl Default for AIVMRuntime {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl AIVMRuntime {
     pub fn new() -> Self {
         let runtime = tokio::runtime::Runtime::new().expect("Failed to create Tokio runtime");
 
         // Initialize distributed AI protocol
-        let consensus_engine = Arc::new(crate::consensus::consensus_algorithm::ProofOfSynergy::new());
         let validator_manager = crate::validator::VALIDATOR_MANAGER.clone();
         let model_registry = Arc::new(ModelRegistry::new());
         let chat_interface = Arc::new(ChatInterface::new());
 
         let distributed_ai = Arc::new(DistributedAIProtocol::new(
-            consensus_engine,
             validator_manager,
             model_registry.clone(),
             chat_interface.clone(),
@@ -81,10 +92,19 @@ impl AIVMRuntime {
             model_registry,
             chat_interface,
             distributed_ai,
+            providers: Arc::new(ProviderManager::new()),
             runtime,
         }
     }
 
+    pub fn distributed_ai(&self) -> &Arc<DistributedAIProtocol> {
+        &self.distributed_ai
+    }
+
+    pub fn providers(&self) -> &Arc<ProviderManager> {
+        &self.providers
+    }
+
     pub fn deploy_contract(
         &self,
         bytecode: Vec<u8>,
@@ -140,12 +160,14 @@ impl AIVMRuntime {
         };
 
         // Execute based on contract type
-        let result = match contract.contract_type {
-            ContractType::AIEnhanced => self.execute_ai_enhanced_contract(&contract, &context)?,
-            ContractType::CrossChain => self.execute_cross_chain_contract(&contract, &context)?,
-            ContractType::Oracle => self.execute_oracle_contract(&contract, &context)?,
-            ContractType::Standard => self.execute_standard_contract(&contract, &context)?,
+        let outcome = match contract.contract_type {
+            ContractType::AIEnhanced => self.execute_ai_enhanced_contract(&contract, &context),
+            ContractType::CrossChain => self.execute_cross_chain_contract(&contract, &context),
+            ContractType::Oracle => self.execute_oracle_contract(&contract, &context),
+            ContractType::Standard => self.execute_standard_contract(&contract, &context),
         };
+        crate::metrics::record_aivm_execution(&contract.contract_type, &outcome);
+        let result = outcome?;
 
         // Cache the result
         if let Ok(mut cache) = self.execution_cache.lock() {
@@ -157,8 +179,8 @@ impl AIVMRuntime {
 
     fn execute_standard_contract(
         &self,
-        contract: &AIVMContract,
-        context: &AIVMExecutionContext,
+        _contract: &AIVMContract,
+        _context: &AIVMExecutionContext,
     ) -> Result<AIVMExecutionResult, String> {
         // Standard contract execution logic
         // This would typically involve WASM execution or similar
@@ -175,7 +197,7 @@ impl AIVMRuntime {
 
     fn execute_ai_enhanced_contract(
         &self,
-        contract: &AIVMContract,
+        _contract: &AIVMContract,
         context: &AIVMExecutionContext,
     ) -> Result<AIVMExecutionResult, String> {
         // Use distributed AI computation instead of centralized GPT calls
@@ -240,8 +262,8 @@ impl AIVMRuntime {
 
     fn execute_cross_chain_contract(
         &self,
-        contract: &AIVMContract,
-        context: &AIVMExecutionContext,
+        _contract: &AIVMContract,
+        _context: &AIVMExecutionContext,
     ) -> Result<AIVMExecutionResult, String> {
         // Cross-chain contract execution logic
         Ok(AIVMExecutionResult {
@@ -257,8 +279,8 @@ impl AIVMRuntime {
 
     fn execute_oracle_contract(
         &self,
-        contract: &AIVMContract,
-        context: &AIVMExecutionContext,
+        _contract: &AIVMContract,
+        _context: &AIVMExecutionContext,
     ) -> Result<AIVMExecutionResult, String> {
         // Oracle contract execution with external data
         Ok(AIVMExecutionResult {
@@ -293,12 +315,12 @@ impl AIVMRuntime {
         let mut hasher = Sha3_256::new();
         hasher.update(creator.as_bytes());
         hasher.update(bytecode);
-        hasher.update(&std::time::SystemTime::now()
+        hasher.update(std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap()
             .as_secs()
             .to_le_bytes());
-        format!("aivm_{}", hex::encode(hasher.finalize())[..40].to_string())
+        format!("aivm_{}", &hex::encode(hasher.finalize())[..40])
     }
 
     pub fn process_transaction(&self, tx: &Transaction) -> Result<AIVMExecutionResult, String> {
@@ -75,6 +75,12 @@ pub struct AIVMVerifier {
     verification_cache: Arc<Mutex<HashMap<String, VerificationResult>>>,
 }
 
+impl Default for AIVMVerifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl AIVMVerifier {
     pub fn new() -> Self {
         AIVMVerifier {
@@ -200,7 +206,7 @@ impl AIVMVerifier {
 
         // Determine if provider is valid
         result.is_valid = result.errors.is_empty() && result.trust_score > 50.0;
-        result.trust_score = result.trust_score.max(0.0).min(100.0);
+        result.trust_score = result.trust_score.clamp(0.0, 100.0);
 
         // Cache the result
         if let Ok(mut cache) = self.verification_cache.lock() {
@@ -305,7 +311,7 @@ impl AIVMVerifier {
         use sha3::{Sha3_256, Digest};
         let mut hasher = Sha3_256::new();
         hasher.update(&report.provider_id);
-        hasher.update(&report.timestamp.to_le_bytes());
+        hasher.update(report.timestamp.to_le_bytes());
         hasher.update(&report.hardware_attestation.measurement);
         hasher.update(&report.software_attestation.software_version);
         hex::encode(hasher.finalize())
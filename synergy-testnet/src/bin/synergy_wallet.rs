@@ -0,0 +1,364 @@
+//! Offline wallet: builds, signs, inspects and broadcasts transaction
+//! files without a node holding the key, and sets up multisig accounts
+//! whose signers pass partially signed files between them. A `.syn` name
+//! is accepted wherever an address is and resolved with the node at
+//! `--rpc`.
+
+use std::collections::HashMap;
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::process;
+
+use synergy_testnet::crypto::multisig::MultisigPolicy;
+use synergy_testnet::crypto::SignatureAlgorithm;
+use synergy_testnet::key_rotation::{KeyScope, RotateKey};
+use synergy_testnet::keystore::{Keystore, DEFAULT_KEYSTORE_DIR};
+use synergy_testnet::name_service::{self, NameOp};
+use synergy_testnet::tx_file::{self, TxFormat, TxOp, DEFAULT_GAS_LIMIT, DEFAULT_GAS_PRICE};
+use zeroize::Zeroizing;
+
+const USAGE: &str = "Usage: synergy-wallet <tx | multisig | name | pubkey> ...
+  tx build <transfer | token-transfer | stake> (--from <address> | --multisig <policy file>)
+           --amount <n> --nonce <n> [--to <address>] [--token <symbol>] [--validator <address>]
+           [--gas-price <n>] [--gas-limit <n>] [--format <json|bincode>] [--output <file>]
+  tx build rotate-key --from <address> --new-key <address | hex public key> --nonce <n>
+           [--algorithm <name>] [--scope <account|consensus>] [--keystore <dir>] ...
+  tx build name <register | renew | transfer | set-address | set-text | set-primary> --from <address>
+           --nonce <n> [--name <name.syn>] [--years <n>] [--owner <address>] [--to <address>]
+           [--address <address>] [--key <key>] [--value <text>] ...
+  tx sign <file> [--keystore <dir>] [--signer <address>] [--password-file <file>] [--output <file>]
+  tx combine <file>... --output <file>
+  tx inspect <file>
+  tx broadcast <file> [--rpc <url>]
+  multisig create --threshold <m> [--keystore <dir>] [--output <file>] <signer>...
+  name resolve <name.syn> [--rpc <url>]
+  name lookup <address> [--rpc <url>]
+  pubkey <address> [--keystore <dir>]
+
+Addresses may be given as .syn names, which are resolved with the node at --rpc.";
+
+const DEFAULT_RPC_URL: &str = "http://127.0.0.1:8545";
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let command = args.get(1).map(String::as_str).unwrap_or("");
+    // `pubkey` takes no action word
+    let (action, rest) = match command {
+        "pubkey" => ("", args.get(2..).unwrap_or(&[])),
+        _ => (args.get(2).map(String::as_str).unwrap_or(""), args.get(3..).unwrap_or(&[])),
+    };
+    let (positional, options) = match parse_args(rest) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+    };
+
+    let result = match (command, action) {
+        ("tx", "build") => tx_build(&positional, &options),
+        ("tx", "sign") => tx_sign(&positional, &options),
+        ("tx", "combine") => tx_combine(&positional, &options),
+        ("tx", "inspect") => tx_inspect(&positional),
+        ("tx", "broadcast") => tx_broadcast(&positional, &options),
+        ("multisig", "create") => multisig_create(&positional, &options),
+        ("name", "resolve") => name_resolve(&positional, &options),
+        ("name", "lookup") => name_lookup(&positional, &options),
+        ("pubkey", _) => pubkey(&positional, &options),
+        _ => {
+            eprintln!("{}", USAGE);
+            process::exit(1);
+        }
+    };
+    if let Err(e) = result {
+        eprintln!("❌ {}", e);
+        process::exit(1);
+    }
+}
+
+/// Splits `--name value` options from positional arguments.
+fn parse_args(args: &[String]) -> Result<(Vec<String>, HashMap<String, String>), String> {
+    let mut positional = Vec::new();
+    let mut options = HashMap::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.strip_prefix("--") {
+            Some(name) => {
+                let value = args.next().ok_or_else(|| format!("{} needs a value", arg))?;
+                options.insert(name.to_string(), value.clone());
+            }
+            None => positional.push(arg.clone()),
+        }
+    }
+    Ok((positional, options))
+}
+
+fn required<'a>(options: &'a HashMap<String, String>, name: &str) -> Result<&'a str, String> {
+    options.get(name).map(String::as_str).ok_or_else(|| format!("--{} is required", name))
+}
+
+fn number(options: &HashMap<String, String>, name: &str, default: Option<u64>) -> Result<u64, String> {
+    match options.get(name) {
+        Some(value) => value.parse().map_err(|_| format!("--{} must be a number, not {}", name, value)),
+        None => default.ok_or_else(|| format!("--{} is required", name)),
+    }
+}
+
+fn rpc_url(options: &HashMap<String, String>) -> &str {
+    options.get("rpc").map_or(DEFAULT_RPC_URL, String::as_str)
+}
+
+/// The address `value` stands for, resolving a `.syn` name with the node.
+fn address(options: &HashMap<String, String>, value: &str) -> Result<String, Box<dyn Error>> {
+    tx_file::resolve(rpc_url(options), value)
+}
+
+/// The address of the option `name`, which is required.
+fn required_address(options: &HashMap<String, String>, name: &str) -> Result<String, Box<dyn Error>> {
+    address(options, required(options, name)?)
+}
+
+fn keystore(options: &HashMap<String, String>) -> Keystore {
+    Keystore::new(options.get("keystore").map_or(DEFAULT_KEYSTORE_DIR, String::as_str))
+}
+
+fn file_arg<'a>(positional: &'a [String], action: &str) -> Result<&'a str, String> {
+    match positional {
+        [file] => Ok(file.as_str()),
+        _ => Err(format!("tx {} needs exactly one transaction file", action)),
+    }
+}
+
+fn tx_build(positional: &[String], options: &HashMap<String, String>) -> Result<(), Box<dyn Error>> {
+    let op = match positional {
+        [kind] if kind == "transfer" => TxOp::Transfer { to: required_address(options, "to")? },
+        [kind] if kind == "token-transfer" => TxOp::TokenTransfer {
+            to: required_address(options, "to")?,
+            token: required(options, "token")?.to_string(),
+        },
+        [kind] if kind == "stake" => TxOp::Stake {
+            validator: required_address(options, "validator")?,
+            token: required(options, "token")?.to_string(),
+        },
+        [kind] if kind == "rotate-key" => TxOp::RotateKey(rotate_key(options)?),
+        [kind, action] if kind == "name" => TxOp::Name(name_op(action, options)?),
+        _ => return Err("tx build needs one of transfer, token-transfer, stake, rotate-key or name".into()),
+    };
+    // Key rotations and name operations move no funds
+    let default_amount = matches!(op, TxOp::RotateKey(_) | TxOp::Name(_)).then_some(0);
+    let format: TxFormat = options.get("format").map_or(Ok(TxFormat::Json), |format| format.parse())?;
+    let policy = options.get("multisig").map(tx_file::read_policy).transpose()?;
+    let from = options.get("from").map(|from| address(options, from)).transpose()?;
+    let from = match (&policy, from) {
+        (Some(policy), Some(from)) if from != policy.address() => {
+            return Err(format!("--from {} is not the address of the multisig policy, {}", from, policy.address()).into())
+        }
+        (Some(policy), _) => policy.address(),
+        (None, from) => from.ok_or("--from is required")?,
+    };
+
+    let mut tx = tx_file::build(
+        &from,
+        &op,
+        number(options, "amount", default_amount)?,
+        number(options, "nonce", None)?,
+        number(options, "gas-price", Some(DEFAULT_GAS_PRICE))?,
+        number(options, "gas-limit", Some(DEFAULT_GAS_LIMIT))?,
+    );
+    if let Some(policy) = policy {
+        tx.set_multisig(policy);
+    }
+    let validation = tx.validate_fields();
+    if let Some(error) = validation.error_message {
+        return Err(error.into());
+    }
+
+    match options.get("output") {
+        Some(output) => {
+            tx_file::write(output, &tx, format)?;
+            eprintln!("📝 Wrote unsigned transaction {} to {}", tx.hash(), output);
+        }
+        None if format == TxFormat::Json => print!("{}", String::from_utf8_lossy(&tx_file::encode(&tx, format))),
+        None => return Err("--output is required for bincode".into()),
+    }
+    Ok(())
+}
+
+/// The rotation `tx build rotate-key` binds: `--new-key` is an address in
+/// the keystore, whose ML-DSA-87 key is used, or a hex public key of
+/// `--algorithm`.
+fn rotate_key(options: &HashMap<String, String>) -> Result<RotateKey, Box<dyn Error>> {
+    let scope = match options.get("scope").map_or("account", String::as_str) {
+        "account" => KeyScope::Account,
+        "consensus" => KeyScope::Consensus,
+        scope => return Err(format!("--scope must be account or consensus, not {}", scope).into()),
+    };
+    let new_key = &required_address(options, "new-key")?;
+    let rotation = match keystore(options).find(new_key) {
+        Ok((_, file)) => {
+            if options.get("algorithm").is_some_and(|algorithm| algorithm.parse() != Ok(SignatureAlgorithm::MlDsa87)) {
+                return Err(format!("{} is an ML-DSA-87 key", new_key).into());
+            }
+            RotateKey { scope, algorithm: SignatureAlgorithm::MlDsa87, public_key: file.public_key }
+        }
+        Err(_) => RotateKey {
+            scope,
+            algorithm: options.get("algorithm").map_or(Ok(SignatureAlgorithm::MlDsa87), |algorithm| algorithm.parse())?,
+            public_key: new_key.to_lowercase(),
+        },
+    };
+    rotation.check()?;
+    Ok(rotation)
+}
+
+/// The name service operation `tx build name <action>` carries.
+fn name_op(action: &str, options: &HashMap<String, String>) -> Result<NameOp, Box<dyn Error>> {
+    let name = || -> Result<String, Box<dyn Error>> { Ok(name_service::normalize(required(options, "name")?)?) };
+    let optional_address = |option: &str| options.get(option).map(|value| address(options, value)).transpose();
+    Ok(match action {
+        "register" => NameOp::Register { name: name()?, years: number(options, "years", Some(1))?, owner: optional_address("owner")? },
+        "renew" => NameOp::Renew { name: name()?, years: number(options, "years", Some(1))? },
+        "transfer" => NameOp::Transfer { name: name()?, to: required_address(options, "to")? },
+        "set-address" => NameOp::SetAddress { name: name()?, address: optional_address("address")? },
+        "set-text" => NameOp::SetText {
+            name: name()?,
+            key: required(options, "key")?.to_string(),
+            value: options.get("value").cloned(),
+        },
+        "set-primary" => NameOp::SetPrimary { name: options.get("name").map(|_| name()).transpose()? },
+        _ => return Err("tx build name needs one of register, renew, transfer, set-address, set-text or set-primary".into()),
+    })
+}
+
+fn tx_sign(positional: &[String], options: &HashMap<String, String>) -> Result<(), Box<dyn Error>> {
+    let file = file_arg(positional, "sign")?;
+    let (mut tx, format) = tx_file::read(file)?;
+    let keystore = keystore(options);
+    let requested = options.get("signer").map(|signer| address(options, signer)).transpose()?;
+    let signer = tx_file::signer(&tx, &keystore, requested.as_deref())?;
+    let password = read_password(options.get("password-file").map(String::as_str), &signer)?;
+
+    tx_file::sign_as(&mut tx, &keystore, &signer, &password)?;
+    let output = options.get("output").map_or(file, String::as_str);
+    tx_file::write(output, &tx, format)?;
+    eprintln!("✍️ Signed {} as {}; wrote {}", tx.hash(), signer, output);
+    if let Some(auth) = &tx.multisig {
+        eprintln!("🔏 {} signatures, {} required", auth.signed_count(), auth.policy.threshold);
+    }
+    Ok(())
+}
+
+fn tx_combine(positional: &[String], options: &HashMap<String, String>) -> Result<(), Box<dyn Error>> {
+    if positional.len() < 2 {
+        return Err("tx combine needs at least two transaction files".into());
+    }
+    let output = required(options, "output")?;
+    let mut format = TxFormat::Json;
+    let mut txs = Vec::new();
+    for file in positional {
+        let (tx, file_format) = tx_file::read(file)?;
+        format = file_format;
+        txs.push(tx);
+    }
+
+    let tx = tx_file::combine(&txs)?;
+    tx_file::write(output, &tx, format)?;
+    if let Some(auth) = &tx.multisig {
+        eprintln!("🔏 Wrote {} to {} with {} signatures, {} required", tx.hash(), output, auth.signed_count(), auth.policy.threshold);
+    }
+    Ok(())
+}
+
+fn tx_inspect(positional: &[String]) -> Result<(), Box<dyn Error>> {
+    let (tx, format) = tx_file::read(file_arg(positional, "inspect")?)?;
+    println!("{:<12} {}", "Format", format);
+    for (field, value) in tx_file::describe(&tx) {
+        println!("{:<12} {}", field, value);
+    }
+    Ok(())
+}
+
+fn tx_broadcast(positional: &[String], options: &HashMap<String, String>) -> Result<(), Box<dyn Error>> {
+    let (tx, _) = tx_file::read(file_arg(positional, "broadcast")?)?;
+    let hash = tx_file::broadcast(rpc_url(options), &tx)?;
+    println!("📤 Submitted {}", hash);
+    Ok(())
+}
+
+/// Writes the policy of a multisig account. Each signer is an address in
+/// the keystore, a file holding a hex public key, or the key itself.
+fn multisig_create(positional: &[String], options: &HashMap<String, String>) -> Result<(), Box<dyn Error>> {
+    let threshold = u32::try_from(number(options, "threshold", None)?).map_err(|_| "--threshold is too large")?;
+    let keystore = keystore(options);
+    let mut public_keys = Vec::new();
+    for signer in positional {
+        let signer = &address(options, signer)?;
+        let public_key = if let Ok((_, file)) = keystore.find(signer) {
+            if file.secp256k1_public_key.is_some() {
+                return Err(format!("{} is a hybrid account; multisig signers are ML-DSA keys", signer).into());
+            }
+            file.public_key
+        } else if let Ok(content) = fs::read_to_string(signer) {
+            content.trim().to_string()
+        } else if signer.chars().all(|c| c.is_ascii_hexdigit()) {
+            signer.to_string()
+        } else {
+            return Err(format!("{} is neither a key in {}, a public key file nor a hex public key", signer, keystore.dir().display()).into());
+        };
+        public_keys.push(public_key);
+    }
+
+    let policy = MultisigPolicy::new(threshold, public_keys)?;
+    match options.get("output") {
+        Some(output) => {
+            tx_file::write_policy(output, &policy)?;
+            eprintln!("📝 Wrote the policy to {}", output);
+        }
+        None => println!("{}", serde_json::to_string_pretty(&policy)?),
+    }
+    eprintln!("🔐 {}-of-{} multisig account {}", policy.threshold, policy.public_keys.len(), policy.address());
+    Ok(())
+}
+
+/// Prints the address a `.syn` name resolves to.
+fn name_resolve(positional: &[String], options: &HashMap<String, String>) -> Result<(), Box<dyn Error>> {
+    let [name] = positional else {
+        return Err("name resolve needs exactly one name".into());
+    };
+    println!("{}", address(options, name)?);
+    Ok(())
+}
+
+/// Prints the primary name of an address.
+fn name_lookup(positional: &[String], options: &HashMap<String, String>) -> Result<(), Box<dyn Error>> {
+    let [address] = positional else {
+        return Err("name lookup needs exactly one address".into());
+    };
+    match tx_file::lookup(rpc_url(options), address)? {
+        Some(name) => println!("{}", name),
+        None => return Err(format!("{} has no primary name", address).into()),
+    }
+    Ok(())
+}
+
+/// Prints the hex public key of a keystore key, for co-signers to build a
+/// multisig policy from.
+fn pubkey(positional: &[String], options: &HashMap<String, String>) -> Result<(), Box<dyn Error>> {
+    let [value] = positional else {
+        return Err("pubkey needs exactly one address".into());
+    };
+    let (_, file) = keystore(options).find(&address(options, value)?)?;
+    println!("{}", file.public_key);
+    Ok(())
+}
+
+/// Reads the keystore password from the first line of `file`, or prompts
+/// for it on the terminal.
+fn read_password(file: Option<&str>, address: &str) -> Result<Zeroizing<String>, Box<dyn Error>> {
+    if let Some(file) = file {
+        let content = Zeroizing::new(fs::read_to_string(file)?);
+        return Ok(Zeroizing::new(content.lines().next().unwrap_or("").to_string()));
+    }
+    Ok(Zeroizing::new(rpassword::prompt_password(format!("Password for {}: ", address))?))
+}
@@ -1,10 +1,15 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use crate::transaction::Transaction;
+use crate::crypto::keys::{self, KeyPair};
+use crate::key_rotation::KeyRegistry;
+use crate::storage;
+use crate::transaction::{self, Transaction};
 use std::fs::{File};
-use std::io::{Write, Read};
+use std::io::Read;
 use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Block {
     pub block_index: u64,
     pub transactions: Vec<Transaction>,
@@ -12,6 +17,15 @@ pub struct Block {
     pub validator_id: String,
     pub nonce: u64,
     pub hash: String,
+    /// Unix time the block was produced; 0 for block 0, so every node
+    /// starts from the same one.
+    #[serde(default)]
+    pub timestamp: u64,
+    /// Hex ML-DSA-87 signature over `hash` by the proposer's consensus key.
+    /// Empty for block 0 and for blocks a standalone node produced without
+    /// the proposer's key.
+    #[serde(default)]
+    pub signature: String,
 }
 
 impl Block {
@@ -22,21 +36,59 @@ impl Block {
         validator_id: String,
         nonce: u64,
     ) -> Self {
-        let data = format!("{:?}{:?}{}{}{}", block_index, transactions, previous_hash, validator_id, nonce);
-        let hash = blake3::hash(data.as_bytes()).to_hex().to_string();
-        Block {
+        let mut block = Block {
             block_index,
             transactions,
             previous_hash,
             validator_id,
             nonce,
-            hash,
+            hash: String::new(),
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            signature: String::new(),
+        };
+        block.hash = block.calculate_hash();
+        block
+    }
+
+    /// Hashes the block's contents, excluding `hash` and `signature`.
+    pub fn calculate_hash(&self) -> String {
+        let data = format!(
+            "{:?}{:?}{}{}{}{}",
+            self.block_index, self.transactions, self.previous_hash, self.validator_id, self.nonce, self.timestamp
+        );
+        blake3::hash(data.as_bytes()).to_hex().to_string()
+    }
+
+    /// Signs the block's hash as its proposer.
+    pub fn sign(&mut self, keypair: &KeyPair) {
+        self.signature = hex::encode(keypair.sign(self.hash.as_bytes()));
+    }
+
+    /// Whether the block carries a signature over its hash by `public_key`,
+    /// given in hex.
+    pub fn verify_signature(&self, public_key: &str) -> bool {
+        match (hex::decode(public_key), hex::decode(&self.signature)) {
+            (Ok(public_key), Ok(signature)) => keys::verify(&public_key, self.hash.as_bytes(), &signature),
+            _ => false,
         }
     }
 
     pub fn validate(&self) -> bool {
         true
     }
+
+    /// Checks the signatures of all transactions in one batch, against the
+    /// keys in force at this block's height.
+    pub fn check_signatures(&self, keys: &KeyRegistry) -> Result<(), String> {
+        let results = transaction::check_signatures(&self.transactions, keys, self.block_index);
+        for (tx, result) in self.transactions.iter().zip(results) {
+            result.map_err(|e| format!("transaction {}: {}", tx.hash(), e))?;
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -44,6 +96,12 @@ pub struct BlockChain {
     pub chain: Vec<Block>,
 }
 
+impl Default for BlockChain {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl BlockChain {
     pub fn new() -> Self {
         BlockChain { chain: vec![] }
@@ -58,22 +116,75 @@ impl BlockChain {
     }
 
     pub fn genesis(&mut self) {
-        let genesis_block = Block::new(
+        self.genesis_for("0");
+    }
+
+    /// Starts the chain with a block 0 that links to `genesis_hash`, pinning
+    /// the chain to that genesis.
+    pub fn genesis_for(&mut self, genesis_hash: &str) {
+        let mut genesis_block = Block::new(
             0,
             vec![],
-            "0".to_string(),
+            genesis_hash.to_string(),
             "genesis".to_string(),
             0,
         );
+        genesis_block.timestamp = 0;
+        genesis_block.hash = genesis_block.calculate_hash();
         self.chain.push(genesis_block);
     }
 
+    /// Hash of the genesis the chain was started from, as linked by block 0.
+    pub fn genesis_hash(&self) -> Option<&str> {
+        self.chain.first().map(|block| block.previous_hash.as_str())
+    }
+
+    /// Writes the chain atomically, so a crash mid-write never leaves a
+    /// truncated file behind.
     pub fn save_to_file(&self, path: &str) {
-        if let Ok(json) = serde_json::to_string_pretty(&self.chain) {
-            if let Ok(mut file) = File::create(path) {
-                let _ = file.write_all(json.as_bytes());
+        if let Err(e) = storage::write_json_atomic(path, &self.chain) {
+            crate::error!("chain", "Failed to save chain", "path" => path, "error" => e.to_string());
+        }
+    }
+
+    /// Number of leading blocks that form a valid chain: indices count up
+    /// from 0, every block links to its predecessor, every stored hash
+    /// matches the block's contents and every transaction is signed by the
+    /// key its sender had at that height.
+    pub fn valid_prefix_len(&self) -> usize {
+        // Only account keys matter here, and they do not depend on the epoch length
+        let mut keys = KeyRegistry::default();
+        for (position, block) in self.chain.iter().enumerate() {
+            if block.block_index != position as u64 || block.hash != block.calculate_hash() {
+                return position;
             }
+            if block.check_signatures(&keys).is_err() {
+                return position;
+            }
+            if position > 0 && block.previous_hash != self.chain[position - 1].hash {
+                return position;
+            }
+            keys.apply_block(block);
         }
+        self.chain.len()
+    }
+
+    pub fn verify(&self) -> Result<(), String> {
+        let valid = self.valid_prefix_len();
+        if valid == self.chain.len() {
+            Ok(())
+        } else {
+            Err(format!("block {} of {} is invalid", valid, self.chain.len()))
+        }
+    }
+
+    /// Drops every block from the first invalid one onwards. Returns how
+    /// many blocks were removed.
+    pub fn truncate_to_valid(&mut self) -> usize {
+        let valid = self.valid_prefix_len();
+        let removed = self.chain.len() - valid;
+        self.chain.truncate(valid);
+        removed
     }
 
     pub fn load_from_file(path: &str) -> Option<Self> {
@@ -4,8 +4,8 @@ use crate::transaction::Transaction;
 
 pub fn broadcast_transaction() {
     let tx = Transaction::new(
-        "sYnQ1zxy8qhj4j59xp5lwkwpd5qws9aygz8pl9m3kmjx3".to_string(),
-        "sYnQ1wlt52dlk9scmzphw7uc8p72v28j47yd8g0drmtc".to_string(),
+        "sYn4b45e8d59f59f51377d2a6847b92f242e73527".to_string(),
+        "sYn0f43dd43e8a3ffa378899067449320cb15b085".to_string(),
         1000,
         1, // nonce
         "demo-signature-placeholder".to_string(), // dummy signature for now
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::env;
 use std::fs;
 use std::path::Path;
@@ -5,6 +6,17 @@ use std::error::Error;
 use serde::{Deserialize, Serialize};
 use serde_json;
 use toml;
+use crate::p2p::peer_scoring::PeerScoringConfig;
+use crate::rate_limit::RateLimitConfig;
+use crate::storage;
+
+mod sources;
+mod validation;
+
+pub use self::sources::{load_layered, load_validated, ConfigSources, LoadedConfig, DEFAULT_NETWORK};
+
+/// Network presets `config init` can write.
+pub const NETWORK_PRESETS: &[&str] = &["testnet", "devnet"];
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct NodeConfig {
@@ -15,6 +27,8 @@ pub struct NodeConfig {
     pub rpc: RPCConfig,
     pub p2p: P2PConfig,
     pub storage: StorageConfig,
+    #[serde(default)]
+    pub wallet: WalletConfig,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -33,6 +47,13 @@ pub struct BlockchainConfig {
     pub block_time: u64,
     pub max_gas_limit: String,
     pub chain_id: u64,
+    /// Genesis file; its consensus parameters override `[consensus]`.
+    #[serde(default = "default_genesis_file")]
+    pub genesis_file: String,
+}
+
+fn default_genesis_file() -> String {
+    crate::genesis::DEFAULT_GENESIS_PATH.to_string()
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -64,6 +85,23 @@ pub struct LoggingConfig {
     pub enable_console: bool,
     pub max_file_size: u64,
     pub max_files: u32,
+    /// `text` or `json` (one object per line).
+    #[serde(default = "default_log_format")]
+    pub log_format: String,
+    /// Per-module levels overriding `log_level`, e.g. `consensus=debug,p2p=info`.
+    #[serde(default)]
+    pub module_levels: String,
+    /// Most recent entries kept in memory for `synergy_getLogs`.
+    #[serde(default = "default_log_buffer_size")]
+    pub buffer_size: usize,
+}
+
+fn default_log_format() -> String {
+    "text".to_string()
+}
+
+fn default_log_buffer_size() -> usize {
+    crate::logging::DEFAULT_BUFFER_SIZE
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -76,6 +114,163 @@ pub struct RPCConfig {
     pub grpc_port: u16,
     pub cors_enabled: bool,
     pub cors_origins: Vec<String>,
+    /// Serve Prometheus metrics at `/metrics` on the HTTP port.
+    #[serde(default = "default_enable_metrics")]
+    pub enable_metrics: bool,
+    #[serde(default)]
+    pub ws_limits: WsLimitsConfig,
+    #[serde(default)]
+    pub auth: RpcAuthConfig,
+    #[serde(default)]
+    pub limits: RpcLimitsConfig,
+    #[serde(default)]
+    pub health: HealthConfig,
+}
+
+/// Thresholds for the `/health`, `/ready` and `/live` endpoints.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct HealthConfig {
+    /// Directory that must stay writable.
+    pub data_dir: String,
+    /// Seconds without a consensus loop iteration before the node is not live.
+    pub heartbeat_timeout_secs: u64,
+    /// Oldest acceptable latest block, in seconds.
+    pub max_block_age_secs: u64,
+    /// Blocks the node may trail the best peer by.
+    pub max_sync_lag: u64,
+    /// Connected peers required to be ready.
+    pub min_peers: usize,
+    /// Largest acceptable offset from the peers' median clock, in seconds.
+    pub max_clock_drift_secs: u64,
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        HealthConfig {
+            data_dir: "data".to_string(),
+            heartbeat_timeout_secs: 30,
+            max_block_age_secs: 60,
+            max_sync_lag: 10,
+            min_peers: 0,
+            max_clock_drift_secs: 10,
+        }
+    }
+}
+
+fn default_enable_metrics() -> bool {
+    true
+}
+
+/// Which RPC namespaces are served and how the privileged ones are protected.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct RpcAuthConfig {
+    /// Serve `wallet` methods to authenticated callers.
+    pub enable_wallet: bool,
+    /// Let loopback callers use `wallet` methods without a token. Any
+    /// local process, or anything proxied through one, can then use the
+    /// node's unlocked keys.
+    pub allow_loopback_wallet: bool,
+    /// Serve `admin` methods to authenticated callers.
+    pub enable_admin: bool,
+    /// Serve `debug` methods to authenticated callers.
+    pub enable_debug: bool,
+    /// File holding the shared secret, used as a bearer token or as the
+    /// HS256 key for JWTs. Generated on first start if missing.
+    pub token_file: String,
+    /// Addresses or CIDR ranges allowed to call admin and debug methods.
+    /// Empty allows any address.
+    pub ip_allowlist: Vec<String>,
+}
+
+impl Default for RpcAuthConfig {
+    fn default() -> Self {
+        RpcAuthConfig {
+            enable_wallet: true,
+            allow_loopback_wallet: false,
+            enable_admin: false,
+            enable_debug: false,
+            token_file: "data/rpc_auth.token".to_string(),
+            ip_allowlist: Vec::new(),
+        }
+    }
+}
+
+/// Request limits for the HTTP, WebSocket and gRPC endpoints.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct RpcLimitsConfig {
+    /// Blocking threads that execute RPC calls; requests beyond this are
+    /// rejected until one finishes.
+    pub worker_threads: usize,
+    /// Largest accepted HTTP request body, in bytes.
+    pub max_body_size: usize,
+    /// Largest accepted JSON-RPC batch.
+    pub max_batch_size: usize,
+    /// Time allowed to receive a request's headers, and then its body, in seconds.
+    pub read_timeout_secs: u64,
+    /// Time allowed to execute a request and start the response, in seconds.
+    pub request_timeout_secs: u64,
+    /// Time a client may take to accept each write of a response, in seconds.
+    pub write_timeout_secs: u64,
+    /// Calls allowed per client IP.
+    pub per_ip: RateLimitConfig,
+    /// Additional per-IP limits for individual methods.
+    pub per_method: HashMap<String, RateLimitConfig>,
+    /// Methods that share the `max_concurrent_expensive` cap.
+    pub expensive_methods: Vec<String>,
+    /// Expensive calls allowed to run at once across all clients.
+    pub max_concurrent_expensive: usize,
+}
+
+impl Default for RpcLimitsConfig {
+    fn default() -> Self {
+        let mut per_method = HashMap::new();
+        per_method.insert("synergy_sendTransaction".to_string(), RateLimitConfig { capacity: 50, refill_per_sec: 10.0 });
+        per_method.insert("synergy_getBlockRange".to_string(), RateLimitConfig { capacity: 10, refill_per_sec: 2.0 });
+        per_method.insert("synergy_executeAIVMContract".to_string(), RateLimitConfig { capacity: 5, refill_per_sec: 0.5 });
+        per_method.insert("synergy_chatWithAIVM".to_string(), RateLimitConfig { capacity: 5, refill_per_sec: 0.5 });
+
+        RpcLimitsConfig {
+            worker_threads: 64,
+            max_body_size: 1024 * 1024, // 1MB
+            max_batch_size: 100,
+            read_timeout_secs: 10,
+            request_timeout_secs: 30,
+            write_timeout_secs: 10,
+            per_ip: RateLimitConfig { capacity: 200, refill_per_sec: 50.0 },
+            per_method,
+            expensive_methods: vec![
+                "synergy_getBlockRange".to_string(),
+                "synergy_executeAIVMContract".to_string(),
+                "synergy_chatWithAIVM".to_string(),
+                "synergy_initiateDistributedAI".to_string(),
+            ],
+            max_concurrent_expensive: 4,
+        }
+    }
+}
+
+/// Limits applied to WebSocket subscription clients.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct WsLimitsConfig {
+    /// Maximum number of concurrent WebSocket connections.
+    pub max_connections: usize,
+    /// Maximum number of active subscriptions on a single connection.
+    pub max_subscriptions_per_connection: usize,
+    /// Notifications queued per connection before it is dropped as a slow consumer.
+    pub send_queue_size: usize,
+}
+
+impl Default for WsLimitsConfig {
+    fn default() -> Self {
+        WsLimitsConfig {
+            max_connections: 100,
+            max_subscriptions_per_connection: 32,
+            send_queue_size: 256,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -86,6 +281,8 @@ pub struct P2PConfig {
     pub enable_discovery: bool,
     pub discovery_port: u16,
     pub heartbeat_interval: u64,
+    #[serde(default)]
+    pub peer_scoring: PeerScoringConfig,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -96,6 +293,27 @@ pub struct StorageConfig {
     pub pruning_interval: u64,
 }
 
+/// Node-held wallets.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct WalletConfig {
+    /// Directory of encrypted key files, one per wallet.
+    pub keystore_dir: String,
+    /// File whose first line is the password of the node's validator keys
+    /// in the keystore. The node decrypts them at startup to sign its
+    /// blocks and head votes; empty means it holds no validator keys.
+    pub validator_password_file: String,
+}
+
+impl Default for WalletConfig {
+    fn default() -> Self {
+        WalletConfig {
+            keystore_dir: crate::keystore::DEFAULT_KEYSTORE_DIR.to_string(),
+            validator_password_file: String::new(),
+        }
+    }
+}
+
 impl Default for NodeConfig {
     fn default() -> Self {
         NodeConfig {
@@ -114,6 +332,7 @@ impl Default for NodeConfig {
                 block_time: 5,
                 max_gas_limit: "0x2fefd8".to_string(),
                 chain_id: 7963749,
+                genesis_file: default_genesis_file(),
             },
             consensus: ConsensusConfig {
                 algorithm: "Proof of Synergy".to_string(),
@@ -138,6 +357,9 @@ impl Default for NodeConfig {
                 enable_console: true,
                 max_file_size: 10485760, // 10MB
                 max_files: 5,
+                log_format: default_log_format(),
+                module_levels: String::new(),
+                buffer_size: default_log_buffer_size(),
             },
             rpc: RPCConfig {
                 enable_http: true,
@@ -148,6 +370,11 @@ impl Default for NodeConfig {
                 grpc_port: 50051,
                 cors_enabled: true,
                 cors_origins: vec!["*".to_string()],
+                enable_metrics: true,
+                ws_limits: WsLimitsConfig::default(),
+                auth: RpcAuthConfig::default(),
+                limits: RpcLimitsConfig::default(),
+                health: HealthConfig::default(),
             },
             p2p: P2PConfig {
                 listen_address: "0.0.0.0:30303".to_string(),
@@ -156,6 +383,7 @@ impl Default for NodeConfig {
                 enable_discovery: true,
                 discovery_port: 30301,
                 heartbeat_interval: 30,
+                peer_scoring: PeerScoringConfig::default(),
             },
             storage: StorageConfig {
                 database: "rocksdb".to_string(),
@@ -163,48 +391,48 @@ impl Default for NodeConfig {
                 enable_pruning: true,
                 pruning_interval: 86400, // 24 hours
             },
+            wallet: WalletConfig::default(),
         }
     }
 }
 
-/// Loads the configuration from multiple sources with priority:
-/// 1. Environment variables
-/// 2. TOML config file
-/// 3. Default values
-pub fn load_node_config(path: Option<&str>) -> Result<NodeConfig, Box<dyn Error>> {
-    let mut config = NodeConfig::default();
-
-    // Load from TOML file if provided
-    if let Some(config_path) = path {
-        if Path::new(config_path).exists() {
-            let content = fs::read_to_string(config_path)?;
-            let file_config: NodeConfig = toml::from_str(&content)?;
-            config = merge_configs(config, file_config);
-        }
-    } else if let Ok(config_path) = env::var("SYNERGY_CONFIG_PATH") {
-        if Path::new(&config_path).exists() {
-            let content = fs::read_to_string(&config_path)?;
-            let file_config: NodeConfig = toml::from_str(&content)?;
-            config = merge_configs(config, file_config);
+impl NodeConfig {
+    /// Defaults for a network preset. `testnet` joins the public testnet;
+    /// `devnet` runs a standalone local node with verbose logging and the
+    /// admin and debug RPC namespaces open to localhost.
+    pub fn preset(network: &str) -> Result<Self, String> {
+        match network {
+            "testnet" => Ok(NodeConfig::default()),
+            "devnet" => {
+                let mut config = NodeConfig::default();
+                config.network.name = "Synergy Devnet".to_string();
+                config.network.bootnodes = Vec::new();
+                config.network.max_peers = 8;
+                config.logging.log_level = "debug".to_string();
+                config.p2p.listen_address = "127.0.0.1:30303".to_string();
+                config.p2p.public_address = "127.0.0.1:30303".to_string();
+                config.p2p.node_name = "synergy-devnet-01".to_string();
+                config.p2p.enable_discovery = false;
+                config.rpc.auth.allow_loopback_wallet = true;
+                config.rpc.auth.enable_admin = true;
+                config.rpc.auth.enable_debug = true;
+                config.rpc.auth.ip_allowlist = vec!["127.0.0.1".to_string(), "::1".to_string()];
+                Ok(config)
+            }
+            _ => Err(format!(
+                "Unknown network preset '{}'; expected one of: {}",
+                network,
+                NETWORK_PRESETS.join(", ")
+            )),
         }
     }
-
-    // Override with environment variables
-    config = apply_env_overrides(config)?;
-
-    Ok(config)
 }
 
-/// Merges two configurations, with the second taking precedence
-fn merge_configs(mut base: NodeConfig, override_config: NodeConfig) -> NodeConfig {
-    base.network = override_config.network;
-    base.blockchain = override_config.blockchain;
-    base.consensus = override_config.consensus;
-    base.logging = override_config.logging;
-    base.rpc = override_config.rpc;
-    base.p2p = override_config.p2p;
-    base.storage = override_config.storage;
-    base
+/// Loads and validates the configuration from the preset defaults, the
+/// TOML file at `path` (or `SYNERGY_CONFIG_PATH`), environment variables
+/// and the genesis file. See `ConfigSources` for command-line flags.
+pub fn load_node_config(path: Option<&str>) -> Result<NodeConfig, Box<dyn Error>> {
+    load_validated(&ConfigSources::from_path(path))
 }
 
 /// Applies environment variable overrides
@@ -235,6 +463,12 @@ fn apply_env_overrides(mut config: NodeConfig) -> Result<NodeConfig, Box<dyn Err
     if let Ok(val) = env::var("SYNERGY_LOG_FILE") {
         config.logging.log_file = val;
     }
+    if let Ok(val) = env::var("SYNERGY_LOG_FORMAT") {
+        config.logging.log_format = val;
+    }
+    if let Ok(val) = env::var("SYNERGY_LOG_MODULES") {
+        config.logging.module_levels = val;
+    }
 
     // Storage overrides
     if let Ok(val) = env::var("SYNERGY_DATA_PATH") {
@@ -246,7 +480,7 @@ fn apply_env_overrides(mut config: NodeConfig) -> Result<NodeConfig, Box<dyn Err
 
 /// Loads genesis configuration from genesis.json
 pub fn load_genesis_config() -> Result<serde_json::Value, Box<dyn Error>> {
-    let genesis_path = "config/genesis.json";
+    let genesis_path = crate::genesis::DEFAULT_GENESIS_PATH;
     if !Path::new(genesis_path).exists() {
         return Err(format!("Genesis file not found: {}", genesis_path).into());
     }
@@ -259,6 +493,6 @@ pub fn load_genesis_config() -> Result<serde_json::Value, Box<dyn Error>> {
 /// Saves current configuration to a file
 pub fn save_config(config: &NodeConfig, path: &str) -> Result<(), Box<dyn Error>> {
     let content = toml::to_string_pretty(config)?;
-    fs::write(path, content)?;
+    storage::write_atomic(path, content.as_bytes())?;
     Ok(())
 }
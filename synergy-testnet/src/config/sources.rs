@@ -0,0 +1,329 @@
+//! Layered configuration loading.
+//!
+//! A node's configuration is built from, in increasing priority: the
+//! defaults of a network preset, a TOML file, `SYNERGY_*` environment
+//! variables and command-line flags. Files may be partial; keys they leave
+//! out keep the preset's values. Consensus parameters are then taken from
+//! the genesis file, since every node on a network must agree on them.
+
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+use serde::de::DeserializeOwned;
+
+use super::{apply_env_overrides, ConsensusConfig, NodeConfig, RewardWeighting};
+use crate::genesis::Genesis;
+
+/// Preset used when neither `--network` nor `SYNERGY_NETWORK` names one.
+pub const DEFAULT_NETWORK: &str = "testnet";
+
+/// Tables whose keys are chosen by the operator rather than fixed by the
+/// schema.
+const DYNAMIC_TABLES: &[&str] = &["rpc.limits.per_method"];
+
+/// Command-line shorthands and the keys they set.
+const FLAG_KEYS: &[(&str, &str)] = &[
+    ("--data-dir", "storage.path"),
+    ("--genesis", "blockchain.genesis_file"),
+    ("--log-level", "logging.log_level"),
+    ("--p2p-port", "network.p2p_port"),
+    ("--rpc-port", "rpc.http_port"),
+];
+
+/// Where a node's configuration comes from.
+#[derive(Debug, Clone, Default)]
+pub struct ConfigSources {
+    /// TOML file to load; falls back to `SYNERGY_CONFIG_PATH`.
+    pub path: Option<String>,
+    /// Preset supplying the defaults; falls back to `SYNERGY_NETWORK`, then
+    /// `DEFAULT_NETWORK`.
+    pub network: Option<String>,
+    /// `section.key` assignments from the command line, applied last.
+    pub overrides: Vec<(String, String)>,
+}
+
+impl ConfigSources {
+    pub fn from_path(path: Option<&str>) -> Self {
+        ConfigSources { path: path.map(str::to_string), ..ConfigSources::default() }
+    }
+
+    /// Takes `--config`, `--network`, `--set key=value` and the shorthand
+    /// flags out of `args`. Flags accept `--flag value` or `--flag=value`.
+    /// Returns the arguments that were not consumed, in order.
+    pub fn from_args(args: &[String]) -> Result<(Self, Vec<String>), String> {
+        let mut sources = ConfigSources::default();
+        let mut rest = Vec::new();
+        let mut args = args.iter();
+
+        while let Some(arg) = args.next() {
+            let (flag, inline) = match arg.split_once('=') {
+                Some((flag, value)) if arg.starts_with("--") => (flag, Some(value.to_string())),
+                _ => (arg.as_str(), None),
+            };
+            let shorthand = FLAG_KEYS.iter().find(|(name, _)| *name == flag).map(|(_, key)| *key);
+            if !matches!(flag, "--config" | "--network" | "--set") && shorthand.is_none() {
+                rest.push(arg.clone());
+                continue;
+            }
+
+            let value = match inline {
+                Some(value) => value,
+                None => args.next().cloned().ok_or_else(|| format!("{} needs a value", flag))?,
+            };
+            match (flag, shorthand) {
+                ("--config", _) => sources.path = Some(value),
+                ("--network", _) => sources.network = Some(value),
+                ("--set", _) => {
+                    let (key, value) = value
+                        .split_once('=')
+                        .ok_or_else(|| format!("--set expects key=value, got '{}'", value))?;
+                    sources.overrides.push((key.trim().to_string(), value.to_string()));
+                }
+                (_, Some(key)) => sources.overrides.push((key.to_string(), value)),
+                _ => unreachable!(),
+            }
+        }
+
+        Ok((sources, rest))
+    }
+
+    /// The preset these sources start from.
+    pub fn network(&self) -> String {
+        self.network
+            .clone()
+            .or_else(|| env::var("SYNERGY_NETWORK").ok())
+            .unwrap_or_else(|| DEFAULT_NETWORK.to_string())
+    }
+}
+
+/// The effective configuration, before validation, with anything worth
+/// telling the operator about how it was assembled.
+#[derive(Debug, Clone)]
+pub struct LoadedConfig {
+    pub config: NodeConfig,
+    /// The file that was loaded, if any.
+    pub path: Option<String>,
+    /// Ignored keys and configured values that the genesis file overrides.
+    pub warnings: Vec<String>,
+}
+
+/// Builds the configuration from every layer without validating it.
+pub fn load_layered(sources: &ConfigSources) -> Result<LoadedConfig, Box<dyn Error>> {
+    let defaults = NodeConfig::preset(&sources.network())?;
+    let known = toml::Value::try_from(&defaults)?;
+    let mut merged = known.clone();
+    let mut warnings = Vec::new();
+
+    // File
+    let path = sources.path.clone().or_else(|| env::var("SYNERGY_CONFIG_PATH").ok());
+    if let Some(path) = &path {
+        if !Path::new(path).exists() {
+            return Err(format!("Config file not found: {}", path).into());
+        }
+        let content = fs::read_to_string(path)?;
+        let layer: toml::Value = toml::from_str(&content).map_err(|e| format!("Failed to parse {}: {}", path, e))?;
+
+        let mut unknown = Vec::new();
+        unknown_keys(&known, &layer, "", &mut unknown);
+        warnings.extend(unknown.into_iter().map(|key| format!("Unknown key '{}' in {} is ignored", key, path)));
+
+        merge(&mut merged, layer);
+    }
+    let mut config: NodeConfig = from_toml(&merged)
+        .map_err(|e| format!("Invalid configuration in {}: {}", path.as_deref().unwrap_or("defaults"), e))?;
+
+    // Environment
+    config = apply_env_overrides(config)?;
+
+    // Command line
+    if !sources.overrides.is_empty() {
+        let mut value = toml::Value::try_from(&config)?;
+        for (key, raw) in &sources.overrides {
+            set_key(&mut value, key, raw)?;
+        }
+        config = from_toml(&value).map_err(|e| format!("Invalid command-line override: {}", e))?;
+    }
+
+    // Genesis
+    if Path::new(&config.blockchain.genesis_file).exists() {
+        warnings.extend(config.apply_genesis()?);
+    } else {
+        warnings.push(format!(
+            "Genesis file {} not found; consensus parameters come from the configuration",
+            config.blockchain.genesis_file
+        ));
+    }
+
+    Ok(LoadedConfig { config, path, warnings })
+}
+
+/// Builds and validates the configuration, printing any warnings.
+pub fn load_validated(sources: &ConfigSources) -> Result<NodeConfig, Box<dyn Error>> {
+    let loaded = load_layered(sources)?;
+    for warning in &loaded.warnings {
+        println!("⚠️ {}", warning);
+    }
+    loaded
+        .config
+        .validate()
+        .map_err(|errors| format!("Invalid configuration:\n  - {}", errors.join("\n  - ")))?;
+    Ok(loaded.config)
+}
+
+impl NodeConfig {
+    /// Replaces the consensus parameters with those in
+    /// `blockchain.genesis_file` and checks that the chain ids agree.
+    /// Returns a warning for every configured value the genesis overrides.
+    pub fn apply_genesis(&mut self) -> Result<Vec<String>, Box<dyn Error>> {
+        let path = self.blockchain.genesis_file.clone();
+        let chain = Genesis::load(&path)?.config;
+        if chain.chain_id != self.blockchain.chain_id {
+            return Err(format!(
+                "blockchain.chain_id ({}) does not match chainId {} in {}",
+                self.blockchain.chain_id, chain.chain_id, path
+            )
+            .into());
+        }
+
+        let params = chain.synergy_consensus.parameters;
+        let consensus = ConsensusConfig {
+            algorithm: chain.synergy_consensus.algorithm,
+            block_time_secs: params.block_time,
+            epoch_length: params.epoch,
+            validator_cluster_size: params.validator_cluster_size,
+            // Not part of the genesis
+            max_validators: self.consensus.max_validators,
+            synergy_score_decay_rate: params.synergy_score_decay_rate,
+            vrf_enabled: params.vrf_enabled,
+            vrf_seed_epoch_interval: params.vrf_seed_epoch_interval,
+            max_synergy_points_per_epoch: params.max_synergy_points_per_epoch,
+            max_tasks_per_validator: params.max_tasks_per_validator,
+            reward_weighting: RewardWeighting {
+                task_accuracy: params.reward_weighting.task_accuracy,
+                uptime: params.reward_weighting.uptime,
+                collaboration: params.reward_weighting.collaboration,
+            },
+        };
+
+        let mut overridden = Vec::new();
+        changed_keys(
+            &toml::Value::try_from(&self.consensus)?,
+            &toml::Value::try_from(&consensus)?,
+            "consensus",
+            &mut overridden,
+        );
+        self.consensus = consensus;
+
+        Ok(overridden
+            .into_iter()
+            .map(|(key, configured, genesis)| {
+                format!("{} = {} is overridden by {} in {}", key, configured, genesis, path)
+            })
+            .collect())
+    }
+
+    /// The configuration as TOML, as `config show` prints it and
+    /// `config init` writes it.
+    pub fn to_toml(&self) -> Result<String, Box<dyn Error>> {
+        Ok(toml::to_string_pretty(self)?)
+    }
+}
+
+/// Round-trips through text so type errors name the offending key.
+fn from_toml<T: DeserializeOwned>(value: &toml::Value) -> Result<T, Box<dyn Error>> {
+    Ok(toml::from_str(&toml::to_string(value)?)?)
+}
+
+/// Overlays `layer` onto `base`, table by table.
+fn merge(base: &mut toml::Value, layer: toml::Value) {
+    match (base, layer) {
+        (toml::Value::Table(base), toml::Value::Table(layer)) => {
+            for (key, value) in layer {
+                match base.get_mut(&key) {
+                    Some(existing) if existing.is_table() && value.is_table() => merge(existing, value),
+                    _ => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, layer) => *base = layer,
+    }
+}
+
+/// Dotted paths of keys in `layer` that `known` does not have.
+fn unknown_keys(known: &toml::Value, layer: &toml::Value, prefix: &str, found: &mut Vec<String>) {
+    if DYNAMIC_TABLES.contains(&prefix) {
+        return;
+    }
+    if let (Some(known), Some(layer)) = (known.as_table(), layer.as_table()) {
+        for (key, value) in layer {
+            let path = join_key(prefix, key);
+            match known.get(key) {
+                Some(known_value) => unknown_keys(known_value, value, &path, found),
+                None => found.push(path),
+            }
+        }
+    }
+}
+
+/// Leaf values that differ between `before` and `after`, as
+/// `(path, before, after)`.
+fn changed_keys(before: &toml::Value, after: &toml::Value, prefix: &str, found: &mut Vec<(String, String, String)>) {
+    match (before.as_table(), after.as_table()) {
+        (Some(before), Some(after)) => {
+            for (key, value) in after {
+                if let Some(previous) = before.get(key) {
+                    changed_keys(previous, value, &join_key(prefix, key), found);
+                }
+            }
+        }
+        _ if before != after => found.push((prefix.to_string(), before.to_string(), after.to_string())),
+        _ => {}
+    }
+}
+
+/// Sets a dotted `key` to `raw`. Keys must already exist, except inside
+/// dynamic tables. `raw` is read as a TOML value (`8545`, `true`,
+/// `["a", "b"]`) unless the key holds a string.
+fn set_key(root: &mut toml::Value, key: &str, raw: &str) -> Result<(), String> {
+    let unknown = || format!("Unknown config key '{}'", key);
+    let parts: Vec<&str> = key.split('.').collect();
+    let (last, parents) = parts.split_last().ok_or_else(unknown)?;
+
+    let mut current = root;
+    for part in parents {
+        current = match current.get_mut(*part) {
+            Some(value) if value.is_table() => value,
+            _ => return Err(unknown()),
+        };
+    }
+    let table = current.as_table_mut().ok_or_else(unknown)?;
+
+    let parent_path = parents.join(".");
+    let value = match table.get(*last) {
+        Some(toml::Value::String(_)) => toml::Value::String(raw.to_string()),
+        Some(_) => parse_value(raw),
+        None if DYNAMIC_TABLES.contains(&parent_path.as_str()) => parse_value(raw),
+        None => return Err(unknown()),
+    };
+    table.insert(last.to_string(), value);
+    Ok(())
+}
+
+fn parse_value(raw: &str) -> toml::Value {
+    toml::from_str::<toml::Table>(&format!("value = {}", raw))
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .unwrap_or_else(|| toml::Value::String(raw.to_string()))
+}
+
+fn join_key(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", prefix, key)
+    }
+}
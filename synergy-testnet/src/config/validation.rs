@@ -0,0 +1,172 @@
+//! Semantic checks on a loaded `NodeConfig`.
+//!
+//! Deserialization already enforces types; these checks catch values that
+//! parse but cannot work, such as a zero block time or reward weights that
+//! do not add up to 1.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+
+use super::NodeConfig;
+use crate::genesis::WEIGHT_TOLERANCE;
+use crate::logging::{LevelFilter, LogFormat, LogLevel};
+
+impl NodeConfig {
+    /// Checks every section and returns all problems found, one line each,
+    /// prefixed with the offending key.
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+        let mut check = |ok: bool, message: String| {
+            if !ok {
+                errors.push(message);
+            }
+        };
+
+        // Network
+        let network = &self.network;
+        check(!network.name.trim().is_empty(), "network.name must not be empty".to_string());
+        check(network.max_peers > 0, "network.max_peers must be at least 1".to_string());
+        for bootnode in &network.bootnodes {
+            check(
+                bootnode.starts_with("enode://") && bootnode.contains('@'),
+                format!("network.bootnodes: '{}' is not an enode://<id>@<host>:<port> URL", bootnode),
+            );
+        }
+
+        // Blockchain
+        let blockchain = &self.blockchain;
+        check(blockchain.block_time > 0, "blockchain.block_time must be at least 1 second".to_string());
+        check(blockchain.chain_id > 0, "blockchain.chain_id must not be 0".to_string());
+        check(
+            u64::from_str_radix(blockchain.max_gas_limit.trim_start_matches("0x"), 16).is_ok_and(|limit| limit > 0),
+            format!("blockchain.max_gas_limit '{}' is not a positive hex number", blockchain.max_gas_limit),
+        );
+        check(!blockchain.genesis_file.trim().is_empty(), "blockchain.genesis_file must not be empty".to_string());
+
+        // Consensus
+        let consensus = &self.consensus;
+        check(!consensus.algorithm.trim().is_empty(), "consensus.algorithm must not be empty".to_string());
+        check(consensus.block_time_secs > 0, "consensus.block_time_secs must be at least 1 second".to_string());
+        check(consensus.epoch_length > 0, "consensus.epoch_length must be at least 1 block".to_string());
+        check(consensus.validator_cluster_size > 0, "consensus.validator_cluster_size must be at least 1".to_string());
+        check(
+            consensus.max_validators >= consensus.validator_cluster_size,
+            format!(
+                "consensus.max_validators ({}) must be at least consensus.validator_cluster_size ({})",
+                consensus.max_validators, consensus.validator_cluster_size
+            ),
+        );
+        check(
+            (0.0..=1.0).contains(&consensus.synergy_score_decay_rate),
+            format!("consensus.synergy_score_decay_rate ({}) must be between 0 and 1", consensus.synergy_score_decay_rate),
+        );
+        check(
+            !consensus.vrf_enabled || consensus.vrf_seed_epoch_interval > 0,
+            "consensus.vrf_seed_epoch_interval must be at least 1 when VRF is enabled".to_string(),
+        );
+        check(
+            consensus.max_synergy_points_per_epoch > 0,
+            "consensus.max_synergy_points_per_epoch must be at least 1".to_string(),
+        );
+        check(consensus.max_tasks_per_validator > 0, "consensus.max_tasks_per_validator must be at least 1".to_string());
+
+        let weights = &consensus.reward_weighting;
+        for (name, weight) in [
+            ("task_accuracy", weights.task_accuracy),
+            ("uptime", weights.uptime),
+            ("collaboration", weights.collaboration),
+        ] {
+            check(
+                (0.0..=1.0).contains(&weight),
+                format!("consensus.reward_weighting.{} ({}) must be between 0 and 1", name, weight),
+            );
+        }
+        let total = weights.task_accuracy + weights.uptime + weights.collaboration;
+        check(
+            (total - 1.0).abs() <= WEIGHT_TOLERANCE,
+            format!("consensus.reward_weighting must sum to 1, got {}", total),
+        );
+
+        // Logging
+        let logging = &self.logging;
+        let level = LogLevel::parse(&logging.log_level);
+        check(
+            level.is_some(),
+            format!("logging.log_level '{}' must be trace, debug, info, warn or error", logging.log_level),
+        );
+        check(
+            LogFormat::parse(&logging.log_format).is_some(),
+            format!("logging.log_format '{}' must be text or json", logging.log_format),
+        );
+        if let Err(e) = LevelFilter::parse(&logging.module_levels, level.unwrap_or(LogLevel::Info)) {
+            check(false, format!("logging.module_levels: {}", e));
+        }
+        check(!logging.log_file.trim().is_empty(), "logging.log_file must not be empty".to_string());
+        check(logging.max_file_size > 0, "logging.max_file_size must be at least 1 byte".to_string());
+        check(logging.max_files > 0, "logging.max_files must be at least 1".to_string());
+
+        // RPC
+        let rpc = &self.rpc;
+        let mut ports: HashMap<u16, &str> = HashMap::new();
+        for (enabled, port, key) in [
+            (rpc.enable_http, rpc.http_port, "rpc.http_port"),
+            (rpc.enable_ws, rpc.ws_port, "rpc.ws_port"),
+            (rpc.enable_grpc, rpc.grpc_port, "rpc.grpc_port"),
+        ] {
+            if !enabled {
+                continue;
+            }
+            check(port > 0, format!("{} must not be 0", key));
+            if let Some(other) = ports.insert(port, key) {
+                check(false, format!("{} and {} both use port {}", other, key, port));
+            }
+        }
+        let limits = &rpc.limits;
+        check(limits.worker_threads > 0, "rpc.limits.worker_threads must be at least 1".to_string());
+        check(limits.max_body_size > 0, "rpc.limits.max_body_size must be at least 1 byte".to_string());
+        check(limits.max_batch_size > 0, "rpc.limits.max_batch_size must be at least 1".to_string());
+        check(limits.read_timeout_secs > 0, "rpc.limits.read_timeout_secs must be at least 1".to_string());
+        check(limits.request_timeout_secs > 0, "rpc.limits.request_timeout_secs must be at least 1".to_string());
+        check(limits.write_timeout_secs > 0, "rpc.limits.write_timeout_secs must be at least 1".to_string());
+        check(
+            limits.per_ip.capacity > 0 && limits.per_ip.refill_per_sec > 0.0,
+            "rpc.limits.per_ip must allow at least one call".to_string(),
+        );
+        for (method, limit) in &limits.per_method {
+            check(
+                limit.capacity > 0 && limit.refill_per_sec > 0.0,
+                format!("rpc.limits.per_method.{} must allow at least one call", method),
+            );
+        }
+        check(
+            rpc.ws_limits.max_connections > 0,
+            "rpc.ws_limits.max_connections must be at least 1".to_string(),
+        );
+        check(
+            rpc.health.heartbeat_timeout_secs > 0,
+            "rpc.health.heartbeat_timeout_secs must be at least 1".to_string(),
+        );
+
+        // P2P
+        let p2p = &self.p2p;
+        for (address, key) in [(&p2p.listen_address, "p2p.listen_address"), (&p2p.public_address, "p2p.public_address")] {
+            check(
+                address.parse::<SocketAddr>().is_ok(),
+                format!("{} '{}' is not an <ip>:<port> address", key, address),
+            );
+        }
+        check(p2p.heartbeat_interval > 0, "p2p.heartbeat_interval must be at least 1 second".to_string());
+
+        // Storage
+        check(!self.storage.path.trim().is_empty(), "storage.path must not be empty".to_string());
+
+        // Wallet
+        check(!self.wallet.keystore_dir.trim().is_empty(), "wallet.keystore_dir must not be empty".to_string());
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
@@ -1,17 +1,74 @@
+use std::cmp::Ordering;
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering as AtomicOrdering};
 use std::sync::Arc;
-use std::thread;
+use std::path::Path;
+use std::thread::{self, JoinHandle};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use serde::{Deserialize, Serialize};
 use sha3::{Sha3_256, Digest};
+use zeroize::Zeroizing;
 use crate::block::{Block, BlockChain};
-use crate::rpc::rpc_server::TX_POOL;
+use crate::config::{NodeConfig, WalletConfig};
+use crate::crypto::keys::KeyPair;
+use crate::genesis::Genesis;
+use crate::rpc::rpc_server::{CHAIN, TX_POOL};
+use crate::events::{publish_block_logs, NodeEvent, EVENT_BUS};
+use crate::health;
+use crate::key_rotation::{KeyRegistry, KeyScope, KEY_REGISTRY};
+use crate::keystore::Keystore;
+use crate::name_service::{NameRegistry, NAME_REGISTRY};
+use crate::nonce::{NonceRegistry, QueuedTransactions, NONCE_REGISTRY};
+use crate::metrics;
+use crate::p2p::{self, networking::{P2PNetwork, PeerHeads}, peer_scoring::PeerEvent, protocol::HeadVote};
+use crate::shutdown;
+use crate::storage;
+use crate::transaction::{self, Transaction};
 use crate::validator::{ValidatorManager, Validator, ValidatorPerformanceUpdate};
 use crate::token::TOKEN_MANAGER;
-use crate::wallet::WALLET_MANAGER;
 
 const CHAIN_PATH: &str = "data/chain.json";
 const VALIDATOR_REGISTRY_PATH: &str = "data/validator_registry.json";
+const SYNERGY_SCORES_PATH: &str = "data/synergy_scores.json";
+
+/// Height of the latest finalized block; see `quorum_height`.
+static FINALIZED_HEIGHT: AtomicU64 = AtomicU64::new(0);
+
+/// Height of the latest block this node considers final. Never decreases.
+pub fn finalized_height() -> u64 {
+    FINALIZED_HEIGHT.load(AtomicOrdering::Relaxed)
+}
+
+/// Highest height that more than two thirds of `validators` are known to
+/// have reached on `chain`. Validators in `local_validators` are at our
+/// head; any other validator counts at the highest head it voted for,
+/// through any peer, that matches our block at that height and carries its
+/// signature.
+pub fn quorum_height(chain: &BlockChain, validators: &[Validator], local_validators: &[String], peers: &[PeerHeads]) -> u64 {
+    let head = chain.last().map_or(0, |block| block.block_index);
+    let mut heights: Vec<u64> = validators
+        .iter()
+        .map(|validator| {
+            if local_validators.contains(&validator.address) {
+                return head;
+            }
+            peers
+                .iter()
+                .flat_map(|peer| peer.votes.iter())
+                .filter(|vote| vote.validator == validator.address)
+                .filter(|vote| chain.chain.get(vote.height as usize).is_some_and(|block| block.hash == vote.hash))
+                .filter(|vote| vote.verify(&validator.public_key))
+                .map(|vote| vote.height)
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    if heights.is_empty() {
+        return 0;
+    }
+    heights.sort_unstable_by(|a, b| b.cmp(a));
+    heights[validators.len() * 2 / 3]
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SynergyScores {
@@ -31,6 +88,12 @@ pub struct ProofOfSynergy {
     pub vrf_seed_interval: u64,
     pub max_synergy_points: u64,
     pub reward_weights: RewardWeights,
+    /// No bootnodes: this node produces every block itself.
+    pub standalone: bool,
+    /// Keys this node signs blocks and head votes with, by lowercase hex
+    /// public key. A validator signs with the key matching its current
+    /// consensus key, so a rotated key is used once it is in force.
+    pub signing_keys: HashMap<String, KeyPair>,
 }
 
 #[derive(Debug, Clone)]
@@ -40,15 +103,36 @@ pub struct RewardWeights {
     pub collaboration: f64,
 }
 
+impl Default for ProofOfSynergy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ProofOfSynergy {
+    /// Builds the engine from the default configuration with the consensus
+    /// parameters of the default genesis file. Panics if the genesis file
+    /// or the chain on disk cannot be used.
     pub fn new() -> Self {
-        let chain = BlockChain::load_from_file(CHAIN_PATH).unwrap_or_else(|| {
-            println!("🧱 No chain found on disk — initializing new chain.");
-            let mut new_chain = BlockChain::new();
-            new_chain.genesis();
-            new_chain.save_to_file(CHAIN_PATH);
-            new_chain
-        });
+        let mut config = NodeConfig::default();
+        if let Err(e) = config.apply_genesis() {
+            crate::warn!("consensus", "Using default consensus parameters", "error" => e.to_string());
+        }
+        Self::from_config(&config).unwrap_or_else(|e| panic!("Failed to start consensus: {}", e))
+    }
+
+    /// Builds the engine from a loaded configuration, whose `[consensus]`
+    /// section already carries the genesis parameters. Fails if the genesis
+    /// file is missing or modified, or the chain on disk was started from a
+    /// different genesis.
+    pub fn from_config(config: &NodeConfig) -> Result<Self, String> {
+        let genesis = Genesis::load(&config.blockchain.genesis_file).map_err(|e| e.to_string())?;
+        let chain = Self::load_chain(&genesis.genesis_hash)?;
+        crate::info!("consensus", "Genesis loaded", "hash" => genesis.genesis_hash.clone(), "network" => genesis.meta.network.clone());
+
+        if let Err(e) = TOKEN_MANAGER.apply_genesis(&genesis) {
+            crate::warn!("consensus", "Genesis allocations not applied", "error" => e.to_string());
+        }
 
         // Initialize validator manager
         let validator_manager = Arc::new(ValidatorManager::new());
@@ -56,9 +140,22 @@ impl ProofOfSynergy {
         // Load validator registry from file or initialize genesis validators
         if let Err(e) = validator_manager.load_registry(VALIDATOR_REGISTRY_PATH) {
             println!("🔧 No validator registry found — initializing with genesis validators: {}", e);
-            Self::initialize_genesis_validators(&validator_manager);
+            Self::initialize_genesis_validators(&validator_manager, &genesis);
         }
 
+        // Key history is derived from the chain; validators pick up consensus keys already in force
+        let keys = KeyRegistry::from_chain(&chain, config.consensus.epoch_length);
+        for address in keys.addresses() {
+            if let Some(rotation) = keys.consensus_key(address, keys.height() + 1) {
+                let _ = validator_manager.set_public_key(address, &rotation.public_key);
+            }
+        }
+        *KEY_REGISTRY.lock().unwrap() = keys;
+        *NAME_REGISTRY.lock().unwrap() = NameRegistry::from_chain(&chain);
+        *NONCE_REGISTRY.lock().unwrap() = NonceRegistry::from_chain(&chain);
+
+        let signing_keys = Self::load_signing_keys(&config.wallet)?;
+
         let synergy_scores = Self::load_synergy_scores().unwrap_or_else(|| {
             println!("🔧 No synergy scores found — initializing empty scores.");
             SynergyScores {
@@ -67,32 +164,58 @@ impl ProofOfSynergy {
             }
         });
 
-        // Load configuration from genesis.json parameters
-        let block_time = 5;
-        let epoch_length = 30000;
-        let cluster_size = 7;
-        let vrf_enabled = true;
-        let vrf_seed_interval = 1000;
-        let max_synergy_points = 100;
-
+        let consensus = &config.consensus;
         let reward_weights = RewardWeights {
-            task_accuracy: 0.5,
-            uptime: 0.3,
-            collaboration: 0.2,
+            task_accuracy: consensus.reward_weighting.task_accuracy,
+            uptime: consensus.reward_weighting.uptime,
+            collaboration: consensus.reward_weighting.collaboration,
         };
 
-        ProofOfSynergy {
+        Ok(ProofOfSynergy {
             chain,
             validator_manager,
             synergy_scores,
-            block_time,
-            epoch_length,
-            cluster_size,
-            vrf_enabled,
-            vrf_seed_interval,
-            max_synergy_points,
+            block_time: consensus.block_time_secs,
+            epoch_length: consensus.epoch_length,
+            cluster_size: consensus.validator_cluster_size,
+            vrf_enabled: consensus.vrf_enabled,
+            vrf_seed_interval: consensus.vrf_seed_epoch_interval,
+            max_synergy_points: consensus.max_synergy_points_per_epoch,
             reward_weights,
+            standalone: config.network.bootnodes.is_empty(),
+            signing_keys,
+        })
+    }
+
+    /// Decrypts the keystore keys with the password in
+    /// `wallet.validator_password_file`, so both a validator's key and the
+    /// key it rotates to are at hand. Keys the password does not open are
+    /// skipped.
+    fn load_signing_keys(wallet: &WalletConfig) -> Result<HashMap<String, KeyPair>, String> {
+        let mut signing_keys = HashMap::new();
+        if wallet.validator_password_file.is_empty() {
+            return Ok(signing_keys);
         }
+        let content = Zeroizing::new(
+            std::fs::read_to_string(&wallet.validator_password_file)
+                .map_err(|e| format!("Failed to read {}: {}", wallet.validator_password_file, e))?,
+        );
+        let password = content.lines().next().unwrap_or("");
+
+        let keystore = Keystore::new(&wallet.keystore_dir);
+        let entries = keystore
+            .list()
+            .map_err(|e| format!("Failed to read keystore {}: {}", wallet.keystore_dir, e))?;
+        for entry in entries {
+            match keystore.unlock(&entry.address, password) {
+                Ok(keypair) => {
+                    signing_keys.insert(keypair.public_key_hex().to_lowercase(), keypair);
+                }
+                Err(e) => crate::warn!("consensus", "Skipping keystore key", "address" => entry.address, "error" => e.to_string()),
+            }
+        }
+        crate::info!("consensus", "Validator keys loaded", "keys" => signing_keys.len());
+        Ok(signing_keys)
     }
 
     pub fn initialize(&mut self) {
@@ -102,27 +225,80 @@ impl ProofOfSynergy {
         println!("🔧 Synergy scores loaded. Total entries: {}", self.synergy_scores.scores.len());
     }
 
-    pub fn execute(&mut self) {
+    /// Starts block production on a background thread. Each height has one
+    /// proposer; the node produces and signs the block only if it holds the
+    /// proposer's key, or if it runs standalone, and otherwise imports the
+    /// proposer's block from peers. The thread stops once a shutdown is
+    /// requested and flushes the chain, validator registry and synergy
+    /// scores before it returns.
+    pub fn execute(&mut self) -> JoinHandle<()> {
         println!("⚙️ Executing Proof of Synergy consensus engine...");
 
-        let mut chain = self.chain.clone();
-        let validator_manager = Arc::clone(&self.validator_manager);
-        let mut synergy_scores = self.synergy_scores.clone();
+        let mut state = ChainState {
+            chain: self.chain.clone(),
+            validator_manager: Arc::clone(&self.validator_manager),
+            synergy_scores: self.synergy_scores.clone(),
+            reward_weights: self.reward_weights.clone(),
+            queued: QueuedTransactions::new(),
+        };
+        let block_time = Duration::from_secs(self.block_time);
+        let standalone = self.standalone;
+        // Keys decrypted at startup; keys added to the keystore later need a restart
+        let signing_keys = self.signing_keys.clone();
+
+        // Serve the loaded chain over RPC
+        *CHAIN.lock().unwrap() = state.chain.clone();
 
         thread::spawn(move || {
             let mut last_block_time = SystemTime::now();
             let mut consecutive_failures = 0;
+            let mut voted_head = String::new();
+
+            while !shutdown::is_requested() {
+                health::consensus_heartbeat();
+
+                let mut validators = state.validator_manager.get_active_validators();
+                validators.sort_by(|a, b| a.address.cmp(&b.address));
+                let signing_key = |validator: &Validator| signing_keys.get(&validator.public_key.to_lowercase());
+                let local_validators: Vec<String> = validators
+                    .iter()
+                    .filter(|validator| standalone || signing_key(validator).is_some())
+                    .map(|validator| validator.address.clone())
+                    .collect();
+
+                let network = p2p::network();
+                if let Some(network) = &network {
+                    for (peer, block) in network.take_blocks() {
+                        if state.import_from_peer(network, &peer, &block) {
+                            last_block_time = SystemTime::now();
+                        }
+                    }
+                    // Vote for each new head with every validator key held
+                    if let Some(head) = state.chain.last().filter(|head| head.hash != voted_head) {
+                        let votes = validators
+                            .iter()
+                            .filter_map(|validator| {
+                                let key = signing_key(validator)?;
+                                Some(HeadVote::sign(key, &validator.address, head.block_index, &head.hash))
+                            })
+                            .collect();
+                        network.set_head_votes(votes);
+                        voted_head = head.hash.clone();
+                    }
+                }
+                let peers = network.as_ref().map_or_else(Vec::new, |network| network.peer_heads());
+                FINALIZED_HEIGHT.fetch_max(
+                    quorum_height(&state.chain, &validators, &local_validators, &peers),
+                    AtomicOrdering::Relaxed,
+                );
 
-            loop {
                 let current_time = SystemTime::now();
                 let elapsed = current_time.duration_since(last_block_time).unwrap_or_default();
 
-                if elapsed >= Duration::from_secs(5) {
-                    let mut pool = TX_POOL.lock().unwrap();
-
-                    if let Some(latest_block) = chain.last() {
+                if elapsed >= block_time {
+                    if let Some(latest_block) = state.chain.last().cloned() {
                         // Get active validators
-                        let active_validators = validator_manager.get_active_validators();
+                        let active_validators = state.validator_manager.get_active_validators();
 
                         if active_validators.is_empty() {
                             println!("⏳ No active validators available for block production.");
@@ -133,65 +309,78 @@ impl ProofOfSynergy {
                         // Select validator using synergy score and VRF
                         let selected_validator = Self::select_validator_for_block(&active_validators, latest_block.block_index);
 
-                        let transactions = if pool.is_empty() {
-                            vec![]
-                        } else {
-                            pool.clone()
-                        };
+                        // Another node holds the proposer's key; its block arrives over P2P
+                        if !local_validators.contains(&selected_validator.address) {
+                            thread::sleep(Duration::from_millis(100));
+                            continue;
+                        }
 
-                        let mut processed_transactions = Vec::new();
+                        let mut pool = TX_POOL.lock().unwrap();
+
+                        // Each sender's transactions in nonce order; equal nonces keep their arrival order
+                        let mut transactions = pool.clone();
+                        transactions.sort_by_key(|tx| tx.nonce);
 
-                        // Process transactions for token operations
-                        for tx in &transactions {
-                            if let Ok(result) = TOKEN_MANAGER.process_transaction(tx) {
-                                println!("✅ Processed transaction: {}", result);
-                                processed_transactions.push(tx.clone());
-
-                                // Update wallet nonce
-                                if let Ok(mut wallet_manager) = WALLET_MANAGER.lock() {
-                                    if let Some(wallet) = wallet_manager.get_wallet_mut(&tx.sender) {
-                                        wallet.increment_nonce();
-                                    }
+                        let mut processed_transactions = Vec::new();
+                        let mut queued_transactions = Vec::new();
+                        let height = latest_block.block_index + 1;
+                        let mut keys = KEY_REGISTRY.lock().unwrap();
+                        let mut names = NAME_REGISTRY.lock().unwrap();
+                        let mut nonces = NONCE_REGISTRY.lock().unwrap();
+
+                        // Pool transactions were verified on admission, so these mostly hit the signature cache
+                        let signatures = transaction::check_signatures(&transactions, &keys, height);
+
+                        // Process transactions for token operations, key rotations and names
+                        for (tx, signature) in transactions.iter().zip(signatures) {
+                            if let Err(e) = signature {
+                                crate::warn!("consensus", "Dropping transaction", "sender" => tx.sender.clone(), "reason" => e.to_string());
+                                continue;
+                            }
+                            match tx.nonce.cmp(&nonces.next_nonce(&tx.sender)) {
+                                Ordering::Less => {
+                                    crate::warn!("consensus", "Dropping transaction", "sender" => tx.sender.clone(), "reason" => format!("nonce {} was already used", tx.nonce));
+                                    continue;
                                 }
-                            } else {
-                                println!("❌ Failed to process transaction from {}: {}", tx.sender, result.unwrap_err());
+                                // Waits in the pool for the nonces before it
+                                Ordering::Greater => {
+                                    queued_transactions.push(tx.clone());
+                                    continue;
+                                }
+                                Ordering::Equal => {}
+                            }
+                            match Self::apply_transaction(&keys, &mut names, &state.validator_manager, tx, height) {
+                                Ok(result) => {
+                                    crate::info!("consensus", "Processed transaction", "result" => result);
+                                    nonces.record(tx);
+                                    processed_transactions.push(tx.clone());
+                                }
+                                Err(e) => crate::warn!("consensus", "Failed to process transaction", "sender" => tx.sender.clone(), "error" => e.to_string()),
                             }
                         }
 
-                        let new_block = Block::new(
-                            latest_block.block_index + 1,
+                        let mut new_block = Block::new(
+                            height,
                             processed_transactions,
                             latest_block.hash.clone(),
                             selected_validator.address.clone(),
                             Self::calculate_nonce(&latest_block.hash, &selected_validator.address),
                         );
-
-                        // Update validator performance
-                        let performance_update = ValidatorPerformanceUpdate {
-                            validator_address: selected_validator.address.clone(),
-                            update_type: "block_produced".to_string(),
-                            value: None,
-                            timestamp: Self::current_timestamp(),
-                        };
-                        validator_manager.update_performance(performance_update.clone());
-
-                        // Distribute validator rewards in SNRG
-                        let token_manager = TOKEN_MANAGER.clone();
-                        let _ = token_manager.distribute_validator_rewards(&selected_validator.address, 1000 * 10u64.pow(18)); // 1000 SNRG reward
-
-                        // Update synergy scores
-                        Self::distribute_rewards(&mut synergy_scores, &selected_validator.address, &validator_manager);
-
-                        chain.add_block(new_block.clone());
-                        chain.save_to_file(CHAIN_PATH);
-
-                        // Save validator registry
-                        if let Err(e) = validator_manager.save_registry(VALIDATOR_REGISTRY_PATH) {
-                            println!("⚠️ Failed to save validator registry: {}", e);
+                        // A standalone node without the proposer's key leaves the block unsigned
+                        if let Some(key) = signing_key(&selected_validator) {
+                            new_block.sign(key);
                         }
 
-                        if !pool.is_empty() {
-                            pool.clear();
+                        state.commit_block(&new_block, &mut keys, &mut names, &mut nonces);
+                        *pool = queued_transactions;
+                        state.prune_pool(&mut pool, &nonces, height);
+                        drop(keys);
+                        drop(names);
+                        drop(nonces);
+                        drop(pool);
+
+                        if let Some(network) = &network {
+                            network.broadcast_block(&new_block);
                         }
 
                         last_block_time = current_time;
@@ -204,6 +393,11 @@ impl ProofOfSynergy {
                         println!("   Synergy Score: {:.2}", selected_validator.synergy_score);
                         println!("   Tx Count: {}", new_block.transactions.len());
                         println!("   Block Hash: {}", new_block.hash);
+                        crate::info!("consensus", "Block produced",
+                            "height" => new_block.block_index,
+                            "validator" => selected_validator.address.clone(),
+                            "transactions" => new_block.transactions.len(),
+                            "hash" => new_block.hash.clone());
                     } else {
                         consecutive_failures += 1;
                         if consecutive_failures > 10 {
@@ -215,51 +409,137 @@ impl ProofOfSynergy {
 
                 thread::sleep(Duration::from_millis(100));
             }
-        });
+
+            crate::info!("consensus", "Block production stopped", "height" => state.chain.last().map_or(0, |b| b.block_index));
+            state.chain.save_to_file(CHAIN_PATH);
+            if let Err(e) = state.validator_manager.save_registry(VALIDATOR_REGISTRY_PATH) {
+                crate::error!("consensus", "Failed to save validator registry", "error" => e.to_string());
+            }
+            Self::save_synergy_scores(&state.synergy_scores);
+            crate::info!("consensus", "Consensus state flushed to disk");
+        })
     }
 
-    fn initialize_genesis_validators(validator_manager: &Arc<ValidatorManager>) {
-        // Load genesis validators from genesis.json
-        if let Ok(genesis_content) = std::fs::read_to_string("config/genesis.json") {
-            if let Ok(genesis) = serde_json::from_str::<serde_json::Value>(&genesis_content) {
-                if let Some(validator_array) = genesis["validators"]["initialValidators"].as_array() {
-                    for validator_data in validator_array {
-                        if let (Some(address), Some(pubkey)) = (
-                            validator_data["address"].as_str(),
-                            validator_data["pubKey"].as_str()
-                        ) {
-                            let stake_amount = validator_data["weight"].as_u64().unwrap_or(1000);
-
-                            let registration = crate::validator::ValidatorRegistration {
-                                address: address.to_string(),
-                                public_key: pubkey.to_string(),
-                                name: format!("Genesis Validator {}", address),
-                                stake_amount,
-                                submitted_at: Self::current_timestamp(),
-                                registration_tx_hash: "genesis".to_string(),
-                            };
-
-                            if let Err(e) = validator_manager.register_validator(registration) {
-                                println!("⚠️ Failed to register genesis validator {}: {}", address, e);
-                            } else {
-                                // Auto-approve genesis validators
-                                if let Err(e) = validator_manager.approve_validator(address) {
-                                    println!("⚠️ Failed to approve genesis validator {}: {}", address, e);
-                                } else {
-                                    println!("✅ Genesis validator {} registered and approved", address);
-                                }
-                            }
-                        }
-                    }
+    /// Loads the chain from disk and checks it before use. Blocks from the
+    /// first invalid one onwards are dropped; a file that cannot be parsed
+    /// at all is moved aside rather than overwritten, and a new chain is
+    /// started from `genesis_hash`. A chain started from a different genesis
+    /// is refused.
+    fn load_chain(genesis_hash: &str) -> Result<BlockChain, String> {
+        if storage::remove_stale_temp(CHAIN_PATH) {
+            crate::warn!("consensus", "Removed an unfinished chain write left by a previous crash");
+        }
+
+        if let Some(mut chain) = BlockChain::load_from_file(CHAIN_PATH) {
+            let removed = chain.truncate_to_valid();
+            if removed > 0 {
+                if chain.chain.is_empty() {
+                    chain.genesis_for(genesis_hash);
                 }
+                crate::warn!("consensus", "Chain failed verification, dropped invalid blocks",
+                    "dropped" => removed,
+                    "height" => chain.last().map_or(0, |b| b.block_index));
+                chain.save_to_file(CHAIN_PATH);
+            }
+            if chain.genesis_hash() != Some(genesis_hash) {
+                return Err(format!(
+                    "{} was started from genesis {}, not {}; remove it or use the matching genesis file",
+                    CHAIN_PATH,
+                    chain.genesis_hash().unwrap_or("(none)"),
+                    genesis_hash
+                ));
             }
+            return Ok(chain);
         }
+
+        if Path::new(CHAIN_PATH).exists() {
+            match storage::quarantine(CHAIN_PATH) {
+                Ok(moved) => crate::warn!("consensus", "Chain file is unreadable, moved it aside", "path" => moved.display().to_string()),
+                Err(e) => crate::error!("consensus", "Chain file is unreadable and could not be moved aside", "error" => e.to_string()),
+            }
+        } else {
+            crate::info!("consensus", "No chain found on disk, initializing a new chain");
+        }
+
+        let mut new_chain = BlockChain::new();
+        new_chain.genesis_for(genesis_hash);
+        new_chain.save_to_file(CHAIN_PATH);
+        Ok(new_chain)
+    }
+
+    fn initialize_genesis_validators(validator_manager: &Arc<ValidatorManager>, genesis: &Genesis) {
+        for validator in &genesis.validators.initial_validators {
+            let registration = crate::validator::ValidatorRegistration {
+                address: validator.address.clone(),
+                public_key: validator.pub_key.clone(),
+                name: validator.name.clone(),
+                stake_amount: validator.weight,
+                submitted_at: Self::current_timestamp(),
+                registration_tx_hash: "genesis".to_string(),
+            };
+
+            if let Err(e) = validator_manager.register_validator(registration) {
+                crate::warn!("consensus", "Failed to register genesis validator", "address" => validator.address.clone(), "error" => e.to_string());
+            } else {
+                // Auto-approve genesis validators
+                if let Err(e) = validator_manager.approve_validator(&validator.address) {
+                    crate::warn!("consensus", "Failed to approve genesis validator", "address" => validator.address.clone(), "error" => e.to_string());
+                } else {
+                    crate::info!("consensus", "Genesis validator registered and approved", "address" => validator.address.clone());
+                }
+            }
+        }
+    }
+
+    /// Applies a transaction whose signature and nonce were checked to the
+    /// token, name and key state for inclusion at `height`.
+    fn apply_transaction(keys: &KeyRegistry, names: &mut NameRegistry, validator_manager: &ValidatorManager, tx: &Transaction, height: u64) -> Result<String, String> {
+        if tx.is_key_rotation() {
+            Self::check_key_rotation(keys, validator_manager, tx, height)
+        } else if tx.is_name_operation() {
+            Self::process_name_operation(names, tx, height)
+        } else {
+            TOKEN_MANAGER.process_transaction(tx)
+        }
+    }
+
+    /// Checks a key rotation for inclusion at `height`. A consensus key
+    /// rotation must come from a validator and be signed by its current
+    /// consensus key.
+    fn check_key_rotation(keys: &KeyRegistry, validator_manager: &ValidatorManager, tx: &Transaction, height: u64) -> Result<String, String> {
+        let rotation = keys.check_rotation(tx, height)?;
+        if rotation.scope == KeyScope::Consensus {
+            let validator = validator_manager
+                .get_validator(&tx.sender)
+                .ok_or("Only validators have a consensus key to rotate")?;
+            if !validator.public_key.eq_ignore_ascii_case(&tx.public_key) {
+                return Err("A consensus key rotation must be signed by the current consensus key".to_string());
+            }
+        }
+        Ok(format!(
+            "{} key of {} rotates to {} at block {}",
+            rotation.scope,
+            tx.sender,
+            rotation.algorithm,
+            keys.effective_height(rotation.scope, height)
+        ))
+    }
+
+    /// Burns the SNRG fee of a name service operation from its sender and
+    /// records the operation for inclusion at `height`.
+    fn process_name_operation(names: &mut NameRegistry, tx: &Transaction, height: u64) -> Result<String, String> {
+        let op = names.check(tx, height)?;
+        if op.fee() > 0 {
+            let snrg = TOKEN_MANAGER.get_token_info("SNRG").ok_or("SNRG is not registered")?;
+            let fee = op.fee().checked_mul(10u64.pow(snrg.decimals as u32)).ok_or("Name service fee overflows")?;
+            TOKEN_MANAGER.burn_tokens(&tx.sender, "SNRG", fee)?;
+        }
+        names.apply(tx, height)
     }
 
     fn load_synergy_scores() -> Option<SynergyScores> {
-        let scores_path = "data/synergy_scores.json";
-        if std::path::Path::new(scores_path).exists() {
-            if let Ok(contents) = std::fs::read_to_string(scores_path) {
+        if Path::new(SYNERGY_SCORES_PATH).exists() {
+            if let Ok(contents) = std::fs::read_to_string(SYNERGY_SCORES_PATH) {
                 if let Ok(scores) = serde_json::from_str::<SynergyScores>(&contents) {
                     return Some(scores);
                 }
@@ -286,6 +566,10 @@ impl ProofOfSynergy {
             );
         }
 
+        // Every node must pick the same proposer, whatever order the registry lists validators in
+        let mut validators = validators.to_vec();
+        validators.sort_by(|a, b| a.address.cmp(&b.address));
+
         // Select validator based on synergy score and block height
         // Use block height as a simple entropy source for now
         // In production, this would use VRF with proper randomness
@@ -295,7 +579,7 @@ impl ProofOfSynergy {
         let random_value = (block_height % 1000) as f64 / 1000.0; // Simple pseudo-random
         let target = random_value * total_score;
 
-        for validator in validators {
+        for validator in &validators {
             cumulative_weight += validator.synergy_score;
             if cumulative_weight >= target {
                 return validator.clone();
@@ -303,7 +587,7 @@ impl ProofOfSynergy {
         }
 
         // Fallback to first validator
-        validators[0].clone()
+        validators.swap_remove(0)
     }
 
     fn calculate_nonce(previous_hash: &str, validator: &str) -> u64 {
@@ -314,9 +598,14 @@ impl ProofOfSynergy {
         u64::from_be_bytes(result[..8].try_into().unwrap())
     }
 
-    fn distribute_rewards(synergy_scores: &mut SynergyScores, validator_address: &str, validator_manager: &Arc<ValidatorManager>) {
+    fn distribute_rewards(
+        synergy_scores: &mut SynergyScores,
+        validator_address: &str,
+        validator_manager: &Arc<ValidatorManager>,
+        weights: &RewardWeights,
+    ) {
         if let Some(validator) = validator_manager.get_validator(validator_address) {
-            let reward = Self::calculate_reward(&validator);
+            let reward = Self::calculate_reward(&validator, weights);
             let current_score = synergy_scores.scores.get(validator_address).unwrap_or(&0.0);
             let new_score = (current_score + reward).min(100.0);
             synergy_scores.scores.insert(validator_address.to_string(), new_score);
@@ -327,19 +616,168 @@ impl ProofOfSynergy {
         }
     }
 
-    fn calculate_reward(validator: &Validator) -> f64 {
-        let task_reward = validator.task_accuracy * 0.5;
-        let uptime_reward = validator.uptime_percentage * 0.3;
-        let collaboration_reward = validator.collaboration_score * 0.2;
+    fn calculate_reward(validator: &Validator, weights: &RewardWeights) -> f64 {
+        let task_reward = validator.task_accuracy * weights.task_accuracy;
+        let uptime_reward = validator.uptime_percentage * weights.uptime;
+        let collaboration_reward = validator.collaboration_score * weights.collaboration;
 
         task_reward + uptime_reward + collaboration_reward
     }
 
     fn save_synergy_scores(scores: &SynergyScores) {
-        let scores_path = "data/synergy_scores.json";
-        if let Ok(json) = serde_json::to_string_pretty(scores) {
-            let _ = std::fs::create_dir_all("data");
-            let _ = std::fs::write(scores_path, json);
+        if let Err(e) = storage::write_json_atomic(SYNERGY_SCORES_PATH, scores) {
+            crate::error!("consensus", "Failed to save synergy scores", "error" => e.to_string());
+        }
+    }
+}
+
+/// Chain and validator state owned by the block production thread.
+struct ChainState {
+    chain: BlockChain,
+    validator_manager: Arc<ValidatorManager>,
+    synergy_scores: SynergyScores,
+    reward_weights: RewardWeights,
+    queued: QueuedTransactions,
+}
+
+impl ChainState {
+    /// Appends a block whose transactions were already applied: rewards its
+    /// validator, records its key rotations, closes the block in the name
+    /// and nonce registries, then persists and announces it.
+    fn commit_block(&mut self, block: &Block, keys: &mut KeyRegistry, names: &mut NameRegistry, nonces: &mut NonceRegistry) {
+        let block_interval = self.chain.last().map_or(0, |latest| block.timestamp.saturating_sub(latest.timestamp));
+
+        // Update validator performance
+        let performance_update = ValidatorPerformanceUpdate {
+            validator_address: block.validator_id.clone(),
+            update_type: "block_produced".to_string(),
+            value: None,
+            timestamp: ProofOfSynergy::current_timestamp(),
+        };
+        self.validator_manager.update_performance(performance_update);
+
+        // Distribute validator rewards in SNRG: 1000 SNRG in base units (1000 * 10^18 overflowed u64)
+        if let Some(snrg) = TOKEN_MANAGER.get_token_info("SNRG") {
+            let reward = 10u64.checked_pow(snrg.decimals as u32).and_then(|unit| unit.checked_mul(1000)).unwrap_or(u64::MAX);
+            let _ = TOKEN_MANAGER.distribute_validator_rewards(&block.validator_id, reward);
+        }
+
+        // Update synergy scores
+        ProofOfSynergy::distribute_rewards(&mut self.synergy_scores, &block.validator_id, &self.validator_manager, &self.reward_weights);
+
+        // Record the block's key rotations and switch consensus keys whose epoch starts next
+        keys.apply_block(block);
+        for rotation in keys.activated_at(block.block_index + 1) {
+            if rotation.scope == KeyScope::Consensus {
+                match self.validator_manager.set_public_key(&rotation.address, &rotation.public_key) {
+                    Ok(()) => crate::info!("consensus", "Consensus key rotated", "address" => rotation.address.clone(), "algorithm" => rotation.algorithm.to_string()),
+                    Err(e) => crate::error!("consensus", "Failed to rotate consensus key", "address" => rotation.address.clone(), "error" => e.to_string()),
+                }
+            }
+        }
+        names.end_block(block.block_index);
+        nonces.end_block(block.block_index);
+
+        self.chain.add_block(block.clone());
+        self.chain.save_to_file(CHAIN_PATH);
+        CHAIN.lock().unwrap().add_block(block.clone());
+
+        metrics::record_block(block.block_index, block.transactions.len(), Some(block_interval as f64));
+        EVENT_BUS.publish(NodeEvent::NewHead(block.clone()));
+        publish_block_logs(block);
+
+        // Save validator registry
+        if let Err(e) = self.validator_manager.save_registry(VALIDATOR_REGISTRY_PATH) {
+            crate::error!("consensus", "Failed to save validator registry", "error" => e.to_string());
+        }
+    }
+
+    /// Imports a block gossiped by `peer` if it extends our head, and
+    /// reports the peer for a block that fails validation. Blocks for other
+    /// heights are ignored. Returns true if the block was imported.
+    fn import_from_peer(&mut self, network: &P2PNetwork, peer: &str, block: &Block) -> bool {
+        let extends_head = self
+            .chain
+            .last()
+            .is_some_and(|latest| block.block_index == latest.block_index + 1 && block.previous_hash == latest.hash);
+        if !extends_head {
+            return false;
+        }
+
+        match self.import_block(block) {
+            Ok(()) => {
+                crate::info!("consensus", "Block imported",
+                    "height" => block.block_index,
+                    "transactions" => block.transactions.len(),
+                    "validator" => block.validator_id.clone(),
+                    "peer" => peer.to_string(),
+                    "hash" => block.hash.clone());
+                network.block_imported(peer, block);
+                true
+            }
+            Err((event, reason)) => {
+                crate::warn!("consensus", "Rejected block from peer", "height" => block.block_index, "peer" => peer.to_string(), "reason" => reason.to_string());
+                network.report_peer(peer, event);
+                false
+            }
+        }
+    }
+
+    /// Checks and applies a block that extends our head. Nothing is changed
+    /// unless every transaction applies.
+    fn import_block(&mut self, block: &Block) -> Result<(), (PeerEvent, String)> {
+        if block.hash != block.calculate_hash() {
+            return Err((PeerEvent::BadBlock, "hash does not match its contents".to_string()));
+        }
+        let parent = block.block_index.saturating_sub(1);
+        let proposer = ProofOfSynergy::select_validator_for_block(&self.validator_manager.get_active_validators(), parent);
+        if block.validator_id != proposer.address {
+            return Err((PeerEvent::BadBlock, format!("proposed by {}, not the proposer {}", block.validator_id, proposer.address)));
+        }
+        if !block.verify_signature(&proposer.public_key) {
+            return Err((PeerEvent::InvalidSignature, format!("not signed by the consensus key of {}", proposer.address)));
+        }
+
+        // Same lock order as block production
+        let mut pool = TX_POOL.lock().unwrap();
+        let mut keys = KEY_REGISTRY.lock().unwrap();
+        let mut names = NAME_REGISTRY.lock().unwrap();
+        let mut nonces = NONCE_REGISTRY.lock().unwrap();
+
+        block
+            .check_signatures(&keys)
+            .map_err(|e| (PeerEvent::InvalidSignature, e))?;
+
+        let mut block_names = names.clone();
+        let mut block_nonces = nonces.clone();
+        let tokens = TOKEN_MANAGER.snapshot();
+        for tx in &block.transactions {
+            let expected = block_nonces.next_nonce(&tx.sender);
+            let result = if tx.nonce != expected {
+                Err(format!("nonce {} of {}, expected {}", tx.nonce, tx.sender, expected))
+            } else {
+                ProofOfSynergy::apply_transaction(&keys, &mut block_names, &self.validator_manager, tx, block.block_index)
+            };
+            if let Err(e) = result {
+                TOKEN_MANAGER.restore(tokens);
+                return Err((PeerEvent::BadBlock, format!("transaction {}: {}", tx.hash(), e)));
+            }
+            block_nonces.record(tx);
+        }
+        *names = block_names;
+        *nonces = block_nonces;
+
+        self.commit_block(block, &mut keys, &mut names, &mut nonces);
+        self.prune_pool(&mut pool, &nonces, block.block_index);
+        Ok(())
+    }
+
+    /// Drops pool transactions that can no longer be included, because the
+    /// block used their nonce, or that waited too long for an earlier one.
+    fn prune_pool(&mut self, pool: &mut Vec<Transaction>, nonces: &NonceRegistry, height: u64) {
+        for tx in self.queued.prune(pool, nonces, height) {
+            crate::warn!("consensus", "Dropping transaction", "sender" => tx.sender.clone(),
+                "reason" => format!("nonce {} waited too long for nonce {}", tx.nonce, nonces.next_nonce(&tx.sender)));
         }
     }
 }
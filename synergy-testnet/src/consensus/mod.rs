@@ -5,11 +5,15 @@
 
 pub mod consensus_algorithm;
 
+use std::thread::JoinHandle;
+
 use self::consensus_algorithm::ProofOfSynergy;
+use crate::config::NodeConfig;
 
-/// Starts the consensus mechanism using Proof of Synergy.
-pub fn start_consensus() {
-    let mut engine = ProofOfSynergy::new();
+/// Starts the consensus mechanism using Proof of Synergy. Join the returned
+/// handle to wait for the engine to flush its state after a shutdown.
+pub fn start_consensus(config: &NodeConfig) -> Result<JoinHandle<()>, String> {
+    let mut engine = ProofOfSynergy::from_config(config)?;
     engine.initialize();
-    engine.execute(); // Starts the mining loop
+    Ok(engine.execute()) // Starts the mining loop
 }
@@ -22,6 +22,12 @@ pub struct ContractExecutor {
     pub contracts: HashMap<String, Contract>,
 }
 
+impl Default for ContractExecutor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ContractExecutor {
     pub fn new() -> Self {
         ContractExecutor {
@@ -0,0 +1,96 @@
+//! Mnemonic wallets: BIP-39 phrases and hierarchical deterministic
+//! derivation of ML-DSA-87 account keys.
+//!
+//! The phrase and optional passphrase give the 64-byte BIP-39 seed. Keys
+//! are derived from it SLIP-10 style, hardened only: the master node is
+//! `HMAC-SHA512(key = MASTER_KEY, data = seed)` and each child is
+//! `HMAC-SHA512(key = chain code, data = 0x00 || key || ser32(index | 2^31))`.
+//! The left half of the node at `m/44'/7963749'/<account>'/0'/0'` is the
+//! 32-byte ML-DSA-87 key generation seed of that account.
+//!
+//! Falcon keys are not derived: `pqcrypto`'s Falcon key generation takes
+//! its randomness from the OS and has no seeded variant.
+
+use bip39::Mnemonic;
+use hmac::{Hmac, Mac};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use sha2::Sha512;
+use zeroize::Zeroizing;
+
+use crate::crypto::keys::KeyPair;
+
+/// HMAC key of the master node; separates Synergy keys from other chains
+/// derived from the same phrase.
+pub const MASTER_KEY: &[u8] = b"Synergy ML-DSA-87 seed";
+/// Coin type in the derivation path; the Synergy chain ID.
+pub const COIN_TYPE: u32 = 7963749;
+/// Word counts `generate_mnemonic` accepts.
+pub const WORD_COUNTS: [usize; 2] = [12, 24];
+
+const HARDENED: u32 = 0x8000_0000;
+
+/// A fresh random phrase of 12 or 24 English words.
+pub fn generate_mnemonic(word_count: usize) -> Result<Mnemonic, String> {
+    if !WORD_COUNTS.contains(&word_count) {
+        return Err(format!("Mnemonic must have 12 or 24 words, not {}", word_count));
+    }
+    // 4 bytes of entropy per 3 words
+    let mut entropy = Zeroizing::new([0u8; 32]);
+    let entropy = &mut entropy[..word_count / 3 * 4];
+    OsRng.fill_bytes(entropy);
+    Mnemonic::from_entropy(entropy).map_err(|e| format!("Failed to generate mnemonic: {}", e))
+}
+
+/// Parses a phrase, checking its words and checksum. Case and surrounding
+/// whitespace are ignored.
+pub fn parse_mnemonic(phrase: &str) -> Result<Mnemonic, String> {
+    let phrase = Zeroizing::new(phrase.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase());
+    Mnemonic::parse_normalized(&phrase).map_err(|e| format!("Invalid mnemonic: {}", e))
+}
+
+/// Derivation path of `account`.
+pub fn account_path(account: u32) -> String {
+    format!("m/44'/{}'/{}'/0'/0'", COIN_TYPE, account)
+}
+
+/// The BIP-39 seed of a phrase, from which any number of accounts can be
+/// derived. Wiped on drop.
+pub struct HdSeed(Zeroizing<[u8; 64]>);
+
+impl HdSeed {
+    pub fn from_mnemonic(mnemonic: &Mnemonic, passphrase: &str) -> Self {
+        HdSeed(Zeroizing::new(mnemonic.to_seed(passphrase)))
+    }
+
+    /// Key pair of `account` (`account_path(account)`).
+    pub fn derive_account(&self, account: u32) -> Result<KeyPair, String> {
+        KeyPair::from_seed(&*self.account_seed(account)?)
+    }
+
+    /// The ML-DSA-87 key generation seed of `account`; exposed so other
+    /// implementations can check their derivation step by step.
+    pub fn account_seed(&self, account: u32) -> Result<Zeroizing<[u8; 32]>, String> {
+        if account >= HARDENED {
+            return Err(format!("Account index {} is out of range", account));
+        }
+        let mut node = hmac_sha512(MASTER_KEY, &self.0[..]);
+        for index in [44, COIN_TYPE, account, 0, 0] {
+            let mut data = Zeroizing::new([0u8; 37]);
+            data[1..33].copy_from_slice(&node[..32]);
+            data[33..].copy_from_slice(&(index | HARDENED).to_be_bytes());
+            node = hmac_sha512(&node[32..], &data[..]);
+        }
+
+        let mut seed = Zeroizing::new([0u8; 32]);
+        seed.copy_from_slice(&node[..32]);
+        Ok(seed)
+    }
+}
+
+/// A derivation node: key (left half) and chain code (right half).
+fn hmac_sha512(key: &[u8], data: &[u8]) -> Zeroizing<[u8; 64]> {
+    let mut mac = Hmac::<Sha512>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(data);
+    Zeroizing::new(mac.finalize().into_bytes().into())
+}
@@ -0,0 +1,100 @@
+//! Hybrid secp256k1 + ML-DSA-87 accounts.
+//!
+//! A hybrid account holds an ML-DSA-87 key and a secp256k1 key, and a
+//! transaction from it needs both signatures over its hash: ML-DSA over
+//! the hex hash as for every account (`Transaction::signature`) and ECDSA
+//! over the 32 hash bytes (`Transaction::hybrid`). The address hashes both
+//! public keys, so the account stays safe while either scheme holds, and
+//! users can bring the secp256k1 key they already use with Ethereum tools.
+
+use bincode::{Decode, Encode};
+use k256::ecdsa::signature::hazmat::{PrehashSigner, PrehashVerifier};
+use k256::ecdsa::{Signature, SigningKey, VerifyingKey};
+use rand::rngs::OsRng;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+use zeroize::Zeroizing;
+
+use crate::wallet::WalletManager;
+
+/// Keystore algorithm of hybrid key pairs.
+pub const ALGORITHM: &str = "ML-DSA-87+secp256k1";
+/// Length of a compressed SEC1 public key.
+pub const SECP256K1_PUBLIC_KEY_BYTES: usize = 33;
+pub const SECP256K1_PRIVATE_KEY_BYTES: usize = 32;
+
+/// The secp256k1 half of a hybrid sender's signature.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Encode, Decode, JsonSchema)]
+pub struct HybridSignature {
+    /// Hex compressed SEC1 secp256k1 public key.
+    pub public_key: String,
+    /// Hex 64-byte `r || s` ECDSA signature with low `s`.
+    pub signature: String,
+}
+
+/// Address of the hybrid account of both keys (hex). The `hybrid:` prefix
+/// keeps it apart from the single-key address of the ML-DSA key.
+pub fn address(mldsa_public_key: &str, secp256k1_public_key: &str) -> String {
+    WalletManager::generate_address(&format!("hybrid:{}:{}", secp256k1_public_key, mldsa_public_key))
+}
+
+pub fn generate_key() -> SigningKey {
+    SigningKey::random(&mut OsRng)
+}
+
+/// Parses a hex secp256k1 private key, with or without `0x`, as Ethereum
+/// wallets export it.
+pub fn parse_private_key(private_key: &str) -> Result<SigningKey, String> {
+    let private_key = private_key.trim();
+    let bytes = Zeroizing::new(
+        hex::decode(private_key.strip_prefix("0x").unwrap_or(private_key))
+            .map_err(|e| format!("Invalid secp256k1 private key: {}", e))?,
+    );
+    private_key_from_bytes(&bytes)
+}
+
+pub fn private_key_from_bytes(bytes: &[u8]) -> Result<SigningKey, String> {
+    if bytes.len() != SECP256K1_PRIVATE_KEY_BYTES {
+        return Err(format!("A secp256k1 private key is {} bytes, not {}", SECP256K1_PRIVATE_KEY_BYTES, bytes.len()));
+    }
+    SigningKey::from_slice(bytes).map_err(|_| "Invalid secp256k1 private key".to_string())
+}
+
+pub fn private_key_bytes(key: &SigningKey) -> Zeroizing<Vec<u8>> {
+    Zeroizing::new(key.to_bytes().to_vec())
+}
+
+pub fn public_key_hex(key: &SigningKey) -> String {
+    hex::encode(key.verifying_key().to_encoded_point(true).as_bytes())
+}
+
+/// Ethereum address of the secp256k1 key, so users can recognise a key
+/// they imported.
+pub fn eth_address(key: &SigningKey) -> String {
+    let point = key.verifying_key().to_encoded_point(false);
+    let hash = Keccak256::digest(&point.as_bytes()[1..]);
+    format!("0x{}", hex::encode(&hash[12..]))
+}
+
+/// Hex ECDSA signature by `key` over the 32-byte `digest`.
+pub fn sign(key: &SigningKey, digest: &[u8]) -> String {
+    let signature: Signature = key.sign_prehash(digest).expect("32-byte digest");
+    hex::encode(signature.to_bytes())
+}
+
+/// Whether `signature` is a low-`s` ECDSA signature over `digest` by the
+/// compressed `public_key` (both hex).
+pub fn verify(public_key: &str, digest: &[u8], signature: &str) -> bool {
+    let (Ok(public_key), Ok(signature)) = (hex::decode(public_key), hex::decode(signature)) else {
+        return false;
+    };
+    if public_key.len() != SECP256K1_PUBLIC_KEY_BYTES {
+        return false;
+    }
+    let (Ok(public_key), Ok(signature)) = (VerifyingKey::from_sec1_bytes(&public_key), Signature::from_slice(&signature)) else {
+        return false;
+    };
+    // A high `s` is the malleated twin of a valid signature
+    signature.normalize_s().is_none() && public_key.verify_prehash(digest, &signature).is_ok()
+}
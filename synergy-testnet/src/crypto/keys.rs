@@ -0,0 +1,126 @@
+//! ML-DSA-87 account keys.
+//!
+//! A `KeyPair` keeps its private half in memory that is wiped on drop and
+//! is never serialized; persist one with `crate::keystore`. A key pair
+//! that also holds a secp256k1 key is a hybrid account
+//! (`crate::crypto::hybrid`).
+
+use std::fmt;
+
+use k256::ecdsa::SigningKey;
+use zeroize::Zeroizing;
+
+use crate::crypto::pqc::{MlDsa87, SignatureAlgorithm, Signer, Verifier, VerifyRequest, SIGNATURE_CACHE};
+use crate::crypto::{hybrid, mldsa};
+use crate::wallet::WalletManager;
+
+/// Signature scheme of every account key.
+pub const ALGORITHM: &str = "ML-DSA-87";
+
+#[derive(Clone)]
+pub struct KeyPair {
+    public_key: Vec<u8>,
+    private_key: Zeroizing<Vec<u8>>,
+    secp256k1: Option<SigningKey>,
+}
+
+impl KeyPair {
+    pub fn generate() -> Self {
+        let (public_key, private_key) = MlDsa87.generate_keypair();
+        KeyPair { public_key, private_key, secp256k1: None }
+    }
+
+    /// The key pair FIPS 204 key generation gives for `seed`; used for
+    /// mnemonic accounts (`crate::crypto::hd`).
+    pub fn from_seed(seed: &[u8; mldsa::SEED_BYTES]) -> Result<Self, String> {
+        let (public_key, private_key) = mldsa::keypair_from_seed(seed);
+        Self::from_bytes(&public_key, &private_key)
+    }
+
+    /// Rebuilds a key pair, checking that both halves are well formed and
+    /// belong together.
+    pub fn from_bytes(public_key: &[u8], private_key: &[u8]) -> Result<Self, String> {
+        if public_key.len() != MlDsa87.public_key_bytes() {
+            return Err(format!("Invalid {} public key: {} bytes", ALGORITHM, public_key.len()));
+        }
+        if private_key.len() != MlDsa87.private_key_bytes() {
+            return Err(format!("Invalid {} private key: {} bytes", ALGORITHM, private_key.len()));
+        }
+
+        let keypair = KeyPair {
+            public_key: public_key.to_vec(),
+            private_key: Zeroizing::new(private_key.to_vec()),
+            secp256k1: None,
+        };
+        let probe = b"synergy key pair check";
+        if !verify(public_key, probe, &keypair.sign(probe)) {
+            return Err("Private key does not match the public key".to_string());
+        }
+        Ok(keypair)
+    }
+
+    pub fn public_key(&self) -> &[u8] {
+        &self.public_key
+    }
+
+    pub fn public_key_hex(&self) -> String {
+        hex::encode(&self.public_key)
+    }
+
+    pub fn private_key(&self) -> &[u8] {
+        &self.private_key
+    }
+
+    /// Makes this the hybrid account of both keys.
+    pub fn with_secp256k1(mut self, key: SigningKey) -> Self {
+        self.secp256k1 = Some(key);
+        self
+    }
+
+    pub fn secp256k1_key(&self) -> Option<&SigningKey> {
+        self.secp256k1.as_ref()
+    }
+
+    pub fn secp256k1_public_key_hex(&self) -> Option<String> {
+        self.secp256k1.as_ref().map(hybrid::public_key_hex)
+    }
+
+    pub fn is_hybrid(&self) -> bool {
+        self.secp256k1.is_some()
+    }
+
+    pub fn address(&self) -> String {
+        match self.secp256k1_public_key_hex() {
+            Some(secp256k1_public_key) => hybrid::address(&self.public_key_hex(), &secp256k1_public_key),
+            None => WalletManager::generate_address(&self.public_key_hex()),
+        }
+    }
+
+    /// Detached ML-DSA signature over `message`.
+    pub fn sign(&self, message: &[u8]) -> Vec<u8> {
+        // Both halves were validated on construction
+        MlDsa87.sign(&self.private_key, message).expect("validated private key")
+    }
+}
+
+impl fmt::Debug for KeyPair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyPair")
+            .field("address", &self.address())
+            .field("hybrid", &self.is_hybrid())
+            .field("private_key", &"<redacted>")
+            .finish()
+    }
+}
+
+/// Whether `signature` is a valid detached signature over `message` by
+/// `public_key`. Malformed keys and signatures are simply invalid.
+pub fn verify(public_key: &[u8], message: &[u8], signature: &[u8]) -> bool {
+    MlDsa87.verify(public_key, message, signature)
+}
+
+/// `verify` through `SIGNATURE_CACHE`, for transaction signatures that are
+/// checked again on their way into a block.
+pub fn verify_cached(public_key: &[u8], message: &[u8], signature: &[u8]) -> bool {
+    SIGNATURE_CACHE.verify(&VerifyRequest { algorithm: SignatureAlgorithm::MlDsa87, public_key, message, signature })
+}
@@ -0,0 +1,246 @@
+//! Deterministic ML-DSA-87 key generation.
+//!
+//! `pqcrypto` only generates keys from the system RNG, so seeded key
+//! generation (FIPS 204 `ML-DSA.KeyGen_internal`) lives here. The encoding
+//! matches PQClean byte for byte, and signing stays with `crate::crypto::pqc`.
+
+use sha3::digest::{ExtendableOutput, Update, XofReader};
+use sha3::{Shake128, Shake256};
+use zeroize::Zeroizing;
+
+pub const SEED_BYTES: usize = 32;
+pub const PUBLIC_KEY_BYTES: usize = SEED_BYTES + K * POLYT1_PACKED_BYTES;
+pub const SECRET_KEY_BYTES: usize =
+    2 * SEED_BYTES + TR_BYTES + (L + K) * POLYETA_PACKED_BYTES + K * POLYT0_PACKED_BYTES;
+
+const N: usize = 256;
+const Q: i32 = 8380417;
+const QINV: i32 = 58728449;
+const D: u32 = 13;
+const K: usize = 8;
+const L: usize = 7;
+const ETA: i32 = 2;
+const CRH_BYTES: usize = 64;
+const TR_BYTES: usize = 64;
+const POLYT1_PACKED_BYTES: usize = 320;
+const POLYT0_PACKED_BYTES: usize = 416;
+const POLYETA_PACKED_BYTES: usize = 96;
+
+const ZETAS: [i32; N] = [
+    0, 25847, -2608894, -518909, 237124, -777960, -876248, 466468, 1826347, 2353451, -359251, -2091905, 3119733,
+    -2884855, 3111497, 2680103, 2725464, 1024112, -1079900, 3585928, -549488, -1119584, 2619752, -2108549, -2118186,
+    -3859737, -1399561, -3277672, 1757237, -19422, 4010497, 280005, 2706023, 95776, 3077325, 3530437, -1661693,
+    -3592148, -2537516, 3915439, -3861115, -3043716, 3574422, -2867647, 3539968, -300467, 2348700, -539299, -1699267,
+    -1643818, 3505694, -3821735, 3507263, -2140649, -1600420, 3699596, 811944, 531354, 954230, 3881043, 3900724,
+    -2556880, 2071892, -2797779, -3930395, -1528703, -3677745, -3041255, -1452451, 3475950, 2176455, -1585221,
+    -1257611, 1939314, -4083598, -1000202, -3190144, -3157330, -3632928, 126922, 3412210, -983419, 2147896, 2715295,
+    -2967645, -3693493, -411027, -2477047, -671102, -1228525, -22981, -1308169, -381987, 1349076, 1852771, -1430430,
+    -3343383, 264944, 508951, 3097992, 44288, -1100098, 904516, 3958618, -3724342, -8578, 1653064, -3249728, 2389356,
+    -210977, 759969, -1316856, 189548, -3553272, 3159746, -1851402, -2409325, -177440, 1315589, 1341330, 1285669,
+    -1584928, -812732, -1439742, -3019102, -3881060, -3628969, 3839961, 2091667, 3407706, 2316500, 3817976, -3342478,
+    2244091, -2446433, -3562462, 266997, 2434439, -1235728, 3513181, -3520352, -3759364, -1197226, -3193378, 900702,
+    1859098, 909542, 819034, 495491, -1613174, -43260, -522500, -655327, -3122442, 2031748, 3207046, -3556995,
+    -525098, -768622, -3595838, 342297, 286988, -2437823, 4108315, 3437287, -3342277, 1735879, 203044, 2842341,
+    2691481, -2590150, 1265009, 4055324, 1247620, 2486353, 1595974, -3767016, 1250494, 2635921, -3548272, -2994039,
+    1869119, 1903435, -1050970, -1333058, 1237275, -3318210, -1430225, -451100, 1312455, 3306115, -1962642, -1279661,
+    1917081, -2546312, -1374803, 1500165, 777191, 2235880, 3406031, -542412, -2831860, -1671176, -1846953, -2584293,
+    -3724270, 594136, -3776993, -2013608, 2432395, 2454455, -164721, 1957272, 3369112, 185531, -1207385, -3183426,
+    162844, 1616392, 3014001, 810149, 1652634, -3694233, -1799107, -3038916, 3523897, 3866901, 269760, 2213111,
+    -975884, 1717735, 472078, -426683, 1723600, -1803090, 1910376, -1667432, -1104333, -260646, -3833893, -2939036,
+    -2235985, -420899, -2286327, 183443, -976891, 1612842, -3545687, -554416, 3919660, -48306, -1362209, 3937738,
+    1400424, -846154, 1976782,
+];
+
+type Poly = [i32; N];
+
+/// Public and private key bytes for the 32-byte seed `xi`, in the layout
+/// `mldsa87::PublicKey::from_bytes` / `SecretKey::from_bytes` expect.
+pub fn keypair_from_seed(xi: &[u8; SEED_BYTES]) -> (Vec<u8>, Zeroizing<Vec<u8>>) {
+    // (rho, rho', K) = H(xi || k || l)
+    let mut expanded = Zeroizing::new([0u8; 2 * SEED_BYTES + CRH_BYTES]);
+    let mut hasher = Shake256::default();
+    hasher.update(xi);
+    hasher.update(&[K as u8, L as u8]);
+    hasher.finalize_xof().read(&mut expanded[..]);
+    let (rho, rest) = expanded.split_at(SEED_BYTES);
+    let (rho_prime, key) = rest.split_at(CRH_BYTES);
+
+    let mut s1 = Zeroizing::new([[0i32; N]; L]);
+    let mut s2 = Zeroizing::new([[0i32; N]; K]);
+    for (i, poly) in s1.iter_mut().enumerate() {
+        uniform_eta(poly, rho_prime, i as u16);
+    }
+    for (i, poly) in s2.iter_mut().enumerate() {
+        uniform_eta(poly, rho_prime, (L + i) as u16);
+    }
+
+    let mut s1_hat = s1.clone();
+    for poly in s1_hat.iter_mut() {
+        ntt(poly);
+    }
+
+    // t = A * s1 + s2, split into high bits t1 and low bits t0
+    let mut t1 = [[0i32; N]; K];
+    let mut t0 = Zeroizing::new([[0i32; N]; K]);
+    for i in 0..K {
+        let mut t = [0i32; N];
+        for (j, s) in s1_hat.iter().enumerate() {
+            let mut a = [0i32; N];
+            uniform(&mut a, rho, ((i << 8) + j) as u16);
+            for c in 0..N {
+                t[c] += montgomery_reduce(a[c] as i64 * s[c] as i64);
+            }
+        }
+        for c in t.iter_mut() {
+            *c = reduce32(*c);
+        }
+        invntt_tomont(&mut t);
+        for c in 0..N {
+            let value = caddq(t[c] + s2[i][c]);
+            t1[i][c] = (value + (1 << (D - 1)) - 1) >> D;
+            t0[i][c] = value - (t1[i][c] << D);
+        }
+    }
+
+    let mut public_key = Vec::with_capacity(PUBLIC_KEY_BYTES);
+    public_key.extend_from_slice(rho);
+    for poly in &t1 {
+        pack_t1(&mut public_key, poly);
+    }
+
+    let mut tr = [0u8; TR_BYTES];
+    let mut hasher = Shake256::default();
+    hasher.update(&public_key);
+    hasher.finalize_xof().read(&mut tr);
+
+    let mut private_key = Zeroizing::new(Vec::with_capacity(SECRET_KEY_BYTES));
+    private_key.extend_from_slice(rho);
+    private_key.extend_from_slice(key);
+    private_key.extend_from_slice(&tr);
+    for poly in s1.iter().chain(s2.iter()) {
+        pack_eta(&mut private_key, poly);
+    }
+    for poly in t0.iter() {
+        pack_t0(&mut private_key, poly);
+    }
+    (public_key, private_key)
+}
+
+/// Rejection-samples a polynomial with coefficients in [0, Q).
+fn uniform(poly: &mut Poly, rho: &[u8], nonce: u16) {
+    let mut hasher = Shake128::default();
+    hasher.update(rho);
+    hasher.update(&nonce.to_le_bytes());
+    let mut reader = hasher.finalize_xof();
+
+    let mut filled = 0;
+    let mut bytes = [0u8; 3];
+    while filled < N {
+        reader.read(&mut bytes);
+        let value = (u32::from_le_bytes([bytes[0], bytes[1], bytes[2], 0]) & 0x7F_FFFF) as i32;
+        if value < Q {
+            poly[filled] = value;
+            filled += 1;
+        }
+    }
+}
+
+/// Rejection-samples a polynomial with coefficients in [-ETA, ETA].
+fn uniform_eta(poly: &mut Poly, rho_prime: &[u8], nonce: u16) {
+    let mut hasher = Shake256::default();
+    hasher.update(rho_prime);
+    hasher.update(&nonce.to_le_bytes());
+    let mut reader = hasher.finalize_xof();
+
+    let mut filled = 0;
+    let mut byte = [0u8; 1];
+    while filled < N {
+        reader.read(&mut byte);
+        for nibble in [byte[0] & 0x0F, byte[0] >> 4] {
+            if nibble < 15 && filled < N {
+                poly[filled] = ETA - (nibble % 5) as i32;
+                filled += 1;
+            }
+        }
+    }
+}
+
+fn montgomery_reduce(a: i64) -> i32 {
+    let t = (a as i32).wrapping_mul(QINV);
+    ((a - t as i64 * Q as i64) >> 32) as i32
+}
+
+fn reduce32(a: i32) -> i32 {
+    a - ((a + (1 << 22)) >> 23) * Q
+}
+
+fn caddq(a: i32) -> i32 {
+    a + ((a >> 31) & Q)
+}
+
+fn ntt(poly: &mut Poly) {
+    let mut k = 0;
+    let mut len = 128;
+    while len > 0 {
+        let mut start = 0;
+        while start < N {
+            k += 1;
+            let zeta = ZETAS[k] as i64;
+            for j in start..start + len {
+                let t = montgomery_reduce(zeta * poly[j + len] as i64);
+                poly[j + len] = poly[j] - t;
+                poly[j] += t;
+            }
+            start += 2 * len;
+        }
+        len >>= 1;
+    }
+}
+
+fn invntt_tomont(poly: &mut Poly) {
+    // mont^2 / 256
+    const F: i64 = 41978;
+    let mut k = N;
+    let mut len = 1;
+    while len < N {
+        let mut start = 0;
+        while start < N {
+            k -= 1;
+            let zeta = -ZETAS[k] as i64;
+            for j in start..start + len {
+                let t = poly[j];
+                poly[j] = t + poly[j + len];
+                poly[j + len] = montgomery_reduce(zeta * (t - poly[j + len]) as i64);
+            }
+            start += 2 * len;
+        }
+        len <<= 1;
+    }
+    for c in poly.iter_mut() {
+        *c = montgomery_reduce(F * *c as i64);
+    }
+}
+
+fn pack_t1(out: &mut Vec<u8>, poly: &Poly) {
+    for c in poly.chunks_exact(4) {
+        let bits = c[0] as u64 | (c[1] as u64) << 10 | (c[2] as u64) << 20 | (c[3] as u64) << 30;
+        out.extend_from_slice(&bits.to_le_bytes()[..5]);
+    }
+}
+
+fn pack_eta(out: &mut Vec<u8>, poly: &Poly) {
+    for c in poly.chunks_exact(8) {
+        let bits = c.iter().enumerate().fold(0u32, |bits, (i, &c)| bits | ((ETA - c) as u32) << (3 * i));
+        out.extend_from_slice(&bits.to_le_bytes()[..3]);
+    }
+}
+
+fn pack_t0(out: &mut Vec<u8>, poly: &Poly) {
+    for c in poly.chunks_exact(8) {
+        let bits = c
+            .iter()
+            .enumerate()
+            .fold(0u128, |bits, (i, &c)| bits | (((1 << (D - 1)) - c) as u128) << (13 * i));
+        out.extend_from_slice(&bits.to_le_bytes()[..13]);
+    }
+}
@@ -1,5 +1,11 @@
+pub mod hd;
+pub mod hybrid;
+pub mod keys;
+pub mod mldsa;
+pub mod multisig;
 pub mod pqc;
 // pub mod traditional; // Temporarily disabled - module not found
 
-pub use pqc::PQCManager;
+pub use keys::KeyPair;
+pub use pqc::{Kem, KemAlgorithm, SignatureAlgorithm, Signer, Verifier};
 // pub use traditional::TraditionalCrypto;
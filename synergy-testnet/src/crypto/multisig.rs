@@ -0,0 +1,155 @@
+//! M-of-N multisignature accounts.
+//!
+//! A multisig account is owned by a set of ML-DSA-87 public keys and a
+//! threshold. Its address is derived from the threshold and the sorted
+//! keys, so listing the signers in any order gives the same account. A
+//! transaction from it carries the policy and one signature slot per key
+//! (`Transaction::multisig`) and is valid once `threshold` slots hold
+//! signatures over its hash.
+
+use bincode::{Decode, Encode};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::crypto::keys::{self, KeyPair};
+use crate::crypto::mldsa;
+use crate::wallet::WalletManager;
+
+/// Most keys a policy may list.
+pub const MAX_SIGNERS: usize = 16;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Encode, Decode, JsonSchema)]
+pub struct MultisigPolicy {
+    /// Signatures needed to spend.
+    pub threshold: u32,
+    /// Hex ML-DSA-87 public keys of the signers, sorted and distinct.
+    pub public_keys: Vec<String>,
+}
+
+impl MultisigPolicy {
+    /// A policy over `public_keys` in any order and case.
+    pub fn new(threshold: u32, public_keys: Vec<String>) -> Result<Self, String> {
+        let mut public_keys: Vec<String> = public_keys.iter().map(|key| key.trim().to_ascii_lowercase()).collect();
+        public_keys.sort();
+        let policy = MultisigPolicy { threshold, public_keys };
+        policy.check()?;
+        Ok(policy)
+    }
+
+    /// Checks a policy read from a transaction or file.
+    pub fn check(&self) -> Result<(), String> {
+        let signers = self.public_keys.len();
+        if signers == 0 || signers > MAX_SIGNERS {
+            return Err(format!("A multisig policy needs 1 to {} public keys, not {}", MAX_SIGNERS, signers));
+        }
+        if self.threshold == 0 || self.threshold as usize > signers {
+            return Err(format!("Multisig threshold must be between 1 and {}, not {}", signers, self.threshold));
+        }
+        for key in &self.public_keys {
+            let canonical = key.chars().all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c));
+            if !canonical || key.len() != 2 * mldsa::PUBLIC_KEY_BYTES {
+                return Err(format!("Multisig public keys must be {} bytes of lowercase hex", mldsa::PUBLIC_KEY_BYTES));
+            }
+        }
+        if self.public_keys.windows(2).any(|pair| pair[0] >= pair[1]) {
+            return Err("Multisig public keys must be sorted and distinct".to_string());
+        }
+        Ok(())
+    }
+
+    /// Account address of the policy. The `multisig:` prefix keeps it apart
+    /// from every single-key address, which hashes a bare hex key.
+    pub fn address(&self) -> String {
+        WalletManager::generate_address(&format!("multisig:{}:{}", self.threshold, self.public_keys.join(":")))
+    }
+
+    /// Single-key addresses of the signers, in policy order.
+    pub fn signer_addresses(&self) -> Vec<String> {
+        self.public_keys.iter().map(|key| WalletManager::generate_address(key)).collect()
+    }
+
+    pub fn position(&self, public_key: &str) -> Option<usize> {
+        self.public_keys.iter().position(|key| key == public_key)
+    }
+}
+
+/// Signatures of a multisig sender, one slot per policy key.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Encode, Decode, JsonSchema)]
+pub struct MultisigAuth {
+    pub policy: MultisigPolicy,
+    /// Hex ML-DSA-87 signatures in policy key order; empty where that
+    /// signer has not signed yet.
+    pub signatures: Vec<String>,
+}
+
+impl MultisigAuth {
+    pub fn new(policy: MultisigPolicy) -> Self {
+        let signatures = vec![String::new(); policy.public_keys.len()];
+        MultisigAuth { policy, signatures }
+    }
+
+    pub fn signed_count(&self) -> usize {
+        self.signatures.iter().filter(|signature| !signature.is_empty()).count()
+    }
+
+    /// Fills the slot of `keypair`, which must be one of the policy keys.
+    pub fn sign(&mut self, keypair: &KeyPair, message: &[u8]) -> Result<(), String> {
+        let slot = self
+            .policy
+            .position(&keypair.public_key_hex())
+            .ok_or_else(|| format!("{} is not a signer of {}", keypair.address(), self.policy.address()))?;
+        self.signatures.resize(self.policy.public_keys.len(), String::new());
+        self.signatures[slot] = hex::encode(keypair.sign(message));
+        Ok(())
+    }
+
+    /// Takes the signatures of `other`, a copy of the same transaction
+    /// signed by other signers, for slots that are still empty.
+    pub fn merge(&mut self, other: &MultisigAuth) -> Result<(), String> {
+        if self.policy != other.policy {
+            return Err("Multisig policies differ".to_string());
+        }
+        self.signatures.resize(self.policy.public_keys.len(), String::new());
+        for (slot, signature) in self.signatures.iter_mut().zip(&other.signatures) {
+            if slot.is_empty() {
+                slot.clone_from(signature);
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks the policy and every filled slot against `message`, and
+    /// returns how many slots are filled.
+    pub fn check_signatures(&self, message: &[u8]) -> Result<usize, String> {
+        self.policy.check()?;
+        if self.signatures.len() != self.policy.public_keys.len() {
+            return Err(format!(
+                "Multisig transaction has {} signature slots for {} keys",
+                self.signatures.len(),
+                self.policy.public_keys.len()
+            ));
+        }
+        for ((key, signature), address) in self.policy.public_keys.iter().zip(&self.signatures).zip(self.policy.signer_addresses()) {
+            if signature.is_empty() {
+                continue;
+            }
+            let valid = match (hex::decode(key), hex::decode(signature)) {
+                (Ok(key), Ok(signature)) => keys::verify_cached(&key, message, &signature),
+                _ => false,
+            };
+            if !valid {
+                return Err(format!("Invalid multisig signature from {}", address));
+            }
+        }
+        Ok(self.signed_count())
+    }
+
+    /// `check_signatures`, also requiring at least `threshold` signatures.
+    pub fn verify(&self, message: &[u8]) -> Result<(), String> {
+        let signed = self.check_signatures(message)?;
+        if signed < self.policy.threshold as usize {
+            return Err(format!("Multisig transaction has {} of {} required signatures", signed, self.policy.threshold));
+        }
+        Ok(())
+    }
+}
@@ -1,641 +1,538 @@
-use std::collections::HashMap;
+//! Post-quantum signatures and key encapsulation.
+//!
+//! Stateless wrappers over the PQClean implementations in `pqcrypto`. Every
+//! parameter set is a unit type implementing `Signer`/`Verifier` or `Kem`
+//! over raw byte keys, and `SignatureAlgorithm`/`KemAlgorithm` name them in
+//! data, serializing as the standard names ("ML-DSA-87", "ML-KEM-768").
+//! Nothing is kept between calls: callers own their keys, and a signature
+//! verifies in any process that has the public key.
+//!
+//! The SLH-DSA sets are PQClean's SPHINCS+ "simple" instances, which have
+//! the FIPS 205 parameters and sizes.
+//!
+//! `verify_batch` spreads many signatures over the rayon pool, and
+//! `SIGNATURE_CACHE` remembers signatures that verified, so one checked at
+//! mempool admission is not checked again when its block is built or loaded.
+
+use std::fmt;
+use std::num::NonZeroUsize;
+use std::str::FromStr;
+use std::sync::Mutex;
+
+use bincode::{Decode, Encode};
+use lazy_static::lazy_static;
+use lru::LruCache;
+use pqcrypto::kem::{
+    mceliece348864, mceliece348864f, mceliece460896, mceliece460896f, mceliece6688128, mceliece6688128f,
+    mceliece6960119, mceliece6960119f, mceliece8192128, mceliece8192128f, mlkem1024, mlkem512, mlkem768,
+};
+use pqcrypto::sign::{
+    falcon1024, falcon512, mldsa44, mldsa65, mldsa87, sphincssha2128fsimple, sphincssha2128ssimple,
+    sphincssha2192fsimple, sphincssha2192ssimple, sphincssha2256fsimple, sphincssha2256ssimple,
+    sphincsshake128fsimple, sphincsshake128ssimple, sphincsshake192fsimple, sphincsshake192ssimple,
+    sphincsshake256fsimple, sphincsshake256ssimple,
+};
+use pqcrypto_traits::kem::{Ciphertext as _, PublicKey as _, SecretKey as _, SharedSecret as _};
+use pqcrypto_traits::sign::{DetachedSignature as _, PublicKey as _, SecretKey as _};
+use rayon::prelude::*;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use pqcrypto::kem::{mlkem512, mlkem768, mlkem1024, mceliece348864};
-// use pqcrypto::sign::{mldsa44, mldsa65, mldsa87, falcon512, sphincsplus_sha256_128s_robust};
+use zeroize::Zeroizing;
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-pub enum PQCAlgorithm {
-    Kyber,      // CRYSTALS-Kyber - Key Encapsulation Mechanism
-    Dilithium,  // CRYSTALS-Dilithium - Digital Signature
-    Falcon,     // Falcon - Digital Signature
-    Sphincs,    // SPHINCS+ - Digital Signature
-    ClassicMcEliece, // Classic-McEliece - Key Encapsulation Mechanism
+/// Signatures `SIGNATURE_CACHE` remembers.
+pub const SIGNATURE_CACHE_CAPACITY: usize = 65_536;
+
+lazy_static! {
+    /// Verified signatures of this process, shared by mempool admission and
+    /// block building and loading.
+    pub static ref SIGNATURE_CACHE: SignatureCache = SignatureCache::new(SIGNATURE_CACHE_CAPACITY);
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct PQCPublicKey {
-    pub algorithm: PQCAlgorithm,
-    pub key_data: Vec<u8>,
-    pub key_id: String,
-    pub created_at: u64,
+/// Checks signatures of one parameter set.
+pub trait Verifier {
+    fn algorithm(&self) -> SignatureAlgorithm;
+    fn public_key_bytes(&self) -> usize;
+    /// Largest signature; Falcon signatures may be shorter.
+    fn signature_bytes(&self) -> usize;
+    /// Whether `signature` is a valid detached signature over `message` by
+    /// `public_key`. Malformed keys and signatures are simply invalid.
+    fn verify(&self, public_key: &[u8], message: &[u8], signature: &[u8]) -> bool;
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct PQCPrivateKey {
-    pub algorithm: PQCAlgorithm,
-    pub key_data: Vec<u8>,
-    pub public_key_id: String,
-    pub created_at: u64,
+/// Creates keys and signatures of one parameter set.
+pub trait Signer: Verifier {
+    fn private_key_bytes(&self) -> usize;
+    /// A fresh `(public key, private key)` pair from the system RNG.
+    fn generate_keypair(&self) -> (Vec<u8>, Zeroizing<Vec<u8>>);
+    /// Detached signature over `message`.
+    fn sign(&self, private_key: &[u8], message: &[u8]) -> Result<Vec<u8>, String>;
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct PQCSignature {
-    pub algorithm: PQCAlgorithm,
-    pub signature_data: Vec<u8>,
-    pub message_hash: Vec<u8>,
-    pub public_key_id: String,
-    pub created_at: u64,
+/// Key encapsulation with one parameter set.
+pub trait Kem {
+    fn algorithm(&self) -> KemAlgorithm;
+    fn public_key_bytes(&self) -> usize;
+    fn private_key_bytes(&self) -> usize;
+    fn ciphertext_bytes(&self) -> usize;
+    fn shared_secret_bytes(&self) -> usize;
+    /// A fresh `(public key, private key)` pair from the system RNG.
+    fn generate_keypair(&self) -> (Vec<u8>, Zeroizing<Vec<u8>>);
+    /// A fresh shared secret for `public_key` and the ciphertext that
+    /// carries it, as `(ciphertext, shared secret)`.
+    fn encapsulate(&self, public_key: &[u8]) -> Result<(Vec<u8>, Zeroizing<Vec<u8>>), String>;
+    /// The shared secret in `ciphertext`. ML-KEM rejects implicitly: a
+    /// tampered ciphertext gives an unrelated secret, not an error.
+    fn decapsulate(&self, private_key: &[u8], ciphertext: &[u8]) -> Result<Zeroizing<Vec<u8>>, String>;
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct PQCCiphertext {
-    pub algorithm: PQCAlgorithm,
-    pub ciphertext: Vec<u8>,
-    pub encapsulated_key: Vec<u8>,
-    pub public_key_id: String,
-    pub created_at: u64,
+/// Stack for Classic McEliece, whose PQClean code keeps its megabyte keys
+/// and working arrays on the stack; more than a tokio worker or test
+/// thread has.
+const MCELIECE_STACK_BYTES: usize = 32 << 20;
+
+/// Runs `operation` on a thread with `stack_bytes` of stack, or inline when
+/// that is 0.
+fn with_stack<T: Send>(stack_bytes: usize, operation: impl FnOnce() -> T + Send) -> T {
+    if stack_bytes == 0 {
+        return operation();
+    }
+    std::thread::scope(|scope| {
+        std::thread::Builder::new()
+            .stack_size(stack_bytes)
+            .spawn_scoped(scope, operation)
+            .expect("spawn PQC thread")
+            .join()
+            .expect("PQC thread panicked")
+    })
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct PQCSharedSecret {
-    pub algorithm: PQCAlgorithm,
-    pub shared_secret: Vec<u8>,
-    pub session_id: String,
-    pub created_at: u64,
+fn check_length(name: &str, what: &str, bytes: &[u8], expected: usize) -> Result<(), String> {
+    if bytes.len() != expected {
+        return Err(format!("A {} {} is {} bytes, not {}", name, what, expected, bytes.len()));
+    }
+    Ok(())
 }
 
-#[derive(Debug)]
-pub struct PQCManager {
-    public_keys: HashMap<String, PQCPublicKey>,
-    private_keys: HashMap<String, PQCPrivateKey>,
-    signatures: HashMap<String, PQCSignature>,
-    ciphertexts: HashMap<String, PQCCiphertext>,
-    shared_secrets: HashMap<String, PQCSharedSecret>,
+macro_rules! signature_algorithms {
+    ($($scheme:ident => $module:ident, $name:literal;)*) => {
+        $(
+            #[doc = concat!("The ", $name, " parameter set.")]
+            #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+            pub struct $scheme;
+
+            impl Verifier for $scheme {
+                fn algorithm(&self) -> SignatureAlgorithm {
+                    SignatureAlgorithm::$scheme
+                }
+
+                fn public_key_bytes(&self) -> usize {
+                    $module::public_key_bytes()
+                }
+
+                fn signature_bytes(&self) -> usize {
+                    $module::signature_bytes()
+                }
+
+                fn verify(&self, public_key: &[u8], message: &[u8], signature: &[u8]) -> bool {
+                    let (Ok(public_key), Ok(signature)) = (
+                        $module::PublicKey::from_bytes(public_key),
+                        $module::DetachedSignature::from_bytes(signature),
+                    ) else {
+                        return false;
+                    };
+                    $module::verify_detached_signature(&signature, message, &public_key).is_ok()
+                }
+            }
+
+            impl Signer for $scheme {
+                fn private_key_bytes(&self) -> usize {
+                    $module::secret_key_bytes()
+                }
+
+                fn generate_keypair(&self) -> (Vec<u8>, Zeroizing<Vec<u8>>) {
+                    let (public_key, private_key) = $module::keypair();
+                    (public_key.as_bytes().to_vec(), Zeroizing::new(private_key.as_bytes().to_vec()))
+                }
+
+                fn sign(&self, private_key: &[u8], message: &[u8]) -> Result<Vec<u8>, String> {
+                    check_length($name, "private key", private_key, $module::secret_key_bytes())?;
+                    let private_key = $module::SecretKey::from_bytes(private_key).map_err(|e| e.to_string())?;
+                    Ok($module::detached_sign(message, &private_key).as_bytes().to_vec())
+                }
+            }
+        )*
+
+        /// A signature parameter set.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Encode, Decode, JsonSchema)]
+        pub enum SignatureAlgorithm {
+            $(
+                #[serde(rename = $name)]
+                $scheme,
+            )*
+        }
+
+        impl SignatureAlgorithm {
+            pub const ALL: &'static [SignatureAlgorithm] = &[$(SignatureAlgorithm::$scheme),*];
+
+            pub fn name(self) -> &'static str {
+                match self {
+                    $(SignatureAlgorithm::$scheme => $name,)*
+                }
+            }
+
+            pub fn scheme(self) -> &'static dyn Signer {
+                match self {
+                    $(SignatureAlgorithm::$scheme => &$scheme,)*
+                }
+            }
+        }
+    };
 }
 
-impl PQCManager {
-    pub fn new() -> Self {
-        PQCManager {
-            public_keys: HashMap::new(),
-            private_keys: HashMap::new(),
-            signatures: HashMap::new(),
-            ciphertexts: HashMap::new(),
-            shared_secrets: HashMap::new(),
+macro_rules! kem_algorithms {
+    ($($scheme:ident => $module:ident, $name:literal, $stack:expr;)*) => {
+        $(
+            #[doc = concat!("The ", $name, " parameter set.")]
+            #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+            pub struct $scheme;
+
+            impl Kem for $scheme {
+                fn algorithm(&self) -> KemAlgorithm {
+                    KemAlgorithm::$scheme
+                }
+
+                fn public_key_bytes(&self) -> usize {
+                    $module::public_key_bytes()
+                }
+
+                fn private_key_bytes(&self) -> usize {
+                    $module::secret_key_bytes()
+                }
+
+                fn ciphertext_bytes(&self) -> usize {
+                    $module::ciphertext_bytes()
+                }
+
+                fn shared_secret_bytes(&self) -> usize {
+                    $module::shared_secret_bytes()
+                }
+
+                fn generate_keypair(&self) -> (Vec<u8>, Zeroizing<Vec<u8>>) {
+                    with_stack($stack, || {
+                        let (public_key, private_key) = $module::keypair();
+                        (public_key.as_bytes().to_vec(), Zeroizing::new(private_key.as_bytes().to_vec()))
+                    })
+                }
+
+                fn encapsulate(&self, public_key: &[u8]) -> Result<(Vec<u8>, Zeroizing<Vec<u8>>), String> {
+                    check_length($name, "public key", public_key, $module::public_key_bytes())?;
+                    with_stack($stack, || {
+                        let public_key = $module::PublicKey::from_bytes(public_key).map_err(|e| e.to_string())?;
+                        let (shared_secret, ciphertext) = $module::encapsulate(&public_key);
+                        Ok((ciphertext.as_bytes().to_vec(), Zeroizing::new(shared_secret.as_bytes().to_vec())))
+                    })
+                }
+
+                fn decapsulate(&self, private_key: &[u8], ciphertext: &[u8]) -> Result<Zeroizing<Vec<u8>>, String> {
+                    check_length($name, "private key", private_key, $module::secret_key_bytes())?;
+                    check_length($name, "ciphertext", ciphertext, $module::ciphertext_bytes())?;
+                    with_stack($stack, || {
+                        let private_key = $module::SecretKey::from_bytes(private_key).map_err(|e| e.to_string())?;
+                        let ciphertext = $module::Ciphertext::from_bytes(ciphertext).map_err(|e| e.to_string())?;
+                        Ok(Zeroizing::new($module::decapsulate(&ciphertext, &private_key).as_bytes().to_vec()))
+                    })
+                }
+            }
+        )*
+
+        /// A key encapsulation parameter set.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Encode, Decode, JsonSchema)]
+        pub enum KemAlgorithm {
+            $(
+                #[serde(rename = $name)]
+                $scheme,
+            )*
         }
-    }
 
-    pub fn generate_keypair(&self, algorithm: PQCAlgorithm) -> Result<(PQCPublicKey, PQCPrivateKey), String> {
-        let timestamp = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
+        impl KemAlgorithm {
+            pub const ALL: &'static [KemAlgorithm] = &[$(KemAlgorithm::$scheme),*];
+
+            pub fn name(self) -> &'static str {
+                match self {
+                    $(KemAlgorithm::$scheme => $name,)*
+                }
+            }
 
-        match algorithm {
-            PQCAlgorithm::Kyber => self.generate_kyber_keypair(timestamp),
-            PQCAlgorithm::Dilithium => self.generate_dilithium_keypair(timestamp),
-            PQCAlgorithm::Falcon => self.generate_falcon_keypair(timestamp),
-            PQCAlgorithm::Sphincs => self.generate_sphincs_keypair(timestamp),
-            PQCAlgorithm::ClassicMcEliece => self.generate_mceliece_keypair(timestamp),
+            pub fn scheme(self) -> &'static dyn Kem {
+                match self {
+                    $(KemAlgorithm::$scheme => &$scheme,)*
+                }
+            }
         }
+    };
+}
+
+signature_algorithms! {
+    MlDsa44 => mldsa44, "ML-DSA-44";
+    MlDsa65 => mldsa65, "ML-DSA-65";
+    MlDsa87 => mldsa87, "ML-DSA-87";
+    Falcon512 => falcon512, "Falcon-512";
+    Falcon1024 => falcon1024, "Falcon-1024";
+    SlhDsaSha2_128s => sphincssha2128ssimple, "SLH-DSA-SHA2-128s";
+    SlhDsaSha2_128f => sphincssha2128fsimple, "SLH-DSA-SHA2-128f";
+    SlhDsaSha2_192s => sphincssha2192ssimple, "SLH-DSA-SHA2-192s";
+    SlhDsaSha2_192f => sphincssha2192fsimple, "SLH-DSA-SHA2-192f";
+    SlhDsaSha2_256s => sphincssha2256ssimple, "SLH-DSA-SHA2-256s";
+    SlhDsaSha2_256f => sphincssha2256fsimple, "SLH-DSA-SHA2-256f";
+    SlhDsaShake128s => sphincsshake128ssimple, "SLH-DSA-SHAKE-128s";
+    SlhDsaShake128f => sphincsshake128fsimple, "SLH-DSA-SHAKE-128f";
+    SlhDsaShake192s => sphincsshake192ssimple, "SLH-DSA-SHAKE-192s";
+    SlhDsaShake192f => sphincsshake192fsimple, "SLH-DSA-SHAKE-192f";
+    SlhDsaShake256s => sphincsshake256ssimple, "SLH-DSA-SHAKE-256s";
+    SlhDsaShake256f => sphincsshake256fsimple, "SLH-DSA-SHAKE-256f";
+}
+
+kem_algorithms! {
+    MlKem512 => mlkem512, "ML-KEM-512", 0;
+    MlKem768 => mlkem768, "ML-KEM-768", 0;
+    MlKem1024 => mlkem1024, "ML-KEM-1024", 0;
+    McEliece348864 => mceliece348864, "Classic-McEliece-348864", MCELIECE_STACK_BYTES;
+    McEliece348864f => mceliece348864f, "Classic-McEliece-348864f", MCELIECE_STACK_BYTES;
+    McEliece460896 => mceliece460896, "Classic-McEliece-460896", MCELIECE_STACK_BYTES;
+    McEliece460896f => mceliece460896f, "Classic-McEliece-460896f", MCELIECE_STACK_BYTES;
+    McEliece6688128 => mceliece6688128, "Classic-McEliece-6688128", MCELIECE_STACK_BYTES;
+    McEliece6688128f => mceliece6688128f, "Classic-McEliece-6688128f", MCELIECE_STACK_BYTES;
+    McEliece6960119 => mceliece6960119, "Classic-McEliece-6960119", MCELIECE_STACK_BYTES;
+    McEliece6960119f => mceliece6960119f, "Classic-McEliece-6960119f", MCELIECE_STACK_BYTES;
+    McEliece8192128 => mceliece8192128, "Classic-McEliece-8192128", MCELIECE_STACK_BYTES;
+    McEliece8192128f => mceliece8192128f, "Classic-McEliece-8192128f", MCELIECE_STACK_BYTES;
+}
+
+impl Verifier for SignatureAlgorithm {
+    fn algorithm(&self) -> SignatureAlgorithm {
+        *self
     }
 
-    pub fn sign_message(&self, private_key_id: &str, message: &[u8]) -> Result<PQCSignature, String> {
-        let private_key = self.private_keys.get(private_key_id)
-            .ok_or_else(|| format!("Private key {} not found", private_key_id))?;
+    fn public_key_bytes(&self) -> usize {
+        self.scheme().public_key_bytes()
+    }
 
-        let message_hash = self.hash_message(message);
+    fn signature_bytes(&self) -> usize {
+        self.scheme().signature_bytes()
+    }
 
-        match private_key.algorithm {
-            PQCAlgorithm::Dilithium => self.sign_dilithium(&private_key, &message_hash),
-            PQCAlgorithm::Falcon => self.sign_falcon(&private_key, &message_hash),
-            PQCAlgorithm::Sphincs => self.sign_sphincs(&private_key, &message_hash),
-            _ => Err(format!("Signing not supported for algorithm {:?}", private_key.algorithm)),
-        }
+    fn verify(&self, public_key: &[u8], message: &[u8], signature: &[u8]) -> bool {
+        self.scheme().verify(public_key, message, signature)
     }
+}
 
-    pub fn verify_signature(&self, signature_id: &str, message: &[u8]) -> Result<bool, String> {
-        let signature = self.signatures.get(signature_id)
-            .ok_or_else(|| format!("Signature {} not found", signature_id))?;
+impl Signer for SignatureAlgorithm {
+    fn private_key_bytes(&self) -> usize {
+        self.scheme().private_key_bytes()
+    }
 
-        let message_hash = self.hash_message(message);
+    fn generate_keypair(&self) -> (Vec<u8>, Zeroizing<Vec<u8>>) {
+        self.scheme().generate_keypair()
+    }
 
-        if signature.message_hash != message_hash {
-            return Ok(false);
-        }
+    fn sign(&self, private_key: &[u8], message: &[u8]) -> Result<Vec<u8>, String> {
+        self.scheme().sign(private_key, message)
+    }
+}
 
-        let public_key = self.public_keys.get(&signature.public_key_id)
-            .ok_or_else(|| format!("Public key {} not found", signature.public_key_id))?;
+impl Kem for KemAlgorithm {
+    fn algorithm(&self) -> KemAlgorithm {
+        *self
+    }
 
-        match signature.algorithm {
-            PQCAlgorithm::Dilithium => self.verify_dilithium(&public_key, &signature, &message_hash),
-            PQCAlgorithm::Falcon => self.verify_falcon(&public_key, &signature, &message_hash),
-            PQCAlgorithm::Sphincs => self.verify_sphincs(&public_key, &signature, &message_hash),
-            _ => Err(format!("Verification not supported for algorithm {:?}", signature.algorithm)),
-        }
+    fn public_key_bytes(&self) -> usize {
+        self.scheme().public_key_bytes()
     }
 
-    pub fn encapsulate_key(&self, public_key_id: &str) -> Result<(PQCCiphertext, PQCSharedSecret), String> {
-        let public_key = self.public_keys.get(public_key_id)
-            .ok_or_else(|| format!("Public key {} not found", public_key_id))?;
+    fn private_key_bytes(&self) -> usize {
+        self.scheme().private_key_bytes()
+    }
 
-        match public_key.algorithm {
-            PQCAlgorithm::Kyber => self.encapsulate_kyber(&public_key),
-            PQCAlgorithm::ClassicMcEliece => self.encapsulate_mceliece(&public_key),
-            _ => Err(format!("Encapsulation not supported for algorithm {:?}", public_key.algorithm)),
-        }
+    fn ciphertext_bytes(&self) -> usize {
+        self.scheme().ciphertext_bytes()
+    }
+
+    fn shared_secret_bytes(&self) -> usize {
+        self.scheme().shared_secret_bytes()
     }
 
-    pub fn decapsulate_key(&self, private_key_id: &str, ciphertext_id: &str) -> Result<PQCSharedSecret, String> {
-        let private_key = self.private_keys.get(private_key_id)
-            .ok_or_else(|| format!("Private key {} not found", private_key_id))?;
+    fn generate_keypair(&self) -> (Vec<u8>, Zeroizing<Vec<u8>>) {
+        self.scheme().generate_keypair()
+    }
 
-        let ciphertext = self.ciphertexts.get(ciphertext_id)
-            .ok_or_else(|| format!("Ciphertext {} not found", ciphertext_id))?;
+    fn encapsulate(&self, public_key: &[u8]) -> Result<(Vec<u8>, Zeroizing<Vec<u8>>), String> {
+        self.scheme().encapsulate(public_key)
+    }
 
-        if private_key.public_key_id != ciphertext.public_key_id {
-            return Err("Key mismatch".to_string());
-        }
+    fn decapsulate(&self, private_key: &[u8], ciphertext: &[u8]) -> Result<Zeroizing<Vec<u8>>, String> {
+        self.scheme().decapsulate(private_key, ciphertext)
+    }
+}
 
-        match private_key.algorithm {
-            PQCAlgorithm::Kyber => self.decapsulate_kyber(&private_key, &ciphertext),
-            PQCAlgorithm::ClassicMcEliece => self.decapsulate_mceliece(&private_key, &ciphertext),
-            _ => Err(format!("Decapsulation not supported for algorithm {:?}", private_key.algorithm)),
-        }
+impl fmt::Display for SignatureAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
     }
+}
 
-    // Implementation methods for each algorithm (simplified for demo)
-    fn generate_kyber_keypair(&self, timestamp: u64) -> Result<(PQCPublicKey, PQCPrivateKey), String> {
-        // Use real pqcrypto crate for ML-KEM (Kyber)
-        let key_id = format!("mlkem_{}", timestamp);
-
-        // Generate ML-KEM-1024 keypair using pqcrypto
-        let (pk, sk) = match mlkem1024::keypair() {
-            Ok(keypair) => keypair,
-            Err(e) => return Err(format!("ML-KEM key generation failed: {:?}", e)),
-        };
-
-        let public_key = PQCPublicKey {
-            algorithm: PQCAlgorithm::Kyber,
-            key_data: pk.as_bytes().to_vec(),
-            key_id: key_id.clone(),
-            created_at: timestamp,
-        };
-
-        let private_key = PQCPrivateKey {
-            algorithm: PQCAlgorithm::Kyber,
-            key_data: sk.as_bytes().to_vec(),
-            public_key_id: key_id.clone(),
-            created_at: timestamp,
-        };
-
-        Ok((public_key, private_key))
-    }
-
-    fn generate_dilithium_keypair(&self, timestamp: u64) -> Result<(PQCPublicKey, PQCPrivateKey), String> {
-        // Use real pqcrypto crate for ML-DSA (Dilithium)
-        let key_id = format!("mldsa_{}", timestamp);
-
-        // Generate ML-DSA-87 keypair using pqcrypto (largest variant)
-        let (pk, sk) = match mldsa87::keypair() {
-            Ok(keypair) => keypair,
-            Err(e) => return Err(format!("ML-DSA key generation failed: {:?}", e)),
-        };
-
-        let public_key = PQCPublicKey {
-            algorithm: PQCAlgorithm::Dilithium,
-            key_data: pk.as_bytes().to_vec(),
-            key_id: key_id.clone(),
-            created_at: timestamp,
-        };
-
-        let private_key = PQCPrivateKey {
-            algorithm: PQCAlgorithm::Dilithium,
-            key_data: sk.as_bytes().to_vec(),
-            public_key_id: key_id.clone(),
-            created_at: timestamp,
-        };
-
-        Ok((public_key, private_key))
-    }
-
-    fn generate_falcon_keypair(&self, timestamp: u64) -> Result<(PQCPublicKey, PQCPrivateKey), String> {
-        // Use real pqcrypto crate for Falcon-512
-        let key_id = format!("falcon_{}", timestamp);
-
-        // Generate Falcon-512 keypair using pqcrypto
-        let (pk, sk) = match falcon512::keypair() {
-            Ok(keypair) => keypair,
-            Err(e) => return Err(format!("Falcon key generation failed: {:?}", e)),
-        };
-
-        let public_key = PQCPublicKey {
-            algorithm: PQCAlgorithm::Falcon,
-            key_data: pk.as_bytes().to_vec(),
-            key_id: key_id.clone(),
-            created_at: timestamp,
-        };
-
-        let private_key = PQCPrivateKey {
-            algorithm: PQCAlgorithm::Falcon,
-            key_data: sk.as_bytes().to_vec(),
-            public_key_id: key_id.clone(),
-            created_at: timestamp,
-        };
-
-        Ok((public_key, private_key))
-    }
-
-    fn generate_sphincs_keypair(&self, timestamp: u64) -> Result<(PQCPublicKey, PQCPrivateKey), String> {
-        // Use real pqcrypto crate for SPHINCS+
-        let key_id = format!("sphincs_{}", timestamp);
-
-        // Generate SPHINCS+-SHA256-128s keypair using pqcrypto
-        let (pk, sk) = match sphincsplus_sha256_128s_robust::keypair() {
-            Ok(keypair) => keypair,
-            Err(e) => return Err(format!("SPHINCS+ key generation failed: {:?}", e)),
-        };
-
-        let public_key = PQCPublicKey {
-            algorithm: PQCAlgorithm::Sphincs,
-            key_data: pk.as_bytes().to_vec(),
-            key_id: key_id.clone(),
-            created_at: timestamp,
-        };
-
-        let private_key = PQCPrivateKey {
-            algorithm: PQCAlgorithm::Sphincs,
-            key_data: sk.as_bytes().to_vec(),
-            public_key_id: key_id.clone(),
-            created_at: timestamp,
-        };
-
-        Ok((public_key, private_key))
-    }
-
-    fn generate_mceliece_keypair(&self, timestamp: u64) -> Result<(PQCPublicKey, PQCPrivateKey), String> {
-        // Use real pqcrypto crate for Classic-McEliece-348864
-        let key_id = format!("mceliece_{}", timestamp);
-
-        // Generate Classic-McEliece-348864 keypair using pqcrypto
-        let (pk, sk) = match classicmceliece348864::keypair() {
-            Ok(keypair) => keypair,
-            Err(e) => return Err(format!("Classic-McEliece key generation failed: {:?}", e)),
-        };
-
-        let public_key = PQCPublicKey {
-            algorithm: PQCAlgorithm::ClassicMcEliece,
-            key_data: pk.as_bytes().to_vec(),
-            key_id: key_id.clone(),
-            created_at: timestamp,
-        };
-
-        let private_key = PQCPrivateKey {
-            algorithm: PQCAlgorithm::ClassicMcEliece,
-            key_data: sk.as_bytes().to_vec(),
-            public_key_id: key_id.clone(),
-            created_at: timestamp,
-        };
-
-        Ok((public_key, private_key))
-    }
-
-    fn sign_dilithium(&self, private_key: &PQCPrivateKey, message_hash: &[u8]) -> Result<PQCSignature, String> {
-        let signature_id = format!("sig_{}", std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs());
-
-        // Create ML-DSA-87 secret key from stored data
-        let sk = match mldsa87::SecretKey::from_bytes(&private_key.key_data) {
-            Ok(key) => key,
-            Err(e) => return Err(format!("Failed to create secret key: {:?}", e)),
-        };
-
-        // Sign the message
-        let signed_message = match mldsa87::sign(&message_hash, &sk) {
-            Ok(sig) => sig,
-            Err(e) => return Err(format!("Signing failed: {:?}", e)),
-        };
-
-        let signature = PQCSignature {
-            algorithm: PQCAlgorithm::Dilithium,
-            signature_data: signed_message.as_bytes().to_vec(),
-            message_hash: message_hash.to_vec(),
-            public_key_id: private_key.public_key_id.clone(),
-            created_at: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
-        };
-
-        Ok(signature)
-    }
-
-    fn verify_dilithium(&self, public_key: &PQCPublicKey, signature: &PQCSignature, message_hash: &[u8]) -> Result<bool, String> {
-        // Create ML-DSA-87 public key from stored data
-        let pk = match mldsa87::PublicKey::from_bytes(&public_key.key_data) {
-            Ok(key) => key,
-            Err(e) => return Err(format!("Failed to create public key: {:?}", e)),
-        };
-
-        // Create signed message from signature data
-        let signed_message = match mldsa87::SignedMessage::from_bytes(&signature.signature_data) {
-            Ok(sig) => sig,
-            Err(e) => return Err(format!("Failed to create signed message: {:?}", e)),
-        };
-
-        // Verify the signature
-        match mldsa87::verify(&signed_message, &message_hash, &pk) {
-            Ok(_) => Ok(true),
-            Err(_) => Ok(false),
-        }
+impl fmt::Display for KemAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
     }
+}
+
+/// Parses a standard name in any case, as in config files and CLI flags.
+impl FromStr for SignatureAlgorithm {
+    type Err = String;
 
-    fn sign_falcon(&self, private_key: &PQCPrivateKey, message_hash: &[u8]) -> Result<PQCSignature, String> {
-        let signature_id = format!("sig_{}", std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs());
-
-        // Create Falcon-512 secret key from stored data
-        let sk = match falcon512::SecretKey::from_bytes(&private_key.key_data) {
-            Ok(key) => key,
-            Err(e) => return Err(format!("Failed to create secret key: {:?}", e)),
-        };
-
-        // Sign the message
-        let signed_message = match falcon512::sign(&message_hash, &sk) {
-            Ok(sig) => sig,
-            Err(e) => return Err(format!("Signing failed: {:?}", e)),
-        };
-
-        let signature = PQCSignature {
-            algorithm: PQCAlgorithm::Falcon,
-            signature_data: signed_message.as_bytes().to_vec(),
-            message_hash: message_hash.to_vec(),
-            public_key_id: private_key.public_key_id.clone(),
-            created_at: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
-        };
-
-        Ok(signature)
-    }
-
-    fn verify_falcon(&self, public_key: &PQCPublicKey, signature: &PQCSignature, message_hash: &[u8]) -> Result<bool, String> {
-        // Create Falcon-512 public key from stored data
-        let pk = match falcon512::PublicKey::from_bytes(&public_key.key_data) {
-            Ok(key) => key,
-            Err(e) => return Err(format!("Failed to create public key: {:?}", e)),
-        };
-
-        // Create signed message from signature data
-        let signed_message = match falcon512::SignedMessage::from_bytes(&signature.signature_data) {
-            Ok(sig) => sig,
-            Err(e) => return Err(format!("Failed to create signed message: {:?}", e)),
-        };
-
-        // Verify the signature
-        match falcon512::verify(&signed_message, &message_hash, &pk) {
-            Ok(_) => Ok(true),
-            Err(_) => Ok(false),
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        SignatureAlgorithm::ALL
+            .iter()
+            .copied()
+            .find(|algorithm| algorithm.name().eq_ignore_ascii_case(name.trim()))
+            .ok_or_else(|| format!("Unknown signature algorithm: {}", name))
+    }
+}
+
+impl FromStr for KemAlgorithm {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        KemAlgorithm::ALL
+            .iter()
+            .copied()
+            .find(|algorithm| algorithm.name().eq_ignore_ascii_case(name.trim()))
+            .ok_or_else(|| format!("Unknown KEM algorithm: {}", name))
+    }
+}
+
+/// Either kind of parameter set, for records that may name both.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum PQCAlgorithm {
+    Signature(SignatureAlgorithm),
+    Kem(KemAlgorithm),
+}
+
+impl From<SignatureAlgorithm> for PQCAlgorithm {
+    fn from(algorithm: SignatureAlgorithm) -> Self {
+        PQCAlgorithm::Signature(algorithm)
+    }
+}
+
+impl From<KemAlgorithm> for PQCAlgorithm {
+    fn from(algorithm: KemAlgorithm) -> Self {
+        PQCAlgorithm::Kem(algorithm)
+    }
+}
+
+/// A detached signature with the parameter set and public key that verify
+/// it, so it can be checked wherever it is sent.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Encode, Decode, JsonSchema)]
+pub struct PQCSignature {
+    pub algorithm: SignatureAlgorithm,
+    /// Hex public key.
+    pub public_key: String,
+    /// Hex detached signature.
+    pub signature: String,
+}
+
+impl PQCSignature {
+    pub fn sign(algorithm: SignatureAlgorithm, public_key: &[u8], private_key: &[u8], message: &[u8]) -> Result<Self, String> {
+        check_length(algorithm.name(), "public key", public_key, algorithm.public_key_bytes())?;
+        Ok(PQCSignature {
+            algorithm,
+            public_key: hex::encode(public_key),
+            signature: hex::encode(algorithm.sign(private_key, message)?),
+        })
+    }
+
+    pub fn verify(&self, message: &[u8]) -> bool {
+        match (hex::decode(&self.public_key), hex::decode(&self.signature)) {
+            (Ok(public_key), Ok(signature)) => self.algorithm.verify(&public_key, message, &signature),
+            _ => false,
         }
     }
+}
 
-    fn sign_sphincs(&self, private_key: &PQCPrivateKey, message_hash: &[u8]) -> Result<PQCSignature, String> {
-        let signature_id = format!("sig_{}", std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs());
-
-        let signature = PQCSignature {
-            algorithm: PQCAlgorithm::Sphincs,
-            signature_data: vec![0; 29792], // SPHINCS+-256s signature size
-            message_hash: message_hash.to_vec(),
-            public_key_id: private_key.public_key_id.clone(),
-            created_at: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
-        };
-
-        Ok(signature)
-    }
-
-    fn verify_sphincs(&self, public_key: &PQCPublicKey, signature: &PQCSignature, message_hash: &[u8]) -> Result<bool, String> {
-        Ok(signature.message_hash == message_hash && !signature.signature_data.is_empty())
-    }
-
-    fn encapsulate_kyber(&self, public_key: &PQCPublicKey) -> Result<(PQCCiphertext, PQCSharedSecret), String> {
-        // Create ML-KEM-1024 public key from stored data
-        let pk = match mlkem1024::PublicKey::from_bytes(&public_key.key_data) {
-            Ok(key) => key,
-            Err(e) => return Err(format!("Failed to create public key: {:?}", e)),
-        };
-
-        // Perform key encapsulation
-        let (shared_secret_bytes, ciphertext_bytes) = match mlkem1024::encapsulate(&pk) {
-            Ok((ss, ct)) => (ss.as_bytes().to_vec(), ct.as_bytes().to_vec()),
-            Err(e) => return Err(format!("Encapsulation failed: {:?}", e)),
-        };
-
-        let ciphertext_id = format!("ct_{}", std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs());
-
-        let ciphertext = PQCCiphertext {
-            algorithm: PQCAlgorithm::Kyber,
-            ciphertext: ciphertext_bytes,
-            encapsulated_key: shared_secret_bytes.clone(),
-            public_key_id: public_key.key_id.clone(),
-            created_at: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
-        };
-
-        let shared_secret = PQCSharedSecret {
-            algorithm: PQCAlgorithm::Kyber,
-            shared_secret: shared_secret_bytes,
-            session_id: ciphertext_id.clone(),
-            created_at: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
-        };
-
-        Ok((ciphertext, shared_secret))
-    }
-
-    fn decapsulate_kyber(&self, private_key: &PQCPrivateKey, ciphertext: &PQCCiphertext) -> Result<PQCSharedSecret, String> {
-        // Create ML-KEM-1024 secret key from stored data
-        let sk = match mlkem1024::SecretKey::from_bytes(&private_key.key_data) {
-            Ok(key) => key,
-            Err(e) => return Err(format!("Failed to create secret key: {:?}", e)),
-        };
-
-        // Create ciphertext from stored data
-        let ct = match mlkem1024::Ciphertext::from_bytes(&ciphertext.ciphertext) {
-            Ok(ct) => ct,
-            Err(e) => return Err(format!("Failed to create ciphertext: {:?}", e)),
-        };
-
-        // Perform decapsulation
-        let shared_secret_bytes = match mlkem1024::decapsulate(&ct, &sk) {
-            Ok(ss) => ss.as_bytes().to_vec(),
-            Err(e) => return Err(format!("Decapsulation failed: {:?}", e)),
-        };
-
-        let shared_secret = PQCSharedSecret {
-            algorithm: PQCAlgorithm::Kyber,
-            shared_secret: shared_secret_bytes,
-            session_id: format!("ss_{}", std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs()),
-            created_at: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
-        };
-
-        Ok(shared_secret)
-    }
-
-    fn encapsulate_mceliece(&self, public_key: &PQCPublicKey) -> Result<(PQCCiphertext, PQCSharedSecret), String> {
-        // Create Classic-McEliece-348864 public key from stored data
-        let pk = match classicmceliece348864::PublicKey::from_bytes(&public_key.key_data) {
-            Ok(key) => key,
-            Err(e) => return Err(format!("Failed to create public key: {:?}", e)),
-        };
-
-        // Perform key encapsulation
-        let (shared_secret_bytes, ciphertext_bytes) = match classicmceliece348864::encapsulate(&pk) {
-            Ok((ss, ct)) => (ss.as_bytes().to_vec(), ct.as_bytes().to_vec()),
-            Err(e) => return Err(format!("Encapsulation failed: {:?}", e)),
-        };
-
-        let ciphertext_id = format!("ct_{}", std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs());
-
-        let ciphertext = PQCCiphertext {
-            algorithm: PQCAlgorithm::ClassicMcEliece,
-            ciphertext: ciphertext_bytes,
-            encapsulated_key: shared_secret_bytes.clone(),
-            public_key_id: public_key.key_id.clone(),
-            created_at: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
-        };
-
-        let shared_secret = PQCSharedSecret {
-            algorithm: PQCAlgorithm::ClassicMcEliece,
-            shared_secret: shared_secret_bytes,
-            session_id: ciphertext_id.clone(),
-            created_at: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
-        };
-
-        Ok((ciphertext, shared_secret))
-    }
-
-    fn decapsulate_mceliece(&self, private_key: &PQCPrivateKey, ciphertext: &PQCCiphertext) -> Result<PQCSharedSecret, String> {
-        // Create Classic-McEliece-348864 secret key from stored data
-        let sk = match classicmceliece348864::SecretKey::from_bytes(&private_key.key_data) {
-            Ok(key) => key,
-            Err(e) => return Err(format!("Failed to create secret key: {:?}", e)),
-        };
-
-        // Create ciphertext from stored data
-        let ct = match classicmceliece348864::Ciphertext::from_bytes(&ciphertext.ciphertext) {
-            Ok(ct) => ct,
-            Err(e) => return Err(format!("Failed to create ciphertext: {:?}", e)),
-        };
-
-        // Perform decapsulation
-        let shared_secret_bytes = match classicmceliece348864::decapsulate(&ct, &sk) {
-            Ok(ss) => ss.as_bytes().to_vec(),
-            Err(e) => return Err(format!("Decapsulation failed: {:?}", e)),
-        };
-
-        let shared_secret = PQCSharedSecret {
-            algorithm: PQCAlgorithm::ClassicMcEliece,
-            shared_secret: shared_secret_bytes,
-            session_id: format!("ss_{}", std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs()),
-            created_at: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
-        };
-
-        Ok(shared_secret)
-    }
-
-    fn hash_message(&self, message: &[u8]) -> Vec<u8> {
-        use sha3::{Sha3_256, Digest};
-        let mut hasher = Sha3_256::new();
-        hasher.update(message);
-        hasher.finalize().to_vec()
-    }
-
-    pub fn get_supported_algorithms(&self) -> Vec<PQCAlgorithm> {
-        vec![
-            PQCAlgorithm::Kyber,
-            PQCAlgorithm::Dilithium,
-            PQCAlgorithm::Falcon,
-            PQCAlgorithm::Sphincs,
-            PQCAlgorithm::ClassicMcEliece,
-        ]
-    }
-
-    pub fn get_algorithm_info(&self, algorithm: &PQCAlgorithm) -> HashMap<String, String> {
-        let mut info = HashMap::new();
-
-        match algorithm {
-            PQCAlgorithm::Kyber => {
-                info.insert("name".to_string(), "CRYSTALS-Kyber".to_string());
-                info.insert("type".to_string(), "Key Encapsulation Mechanism".to_string());
-                info.insert("security_level".to_string(), "NIST Level 5".to_string());
-                info.insert("public_key_size".to_string(), "1184 bytes".to_string());
-                info.insert("private_key_size".to_string(), "3168 bytes".to_string());
-                info.insert("ciphertext_size".to_string(), "1088 bytes".to_string());
-                info.insert("shared_secret_size".to_string(), "32 bytes".to_string());
-            },
-            PQCAlgorithm::Dilithium => {
-                info.insert("name".to_string(), "CRYSTALS-Dilithium".to_string());
-                info.insert("type".to_string(), "Digital Signature".to_string());
-                info.insert("security_level".to_string(), "NIST Level 5".to_string());
-                info.insert("public_key_size".to_string(), "1312 bytes".to_string());
-                info.insert("private_key_size".to_string(), "2544 bytes".to_string());
-                info.insert("signature_size".to_string(), "2420 bytes".to_string());
-            },
-            PQCAlgorithm::Falcon => {
-                info.insert("name".to_string(), "Falcon".to_string());
-                info.insert("type".to_string(), "Digital Signature".to_string());
-                info.insert("security_level".to_string(), "NIST Level 5".to_string());
-                info.insert("public_key_size".to_string(), "897 bytes".to_string());
-                info.insert("private_key_size".to_string(), "1281 bytes".to_string());
-                info.insert("signature_size".to_string(), "666 bytes".to_string());
-            },
-            PQCAlgorithm::Sphincs => {
-                info.insert("name".to_string(), "SPHINCS+".to_string());
-                info.insert("type".to_string(), "Digital Signature".to_string());
-                info.insert("security_level".to_string(), "NIST Level 5".to_string());
-                info.insert("public_key_size".to_string(), "64 bytes".to_string());
-                info.insert("private_key_size".to_string(), "128 bytes".to_string());
-                info.insert("signature_size".to_string(), "29792 bytes".to_string());
-            },
-            PQCAlgorithm::ClassicMcEliece => {
-                info.insert("name".to_string(), "Classic-McEliece".to_string());
-                info.insert("type".to_string(), "Key Encapsulation Mechanism".to_string());
-                info.insert("security_level".to_string(), "NIST Level 5".to_string());
-                info.insert("public_key_size".to_string(), "1357824 bytes".to_string());
-                info.insert("private_key_size".to_string(), "1416 bytes".to_string());
-                info.insert("ciphertext_size".to_string(), "128 bytes".to_string());
-                info.insert("shared_secret_size".to_string(), "32 bytes".to_string());
-            },
+/// One signature of a batch.
+#[derive(Debug, Clone, Copy)]
+pub struct VerifyRequest<'a> {
+    pub algorithm: SignatureAlgorithm,
+    pub public_key: &'a [u8],
+    pub message: &'a [u8],
+    pub signature: &'a [u8],
+}
+
+impl VerifyRequest<'_> {
+    pub fn verify(&self) -> bool {
+        self.algorithm.verify(self.public_key, self.message, self.signature)
+    }
+}
+
+/// Verifies `requests` on the rayon pool; entry `i` of the result is
+/// whether request `i` is valid.
+pub fn verify_batch(requests: &[VerifyRequest]) -> Vec<bool> {
+    requests.par_iter().map(VerifyRequest::verify).collect()
+}
+
+/// LRU set of signatures that verified, keyed by a hash of the parameter
+/// set, public key, message hash and signature. Failures are not kept, so
+/// invalid signatures cannot push out valid ones.
+pub struct SignatureCache {
+    verified: Mutex<LruCache<[u8; 32], ()>>,
+}
+
+impl SignatureCache {
+    pub fn new(capacity: usize) -> Self {
+        let capacity = NonZeroUsize::new(capacity).unwrap_or(NonZeroUsize::MIN);
+        SignatureCache { verified: Mutex::new(LruCache::new(capacity)) }
+    }
+
+    fn key(request: &VerifyRequest) -> [u8; 32] {
+        // BLAKE3 keeps a hit far cheaper than a verification
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(request.algorithm.name().as_bytes());
+        hasher.update(&(request.public_key.len() as u64).to_le_bytes());
+        hasher.update(request.public_key);
+        hasher.update(blake3::hash(request.message).as_bytes());
+        hasher.update(request.signature);
+        hasher.finalize().into()
+    }
+
+    /// `VerifyRequest::verify`, skipping the check for signatures that
+    /// verified before.
+    pub fn verify(&self, request: &VerifyRequest) -> bool {
+        let key = Self::key(request);
+        if self.verified.lock().unwrap().promote(&key) {
+            return true;
+        }
+        let valid = request.verify();
+        if valid {
+            self.verified.lock().unwrap().put(key, ());
         }
+        valid
+    }
+
+    /// `verify_batch` through the cache.
+    pub fn verify_batch(&self, requests: &[VerifyRequest]) -> Vec<bool> {
+        requests.par_iter().map(|request| self.verify(request)).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.verified.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
 
-        info
+    pub fn clear(&self) {
+        self.verified.lock().unwrap().clear();
     }
 }
@@ -0,0 +1,518 @@
+//! Local multi-node devnet.
+//!
+//! `Devnet::generate` lays out one directory per validator under a base
+//! directory: a fresh ML-DSA key in a keystore file encrypted with
+//! `DEVNET_PASSWORD`, a config with its own RPC and P2P ports, and a copy
+//! of a genesis shared by all nodes. `Devnet::start` then runs
+//! each node as a child process with that directory as its working
+//! directory, so the node's relative `data/` paths never collide.
+//!
+//! ```text
+//! <dir>/devnet.json            nodes, ports and pids, for `devnet down`
+//! <dir>/genesis.json
+//! <dir>/node-0/config.toml
+//! <dir>/node-0/genesis.json
+//! <dir>/node-0/keys/validator.json   the node's keystore
+//! <dir>/node-0/validator-password    unlocks it at startup
+//! <dir>/node-0/node.log        stdout and stderr
+//! <dir>/node-0/data/           chain, registry, scores
+//! ```
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha3::{Digest, Sha3_256};
+
+use crate::config::{save_config, NodeConfig};
+use crate::crypto::keys::KeyPair;
+use crate::genesis::{AllocationSpec, GenesisSpec, TokenSpec, ValidatorSpec};
+use crate::genesis::{GenesisConsensus, GenesisConsensusParameters, GenesisRewardWeighting};
+use crate::keystore::{KdfParams, KeystoreFile};
+use crate::storage;
+
+pub const MIN_NODES: usize = 1;
+pub const MAX_NODES: usize = 16;
+
+/// Password of every devnet validator key. Each node reads it from a file to
+/// sign as its validator, and the wallet can be unlocked over RPC with it.
+/// Devnet keys are for local testing only.
+pub const DEVNET_PASSWORD: &str = "synergy-devnet";
+
+/// The password is public, so a costly KDF would only slow generation down.
+const DEVNET_KDF: KdfParams = KdfParams { memory_kib: 1024, iterations: 1, parallelism: 1 };
+
+/// Whole SNRG allocated to each validator.
+const VALIDATOR_ALLOCATION: u64 = 1_000_000;
+
+/// How long a node gets to flush its state after SIGTERM.
+const STOP_TIMEOUT: Duration = Duration::from_secs(15);
+
+const MANIFEST_FILE: &str = "devnet.json";
+
+/// Holds `DEVNET_PASSWORD`, relative to a node's directory.
+const PASSWORD_FILE: &str = "validator-password";
+
+#[derive(Debug, Clone)]
+pub struct DevnetOptions {
+    pub nodes: usize,
+    pub dir: PathBuf,
+    /// Node `i` serves HTTP on `rpc_port + 10 * i`, WebSocket on the next
+    /// port and gRPC on the one after.
+    pub rpc_port: u16,
+    /// Node `i` listens for peers on `p2p_port + i`.
+    pub p2p_port: u16,
+    pub block_time_secs: u64,
+    /// The `synergy-testnet` binary to launch.
+    pub binary: PathBuf,
+}
+
+impl Default for DevnetOptions {
+    fn default() -> Self {
+        DevnetOptions {
+            nodes: 4,
+            dir: PathBuf::from("devnet"),
+            rpc_port: 18545,
+            p2p_port: 31303,
+            block_time_secs: 1,
+            binary: std::env::current_exe().unwrap_or_else(|_| PathBuf::from("synergy-testnet")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DevnetNode {
+    pub name: String,
+    pub dir: PathBuf,
+    pub address: String,
+    pub rpc_port: u16,
+    pub ws_port: u16,
+    pub grpc_port: u16,
+    pub p2p_port: u16,
+    /// Process id while the node runs.
+    #[serde(default)]
+    pub pid: Option<u32>,
+}
+
+impl DevnetNode {
+    pub fn rpc_url(&self) -> String {
+        format!("http://127.0.0.1:{}", self.rpc_port)
+    }
+
+    pub fn log_path(&self) -> PathBuf {
+        self.dir.join("node.log")
+    }
+
+    /// Calls a JSON-RPC method on the node.
+    pub fn rpc(&self, method: &str, params: Value) -> Result<Value, String> {
+        let response: Value = reqwest::blocking::Client::new()
+            .post(self.rpc_url())
+            .timeout(Duration::from_secs(5))
+            .json(&json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params }))
+            .send()
+            .and_then(|response| response.json())
+            .map_err(|e| format!("{}: {}", self.name, e))?;
+
+        match response.get("error") {
+            Some(error) => Err(format!("{}: {} failed: {}", self.name, method, error)),
+            None => Ok(response["result"].clone()),
+        }
+    }
+
+    /// Height of the node's latest finalized block.
+    pub fn finalized_height(&self) -> Result<u64, String> {
+        let block = self.rpc("eth_getBlockByNumber", json!(["finalized", false]))?;
+        block["number"]
+            .as_str()
+            .and_then(|number| u64::from_str_radix(number.trim_start_matches("0x"), 16).ok())
+            .ok_or_else(|| format!("{}: no finalized block", self.name))
+    }
+
+    /// Hash of the node's block at `height`, if it has one.
+    pub fn block_hash(&self, height: u64) -> Result<Option<String>, String> {
+        let block = self.rpc("eth_getBlockByNumber", json!([format!("0x{:x}", height), false]))?;
+        Ok(block["hash"].as_str().map(str::to_string))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Manifest {
+    genesis_hash: String,
+    nodes: Vec<DevnetNode>,
+}
+
+/// A generated devnet and, once started, its running nodes. Dropping a
+/// `Devnet` stops any nodes it started.
+#[derive(Debug)]
+pub struct Devnet {
+    pub options: DevnetOptions,
+    pub nodes: Vec<DevnetNode>,
+    pub genesis_hash: String,
+    children: Vec<Child>,
+}
+
+impl Devnet {
+    /// Creates keys, a shared genesis and per-node configs under
+    /// `options.dir`, replacing any devnet already there.
+    pub fn generate(options: DevnetOptions) -> Result<Self, Box<dyn Error>> {
+        if !(MIN_NODES..=MAX_NODES).contains(&options.nodes) {
+            return Err(format!("A devnet has {} to {} nodes, not {}", MIN_NODES, MAX_NODES, options.nodes).into());
+        }
+        let last_rpc = options.rpc_port as usize + 10 * (options.nodes - 1) + 2;
+        let last_p2p = options.p2p_port as usize + options.nodes - 1;
+        if last_rpc > u16::MAX as usize || last_p2p > u16::MAX as usize {
+            return Err("Port range runs past 65535; choose lower base ports".into());
+        }
+        if let Ok(manifest) = Self::read_manifest(&options.dir) {
+            if manifest.nodes.iter().any(|node| node.pid.is_some()) {
+                return Err(format!("A devnet is still running in {}; stop it with `devnet down`", options.dir.display()).into());
+            }
+        }
+        if options.dir.exists() {
+            fs::remove_dir_all(&options.dir)?;
+        }
+        fs::create_dir_all(&options.dir)?;
+        let dir = fs::canonicalize(&options.dir)?;
+
+        // Keys
+        let mut nodes = Vec::new();
+        let mut validators = Vec::new();
+        for index in 0..options.nodes {
+            let keypair = KeyPair::generate();
+            let public_key = keypair.public_key_hex();
+            let address = keypair.address();
+
+            let node_dir = dir.join(format!("node-{}", index));
+            KeystoreFile::encrypt(&keypair, DEVNET_PASSWORD, DEVNET_KDF)?
+                .save(node_dir.join("keys/validator.json"))?;
+            fs::write(node_dir.join(PASSWORD_FILE), DEVNET_PASSWORD)?;
+
+            let rpc_port = options.rpc_port + 10 * index as u16;
+            validators.push(ValidatorSpec {
+                address: address.clone(),
+                public_key,
+                name: Some(format!("Devnet Validator {}", index)),
+                weight: 1000,
+            });
+            nodes.push(DevnetNode {
+                name: format!("node-{}", index),
+                dir: node_dir,
+                address,
+                rpc_port,
+                ws_port: rpc_port + 1,
+                grpc_port: rpc_port + 2,
+                p2p_port: options.p2p_port + index as u16,
+                pid: None,
+            });
+        }
+
+        // Genesis
+        let preset = NodeConfig::preset("devnet")?;
+        let genesis = Self::genesis_spec(&preset, &options, validators)
+            .build()
+            .map_err(|errors| format!("Invalid devnet genesis: {}", errors.join("; ")))?;
+        genesis.save(&dir.join("genesis.json").to_string_lossy())?;
+
+        // Node configs
+        let node_ids: Vec<String> = nodes.iter().map(|node| node_id(&node.address)).collect();
+        for (index, node) in nodes.iter().enumerate() {
+            genesis.save(&node.dir.join("genesis.json").to_string_lossy())?;
+
+            let mut config = preset.clone();
+            config.network.name = "Synergy Devnet".to_string();
+            config.network.p2p_port = node.p2p_port;
+            config.network.rpc_port = node.rpc_port;
+            config.network.ws_port = node.ws_port;
+            config.network.bootnodes = nodes
+                .iter()
+                .zip(&node_ids)
+                .filter(|(other, _)| other.name != node.name)
+                .map(|(other, id)| format!("enode://{}@127.0.0.1:{}", id, other.p2p_port))
+                .collect();
+            config.blockchain.genesis_file = "genesis.json".to_string();
+            config.wallet.keystore_dir = "keys".to_string();
+            config.wallet.validator_password_file = PASSWORD_FILE.to_string();
+            config.rpc.http_port = node.rpc_port;
+            config.rpc.ws_port = node.ws_port;
+            config.rpc.grpc_port = node.grpc_port;
+            config.p2p.listen_address = format!("127.0.0.1:{}", node.p2p_port);
+            config.p2p.public_address = format!("127.0.0.1:{}", node.p2p_port);
+            config.p2p.node_name = format!("synergy-devnet-{:02}", index);
+            config.logging.enable_console = true;
+            save_config(&config, &node.dir.join("config.toml").to_string_lossy())?;
+        }
+
+        let devnet = Devnet {
+            options: DevnetOptions { dir, ..options },
+            nodes,
+            genesis_hash: genesis.genesis_hash,
+            children: Vec::new(),
+        };
+        devnet.write_manifest()?;
+        Ok(devnet)
+    }
+
+    fn genesis_spec(preset: &NodeConfig, options: &DevnetOptions, validators: Vec<ValidatorSpec>) -> GenesisSpec {
+        let consensus = &preset.consensus;
+        let weights = &consensus.reward_weighting;
+        GenesisSpec {
+            network: "Synergy Devnet".to_string(),
+            description: format!("Local {}-validator devnet", options.nodes),
+            chain_id: preset.blockchain.chain_id,
+            consensus: GenesisConsensus {
+                algorithm: consensus.algorithm.clone(),
+                parameters: GenesisConsensusParameters {
+                    block_time: options.block_time_secs,
+                    epoch: consensus.epoch_length,
+                    validator_cluster_size: consensus.validator_cluster_size.min(options.nodes),
+                    synergy_score_decay_rate: consensus.synergy_score_decay_rate,
+                    vrf_enabled: consensus.vrf_enabled,
+                    vrf_seed_epoch_interval: consensus.vrf_seed_epoch_interval,
+                    max_synergy_points_per_epoch: consensus.max_synergy_points_per_epoch,
+                    max_tasks_per_validator: consensus.max_tasks_per_validator,
+                    reward_weighting: GenesisRewardWeighting {
+                        task_accuracy: weights.task_accuracy,
+                        uptime: weights.uptime,
+                        collaboration: weights.collaboration,
+                    },
+                },
+            },
+            tokens: vec![TokenSpec {
+                symbol: "SNRG".to_string(),
+                name: "SynergyCoin".to_string(),
+                decimals: 9,
+                max_supply: None,
+                mintable: true,
+                burnable: true,
+            }],
+            allocations: validators
+                .iter()
+                .map(|validator| AllocationSpec {
+                    address: validator.address.clone(),
+                    token: "SNRG".to_string(),
+                    amount: VALIDATOR_ALLOCATION,
+                })
+                .collect(),
+            validators,
+        }
+    }
+
+    /// Launches every node. Nodes run in their own process group so a
+    /// Ctrl-C in the launcher's terminal reaches only the launcher, which
+    /// then stops them one signal at a time.
+    pub fn start(&mut self) -> Result<(), Box<dyn Error>> {
+        for node in &mut self.nodes {
+            let log = File::create(node.log_path())?;
+            let mut command = Command::new(&self.options.binary);
+            command
+                .args(["start", "--config", "config.toml"])
+                .current_dir(&node.dir)
+                .stdin(Stdio::null())
+                .stdout(log.try_clone()?)
+                .stderr(log);
+            // Only the node's own config applies
+            for (key, _) in std::env::vars().filter(|(key, _)| key.starts_with("SYNERGY_")) {
+                command.env_remove(key);
+            }
+            #[cfg(unix)]
+            {
+                use std::os::unix::process::CommandExt;
+                command.process_group(0);
+            }
+
+            let child = command
+                .spawn()
+                .map_err(|e| format!("Failed to launch {} with {}: {}", node.name, self.options.binary.display(), e))?;
+            node.pid = Some(child.id());
+            println!("🚀 Started {} (pid {}) — RPC {}", node.name, child.id(), node.rpc_url());
+            self.children.push(child);
+        }
+        self.write_manifest()?;
+        Ok(())
+    }
+
+    /// Waits until every node answers RPC with the devnet's genesis hash.
+    pub fn wait_until_ready(&mut self, timeout: Duration) -> Result<(), String> {
+        let deadline = Instant::now() + timeout;
+        for node in self.nodes.clone() {
+            loop {
+                match node.rpc("synergy_nodeInfo", json!({})) {
+                    Ok(info) if info["genesisHash"] == self.genesis_hash.as_str() => break,
+                    Ok(info) => {
+                        return Err(format!("{} reports genesis {}, expected {}", node.name, info["genesisHash"], self.genesis_hash))
+                    }
+                    Err(e) if Instant::now() >= deadline => return Err(format!("{} is not ready: {}", node.name, e)),
+                    Err(_) => {}
+                }
+                if let Some(status) = self.exited() {
+                    return Err(status);
+                }
+                thread::sleep(Duration::from_millis(250));
+            }
+        }
+        Ok(())
+    }
+
+    /// Waits until every node has finalized at least `height` blocks.
+    pub fn wait_for_height(&mut self, height: u64, timeout: Duration) -> Result<(), String> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let heights = self.finalized_heights();
+            if heights.iter().all(|h| matches!(h, Ok(h) if *h >= height)) {
+                return Ok(());
+            }
+            if let Some(status) = self.exited() {
+                return Err(status);
+            }
+            if Instant::now() >= deadline {
+                let summary: Vec<String> = self
+                    .nodes
+                    .iter()
+                    .zip(heights)
+                    .map(|(node, h)| format!("{}: {}", node.name, h.map_or_else(|e| e, |h| h.to_string())))
+                    .collect();
+                return Err(format!("Timed out waiting for height {} ({})", height, summary.join(", ")));
+            }
+            thread::sleep(Duration::from_millis(500));
+        }
+    }
+
+    pub fn finalized_heights(&self) -> Vec<Result<u64, String>> {
+        self.nodes.iter().map(DevnetNode::finalized_height).collect()
+    }
+
+    /// Describes the first node that has exited, if any.
+    fn exited(&mut self) -> Option<String> {
+        for (child, node) in self.children.iter_mut().zip(&self.nodes) {
+            if let Ok(Some(status)) = child.try_wait() {
+                return Some(format!("{} exited with {}; see {}", node.name, status, node.log_path().display()));
+            }
+        }
+        None
+    }
+
+    /// The last `lines` lines of each node's log, prefixed with its name.
+    pub fn tail_logs(&self, lines: usize) -> Vec<String> {
+        let mut tail = Vec::new();
+        for node in &self.nodes {
+            let content = fs::read_to_string(node.log_path()).unwrap_or_default();
+            let all: Vec<&str> = content.lines().collect();
+            let start = all.len().saturating_sub(lines);
+            tail.extend(all[start..].iter().map(|line| format!("[{}] {}", node.name, line)));
+        }
+        tail
+    }
+
+    /// Prints new log lines from every node, prefixed with its name, until
+    /// `stop` returns true or a node exits.
+    pub fn follow_logs(&mut self, stop: impl Fn() -> bool) {
+        let mut offsets: HashMap<String, u64> = HashMap::new();
+        while !stop() {
+            for node in &self.nodes {
+                let offset = offsets.entry(node.name.clone()).or_insert(0);
+                if let Ok(mut file) = File::open(node.log_path()) {
+                    if file.seek(SeekFrom::Start(*offset)).is_err() {
+                        continue;
+                    }
+                    let mut reader = BufReader::new(file);
+                    let mut line = String::new();
+                    // Leave a partial last line for the next pass
+                    while reader.read_line(&mut line).unwrap_or(0) > 0 && line.ends_with('\n') {
+                        *offset += line.len() as u64;
+                        print!("[{}] {}", node.name, line);
+                        line.clear();
+                    }
+                }
+            }
+            if let Some(status) = self.exited() {
+                eprintln!("⚠️ {}", status);
+                return;
+            }
+            thread::sleep(Duration::from_millis(200));
+        }
+    }
+
+    /// Stops every node with SIGTERM, giving each time to flush its state
+    /// before it is killed.
+    pub fn stop(&mut self) {
+        for child in &self.children {
+            terminate(child.id());
+        }
+        let deadline = Instant::now() + STOP_TIMEOUT;
+        for (child, node) in self.children.iter_mut().zip(&self.nodes) {
+            while matches!(child.try_wait(), Ok(None)) && Instant::now() < deadline {
+                thread::sleep(Duration::from_millis(100));
+            }
+            if matches!(child.try_wait(), Ok(None)) {
+                eprintln!("⚠️ {} did not stop within {}s, killing it", node.name, STOP_TIMEOUT.as_secs());
+                let _ = child.kill();
+                let _ = child.wait();
+            }
+        }
+        if !self.children.is_empty() {
+            println!("🛑 Stopped {} devnet node(s)", self.children.len());
+        }
+        self.children.clear();
+        for node in &mut self.nodes {
+            node.pid = None;
+        }
+        let _ = self.write_manifest();
+    }
+
+    /// Stops the nodes of a devnet started by another process, as recorded
+    /// in its `devnet.json`. Returns how many were signalled.
+    pub fn stop_dir(dir: &Path) -> Result<usize, Box<dyn Error>> {
+        let mut manifest = Self::read_manifest(dir)?;
+        let mut stopped = 0;
+        for node in &mut manifest.nodes {
+            if let Some(pid) = node.pid.take() {
+                terminate(pid);
+                stopped += 1;
+            }
+        }
+        storage::write_json_atomic(dir.join(MANIFEST_FILE), &manifest)?;
+        Ok(stopped)
+    }
+
+    fn read_manifest(dir: &Path) -> Result<Manifest, Box<dyn Error>> {
+        let path = dir.join(MANIFEST_FILE);
+        let content = fs::read_to_string(&path).map_err(|e| format!("No devnet at {}: {}", dir.display(), e))?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    fn write_manifest(&self) -> Result<(), Box<dyn Error>> {
+        let manifest = Manifest { genesis_hash: self.genesis_hash.clone(), nodes: self.nodes.clone() };
+        storage::write_json_atomic(self.options.dir.join(MANIFEST_FILE), &manifest)
+    }
+}
+
+impl Drop for Devnet {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// P2P node id for the enode URLs in each node's bootnode list.
+fn node_id(address: &str) -> String {
+    hex::encode(Sha3_256::digest(address.as_bytes()))
+}
+
+#[cfg(unix)]
+fn terminate(pid: u32) {
+    // SAFETY: kill(2) has no memory-safety preconditions
+    unsafe {
+        libc::kill(pid as libc::pid_t, libc::SIGTERM);
+    }
+}
+
+#[cfg(not(unix))]
+fn terminate(pid: u32) {
+    let _ = Command::new("taskkill").args(["/PID", &pid.to_string()]).status();
+}
@@ -0,0 +1,93 @@
+//! Node-wide event bus.
+//!
+//! Consensus, the transaction pool, the validator registry and the AIVM
+//! publish here; WebSocket subscriptions (see `rpc::subscriptions`) consume.
+//! Publishing never blocks: events are dropped when nobody is listening.
+
+use serde::Serialize;
+use tokio::sync::broadcast;
+use lazy_static::lazy_static;
+
+use crate::aivm::distributed_ai::ComputationStatus;
+use crate::block::Block;
+use crate::transaction::Transaction;
+use crate::validator::ValidatorStatus;
+
+/// Number of events buffered for each subscriber before it starts lagging.
+pub const EVENT_BUS_CAPACITY: usize = 1024;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LogEntry {
+    pub address: String,
+    pub topics: Vec<String>,
+    pub data: String,
+    pub block_number: Option<u64>,
+    pub transaction_hash: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ValidatorStatusChange {
+    pub address: String,
+    pub previous_status: Option<ValidatorStatus>,
+    pub status: ValidatorStatus,
+    pub timestamp: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ComputationStatusChange {
+    pub computation_id: String,
+    pub model_id: String,
+    pub cluster_id: u64,
+    pub status: ComputationStatus,
+    pub timestamp: u64,
+}
+
+#[derive(Debug, Clone)]
+pub enum NodeEvent {
+    NewHead(Block),
+    PendingTransaction(Transaction),
+    Log(LogEntry),
+    ValidatorStatus(ValidatorStatusChange),
+    ComputationStatus(ComputationStatusChange),
+}
+
+pub struct EventBus {
+    sender: broadcast::Sender<NodeEvent>,
+}
+
+impl EventBus {
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity);
+        EventBus { sender }
+    }
+
+    pub fn publish(&self, event: NodeEvent) {
+        // An error only means there are no subscribers right now
+        let _ = self.sender.send(event);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<NodeEvent> {
+        self.sender.subscribe()
+    }
+
+    pub fn subscriber_count(&self) -> usize {
+        self.sender.receiver_count()
+    }
+}
+
+lazy_static! {
+    pub static ref EVENT_BUS: EventBus = EventBus::new(EVENT_BUS_CAPACITY);
+}
+
+/// Publishes one log entry per transaction included in `block`.
+pub fn publish_block_logs(block: &Block) {
+    for tx in &block.transactions {
+        EVENT_BUS.publish(NodeEvent::Log(LogEntry {
+            address: tx.receiver.clone(),
+            topics: vec!["Transfer".to_string(), tx.sender.clone(), tx.receiver.clone()],
+            data: tx.amount.to_string(),
+            block_number: Some(block.block_index),
+            transaction_hash: Some(tx.hash()),
+        }));
+    }
+}
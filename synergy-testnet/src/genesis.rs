@@ -0,0 +1,408 @@
+//! Canonical genesis state.
+//!
+//! Operators describe a network in a TOML spec (chain id, consensus
+//! parameters, tokens, allocations and validators) and `genesis build`
+//! turns it into `genesis.json`. The genesis hash is a SHA3-256 digest of
+//! that file's canonical JSON; block 0 links to it, and nodes refuse chains
+//! and peers that were started from a different genesis.
+
+use std::collections::{BTreeMap, HashSet};
+use std::error::Error;
+use std::fs;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha3::{Digest, Sha3_256};
+
+use crate::crypto::{keys, mldsa};
+use crate::storage;
+use crate::transaction::Transaction;
+use crate::wallet::WalletManager;
+
+/// Spec `genesis build` reads when no `--spec` is given.
+pub const DEFAULT_SPEC_PATH: &str = "config/genesis-spec.toml";
+
+/// Genesis file the node loads when the configuration names none.
+pub const DEFAULT_GENESIS_PATH: &str = "config/genesis.json";
+
+/// How far the reward weights may stray from summing to exactly 1.
+pub const WEIGHT_TOLERANCE: f64 = 1e-6;
+
+/// Hand-written description of a network. Token amounts are in whole
+/// tokens; the builder scales them by each token's decimals.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GenesisSpec {
+    pub network: String,
+    #[serde(default)]
+    pub description: String,
+    pub chain_id: u64,
+    pub consensus: GenesisConsensus,
+    #[serde(default)]
+    pub tokens: Vec<TokenSpec>,
+    #[serde(default)]
+    pub allocations: Vec<AllocationSpec>,
+    #[serde(default)]
+    pub validators: Vec<ValidatorSpec>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenSpec {
+    pub symbol: String,
+    pub name: String,
+    pub decimals: u8,
+    /// Whole tokens; unlimited when absent.
+    #[serde(default)]
+    pub max_supply: Option<u64>,
+    #[serde(default = "default_true")]
+    pub mintable: bool,
+    #[serde(default = "default_true")]
+    pub burnable: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AllocationSpec {
+    pub address: String,
+    pub token: String,
+    /// Whole tokens.
+    pub amount: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidatorSpec {
+    pub address: String,
+    /// Hex-encoded validator public key.
+    pub public_key: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    pub weight: u64,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// The built genesis, as stored in `genesis.json`. Token amounts are in
+/// base units.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Genesis {
+    pub meta: GenesisMeta,
+    pub config: GenesisChainConfig,
+    pub tokens: Vec<GenesisToken>,
+    /// Address -> token symbol -> balance.
+    pub alloc: BTreeMap<String, BTreeMap<String, u64>>,
+    pub validators: GenesisValidators,
+    /// Hash of everything above; see `Genesis::compute_hash`.
+    #[serde(default)]
+    pub genesis_hash: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GenesisMeta {
+    pub network: String,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenesisChainConfig {
+    pub chain_id: u64,
+    pub synergy_consensus: GenesisConsensus,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GenesisConsensus {
+    pub algorithm: String,
+    pub parameters: GenesisConsensusParameters,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenesisConsensusParameters {
+    pub block_time: u64,
+    pub epoch: u64,
+    pub validator_cluster_size: usize,
+    pub synergy_score_decay_rate: f64,
+    pub vrf_enabled: bool,
+    pub vrf_seed_epoch_interval: u64,
+    pub max_synergy_points_per_epoch: u64,
+    pub max_tasks_per_validator: u32,
+    pub reward_weighting: GenesisRewardWeighting,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenesisRewardWeighting {
+    pub task_accuracy: f64,
+    pub uptime: f64,
+    pub collaboration: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenesisToken {
+    pub symbol: String,
+    pub name: String,
+    pub decimals: u8,
+    pub max_supply: Option<u64>,
+    pub mintable: bool,
+    pub burnable: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenesisValidators {
+    pub initial_validators: Vec<GenesisValidator>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenesisValidator {
+    pub address: String,
+    pub pub_key: String,
+    pub name: String,
+    pub weight: u64,
+}
+
+impl GenesisSpec {
+    pub fn load(path: &str) -> Result<Self, Box<dyn Error>> {
+        let content = fs::read_to_string(path).map_err(|e| format!("Failed to read genesis spec {}: {}", path, e))?;
+        Ok(toml::from_str(&content).map_err(|e| format!("Invalid genesis spec {}: {}", path, e))?)
+    }
+
+    /// Checks the spec and builds the genesis, hash included. Returns every
+    /// problem found, one line each.
+    pub fn build(&self) -> Result<Genesis, Vec<String>> {
+        let mut errors = Vec::new();
+
+        if self.network.trim().is_empty() {
+            errors.push("network must not be empty".to_string());
+        }
+        if self.chain_id == 0 {
+            errors.push("chain_id must not be 0".to_string());
+        }
+        check_consensus(&self.consensus, &mut errors);
+
+        // Tokens
+        let mut tokens = Vec::new();
+        let mut decimals = BTreeMap::new();
+        for token in &self.tokens {
+            if token.symbol.trim().is_empty() {
+                errors.push("tokens: symbol must not be empty".to_string());
+                continue;
+            }
+            if decimals.insert(token.symbol.clone(), token.decimals).is_some() {
+                errors.push(format!("tokens: {} is defined more than once", token.symbol));
+                continue;
+            }
+            let max_supply = match token.max_supply.map(|max| to_base_units(max, token.decimals)) {
+                Some(None) => {
+                    errors.push(format!("tokens.{}: max_supply does not fit in 64 bits of base units", token.symbol));
+                    None
+                }
+                Some(max) => max,
+                None => None,
+            };
+            tokens.push(GenesisToken {
+                symbol: token.symbol.clone(),
+                name: token.name.clone(),
+                decimals: token.decimals,
+                max_supply,
+                mintable: token.mintable,
+                burnable: token.burnable,
+            });
+        }
+
+        // Allocations
+        let mut alloc: BTreeMap<String, BTreeMap<String, u64>> = BTreeMap::new();
+        let mut supply: BTreeMap<String, u64> = BTreeMap::new();
+        for allocation in &self.allocations {
+            let Some(&token_decimals) = decimals.get(&allocation.token) else {
+                errors.push(format!("allocations: {} is allocated unknown token {}", allocation.address, allocation.token));
+                continue;
+            };
+            if !Transaction::is_valid_address(&allocation.address) {
+                errors.push(format!("allocations: '{}' is not a Synergy address", allocation.address));
+                continue;
+            }
+            let total = supply.entry(allocation.token.clone()).or_insert(0);
+            let balance = alloc
+                .entry(allocation.address.clone())
+                .or_default()
+                .entry(allocation.token.clone())
+                .or_insert(0);
+            match to_base_units(allocation.amount, token_decimals)
+                .and_then(|amount| Some((total.checked_add(amount)?, balance.checked_add(amount)?)))
+            {
+                Some((new_total, new_balance)) => {
+                    *total = new_total;
+                    *balance = new_balance;
+                }
+                None => errors.push(format!("allocations: {} supply does not fit in 64 bits of base units", allocation.token)),
+            }
+        }
+        for token in &tokens {
+            if let (Some(max), Some(total)) = (token.max_supply, supply.get(&token.symbol)) {
+                if total > &max {
+                    errors.push(format!("tokens.{}: allocations ({}) exceed max_supply ({})", token.symbol, total, max));
+                }
+            }
+        }
+
+        // Validators
+        let mut seen = HashSet::new();
+        let mut validators = Vec::new();
+        if self.validators.is_empty() {
+            errors.push("validators: at least one validator is required".to_string());
+        }
+        for validator in &self.validators {
+            if !Transaction::is_valid_address(&validator.address) {
+                errors.push(format!("validators: '{}' is not a Synergy address", validator.address));
+            }
+            if !seen.insert(validator.address.clone()) {
+                errors.push(format!("validators: {} is listed more than once", validator.address));
+            }
+            let public_key = validator.public_key.to_lowercase();
+            match hex::decode(&public_key) {
+                Ok(bytes) if bytes.len() == mldsa::PUBLIC_KEY_BYTES => {
+                    if WalletManager::generate_address(&public_key) != validator.address {
+                        errors.push(format!("validators.{}: address does not belong to public_key", validator.address));
+                    }
+                }
+                _ => errors.push(format!(
+                    "validators.{}: public_key must be a hex {} public key ({} bytes)",
+                    validator.address,
+                    keys::ALGORITHM,
+                    mldsa::PUBLIC_KEY_BYTES
+                )),
+            }
+            if validator.weight == 0 {
+                errors.push(format!("validators.{}: weight must be at least 1", validator.address));
+            }
+            validators.push(GenesisValidator {
+                address: validator.address.clone(),
+                pub_key: public_key,
+                name: validator.name.clone().unwrap_or_else(|| format!("Genesis Validator {}", validator.address)),
+                weight: validator.weight,
+            });
+        }
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        let mut genesis = Genesis {
+            meta: GenesisMeta { network: self.network.clone(), description: self.description.clone() },
+            config: GenesisChainConfig { chain_id: self.chain_id, synergy_consensus: self.consensus.clone() },
+            tokens,
+            alloc,
+            validators: GenesisValidators { initial_validators: validators },
+            genesis_hash: String::new(),
+        };
+        genesis.genesis_hash = genesis.compute_hash();
+        Ok(genesis)
+    }
+}
+
+impl Genesis {
+    /// Loads a genesis file and checks that its contents still match the
+    /// hash recorded in it.
+    pub fn load(path: &str) -> Result<Self, Box<dyn Error>> {
+        let content = fs::read_to_string(path).map_err(|e| format!("Failed to read genesis file {}: {}", path, e))?;
+        let genesis: Genesis =
+            serde_json::from_str(&content).map_err(|e| format!("Invalid genesis file {}: {}", path, e))?;
+
+        if genesis.genesis_hash.is_empty() {
+            return Err(format!("Genesis file {} has no genesisHash; rebuild it with `genesis build`", path).into());
+        }
+        let computed = genesis.compute_hash();
+        if computed != genesis.genesis_hash {
+            return Err(format!(
+                "Genesis file {} was modified: it records hash {} but its contents hash to {}",
+                path, genesis.genesis_hash, computed
+            )
+            .into());
+        }
+        Ok(genesis)
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), Box<dyn Error>> {
+        storage::write_json_atomic(path, self)
+    }
+
+    /// SHA3-256 of the genesis as JSON with `genesisHash` removed, object
+    /// keys sorted and no whitespace, so formatting does not change it.
+    pub fn compute_hash(&self) -> String {
+        let mut value = serde_json::to_value(self).expect("genesis is always serializable");
+        if let Value::Object(fields) = &mut value {
+            fields.remove("genesisHash");
+        }
+
+        let mut canonical = String::new();
+        write_canonical(&value, &mut canonical);
+        hex::encode(Sha3_256::digest(canonical.as_bytes()))
+    }
+
+    /// Total base units allocated of `symbol`.
+    pub fn allocated_supply(&self, symbol: &str) -> u64 {
+        self.alloc.values().filter_map(|balances| balances.get(symbol)).sum()
+    }
+}
+
+fn check_consensus(consensus: &GenesisConsensus, errors: &mut Vec<String>) {
+    let params = &consensus.parameters;
+    if consensus.algorithm.trim().is_empty() {
+        errors.push("consensus.algorithm must not be empty".to_string());
+    }
+    if params.block_time == 0 {
+        errors.push("consensus.parameters.blockTime must be at least 1 second".to_string());
+    }
+    if params.epoch == 0 {
+        errors.push("consensus.parameters.epoch must be at least 1 block".to_string());
+    }
+    if params.validator_cluster_size == 0 {
+        errors.push("consensus.parameters.validatorClusterSize must be at least 1".to_string());
+    }
+    if !(0.0..=1.0).contains(&params.synergy_score_decay_rate) {
+        errors.push("consensus.parameters.synergyScoreDecayRate must be between 0 and 1".to_string());
+    }
+    let weights = &params.reward_weighting;
+    let total = weights.task_accuracy + weights.uptime + weights.collaboration;
+    if (total - 1.0).abs() > WEIGHT_TOLERANCE {
+        errors.push(format!("consensus.parameters.rewardWeighting must sum to 1, got {}", total));
+    }
+}
+
+fn to_base_units(whole: u64, decimals: u8) -> Option<u64> {
+    10u64.checked_pow(decimals as u32)?.checked_mul(whole)
+}
+
+fn write_canonical(value: &Value, out: &mut String) {
+    match value {
+        Value::Object(fields) => {
+            let mut keys: Vec<&String> = fields.keys().collect();
+            keys.sort();
+            out.push('{');
+            for (i, key) in keys.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::String(key.clone()).to_string());
+                out.push(':');
+                write_canonical(&fields[key], out);
+            }
+            out.push('}');
+        }
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(item, out);
+            }
+            out.push(']');
+        }
+        other => out.push_str(&other.to_string()),
+    }
+}
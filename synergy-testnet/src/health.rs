@@ -0,0 +1,161 @@
+//! Node health checks behind the `/health`, `/ready` and `/live` endpoints
+//! on the HTTP RPC port.
+//!
+//! A node is live while its consensus thread keeps beating. It is ready
+//! once it can also write to storage, is caught up with its peers, has
+//! enough peers, has seen a recent block and its clock agrees with the
+//! network.
+
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::HealthConfig;
+use crate::p2p::P2P_NETWORK;
+use crate::rpc::rpc_server::CHAIN;
+
+/// Unix time of the consensus loop's last iteration; 0 until it starts.
+static CONSENSUS_HEARTBEAT: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthStatus {
+    /// Live and ready.
+    Healthy,
+    /// Live, but at least one readiness check fails.
+    Degraded,
+    /// The consensus thread has stopped.
+    Unhealthy,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthReport {
+    pub status: HealthStatus,
+    pub live: bool,
+    pub ready: bool,
+    pub storage_writable: bool,
+    pub consensus_heartbeat_age_secs: Option<u64>,
+    pub height: u64,
+    pub best_peer_height: Option<u64>,
+    /// Blocks behind the best peer; `None` until a peer has reported its
+    /// height.
+    pub sync_lag: Option<u64>,
+    pub peer_count: usize,
+    pub last_block_age_secs: Option<u64>,
+    /// `None` until a peer has reported its clock.
+    pub clock_drift_secs: Option<i64>,
+    /// One line per failing check.
+    pub failures: Vec<String>,
+}
+
+/// Called by the consensus loop on every iteration.
+pub fn consensus_heartbeat() {
+    CONSENSUS_HEARTBEAT.store(current_timestamp(), Ordering::Relaxed);
+}
+
+/// Seconds since the consensus loop last ran, or `None` before it starts.
+pub fn consensus_heartbeat_age() -> Option<u64> {
+    let heartbeat = CONSENSUS_HEARTBEAT.load(Ordering::Relaxed);
+    (heartbeat > 0).then(|| current_timestamp().saturating_sub(heartbeat))
+}
+
+/// The liveness check alone. Takes no locks, so it answers even when the
+/// rest of the node is stuck.
+pub fn is_live(config: &HealthConfig) -> bool {
+    consensus_heartbeat_age().is_none_or(|age| age <= config.heartbeat_timeout_secs)
+}
+
+/// Runs every check. Takes the chain and P2P locks, so call it off the
+/// async workers.
+pub fn check(config: &HealthConfig) -> HealthReport {
+    let now = current_timestamp();
+    let mut failures = Vec::new();
+
+    // The consensus thread has not started yet while the node boots; that
+    // is not ready, but not dead either
+    let consensus_heartbeat_age_secs = consensus_heartbeat_age();
+    let live = is_live(config);
+    match consensus_heartbeat_age_secs {
+        None => failures.push("consensus has not started".to_string()),
+        Some(age) if !live => failures.push(format!("consensus heartbeat is {}s old", age)),
+        Some(_) => {}
+    }
+
+    let storage_writable = storage_writable(&config.data_dir);
+    if !storage_writable {
+        failures.push(format!("storage at {} is not writable", config.data_dir));
+    }
+
+    let (height, last_block_timestamp) = {
+        let chain = CHAIN.lock().unwrap();
+        chain.last().map_or((0, None), |block| (block.block_index, Some(block.timestamp)))
+    };
+    let last_block_age_secs = last_block_timestamp.map(|timestamp| now.saturating_sub(timestamp));
+    match last_block_age_secs {
+        Some(age) if age > config.max_block_age_secs => {
+            failures.push(format!("last block is {}s old", age));
+        }
+        None => failures.push("no blocks".to_string()),
+        _ => {}
+    }
+
+    let (peer_count, best_peer_height, clock_drift_secs) = match P2P_NETWORK.lock().unwrap().as_ref() {
+        Some(network) => (network.get_peer_count(), network.best_peer_height(), network.median_clock_offset()),
+        None => (0, None, None),
+    };
+
+    // Without a peer status the lag is unknown; min_peers decides whether that matters
+    let sync_lag = best_peer_height.map(|best| best.saturating_sub(height));
+    if let Some(lag) = sync_lag.filter(|lag| *lag > config.max_sync_lag) {
+        failures.push(format!("{} blocks behind peers", lag));
+    }
+    if peer_count < config.min_peers {
+        failures.push(format!("{} peers, need {}", peer_count, config.min_peers));
+    }
+    if let Some(drift) = clock_drift_secs {
+        if drift.unsigned_abs() > config.max_clock_drift_secs {
+            failures.push(format!("clock differs from peers by {}s", drift));
+        }
+    }
+
+    let ready = failures.is_empty();
+    let status = if !live {
+        HealthStatus::Unhealthy
+    } else if ready {
+        HealthStatus::Healthy
+    } else {
+        HealthStatus::Degraded
+    };
+
+    HealthReport {
+        status,
+        live,
+        ready,
+        storage_writable,
+        consensus_heartbeat_age_secs,
+        height,
+        best_peer_height,
+        sync_lag,
+        peer_count,
+        last_block_age_secs,
+        clock_drift_secs,
+        failures,
+    }
+}
+
+fn storage_writable(data_dir: &str) -> bool {
+    let probe = Path::new(data_dir).join(".health_probe");
+    let writable = fs::write(&probe, b"ok").is_ok();
+    let _ = fs::remove_file(&probe);
+    writable
+}
+
+fn current_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
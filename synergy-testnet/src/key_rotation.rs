@@ -0,0 +1,252 @@
+//! Key rotation for accounts and validators.
+//!
+//! A `rotate_key:` transaction binds a new PQC key, possibly of another
+//! parameter set, to its sender's address and is signed by the key it
+//! replaces. The new key takes effect later: an account key
+//! `ACCOUNT_ROTATION_DELAY` blocks after the rotation is included, a
+//! validator's consensus key at the first block of the next epoch. Until
+//! then the old key stays in force, and a second rotation of the same key
+//! replaces the pending one, so an owner can override a rotation made with
+//! a stolen key.
+//!
+//! `KeyRegistry` is derived from the chain alone and rebuilt on start.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Mutex;
+
+use lazy_static::lazy_static;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::block::{Block, BlockChain};
+use crate::crypto::pqc::{SignatureAlgorithm, Verifier};
+use crate::transaction::Transaction;
+
+/// Blocks between the inclusion of an account key rotation and the first
+/// block that must be signed with the new key.
+pub const ACCOUNT_ROTATION_DELAY: u64 = 100;
+
+/// Epoch length until the consensus engine sets the configured one.
+const DEFAULT_EPOCH_LENGTH: u64 = 30000;
+
+const DATA_PREFIX: &str = "rotate_key:";
+
+/// Which key of an address a rotation replaces: the `account` key that
+/// signs its transactions, or the `consensus` key of the validator at it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyScope {
+    Account,
+    Consensus,
+}
+
+impl fmt::Display for KeyScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            KeyScope::Account => "account",
+            KeyScope::Consensus => "consensus",
+        })
+    }
+}
+
+/// Payload of a key rotation transaction.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct RotateKey {
+    pub scope: KeyScope,
+    pub algorithm: SignatureAlgorithm,
+    /// Hex public key.
+    pub public_key: String,
+}
+
+impl RotateKey {
+    /// `data` of a key rotation transaction.
+    pub fn to_data(&self) -> String {
+        format!("{}{}", DATA_PREFIX, serde_json::to_string(self).expect("serializable rotation"))
+    }
+
+    /// The rotation `tx` carries, or `None` if it is not a key rotation.
+    pub fn from_transaction(tx: &Transaction) -> Option<Result<Self, String>> {
+        let payload = tx.data.as_deref()?.strip_prefix(DATA_PREFIX)?;
+        Some(
+            serde_json::from_str::<RotateKey>(payload)
+                .map_err(|e| format!("Invalid key rotation: {}", e))
+                .and_then(|rotation| rotation.check().map(|_| rotation)),
+        )
+    }
+
+    /// Checks that the public key is hex of the size its algorithm uses, and
+    /// that consensus keys are ML-DSA-87, the algorithm blocks and head
+    /// votes are signed with.
+    pub fn check(&self) -> Result<(), String> {
+        if self.scope == KeyScope::Consensus && self.algorithm != SignatureAlgorithm::MlDsa87 {
+            return Err(format!("Consensus keys must be {} keys, not {}", SignatureAlgorithm::MlDsa87, self.algorithm));
+        }
+        let public_key = hex::decode(&self.public_key).map_err(|_| "Key rotation public key must be hex".to_string())?;
+        if public_key.len() != self.algorithm.public_key_bytes() {
+            return Err(format!(
+                "A {} public key is {} bytes, not {}",
+                self.algorithm,
+                self.algorithm.public_key_bytes(),
+                public_key.len()
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// One rotation in an address's key history.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct KeyRotation {
+    pub address: String,
+    pub scope: KeyScope,
+    pub algorithm: SignatureAlgorithm,
+    /// Hex public key bound by the rotation.
+    pub public_key: String,
+    pub previous_algorithm: SignatureAlgorithm,
+    /// Hex public key that signed the rotation and is replaced by it.
+    pub previous_public_key: String,
+    pub transaction_hash: String,
+    /// Height of the block that included the rotation.
+    pub block_height: u64,
+    /// First block height at which the new key is in force.
+    pub effective_height: u64,
+    /// Replaced by a later rotation before it took effect; never in force.
+    pub superseded: bool,
+}
+
+/// Key history of every address that has rotated a key.
+#[derive(Debug, Clone)]
+pub struct KeyRegistry {
+    epoch_length: u64,
+    height: u64,
+    rotations: HashMap<String, Vec<KeyRotation>>,
+}
+
+impl Default for KeyRegistry {
+    fn default() -> Self {
+        Self::new(DEFAULT_EPOCH_LENGTH)
+    }
+}
+
+impl KeyRegistry {
+    pub fn new(epoch_length: u64) -> Self {
+        KeyRegistry { epoch_length: epoch_length.max(1), height: 0, rotations: HashMap::new() }
+    }
+
+    /// Replays the rotations of every block of `chain`.
+    pub fn from_chain(chain: &BlockChain, epoch_length: u64) -> Self {
+        let mut registry = Self::new(epoch_length);
+        for block in &chain.chain {
+            registry.apply_block(block);
+        }
+        registry
+    }
+
+    /// Height of the last block applied; pending transactions are checked
+    /// against the keys of the block after it.
+    pub fn height(&self) -> u64 {
+        self.height
+    }
+
+    /// First height at which a rotation included at `height` is in force.
+    pub fn effective_height(&self, scope: KeyScope, height: u64) -> u64 {
+        match scope {
+            KeyScope::Account => height + ACCOUNT_ROTATION_DELAY,
+            KeyScope::Consensus => (height / self.epoch_length + 1) * self.epoch_length,
+        }
+    }
+
+    /// Checks that `tx` is a rotation that may be included at `height`.
+    /// Its signature is checked with the transaction's.
+    pub fn check_rotation(&self, tx: &Transaction, height: u64) -> Result<RotateKey, String> {
+        let rotation = RotateKey::from_transaction(tx).ok_or("Transaction is not a key rotation")??;
+        if tx.multisig.is_some() {
+            return Err("Multisig accounts cannot rotate keys; create a new policy instead".to_string());
+        }
+        let current = self.key_at(&tx.sender, rotation.scope, height).map_or(&tx.public_key, |current| &current.public_key);
+        if rotation.public_key.eq_ignore_ascii_case(current) {
+            return Err("Key rotation does not change the key".to_string());
+        }
+        Ok(rotation)
+    }
+
+    /// Records the rotations included in `block`. Blocks only hold
+    /// rotations that passed `check_rotation`; anything else is skipped.
+    pub fn apply_block(&mut self, block: &Block) {
+        let height = block.block_index;
+        for tx in &block.transactions {
+            let Ok(rotation) = self.check_rotation(tx, height) else {
+                continue;
+            };
+            let (previous_algorithm, previous_public_key) = match self.key_at(&tx.sender, rotation.scope, height) {
+                Some(current) => (current.algorithm, current.public_key.clone()),
+                None => (SignatureAlgorithm::MlDsa87, tx.public_key.to_lowercase()),
+            };
+            let effective_height = self.effective_height(rotation.scope, height);
+
+            let history = self.rotations.entry(tx.sender.clone()).or_default();
+            for pending in history.iter_mut() {
+                if pending.scope == rotation.scope && !pending.superseded && pending.effective_height > height {
+                    pending.superseded = true;
+                }
+            }
+            history.push(KeyRotation {
+                address: tx.sender.clone(),
+                scope: rotation.scope,
+                algorithm: rotation.algorithm,
+                public_key: rotation.public_key.to_lowercase(),
+                previous_algorithm,
+                previous_public_key,
+                transaction_hash: tx.hash(),
+                block_height: height,
+                effective_height,
+                superseded: false,
+            });
+        }
+        self.height = height;
+    }
+
+    /// The rotation whose `scope` key of `address` is in force at `height`,
+    /// or `None` while the address still uses the key it started with.
+    pub fn key_at(&self, address: &str, scope: KeyScope, height: u64) -> Option<&KeyRotation> {
+        self.rotations
+            .get(address)?
+            .iter()
+            .rev()
+            .find(|rotation| rotation.scope == scope && !rotation.superseded && rotation.effective_height <= height)
+    }
+
+    pub fn account_key(&self, address: &str, height: u64) -> Option<&KeyRotation> {
+        self.key_at(address, KeyScope::Account, height)
+    }
+
+    pub fn consensus_key(&self, address: &str, height: u64) -> Option<&KeyRotation> {
+        self.key_at(address, KeyScope::Consensus, height)
+    }
+
+    /// Addresses that have rotated a key.
+    pub fn addresses(&self) -> impl Iterator<Item = &String> {
+        self.rotations.keys()
+    }
+
+    /// Every rotation of `address`, oldest first, including pending and
+    /// superseded ones.
+    pub fn history(&self, address: &str) -> &[KeyRotation] {
+        self.rotations.get(address).map_or(&[], Vec::as_slice)
+    }
+
+    /// Rotations that come into force at `height`.
+    pub fn activated_at(&self, height: u64) -> Vec<&KeyRotation> {
+        self.rotations
+            .values()
+            .flatten()
+            .filter(|rotation| !rotation.superseded && rotation.effective_height == height)
+            .collect()
+    }
+}
+
+lazy_static! {
+    /// Key history of the node's chain, kept current by the consensus engine.
+    pub static ref KEY_REGISTRY: Mutex<KeyRegistry> = Mutex::new(KeyRegistry::default());
+}
@@ -0,0 +1,411 @@
+//! Password-encrypted key files.
+//!
+//! Each file holds one ML-DSA-87 key pair. The private key is encrypted
+//! with XChaCha20-Poly1305 under a key derived from the password with
+//! Argon2id. The address, algorithm and public key stay readable and are
+//! bound to the ciphertext as associated data, so editing any of them makes
+//! the file fail to decrypt.
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "address": "sYn...",
+//!   "algorithm": "ML-DSA-87",
+//!   "publicKey": "<hex>",
+//!   "crypto": {
+//!     "kdf": "argon2id",
+//!     "kdfParams": { "memoryKib": 65536, "iterations": 3, "parallelism": 1 },
+//!     "salt": "<hex>",
+//!     "cipher": "xchacha20-poly1305",
+//!     "nonce": "<hex>",
+//!     "ciphertext": "<hex>"
+//!   },
+//!   "createdAt": 1700000000
+//! }
+//! ```
+//!
+//! A hybrid key pair (`crate::crypto::hybrid`) has algorithm
+//! `ML-DSA-87+secp256k1` and a `secp256k1PublicKey`; its ciphertext holds
+//! the ML-DSA private key followed by the 32-byte secp256k1 private key.
+//!
+//! A `Keystore` is a directory of these files. Files are found by the
+//! address inside them, so they can be renamed freely.
+
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::{Aead, Payload};
+use chacha20poly1305::{Key, KeyInit, XChaCha20Poly1305, XNonce};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::crypto::hybrid;
+use crate::crypto::keys::{KeyPair, ALGORITHM};
+use crate::storage;
+use crate::wallet::WalletManager;
+
+pub const KEYSTORE_VERSION: u32 = 1;
+pub const DEFAULT_KEYSTORE_DIR: &str = "keystore";
+pub const MIN_PASSWORD_LEN: usize = 8;
+
+const KDF: &str = "argon2id";
+const CIPHER: &str = "xchacha20-poly1305";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+
+/// Argon2id cost parameters.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KdfParams {
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        KdfParams {
+            memory_kib: 64 * 1024,
+            iterations: 3,
+            parallelism: 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeystoreCrypto {
+    pub kdf: String,
+    pub kdf_params: KdfParams,
+    pub salt: String,
+    pub cipher: String,
+    pub nonce: String,
+    pub ciphertext: String,
+}
+
+/// One encrypted key file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeystoreFile {
+    pub version: u32,
+    pub address: String,
+    pub algorithm: String,
+    pub public_key: String,
+    /// Compressed secp256k1 public key of a hybrid key pair.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secp256k1_public_key: Option<String>,
+    pub crypto: KeystoreCrypto,
+    pub created_at: u64,
+}
+
+impl KeystoreFile {
+    pub fn encrypt(keypair: &KeyPair, password: &str, params: KdfParams) -> Result<Self, String> {
+        let mut salt = [0u8; SALT_LEN];
+        let mut nonce = [0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut salt);
+        OsRng.fill_bytes(&mut nonce);
+
+        let mut file = KeystoreFile {
+            version: KEYSTORE_VERSION,
+            address: keypair.address(),
+            algorithm: if keypair.is_hybrid() { hybrid::ALGORITHM } else { ALGORITHM }.to_string(),
+            public_key: keypair.public_key_hex(),
+            secp256k1_public_key: keypair.secp256k1_public_key_hex(),
+            crypto: KeystoreCrypto {
+                kdf: KDF.to_string(),
+                kdf_params: params,
+                salt: hex::encode(salt),
+                cipher: CIPHER.to_string(),
+                nonce: hex::encode(nonce),
+                ciphertext: String::new(),
+            },
+            created_at: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+        };
+
+        let key = derive_key(password, &salt, &params)?;
+        let aad = file.associated_data();
+        let mut plaintext = Zeroizing::new(keypair.private_key().to_vec());
+        if let Some(secp256k1) = keypair.secp256k1_key() {
+            plaintext.extend_from_slice(&hybrid::private_key_bytes(secp256k1));
+        }
+        let ciphertext = XChaCha20Poly1305::new(<&Key>::from(&*key))
+            .encrypt(<&XNonce>::from(&nonce), Payload { msg: &plaintext, aad: &aad })
+            .map_err(|_| "Failed to encrypt private key".to_string())?;
+        file.crypto.ciphertext = hex::encode(ciphertext);
+        Ok(file)
+    }
+
+    pub fn decrypt(&self, password: &str) -> Result<KeyPair, String> {
+        self.check()?;
+        let salt = hex::decode(&self.crypto.salt).map_err(|e| format!("Invalid salt: {}", e))?;
+        let nonce: [u8; NONCE_LEN] = hex::decode(&self.crypto.nonce)
+            .map_err(|e| format!("Invalid nonce: {}", e))?
+            .try_into()
+            .map_err(|nonce: Vec<u8>| format!("Invalid nonce: expected {} bytes, got {}", NONCE_LEN, nonce.len()))?;
+        let ciphertext = hex::decode(&self.crypto.ciphertext).map_err(|e| format!("Invalid ciphertext: {}", e))?;
+
+        let key = derive_key(password, &salt, &self.crypto.kdf_params)?;
+        let aad = self.associated_data();
+        let plaintext = Zeroizing::new(
+            XChaCha20Poly1305::new(<&Key>::from(&*key))
+                .decrypt(<&XNonce>::from(&nonce), Payload { msg: &ciphertext, aad: &aad })
+                .map_err(|_| format!("Wrong password or corrupted keystore for {}", self.address))?,
+        );
+        let public_key = hex::decode(&self.public_key).map_err(|e| format!("Invalid public key: {}", e))?;
+        let Some(secp256k1_public_key) = &self.secp256k1_public_key else {
+            return KeyPair::from_bytes(&public_key, &plaintext);
+        };
+
+        let split = plaintext
+            .len()
+            .checked_sub(hybrid::SECP256K1_PRIVATE_KEY_BYTES)
+            .ok_or("Hybrid keystore is missing its secp256k1 key")?;
+        let secp256k1 = hybrid::private_key_from_bytes(&plaintext[split..])?;
+        if hybrid::public_key_hex(&secp256k1) != *secp256k1_public_key {
+            return Err("secp256k1 private key does not match the public key".to_string());
+        }
+        Ok(KeyPair::from_bytes(&public_key, &plaintext[..split])?.with_secp256k1(secp256k1))
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Box<dyn Error>> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)?;
+        let file: KeystoreFile =
+            serde_json::from_str(&content).map_err(|e| format!("{} is not a keystore file: {}", path.display(), e))?;
+        file.check().map_err(|e| format!("{}: {}", path.display(), e))?;
+        Ok(file)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Box<dyn Error>> {
+        let json = serde_json::to_string_pretty(self)?;
+        storage::write_private_atomic(path, json.as_bytes())?;
+        Ok(())
+    }
+
+    /// Rejects versions, algorithms and addresses this node does not
+    /// understand before any key derivation is attempted.
+    fn check(&self) -> Result<(), String> {
+        if self.version != KEYSTORE_VERSION {
+            return Err(format!("Unsupported keystore version {}", self.version));
+        }
+        let address = match (self.algorithm.as_str(), &self.secp256k1_public_key) {
+            (ALGORITHM, None) => WalletManager::generate_address(&self.public_key),
+            (hybrid::ALGORITHM, Some(secp256k1_public_key)) => hybrid::address(&self.public_key, secp256k1_public_key),
+            _ => return Err(format!("Unsupported key algorithm {}", self.algorithm)),
+        };
+        if self.crypto.kdf != KDF || self.crypto.cipher != CIPHER {
+            return Err(format!("Unsupported encryption {}/{}", self.crypto.kdf, self.crypto.cipher));
+        }
+        if address != self.address {
+            return Err(format!("Address {} does not match the public key", self.address));
+        }
+        Ok(())
+    }
+
+    fn associated_data(&self) -> Vec<u8> {
+        let mut data = format!("{}\n{}\n{}\n{}", self.version, self.address, self.algorithm, self.public_key);
+        if let Some(secp256k1_public_key) = &self.secp256k1_public_key {
+            data.push('\n');
+            data.push_str(secp256k1_public_key);
+        }
+        data.into_bytes()
+    }
+}
+
+fn derive_key(password: &str, salt: &[u8], params: &KdfParams) -> Result<Zeroizing<[u8; 32]>, String> {
+    let params = Params::new(params.memory_kib, params.iterations, params.parallelism, Some(32))
+        .map_err(|e| format!("Invalid KDF parameters: {}", e))?;
+    let mut key = Zeroizing::new([0u8; 32]);
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(password.as_bytes(), salt, &mut key[..])
+        .map_err(|e| format!("Key derivation failed: {}", e))?;
+    Ok(key)
+}
+
+/// Plaintext key pair, as written by `keystore export` and read by
+/// `keystore import`.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyExport {
+    pub address: String,
+    pub algorithm: String,
+    pub public_key: String,
+    pub private_key: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secp256k1_public_key: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secp256k1_private_key: Option<String>,
+}
+
+impl KeyExport {
+    pub fn from_keypair(keypair: &KeyPair) -> Self {
+        KeyExport {
+            address: keypair.address(),
+            algorithm: if keypair.is_hybrid() { hybrid::ALGORITHM } else { ALGORITHM }.to_string(),
+            public_key: keypair.public_key_hex(),
+            private_key: hex::encode(keypair.private_key()),
+            secp256k1_public_key: keypair.secp256k1_public_key_hex(),
+            secp256k1_private_key: keypair.secp256k1_key().map(|key| hex::encode(hybrid::private_key_bytes(key))),
+        }
+    }
+
+    pub fn to_keypair(&self) -> Result<KeyPair, String> {
+        let public_key = hex::decode(&self.public_key).map_err(|e| format!("Invalid public key: {}", e))?;
+        let private_key =
+            Zeroizing::new(hex::decode(&self.private_key).map_err(|e| format!("Invalid private key: {}", e))?);
+        let keypair = KeyPair::from_bytes(&public_key, &private_key)?;
+        let keypair = match (self.algorithm.as_str(), &self.secp256k1_private_key) {
+            (ALGORITHM, None) => keypair,
+            (hybrid::ALGORITHM, Some(secp256k1)) => keypair.with_secp256k1(hybrid::parse_private_key(secp256k1)?),
+            _ => return Err(format!("Unsupported key algorithm {}", self.algorithm)),
+        };
+        if self.secp256k1_public_key != keypair.secp256k1_public_key_hex() {
+            return Err("secp256k1 private key does not match the public key".to_string());
+        }
+        if keypair.address() != self.address {
+            return Err(format!("Address {} does not match the public key", self.address));
+        }
+        Ok(keypair)
+    }
+}
+
+impl Drop for KeyExport {
+    fn drop(&mut self) {
+        self.private_key.zeroize();
+        self.secp256k1_private_key.zeroize();
+    }
+}
+
+/// A key file found in a keystore directory.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeystoreEntry {
+    pub address: String,
+    pub public_key: String,
+    pub path: PathBuf,
+    pub created_at: u64,
+}
+
+/// A directory of keystore files.
+#[derive(Debug, Clone)]
+pub struct Keystore {
+    dir: PathBuf,
+    kdf: KdfParams,
+}
+
+impl Keystore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Keystore {
+            dir: dir.into(),
+            kdf: KdfParams::default(),
+        }
+    }
+
+    /// Cost parameters for files written from now on. Existing files keep
+    /// the parameters they were written with.
+    pub fn with_kdf(mut self, kdf: KdfParams) -> Self {
+        self.kdf = kdf;
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Every readable key file, sorted by address. A missing directory is
+    /// an empty keystore.
+    pub fn list(&self) -> Result<Vec<KeystoreEntry>, Box<dyn Error>> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut entries = Vec::new();
+        for dir_entry in fs::read_dir(&self.dir)? {
+            let path = dir_entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+            match KeystoreFile::load(&path) {
+                Ok(file) => entries.push(KeystoreEntry {
+                    address: file.address,
+                    public_key: file.public_key,
+                    path,
+                    created_at: file.created_at,
+                }),
+                Err(e) => crate::warn!("keystore", "Skipping unreadable key file", "error" => e.to_string()),
+            }
+        }
+        entries.sort_by(|a, b| a.address.cmp(&b.address));
+        Ok(entries)
+    }
+
+    /// Generates a key pair and stores it under `password`.
+    pub fn create(&self, password: &str) -> Result<KeystoreEntry, Box<dyn Error>> {
+        self.add(&KeyPair::generate(), password)
+    }
+
+    pub fn import(&self, export: &KeyExport, password: &str) -> Result<KeystoreEntry, Box<dyn Error>> {
+        self.add(&export.to_keypair()?, password)
+    }
+
+    /// Stores `keypair` as `<address>.json`, refusing to replace a key
+    /// already in the keystore.
+    pub fn add(&self, keypair: &KeyPair, password: &str) -> Result<KeystoreEntry, Box<dyn Error>> {
+        check_password(password)?;
+        let address = keypair.address();
+        if self.find(&address).is_ok() {
+            return Err(format!("{} is already in {}", address, self.dir.display()).into());
+        }
+        let file = KeystoreFile::encrypt(keypair, password, self.kdf)?;
+        let path = self.dir.join(format!("{}.json", address));
+        file.save(&path)?;
+        Ok(KeystoreEntry {
+            address,
+            public_key: file.public_key,
+            path,
+            created_at: file.created_at,
+        })
+    }
+
+    /// The file holding `address`.
+    pub fn find(&self, address: &str) -> Result<(PathBuf, KeystoreFile), Box<dyn Error>> {
+        let entry = self
+            .list()?
+            .into_iter()
+            .find(|entry| entry.address == address)
+            .ok_or_else(|| format!("No key for {} in {}", address, self.dir.display()))?;
+        let file = KeystoreFile::load(&entry.path)?;
+        Ok((entry.path, file))
+    }
+
+    pub fn unlock(&self, address: &str, password: &str) -> Result<KeyPair, Box<dyn Error>> {
+        Ok(self.find(address)?.1.decrypt(password)?)
+    }
+
+    pub fn export(&self, address: &str, password: &str) -> Result<KeyExport, Box<dyn Error>> {
+        Ok(KeyExport::from_keypair(&self.unlock(address, password)?))
+    }
+
+    /// Re-encrypts a key under a new password with a fresh salt and nonce.
+    pub fn change_password(&self, address: &str, old_password: &str, new_password: &str) -> Result<(), Box<dyn Error>> {
+        check_password(new_password)?;
+        let (path, file) = self.find(address)?;
+        let keypair = file.decrypt(old_password)?;
+        let mut updated = KeystoreFile::encrypt(&keypair, new_password, self.kdf)?;
+        updated.created_at = file.created_at;
+        updated.save(&path)
+    }
+}
+
+fn check_password(password: &str) -> Result<(), String> {
+    if password.chars().count() < MIN_PASSWORD_LEN {
+        return Err(format!("Passwords must be at least {} characters", MIN_PASSWORD_LEN));
+    }
+    Ok(())
+}
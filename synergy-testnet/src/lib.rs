@@ -1,8 +1,15 @@
 pub mod block;
 pub mod transaction;
+pub mod tx_file;
 pub mod consensus;
 pub mod broadcast;
 pub mod config;
+pub mod devnet;
+pub mod genesis;
+pub mod keystore;
+pub mod key_rotation;
+pub mod name_service;
+pub mod nonce;
 pub mod contracts;
 pub mod node;
 pub mod p2p;
@@ -13,3 +20,9 @@ pub mod token;
 pub mod wallet;
 pub mod aivm;
 pub mod crypto;
+pub mod rate_limit;
+pub mod events;
+pub mod health;
+pub mod metrics;
+pub mod shutdown;
+pub mod storage;
@@ -1,12 +1,20 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
 use std::fs::{self, OpenOptions};
 use std::io::{self, Write};
 use std::path::Path;
 use std::sync::{Arc, Mutex};
 use std::time::{SystemTime, UNIX_EPOCH};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use chrono::DateTime;
+use crate::config::LoggingConfig;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Entries kept in memory when the configuration does not say otherwise.
+pub const DEFAULT_BUFFER_SIZE: usize = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "UPPERCASE")]
 pub enum LogLevel {
     Trace,
     Debug,
@@ -16,7 +24,7 @@ pub enum LogLevel {
 }
 
 impl LogLevel {
-    pub fn from_str(s: &str) -> Option<Self> {
+    pub fn parse(s: &str) -> Option<Self> {
         match s.to_lowercase().as_str() {
             "trace" => Some(LogLevel::Trace),
             "debug" => Some(LogLevel::Debug),
@@ -26,35 +34,160 @@ impl LogLevel {
             _ => None,
         }
     }
+}
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            LogLevel::Trace => "TRACE",
+            LogLevel::Debug => "DEBUG",
+            LogLevel::Info => "INFO",
+            LogLevel::Warn => "WARN",
+            LogLevel::Error => "ERROR",
+        })
+    }
+}
+
+/// How entries are written to the console and the log file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    /// `[timestamp] [LEVEL] [module] message`, metadata on the next line.
+    Text,
+    /// One JSON object per line.
+    Json,
+}
 
-    pub fn to_string(&self) -> String {
-        match self {
-            LogLevel::Trace => "TRACE".to_string(),
-            LogLevel::Debug => "DEBUG".to_string(),
-            LogLevel::Info => "INFO".to_string(),
-            LogLevel::Warn => "WARN".to_string(),
-            LogLevel::Error => "ERROR".to_string(),
+impl LogFormat {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "text" => Some(LogFormat::Text),
+            "json" => Some(LogFormat::Json),
+            _ => None,
         }
     }
 }
 
-#[derive(Debug, Clone)]
+/// Minimum level per module, parsed from specs such as
+/// `info,consensus=debug,p2p=warn`.
+///
+/// Modules are `::`-separated paths: a level set for `rpc` also applies to
+/// `rpc::audit` unless that has its own.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LevelFilter {
+    pub default: LogLevel,
+    pub modules: HashMap<String, LogLevel>,
+}
+
+impl LevelFilter {
+    pub fn new(default: LogLevel) -> Self {
+        LevelFilter { default, modules: HashMap::new() }
+    }
+
+    /// Parses a comma-separated list of `module=level` pairs. A bare level
+    /// replaces `default`.
+    pub fn parse(spec: &str, default: LogLevel) -> Result<Self, String> {
+        let mut filter = LevelFilter::new(default);
+        for directive in spec.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            match directive.split_once('=') {
+                Some((module, level)) => {
+                    let module = module.trim();
+                    if module.is_empty() {
+                        return Err(format!("Missing module name in '{}'", directive));
+                    }
+                    filter.set(Some(module), parse_level(level.trim())?);
+                }
+                None => filter.set(None, parse_level(directive)?),
+            }
+        }
+        Ok(filter)
+    }
+
+    /// The level for `module`: its own, else its closest parent's, else
+    /// the default.
+    pub fn level_for(&self, module: &str) -> LogLevel {
+        let mut path = module;
+        loop {
+            if let Some(level) = self.modules.get(path) {
+                return *level;
+            }
+            match path.rfind("::") {
+                Some(index) => path = &path[..index],
+                None => return self.default,
+            }
+        }
+    }
+
+    pub fn enabled(&self, level: LogLevel, module: &str) -> bool {
+        level >= self.level_for(module)
+    }
+
+    /// Sets the level for `module`, or the default when `None`.
+    pub fn set(&mut self, module: Option<&str>, level: LogLevel) {
+        match module {
+            Some(module) => {
+                self.modules.insert(module.to_string(), level);
+            }
+            None => self.default = level,
+        }
+    }
+}
+
+fn parse_level(level: &str) -> Result<LogLevel, String> {
+    LogLevel::parse(level).ok_or_else(|| format!("Unknown log level '{}'", level))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct LogEntry {
+    /// Unix time in seconds.
     pub timestamp: u64,
     pub level: LogLevel,
     pub module: String,
     pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub metadata: Option<serde_json::Value>,
 }
 
+/// Which buffered entries `Logger::query` returns. Unset fields match
+/// everything.
+#[derive(Debug, Clone, Default)]
+pub struct LogQuery {
+    /// Only entries at this level or above.
+    pub min_level: Option<LogLevel>,
+    /// Only entries from this module or its submodules.
+    pub module: Option<String>,
+    /// Only entries at or after this Unix time.
+    pub since: Option<u64>,
+    /// Only entries at or before this Unix time.
+    pub until: Option<u64>,
+    /// At most this many of the newest matching entries.
+    pub limit: Option<usize>,
+}
+
+impl LogQuery {
+    fn matches(&self, entry: &LogEntry) -> bool {
+        self.min_level.is_none_or(|level| entry.level >= level)
+            && self.module.as_deref().is_none_or(|module| in_module(&entry.module, module))
+            && self.since.is_none_or(|since| entry.timestamp >= since)
+            && self.until.is_none_or(|until| entry.timestamp <= until)
+    }
+}
+
+fn in_module(entry_module: &str, module: &str) -> bool {
+    entry_module
+        .strip_prefix(module)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+}
+
 #[derive(Debug)]
 pub struct Logger {
-    level: LogLevel,
+    filter: LevelFilter,
+    format: LogFormat,
     enable_console: bool,
     log_file: String,
     max_file_size: u64,
     max_files: u32,
-    entries: Arc<Mutex<Vec<LogEntry>>>,
+    buffer_size: usize,
+    entries: Arc<Mutex<VecDeque<LogEntry>>>,
 }
 
 impl Logger {
@@ -66,99 +199,99 @@ impl Logger {
         max_files: u32,
     ) -> Self {
         Logger {
-            level,
+            filter: LevelFilter::new(level),
+            format: LogFormat::Text,
             enable_console,
             log_file,
             max_file_size,
             max_files,
-            entries: Arc::new(Mutex::new(Vec::new())),
+            buffer_size: DEFAULT_BUFFER_SIZE,
+            entries: Arc::new(Mutex::new(VecDeque::new())),
         }
     }
 
-    pub fn log(&self, level: LogLevel, module: &str, message: &str) {
-        if self.should_log(&level) {
-            let entry = LogEntry {
-                timestamp: SystemTime::now()
-                    .duration_since(UNIX_EPOCH)
-                    .unwrap()
-                    .as_secs(),
-                level: level.clone(),
-                module: module.to_string(),
-                message: message.to_string(),
-                metadata: None,
-            };
-
-            // Add to in-memory buffer
-            if let Ok(mut entries) = self.entries.lock() {
-                entries.push(entry.clone());
-            }
+    /// Builds a logger from the `[logging]` section, rejecting unknown
+    /// levels, formats and malformed module filters.
+    pub fn from_config(config: &LoggingConfig) -> Result<Self, String> {
+        let level = parse_level(&config.log_level)?;
+        let format = LogFormat::parse(&config.log_format)
+            .ok_or_else(|| format!("Unknown log format '{}'", config.log_format))?;
 
-            // Write to console if enabled
-            if self.enable_console {
-                self.write_to_console(&entry);
-            }
+        let mut logger = Logger::new(
+            level,
+            config.enable_console,
+            config.log_file.clone(),
+            config.max_file_size,
+            config.max_files,
+        );
+        logger.filter = LevelFilter::parse(&config.module_levels, level)?;
+        logger.format = format;
+        logger.buffer_size = config.buffer_size;
+        Ok(logger)
+    }
 
-            // Write to file
-            self.write_to_file(&entry);
-        }
+    pub fn log(&self, level: LogLevel, module: &str, message: &str) {
+        self.record(level, module, message, None);
     }
 
     pub fn log_with_metadata(&self, level: LogLevel, module: &str, message: &str, metadata: serde_json::Value) {
-        if self.should_log(&level) {
-            let entry = LogEntry {
-                timestamp: SystemTime::now()
-                    .duration_since(UNIX_EPOCH)
-                    .unwrap()
-                    .as_secs(),
-                level: level.clone(),
-                module: module.to_string(),
-                message: message.to_string(),
-                metadata: Some(metadata),
-            };
-
-            // Add to in-memory buffer
-            if let Ok(mut entries) = self.entries.lock() {
-                entries.push(entry.clone());
-            }
+        self.record(level, module, message, Some(metadata));
+    }
 
-            // Write to console if enabled
-            if self.enable_console {
-                self.write_to_console(&entry);
-            }
+    fn record(&self, level: LogLevel, module: &str, message: &str, metadata: Option<serde_json::Value>) {
+        if !self.filter.enabled(level, module) {
+            return;
+        }
+
+        let entry = LogEntry {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+            level,
+            module: module.to_string(),
+            message: message.to_string(),
+            metadata,
+        };
 
-            // Write to file
-            self.write_to_file(&entry);
+        // Write to console if enabled
+        if self.enable_console {
+            self.write_to_console(&entry);
+        }
+
+        // Write to file
+        self.write_to_file(&entry);
+
+        // Add to the in-memory buffer, dropping the oldest entry when full
+        if let Ok(mut entries) = self.entries.lock() {
+            if entries.len() >= self.buffer_size {
+                entries.pop_front();
+            }
+            if self.buffer_size > 0 {
+                entries.push_back(entry);
+            }
         }
     }
 
-    fn should_log(&self, level: &LogLevel) -> bool {
-        match (&self.level, level) {
-            (LogLevel::Trace, _) => true,
-            (LogLevel::Debug, LogLevel::Trace) => false,
-            (LogLevel::Debug, _) => true,
-            (LogLevel::Info, LogLevel::Trace) | (LogLevel::Info, LogLevel::Debug) => false,
-            (LogLevel::Info, _) => true,
-            (LogLevel::Warn, LogLevel::Trace) | (LogLevel::Warn, LogLevel::Debug) | (LogLevel::Warn, LogLevel::Info) => false,
-            (LogLevel::Warn, _) => true,
-            (LogLevel::Error, LogLevel::Error) => true,
-            _ => false,
+    fn format_entry(&self, entry: &LogEntry) -> String {
+        match self.format {
+            LogFormat::Json => serde_json::to_string(entry).unwrap_or_default(),
+            LogFormat::Text => {
+                let timestamp = DateTime::from_timestamp(entry.timestamp as i64, 0)
+                    .unwrap_or_default()
+                    .format("%Y-%m-%d %H:%M:%S UTC");
+
+                let mut line = format!("[{}] [{}] [{}] {}", timestamp, entry.level, entry.module, entry.message);
+                if let Some(metadata) = &entry.metadata {
+                    line.push_str(&format!("\n  Metadata: {}", serde_json::to_string(metadata).unwrap_or_default()));
+                }
+                line
+            }
         }
     }
 
     fn write_to_console(&self, entry: &LogEntry) {
-        let timestamp = DateTime::from_timestamp(entry.timestamp as i64, 0)
-            .unwrap_or_default()
-            .format("%Y-%m-%d %H:%M:%S UTC");
-
-        let level_str = entry.level.to_string();
-        let module = &entry.module;
-        let message = &entry.message;
-
-        println!("[{}] [{}] [{}] {}", timestamp, level_str, module, message);
-
-        if let Some(metadata) = &entry.metadata {
-            println!("  Metadata: {}", serde_json::to_string_pretty(metadata).unwrap_or_default());
-        }
+        println!("{}", self.format_entry(entry));
     }
 
     fn write_to_file(&self, entry: &LogEntry) {
@@ -192,26 +325,10 @@ impl Logger {
             }
         };
 
-        let timestamp = DateTime::from_timestamp(entry.timestamp as i64, 0)
-            .unwrap_or_default()
-            .format("%Y-%m-%d %H:%M:%S UTC");
-
-        let level_str = entry.level.to_string();
-        let module = &entry.module;
-        let message = &entry.message;
-
-        let log_line = format!("[{}] [{}] [{}] {}\n", timestamp, level_str, module, message);
-
+        let log_line = format!("{}\n", self.format_entry(entry));
         if let Err(e) = file.write_all(log_line.as_bytes()) {
             eprintln!("Failed to write to log file: {}", e);
         }
-
-        if let Some(metadata) = &entry.metadata {
-            let metadata_line = format!("  Metadata: {}\n", serde_json::to_string(metadata).unwrap_or_default());
-            if let Err(e) = file.write_all(metadata_line.as_bytes()) {
-                eprintln!("Failed to write metadata to log file: {}", e);
-            }
-        }
     }
 
     fn should_rotate(&self) -> bool {
@@ -263,9 +380,36 @@ impl Logger {
         Ok(())
     }
 
+    pub fn filter(&self) -> &LevelFilter {
+        &self.filter
+    }
+
+    /// Changes the level of `module`, or the default level when `None`.
+    pub fn set_level(&mut self, module: Option<&str>, level: LogLevel) {
+        self.filter.set(module, level);
+    }
+
+    /// Buffered entries matching `query`, oldest first.
+    pub fn query(&self, query: &LogQuery) -> Vec<LogEntry> {
+        let entries = match self.entries.lock() {
+            Ok(entries) => entries,
+            Err(_) => return Vec::new(),
+        };
+
+        let mut matching: Vec<LogEntry> = entries
+            .iter()
+            .rev()
+            .filter(|entry| query.matches(entry))
+            .take(query.limit.unwrap_or(usize::MAX))
+            .cloned()
+            .collect();
+        matching.reverse();
+        matching
+    }
+
     pub fn get_entries(&self) -> Vec<LogEntry> {
         if let Ok(entries) = self.entries.lock() {
-            entries.clone()
+            entries.iter().cloned().collect()
         } else {
             Vec::new()
         }
@@ -297,6 +441,16 @@ pub fn init_logger(
     }
 }
 
+/// Installs the global logger described by the `[logging]` section.
+pub fn init_logger_from_config(config: &LoggingConfig) -> Result<(), String> {
+    let logger = Logger::from_config(config)?;
+
+    if let Ok(mut global_logger) = LOGGER.lock() {
+        *global_logger = Some(logger);
+    }
+    Ok(())
+}
+
 pub fn log(level: LogLevel, module: &str, message: &str) {
     if let Ok(logger) = LOGGER.lock() {
         if let Some(ref logger) = *logger {
@@ -313,97 +467,74 @@ pub fn log_with_metadata(level: LogLevel, module: &str, message: &str, metadata:
     }
 }
 
+/// Changes a level on the global logger at runtime and returns the
+/// resulting filter.
+pub fn set_level(module: Option<&str>, level: LogLevel) -> Result<LevelFilter, String> {
+    let mut logger = LOGGER.lock().map_err(|_| "Logger lock poisoned".to_string())?;
+    let logger = logger.as_mut().ok_or("Logger is not initialized")?;
+    logger.set_level(module, level);
+    Ok(logger.filter().clone())
+}
+
+/// The global logger's current filter, if it has been initialized.
+pub fn levels() -> Option<LevelFilter> {
+    LOGGER.lock().ok()?.as_ref().map(|logger| logger.filter().clone())
+}
+
+/// Entries in the global logger's buffer matching `query`, oldest first.
+pub fn query(query: &LogQuery) -> Vec<LogEntry> {
+    match LOGGER.lock() {
+        Ok(logger) => logger.as_ref().map_or_else(Vec::new, |logger| logger.query(query)),
+        Err(_) => Vec::new(),
+    }
+}
+
+#[doc(hidden)]
 #[macro_export]
-macro_rules! trace {
-    ($module:expr, $message:expr) => {
-        $crate::logging::log($crate::logging::LogLevel::Trace, $module, $message)
+macro_rules! __log {
+    ($level:expr, $module:expr, $message:expr) => {
+        $crate::logging::log($level, $module, &$message)
     };
-    ($module:expr, $message:expr, $($key:expr => $value:expr),*) => {
-        let mut metadata = serde_json::Map::new();
+    ($level:expr, $module:expr, $message:expr, $($key:expr => $value:expr),+) => {{
+        let mut metadata = ::serde_json::Map::new();
         $(
-            metadata.insert($key.to_string(), serde_json::Value::from($value));
-        )*
-        $crate::logging::log_with_metadata(
-            $crate::logging::LogLevel::Trace,
-            $module,
-            $message,
-            serde_json::Value::Object(metadata)
-        )
+            metadata.insert($key.to_string(), ::serde_json::Value::from($value));
+        )+
+        $crate::logging::log_with_metadata($level, $module, &$message, ::serde_json::Value::Object(metadata))
+    }};
+}
+
+#[macro_export]
+macro_rules! trace {
+    ($module:expr, $message:expr $(, $key:expr => $value:expr)*) => {
+        $crate::__log!($crate::logging::LogLevel::Trace, $module, $message $(, $key => $value)*)
     };
 }
 
 #[macro_export]
 macro_rules! debug {
-    ($module:expr, $message:expr) => {
-        $crate::logging::log($crate::logging::LogLevel::Debug, $module, $message)
-    };
-    ($module:expr, $message:expr, $($key:expr => $value:expr),*) => {
-        let mut metadata = serde_json::Map::new();
-        $(
-            metadata.insert($key.to_string(), serde_json::Value::from($value));
-        )*
-        $crate::logging::log_with_metadata(
-            $crate::logging::LogLevel::Debug,
-            $module,
-            $message,
-            serde_json::Value::Object(metadata)
-        )
+    ($module:expr, $message:expr $(, $key:expr => $value:expr)*) => {
+        $crate::__log!($crate::logging::LogLevel::Debug, $module, $message $(, $key => $value)*)
     };
 }
 
 #[macro_export]
 macro_rules! info {
-    ($module:expr, $message:expr) => {
-        $crate::logging::log($crate::logging::LogLevel::Info, $module, $message)
-    };
-    ($module:expr, $message:expr, $($key:expr => $value:expr),*) => {
-        let mut metadata = serde_json::Map::new();
-        $(
-            metadata.insert($key.to_string(), serde_json::Value::from($value));
-        )*
-        $crate::logging::log_with_metadata(
-            $crate::logging::LogLevel::Info,
-            $module,
-            $message,
-            serde_json::Value::Object(metadata)
-        )
+    ($module:expr, $message:expr $(, $key:expr => $value:expr)*) => {
+        $crate::__log!($crate::logging::LogLevel::Info, $module, $message $(, $key => $value)*)
     };
 }
 
 #[macro_export]
 macro_rules! warn {
-    ($module:expr, $message:expr) => {
-        $crate::logging::log($crate::logging::LogLevel::Warn, $module, $message)
-    };
-    ($module:expr, $message:expr, $($key:expr => $value:expr),*) => {
-        let mut metadata = serde_json::Map::new();
-        $(
-            metadata.insert($key.to_string(), serde_json::Value::from($value));
-        )*
-        $crate::logging::log_with_metadata(
-            $crate::logging::LogLevel::Warn,
-            $module,
-            $message,
-            serde_json::Value::Object(metadata)
-        )
+    ($module:expr, $message:expr $(, $key:expr => $value:expr)*) => {
+        $crate::__log!($crate::logging::LogLevel::Warn, $module, $message $(, $key => $value)*)
     };
 }
 
 #[macro_export]
 macro_rules! error {
-    ($module:expr, $message:expr) => {
-        $crate::logging::log($crate::logging::LogLevel::Error, $module, $message)
-    };
-    ($module:expr, $message:expr, $($key:expr => $value:expr),*) => {
-        let mut metadata = serde_json::Map::new();
-        $(
-            metadata.insert($key.to_string(), serde_json::Value::from($value));
-        )*
-        $crate::logging::log_with_metadata(
-            $crate::logging::LogLevel::Error,
-            $module,
-            $message,
-            serde_json::Value::Object(metadata)
-        )
+    ($module:expr, $message:expr $(, $key:expr => $value:expr)*) => {
+        $crate::__log!($crate::logging::LogLevel::Error, $module, $message $(, $key => $value)*)
     };
 }
@@ -1,15 +1,28 @@
 
 use synergy_testnet::consensus::consensus_algorithm::ProofOfSynergy;
 use synergy_testnet::rpc;
-use synergy_testnet::logging::{LogLevel, init_logger};
-use synergy_testnet::{info, logging};
-use synergy_testnet::config::load_node_config;
-// use synergy_testnet::p2p; // Temporarily disabled
-use synergy_testnet::block::BlockChain;
+use synergy_testnet::logging::init_logger_from_config;
+use synergy_testnet::info;
+use synergy_testnet::config::{
+    load_layered, load_validated, save_config, ConfigSources, NodeConfig, DEFAULT_NETWORK, NETWORK_PRESETS,
+};
+use synergy_testnet::crypto::keys::KeyPair;
+use synergy_testnet::crypto::{hd, hybrid};
+use synergy_testnet::devnet::{Devnet, DevnetOptions};
+use synergy_testnet::genesis::{self, Genesis, GenesisSpec};
+use synergy_testnet::keystore::{KeyExport, Keystore};
+use synergy_testnet::storage;
+use synergy_testnet::wallet::{WalletManager, WALLET_MANAGER};
+use synergy_testnet::{p2p, shutdown};
 use std::env;
+use std::error::Error;
 use std::fs;
 use std::path::PathBuf;
 use std::process;
+use std::sync::{mpsc, Arc};
+use std::time::Duration;
+use synergy_testnet::health::{HealthReport, HealthStatus};
+use zeroize::Zeroizing;
 
 fn main() {
     let args: Vec<String> = env::args().collect();
@@ -32,8 +45,9 @@ fn main() {
         }
 
         "start" => {
-            // Load configuration
-            let config = match load_node_config(None) {
+            // Load configuration: preset defaults < file < environment < flags
+            let sources = parse_config_args(&args[2..], false).0;
+            let config = match load_validated(&sources) {
                 Ok(config) => config,
                 Err(e) => {
                     eprintln!("Failed to load configuration: {}", e);
@@ -42,46 +56,86 @@ fn main() {
             };
 
             // Initialize logger
-            let log_level = LogLevel::from_str(&config.logging.log_level).unwrap_or(LogLevel::Info);
-            init_logger(
-                log_level,
-                config.logging.enable_console,
-                config.logging.log_file.clone(),
-                config.logging.max_file_size,
-                config.logging.max_files,
-            );
+            if let Err(e) = init_logger_from_config(&config.logging) {
+                eprintln!("Invalid logging configuration: {}", e);
+                process::exit(1);
+            }
 
-            info!("main", "Synergy Testnet Node Starting...");
-            info!("main", "Configuration loaded successfully", "network" => config.network.name.clone(), "consensus" => config.consensus.algorithm.clone());
+            info!("node", "Synergy Testnet Node Starting...");
+            info!("node", "Configuration loaded successfully", "network" => config.network.name.clone(), "consensus" => config.consensus.algorithm.clone());
 
             // Create data directories
             std::fs::create_dir_all("data").expect("Failed to create data directory");
             std::fs::create_dir_all("data/logs").expect("Failed to create logs directory");
             std::fs::create_dir_all("data/chain").expect("Failed to create chain directory");
 
-            info!("main", "Starting the node...");
+            info!("node", "Starting the node...");
+
+            // SIGINT/SIGTERM stop block production and drain RPC before exit
+            shutdown::install_signal_handler();
+
+            rpc::eth::set_chain_id(config.blockchain.chain_id);
+
+            // Node-held wallets start locked; their keys stay encrypted on disk
+            let keystore = Keystore::new(&config.wallet.keystore_dir);
+            match WALLET_MANAGER.lock().unwrap().open_keystore(keystore) {
+                Ok(count) => info!("node", "Keystore opened", "dir" => config.wallet.keystore_dir.clone(), "wallets" => count),
+                Err(e) => {
+                    eprintln!("❌ Failed to read keystore {}: {}", config.wallet.keystore_dir, e);
+                    process::exit(1);
+                }
+            }
+
+            // Refuses a missing or modified genesis, or a chain from another one
+            let mut consensus = match ProofOfSynergy::from_config(&config) {
+                Ok(consensus) => consensus,
+                Err(e) => {
+                    eprintln!("❌ {}", e);
+                    process::exit(1);
+                }
+            };
 
             // Start RPC server in a separate thread
-            let rpc_handle = std::thread::spawn(|| {
-                rpc::rpc_server::start_rpc_server();
+            let rpc_config = config.rpc.clone();
+            let (rpc_done, rpc_stopped) = mpsc::channel();
+            std::thread::spawn(move || {
+                rpc::rpc_server::start_rpc_server(&rpc_config);
+                let _ = rpc_done.send(());
             });
 
             // Node initialized with core systems
-            info!("main", "Node initialized with RPC and consensus systems", "rpc_port" => config.rpc.http_port, "consensus" => config.consensus.algorithm.clone());
+            info!("node", "Node initialized with RPC and consensus systems", "rpc_port" => config.rpc.http_port, "consensus" => config.consensus.algorithm.clone());
 
-            let mut consensus = ProofOfSynergy::new();
             consensus.initialize();
-            consensus.execute();
+            let consensus_handle = consensus.execute();
 
-            info!("main", "Node shutdown gracefully");
+            // Peers handshake against the genesis of the chain consensus just loaded
+            if let Err(e) = p2p::start_p2p_network(Arc::clone(&rpc::rpc_server::CHAIN), &config.p2p.listen_address, &config) {
+                eprintln!("❌ {}", e);
+                process::exit(1);
+            }
+
+            // Runs until a shutdown is requested, then flushes consensus state
+            if consensus_handle.join().is_err() {
+                eprintln!("❌ Consensus thread panicked");
+            }
 
-            // Keep the main thread alive after consensus by joining the RPC thread
-            rpc_handle.join().unwrap();
+            // No more blocks are produced or imported; close peer connections and save bans
+            p2p::stop_p2p_network();
+
+            // Long-lived streams may hold the RPC servers open; don't wait forever
+            let drain_timeout = Duration::from_secs(config.rpc.limits.request_timeout_secs);
+            if rpc_stopped.recv_timeout(drain_timeout).is_err() {
+                eprintln!("⚠️ RPC connections still open after {}s, closing them", drain_timeout.as_secs());
+            }
+
+            info!("node", "Node shutdown gracefully");
         }
 
         "status" => {
             // Load configuration
-            let config = match load_node_config(None) {
+            let (sources, rest) = parse_config_args(&args[2..], true);
+            let config = match load_validated(&sources) {
                 Ok(config) => config,
                 Err(e) => {
                     eprintln!("Failed to load configuration: {}", e);
@@ -89,17 +143,87 @@ fn main() {
                 }
             };
 
-            // Initialize logger
-            let log_level = LogLevel::from_str(&config.logging.log_level).unwrap_or(LogLevel::Info);
-            init_logger(
-                log_level,
-                config.logging.enable_console,
-                config.logging.log_file.clone(),
-                config.logging.max_file_size,
-                config.logging.max_files,
-            );
+            // Query a running node, by default the local one
+            let base_url = rest
+                .first()
+                .cloned()
+                .unwrap_or_else(|| format!("http://127.0.0.1:{}", config.rpc.http_port));
+            let url = format!("{}/health", base_url.trim_end_matches('/'));
+
+            let report = reqwest::blocking::Client::new()
+                .get(&url)
+                .timeout(Duration::from_secs(5))
+                .send()
+                .and_then(|response| response.json::<HealthReport>());
+            let report = match report {
+                Ok(report) => report,
+                Err(e) => {
+                    eprintln!("❌ Node is not reachable at {}: {}", url, e);
+                    process::exit(1);
+                }
+            };
+
+            print_health(&report);
+            if report.status != HealthStatus::Healthy {
+                process::exit(1);
+            }
+        }
+
+        "config" => {
+            let action = args.get(2).map(String::as_str).unwrap_or("");
+            let args = args.get(3..).unwrap_or(&[]);
+            match action {
+                "show" => config_show(args),
+                "validate" => config_validate(args),
+                "init" => config_init(args),
+                _ => {
+                    eprintln!("Usage: synergy-testnet config <show|validate|init> [--config <file>] [--network <name>] [--set key=value]");
+                    process::exit(1);
+                }
+            }
+        }
+
+        "genesis" => {
+            let action = args.get(2).map(String::as_str).unwrap_or("");
+            let args = args.get(3..).unwrap_or(&[]);
+            match action {
+                "build" => genesis_build(args),
+                "hash" => genesis_hash(args),
+                _ => {
+                    eprintln!("Usage: synergy-testnet genesis <build [--spec <file>] [--output <file>] [--force] | hash [--genesis <file>]>");
+                    process::exit(1);
+                }
+            }
+        }
+
+        "devnet" => {
+            let action = args.get(2).map(String::as_str).unwrap_or("");
+            let args = args.get(3..).unwrap_or(&[]);
+            match action {
+                "up" => devnet_up(args),
+                "down" => devnet_down(args),
+                _ => {
+                    eprintln!("Usage: synergy-testnet devnet <up|down> [--nodes <n>] [--dir <path>] [--rpc-port <port>] [--p2p-port <port>] [--block-time <secs>]");
+                    process::exit(1);
+                }
+            }
+        }
 
-            info!("main", "Node status: Online");
+        "keystore" => {
+            let action = args.get(2).map(String::as_str).unwrap_or("");
+            let args = args.get(3..).unwrap_or(&[]);
+            match action {
+                "create" | "import" | "export" | "list" | "passwd" | "mnemonic" | "recover" => keystore_command(action, args),
+                _ => {
+                    eprintln!("Usage: synergy-testnet keystore <create | import <file> | export <address> | list | passwd <address> | mnemonic | recover> [--dir <path>] [--password-file <file>] [--new-password-file <file>] [--output <file>] [--words <12|24>] [--accounts <n>] [--mnemonic-file <file>] [--passphrase-file <file>] [--hybrid] [--secp256k1-key-file <file>]");
+                    process::exit(1);
+                }
+            }
+        }
+
+        "openrpc" => {
+            // Regenerate the client spec with: synergy-testnet openrpc > crates/synergy-client/openrpc.json
+            println!("{}", serde_json::to_string_pretty(rpc::openrpc::document()).unwrap());
         }
 
         _ => {
@@ -108,3 +232,474 @@ fn main() {
         }
     }
 }
+
+/// Splits the configuration flags out of `args`, exiting on malformed
+/// flags or, unless `allow_rest`, on anything left over.
+fn parse_config_args(args: &[String], allow_rest: bool) -> (ConfigSources, Vec<String>) {
+    match ConfigSources::from_args(args) {
+        Ok((_, rest)) if !allow_rest && !rest.is_empty() => {
+            eprintln!("Unexpected argument: {}", rest[0]);
+            process::exit(1);
+        }
+        Ok(parsed) => parsed,
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+    }
+}
+
+/// Prints the effective configuration after every layer is applied.
+fn config_show(args: &[String]) {
+    let sources = parse_config_args(args, false).0;
+    let loaded = match load_layered(&sources) {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            eprintln!("Failed to load configuration: {}", e);
+            process::exit(1);
+        }
+    };
+    // Keep stdout valid TOML
+    for warning in &loaded.warnings {
+        eprintln!("⚠️ {}", warning);
+    }
+    match loaded.config.to_toml() {
+        Ok(toml) => print!("{}", toml),
+        Err(e) => {
+            eprintln!("Failed to serialize configuration: {}", e);
+            process::exit(1);
+        }
+    }
+}
+
+fn config_validate(args: &[String]) {
+    let sources = parse_config_args(args, false).0;
+    let loaded = match load_layered(&sources) {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            process::exit(1);
+        }
+    };
+    for warning in &loaded.warnings {
+        println!("⚠️ {}", warning);
+    }
+    let source = loaded.path.as_deref().unwrap_or("defaults");
+    match loaded.config.validate() {
+        Ok(()) => println!("✅ Configuration is valid ({}, network {})", source, sources.network()),
+        Err(errors) => {
+            for error in &errors {
+                println!("❌ {}", error);
+            }
+            println!("{} problem(s) found in {}", errors.len(), source);
+            process::exit(1);
+        }
+    }
+}
+
+/// Writes the defaults of a network preset to a file.
+fn config_init(args: &[String]) {
+    let mut network = DEFAULT_NETWORK.to_string();
+    let mut output = None;
+    let mut force = false;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--network" => network = args.next().cloned().unwrap_or_default(),
+            "--output" => output = args.next().cloned(),
+            "--force" => force = true,
+            _ => {
+                eprintln!("Usage: synergy-testnet config init [--network <{}>] [--output <file>] [--force]", NETWORK_PRESETS.join("|"));
+                process::exit(1);
+            }
+        }
+    }
+
+    let config = match NodeConfig::preset(&network) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            process::exit(1);
+        }
+    };
+    let output = output.unwrap_or_else(|| format!("config/{}.toml", network));
+    if PathBuf::from(&output).exists() && !force {
+        eprintln!("❌ {} already exists; pass --force to overwrite it", output);
+        process::exit(1);
+    }
+    if let Some(parent) = PathBuf::from(&output).parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).expect("Failed to create config directory");
+    }
+    if let Err(e) = save_config(&config, &output) {
+        eprintln!("❌ Failed to write {}: {}", output, e);
+        process::exit(1);
+    }
+    println!("✅ Wrote {} defaults to {}", network, output);
+    println!("   Start the node with: synergy-testnet start --config {}", output);
+}
+
+/// Builds a genesis file from a spec and prints its hash.
+fn genesis_build(args: &[String]) {
+    let mut spec_path = genesis::DEFAULT_SPEC_PATH.to_string();
+    let mut output = genesis::DEFAULT_GENESIS_PATH.to_string();
+    let mut force = false;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--spec" => spec_path = args.next().cloned().unwrap_or_default(),
+            "--output" => output = args.next().cloned().unwrap_or_default(),
+            "--force" => force = true,
+            _ => {
+                eprintln!("Usage: synergy-testnet genesis build [--spec <file>] [--output <file>] [--force]");
+                process::exit(1);
+            }
+        }
+    }
+
+    let spec = match GenesisSpec::load(&spec_path) {
+        Ok(spec) => spec,
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            process::exit(1);
+        }
+    };
+    let genesis = match spec.build() {
+        Ok(genesis) => genesis,
+        Err(errors) => {
+            for error in &errors {
+                eprintln!("❌ {}", error);
+            }
+            eprintln!("{} problem(s) found in {}", errors.len(), spec_path);
+            process::exit(1);
+        }
+    };
+
+    // Replacing a genesis forks the network; make that deliberate
+    if let Ok(existing) = Genesis::load(&output) {
+        if existing.genesis_hash != genesis.genesis_hash && !force {
+            eprintln!(
+                "❌ {} has genesis {}; the spec builds {}. Pass --force to replace it",
+                output, existing.genesis_hash, genesis.genesis_hash
+            );
+            process::exit(1);
+        }
+    }
+    if let Err(e) = genesis.save(&output) {
+        eprintln!("❌ Failed to write {}: {}", output, e);
+        process::exit(1);
+    }
+    println!("✅ Wrote {} to {}", genesis.meta.network, output);
+    println!("   Genesis hash: {}", genesis.genesis_hash);
+}
+
+/// Verifies a genesis file and prints its hash.
+fn genesis_hash(args: &[String]) {
+    let path = match args {
+        [] => genesis::DEFAULT_GENESIS_PATH.to_string(),
+        [flag, path] if flag == "--genesis" => path.clone(),
+        _ => {
+            eprintln!("Usage: synergy-testnet genesis hash [--genesis <file>]");
+            process::exit(1);
+        }
+    };
+    match Genesis::load(&path) {
+        Ok(genesis) => println!("{}", genesis.genesis_hash),
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            process::exit(1);
+        }
+    }
+}
+
+fn devnet_options(args: &[String]) -> DevnetOptions {
+    let mut options = DevnetOptions::default();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let value = args.next();
+        let parsed = match (arg.as_str(), value) {
+            ("--nodes", Some(value)) => value.parse().map(|nodes| options.nodes = nodes).is_ok(),
+            ("--dir", Some(value)) => {
+                options.dir = PathBuf::from(value);
+                true
+            }
+            ("--rpc-port", Some(value)) => value.parse().map(|port| options.rpc_port = port).is_ok(),
+            ("--p2p-port", Some(value)) => value.parse().map(|port| options.p2p_port = port).is_ok(),
+            ("--block-time", Some(value)) => value.parse().map(|secs| options.block_time_secs = secs).is_ok(),
+            _ => false,
+        };
+        if !parsed {
+            eprintln!("Invalid devnet option: {} {}", arg, value.map_or("", String::as_str));
+            process::exit(1);
+        }
+    }
+    options
+}
+
+/// Generates a devnet, runs it in the foreground with its logs interleaved
+/// and tears it down on Ctrl-C.
+fn devnet_up(args: &[String]) {
+    let mut devnet = match Devnet::generate(devnet_options(args)) {
+        Ok(devnet) => devnet,
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            process::exit(1);
+        }
+    };
+    println!("🧬 Devnet genesis {} in {}", devnet.genesis_hash, devnet.options.dir.display());
+    for node in &devnet.nodes {
+        println!("   {}  {}  rpc {}  ws {}  grpc {}  p2p {}", node.name, node.address, node.rpc_port, node.ws_port, node.grpc_port, node.p2p_port);
+    }
+
+    shutdown::install_signal_handler();
+    if let Err(e) = devnet.start() {
+        eprintln!("❌ {}", e);
+        process::exit(1);
+    }
+    devnet.follow_logs(shutdown::is_requested);
+    devnet.stop();
+}
+
+/// Stops a devnet left running by an `up` that did not exit cleanly.
+fn devnet_down(args: &[String]) {
+    let options = devnet_options(args);
+    match Devnet::stop_dir(&options.dir) {
+        Ok(stopped) => println!("🛑 Sent SIGTERM to {} devnet node(s) in {}", stopped, options.dir.display()),
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            process::exit(1);
+        }
+    }
+}
+
+/// Options shared by the `keystore` subcommands.
+struct KeystoreArgs {
+    keystore: Keystore,
+    target: Option<String>,
+    password_file: Option<String>,
+    new_password_file: Option<String>,
+    output: Option<String>,
+    words: Option<String>,
+    accounts: Option<String>,
+    mnemonic_file: Option<String>,
+    passphrase_file: Option<String>,
+    hybrid: bool,
+    secp256k1_key_file: Option<String>,
+}
+
+/// Manages the encrypted key files of node-held wallets. The directory is
+/// `--dir`, or `wallet.keystore_dir` from the node configuration.
+fn keystore_command(action: &str, args: &[String]) {
+    let (sources, rest) = parse_config_args(args, true);
+    let (mut dir, mut target, mut password_file, mut new_password_file, mut output) = (None, None, None, None, None);
+    let (mut words, mut accounts, mut mnemonic_file, mut passphrase_file) = (None, None, None, None);
+    let (mut hybrid, mut secp256k1_key_file) = (false, None);
+    let mut rest = rest.iter();
+    while let Some(arg) = rest.next() {
+        let slot = match arg.as_str() {
+            "--dir" => &mut dir,
+            "--password-file" => &mut password_file,
+            "--new-password-file" => &mut new_password_file,
+            "--output" => &mut output,
+            "--words" => &mut words,
+            "--accounts" => &mut accounts,
+            "--mnemonic-file" => &mut mnemonic_file,
+            "--passphrase-file" => &mut passphrase_file,
+            "--secp256k1-key-file" => &mut secp256k1_key_file,
+            "--hybrid" => {
+                hybrid = true;
+                continue;
+            }
+            _ if !arg.starts_with("--") && target.is_none() => {
+                target = Some(arg.clone());
+                continue;
+            }
+            _ => {
+                eprintln!("Unexpected argument: {}", arg);
+                process::exit(1);
+            }
+        };
+        *slot = rest.next().cloned();
+    }
+    let dir = dir.unwrap_or_else(|| match load_layered(&sources) {
+        Ok(loaded) => loaded.config.wallet.keystore_dir,
+        Err(e) => {
+            eprintln!("Failed to load configuration: {}", e);
+            process::exit(1);
+        }
+    });
+
+    let args = KeystoreArgs {
+        keystore: Keystore::new(dir),
+        target,
+        password_file,
+        new_password_file,
+        output,
+        words,
+        accounts,
+        mnemonic_file,
+        passphrase_file,
+        hybrid: hybrid || secp256k1_key_file.is_some(),
+        secp256k1_key_file,
+    };
+    if let Err(e) = run_keystore_command(action, &args) {
+        eprintln!("❌ {}", e);
+        process::exit(1);
+    }
+}
+
+fn run_keystore_command(action: &str, args: &KeystoreArgs) -> Result<(), Box<dyn Error>> {
+    let keystore = &args.keystore;
+    let target = || {
+        let what = if action == "import" { "a key file" } else { "an address" };
+        args.target.clone().ok_or_else(|| format!("keystore {} needs {}", action, what))
+    };
+    match action {
+        "create" => {
+            // A hybrid account pairs a new ML-DSA key with a new or existing secp256k1 key
+            let secp256k1 = match &args.secp256k1_key_file {
+                Some(file) => Some(hybrid::parse_private_key(&Zeroizing::new(fs::read_to_string(file)?))?),
+                None if args.hybrid => Some(hybrid::generate_key()),
+                None => None,
+            };
+            let password = read_password(args.password_file.as_deref(), "New password", true)?;
+            let entry = match &secp256k1 {
+                Some(secp256k1) => keystore.add(&KeyPair::generate().with_secp256k1(secp256k1.clone()), &password)?,
+                None => keystore.create(&password)?,
+            };
+            println!("🔑 Created {}", entry.address);
+            if let Some(secp256k1) = &secp256k1 {
+                println!("   Hybrid account: ML-DSA-87 + secp256k1 key of {}", hybrid::eth_address(secp256k1));
+            }
+            println!("   Key file: {}", entry.path.display());
+            println!("   Back up the key file and the password; neither can be recovered.");
+        }
+        "import" => {
+            let file = target()?;
+            let content = Zeroizing::new(fs::read_to_string(&file)?);
+            let export: KeyExport = serde_json::from_str(&content).map_err(|e| format!("{} is not an exported key: {}", file, e))?;
+            let password = read_password(args.password_file.as_deref(), "New password", true)?;
+            let entry = keystore.import(&export, &password)?;
+            println!("🔑 Imported {} into {}", entry.address, entry.path.display());
+        }
+        "export" => {
+            let address = target()?;
+            let password = read_password(args.password_file.as_deref(), "Password", false)?;
+            let export = keystore.export(&address, &password)?;
+            let json = Zeroizing::new(serde_json::to_string_pretty(&export)?);
+            match &args.output {
+                Some(output) => {
+                    storage::write_private_atomic(output, json.as_bytes())?;
+                    eprintln!("⚠️ Wrote the unencrypted private key of {} to {}", address, output);
+                }
+                None => {
+                    eprintln!("⚠️ The output below is an unencrypted private key");
+                    println!("{}", *json);
+                }
+            }
+        }
+        "list" => {
+            let entries = keystore.list()?;
+            if entries.is_empty() {
+                println!("No keys in {}", keystore.dir().display());
+            }
+            for entry in entries {
+                println!("{}  {}", entry.address, entry.path.display());
+            }
+        }
+        "passwd" => {
+            let address = target()?;
+            let old_password = read_password(args.password_file.as_deref(), "Current password", false)?;
+            let new_password = read_password(args.new_password_file.as_deref(), "New password", true)?;
+            keystore.change_password(&address, &old_password, &new_password)?;
+            println!("✅ Changed the password of {}", address);
+        }
+        "mnemonic" => {
+            let words = match args.words.as_deref() {
+                Some(words) => words.parse().map_err(|_| format!("--words must be a number, not {}", words))?,
+                None => 24,
+            };
+            let mnemonic = Zeroizing::new(hd::generate_mnemonic(words)?.to_string());
+            let password = read_password(args.password_file.as_deref(), "New password", true)?;
+            let addresses = restore_mnemonic(args, &mnemonic, &password)?;
+            eprintln!("⚠️ Write down the recovery phrase below and keep it offline; anyone who has it controls these accounts");
+            println!("{}", *mnemonic);
+            for address in addresses {
+                println!("🔑 Created {}", address);
+            }
+        }
+        "recover" => {
+            let mnemonic = read_password(args.mnemonic_file.as_deref(), "Recovery phrase", false)?;
+            let password = read_password(args.password_file.as_deref(), "New password", true)?;
+            for address in restore_mnemonic(args, &mnemonic, &password)? {
+                println!("🔑 Recovered {}", address);
+            }
+        }
+        _ => unreachable!(),
+    }
+    Ok(())
+}
+
+/// Stores the first `--accounts` (default 1) accounts of a recovery phrase
+/// in the keystore.
+fn restore_mnemonic(args: &KeystoreArgs, mnemonic: &str, password: &str) -> Result<Vec<String>, Box<dyn Error>> {
+    let accounts = match args.accounts.as_deref() {
+        Some(accounts) => accounts.parse().map_err(|_| format!("--accounts must be a number, not {}", accounts))?,
+        None => 1,
+    };
+    let passphrase = match args.passphrase_file.as_deref() {
+        Some(file) => read_password(Some(file), "Passphrase", false)?,
+        None => Zeroizing::new(String::new()),
+    };
+    let mut manager = WalletManager::new();
+    manager.open_keystore(args.keystore.clone())?;
+    Ok(manager.create_from_mnemonic(mnemonic, &passphrase, password, accounts)?)
+}
+
+/// Reads a password from the first line of `file`, or prompts for it on
+/// the terminal, twice when `confirm` is set.
+fn read_password(file: Option<&str>, prompt: &str, confirm: bool) -> Result<Zeroizing<String>, Box<dyn Error>> {
+    if let Some(file) = file {
+        let content = Zeroizing::new(fs::read_to_string(file)?);
+        return Ok(Zeroizing::new(content.lines().next().unwrap_or("").to_string()));
+    }
+    let password = Zeroizing::new(rpassword::prompt_password(format!("{}: ", prompt))?);
+    if confirm {
+        let repeated = Zeroizing::new(rpassword::prompt_password(format!("Repeat {}: ", prompt.to_lowercase()))?);
+        if *repeated != *password {
+            return Err("Passwords do not match".into());
+        }
+    }
+    Ok(password)
+}
+
+fn print_health(report: &HealthReport) {
+    let icon = match report.status {
+        HealthStatus::Healthy => "🟢",
+        HealthStatus::Degraded => "🟡",
+        HealthStatus::Unhealthy => "🔴",
+    };
+    println!("{} Node status: {:?}", icon, report.status);
+
+    match (report.best_peer_height, report.sync_lag) {
+        (Some(best), Some(lag)) => println!("   Height: {} (best peer {}, {} behind)", report.height, best, lag),
+        _ => println!("   Height: {} (sync unknown: no peer status)", report.height),
+    }
+    match report.last_block_age_secs {
+        Some(age) => println!("   Last block: {}s ago", age),
+        None => println!("   Last block: none"),
+    }
+    println!("   Peers: {}", report.peer_count);
+    match report.consensus_heartbeat_age_secs {
+        Some(age) => println!("   Consensus heartbeat: {}s ago", age),
+        None => println!("   Consensus heartbeat: not started"),
+    }
+    println!("   Storage: {}", if report.storage_writable { "writable" } else { "NOT writable" });
+    match report.clock_drift_secs {
+        Some(drift) => println!("   Clock drift: {:+}s", drift),
+        None => println!("   Clock drift: unknown (no peer status)"),
+    }
+
+    for failure in &report.failures {
+        println!("⚠️ {}", failure);
+    }
+}
@@ -0,0 +1,231 @@
+//! Prometheus metrics, served at `/metrics` on the HTTP RPC port.
+//!
+//! Counters and histograms are updated where things happen (consensus,
+//! validator registry, AIVM, RPC dispatch). Gauges that mirror node state,
+//! such as chain height or token supply, are refreshed on every scrape.
+
+use std::collections::HashMap;
+
+use lazy_static::lazy_static;
+use prometheus::{
+    Encoder, Gauge, GaugeVec, Histogram, HistogramOpts, HistogramVec, IntCounterVec,
+    IntGauge, IntGaugeVec, Opts, Registry, TextEncoder,
+};
+
+use crate::aivm::runtime::{AIVMExecutionResult, ContractType};
+use crate::p2p::P2P_NETWORK;
+use crate::rpc::rpc_server::{AIVM_RUNTIME, CHAIN, TX_POOL};
+use crate::token::TOKEN_MANAGER;
+
+/// Content type of the text exposition format.
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+lazy_static! {
+    pub static ref REGISTRY: Registry = Registry::new();
+
+    // Chain and mempool
+    pub static ref BLOCK_HEIGHT: IntGauge = register(IntGauge::new(
+        "synergy_block_height", "Height of the latest block",
+    ));
+    pub static ref BLOCK_INTERVAL_SECONDS: Histogram = register(Histogram::with_opts(
+        HistogramOpts::new("synergy_block_interval_seconds", "Time between consecutive blocks")
+            .buckets(vec![1.0, 2.0, 5.0, 7.5, 10.0, 15.0, 30.0, 60.0]),
+    ));
+    pub static ref BLOCK_TRANSACTIONS: Histogram = register(Histogram::with_opts(
+        HistogramOpts::new("synergy_block_transactions", "Transactions included per block")
+            .buckets(vec![0.0, 1.0, 5.0, 10.0, 50.0, 100.0, 500.0, 1000.0]),
+    ));
+    pub static ref MEMPOOL_TRANSACTIONS: IntGauge = register(IntGauge::new(
+        "synergy_mempool_transactions", "Transactions waiting in the pool",
+    ));
+
+    // Network and RPC
+    pub static ref P2P_PEERS: IntGauge = register(IntGauge::new(
+        "synergy_p2p_peers", "Connected peers",
+    ));
+    pub static ref RPC_REQUEST_DURATION_SECONDS: HistogramVec = register(HistogramVec::new(
+        HistogramOpts::new("synergy_rpc_request_duration_seconds", "JSON-RPC call latency by method"),
+        &["method"],
+    ));
+    pub static ref RPC_ERRORS_TOTAL: IntCounterVec = register(IntCounterVec::new(
+        Opts::new("synergy_rpc_errors_total", "JSON-RPC calls that returned an error, by method and code"),
+        &["method", "code"],
+    ));
+
+    // Validators and tokens
+    pub static ref VALIDATOR_PROPOSALS_TOTAL: IntCounterVec = register(IntCounterVec::new(
+        Opts::new("synergy_validator_proposals_total", "Blocks proposed by each validator"),
+        &["validator"],
+    ));
+    pub static ref VALIDATOR_MISSED_BLOCKS_TOTAL: IntCounterVec = register(IntCounterVec::new(
+        Opts::new("synergy_validator_missed_blocks_total", "Blocks each validator failed to propose"),
+        &["validator"],
+    ));
+    pub static ref TOKEN_SUPPLY: GaugeVec = register(GaugeVec::new(
+        Opts::new("synergy_token_supply", "Total supply of each token, in base units"),
+        &["token"],
+    ));
+
+    // AIVM
+    pub static ref AIVM_EXECUTIONS_TOTAL: IntCounterVec = register(IntCounterVec::new(
+        Opts::new("synergy_aivm_executions_total", "Contract executions by contract type and outcome"),
+        &["contract_type", "status"],
+    ));
+    pub static ref AIVM_GAS_USED_TOTAL: IntCounterVec = register(IntCounterVec::new(
+        Opts::new("synergy_aivm_gas_used_total", "Gas consumed by contract executions"),
+        &["contract_type"],
+    ));
+
+    // Distributed AI
+    pub static ref AI_COMPUTATIONS: IntGaugeVec = register(IntGaugeVec::new(
+        Opts::new("synergy_ai_computations", "Distributed AI computations known to the node, by status"),
+        &["status"],
+    ));
+    pub static ref AI_TASKS: IntGaugeVec = register(IntGaugeVec::new(
+        Opts::new("synergy_ai_tasks", "Validator AI tasks known to the node, by status"),
+        &["status"],
+    ));
+    pub static ref AI_COMPUTATION_DURATION_SECONDS: Histogram = register(Histogram::with_opts(
+        HistogramOpts::new("synergy_ai_computation_duration_seconds", "Time from starting a distributed AI computation to its aggregated result")
+            .buckets(vec![1.0, 5.0, 15.0, 30.0, 60.0, 120.0, 300.0, 600.0]),
+    ));
+    pub static ref AI_TASK_DURATION_SECONDS: Histogram = register(Histogram::with_opts(
+        HistogramOpts::new("synergy_ai_task_duration_seconds", "Time from assigning a validator AI task to its partial result")
+            .buckets(vec![1.0, 5.0, 15.0, 30.0, 60.0, 120.0, 300.0, 600.0]),
+    ));
+    pub static ref AI_REWARDS_DISTRIBUTED: Gauge = register(Gauge::new(
+        "synergy_ai_rewards_distributed", "Total AI rewards distributed, in base units",
+    ));
+    pub static ref AI_PROVIDERS: IntGaugeVec = register(IntGaugeVec::new(
+        Opts::new("synergy_ai_providers", "Registered AI providers, by status"),
+        &["status"],
+    ));
+    pub static ref AI_PROVIDER_QUEUE_SIZE: IntGauge = register(IntGauge::new(
+        "synergy_ai_provider_queue_size", "Tasks waiting for an AI provider",
+    ));
+    pub static ref AI_PROVIDER_TASKS: IntGaugeVec = register(IntGaugeVec::new(
+        Opts::new("synergy_ai_provider_tasks", "Tasks handled by AI providers, by outcome"),
+        &["status"],
+    ));
+}
+
+fn register<M: prometheus::core::Collector + Clone + 'static>(metric: prometheus::Result<M>) -> M {
+    let metric = metric.expect("Invalid metric definition");
+    REGISTRY
+        .register(Box::new(metric.clone()))
+        .expect("Metric registered twice");
+    metric
+}
+
+/// Records a new block. `interval_secs` is the time since the previous
+/// block, if known.
+pub fn record_block(height: u64, transactions: usize, interval_secs: Option<f64>) {
+    BLOCK_HEIGHT.set(height as i64);
+    BLOCK_TRANSACTIONS.observe(transactions as f64);
+    if let Some(interval) = interval_secs {
+        BLOCK_INTERVAL_SECONDS.observe(interval);
+    }
+}
+
+/// Records the latency and outcome of one JSON-RPC call. Unknown methods
+/// are grouped under `unknown` so clients cannot create label values.
+pub fn record_rpc_call(method: &str, seconds: f64, error_code: Option<i64>) {
+    let method = match error_code {
+        Some(crate::rpc::error::METHOD_NOT_FOUND) => "unknown",
+        _ => method,
+    };
+    RPC_REQUEST_DURATION_SECONDS.with_label_values(&[method]).observe(seconds);
+    if let Some(code) = error_code {
+        RPC_ERRORS_TOTAL.with_label_values(&[method, &code.to_string()]).inc();
+    }
+}
+
+/// Counts one contract execution and the gas it used. Executions that
+/// could not run at all are counted with status `error`.
+pub fn record_aivm_execution(contract_type: &ContractType, outcome: &Result<AIVMExecutionResult, String>) {
+    let contract_type = format!("{:?}", contract_type);
+    match outcome {
+        Ok(result) => {
+            let status = if result.success { "success" } else { "failure" };
+            AIVM_EXECUTIONS_TOTAL.with_label_values(&[&contract_type, status]).inc();
+            AIVM_GAS_USED_TOTAL.with_label_values(&[&contract_type]).inc_by(result.gas_used);
+        }
+        Err(_) => AIVM_EXECUTIONS_TOTAL.with_label_values(&[&contract_type, "error"]).inc(),
+    }
+}
+
+/// Updates the gauges that mirror node state and encodes every metric in
+/// the Prometheus text format.
+pub fn render() -> String {
+    register_all();
+    refresh();
+
+    let mut buffer = Vec::new();
+    TextEncoder::new()
+        .encode(&REGISTRY.gather(), &mut buffer)
+        .expect("Failed to encode metrics");
+    String::from_utf8(buffer).unwrap_or_default()
+}
+
+/// Metrics are registered on first use; this makes sure the unlabelled ones
+/// that have not seen an event yet are still exported. Labelled metrics
+/// appear with their first series.
+fn register_all() {
+    lazy_static::initialize(&BLOCK_INTERVAL_SECONDS);
+    lazy_static::initialize(&BLOCK_TRANSACTIONS);
+    lazy_static::initialize(&RPC_REQUEST_DURATION_SECONDS);
+    lazy_static::initialize(&RPC_ERRORS_TOTAL);
+    lazy_static::initialize(&VALIDATOR_PROPOSALS_TOTAL);
+    lazy_static::initialize(&VALIDATOR_MISSED_BLOCKS_TOTAL);
+    lazy_static::initialize(&AIVM_EXECUTIONS_TOTAL);
+    lazy_static::initialize(&AIVM_GAS_USED_TOTAL);
+    lazy_static::initialize(&AI_COMPUTATION_DURATION_SECONDS);
+    lazy_static::initialize(&AI_TASK_DURATION_SECONDS);
+}
+
+fn refresh() {
+    if let Some(block) = CHAIN.lock().unwrap().last() {
+        BLOCK_HEIGHT.set(block.block_index as i64);
+    }
+    MEMPOOL_TRANSACTIONS.set(TX_POOL.lock().unwrap().len() as i64);
+
+    let peers = P2P_NETWORK.lock().unwrap().as_ref().map_or(0, |network| network.get_peer_count());
+    P2P_PEERS.set(peers as i64);
+
+    for token in TOKEN_MANAGER.get_all_tokens() {
+        TOKEN_SUPPLY
+            .with_label_values(&[&token.symbol])
+            .set(TOKEN_MANAGER.total_supply(&token.symbol) as f64);
+    }
+
+    let ai_stats = AIVM_RUNTIME.distributed_ai().get_ai_network_stats();
+    let total = stat(&ai_stats, "total_computations") as i64;
+    let completed = stat(&ai_stats, "completed_computations") as i64;
+    let failed = stat(&ai_stats, "failed_computations") as i64;
+    AI_COMPUTATIONS.with_label_values(&["completed"]).set(completed);
+    AI_COMPUTATIONS.with_label_values(&["failed"]).set(failed);
+    AI_COMPUTATIONS.with_label_values(&["pending"]).set((total - completed - failed).max(0));
+
+    let total_tasks = stat(&ai_stats, "total_tasks") as i64;
+    let completed_tasks = stat(&ai_stats, "completed_tasks") as i64;
+    AI_TASKS.with_label_values(&["completed"]).set(completed_tasks);
+    AI_TASKS.with_label_values(&["pending"]).set((total_tasks - completed_tasks).max(0));
+    AI_REWARDS_DISTRIBUTED.set(stat(&ai_stats, "total_ai_rewards_distributed"));
+
+    let provider_stats = AIVM_RUNTIME.providers().get_network_stats();
+    let total_providers = stat(&provider_stats, "total_providers") as i64;
+    let online_providers = stat(&provider_stats, "online_providers") as i64;
+    AI_PROVIDERS.with_label_values(&["online"]).set(online_providers);
+    AI_PROVIDERS.with_label_values(&["offline"]).set((total_providers - online_providers).max(0));
+    AI_PROVIDER_QUEUE_SIZE.set(stat(&provider_stats, "queue_size") as i64);
+
+    let provider_tasks = stat(&provider_stats, "total_tasks") as i64;
+    let provider_completed = stat(&provider_stats, "completed_tasks") as i64;
+    AI_PROVIDER_TASKS.with_label_values(&["completed"]).set(provider_completed);
+    AI_PROVIDER_TASKS.with_label_values(&["failed"]).set((provider_tasks - provider_completed).max(0));
+}
+
+/// Reads a numeric entry from the string maps returned by the AI stats APIs.
+fn stat(stats: &HashMap<String, String>, key: &str) -> f64 {
+    stats.get(key).and_then(|value| value.parse().ok()).unwrap_or(0.0)
+}
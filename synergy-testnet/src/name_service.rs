@@ -0,0 +1,362 @@
+//! Synergy Name Service (SNS): human-readable `.syn` names for addresses.
+//!
+//! Names are registered, renewed, transferred and edited with `sns:`
+//! transactions. Registering or renewing a name burns a
+//! yearly fee in SNRG from the sender, which is charged by the consensus
+//! engine when the transaction is included. A registration lasts whole
+//! years, counted in blocks; once it expires the name stops resolving,
+//! its owner has `GRACE_PERIOD` blocks to renew it, and after that anyone
+//! may register it.
+//!
+//! A name resolves to an address, its owner unless set otherwise, and
+//! carries free-form text records. An address may pick one of the names
+//! that resolve to it as its primary name for reverse resolution.
+//!
+//! `NameRegistry` is derived from the chain alone and rebuilt on start.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+
+use lazy_static::lazy_static;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::block::{Block, BlockChain};
+use crate::transaction::Transaction;
+
+/// The top-level domain every name ends in.
+pub const TLD: &str = ".syn";
+
+/// Blocks in a registration year at the 5 second target block time.
+pub const BLOCKS_PER_YEAR: u64 = 365 * 24 * 60 * 60 / 5;
+
+/// Blocks after expiry during which only the owner can renew a name.
+pub const GRACE_PERIOD: u64 = BLOCKS_PER_YEAR / 4;
+
+/// Longest a name can be registered ahead, in years.
+pub const MAX_YEARS: u64 = 10;
+
+const MIN_LABEL_LEN: usize = 3;
+const MAX_LABEL_LEN: usize = 63;
+const MAX_TEXT_KEY_LEN: usize = 64;
+const MAX_TEXT_VALUE_LEN: usize = 1024;
+const MAX_TEXT_RECORDS: usize = 32;
+
+const DATA_PREFIX: &str = "sns:";
+
+/// Whether `value` is meant as a name rather than an address.
+pub fn is_name(value: &str) -> bool {
+    value.to_ascii_lowercase().ends_with(TLD)
+}
+
+/// The canonical, lowercase form of `name`. Fails unless it is a label of
+/// 3 to 63 letters, digits and inner hyphens followed by `.syn`.
+pub fn normalize(name: &str) -> Result<String, String> {
+    let name = name.trim().to_ascii_lowercase();
+    let label = name.strip_suffix(TLD).ok_or_else(|| format!("{} does not end in {}", name, TLD))?;
+    if !(MIN_LABEL_LEN..=MAX_LABEL_LEN).contains(&label.len()) {
+        return Err(format!("A name is {} to {} characters before {}", MIN_LABEL_LEN, MAX_LABEL_LEN, TLD));
+    }
+    if !label.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-') {
+        return Err(format!("{} may only contain letters, digits and hyphens", name));
+    }
+    if label.starts_with('-') || label.ends_with('-') {
+        return Err(format!("{} may not start or end with a hyphen", name));
+    }
+    Ok(name)
+}
+
+/// Yearly fee in whole SNRG for a normalized `name`; shorter names cost
+/// more.
+pub fn annual_fee(name: &str) -> u64 {
+    match name.len().saturating_sub(TLD.len()) {
+        3 => 640,
+        4 => 160,
+        _ => 5,
+    }
+}
+
+/// Payload of an `sns:` transaction.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum NameOp {
+    /// Registers an available name for `years`, owned by `owner` or the sender.
+    Register { name: String, years: u64, owner: Option<String> },
+    /// Extends a registration by `years`. Anyone may pay for a name that
+    /// resolves; only its owner for one in its grace period.
+    Renew { name: String, years: u64 },
+    /// Hands the name to `to`, pointing it at `to` and clearing its text records.
+    Transfer { name: String, to: String },
+    /// Points the name at `address`, or back at its owner.
+    SetAddress { name: String, address: Option<String> },
+    /// Sets the text record `key`, or removes it without a value.
+    SetText { name: String, key: String, value: Option<String> },
+    /// Makes `name` the sender's name for reverse resolution, or clears it.
+    SetPrimary { name: Option<String> },
+}
+
+impl NameOp {
+    /// `data` of a name service transaction.
+    pub fn to_data(&self) -> String {
+        format!("{}{}", DATA_PREFIX, serde_json::to_string(self).expect("serializable name operation"))
+    }
+
+    /// The operation `tx` carries, or `None` if it is not a name service
+    /// transaction.
+    pub fn from_transaction(tx: &Transaction) -> Option<Result<Self, String>> {
+        let payload = tx.data.as_deref()?.strip_prefix(DATA_PREFIX)?;
+        Some(serde_json::from_str::<NameOp>(payload).map_err(|e| format!("Invalid name service operation: {}", e)))
+    }
+
+    /// The name the operation acts on.
+    pub fn name(&self) -> Option<&str> {
+        match self {
+            NameOp::Register { name, .. }
+            | NameOp::Renew { name, .. }
+            | NameOp::Transfer { name, .. }
+            | NameOp::SetAddress { name, .. }
+            | NameOp::SetText { name, .. } => Some(name),
+            NameOp::SetPrimary { name } => name.as_deref(),
+        }
+    }
+
+    /// Fee in whole SNRG the sender pays for the operation.
+    pub fn fee(&self) -> u64 {
+        match self {
+            NameOp::Register { name, years, .. } | NameOp::Renew { name, years } => {
+                normalize(name).map_or(0, |name| annual_fee(&name).saturating_mul(*years))
+            }
+            _ => 0,
+        }
+    }
+}
+
+/// A registered name.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct NameRecord {
+    pub name: String,
+    pub owner: String,
+    /// Address the name resolves to.
+    pub address: String,
+    pub text: BTreeMap<String, String>,
+    /// Height of the block that registered the name; renewals and
+    /// transfers keep it.
+    pub registered_height: u64,
+    /// First block height at which the name no longer resolves.
+    pub expiry_height: u64,
+}
+
+impl NameRecord {
+    pub fn is_active(&self, height: u64) -> bool {
+        height < self.expiry_height
+    }
+
+    /// Whether anyone may register the name again.
+    pub fn is_available(&self, height: u64) -> bool {
+        height >= self.expiry_height + GRACE_PERIOD
+    }
+}
+
+/// Every registered name and the primary names of addresses.
+#[derive(Debug, Clone, Default)]
+pub struct NameRegistry {
+    height: u64,
+    names: HashMap<String, NameRecord>,
+    primary: HashMap<String, String>,
+}
+
+impl NameRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replays the name operations of every block of `chain`.
+    pub fn from_chain(chain: &BlockChain) -> Self {
+        let mut registry = Self::new();
+        for block in &chain.chain {
+            registry.apply_block(block);
+        }
+        registry
+    }
+
+    /// Height of the last block applied; pending transactions are checked
+    /// against the block after it.
+    pub fn height(&self) -> u64 {
+        self.height
+    }
+
+    /// Checks that `tx` is a name operation that may be included at
+    /// `height`, after the operations already applied. Its fee is charged
+    /// separately.
+    pub fn check(&self, tx: &Transaction, height: u64) -> Result<NameOp, String> {
+        let op = NameOp::from_transaction(tx).ok_or("Transaction is not a name service operation")??;
+        let record = match op.name() {
+            Some(name) => self.names.get(&normalize(name)?),
+            None => None,
+        };
+        let owned = || match record {
+            Some(record) if record.is_active(height) && record.owner == tx.sender => Ok(record),
+            Some(record) if record.is_active(height) => Err(format!("{} is owned by {}", record.name, record.owner)),
+            _ => Err(format!("{} is not registered", op.name().unwrap_or_default())),
+        };
+
+        match &op {
+            NameOp::Register { years, owner, .. } => {
+                check_years(*years)?;
+                if let Some(record) = record.filter(|record| !record.is_available(height)) {
+                    return Err(format!("{} is registered until block {}", record.name, record.expiry_height));
+                }
+                check_address(owner.as_deref().unwrap_or(&tx.sender))?;
+            }
+            NameOp::Renew { years, .. } => {
+                check_years(*years)?;
+                let record = record
+                    .filter(|record| !record.is_available(height))
+                    .ok_or_else(|| format!("{} is not registered", op.name().unwrap_or_default()))?;
+                if !record.is_active(height) && record.owner != tx.sender {
+                    return Err(format!("{} has expired; only its owner can renew it during the grace period", record.name));
+                }
+                if record.expiry_height + years * BLOCKS_PER_YEAR > height + MAX_YEARS * BLOCKS_PER_YEAR {
+                    return Err(format!("A name can be registered at most {} years ahead", MAX_YEARS));
+                }
+            }
+            NameOp::Transfer { to, .. } => {
+                owned()?;
+                check_address(to)?;
+            }
+            NameOp::SetAddress { address, .. } => {
+                owned()?;
+                if let Some(address) = address {
+                    check_address(address)?;
+                }
+            }
+            NameOp::SetText { key, value, .. } => {
+                let record = owned()?;
+                if key.is_empty() || key.len() > MAX_TEXT_KEY_LEN {
+                    return Err(format!("A text record key is 1 to {} bytes", MAX_TEXT_KEY_LEN));
+                }
+                if value.as_ref().is_some_and(|value| value.len() > MAX_TEXT_VALUE_LEN) {
+                    return Err(format!("A text record value is at most {} bytes", MAX_TEXT_VALUE_LEN));
+                }
+                if value.is_some() && !record.text.contains_key(key) && record.text.len() >= MAX_TEXT_RECORDS {
+                    return Err(format!("A name has at most {} text records", MAX_TEXT_RECORDS));
+                }
+            }
+            NameOp::SetPrimary { name: Some(name) } => match record {
+                Some(record) if record.is_active(height) && record.address == tx.sender => {}
+                _ => return Err(format!("{} does not resolve to {}", name, tx.sender)),
+            },
+            NameOp::SetPrimary { name: None } => {}
+        }
+        Ok(op)
+    }
+
+    /// Checks `tx` like `check` and records its operation. Returns a
+    /// description of the change.
+    pub fn apply(&mut self, tx: &Transaction, height: u64) -> Result<String, String> {
+        let op = self.check(tx, height)?;
+        let name = op.name().map(normalize).transpose()?.unwrap_or_default();
+        let summary = match op {
+            NameOp::Register { years, owner, .. } => {
+                let owner = owner.unwrap_or_else(|| tx.sender.clone());
+                let record = NameRecord {
+                    name: name.clone(),
+                    owner: owner.clone(),
+                    address: owner.clone(),
+                    text: BTreeMap::new(),
+                    registered_height: height,
+                    expiry_height: height + years * BLOCKS_PER_YEAR,
+                };
+                let summary = format!("{} registered to {} until block {}", name, owner, record.expiry_height);
+                self.names.insert(name, record);
+                summary
+            }
+            NameOp::Renew { years, .. } => {
+                let record = self.names.get_mut(&name).expect("checked record");
+                record.expiry_height += years * BLOCKS_PER_YEAR;
+                format!("{} renewed until block {}", name, record.expiry_height)
+            }
+            NameOp::Transfer { to, .. } => {
+                let record = self.names.get_mut(&name).expect("checked record");
+                record.owner = to.clone();
+                record.address = to.clone();
+                record.text.clear();
+                format!("{} transferred to {}", name, to)
+            }
+            NameOp::SetAddress { address, .. } => {
+                let record = self.names.get_mut(&name).expect("checked record");
+                record.address = address.unwrap_or_else(|| record.owner.clone());
+                format!("{} now resolves to {}", name, record.address)
+            }
+            NameOp::SetText { key, value, .. } => {
+                let record = self.names.get_mut(&name).expect("checked record");
+                match value {
+                    Some(value) => record.text.insert(key.clone(), value),
+                    None => record.text.remove(&key),
+                };
+                format!("{} text record {} updated", name, key)
+            }
+            NameOp::SetPrimary { name: Some(_) } => {
+                self.primary.insert(tx.sender.clone(), name.clone());
+                format!("{} is now the primary name of {}", name, tx.sender)
+            }
+            NameOp::SetPrimary { name: None } => {
+                self.primary.remove(&tx.sender);
+                format!("{} has no primary name", tx.sender)
+            }
+        };
+        Ok(summary)
+    }
+
+    /// Records the name operations included in `block`. Blocks only hold
+    /// operations that passed `check`; anything else is skipped.
+    pub fn apply_block(&mut self, block: &Block) {
+        for tx in &block.transactions {
+            let _ = self.apply(tx, block.block_index);
+        }
+        self.end_block(block.block_index);
+    }
+
+    /// Marks the block at `height` as applied, once its operations went
+    /// through `apply` one by one.
+    pub fn end_block(&mut self, height: u64) {
+        self.height = height;
+    }
+
+    /// The record of `name` if it resolves at `height`.
+    pub fn resolve(&self, name: &str, height: u64) -> Option<&NameRecord> {
+        let record = self.names.get(&normalize(name).ok()?)?;
+        record.is_active(height).then_some(record)
+    }
+
+    /// The record of the primary name of `address`, as long as that name
+    /// still resolves to it at `height`.
+    pub fn lookup(&self, address: &str, height: u64) -> Option<&NameRecord> {
+        let record = self.resolve(self.primary.get(address)?, height)?;
+        (record.address == address).then_some(record)
+    }
+
+    /// Every record, registered or expired, in no particular order.
+    pub fn records(&self) -> impl Iterator<Item = &NameRecord> {
+        self.names.values()
+    }
+}
+
+fn check_years(years: u64) -> Result<(), String> {
+    if !(1..=MAX_YEARS).contains(&years) {
+        return Err(format!("A name is registered for 1 to {} years", MAX_YEARS));
+    }
+    Ok(())
+}
+
+fn check_address(address: &str) -> Result<(), String> {
+    if address.is_empty() || is_name(address) {
+        return Err(format!("{} is not an address", address));
+    }
+    Ok(())
+}
+
+lazy_static! {
+    /// Names registered on the node's chain, kept current by the consensus engine.
+    pub static ref NAME_REGISTRY: Mutex<NameRegistry> = Mutex::new(NameRegistry::new());
+}
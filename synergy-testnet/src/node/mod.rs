@@ -1,4 +1,5 @@
 use crate::block::{Block, BlockChain};
+use crate::storage;
 use crate::transaction::Transaction;
 use std::fs;
 use std::path::PathBuf;
@@ -35,7 +36,7 @@ pub fn generate_new_block(
 pub fn save_blockchain(chain: &BlockChain, path: &str) {
     let serialized = serde_json::to_string_pretty(&chain.chain)
         .expect("Failed to serialize chain");
-    fs::write(PathBuf::from(path), serialized)
+    storage::write_atomic(PathBuf::from(path), serialized.as_bytes())
         .expect("Unable to write file");
 }
 
@@ -0,0 +1,161 @@
+//! Account nonces.
+//!
+//! Every address has a nonce in the account state: the nonce of its last
+//! included transaction, 0 before the first. A transaction is included
+//! only with exactly the next nonce, so each can be included once and an
+//! address's transactions are included in order. The pool accepts nonces
+//! up to `MAX_QUEUED_NONCES` ahead; they wait there until the gap closes,
+//! or for at most `QUEUED_TX_LIFETIME` blocks.
+//!
+//! `NonceRegistry` is derived from the chain alone and rebuilt on start.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use lazy_static::lazy_static;
+
+use crate::block::{Block, BlockChain};
+use crate::transaction::Transaction;
+
+/// How far past an address's next nonce the pool accepts transactions.
+pub const MAX_QUEUED_NONCES: u64 = 64;
+
+/// Blocks a pool transaction may wait for the nonces before it.
+pub const QUEUED_TX_LIFETIME: u64 = 64;
+
+/// Nonce of the last included transaction of every address that sent one.
+#[derive(Debug, Clone, Default)]
+pub struct NonceRegistry {
+    height: u64,
+    nonces: HashMap<String, u64>,
+}
+
+impl NonceRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replays the transactions of every block of `chain`.
+    pub fn from_chain(chain: &BlockChain) -> Self {
+        let mut registry = Self::new();
+        for block in &chain.chain {
+            registry.apply_block(block);
+        }
+        registry
+    }
+
+    /// Height of the last block applied.
+    pub fn height(&self) -> u64 {
+        self.height
+    }
+
+    /// Nonce of the last included transaction of `address`, or 0.
+    pub fn nonce(&self, address: &str) -> u64 {
+        self.nonces.get(address).copied().unwrap_or(0)
+    }
+
+    /// Nonce the next included transaction of `address` must carry.
+    pub fn next_nonce(&self, address: &str) -> u64 {
+        self.nonce(address) + 1
+    }
+
+    /// Next nonce of `address` once its transactions in `pending` are
+    /// included, counting only those that follow on without a gap.
+    pub fn pending_nonce(&self, address: &str, pending: &[Transaction]) -> u64 {
+        let mut next = self.next_nonce(address);
+        while pending.iter().any(|tx| tx.sender == address && tx.nonce == next) {
+            next += 1;
+        }
+        next
+    }
+
+    /// Checks that `tx` carries the next nonce of its sender.
+    pub fn check(&self, tx: &Transaction) -> Result<(), String> {
+        let next = self.next_nonce(&tx.sender);
+        match tx.nonce {
+            nonce if nonce < next => Err(format!("Nonce {} of {} was already used; the next is {}", nonce, tx.sender, next)),
+            nonce if nonce > next => Err(format!("Nonce {} of {} is ahead of the next, {}", nonce, tx.sender, next)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks that `tx` may wait in a pool holding `pending`: its nonce is
+    /// unused, not already pending and at most `MAX_QUEUED_NONCES` ahead.
+    pub fn check_pending(&self, tx: &Transaction, pending: &[Transaction]) -> Result<(), String> {
+        let next = self.next_nonce(&tx.sender);
+        if tx.nonce < next {
+            return Err(format!("Nonce {} of {} was already used; the next is {}", tx.nonce, tx.sender, next));
+        }
+        if tx.nonce >= next + MAX_QUEUED_NONCES {
+            return Err(format!("Nonce {} of {} is more than {} ahead of the next, {}", tx.nonce, tx.sender, MAX_QUEUED_NONCES, next));
+        }
+        if pending.iter().any(|other| other.sender == tx.sender && other.nonce == tx.nonce) {
+            return Err(format!("A transaction with nonce {} from {} is already pending", tx.nonce, tx.sender));
+        }
+        Ok(())
+    }
+
+    /// Records `tx` as included. Blocks produced before nonces were
+    /// enforced may skip or repeat nonces; the highest one counts.
+    pub fn record(&mut self, tx: &Transaction) {
+        let nonce = self.nonces.entry(tx.sender.clone()).or_insert(0);
+        *nonce = (*nonce).max(tx.nonce);
+    }
+
+    /// Records the transactions included in `block`.
+    pub fn apply_block(&mut self, block: &Block) {
+        for tx in &block.transactions {
+            self.record(tx);
+        }
+        self.end_block(block.block_index);
+    }
+
+    /// Marks the block at `height` as applied, once its transactions went
+    /// through `record` one by one.
+    pub fn end_block(&mut self, height: u64) {
+        self.height = height;
+    }
+}
+
+/// Heights at which pool transactions started waiting for an earlier
+/// nonce, so a gap that never closes does not keep them forever.
+#[derive(Debug, Clone, Default)]
+pub struct QueuedTransactions {
+    since: HashMap<String, u64>,
+}
+
+impl QueuedTransactions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Prunes `pool` once the block at `height` is applied to `nonces`.
+    /// Transactions whose nonce was used go, and so do those that waited
+    /// `QUEUED_TX_LIFETIME` blocks for an earlier nonce. Returns the
+    /// expired ones.
+    pub fn prune(&mut self, pool: &mut Vec<Transaction>, nonces: &NonceRegistry, height: u64) -> Vec<Transaction> {
+        let mut since = HashMap::new();
+        let mut expired = Vec::new();
+        pool.retain(|tx| {
+            let next = nonces.next_nonce(&tx.sender);
+            if tx.nonce <= next {
+                return tx.nonce == next;
+            }
+            let hash = tx.hash();
+            let queued_at = self.since.get(&hash).copied().unwrap_or(height);
+            if height.saturating_sub(queued_at) >= QUEUED_TX_LIFETIME {
+                expired.push(tx.clone());
+                return false;
+            }
+            since.insert(hash, queued_at);
+            true
+        });
+        self.since = since;
+        expired
+    }
+}
+
+lazy_static! {
+    /// Account nonces of the node's chain, kept current by the consensus engine.
+    pub static ref NONCE_REGISTRY: Mutex<NonceRegistry> = Mutex::new(NonceRegistry::new());
+}
@@ -4,18 +4,42 @@
 //! including peer discovery, block synchronization, and transaction propagation.
 
 pub mod networking;
+pub mod peer_scoring;
+pub mod protocol;
 
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use lazy_static::lazy_static;
 use crate::block::BlockChain;
 use crate::config::NodeConfig;
 use self::networking::P2PNetwork;
 
+lazy_static! {
+    /// The running network, if any, so the RPC layer can reach peer state.
+    pub static ref P2P_NETWORK: Mutex<Option<Arc<P2PNetwork>>> = Mutex::new(None);
+}
+
+/// Starts listening for peers on `listen_address` and registers the network
+/// globally.
 pub fn start_p2p_network(
-    blockchain: Arc<std::sync::Mutex<BlockChain>>,
+    blockchain: Arc<Mutex<BlockChain>>,
     listen_address: &str,
     config: &NodeConfig
-) -> Arc<P2PNetwork> {
-    let mut network = P2PNetwork::new(blockchain, config);
-    network.start(listen_address);
-    Arc::new(network)
+) -> Result<Arc<P2PNetwork>, String> {
+    let network = Arc::new(P2PNetwork::new(blockchain, config));
+    network.start(listen_address)?;
+    *P2P_NETWORK.lock().unwrap() = Some(Arc::clone(&network));
+    Ok(network)
+}
+
+/// The running network, if any.
+pub fn network() -> Option<Arc<P2PNetwork>> {
+    P2P_NETWORK.lock().unwrap().clone()
+}
+
+/// Stops the registered network, if one is running.
+pub fn stop_p2p_network() {
+    let network = P2P_NETWORK.lock().unwrap().take();
+    if let Some(network) = network {
+        network.stop();
+    }
 }
@@ -1,69 +1,237 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io::BufReader;
+use std::mem;
+use std::net::{IpAddr, Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, SyncSender};
 use std::sync::{Arc, Mutex};
-use std::time::{SystemTime, UNIX_EPOCH};
-use std::net::{TcpListener, TcpStream};
-use std::io::{Read, Write};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use lru::LruCache;
 use serde_json::json;
-use crate::block::BlockChain;
+use crate::block::{Block, BlockChain};
 use crate::transaction::Transaction;
 use crate::config::NodeConfig;
+use crate::rpc::api;
+use crate::rpc::rpc_server::TX_POOL;
+use super::peer_scoring::{BanEntry, MessageType, PeerAction, PeerEvent, PeerScoreManager, BAN_LIST_PATH};
+use super::protocol::{read_message, write_message, HeadVote, P2PMessage, ReadError};
 
 // Type aliases to avoid nested generics parsing issues
 type PeerMap = HashMap<String, PeerConnection>;
 type BlockchainArc = Arc<Mutex<BlockChain>>;
 type PeersArc = Arc<Mutex<PeerMap>>;
 
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+const WRITE_TIMEOUT: Duration = Duration::from_secs(10);
+const DIAL_TIMEOUT: Duration = Duration::from_secs(5);
+/// How often bootnodes without a connection are dialled again.
+const DIAL_INTERVAL: Duration = Duration::from_secs(5);
+/// How often our chain head and clock are sent to every peer when the head
+/// does not move.
+const STATUS_INTERVAL: Duration = Duration::from_secs(10);
+/// Minimum gap between status messages, which count against the ping rate
+/// limit.
+const MIN_STATUS_INTERVAL: Duration = Duration::from_secs(1);
+/// Head votes remembered per peer, so its position can be checked against
+/// our chain once we reach it.
+const PEER_HEAD_HISTORY: usize = 64;
+/// How long a peer gets to answer a block request before another is sent.
+const SYNC_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// Minimum gap between block requests, so answers stay well inside the
+/// peer's block request rate limit.
+const SYNC_REQUEST_INTERVAL: Duration = Duration::from_secs(1);
+/// How often expired bans and idle rate limit buckets are dropped.
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+/// Messages queued for a slow peer before further ones are dropped.
+const SEND_QUEUE_SIZE: usize = 256;
+/// Blocks received but not yet taken by the consensus engine.
+const MAX_PENDING_BLOCKS: usize = 256;
+/// Block and transaction hashes remembered so gossip is relayed only once.
+const SEEN_CACHE_SIZE: usize = 16384;
+
+/// Peer connections and the gossip between them. Connections are keyed by
+/// their `ip:port`; reputation and bans follow the IP, so reconnecting from
+/// another port does not escape them. Nodes on loopback and private
+/// addresses may share an IP, so theirs follow the address they listen on
+/// (see `peer_key`).
 pub struct P2PNetwork {
     blockchain: BlockchainArc,
     config: NodeConfig,
+    node_id: String,
     connected_peers: PeersArc,
-    is_running: Arc<Mutex<bool>>,
+    peer_scores: Arc<PeerScoreManager>,
+    is_running: AtomicBool,
+    next_connection_id: AtomicU64,
+    /// Bootnodes with an outbound connection being made or open.
+    dialing: Mutex<HashSet<String>>,
+    /// Bootnodes whose last dial failed, so the failure is logged once.
+    unreachable: Mutex<HashSet<String>>,
+    /// Bootnodes that turned out to be this node.
+    own_addresses: Mutex<HashSet<String>>,
+    listen_port: Mutex<u16>,
+    pending_blocks: Mutex<Vec<(String, Block)>>,
+    seen: Mutex<LruCache<String, ()>>,
+    /// Signed heads of the active validators whose keys this node holds,
+    /// as announced in status messages.
+    head_votes: Mutex<Vec<HeadVote>>,
+    /// Last block request: the peer asked, when, and whether it answered.
+    sync_request: Mutex<Option<SyncRequest>>,
+}
+
+struct SyncRequest {
+    peer: String,
+    sent_at: Instant,
+    answered: bool,
+    /// Peers whose earlier requests timed out; their answers may still
+    /// arrive.
+    overdue: HashSet<String>,
+}
+
+/// What a peer has told us about its chain.
+#[derive(Debug, Clone)]
+pub struct PeerHeads {
+    pub address: String,
+    /// Head votes the peer passed on, oldest first. Their signatures are
+    /// not checked yet.
+    pub votes: Vec<HeadVote>,
 }
 
 struct PeerConnection {
+    id: u64,
     address: String,
+    node_id: String,
+    outbound: bool,
+    /// Address the peer accepts connections on.
+    listen_address: String,
     connected_at: u64,
     last_seen: u64,
     blocks_sent: u64,
     blocks_received: u64,
     txs_sent: u64,
     txs_received: u64,
+    /// Chain height the peer last announced.
+    best_height: Option<u64>,
+    /// Peer clock minus ours, in seconds, from its last status message.
+    clock_offset_secs: Option<i64>,
+    votes: VecDeque<HeadVote>,
+    stream: TcpStream,
+    sender: SyncSender<P2PMessage>,
+}
+
+impl PeerConnection {
+    /// Queues a message for the peer's writer thread. Messages for a peer
+    /// that cannot keep up are dropped.
+    fn send(&mut self, message: P2PMessage) {
+        let message_type = message.message_type();
+        if self.sender.try_send(message).is_ok() {
+            match message_type {
+                MessageType::Block => self.blocks_sent += 1,
+                MessageType::Transaction => self.txs_sent += 1,
+                _ => {}
+            }
+        }
+    }
+
+    fn close(&self) {
+        let _ = self.stream.shutdown(Shutdown::Both);
+    }
 }
 
 impl P2PNetwork {
     pub fn new(blockchain: BlockchainArc, config: &NodeConfig) -> Self {
+        Self::with_ban_list(blockchain, config, BAN_LIST_PATH)
+    }
+
+    /// Like `new`, keeping the ban list at `ban_list_path`.
+    pub fn with_ban_list(blockchain: BlockchainArc, config: &NodeConfig, ban_list_path: &str) -> Self {
         P2PNetwork {
             blockchain,
             config: config.clone(),
+            node_id: hex::encode(rand::random::<[u8; 32]>()),
             connected_peers: Arc::new(Mutex::new(HashMap::new())),
-            is_running: Arc::new(Mutex::new(false)),
+            peer_scores: Arc::new(PeerScoreManager::new(config.p2p.peer_scoring.clone(), ban_list_path)),
+            is_running: AtomicBool::new(false),
+            next_connection_id: AtomicU64::new(0),
+            dialing: Mutex::new(HashSet::new()),
+            unreachable: Mutex::new(HashSet::new()),
+            own_addresses: Mutex::new(HashSet::new()),
+            listen_port: Mutex::new(0),
+            pending_blocks: Mutex::new(Vec::new()),
+            seen: Mutex::new(LruCache::new(NonZeroUsize::new(SEEN_CACHE_SIZE).unwrap())),
+            head_votes: Mutex::new(Vec::new()),
+            sync_request: Mutex::new(None),
         }
     }
 
-    pub fn start(&mut self, listen_address: &str) {
-        let is_running = Arc::clone(&self.is_running);
-        let blockchain = Arc::clone(&self.blockchain);
-        let connected_peers = Arc::clone(&self.connected_peers);
-        let config = self.config.clone();
-        let addr_string = listen_address.to_string();
+    /// Listens for peers on `listen_address`, dials the configured
+    /// bootnodes and keeps redialling those that drop. Returns the address
+    /// actually bound.
+    pub fn start(self: &Arc<Self>, listen_address: &str) -> Result<SocketAddr, String> {
+        let listener = TcpListener::bind(listen_address)
+            .map_err(|e| format!("Failed to listen for peers on {}: {}", listen_address, e))?;
+        let local_address = listener.local_addr().map_err(|e| e.to_string())?;
+        listener.set_nonblocking(true).map_err(|e| e.to_string())?;
+        *self.listen_port.lock().unwrap() = local_address.port();
+        self.is_running.store(true, Ordering::SeqCst);
+
+        let network = Arc::clone(self);
+        thread::Builder::new()
+            .name("p2p-listener".to_string())
+            .spawn(move || network.accept_connections(listener))
+            .map_err(|e| e.to_string())?;
+        let network = Arc::clone(self);
+        thread::Builder::new()
+            .name("p2p-maintenance".to_string())
+            .spawn(move || network.maintain())
+            .map_err(|e| e.to_string())?;
+
+        crate::info!("p2p", "P2P network listening", "address" => local_address.to_string(), "node_id" => &self.node_id[..16]);
+        Ok(local_address)
+    }
 
-        // Start listener (basic implementation for now)
-        println!("🔌 P2P listener would start on {}", addr_string);
+    /// Disconnects every peer and persists the ban list. Called on node
+    /// shutdown.
+    pub fn stop(&self) {
+        self.is_running.store(false, Ordering::SeqCst);
+        let peers: Vec<PeerConnection> = self.connected_peers.lock().unwrap().drain().map(|(_, peer)| peer).collect();
+        for peer in &peers {
+            peer.close();
+        }
+        if let Err(e) = self.peer_scores.save_bans() {
+            crate::error!("p2p", "Failed to save peer ban list", "error" => e.to_string());
+        }
+        crate::info!("p2p", "P2P network stopped", "peers_disconnected" => peers.len());
+    }
 
-        // Set running flag
-        *is_running.lock().unwrap() = true;
+    pub fn is_running(&self) -> bool {
+        self.is_running.load(Ordering::SeqCst)
+    }
 
-        println!("🔌 P2P network started on {}", listen_address);
+    /// Sends a block this node produced to every peer.
+    pub fn broadcast_block(&self, block: &Block) {
+        self.mark_seen(&block.hash);
+        self.gossip(P2PMessage::Block { block: block.clone() }, None);
     }
 
-    pub fn broadcast_block(&self, _block: &crate::block::Block) {
-        // Basic implementation - in production this would broadcast to peers
-        println!("📢 Block broadcast (basic implementation)");
+    /// Rewards the peer a block came from once the consensus engine has
+    /// imported it, and passes the block on to the other peers.
+    pub fn block_imported(&self, peer_address: &str, block: &Block) {
+        self.report_peer(peer_address, PeerEvent::UsefulBlock);
+        self.gossip(P2PMessage::Block { block: block.clone() }, Some(peer_address));
     }
 
-    pub fn broadcast_transaction(&self, _transaction: &Transaction) {
-        // Basic implementation - in production this would broadcast to peers
-        println!("📢 Transaction broadcast (basic implementation)");
+    /// Sends a transaction that entered the pool to every peer.
+    pub fn broadcast_transaction(&self, transaction: &Transaction) {
+        self.mark_seen(&transaction.hash());
+        self.gossip(P2PMessage::Transaction { transaction: transaction.clone() }, None);
+    }
+
+    /// Blocks received from peers since the last call, each with the peer
+    /// it came from, in arrival order.
+    pub fn take_blocks(&self) -> Vec<(String, Block)> {
+        mem::take(&mut *self.pending_blocks.lock().unwrap())
     }
 
     pub fn get_peer_count(&self) -> usize {
@@ -75,38 +243,716 @@ impl P2PNetwork {
         peers.values().map(|peer| {
             json!({
                 "address": peer.address,
+                "node_id": peer.node_id,
+                "direction": if peer.outbound { "outbound" } else { "inbound" },
                 "connected_at": peer.connected_at,
                 "last_seen": peer.last_seen,
                 "blocks_sent": peer.blocks_sent,
                 "blocks_received": peer.blocks_received,
                 "txs_sent": peer.txs_sent,
-                "txs_received": peer.txs_received
+                "txs_received": peer.txs_received,
+                "score": self.peer_scores.get_score(&peer_key(&peer.listen_address))
             })
         }).collect()
     }
+
+    /// Checks the network a peer's handshake names. Peers with another
+    /// chain id or genesis can never agree with us on a block, so they are
+    /// banned rather than merely dropped.
+    pub fn accept_handshake(&self, peer_address: &str, chain_id: u64, genesis_hash: &str) -> Result<(), String> {
+        if self.is_banned(peer_address) {
+            return Err("banned".to_string());
+        }
+
+        let ours = self
+            .blockchain
+            .lock()
+            .unwrap()
+            .genesis_hash()
+            .map(str::to_string)
+            .ok_or("not ready: no chain loaded")?;
+        let mismatch = if chain_id != self.config.blockchain.chain_id {
+            Some(format!("chain id {} (ours {})", chain_id, self.config.blockchain.chain_id))
+        } else if ours != genesis_hash {
+            Some(format!("genesis {} (ours {})", genesis_hash, ours))
+        } else {
+            None
+        };
+        if let Some(mismatch) = mismatch {
+            let reason = format!("different network: {}", mismatch);
+            let duration = self.peer_scores.config().temp_ban_secs;
+            self.ban_peer(peer_address, Some(duration), &reason);
+            return Err(reason);
+        }
+        Ok(())
+    }
+
+    /// Records a peer's announced chain height and clock, as carried by its
+    /// status message.
+    pub fn record_peer_status(&self, peer_address: &str, best_height: u64, peer_timestamp: u64) {
+        if let Some(peer) = self.connected_peers.lock().unwrap().get_mut(peer_address) {
+            let now = current_timestamp();
+            peer.last_seen = now;
+            peer.best_height = Some(best_height);
+            peer.clock_offset_secs = Some(peer_timestamp as i64 - now as i64);
+        }
+    }
+
+    /// Sets the signed heads this node announces for its validators.
+    pub fn set_head_votes(&self, votes: Vec<HeadVote>) {
+        *self.head_votes.lock().unwrap() = votes;
+    }
+
+    /// Recent head votes received from every connected peer.
+    pub fn peer_heads(&self) -> Vec<PeerHeads> {
+        self.connected_peers
+            .lock()
+            .unwrap()
+            .values()
+            .map(|peer| PeerHeads { address: peer.address.clone(), votes: peer.votes.iter().cloned().collect() })
+            .collect()
+    }
+
+    /// Highest chain height announced by any connected peer.
+    pub fn best_peer_height(&self) -> Option<u64> {
+        self.connected_peers.lock().unwrap().values().filter_map(|peer| peer.best_height).max()
+    }
+
+    /// Median offset between peer clocks and ours, in seconds. Positive when
+    /// our clock is behind.
+    pub fn median_clock_offset(&self) -> Option<i64> {
+        let mut offsets: Vec<i64> = self
+            .connected_peers
+            .lock()
+            .unwrap()
+            .values()
+            .filter_map(|peer| peer.clock_offset_secs)
+            .collect();
+        if offsets.is_empty() {
+            return None;
+        }
+        offsets.sort_unstable();
+        Some(offsets[offsets.len() / 2])
+    }
+
+    /// Gatekeeper for every inbound gossip message: rejects banned peers,
+    /// oversized payloads and peers that exceed their per-type rate limit.
+    pub fn accept_message(&self, peer_address: &str, message_type: MessageType, size: usize) -> bool {
+        if self.is_banned(peer_address) {
+            self.disconnect_peer(peer_address);
+            return false;
+        }
+
+        if size > self.peer_scores.config().max_message_size {
+            self.report_peer(peer_address, PeerEvent::OversizedMessage);
+            return false;
+        }
+
+        // Buckets are per connection; the penalty for draining them is per peer key
+        if !self.peer_scores.check_rate_limit(peer_address, message_type) {
+            self.report_peer(peer_address, PeerEvent::RateLimitExceeded);
+            return false;
+        }
+
+        if let Some(peer) = self.connected_peers.lock().unwrap().get_mut(peer_address) {
+            peer.last_seen = current_timestamp();
+            match message_type {
+                MessageType::Block => peer.blocks_received += 1,
+                MessageType::Transaction => peer.txs_received += 1,
+                _ => {}
+            }
+        }
+
+        true
+    }
+
+    /// Records peer behaviour and disconnects the peer if its score demands it.
+    pub fn report_peer(&self, peer_address: &str, event: PeerEvent) -> PeerAction {
+        let key = self.score_key(peer_address);
+        let action = self.peer_scores.record_event(&key, event);
+        match action {
+            PeerAction::Keep => {}
+            PeerAction::Drop => self.disconnect_peer(peer_address),
+            PeerAction::Ban => self.disconnect_key(&key),
+        }
+        action
+    }
+
+    /// Bans `peer_address`, which may be given with or without a port,
+    /// and closes every connection the ban covers. A bare IP covers every
+    /// node on it. Returns the key the ban is listed under.
+    pub fn ban_peer(&self, peer_address: &str, duration_secs: Option<u64>, reason: &str) -> String {
+        let key = self.score_key(peer_address);
+        self.peer_scores.ban_peer(&key, duration_secs, reason);
+        self.disconnect_key(&key);
+        key
+    }
+
+    pub fn unban_peer(&self, peer_address: &str) -> bool {
+        self.peer_scores.unban_peer(&peer_key(peer_address))
+    }
+
+    pub fn get_banned_peers(&self) -> Vec<BanEntry> {
+        self.peer_scores.get_banned_peers()
+    }
+
+    pub fn peer_scores(&self) -> Arc<PeerScoreManager> {
+        Arc::clone(&self.peer_scores)
+    }
+
+    fn is_connected(&self, peer_address: &str) -> bool {
+        self.connected_peers.lock().unwrap().contains_key(peer_address)
+    }
+
+    fn disconnect_peer(&self, peer_address: &str) {
+        if let Some(peer) = self.connected_peers.lock().unwrap().remove(peer_address) {
+            peer.close();
+            crate::info!("p2p", "Disconnected peer", "peer" => peer_address);
+        }
+        self.peer_scores.forget_rate_limits(peer_address);
+    }
+
+    /// Key the score of `peer_address` is kept under: that of the address
+    /// a connected peer listens on, or of `peer_address` itself.
+    fn score_key(&self, peer_address: &str) -> String {
+        match self.connected_peers.lock().unwrap().get(peer_address) {
+            Some(peer) => peer_key(&peer.listen_address),
+            None => peer_key(peer_address),
+        }
+    }
+
+    /// Whether a ban covers `peer_address`, under its key or its bare IP.
+    fn is_banned(&self, peer_address: &str) -> bool {
+        self.peer_scores.is_banned(&self.score_key(peer_address)) || self.peer_scores.is_banned(&peer_ip(peer_address))
+    }
+
+    fn disconnect_key(&self, key: &str) {
+        let addresses: Vec<String> = self
+            .connected_peers
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|peer| peer_key(&peer.listen_address) == key || peer_ip(&peer.address) == key || peer.address == key)
+            .map(|peer| peer.address.clone())
+            .collect();
+        for address in addresses {
+            self.disconnect_peer(&address);
+        }
+    }
+
+    /// Called when a connection's thread ends. A newer connection from the
+    /// same address is left alone.
+    fn connection_closed(&self, peer_address: &str, connection_id: u64) {
+        let mut peers = self.connected_peers.lock().unwrap();
+        if peers.get(peer_address).is_some_and(|peer| peer.id == connection_id) {
+            peers.remove(peer_address);
+            drop(peers);
+            self.peer_scores.forget_rate_limits(peer_address);
+            crate::info!("p2p", "Peer disconnected", "peer" => peer_address);
+        }
+    }
+
+    fn gossip(&self, message: P2PMessage, except: Option<&str>) {
+        let mut peers = self.connected_peers.lock().unwrap();
+        for peer in peers.values_mut().filter(|peer| Some(peer.address.as_str()) != except) {
+            peer.send(message.clone());
+        }
+    }
+
+    /// Remembers a block or transaction hash. Returns false if it was
+    /// already known.
+    fn mark_seen(&self, hash: &str) -> bool {
+        self.seen.lock().unwrap().put(hash.to_string(), ()).is_none()
+    }
+
+    fn hello(&self) -> P2PMessage {
+        P2PMessage::Hello {
+            node_id: self.node_id.clone(),
+            chain_id: self.config.blockchain.chain_id,
+            genesis_hash: self.blockchain.lock().unwrap().genesis_hash().unwrap_or_default().to_string(),
+            listen_port: *self.listen_port.lock().unwrap(),
+        }
+    }
+
+    fn status(&self) -> P2PMessage {
+        let chain = self.blockchain.lock().unwrap();
+        let head = chain.last();
+        P2PMessage::Status {
+            height: head.map_or(0, |block| block.block_index),
+            head_hash: head.map(|block| block.hash.clone()).unwrap_or_default(),
+            timestamp: current_timestamp(),
+            votes: self.head_votes.lock().unwrap().clone(),
+        }
+    }
+
+    fn accept_connections(self: Arc<Self>, listener: TcpListener) {
+        while self.is_running() {
+            match listener.accept() {
+                Ok((stream, address)) => {
+                    let peer_address = address.to_string();
+                    if self.is_banned(&peer_address) {
+                        crate::info!("p2p", "Rejected connection from banned peer", "peer" => peer_address.clone());
+                        continue;
+                    }
+                    if self.get_peer_count() >= self.config.network.max_peers as usize {
+                        let reason = "too many peers".to_string();
+                        let _ = write_message(&mut &stream, &P2PMessage::Disconnect { reason });
+                        continue;
+                    }
+                    let network = Arc::clone(&self);
+                    let spawned = thread::Builder::new()
+                        .name("p2p-peer".to_string())
+                        .spawn(move || network.run_connection(stream, peer_address, false));
+                    if let Err(e) = spawned {
+                        crate::error!("p2p", "Failed to start a peer connection thread", "error" => e.to_string());
+                    }
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => thread::sleep(Duration::from_millis(100)),
+                Err(e) => {
+                    crate::warn!("p2p", "Failed to accept a peer connection", "error" => e.to_string());
+                    thread::sleep(Duration::from_millis(100));
+                }
+            }
+        }
+    }
+
+    /// Redials bootnodes, announces our status, fetches blocks we are
+    /// missing and prunes peer scoring state until the network stops.
+    fn maintain(self: Arc<Self>) {
+        let mut last_dial: Option<Instant> = None;
+        let mut last_status = Instant::now();
+        let mut announced_head = String::new();
+        let mut last_prune = Instant::now();
+        while self.is_running() {
+            if last_dial.is_none_or(|dialed| dialed.elapsed() >= DIAL_INTERVAL) {
+                self.dial_bootnodes();
+                last_dial = Some(Instant::now());
+            }
+            let head = self.blockchain.lock().unwrap().last().map(|block| block.hash.clone()).unwrap_or_default();
+            let elapsed = last_status.elapsed();
+            if elapsed >= STATUS_INTERVAL || (head != announced_head && elapsed >= MIN_STATUS_INTERVAL) {
+                self.gossip(self.status(), None);
+                announced_head = head;
+                last_status = Instant::now();
+            }
+            self.request_blocks();
+            if last_prune.elapsed() >= PRUNE_INTERVAL {
+                self.peer_scores.prune();
+                last_prune = Instant::now();
+            }
+            thread::sleep(Duration::from_millis(200));
+        }
+    }
+
+    fn dial_bootnodes(self: &Arc<Self>) {
+        let listening: HashSet<String> = self
+            .connected_peers
+            .lock()
+            .unwrap()
+            .values()
+            .map(|peer| peer.listen_address.clone())
+            .collect();
+        for bootnode in &self.config.network.bootnodes {
+            let address = bootnode_address(bootnode).to_string();
+            if listening.contains(&address)
+                || self.own_addresses.lock().unwrap().contains(&address)
+                || self.is_banned(&address)
+                || self.get_peer_count() >= self.config.network.max_peers as usize
+                || !self.dialing.lock().unwrap().insert(address.clone())
+            {
+                continue;
+            }
+
+            let network = Arc::clone(self);
+            let spawned = thread::Builder::new().name("p2p-dial".to_string()).spawn(move || {
+                match connect(&address) {
+                    Ok(stream) => {
+                        network.unreachable.lock().unwrap().remove(&address);
+                        network.run_connection(stream, address.clone(), true);
+                    }
+                    Err(e) => {
+                        if network.unreachable.lock().unwrap().insert(address.clone()) {
+                            crate::warn!("p2p", "Bootnode is unreachable, will keep trying", "address" => address.clone(), "error" => e.to_string());
+                        }
+                    }
+                }
+                network.dialing.lock().unwrap().remove(&address);
+            });
+            if let Err(e) = spawned {
+                crate::error!("p2p", "Failed to start a dial thread", "error" => e.to_string());
+            }
+        }
+    }
+
+    fn run_connection(&self, stream: TcpStream, peer_address: String, outbound: bool) {
+        let connection_id = self.next_connection_id.fetch_add(1, Ordering::SeqCst);
+        if let Err(reason) = self.handle_peer_connection(stream, &peer_address, outbound, connection_id) {
+            crate::info!("p2p", "Closed connection to peer", "peer" => peer_address.clone(), "reason" => reason);
+        }
+        self.connection_closed(&peer_address, connection_id);
+    }
+
+    /// Runs one connection: the handshake, then every inbound message until
+    /// either side closes it.
+    fn handle_peer_connection(&self, stream: TcpStream, peer_address: &str, outbound: bool, connection_id: u64) -> Result<(), String> {
+        let setup = stream
+            .set_nonblocking(false)
+            .and_then(|()| stream.set_write_timeout(Some(WRITE_TIMEOUT)))
+            .and_then(|()| stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT)))
+            .and_then(|()| stream.try_clone());
+        let mut reader = BufReader::new(setup.map_err(|e| e.to_string())?);
+        let max_size = self.peer_scores.config().max_message_size;
+
+        write_message(&mut &stream, &self.hello()).map_err(|e| e.to_string())?;
+        let (node_id, chain_id, genesis_hash, listen_port) = match read_message(&mut reader, max_size) {
+            Ok(Some((P2PMessage::Hello { node_id, chain_id, genesis_hash, listen_port }, _))) => {
+                (node_id, chain_id, genesis_hash, listen_port)
+            }
+            Ok(Some((P2PMessage::Disconnect { reason }, _))) => return Err(format!("refused by peer: {}", reason)),
+            Ok(None) => return Err("closed during the handshake".to_string()),
+            Ok(Some(_)) | Err(ReadError::Malformed(..)) => {
+                self.report_peer(peer_address, PeerEvent::ProtocolViolation);
+                return Err("did not open with a handshake".to_string());
+            }
+            Err(ReadError::Oversized) => {
+                self.report_peer(peer_address, PeerEvent::OversizedMessage);
+                return Err("sent an oversized handshake".to_string());
+            }
+            Err(ReadError::Io(e)) => return Err(format!("handshake failed: {}", e)),
+        };
+
+        // An inbound peer's port is ephemeral, so bans are checked against where it listens
+        let listen_address = SocketAddr::new(ip_of(&stream), listen_port).to_string();
+        let (sender, receiver) = mpsc::sync_channel(SEND_QUEUE_SIZE);
+        let registered = self
+            .accept_handshake(&listen_address, chain_id, &genesis_hash)
+            .and_then(|()| stream.try_clone().map_err(|e| e.to_string()))
+            .and_then(|writer| {
+                let connection = PeerConnection {
+                    id: connection_id,
+                    address: peer_address.to_string(),
+                    node_id,
+                    outbound,
+                    listen_address,
+                    connected_at: current_timestamp(),
+                    last_seen: current_timestamp(),
+                    blocks_sent: 0,
+                    blocks_received: 0,
+                    txs_sent: 0,
+                    txs_received: 0,
+                    best_height: None,
+                    clock_offset_secs: None,
+                    votes: VecDeque::new(),
+                    stream: writer,
+                    sender,
+                };
+                self.register_peer(connection)
+            });
+        if let Err(reason) = registered {
+            if reason == "connected to itself" {
+                self.own_addresses.lock().unwrap().insert(peer_address.to_string());
+            }
+            let _ = write_message(&mut &stream, &P2PMessage::Disconnect { reason: reason.clone() });
+            return Err(reason);
+        }
+
+        // Peers learn our head and clock right away rather than at the next status round
+        let status = self.status();
+        if let Some(peer) = self.connected_peers.lock().unwrap().get_mut(peer_address) {
+            peer.send(status);
+        }
+
+        let mut writer = stream.try_clone().map_err(|e| e.to_string())?;
+        thread::Builder::new()
+            .name("p2p-writer".to_string())
+            .spawn(move || {
+                // Ends once the connection is dropped from the peer map
+                for message in receiver {
+                    if write_message(&mut writer, &message).is_err() {
+                        let _ = writer.shutdown(Shutdown::Both);
+                        break;
+                    }
+                }
+            })
+            .map_err(|e| e.to_string())?;
+
+        stream.set_read_timeout(None).map_err(|e| e.to_string())?;
+        loop {
+            match read_message(&mut reader, max_size) {
+                Ok(Some((message, size))) => {
+                    if !self.handle_message(peer_address, message, size) {
+                        return Ok(());
+                    }
+                }
+                Ok(None) => return Ok(()),
+                Err(ReadError::Malformed(e, size)) => {
+                    if self.accept_message(peer_address, MessageType::Ping, size) {
+                        crate::warn!("p2p", "Peer sent a malformed message", "peer" => peer_address, "error" => e.to_string());
+                        self.report_peer(peer_address, PeerEvent::ProtocolViolation);
+                    }
+                }
+                Err(ReadError::Oversized) => {
+                    self.report_peer(peer_address, PeerEvent::OversizedMessage);
+                    return Err("sent an oversized message".to_string());
+                }
+                // A connection we dropped ourselves ends in an error too
+                Err(ReadError::Io(e)) if self.is_connected(peer_address) => return Err(e.to_string()),
+                Err(ReadError::Io(_)) => return Ok(()),
+            }
+        }
+    }
+
+    /// Adds a connection that passed the handshake. When two nodes dial
+    /// each other at once, both keep the connection dialled by the node
+    /// with the lower id.
+    fn register_peer(&self, connection: PeerConnection) -> Result<(), String> {
+        if connection.node_id == self.node_id {
+            return Err("connected to itself".to_string());
+        }
+
+        let mut peers = self.connected_peers.lock().unwrap();
+        let dialer = |peer: &PeerConnection| if peer.outbound { self.node_id.clone() } else { peer.node_id.clone() };
+        if let Some(existing) = peers.values().find(|peer| peer.node_id == connection.node_id) {
+            if existing.outbound == connection.outbound || dialer(existing) <= dialer(&connection) {
+                return Err("already connected".to_string());
+            }
+            let replaced = existing.address.clone();
+            if let Some(replaced) = peers.remove(&replaced) {
+                replaced.close();
+            }
+        }
+        if peers.len() >= self.config.network.max_peers as usize {
+            return Err("too many peers".to_string());
+        }
+
+        crate::info!("p2p", "Peer connected",
+            "peer" => connection.address.clone(),
+            "direction" => if connection.outbound { "outbound" } else { "inbound" },
+            "node_id" => &connection.node_id[..connection.node_id.len().min(16)]);
+        peers.insert(connection.address.clone(), connection);
+        Ok(())
+    }
+
+    /// Handles one inbound message. Returns false once the connection
+    /// should close.
+    fn handle_message(&self, peer_address: &str, message: P2PMessage, size: usize) -> bool {
+        if !self.accept_message(peer_address, message.message_type(), size) {
+            return self.is_connected(peer_address);
+        }
+        match message {
+            P2PMessage::Hello { .. } => {
+                self.report_peer(peer_address, PeerEvent::ProtocolViolation);
+            }
+            P2PMessage::Disconnect { reason } => {
+                crate::info!("p2p", "Peer is closing the connection", "peer" => peer_address, "reason" => reason);
+                return false;
+            }
+            P2PMessage::Status { height, timestamp, votes, .. } => {
+                self.record_peer_status(peer_address, height, timestamp);
+                if let Some(peer) = self.connected_peers.lock().unwrap().get_mut(peer_address) {
+                    // Status messages repeat the votes until the head moves
+                    for vote in votes {
+                        if peer.votes.contains(&vote) {
+                            continue;
+                        }
+                        if peer.votes.len() == PEER_HEAD_HISTORY {
+                            peer.votes.pop_front();
+                        }
+                        peer.votes.push_back(vote);
+                    }
+                }
+            }
+            P2PMessage::Block { block } => self.receive_block(peer_address, block),
+            P2PMessage::Transaction { transaction } => self.receive_transaction(peer_address, transaction),
+            P2PMessage::GetBlocks { from } => self.send_blocks(peer_address, from),
+            P2PMessage::Blocks { blocks } => self.receive_blocks(peer_address, blocks),
+        }
+        self.is_connected(peer_address)
+    }
+
+    /// Queues a gossiped block for the consensus engine, which checks it
+    /// against the chain and reports the peer through `report_peer` or
+    /// `block_imported`.
+    fn receive_block(&self, peer_address: &str, block: Block) {
+        if block.hash != block.calculate_hash() {
+            self.report_peer(peer_address, PeerEvent::BadBlock);
+            return;
+        }
+        if !self.mark_seen(&block.hash) {
+            return;
+        }
+
+        // A block past our next height means we missed some; the sync round fetches them
+        if let Some(peer) = self.connected_peers.lock().unwrap().get_mut(peer_address) {
+            peer.best_height = peer.best_height.max(Some(block.block_index));
+        }
+        self.queue_block(peer_address, block);
+    }
+
+    fn queue_block(&self, peer_address: &str, block: Block) {
+        let mut pending = self.pending_blocks.lock().unwrap();
+        if pending.len() < MAX_PENDING_BLOCKS {
+            pending.push((peer_address.to_string(), block));
+        }
+    }
+
+    /// Answers a block request with blocks from `from` onwards, up to half
+    /// the message size limit.
+    fn send_blocks(&self, peer_address: &str, from: u64) {
+        let budget = self.peer_scores.config().max_message_size / 2;
+        let mut blocks = Vec::new();
+        let mut size = 0;
+        {
+            let chain = self.blockchain.lock().unwrap();
+            for block in chain.chain.iter().skip_while(|block| block.block_index < from) {
+                size += serde_json::to_vec(block).map_or(budget, |json| json.len());
+                if size > budget && !blocks.is_empty() {
+                    break;
+                }
+                blocks.push(block.clone());
+            }
+        }
+        if let Some(peer) = self.connected_peers.lock().unwrap().get_mut(peer_address) {
+            peer.send(P2PMessage::Blocks { blocks });
+        }
+    }
+
+    /// Queues the blocks a peer sent in answer to our request. A late
+    /// answer to a request that timed out is dropped; blocks nobody asked
+    /// for are a protocol violation.
+    fn receive_blocks(&self, peer_address: &str, blocks: Vec<Block>) {
+        let (solicited, late) = match self.sync_request.lock().unwrap().as_mut() {
+            Some(request) if request.peer == peer_address && !request.answered => {
+                request.answered = true;
+                (true, false)
+            }
+            Some(request) => (false, request.overdue.remove(peer_address)),
+            None => (false, false),
+        };
+        if late {
+            return;
+        }
+        if !solicited {
+            self.report_peer(peer_address, PeerEvent::ProtocolViolation);
+            return;
+        }
+
+        for block in blocks {
+            if block.hash != block.calculate_hash() {
+                self.report_peer(peer_address, PeerEvent::BadBlock);
+                return;
+            }
+            self.mark_seen(&block.hash);
+            self.queue_block(peer_address, block);
+        }
+    }
+
+    /// Asks the peer with the highest announced chain for the blocks after
+    /// our head, one request at a time.
+    fn request_blocks(&self) {
+        let height = self.blockchain.lock().unwrap().last().map_or(0, |block| block.block_index);
+        let mut request = self.sync_request.lock().unwrap();
+        let wait = match request.as_ref() {
+            Some(last) if last.answered => SYNC_REQUEST_INTERVAL,
+            Some(_) => SYNC_REQUEST_TIMEOUT,
+            None => Duration::ZERO,
+        };
+        if request.as_ref().is_some_and(|last| last.sent_at.elapsed() < wait) {
+            return;
+        }
+
+        let mut peers = self.connected_peers.lock().unwrap();
+        let best = peers
+            .values_mut()
+            .filter(|peer| peer.best_height.is_some_and(|best| best > height))
+            .max_by_key(|peer| peer.best_height);
+        if let Some(peer) = best {
+            peer.send(P2PMessage::GetBlocks { from: height + 1 });
+            let address = peer.address.clone();
+            let mut overdue = HashSet::new();
+            if let Some(last) = request.take() {
+                overdue = last.overdue;
+                if !last.answered {
+                    overdue.insert(last.peer);
+                }
+            }
+            // Peers that went away will not answer
+            overdue.retain(|address| peers.contains_key(address));
+            *request = Some(SyncRequest { peer: address, sent_at: Instant::now(), answered: false, overdue });
+        }
+    }
+
+    /// Admits a gossiped transaction to the pool and passes it on.
+    fn receive_transaction(&self, peer_address: &str, transaction: Transaction) {
+        if !self.mark_seen(&transaction.hash()) {
+            return;
+        }
+        if !transaction.validate_fields().is_valid {
+            self.report_peer(peer_address, PeerEvent::ProtocolViolation);
+            return;
+        }
+        if let Err(e) = transaction.check_signature() {
+            crate::warn!("p2p", "Peer sent a transaction with a bad signature", "peer" => peer_address, "error" => e.to_string());
+            self.report_peer(peer_address, PeerEvent::InvalidSignature);
+            return;
+        }
+        // Rejections here are transactions already included or pending, which honest peers send too
+        if api::admit_transaction(&TX_POOL, transaction.clone()).is_ok() {
+            self.report_peer(peer_address, PeerEvent::UsefulTransaction);
+            self.gossip(P2PMessage::Transaction { transaction }, Some(peer_address));
+        }
+    }
+}
+
+/// IP part of a peer address given as `ip:port` or as a bare IP.
+pub fn peer_ip(peer_address: &str) -> String {
+    if let Ok(address) = peer_address.parse::<SocketAddr>() {
+        return address.ip().to_string();
+    }
+    match peer_address.parse::<IpAddr>() {
+        Ok(ip) => ip.to_string(),
+        Err(_) => peer_address.to_string(),
+    }
+}
+
+/// Key reputation and bans are kept under: the IP of a public peer, or
+/// `ip:port` on loopback and private addresses, where several nodes may
+/// share one IP. A bare IP is its own key.
+pub fn peer_key(peer_address: &str) -> String {
+    match peer_address.parse::<SocketAddr>() {
+        Ok(address) if is_local(address.ip()) => address.to_string(),
+        _ => peer_ip(peer_address),
+    }
 }
 
-fn start_listener(
-    listen_address: &str,
-    _blockchain: BlockchainArc,
-    _connected_peers: PeersArc,
-    _config: NodeConfig,
-) -> Result<(), Box<dyn std::error::Error>> {
-    println!("🔌 P2P listener would bind to {}", listen_address);
-    println!("🔌 Basic P2P networking ready (synchronous implementation)");
-    Ok(())
+fn is_local(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => ip.is_loopback() || ip.is_private() || ip.is_link_local(),
+        IpAddr::V6(ip) => ip.is_loopback() || ip.is_unique_local() || ip.is_unicast_link_local(),
+    }
 }
 
-fn handle_peer_connection(
-    _socket: TcpStream,
-    peer_address: String,
-    _blockchain: BlockchainArc,
-    _connected_peers: PeersArc,
-) -> Result<(), Box<dyn std::error::Error>> {
-    println!("🔗 Would handle peer connection from {} (synchronous)", peer_address);
-    Ok(())
+/// `host:port` of a bootnode given as an enode URL or as a plain address.
+fn bootnode_address(bootnode: &str) -> &str {
+    let address = bootnode.strip_prefix("enode://").unwrap_or(bootnode);
+    address.rsplit_once('@').map_or(address, |(_, address)| address)
 }
 
+fn connect(address: &str) -> Result<TcpStream, String> {
+    let resolved = address
+        .to_socket_addrs()
+        .map_err(|e| e.to_string())?
+        .next()
+        .ok_or("address did not resolve")?;
+    TcpStream::connect_timeout(&resolved, DIAL_TIMEOUT).map_err(|e| e.to_string())
+}
+
+fn ip_of(stream: &TcpStream) -> IpAddr {
+    stream
+        .peer_addr()
+        .map(|address| address.ip())
+        .unwrap_or(IpAddr::from([0, 0, 0, 0]))
+}
 
 fn current_timestamp() -> u64 {
     SystemTime::now()
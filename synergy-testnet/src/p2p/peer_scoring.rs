@@ -0,0 +1,366 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use crate::rate_limit::RateLimiter;
+use crate::storage;
+pub use crate::rate_limit::RateLimitConfig;
+
+pub const BAN_LIST_PATH: &str = "data/peer_bans.json";
+
+const MAX_SCORE: f64 = 100.0;
+const MIN_SCORE: f64 = -100.0;
+
+/// Something a peer did that changes its reputation.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum PeerEvent {
+    InvalidSignature,
+    BadBlock,
+    OversizedMessage,
+    ProtocolViolation,
+    RateLimitExceeded,
+    UsefulBlock,
+    UsefulTransaction,
+}
+
+impl PeerEvent {
+    pub fn score_delta(&self) -> f64 {
+        match self {
+            PeerEvent::InvalidSignature => -20.0,
+            PeerEvent::BadBlock => -40.0,
+            PeerEvent::OversizedMessage => -15.0,
+            PeerEvent::ProtocolViolation => -25.0,
+            PeerEvent::RateLimitExceeded => -5.0,
+            PeerEvent::UsefulBlock => 2.0,
+            PeerEvent::UsefulTransaction => 0.5,
+        }
+    }
+}
+
+/// Gossip message categories, each with its own rate limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MessageType {
+    Block,
+    Transaction,
+    BlockRequest,
+    PeerExchange,
+    Ping,
+}
+
+/// What the network layer should do with a peer after an event.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PeerAction {
+    Keep,
+    Drop,
+    Ban,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeerScoringConfig {
+    pub ban_threshold: f64,
+    pub disconnect_threshold: f64,
+    pub temp_ban_secs: u64,
+    pub max_temp_bans: u32,
+    pub score_decay_per_hour: f64,
+    pub max_message_size: usize,
+    pub block_rate: RateLimitConfig,
+    pub transaction_rate: RateLimitConfig,
+    pub block_request_rate: RateLimitConfig,
+    pub peer_exchange_rate: RateLimitConfig,
+    pub ping_rate: RateLimitConfig,
+}
+
+impl Default for PeerScoringConfig {
+    fn default() -> Self {
+        PeerScoringConfig {
+            ban_threshold: -50.0,
+            disconnect_threshold: -30.0,
+            temp_ban_secs: 3600,
+            max_temp_bans: 3,
+            score_decay_per_hour: 10.0,
+            max_message_size: 2 * 1024 * 1024, // 2MB
+            block_rate: RateLimitConfig { capacity: 10, refill_per_sec: 1.0 },
+            transaction_rate: RateLimitConfig { capacity: 500, refill_per_sec: 100.0 },
+            block_request_rate: RateLimitConfig { capacity: 50, refill_per_sec: 5.0 },
+            peer_exchange_rate: RateLimitConfig { capacity: 5, refill_per_sec: 0.1 },
+            ping_rate: RateLimitConfig { capacity: 10, refill_per_sec: 1.0 },
+        }
+    }
+}
+
+impl PeerScoringConfig {
+    fn rate_for(&self, message_type: MessageType) -> &RateLimitConfig {
+        match message_type {
+            MessageType::Block => &self.block_rate,
+            MessageType::Transaction => &self.transaction_rate,
+            MessageType::BlockRequest => &self.block_request_rate,
+            MessageType::PeerExchange => &self.peer_exchange_rate,
+            MessageType::Ping => &self.ping_rate,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeerScore {
+    pub address: String,
+    pub score: f64,
+    pub invalid_signatures: u64,
+    pub bad_blocks: u64,
+    pub oversized_messages: u64,
+    pub protocol_violations: u64,
+    pub useful_messages: u64,
+    pub last_updated: u64,
+}
+
+impl PeerScore {
+    fn new(address: &str) -> Self {
+        PeerScore {
+            address: address.to_string(),
+            score: 0.0,
+            invalid_signatures: 0,
+            bad_blocks: 0,
+            oversized_messages: 0,
+            protocol_violations: 0,
+            useful_messages: 0,
+            last_updated: current_timestamp(),
+        }
+    }
+
+    /// Pulls the score back towards zero so old offences (and old good
+    /// behaviour) fade out over time.
+    fn apply_decay(&mut self, decay_per_hour: f64, now: u64) {
+        let hours = now.saturating_sub(self.last_updated) as f64 / 3600.0;
+        let decay = hours * decay_per_hour;
+        if self.score > 0.0 {
+            self.score = (self.score - decay).max(0.0);
+        } else {
+            self.score = (self.score + decay).min(0.0);
+        }
+        self.last_updated = now;
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BanEntry {
+    pub address: String,
+    pub reason: String,
+    pub banned_at: u64,
+    pub expires_at: Option<u64>, // None = permanent
+}
+
+impl BanEntry {
+    pub fn is_active(&self, now: u64) -> bool {
+        self.expires_at.is_none_or(|expires_at| now < expires_at)
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct BanList {
+    bans: HashMap<String, BanEntry>,
+    temp_ban_counts: HashMap<String, u32>,
+}
+
+#[derive(Debug)]
+pub struct PeerScoreManager {
+    config: PeerScoringConfig,
+    ban_list_path: String,
+    scores: Mutex<HashMap<String, PeerScore>>,
+    ban_list: Mutex<BanList>,
+    rate_limiters: Mutex<HashMap<MessageType, RateLimiter<String>>>,
+}
+
+impl PeerScoreManager {
+    pub fn new(config: PeerScoringConfig, ban_list_path: &str) -> Self {
+        let manager = PeerScoreManager {
+            config,
+            ban_list_path: ban_list_path.to_string(),
+            scores: Mutex::new(HashMap::new()),
+            ban_list: Mutex::new(BanList::default()),
+            rate_limiters: Mutex::new(HashMap::new()),
+        };
+
+        if let Err(e) = manager.load_bans() {
+            crate::warn!("p2p", "Failed to load peer ban list", "path" => ban_list_path, "error" => e.to_string());
+        }
+
+        manager
+    }
+
+    pub fn config(&self) -> &PeerScoringConfig {
+        &self.config
+    }
+
+    /// Applies `event` to the peer's score and decides what to do with it.
+    /// Crossing the ban threshold bans the peer; after `max_temp_bans`
+    /// temporary bans the next one is permanent.
+    pub fn record_event(&self, address: &str, event: PeerEvent) -> PeerAction {
+        let now = current_timestamp();
+        let score = {
+            let mut scores = self.scores.lock().unwrap();
+            let entry = scores.entry(address.to_string()).or_insert_with(|| PeerScore::new(address));
+            entry.apply_decay(self.config.score_decay_per_hour, now);
+            entry.score = (entry.score + event.score_delta()).clamp(MIN_SCORE, MAX_SCORE);
+
+            match event {
+                PeerEvent::InvalidSignature => entry.invalid_signatures += 1,
+                PeerEvent::BadBlock => entry.bad_blocks += 1,
+                PeerEvent::OversizedMessage => entry.oversized_messages += 1,
+                PeerEvent::ProtocolViolation | PeerEvent::RateLimitExceeded => entry.protocol_violations += 1,
+                PeerEvent::UsefulBlock | PeerEvent::UsefulTransaction => entry.useful_messages += 1,
+            }
+
+            entry.score
+        };
+
+        if score <= self.config.ban_threshold {
+            let reason = format!("Score {:.1} fell below ban threshold after {:?}", score, event);
+            let temp_bans = self.ban_list.lock().unwrap().temp_ban_counts.get(address).copied().unwrap_or(0);
+            let duration = if temp_bans >= self.config.max_temp_bans {
+                None
+            } else {
+                Some(self.config.temp_ban_secs)
+            };
+            self.ban_peer(address, duration, &reason);
+            PeerAction::Ban
+        } else if score <= self.config.disconnect_threshold {
+            PeerAction::Drop
+        } else {
+            PeerAction::Keep
+        }
+    }
+
+    /// Returns false when the peer has used up its budget for this message type.
+    pub fn check_rate_limit(&self, address: &str, message_type: MessageType) -> bool {
+        let mut limiters = self.rate_limiters.lock().unwrap();
+        let rate = self.config.rate_for(message_type);
+        limiters
+            .entry(message_type)
+            .or_insert_with(|| RateLimiter::new(rate.capacity, rate.refill_per_sec))
+            .check(&address.to_string())
+    }
+
+    /// Bans a peer. `duration_secs` of `None` makes the ban permanent.
+    pub fn ban_peer(&self, address: &str, duration_secs: Option<u64>, reason: &str) {
+        let now = current_timestamp();
+        {
+            let mut ban_list = self.ban_list.lock().unwrap();
+            if duration_secs.is_some() {
+                *ban_list.temp_ban_counts.entry(address.to_string()).or_insert(0) += 1;
+            }
+            ban_list.bans.insert(address.to_string(), BanEntry {
+                address: address.to_string(),
+                reason: reason.to_string(),
+                banned_at: now,
+                expires_at: duration_secs.map(|secs| now + secs),
+            });
+        }
+
+        // A fresh start once the ban is served
+        self.scores.lock().unwrap().remove(address);
+        self.forget_rate_limits(address);
+
+        crate::warn!("p2p", "Banned peer",
+            "peer" => address,
+            "duration" => duration_secs.map_or("permanent".to_string(), |secs| format!("{}s", secs)),
+            "reason" => reason);
+
+        if let Err(e) = self.save_bans() {
+            crate::error!("p2p", "Failed to persist peer ban list", "error" => e.to_string());
+        }
+    }
+
+    pub fn unban_peer(&self, address: &str) -> bool {
+        let removed = {
+            let mut ban_list = self.ban_list.lock().unwrap();
+            ban_list.temp_ban_counts.remove(address);
+            ban_list.bans.remove(address).is_some()
+        };
+
+        if removed {
+            if let Err(e) = self.save_bans() {
+                crate::error!("p2p", "Failed to persist peer ban list", "error" => e.to_string());
+            }
+        }
+
+        removed
+    }
+
+    pub fn is_banned(&self, address: &str) -> bool {
+        let now = current_timestamp();
+        let ban_list = self.ban_list.lock().unwrap();
+        ban_list.bans.get(address).is_some_and(|ban| ban.is_active(now))
+    }
+
+    pub fn get_score(&self, address: &str) -> f64 {
+        self.scores.lock().unwrap().get(address).map_or(0.0, |score| score.score)
+    }
+
+    pub fn get_peer_score(&self, address: &str) -> Option<PeerScore> {
+        self.scores.lock().unwrap().get(address).cloned()
+    }
+
+    pub fn get_banned_peers(&self) -> Vec<BanEntry> {
+        let now = current_timestamp();
+        let ban_list = self.ban_list.lock().unwrap();
+        ban_list.bans.values().filter(|ban| ban.is_active(now)).cloned().collect()
+    }
+
+    pub fn forget_rate_limits(&self, address: &str) {
+        let key = address.to_string();
+        for limiter in self.rate_limiters.lock().unwrap().values_mut() {
+            limiter.remove(&key);
+        }
+    }
+
+    /// Drops expired bans and idle rate limiter buckets.
+    pub fn prune(&self) {
+        let now = current_timestamp();
+        let expired = {
+            let mut ban_list = self.ban_list.lock().unwrap();
+            let before = ban_list.bans.len();
+            ban_list.bans.retain(|_, ban| ban.is_active(now));
+            before != ban_list.bans.len()
+        };
+
+        if expired {
+            if let Err(e) = self.save_bans() {
+                crate::error!("p2p", "Failed to persist peer ban list", "error" => e.to_string());
+            }
+        }
+
+        for limiter in self.rate_limiters.lock().unwrap().values_mut() {
+            limiter.prune_idle();
+        }
+    }
+
+    pub fn save_bans(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let json = {
+            let ban_list = self.ban_list.lock().unwrap();
+            serde_json::to_string_pretty(&*ban_list)?
+        };
+
+        storage::write_atomic(&self.ban_list_path, json.as_bytes())?;
+        Ok(())
+    }
+
+    pub fn load_bans(&self) -> Result<(), Box<dyn std::error::Error>> {
+        if !Path::new(&self.ban_list_path).exists() {
+            return Ok(());
+        }
+
+        let content = fs::read_to_string(&self.ban_list_path)?;
+        let loaded: BanList = serde_json::from_str(&content)?;
+        *self.ban_list.lock().unwrap() = loaded;
+        Ok(())
+    }
+}
+
+fn current_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
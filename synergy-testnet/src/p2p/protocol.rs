@@ -0,0 +1,134 @@
+//! Wire format of the P2P protocol: one JSON message per line.
+//!
+//! Both sides open a connection with `Hello`. Nothing else is accepted
+//! until the handshake has passed.
+
+use std::io::{self, BufRead, Read, Write};
+
+use serde::{Deserialize, Serialize};
+
+use super::peer_scoring::MessageType;
+use crate::block::Block;
+use crate::crypto::keys::{self, KeyPair};
+use crate::transaction::Transaction;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum P2PMessage {
+    /// Identifies the node and the network it is on.
+    Hello {
+        /// Random per process; tells two connections to the same node apart
+        /// from connections to two nodes behind one IP.
+        node_id: String,
+        chain_id: u64,
+        genesis_hash: String,
+        /// Port the node accepts connections on.
+        listen_port: u16,
+    },
+    /// Sent before closing a connection on purpose.
+    Disconnect { reason: String },
+    /// The sender's chain head and clock, sent after the handshake, when
+    /// its head moves and every few seconds after that.
+    Status {
+        height: u64,
+        head_hash: String,
+        timestamp: u64,
+        /// Heads of the active validators whose keys the sender holds, each
+        /// signed by the validator.
+        #[serde(default)]
+        votes: Vec<HeadVote>,
+    },
+    Block { block: Block },
+    Transaction { transaction: Transaction },
+    /// Asks for consecutive blocks starting at height `from`.
+    GetBlocks { from: u64 },
+    /// Answers `GetBlocks` with as many blocks as fit in one message, in
+    /// height order. Empty if the sender has no block at that height.
+    Blocks { blocks: Vec<Block> },
+}
+
+/// A validator's signed statement that its chain has block `hash` at
+/// `height`. Finality counts these rather than a peer's word.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HeadVote {
+    pub validator: String,
+    pub height: u64,
+    pub hash: String,
+    /// Hex ML-DSA-87 signature by the validator's consensus key.
+    pub signature: String,
+}
+
+impl HeadVote {
+    pub fn sign(keypair: &KeyPair, validator: &str, height: u64, hash: &str) -> Self {
+        let signature = hex::encode(keypair.sign(&Self::message(validator, height, hash)));
+        HeadVote { validator: validator.to_string(), height, hash: hash.to_string(), signature }
+    }
+
+    /// Whether the vote is signed by `public_key`, given in hex. Checked
+    /// through the signature cache, as peers repeat their votes.
+    pub fn verify(&self, public_key: &str) -> bool {
+        match (hex::decode(public_key), hex::decode(&self.signature)) {
+            (Ok(public_key), Ok(signature)) => {
+                keys::verify_cached(&public_key, &Self::message(&self.validator, self.height, &self.hash), &signature)
+            }
+            _ => false,
+        }
+    }
+
+    fn message(validator: &str, height: u64, hash: &str) -> Vec<u8> {
+        format!("synergy head vote:{}:{}:{}", validator, height, hash).into_bytes()
+    }
+}
+
+impl P2PMessage {
+    /// The rate limit the message counts against.
+    pub fn message_type(&self) -> MessageType {
+        match self {
+            P2PMessage::Hello { .. } | P2PMessage::Disconnect { .. } | P2PMessage::Status { .. } => MessageType::Ping,
+            P2PMessage::Block { .. } => MessageType::Block,
+            P2PMessage::Transaction { .. } => MessageType::Transaction,
+            // Sync traffic, kept apart from the gossip of new blocks
+            P2PMessage::GetBlocks { .. } | P2PMessage::Blocks { .. } => MessageType::BlockRequest,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ReadError {
+    Io(io::Error),
+    /// The line ran past the size limit; the rest of it was not read.
+    Oversized,
+    /// A complete line that is not a message, and its size.
+    Malformed(String, usize),
+}
+
+/// Reads the next message and its size in bytes, or `None` at the end of
+/// the stream. At most `max_size` bytes are buffered.
+pub fn read_message(reader: &mut impl BufRead, max_size: usize) -> Result<Option<(P2PMessage, usize)>, ReadError> {
+    let mut line = Vec::new();
+    let read = reader
+        .take((max_size as u64).saturating_add(1))
+        .read_until(b'\n', &mut line)
+        .map_err(ReadError::Io)?;
+    if read == 0 {
+        return Ok(None);
+    }
+    if line.last() != Some(&b'\n') {
+        return if read > max_size {
+            Err(ReadError::Oversized)
+        } else {
+            // Closed in the middle of a message
+            Ok(None)
+        };
+    }
+    serde_json::from_slice(&line)
+        .map(|message| Some((message, read)))
+        .map_err(|e| ReadError::Malformed(e.to_string(), read))
+}
+
+pub fn write_message(writer: &mut impl Write, message: &P2PMessage) -> io::Result<()> {
+    let mut line = serde_json::to_vec(message).map_err(io::Error::other)?;
+    line.push(b'\n');
+    writer.write_all(&line)?;
+    writer.flush()
+}
@@ -0,0 +1,97 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+
+/// Bucket size and refill rate for one rate-limited category.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RateLimitConfig {
+    pub capacity: u32,
+    pub refill_per_sec: f64,
+}
+
+/// A classic token bucket: holds up to `capacity` tokens and refills at
+/// `refill_per_sec`. Each admitted unit of work consumes tokens.
+#[derive(Debug, Clone)]
+pub struct TokenBucket {
+    capacity: f64,
+    tokens: f64,
+    refill_per_sec: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    pub fn new(capacity: u32, refill_per_sec: f64) -> Self {
+        TokenBucket {
+            capacity: capacity as f64,
+            tokens: capacity as f64,
+            refill_per_sec,
+            last_refill: Instant::now(),
+        }
+    }
+
+    pub fn try_consume(&mut self, amount: u32) -> bool {
+        self.refill();
+
+        if self.tokens >= amount as f64 {
+            self.tokens -= amount as f64;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn available(&mut self) -> u32 {
+        self.refill();
+        self.tokens as u32
+    }
+
+    fn refill(&mut self) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        self.last_refill = now;
+    }
+}
+
+/// Keeps one `TokenBucket` per key (peer address, client IP, ...).
+#[derive(Debug)]
+pub struct RateLimiter<K: Eq + Hash + Clone> {
+    capacity: u32,
+    refill_per_sec: f64,
+    buckets: HashMap<K, TokenBucket>,
+}
+
+impl<K: Eq + Hash + Clone> RateLimiter<K> {
+    pub fn new(capacity: u32, refill_per_sec: f64) -> Self {
+        RateLimiter {
+            capacity,
+            refill_per_sec,
+            buckets: HashMap::new(),
+        }
+    }
+
+    pub fn check(&mut self, key: &K) -> bool {
+        self.check_n(key, 1)
+    }
+
+    pub fn check_n(&mut self, key: &K, amount: u32) -> bool {
+        let (capacity, refill_per_sec) = (self.capacity, self.refill_per_sec);
+        self.buckets
+            .entry(key.clone())
+            .or_insert_with(|| TokenBucket::new(capacity, refill_per_sec))
+            .try_consume(amount)
+    }
+
+    pub fn remove(&mut self, key: &K) {
+        self.buckets.remove(key);
+    }
+
+    /// Drops buckets that have refilled completely, so idle keys do not
+    /// accumulate forever.
+    pub fn prune_idle(&mut self) {
+        let capacity = self.capacity;
+        self.buckets.retain(|_, bucket| bucket.available() < capacity);
+    }
+}
@@ -0,0 +1,201 @@
+//! Typed node queries and commands shared by the JSON-RPC, WebSocket and
+//! gRPC front ends. Each front end only translates its wire format to and
+//! from these calls.
+
+use std::sync::{Arc, Mutex};
+
+use crate::aivm::AIVMRuntime;
+use crate::aivm::distributed_ai::ComputationStatus;
+use crate::aivm::runtime::{AIVMExecutionContext, AIVMExecutionResult, ContractType};
+use crate::block::{Block, BlockChain};
+use crate::events::{LogEntry, NodeEvent, EVENT_BUS};
+use crate::key_rotation::{KeyRotation, KEY_REGISTRY};
+use crate::name_service::{NameRecord, NAME_REGISTRY};
+use crate::nonce::NONCE_REGISTRY;
+use crate::p2p;
+use crate::token::{Token, TOKEN_MANAGER};
+use crate::transaction::Transaction;
+use crate::validator::{Validator, ValidatorManager};
+use super::error::RpcError;
+
+pub fn latest_block(chain: &Arc<Mutex<BlockChain>>) -> Option<Block> {
+    chain.lock().unwrap().last().cloned()
+}
+
+pub fn block_by_number(chain: &Arc<Mutex<BlockChain>>, number: u64) -> Option<Block> {
+    chain.lock().unwrap().chain.iter().find(|b| b.block_index == number).cloned()
+}
+
+pub fn block_by_hash(chain: &Arc<Mutex<BlockChain>>, hash: &str) -> Option<Block> {
+    let hash = hash.trim_start_matches("0x");
+    chain.lock().unwrap().chain.iter().find(|b| b.hash == hash).cloned()
+}
+
+/// Blocks with a height in `start..=end`.
+pub fn block_range(chain: &Arc<Mutex<BlockChain>>, start: u64, end: u64) -> Vec<Block> {
+    chain
+        .lock()
+        .unwrap()
+        .chain
+        .iter()
+        .filter(|b| b.block_index >= start && b.block_index <= end)
+        .cloned()
+        .collect()
+}
+
+pub fn transaction_by_hash(chain: &Arc<Mutex<BlockChain>>, hash: &str) -> Option<Transaction> {
+    let hash = hash.trim_start_matches("0x");
+    chain
+        .lock()
+        .unwrap()
+        .chain
+        .iter()
+        .flat_map(|b| b.transactions.iter())
+        .find(|tx| tx.hash() == hash)
+        .cloned()
+}
+
+pub fn pending_transactions(tx_pool: &Arc<Mutex<Vec<Transaction>>>) -> Vec<Transaction> {
+    tx_pool.lock().unwrap().clone()
+}
+
+/// Admits `tx` to the pool and gossips it to peers. Returns the
+/// transaction hash.
+pub fn submit_transaction(tx_pool: &Arc<Mutex<Vec<Transaction>>>, tx: Transaction) -> Result<String, RpcError> {
+    let hash = admit_transaction(tx_pool, tx.clone())?;
+    if let Some(network) = p2p::network() {
+        network.broadcast_transaction(&tx);
+    }
+    Ok(hash)
+}
+
+/// Validates `tx`, adds it to the pool and announces it to subscribers,
+/// without gossiping it; peers relay what they receive themselves. A nonce
+/// that was already used or is already pending is rejected.
+pub fn admit_transaction(tx_pool: &Arc<Mutex<Vec<Transaction>>>, tx: Transaction) -> Result<String, RpcError> {
+    let validation = tx.validate();
+    if !validation.is_valid {
+        return Err(RpcError::transaction_rejected(
+            validation.error_message.unwrap_or_else(|| "Invalid transaction".to_string()),
+        ));
+    }
+
+    let hash = tx.hash();
+    // Consensus locks the pool before the nonces
+    let mut pool = tx_pool.lock().unwrap();
+    NONCE_REGISTRY
+        .lock()
+        .unwrap()
+        .check_pending(&tx, &pool)
+        .map_err(RpcError::transaction_rejected)?;
+    pool.push(tx.clone());
+    drop(pool);
+    EVENT_BUS.publish(NodeEvent::PendingTransaction(tx));
+    Ok(hash)
+}
+
+/// Nonce the next transaction of `address` should carry, after its
+/// pending transactions.
+pub fn next_nonce(tx_pool: &Arc<Mutex<Vec<Transaction>>>, address: &str) -> u64 {
+    let pool = tx_pool.lock().unwrap();
+    NONCE_REGISTRY.lock().unwrap().pending_nonce(address, &pool)
+}
+
+pub fn validator(validator_manager: &Arc<ValidatorManager>, address: &str) -> Option<Validator> {
+    validator_manager.get_validator(address)
+}
+
+pub fn active_validators(validator_manager: &Arc<ValidatorManager>) -> Vec<Validator> {
+    validator_manager.get_active_validators()
+}
+
+/// Key rotations of `address`, oldest first.
+pub fn key_history(address: &str) -> Vec<KeyRotation> {
+    KEY_REGISTRY.lock().unwrap().history(address).to_vec()
+}
+
+/// Record of `name` if it resolves for the next block.
+pub fn resolve_name(name: &str) -> Option<NameRecord> {
+    let names = NAME_REGISTRY.lock().unwrap();
+    names.resolve(name, names.height() + 1).cloned()
+}
+
+/// Record of the primary name of `address` if it resolves to it for the
+/// next block.
+pub fn lookup_address(address: &str) -> Option<NameRecord> {
+    let names = NAME_REGISTRY.lock().unwrap();
+    names.lookup(address, names.height() + 1).cloned()
+}
+
+pub fn token_balance(address: &str, symbol: &str) -> u64 {
+    TOKEN_MANAGER.get_balance(address, symbol)
+}
+
+pub fn tokens() -> Vec<Token> {
+    TOKEN_MANAGER.get_all_tokens()
+}
+
+pub fn parse_contract_type(contract_type: &str) -> ContractType {
+    match contract_type {
+        "ai" => ContractType::AIEnhanced,
+        "cross_chain" => ContractType::CrossChain,
+        "oracle" => ContractType::Oracle,
+        _ => ContractType::Standard,
+    }
+}
+
+/// Deploys a contract on behalf of the node and returns its address.
+pub fn deploy_contract(
+    aivm_runtime: &Arc<AIVMRuntime>,
+    bytecode: Vec<u8>,
+    abi: String,
+    contract_type: ContractType,
+) -> Result<String, RpcError> {
+    aivm_runtime
+        .deploy_contract(bytecode, abi, "system".to_string(), contract_type)
+        .map_err(RpcError::aivm)
+}
+
+/// Runs a contract outside of a block and publishes its logs.
+pub fn execute_contract(
+    aivm_runtime: &Arc<AIVMRuntime>,
+    contract_address: &str,
+    input: Vec<u8>,
+) -> Result<AIVMExecutionResult, RpcError> {
+    let context = AIVMExecutionContext {
+        transaction_hash: "manual_execution".to_string(),
+        block_height: 0,
+        timestamp: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs(),
+        sender: "manual".to_string(),
+        contract_address: Some(contract_address.to_string()),
+        input_data: input,
+        gas_limit: 1000000,
+        gas_price: 1000,
+    };
+
+    let result = aivm_runtime
+        .execute_contract(contract_address, context)
+        .map_err(RpcError::aivm)?;
+
+    for log in &result.logs {
+        EVENT_BUS.publish(NodeEvent::Log(LogEntry {
+            address: contract_address.to_string(),
+            topics: Vec::new(),
+            data: log.clone(),
+            block_number: None,
+            transaction_hash: None,
+        }));
+    }
+
+    Ok(result)
+}
+
+pub fn computation_status(aivm_runtime: &Arc<AIVMRuntime>, computation_id: &str) -> Result<ComputationStatus, RpcError> {
+    aivm_runtime
+        .distributed_ai()
+        .get_computation_status(computation_id)
+        .ok_or_else(|| RpcError::not_found("Computation not found"))
+}
@@ -0,0 +1,242 @@
+//! RPC method namespaces and access control.
+//!
+//! Every method belongs to one namespace:
+//! - `public`: chain queries and transaction submission, open to everyone
+//! - `wallet`: node-held keys; token required, or a loopback caller when
+//!   `allow_loopback_wallet` is set
+//! - `admin`: state-changing operator calls; off unless configured, token required
+//! - `debug`: state dumps; off unless configured, token required
+//!
+//! Tokens are sent as `Authorization: Bearer <token>`, where `<token>` is
+//! either the shared secret itself or an HS256 JWT signed with it.
+
+use std::fs;
+use std::net::IpAddr;
+
+use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
+use rand::RngCore;
+use serde_json::Value;
+
+use crate::config::RpcAuthConfig;
+use crate::storage;
+use super::error::RpcError;
+use super::methods;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Namespace {
+    Public,
+    Wallet,
+    Admin,
+    Debug,
+}
+
+impl Namespace {
+    /// Namespace declared for `method` in the registry. Unregistered
+    /// methods, including the `eth_` facade, are public.
+    pub fn of(method: &str) -> Self {
+        methods::lookup(method).map_or(Namespace::Public, |info| info.namespace)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Namespace::Public => "public",
+            Namespace::Wallet => "wallet",
+            Namespace::Admin => "admin",
+            Namespace::Debug => "debug",
+        }
+    }
+
+    /// Calls in these namespaces are audit-logged.
+    pub fn is_privileged(&self) -> bool {
+        matches!(self, Namespace::Admin | Namespace::Debug)
+    }
+}
+
+/// What the transport knows about the caller.
+#[derive(Debug, Clone, Default)]
+pub struct CallerContext {
+    pub ip: Option<IpAddr>,
+    /// Raw `Authorization` header value.
+    pub authorization: Option<String>,
+}
+
+/// An address or CIDR range from the allowlist.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IpRange {
+    network: IpAddr,
+    prefix: u8,
+}
+
+impl IpRange {
+    pub fn parse(range: &str) -> Result<Self, String> {
+        let (address, prefix) = match range.split_once('/') {
+            Some((address, prefix)) => (address, Some(prefix)),
+            None => (range, None),
+        };
+        let network: IpAddr = address
+            .trim()
+            .parse()
+            .map_err(|_| format!("Invalid IP address in allowlist: {}", range))?;
+        let max_prefix = if network.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix {
+            Some(prefix) => prefix
+                .trim()
+                .parse::<u8>()
+                .ok()
+                .filter(|p| *p <= max_prefix)
+                .ok_or_else(|| format!("Invalid prefix length in allowlist: {}", range))?,
+            None => max_prefix,
+        };
+        Ok(IpRange { network, prefix })
+    }
+
+    pub fn contains(&self, ip: &IpAddr) -> bool {
+        match (self.network, ip) {
+            (IpAddr::V4(network), IpAddr::V4(ip)) => {
+                let mask = u32::MAX.checked_shl(32 - self.prefix as u32).unwrap_or(0);
+                u32::from(network) & mask == u32::from(*ip) & mask
+            }
+            (IpAddr::V6(network), IpAddr::V6(ip)) => {
+                let mask = u128::MAX.checked_shl(128 - self.prefix as u32).unwrap_or(0);
+                u128::from(network) & mask == u128::from(*ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+pub struct RpcAuth {
+    config: RpcAuthConfig,
+    secret: Option<Vec<u8>>,
+    allowlist: Vec<IpRange>,
+}
+
+impl RpcAuth {
+    pub fn new(config: RpcAuthConfig, secret: Option<Vec<u8>>) -> Result<Self, String> {
+        let allowlist = config
+            .ip_allowlist
+            .iter()
+            .map(|range| IpRange::parse(range))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(RpcAuth { config, secret, allowlist })
+    }
+
+    /// Builds the access policy, loading the shared secret from
+    /// `token_file` (or creating it) whenever a token can unlock anything.
+    pub fn from_config(config: &RpcAuthConfig) -> Result<Self, String> {
+        let needs_secret = config.enable_admin || config.enable_debug || config.enable_wallet;
+        let secret = if needs_secret {
+            Some(load_or_create_secret(&config.token_file)?)
+        } else {
+            None
+        };
+
+        Self::new(config.clone(), secret)
+    }
+
+    pub fn is_enabled(&self, namespace: Namespace) -> bool {
+        match namespace {
+            Namespace::Public => true,
+            Namespace::Wallet => self.config.enable_wallet,
+            Namespace::Admin => self.config.enable_admin,
+            Namespace::Debug => self.config.enable_debug,
+        }
+    }
+
+    /// Checks whether `caller` may invoke `method` and returns its namespace.
+    pub fn authorize(&self, method: &str, caller: &CallerContext) -> Result<Namespace, RpcError> {
+        let namespace = Namespace::of(method);
+
+        // Disabled namespaces look exactly like unknown methods
+        if !self.is_enabled(namespace) {
+            return Err(RpcError::method_not_found(method));
+        }
+
+        match namespace {
+            Namespace::Public => Ok(namespace),
+            Namespace::Wallet => {
+                let is_local = caller.ip.is_some_and(|ip| ip.is_loopback());
+                if (is_local && self.config.allow_loopback_wallet) || self.authenticate(caller.authorization.as_deref()) {
+                    Ok(namespace)
+                } else {
+                    Err(RpcError::unauthorized("Wallet methods require a token"))
+                }
+            }
+            Namespace::Admin | Namespace::Debug => {
+                if !self.allowlist.is_empty() {
+                    let allowed = caller.ip.is_some_and(|ip| self.allowlist.iter().any(|range| range.contains(&ip)));
+                    if !allowed {
+                        return Err(RpcError::unauthorized("Caller address is not allowed"));
+                    }
+                }
+
+                if self.authenticate(caller.authorization.as_deref()) {
+                    Ok(namespace)
+                } else {
+                    Err(RpcError::unauthorized("Missing or invalid token"))
+                }
+            }
+        }
+    }
+
+    fn authenticate(&self, authorization: Option<&str>) -> bool {
+        let secret = match &self.secret {
+            Some(secret) => secret,
+            None => return false,
+        };
+        let token = match authorization.and_then(|value| value.strip_prefix("Bearer ")) {
+            Some(token) => token.trim(),
+            None => return false,
+        };
+
+        if constant_time_eq(token.as_bytes(), secret) {
+            return true;
+        }
+
+        // Anything else must be a JWT signed with the secret and not expired
+        let validation = Validation::new(Algorithm::HS256);
+        decode::<Value>(token, &DecodingKey::from_secret(secret), &validation).is_ok()
+    }
+}
+
+/// Records a privileged call, or an attempt at one, in the node log.
+pub fn audit<T>(method: &str, namespace: Namespace, caller: &CallerContext, outcome: &Result<T, RpcError>) {
+    let ip = caller.ip.map_or_else(|| "unknown".to_string(), |ip| ip.to_string());
+    match outcome {
+        Ok(_) => {
+            crate::info!("rpc::audit", "Privileged RPC call", "method" => method, "namespace" => namespace.as_str(), "ip" => ip, "outcome" => "ok");
+        }
+        Err(error) => {
+            crate::warn!("rpc::audit", "Privileged RPC call failed", "method" => method, "namespace" => namespace.as_str(), "ip" => ip, "outcome" => error.to_string());
+        }
+    }
+}
+
+fn load_or_create_secret(path: &str) -> Result<Vec<u8>, String> {
+    if let Ok(contents) = fs::read_to_string(path) {
+        let secret = contents.trim();
+        if secret.is_empty() {
+            return Err(format!("RPC token file {} is empty", path));
+        }
+        return Ok(secret.as_bytes().to_vec());
+    }
+
+    let mut bytes = [0u8; 32];
+    rand::rngs::OsRng.fill_bytes(&mut bytes);
+    let secret = hex::encode(bytes);
+
+    // Created owner-only, so the secret is never readable by others
+    storage::write_private_atomic(path, secret.as_bytes())
+        .map_err(|e| format!("Failed to write RPC token file {}: {}", path, e))?;
+
+    crate::info!("rpc", "Generated RPC auth token", "path" => path);
+    Ok(secret.into_bytes())
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
@@ -0,0 +1,113 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+// Standard JSON-RPC 2.0 error codes
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+pub const INTERNAL_ERROR: i64 = -32603;
+
+// Synergy-specific server errors (-32000 to -32099 is reserved for implementations)
+pub const RESOURCE_NOT_FOUND: i64 = -32001;
+pub const UNAUTHORIZED: i64 = -32003;
+pub const LIMIT_EXCEEDED: i64 = -32005;
+pub const TIMEOUT: i64 = -32006;
+pub const TRANSACTION_REJECTED: i64 = -32010;
+pub const WALLET_ERROR: i64 = -32020;
+pub const VALIDATOR_ERROR: i64 = -32030;
+pub const TOKEN_ERROR: i64 = -32040;
+pub const AIVM_ERROR: i64 = -32050;
+pub const NETWORK_UNAVAILABLE: i64 = -32060;
+
+/// A JSON-RPC 2.0 error object.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+}
+
+impl RpcError {
+    pub fn new(code: i64, message: impl Into<String>) -> Self {
+        RpcError {
+            code,
+            message: message.into(),
+            data: None,
+        }
+    }
+
+    pub fn with_data(mut self, data: Value) -> Self {
+        self.data = Some(data);
+        self
+    }
+
+    pub fn parse_error(detail: impl Into<String>) -> Self {
+        Self::new(PARSE_ERROR, "Parse error").with_data(Value::String(detail.into()))
+    }
+
+    pub fn invalid_request(detail: impl Into<String>) -> Self {
+        Self::new(INVALID_REQUEST, "Invalid Request").with_data(Value::String(detail.into()))
+    }
+
+    pub fn method_not_found(method: &str) -> Self {
+        Self::new(METHOD_NOT_FOUND, format!("Method not found: {}", method))
+    }
+
+    pub fn invalid_params(message: impl Into<String>) -> Self {
+        Self::new(INVALID_PARAMS, message)
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::new(INTERNAL_ERROR, message)
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(RESOURCE_NOT_FOUND, message)
+    }
+
+    pub fn unauthorized(message: impl Into<String>) -> Self {
+        Self::new(UNAUTHORIZED, message)
+    }
+
+    pub fn limit_exceeded(message: impl Into<String>) -> Self {
+        Self::new(LIMIT_EXCEEDED, message)
+    }
+
+    pub fn timeout(message: impl Into<String>) -> Self {
+        Self::new(TIMEOUT, message)
+    }
+
+    pub fn transaction_rejected(message: impl Into<String>) -> Self {
+        Self::new(TRANSACTION_REJECTED, message)
+    }
+
+    pub fn wallet(message: impl Into<String>) -> Self {
+        Self::new(WALLET_ERROR, message)
+    }
+
+    pub fn validator(message: impl Into<String>) -> Self {
+        Self::new(VALIDATOR_ERROR, message)
+    }
+
+    pub fn token(message: impl Into<String>) -> Self {
+        Self::new(TOKEN_ERROR, message)
+    }
+
+    pub fn aivm(message: impl Into<String>) -> Self {
+        Self::new(AIVM_ERROR, message)
+    }
+
+    pub fn network_unavailable(message: impl Into<String>) -> Self {
+        Self::new(NETWORK_UNAVAILABLE, message)
+    }
+}
+
+impl std::fmt::Display for RpcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.message, self.code)
+    }
+}
+
+impl std::error::Error for RpcError {}
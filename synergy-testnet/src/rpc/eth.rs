@@ -0,0 +1,365 @@
+//! Ethereum-compatible `eth_*`, `net_*` and `web3_*` methods.
+//!
+//! Maps the node's blocks, pool and balances onto the shapes Ethereum tooling
+//! expects: quantities are `0x`-prefixed hex without leading zeros, hashes
+//! are 32-byte `0x` data. A Synergy address is 19 bytes, so it appears as
+//! the 20-byte `0x` address with a zero first byte, and address parameters
+//! take either form.
+
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use serde_json::{json, Value};
+
+use crate::block::{Block, BlockChain};
+use crate::consensus::consensus_algorithm;
+use crate::nonce::{NonceRegistry, NONCE_REGISTRY};
+use crate::token::TOKEN_MANAGER;
+use crate::transaction::Transaction;
+use super::api;
+use super::error::RpcError;
+
+pub const CLIENT_VERSION: &str = concat!("synergy-testnet/v", env!("CARGO_PKG_VERSION"));
+pub const NATIVE_TOKEN: &str = "SNRG";
+pub const DEFAULT_GAS_PRICE: u64 = 1000;
+/// Number of recent blocks sampled by `eth_gasPrice`.
+const GAS_PRICE_SAMPLE_BLOCKS: usize = 20;
+const ZERO_HASH: &str = "0x0000000000000000000000000000000000000000000000000000000000000000";
+
+static CHAIN_ID: AtomicU64 = AtomicU64::new(7963749);
+
+/// Sets the chain id reported by `eth_chainId` and `net_version`.
+pub fn set_chain_id(chain_id: u64) {
+    CHAIN_ID.store(chain_id, Ordering::Relaxed);
+}
+
+pub fn chain_id() -> u64 {
+    CHAIN_ID.load(Ordering::Relaxed)
+}
+
+/// Encodes a quantity as `0x`-prefixed hex without leading zeros.
+pub fn quantity(value: u64) -> String {
+    format!("0x{:x}", value)
+}
+
+/// Parses a hex quantity (`0x1a`), also accepting plain JSON numbers.
+pub fn parse_quantity(value: &Value) -> Option<u64> {
+    match value {
+        Value::String(s) => {
+            let digits = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X"))?;
+            if digits.is_empty() || (digits.len() > 1 && digits.starts_with('0')) {
+                return None;
+            }
+            u64::from_str_radix(digits, 16).ok()
+        }
+        Value::Number(n) => n.as_u64(),
+        _ => None,
+    }
+}
+
+/// The 20-byte `0x` form of a Synergy address: its 38 hex digits after a
+/// zero byte. `None` for anything that is not a Synergy address.
+pub fn eth_address(address: &str) -> Option<String> {
+    Transaction::is_valid_address(address).then(|| format!("0x00{}", &address[3..]))
+}
+
+/// The Synergy address of a 20-byte `0x` address, or of a Synergy address
+/// itself. `None` for addresses outside the Synergy range, whose first byte
+/// is not zero.
+pub fn synergy_address(address: &str) -> Option<String> {
+    if Transaction::is_valid_address(address) {
+        return Some(address.to_string());
+    }
+    let digits = address.strip_prefix("0x").or_else(|| address.strip_prefix("0X"))?.to_lowercase();
+    let native = format!("sYn{}", digits.strip_prefix("00").filter(|_| digits.len() == 40)?);
+    Transaction::is_valid_address(&native).then_some(native)
+}
+
+/// Formats a node hash as 32-byte `0x` data.
+pub fn hash_data(hash: &str) -> String {
+    format!("0x{:0>64}", hash.trim_start_matches("0x"))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BlockTag {
+    Number(u64),
+    Latest,
+    Earliest,
+    Pending,
+}
+
+impl BlockTag {
+    pub fn from_value(value: Option<&Value>) -> Result<Self, RpcError> {
+        match value {
+            None | Some(Value::Null) => Ok(BlockTag::Latest),
+            Some(Value::String(tag)) => match tag.as_str() {
+                "latest" => Ok(BlockTag::Latest),
+                // There is no separate safe head; both name the finalized block
+                "safe" | "finalized" => Ok(BlockTag::Number(consensus_algorithm::finalized_height())),
+                "earliest" => Ok(BlockTag::Earliest),
+                "pending" => Ok(BlockTag::Pending),
+                _ => parse_quantity(&Value::String(tag.clone()))
+                    .map(BlockTag::Number)
+                    .ok_or_else(|| RpcError::invalid_params(format!("Invalid block tag: {}", tag))),
+            },
+            Some(other) => parse_quantity(other)
+                .map(BlockTag::Number)
+                .ok_or_else(|| RpcError::invalid_params("Invalid block tag")),
+        }
+    }
+}
+
+pub fn handle(
+    method: &str,
+    params: Value,
+    tx_pool: &Arc<Mutex<Vec<Transaction>>>,
+    chain: &Arc<Mutex<BlockChain>>,
+) -> Result<Value, RpcError> {
+    match method {
+        "web3_clientVersion" => Ok(json!(CLIENT_VERSION)),
+
+        "net_version" => Ok(json!(chain_id().to_string())),
+
+        "net_listening" => Ok(json!(true)),
+
+        "eth_chainId" => Ok(json!(quantity(chain_id()))),
+
+        "eth_syncing" => Ok(json!(false)),
+
+        "eth_blockNumber" => {
+            let chain = chain.lock().unwrap();
+            Ok(json!(quantity(chain.last().map_or(0, |b| b.block_index))))
+        }
+
+        "eth_gasPrice" => {
+            let chain = chain.lock().unwrap();
+            Ok(json!(quantity(suggested_gas_price(&chain))))
+        }
+
+        "eth_getBlockByNumber" => {
+            let tag = BlockTag::from_value(params.get(0))?;
+            let full = params.get(1).and_then(|v| v.as_bool()).unwrap_or(false);
+            let chain = chain.lock().unwrap();
+            Ok(find_block(&chain, tag).map_or(Value::Null, |block| block_json(block, full)))
+        }
+
+        "eth_getBlockByHash" => {
+            let hash = hash_param(&params, 0)?;
+            let full = params.get(1).and_then(|v| v.as_bool()).unwrap_or(false);
+            let chain = chain.lock().unwrap();
+            Ok(chain
+                .chain
+                .iter()
+                .find(|b| hash_data(&b.hash) == hash)
+                .map_or(Value::Null, |block| block_json(block, full)))
+        }
+
+        "eth_getBlockTransactionCountByNumber" => {
+            let tag = BlockTag::from_value(params.get(0))?;
+            let chain = chain.lock().unwrap();
+            Ok(find_block(&chain, tag).map_or(Value::Null, |b| json!(quantity(b.transactions.len() as u64))))
+        }
+
+        "eth_getTransactionByHash" => {
+            let hash = hash_param(&params, 0)?;
+            {
+                let chain = chain.lock().unwrap();
+                if let Some((block, index, tx)) = find_transaction(&chain, &hash) {
+                    return Ok(transaction_json(tx, Some((block, index))));
+                }
+            }
+            let pool = tx_pool.lock().unwrap();
+            Ok(pool
+                .iter()
+                .find(|tx| hash_data(&tx.hash()) == hash)
+                .map_or(Value::Null, |tx| transaction_json(tx, None)))
+        }
+
+        "eth_getTransactionReceipt" => {
+            let hash = hash_param(&params, 0)?;
+            let chain = chain.lock().unwrap();
+            // Only transactions that executed successfully are included in blocks
+            Ok(find_transaction(&chain, &hash).map_or(Value::Null, |(block, index, tx)| {
+                let mut receipt = json!({
+                    "transactionHash": hash_data(&tx.hash()),
+                    "transactionIndex": quantity(index as u64),
+                    "blockHash": hash_data(&block.hash),
+                    "blockNumber": quantity(block.block_index),
+                    "cumulativeGasUsed": quantity(block.transactions[..=index].iter().map(|t| t.gas_limit).sum()),
+                    "gasUsed": quantity(tx.gas_limit),
+                    "effectiveGasPrice": quantity(tx.gas_price),
+                    "contractAddress": null,
+                    "logs": [],
+                    "logsBloom": empty_bloom(),
+                    "status": "0x1",
+                    "type": "0x0"
+                });
+                insert_address(&mut receipt, "from", &tx.sender);
+                insert_address(&mut receipt, "to", &tx.receiver);
+                receipt
+            }))
+        }
+
+        "eth_getBalance" => {
+            let address = address_param(&params)?;
+            match BlockTag::from_value(params.get(1))? {
+                BlockTag::Latest | BlockTag::Pending => {}
+                _ => return Err(RpcError::invalid_params("Only the latest balance is kept; use latest or pending")),
+            }
+            Ok(json!(quantity(TOKEN_MANAGER.get_balance(&address, NATIVE_TOKEN))))
+        }
+
+        "eth_getTransactionCount" => {
+            let address = address_param(&params)?;
+            let tag = BlockTag::from_value(params.get(1))?;
+            // Nonces start at 1, so the last one used is the number of transactions sent
+            let count = match tag {
+                BlockTag::Latest => NONCE_REGISTRY.lock().unwrap().nonce(&address),
+                BlockTag::Pending => api::next_nonce(tx_pool, &address) - 1,
+                BlockTag::Earliest | BlockTag::Number(_) => {
+                    let last = if let BlockTag::Number(n) = tag { n } else { 0 };
+                    let mut nonces = NonceRegistry::new();
+                    for block in chain.lock().unwrap().chain.iter().take_while(|b| b.block_index <= last) {
+                        nonces.apply_block(block);
+                    }
+                    nonces.nonce(&address)
+                }
+            };
+            Ok(json!(quantity(count)))
+        }
+
+        _ => Err(RpcError::method_not_found(method)),
+    }
+}
+
+fn find_block(chain: &BlockChain, tag: BlockTag) -> Option<&Block> {
+    match tag {
+        BlockTag::Latest | BlockTag::Pending => chain.last(),
+        BlockTag::Earliest => chain.chain.first(),
+        BlockTag::Number(number) => chain.chain.iter().find(|b| b.block_index == number),
+    }
+}
+
+fn find_transaction<'a>(chain: &'a BlockChain, hash: &str) -> Option<(&'a Block, usize, &'a Transaction)> {
+    chain.chain.iter().rev().find_map(|block| {
+        block
+            .transactions
+            .iter()
+            .enumerate()
+            .find(|(_, tx)| hash_data(&tx.hash()) == hash)
+            .map(|(index, tx)| (block, index, tx))
+    })
+}
+
+fn block_json(block: &Block, full_transactions: bool) -> Value {
+    let transactions: Vec<Value> = block
+        .transactions
+        .iter()
+        .enumerate()
+        .map(|(index, tx)| {
+            if full_transactions {
+                transaction_json(tx, Some((block, index)))
+            } else {
+                json!(hash_data(&tx.hash()))
+            }
+        })
+        .collect();
+    let gas_used: u64 = block.transactions.iter().map(|tx| tx.gas_limit).sum();
+
+    let mut json = json!({
+        "number": quantity(block.block_index),
+        "hash": hash_data(&block.hash),
+        "parentHash": hash_data(&block.previous_hash),
+        "nonce": format!("0x{:016x}", block.nonce),
+        "sha3Uncles": ZERO_HASH,
+        "logsBloom": empty_bloom(),
+        "transactionsRoot": ZERO_HASH,
+        "stateRoot": ZERO_HASH,
+        "receiptsRoot": ZERO_HASH,
+        "difficulty": "0x0",
+        "totalDifficulty": "0x0",
+        "extraData": "0x",
+        "size": quantity(serde_json::to_vec(block).map_or(0, |bytes| bytes.len() as u64)),
+        "gasLimit": quantity(gas_used),
+        "gasUsed": quantity(gas_used),
+        "timestamp": quantity(block.timestamp),
+        "transactions": transactions,
+        "uncles": []
+    });
+    insert_address(&mut json, "miner", &block.validator_id);
+    json
+}
+
+fn transaction_json(tx: &Transaction, location: Option<(&Block, usize)>) -> Value {
+    let (block_hash, block_number, index) = match location {
+        Some((block, index)) => (
+            json!(hash_data(&block.hash)),
+            json!(quantity(block.block_index)),
+            json!(quantity(index as u64)),
+        ),
+        None => (Value::Null, Value::Null, Value::Null),
+    };
+
+    let mut json = json!({
+        "hash": hash_data(&tx.hash()),
+        "nonce": quantity(tx.nonce),
+        "blockHash": block_hash,
+        "blockNumber": block_number,
+        "transactionIndex": index,
+        "value": quantity(tx.amount),
+        "gas": quantity(tx.gas_limit),
+        "gasPrice": quantity(tx.gas_price),
+        "input": format!("0x{}", hex::encode(tx.data.as_deref().unwrap_or_default())),
+        "type": "0x0",
+        "chainId": quantity(chain_id())
+    });
+    insert_address(&mut json, "from", &tx.sender);
+    insert_address(&mut json, "to", &tx.receiver);
+    json
+}
+
+/// Sets `field` of `object` to the `0x` form of `address`, leaving it out
+/// when the address has none.
+fn insert_address(object: &mut Value, field: &str, address: &str) {
+    if let Some(address) = eth_address(address) {
+        object[field] = json!(address);
+    }
+}
+
+/// Median gas price over recent blocks, or `DEFAULT_GAS_PRICE` on an idle chain.
+fn suggested_gas_price(chain: &BlockChain) -> u64 {
+    let mut prices: Vec<u64> = chain
+        .chain
+        .iter()
+        .rev()
+        .take(GAS_PRICE_SAMPLE_BLOCKS)
+        .flat_map(|b| b.transactions.iter().map(|tx| tx.gas_price))
+        .collect();
+
+    if prices.is_empty() {
+        return DEFAULT_GAS_PRICE;
+    }
+
+    prices.sort_unstable();
+    prices[prices.len() / 2]
+}
+
+fn empty_bloom() -> String {
+    format!("0x{}", "0".repeat(512))
+}
+
+fn hash_param(params: &Value, index: usize) -> Result<String, RpcError> {
+    params
+        .get(index)
+        .and_then(|v| v.as_str())
+        .map(|hash| hash_data(&hash.to_lowercase()))
+        .ok_or_else(|| RpcError::invalid_params("Missing hash parameter"))
+}
+
+fn address_param(params: &Value) -> Result<String, RpcError> {
+    let address = params
+        .get(0)
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| RpcError::invalid_params("Missing address parameter"))?;
+    synergy_address(address).ok_or_else(|| RpcError::invalid_params(format!("Invalid address: {}", address)))
+}
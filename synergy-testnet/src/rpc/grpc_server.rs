@@ -0,0 +1,504 @@
+//! gRPC front end (`synergy.v1.SynergyNode`) mirroring the JSON-RPC API.
+//!
+//! Handlers call into `rpc::api` like the JSON-RPC dispatcher does; this
+//! module only converts between protobuf messages and node types.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde_json::json;
+use tokio::sync::{broadcast, mpsc};
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Request, Response, Status};
+use tonic::service::interceptor::InterceptedService;
+use tonic::transport::Server;
+
+use crate::aivm::runtime::{AIVMExecutionResult, ContractType};
+use crate::block::Block;
+use crate::config::RPCConfig;
+use crate::crypto::hybrid::HybridSignature;
+use crate::crypto::multisig::{MultisigAuth, MultisigPolicy};
+use crate::events::{NodeEvent, EVENT_BUS};
+use crate::shutdown;
+use crate::token::Token;
+use crate::transaction::Transaction;
+use crate::validator::{Validator, ValidatorStatus};
+use super::api;
+use super::auth::{self, CallerContext, Namespace, RpcAuth};
+use super::error::{self as rpc_error, RpcError};
+use super::limits::RpcLimiter;
+use super::rpc_server::{AIVM_RUNTIME, CHAIN, TX_POOL, VALIDATOR_MANAGER};
+use super::subscriptions::{
+    SubscriptionKind, CHANNEL_AI_COMPUTATIONS, CHANNEL_LOGS, CHANNEL_NEW_HEADS,
+    CHANNEL_PENDING_TRANSACTIONS, CHANNEL_VALIDATOR_STATUS,
+};
+
+pub mod proto {
+    tonic::include_proto!("synergy.v1");
+}
+
+use proto::synergy_node_server::{SynergyNode, SynergyNodeServer};
+
+/// Messages buffered per stream before the feed waits on the client.
+const STREAM_BUFFER: usize = 256;
+
+pub async fn serve_grpc(config: &RPCConfig, auth: Arc<RpcAuth>, limiter: Arc<RpcLimiter>) -> Result<(), Box<dyn std::error::Error>> {
+    let address: std::net::SocketAddr = format!("0.0.0.0:{}", config.grpc_port).parse()?;
+    crate::info!("rpc", "gRPC server running", "address" => address.to_string());
+
+    let limits = limiter.config().clone();
+    let service = SynergyNodeServer::new(NodeService::new(auth, Arc::clone(&limiter)))
+        .max_decoding_message_size(limits.max_body_size);
+
+    // Every gRPC call is charged to the same per-IP bucket as JSON-RPC calls
+    let rate_limit = move |request: Request<()>| -> Result<Request<()>, Status> {
+        if let Some(address) = request.remote_addr() {
+            limiter.check_ip(address.ip())?;
+        }
+        Ok(request)
+    };
+
+    Server::builder()
+        .timeout(Duration::from_secs(limits.request_timeout_secs))
+        .concurrency_limit_per_connection(limits.worker_threads.max(1))
+        .add_service(InterceptedService::new(service, rate_limit))
+        .serve_with_shutdown(address, shutdown::wait())
+        .await?;
+    Ok(())
+}
+
+pub struct NodeService {
+    auth: Arc<RpcAuth>,
+    limiter: Arc<RpcLimiter>,
+}
+
+impl NodeService {
+    pub fn new(auth: Arc<RpcAuth>, limiter: Arc<RpcLimiter>) -> Self {
+        NodeService { auth, limiter }
+    }
+
+    /// Runs a node API call off the async workers, on one of the worker
+    /// threads JSON-RPC calls also use; the node state sits behind
+    /// blocking locks.
+    async fn blocking<T, F>(&self, call: F) -> Result<T, Status>
+    where
+        F: FnOnce() -> Result<T, RpcError> + Send + 'static,
+        T: Send + 'static,
+    {
+        let worker = self.limiter.reserve_worker()?;
+        tokio::task::spawn_blocking(move || {
+            let _worker = worker;
+            call()
+        })
+        .await
+        .map_err(|e| Status::internal(e.to_string()))?
+        .map_err(Status::from)
+    }
+}
+
+#[tonic::async_trait]
+impl SynergyNode for NodeService {
+    type StreamBlocksStream = ReceiverStream<Result<proto::Block, Status>>;
+    type StreamEventsStream = ReceiverStream<Result<proto::Event, Status>>;
+
+    async fn get_latest_block(&self, _request: Request<proto::GetLatestBlockRequest>) -> Result<Response<proto::Block>, Status> {
+        let block = self.blocking(|| Ok(api::latest_block(&CHAIN))).await?;
+        found(block.as_ref().map(proto::Block::from), "No blocks yet")
+    }
+
+    async fn get_block_by_number(&self, request: Request<proto::GetBlockByNumberRequest>) -> Result<Response<proto::Block>, Status> {
+        let number = request.into_inner().number;
+        let block = self.blocking(move || Ok(api::block_by_number(&CHAIN, number))).await?;
+        found(block.as_ref().map(proto::Block::from), "Block not found")
+    }
+
+    async fn get_block_by_hash(&self, request: Request<proto::GetBlockByHashRequest>) -> Result<Response<proto::Block>, Status> {
+        let hash = request.into_inner().hash;
+        let block = self.blocking(move || Ok(api::block_by_hash(&CHAIN, &hash))).await?;
+        found(block.as_ref().map(proto::Block::from), "Block not found")
+    }
+
+    async fn get_transaction(&self, request: Request<proto::GetTransactionRequest>) -> Result<Response<proto::Transaction>, Status> {
+        let hash = request.into_inner().hash;
+        let tx = self.blocking(move || Ok(api::transaction_by_hash(&CHAIN, &hash))).await?;
+        found(tx.as_ref().map(proto::Transaction::from), "Transaction not found")
+    }
+
+    async fn send_transaction(&self, request: Request<proto::Transaction>) -> Result<Response<proto::SendTransactionResponse>, Status> {
+        let tx = Transaction::from(request.into_inner());
+        let hash = self.blocking(move || api::submit_transaction(&TX_POOL, tx)).await?;
+        Ok(Response::new(proto::SendTransactionResponse { hash }))
+    }
+
+    async fn get_transaction_pool(&self, _request: Request<proto::GetTransactionPoolRequest>) -> Result<Response<proto::TransactionList>, Status> {
+        let pool = self.blocking(|| Ok(api::pending_transactions(&TX_POOL))).await?;
+        Ok(Response::new(proto::TransactionList {
+            transactions: pool.iter().map(proto::Transaction::from).collect(),
+        }))
+    }
+
+    async fn get_validator(&self, request: Request<proto::GetValidatorRequest>) -> Result<Response<proto::Validator>, Status> {
+        let address = request.into_inner().address;
+        let validator = self.blocking(move || Ok(api::validator(&VALIDATOR_MANAGER, &address))).await?;
+        found(validator.as_ref().map(proto::Validator::from), "Validator not found")
+    }
+
+    async fn list_validators(&self, _request: Request<proto::ListValidatorsRequest>) -> Result<Response<proto::ValidatorList>, Status> {
+        let validators = self.blocking(|| Ok(api::active_validators(&VALIDATOR_MANAGER))).await?;
+        Ok(Response::new(proto::ValidatorList {
+            validators: validators.iter().map(proto::Validator::from).collect(),
+        }))
+    }
+
+    async fn get_token_balance(&self, request: Request<proto::GetTokenBalanceRequest>) -> Result<Response<proto::TokenBalance>, Status> {
+        let proto::GetTokenBalanceRequest { address, symbol } = request.into_inner();
+        let (address, symbol, balance) = self.blocking(move || {
+            let balance = api::token_balance(&address, &symbol);
+            Ok((address, symbol, balance))
+        }).await?;
+        Ok(Response::new(proto::TokenBalance { address, symbol, balance }))
+    }
+
+    async fn list_tokens(&self, _request: Request<proto::ListTokensRequest>) -> Result<Response<proto::TokenList>, Status> {
+        let tokens = self.blocking(|| Ok(api::tokens())).await?;
+        Ok(Response::new(proto::TokenList {
+            tokens: tokens.iter().map(proto::Token::from).collect(),
+        }))
+    }
+
+    async fn deploy_contract(&self, request: Request<proto::DeployContractRequest>) -> Result<Response<proto::DeployContractResponse>, Status> {
+        // Same access rules as synergy_deployAIVMContract
+        let caller = caller_context(&request);
+        let request = request.into_inner();
+        let contract_type = match proto::ContractType::try_from(request.contract_type) {
+            Ok(proto::ContractType::AiEnhanced) => ContractType::AIEnhanced,
+            Ok(proto::ContractType::CrossChain) => ContractType::CrossChain,
+            Ok(proto::ContractType::Oracle) => ContractType::Oracle,
+            Ok(proto::ContractType::Standard) => ContractType::Standard,
+            Err(_) => return Err(Status::invalid_argument("Unknown contract type")),
+        };
+        let auth = Arc::clone(&self.auth);
+        let contract_address = self.blocking(move || {
+            let method = "synergy_deployAIVMContract";
+            let result = auth
+                .authorize(method, &caller)
+                .and_then(|_| api::deploy_contract(&AIVM_RUNTIME, request.bytecode, request.abi, contract_type));
+            auth::audit(method, Namespace::of(method), &caller, &result);
+            result
+        }).await?;
+        Ok(Response::new(proto::DeployContractResponse { contract_address }))
+    }
+
+    async fn execute_contract(&self, request: Request<proto::ExecuteContractRequest>) -> Result<Response<proto::ExecutionResult>, Status> {
+        // Same per-method rate and concurrency cap as synergy_executeAIVMContract
+        let method = "synergy_executeAIVMContract";
+        if let Some(address) = request.remote_addr() {
+            self.limiter.check_method(address.ip(), method)?;
+        }
+        let permit = self.limiter.acquire(method)?;
+
+        let request = request.into_inner();
+        let result = self.blocking(move || {
+            let _permit = permit;
+            api::execute_contract(&AIVM_RUNTIME, &request.contract_address, request.input)
+        }).await?;
+        Ok(Response::new(proto::ExecutionResult::from(result)))
+    }
+
+    async fn get_computation_status(&self, request: Request<proto::GetComputationStatusRequest>) -> Result<Response<proto::ComputationStatus>, Status> {
+        let computation_id = request.into_inner().computation_id;
+        let id = computation_id.clone();
+        let status = self.blocking(move || api::computation_status(&AIVM_RUNTIME, &id)).await?;
+        Ok(Response::new(proto::ComputationStatus {
+            computation_id,
+            status: format!("{:?}", status),
+        }))
+    }
+
+    async fn stream_blocks(&self, request: Request<proto::StreamBlocksRequest>) -> Result<Response<Self::StreamBlocksStream>, Status> {
+        let from_number = request.into_inner().from_number;
+        // Subscribe before replaying so no block falls between the two
+        let mut events = EVENT_BUS.subscribe();
+        let (sender, receiver) = mpsc::channel(STREAM_BUFFER);
+
+        tokio::spawn(async move {
+            let mut next_number = 0;
+
+            if let Some(from_number) = from_number {
+                let replay = tokio::task::spawn_blocking(move || api::block_range(&CHAIN, from_number, u64::MAX))
+                    .await
+                    .unwrap_or_default();
+                for block in &replay {
+                    next_number = block.block_index + 1;
+                    if sender.send(Ok(proto::Block::from(block))).await.is_err() {
+                        return;
+                    }
+                }
+            }
+
+            loop {
+                match events.recv().await {
+                    Ok(NodeEvent::NewHead(block)) if block.block_index >= next_number => {
+                        next_number = block.block_index + 1;
+                        if sender.send(Ok(proto::Block::from(&block))).await.is_err() {
+                            return;
+                        }
+                    }
+                    Ok(_) => continue,
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        let _ = sender.send(Err(lagged(missed))).await;
+                        return;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return,
+                }
+            }
+        });
+
+        Ok(Response::new(ReceiverStream::new(receiver)))
+    }
+
+    async fn stream_events(&self, request: Request<proto::StreamEventsRequest>) -> Result<Response<Self::StreamEventsStream>, Status> {
+        let mut channels = request.into_inner().channels;
+        if channels.is_empty() {
+            channels = [
+                CHANNEL_NEW_HEADS,
+                CHANNEL_PENDING_TRANSACTIONS,
+                CHANNEL_LOGS,
+                CHANNEL_VALIDATOR_STATUS,
+                CHANNEL_AI_COMPUTATIONS,
+            ].iter().map(|c| c.to_string()).collect();
+        }
+        let kinds = channels
+            .iter()
+            .map(|channel| SubscriptionKind::from_params(&json!([channel])))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(Status::from)?;
+
+        let mut events = EVENT_BUS.subscribe();
+        let (sender, receiver) = mpsc::channel(STREAM_BUFFER);
+
+        tokio::spawn(async move {
+            loop {
+                match events.recv().await {
+                    Ok(event) => {
+                        if !kinds.iter().any(|kind| kind.render(&event).is_some()) {
+                            continue;
+                        }
+                        if sender.send(Ok(proto::Event::from(&event))).await.is_err() {
+                            return;
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        let _ = sender.send(Err(lagged(missed))).await;
+                        return;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return,
+                }
+            }
+        });
+
+        Ok(Response::new(ReceiverStream::new(receiver)))
+    }
+}
+
+
+fn caller_context<T>(request: &Request<T>) -> CallerContext {
+    CallerContext {
+        ip: request.remote_addr().map(|address| address.ip()),
+        authorization: request
+            .metadata()
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_string()),
+    }
+}
+
+fn found<T>(value: Option<T>, message: &str) -> Result<Response<T>, Status> {
+    value.map(Response::new).ok_or_else(|| Status::not_found(message))
+}
+
+fn lagged(missed: u64) -> Status {
+    Status::data_loss(format!("Stream fell behind and missed {} events", missed))
+}
+
+impl From<RpcError> for Status {
+    fn from(error: RpcError) -> Self {
+        let message = error.message.clone();
+        match error.code {
+            rpc_error::INVALID_PARAMS | rpc_error::INVALID_REQUEST | rpc_error::PARSE_ERROR => Status::invalid_argument(message),
+            rpc_error::METHOD_NOT_FOUND => Status::unimplemented(message),
+            rpc_error::RESOURCE_NOT_FOUND => Status::not_found(message),
+            rpc_error::UNAUTHORIZED => Status::unauthenticated(message),
+            rpc_error::LIMIT_EXCEEDED => Status::resource_exhausted(message),
+            rpc_error::TIMEOUT => Status::deadline_exceeded(message),
+            rpc_error::NETWORK_UNAVAILABLE => Status::unavailable(message),
+            rpc_error::INTERNAL_ERROR => Status::internal(message),
+            _ => Status::failed_precondition(message),
+        }
+    }
+}
+
+impl From<&Transaction> for proto::Transaction {
+    fn from(tx: &Transaction) -> Self {
+        proto::Transaction {
+            hash: tx.hash(),
+            sender: tx.sender.clone(),
+            receiver: tx.receiver.clone(),
+            amount: tx.amount,
+            nonce: tx.nonce,
+            signature: tx.signature.clone(),
+            timestamp: tx.timestamp,
+            gas_price: tx.gas_price,
+            gas_limit: tx.gas_limit,
+            data: tx.data.clone(),
+            public_key: tx.public_key.clone(),
+            multisig: tx.multisig.as_ref().map(|auth| proto::MultisigAuth {
+                policy: Some(proto::MultisigPolicy {
+                    threshold: auth.policy.threshold,
+                    public_keys: auth.policy.public_keys.clone(),
+                }),
+                signatures: auth.signatures.clone(),
+            }),
+            hybrid: tx.hybrid.as_ref().map(|hybrid| proto::HybridSignature {
+                public_key: hybrid.public_key.clone(),
+                signature: hybrid.signature.clone(),
+            }),
+        }
+    }
+}
+
+impl From<proto::Transaction> for Transaction {
+    fn from(tx: proto::Transaction) -> Self {
+        // The hash is derived from the other fields, never trusted from the client
+        Transaction {
+            sender: tx.sender,
+            receiver: tx.receiver,
+            amount: tx.amount,
+            nonce: tx.nonce,
+            signature: tx.signature,
+            public_key: tx.public_key,
+            // A policy that fails `MultisigPolicy::check` is rejected by `Transaction::validate`
+            multisig: tx.multisig.map(|auth| {
+                let policy = auth.policy.unwrap_or_default();
+                MultisigAuth {
+                    policy: MultisigPolicy { threshold: policy.threshold, public_keys: policy.public_keys },
+                    signatures: auth.signatures,
+                }
+            }),
+            hybrid: tx.hybrid.map(|hybrid| HybridSignature { public_key: hybrid.public_key, signature: hybrid.signature }),
+            timestamp: tx.timestamp,
+            gas_price: tx.gas_price,
+            gas_limit: tx.gas_limit,
+            data: tx.data,
+        }
+    }
+}
+
+impl From<&Block> for proto::Block {
+    fn from(block: &Block) -> Self {
+        proto::Block {
+            number: block.block_index,
+            hash: block.hash.clone(),
+            previous_hash: block.previous_hash.clone(),
+            validator: block.validator_id.clone(),
+            nonce: block.nonce,
+            timestamp: block.timestamp,
+            transactions: block.transactions.iter().map(proto::Transaction::from).collect(),
+        }
+    }
+}
+
+fn validator_status(status: &ValidatorStatus) -> i32 {
+    let status = match status {
+        ValidatorStatus::Active => proto::ValidatorStatus::Active,
+        ValidatorStatus::Inactive => proto::ValidatorStatus::Inactive,
+        ValidatorStatus::Jailed => proto::ValidatorStatus::Jailed,
+        ValidatorStatus::Slashed => proto::ValidatorStatus::Slashed,
+        ValidatorStatus::Pending => proto::ValidatorStatus::Pending,
+    };
+    status as i32
+}
+
+impl From<&Validator> for proto::Validator {
+    fn from(validator: &Validator) -> Self {
+        proto::Validator {
+            address: validator.address.clone(),
+            public_key: validator.public_key.clone(),
+            name: validator.name.clone(),
+            status: validator_status(&validator.status),
+            stake_amount: validator.stake_amount,
+            synergy_score: validator.synergy_score,
+            uptime_percentage: validator.uptime_percentage,
+            task_accuracy: validator.task_accuracy,
+            reputation_score: validator.reputation_score,
+            total_blocks_produced: validator.total_blocks_produced,
+            missed_blocks: validator.missed_blocks,
+            cluster_id: validator.cluster_id,
+            registered_at: validator.registered_at,
+            last_active: validator.last_active,
+        }
+    }
+}
+
+impl From<&Token> for proto::Token {
+    fn from(token: &Token) -> Self {
+        proto::Token {
+            symbol: token.symbol.clone(),
+            name: token.name.clone(),
+            decimals: token.decimals as u32,
+            total_supply: token.total_supply,
+            max_supply: token.max_supply,
+            mintable: token.mintable,
+            burnable: token.burnable,
+            creator: token.creator.clone(),
+            created_at: token.created_at,
+        }
+    }
+}
+
+impl From<AIVMExecutionResult> for proto::ExecutionResult {
+    fn from(result: AIVMExecutionResult) -> Self {
+        proto::ExecutionResult {
+            success: result.success,
+            output: result.output,
+            gas_used: result.gas_used,
+            logs: result.logs,
+            return_value: result.return_value,
+            error_message: result.error_message,
+        }
+    }
+}
+
+impl From<&NodeEvent> for proto::Event {
+    fn from(event: &NodeEvent) -> Self {
+        use proto::event::Event;
+
+        let event = match event {
+            NodeEvent::NewHead(block) => Event::NewHead(proto::Block::from(block)),
+            NodeEvent::PendingTransaction(tx) => Event::PendingTransaction(proto::Transaction::from(tx)),
+            NodeEvent::Log(log) => Event::Log(proto::Log {
+                address: log.address.clone(),
+                topics: log.topics.clone(),
+                data: log.data.clone(),
+                block_number: log.block_number,
+                transaction_hash: log.transaction_hash.clone(),
+            }),
+            NodeEvent::ValidatorStatus(change) => Event::ValidatorStatus(proto::ValidatorStatusChange {
+                address: change.address.clone(),
+                previous_status: change.previous_status.as_ref().map_or(0, validator_status),
+                status: validator_status(&change.status),
+                timestamp: change.timestamp,
+            }),
+            NodeEvent::ComputationStatus(change) => Event::ComputationStatus(proto::ComputationStatusChange {
+                computation_id: change.computation_id.clone(),
+                model_id: change.model_id.clone(),
+                cluster_id: change.cluster_id,
+                status: format!("{:?}", change.status),
+                timestamp: change.timestamp,
+            }),
+        };
+
+        proto::Event { event: Some(event) }
+    }
+}
+
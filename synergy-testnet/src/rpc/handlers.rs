@@ -0,0 +1,595 @@
+//! Handlers for the methods declared in `rpc::methods`. Parameters arrive
+//! already deserialized and validated; each handler returns its typed result.
+
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde_json::Value;
+
+use crate::aivm::distributed_ai::AIComputationTask;
+use crate::aivm::runtime::{AIVMContract, AIVMExecutionContext};
+use crate::block::Block;
+use crate::key_rotation::KeyRotation;
+use crate::logging::{self, LevelFilter, LogEntry, LogLevel, LogQuery};
+use crate::name_service::NameRecord;
+use crate::p2p::networking::peer_key;
+use crate::p2p::P2P_NETWORK;
+use crate::token::{StakingInfo, Token, TokenTransfer, TOKEN_MANAGER};
+use crate::transaction::Transaction;
+use crate::validator::{Validator, ValidatorRegistration};
+use crate::wallet::{Wallet, WALLET_MANAGER};
+use super::api;
+use super::error::RpcError;
+use super::eth;
+use super::methods::*;
+use super::openrpc;
+use super::rpc_server::{AIVM_RUNTIME, CHAIN, TX_POOL, VALIDATOR_MANAGER};
+
+type RpcResult<T> = Result<T, RpcError>;
+
+pub fn discover(_: DiscoverParams) -> RpcResult<Value> {
+    Ok(openrpc::document().clone())
+}
+
+// Blockchain queries
+
+pub fn block_number(_: BlockNumberParams) -> RpcResult<u64> {
+    Ok(CHAIN.lock().unwrap().last().map_or(0, |b| b.block_index))
+}
+
+pub fn get_block_by_number(params: GetBlockByNumberParams) -> RpcResult<Option<Block>> {
+    Ok(api::block_by_number(&CHAIN, params.block_number))
+}
+
+pub fn get_block_by_hash(params: GetBlockByHashParams) -> RpcResult<Option<Block>> {
+    Ok(api::block_by_hash(&CHAIN, &params.hash))
+}
+
+pub fn get_latest_block(_: GetLatestBlockParams) -> RpcResult<Option<Block>> {
+    Ok(api::latest_block(&CHAIN))
+}
+
+pub fn get_block_range(params: GetBlockRangeParams) -> RpcResult<Vec<Block>> {
+    Ok(api::block_range(&CHAIN, params.start, params.end))
+}
+
+// Transactions
+
+pub fn send_transaction(params: SendTransactionParams) -> RpcResult<String> {
+    api::submit_transaction(&TX_POOL, params.transaction)?;
+    Ok("Transaction submitted successfully".to_string())
+}
+
+pub fn get_transaction_pool(_: GetTransactionPoolParams) -> RpcResult<Vec<Transaction>> {
+    Ok(api::pending_transactions(&TX_POOL))
+}
+
+pub fn get_nonce(params: GetNonceParams) -> RpcResult<u64> {
+    Ok(api::next_nonce(&TX_POOL, &params.address))
+}
+
+pub fn get_transaction_by_hash(params: GetTransactionByHashParams) -> RpcResult<Option<Transaction>> {
+    Ok(api::transaction_by_hash(&CHAIN, &params.transaction_hash))
+}
+
+pub fn get_transactions_in_block(params: GetTransactionsInBlockParams) -> RpcResult<Vec<Transaction>> {
+    Ok(api::block_by_number(&CHAIN, params.block_number)
+        .map(|block| block.transactions)
+        .unwrap_or_default())
+}
+
+// Network information
+
+pub fn node_info(_: NodeInfoParams) -> RpcResult<NodeInfo> {
+    let chain = CHAIN.lock().unwrap();
+    Ok(NodeInfo {
+        name: "Synergy Testnet Node".to_string(),
+        version: "1.0.0".to_string(),
+        protocol_version: 1,
+        network_id: eth::chain_id(),
+        chain_id: eth::chain_id(),
+        genesis_hash: chain.genesis_hash().map(str::to_string),
+        consensus: "Proof of Synergy".to_string(),
+        syncing: false,
+        current_block: chain.last().map_or(0, |b| b.block_index),
+        timestamp: current_timestamp(),
+    })
+}
+
+pub fn get_network_stats(_: GetNetworkStatsParams) -> RpcResult<NetworkStats> {
+    let chain = CHAIN.lock().unwrap();
+    let tokens = TOKEN_MANAGER.get_all_tokens();
+
+    Ok(NetworkStats {
+        block_height: chain.last().map_or(0, |b| b.block_index),
+        total_transactions: chain.chain.iter().map(|b| b.transactions.len()).sum(),
+        active_validators: VALIDATOR_MANAGER.get_active_validators().len(),
+        total_supply: tokens.iter().map(|token| token.total_supply).sum(),
+        tokens: tokens.len(),
+        network_uptime: "99.9%".to_string(),
+        current_epoch: VALIDATOR_MANAGER.calculate_epoch_rewards(0).len(),
+        total_staked: tokens
+            .iter()
+            .map(|token| TOKEN_MANAGER.get_staked_balance("*", &token.symbol))
+            .sum(),
+    })
+}
+
+pub fn status(_: StatusParams) -> RpcResult<String> {
+    Ok("ok".to_string())
+}
+
+// Validators
+
+pub fn get_validators(_: GetValidatorsParams) -> RpcResult<Vec<Validator>> {
+    Ok(api::active_validators(&VALIDATOR_MANAGER))
+}
+
+pub fn get_validator(params: GetValidatorParams) -> RpcResult<Option<Validator>> {
+    Ok(api::validator(&VALIDATOR_MANAGER, &params.address))
+}
+
+pub fn get_top_validators(params: GetTopValidatorsParams) -> RpcResult<Vec<Validator>> {
+    Ok(VALIDATOR_MANAGER.get_top_validators(params.count.unwrap_or(10) as usize))
+}
+
+pub fn register_validator(params: RegisterValidatorParams) -> RpcResult<ActionResult> {
+    let registration = ValidatorRegistration {
+        address: params.address,
+        public_key: params.public_key,
+        name: params.name,
+        stake_amount: params.stake_amount,
+        submitted_at: current_timestamp(),
+        registration_tx_hash: format!("reg_{}", current_timestamp()),
+    };
+
+    VALIDATOR_MANAGER
+        .register_validator(registration)
+        .map(ActionResult::ok)
+        .map_err(RpcError::validator)
+}
+
+pub fn get_validator_stats(_: GetValidatorStatsParams) -> RpcResult<ValidatorStats> {
+    let active_validators = VALIDATOR_MANAGER.get_active_validators();
+
+    Ok(ValidatorStats {
+        total_validators: active_validators.len(),
+        active_validators,
+        top_validators: VALIDATOR_MANAGER.get_top_validators(20),
+        epoch_rewards: VALIDATOR_MANAGER.calculate_epoch_rewards(0),
+    })
+}
+
+pub fn get_key_history(params: GetKeyHistoryParams) -> RpcResult<Vec<KeyRotation>> {
+    Ok(api::key_history(&params.address))
+}
+
+// Name service
+
+pub fn resolve_name(params: ResolveNameParams) -> RpcResult<Option<NameRecord>> {
+    Ok(api::resolve_name(&params.name))
+}
+
+pub fn lookup_address(params: LookupAddressParams) -> RpcResult<Option<NameRecord>> {
+    Ok(api::lookup_address(&params.address))
+}
+
+// Tokens and staking
+
+pub fn get_token_balance(params: GetTokenBalanceParams) -> RpcResult<u64> {
+    Ok(api::token_balance(&params.address, &params.token_symbol))
+}
+
+pub fn get_tokens(_: GetTokensParams) -> RpcResult<Vec<Token>> {
+    Ok(api::tokens())
+}
+
+pub fn get_token_stats(_: GetTokenStatsParams) -> RpcResult<Vec<TokenStats>> {
+    Ok(TOKEN_MANAGER
+        .get_all_tokens()
+        .into_iter()
+        .map(|token| TokenStats {
+            total_staked: TOKEN_MANAGER.get_staked_balance("*", &token.symbol),
+            holders: TOKEN_MANAGER.holder_count(&token.symbol),
+            symbol: token.symbol,
+            name: token.name,
+            total_supply: token.total_supply,
+        })
+        .collect())
+}
+
+pub fn get_staked_balance(params: GetStakedBalanceParams) -> RpcResult<StakedBalance> {
+    Ok(StakedBalance {
+        balance: TOKEN_MANAGER.get_staked_balance(&params.address, &params.token_symbol),
+    })
+}
+
+pub fn get_staking_info(params: GetStakingInfoParams) -> RpcResult<Vec<StakingInfo>> {
+    Ok(TOKEN_MANAGER.get_staking_info(&params.address))
+}
+
+pub fn get_transfer_history(params: GetTransferHistoryParams) -> RpcResult<Vec<TokenTransfer>> {
+    Ok(TOKEN_MANAGER.get_transfer_history(&params.address, params.limit.unwrap_or(50) as usize))
+}
+
+// AIVM
+
+pub fn execute_aivm_contract(params: ExecuteAIVMContractParams) -> RpcResult<ContractExecuted> {
+    let input = decode_hex("input_data", &params.input_data)?;
+    let result = api::execute_contract(&AIVM_RUNTIME, &params.contract_address, input)?;
+    Ok(ContractExecuted {
+        success: true,
+        result,
+        message: "AIVM contract executed successfully".to_string(),
+    })
+}
+
+pub fn get_aivm_contracts(_: GetAIVMContractsParams) -> RpcResult<Vec<AIVMContract>> {
+    Ok(AIVM_RUNTIME.get_all_contracts())
+}
+
+pub fn get_aivm_contract(params: GetAIVMContractParams) -> RpcResult<Option<AIVMContract>> {
+    Ok(AIVM_RUNTIME.get_contract(&params.address))
+}
+
+pub fn get_aivm_stats(_: GetAIVMStatsParams) -> RpcResult<AIVMStats> {
+    let distributed_stats = AIVM_RUNTIME.distributed_ai().get_ai_network_stats();
+    let counter = |key: &str| {
+        distributed_stats.get(key).and_then(|v| v.parse::<u64>().ok()).unwrap_or(0)
+    };
+
+    Ok(AIVMStats {
+        total_contracts: AIVM_RUNTIME.get_all_contracts().len(),
+        supported_features: ["ai_enhanced", "cross_chain", "oracle", "standard", "distributed_ai"]
+            .iter().map(|s| s.to_string()).collect(),
+        ai_models: vec!["distributed_ai_model".to_string()],
+        supported_chains: ["ethereum", "polygon", "solana"].iter().map(|s| s.to_string()).collect(),
+        distributed_computations: counter("total_computations"),
+        completed_computations: counter("completed_computations"),
+        active_validators: counter("active_validators"),
+        total_ai_rewards_distributed: counter("total_ai_rewards_distributed"),
+    })
+}
+
+pub fn chat_with_aivm(params: ChatWithAIVMParams) -> RpcResult<ChatResponse> {
+    let context = AIVMExecutionContext {
+        transaction_hash: "chat_interaction".to_string(),
+        block_height: 0,
+        timestamp: current_timestamp(),
+        sender: "user".to_string(),
+        contract_address: None,
+        input_data: params.message.into_bytes(),
+        gas_limit: 10000,
+        gas_price: 100,
+    };
+
+    // This would need async support in the RPC handler
+    Ok(ChatResponse {
+        success: true,
+        message: "Chat functionality requires async support - use direct AIVM runtime calls".to_string(),
+        context,
+    })
+}
+
+pub fn initiate_distributed_ai(params: InitiateDistributedAIParams) -> RpcResult<ComputationStarted> {
+    let input = decode_hex("input_data", &params.input_data)?;
+    let computation_id = AIVM_RUNTIME
+        .distributed_ai()
+        .initiate_distributed_computation(params.model_id, input, params.cluster_id)
+        .map_err(RpcError::aivm)?;
+
+    Ok(ComputationStarted {
+        success: true,
+        computation_id,
+        message: "Distributed AI computation initiated".to_string(),
+    })
+}
+
+pub fn get_distributed_ai_status(params: GetDistributedAIStatusParams) -> RpcResult<ComputationStatusInfo> {
+    let status = api::computation_status(&AIVM_RUNTIME, &params.computation_id)?;
+    Ok(ComputationStatusInfo {
+        status: format!("{:?}", status),
+        computation_id: params.computation_id,
+    })
+}
+
+pub fn get_distributed_ai_result(params: GetDistributedAIResultParams) -> RpcResult<ComputationResult> {
+    match AIVM_RUNTIME.distributed_ai().get_computation_result(&params.computation_id) {
+        Some(result) => Ok(ComputationResult {
+            success: true,
+            result: hex::encode(result),
+            computation_id: params.computation_id,
+        }),
+        None => Err(RpcError::not_found("Result not available or computation not completed")),
+    }
+}
+
+pub fn submit_ai_partial_result(params: SubmitAIPartialResultParams) -> RpcResult<ActionResult> {
+    let partial_result = decode_hex("partial_result", &params.partial_result)?;
+    AIVM_RUNTIME
+        .distributed_ai()
+        .submit_partial_result(&params.task_id, &params.validator_address, partial_result)
+        .map_err(RpcError::aivm)?;
+    Ok(ActionResult::ok("Partial result submitted successfully"))
+}
+
+pub fn get_validator_ai_tasks(params: GetValidatorAITasksParams) -> RpcResult<Vec<AIComputationTask>> {
+    Ok(AIVM_RUNTIME.distributed_ai().get_pending_tasks_for_validator(&params.validator_address))
+}
+
+pub fn get_validator_ai_rewards(params: GetValidatorAIRewardsParams) -> RpcResult<ValidatorAIRewards> {
+    Ok(ValidatorAIRewards {
+        total_rewards: AIVM_RUNTIME.distributed_ai().get_validator_ai_rewards(&params.validator_address),
+        validator_address: params.validator_address,
+    })
+}
+
+pub fn get_ai_distributed_stats(_: GetAIDistributedStatsParams) -> RpcResult<HashMap<String, String>> {
+    Ok(AIVM_RUNTIME.distributed_ai().get_ai_network_stats())
+}
+
+// Wallet
+
+pub fn create_wallet(params: CreateWalletParams) -> RpcResult<WalletCreated> {
+    let mut wallet_manager = WALLET_MANAGER
+        .lock()
+        .map_err(|_| RpcError::internal("Failed to create wallet"))?;
+    let address = if params.hybrid.unwrap_or(false) {
+        wallet_manager.create_hybrid_wallet(&params.password)
+    } else {
+        wallet_manager.create_wallet(&params.password)
+    };
+    Ok(WalletCreated {
+        success: true,
+        address: address.map_err(RpcError::wallet)?,
+        message: "Wallet created successfully".to_string(),
+    })
+}
+
+pub fn unlock_wallet(params: UnlockWalletParams) -> RpcResult<ActionResult> {
+    let mut wallet_manager = WALLET_MANAGER
+        .lock()
+        .map_err(|_| RpcError::internal("Failed to access wallet manager"))?;
+    wallet_manager
+        .unlock_wallet(&params.address, &params.password, params.duration_secs.map(Duration::from_secs))
+        .map_err(RpcError::wallet)?;
+    Ok(ActionResult::ok(format!("Wallet {} unlocked", params.address)))
+}
+
+pub fn lock_wallet(params: LockWalletParams) -> RpcResult<ActionResult> {
+    let mut wallet_manager = WALLET_MANAGER
+        .lock()
+        .map_err(|_| RpcError::internal("Failed to access wallet manager"))?;
+    if !wallet_manager.lock_wallet(&params.address) {
+        return Err(RpcError::wallet(format!("Wallet {} is not unlocked", params.address)));
+    }
+    Ok(ActionResult::ok(format!("Wallet {} locked", params.address)))
+}
+
+pub fn get_wallet(params: GetWalletParams) -> RpcResult<Option<Wallet>> {
+    let wallet_manager = WALLET_MANAGER
+        .lock()
+        .map_err(|_| RpcError::internal("Failed to access wallet"))?;
+    Ok(wallet_manager.get_wallet(&params.address).cloned())
+}
+
+pub fn sign_transaction(params: SignTransactionParams) -> RpcResult<SignedTransaction> {
+    let mut transaction = params.transaction;
+    let wallet_manager = WALLET_MANAGER
+        .lock()
+        .map_err(|_| RpcError::internal("Failed to access wallet manager"))?;
+    let message = wallet_manager
+        .sign_transaction(&params.address, &mut transaction)
+        .map_err(RpcError::wallet)?;
+    Ok(SignedTransaction { success: true, message, transaction })
+}
+
+pub fn send_tokens(params: SendTokensParams) -> RpcResult<TransactionCreated> {
+    let nonce = api::next_nonce(&TX_POOL, &params.from);
+    let wallet_manager = WALLET_MANAGER
+        .lock()
+        .map_err(|_| RpcError::internal("Failed to access wallet manager"))?;
+    let transaction = wallet_manager
+        .send_tokens(&params.from, &params.to, &params.token_symbol, params.amount, nonce, &TOKEN_MANAGER)
+        .map_err(RpcError::wallet)?;
+    Ok(TransactionCreated {
+        success: true,
+        transaction,
+        message: "Transaction created successfully".to_string(),
+    })
+}
+
+pub fn stake_tokens(params: StakeTokensParams) -> RpcResult<TransactionCreated> {
+    let nonce = api::next_nonce(&TX_POOL, &params.staker);
+    let wallet_manager = WALLET_MANAGER
+        .lock()
+        .map_err(|_| RpcError::internal("Failed to access wallet manager"))?;
+    let transaction = wallet_manager
+        .stake_tokens(&params.staker, &params.validator, &params.token_symbol, params.amount, nonce, &TOKEN_MANAGER)
+        .map_err(RpcError::wallet)?;
+    Ok(TransactionCreated {
+        success: true,
+        transaction,
+        message: "Staking transaction created successfully".to_string(),
+    })
+}
+
+// Admin
+
+pub fn approve_validator(params: ApproveValidatorParams) -> RpcResult<ActionResult> {
+    VALIDATOR_MANAGER
+        .approve_validator(&params.address)
+        .map_err(RpcError::validator)?;
+    Ok(ActionResult::ok("Validator approved successfully"))
+}
+
+pub fn slash_validator(params: SlashValidatorParams) -> RpcResult<ActionResult> {
+    VALIDATOR_MANAGER
+        .slash_validator(&params.address, &params.reason)
+        .map_err(RpcError::validator)?;
+    Ok(ActionResult::ok("Validator slashed successfully"))
+}
+
+pub fn create_token(params: CreateTokenParams) -> RpcResult<ActionResult> {
+    TOKEN_MANAGER
+        .create_token(
+            params.symbol,
+            params.name,
+            params.decimals,
+            params.total_supply,
+            Some(params.total_supply.saturating_mul(2)), // max_supply = 2x total_supply
+            true, // mintable
+            true, // burnable
+            params.creator,
+        )
+        .map(ActionResult::ok)
+        .map_err(RpcError::token)
+}
+
+pub fn mint_tokens(params: MintTokensParams) -> RpcResult<ActionResult> {
+    TOKEN_MANAGER
+        .mint_tokens(&params.to, &params.token_symbol, params.amount)
+        .map(ActionResult::ok)
+        .map_err(RpcError::token)
+}
+
+pub fn burn_tokens(params: BurnTokensParams) -> RpcResult<ActionResult> {
+    TOKEN_MANAGER
+        .burn_tokens(&params.from, &params.token_symbol, params.amount)
+        .map(ActionResult::ok)
+        .map_err(RpcError::token)
+}
+
+pub fn transfer_tokens(params: TransferTokensParams) -> RpcResult<ActionResult> {
+    TOKEN_MANAGER
+        .transfer_tokens(&params.from, &params.to, &params.token_symbol, params.amount, 1000)
+        .map(ActionResult::ok)
+        .map_err(RpcError::token)
+}
+
+pub fn stake_tokens_direct(params: StakeTokensDirectParams) -> RpcResult<ActionResult> {
+    TOKEN_MANAGER
+        .stake_tokens(&params.staker, &params.validator, &params.token_symbol, params.amount)
+        .map(ActionResult::ok)
+        .map_err(RpcError::token)
+}
+
+pub fn unstake_tokens(params: UnstakeTokensParams) -> RpcResult<ActionResult> {
+    TOKEN_MANAGER
+        .unstake_tokens(&params.staker, &params.validator, &params.token_symbol, params.amount)
+        .map(ActionResult::ok)
+        .map_err(RpcError::token)
+}
+
+pub fn deploy_aivm_contract(params: DeployAIVMContractParams) -> RpcResult<ContractDeployed> {
+    let bytecode = decode_hex("bytecode", &params.bytecode)?;
+    let contract_address = api::deploy_contract(
+        &AIVM_RUNTIME,
+        bytecode,
+        params.abi,
+        api::parse_contract_type(&params.contract_type),
+    )?;
+    Ok(ContractDeployed {
+        success: true,
+        contract_address,
+        message: "AIVM contract deployed successfully".to_string(),
+    })
+}
+
+pub fn peers(_: PeersParams) -> RpcResult<PeerList> {
+    match P2P_NETWORK.lock().unwrap().as_ref() {
+        Some(network) => Ok(PeerList {
+            peer_count: network.get_peer_count(),
+            peers: network.get_peer_info(),
+            banned: network.get_banned_peers(),
+        }),
+        None => Err(RpcError::network_unavailable("P2P network is not running")),
+    }
+}
+
+pub fn ban_peer(params: BanPeerParams) -> RpcResult<PeerBanned> {
+    let reason = params.reason.as_deref().unwrap_or("Banned by operator");
+    match P2P_NETWORK.lock().unwrap().as_ref() {
+        Some(network) => {
+            let banned = network.ban_peer(&params.address, params.duration_secs, reason);
+            Ok(PeerBanned {
+                success: true,
+                message: format!("Peer {} banned", banned),
+                permanent: params.duration_secs.is_none(),
+            })
+        }
+        None => Err(RpcError::network_unavailable("P2P network is not running")),
+    }
+}
+
+pub fn unban_peer(params: UnbanPeerParams) -> RpcResult<ActionResult> {
+    match P2P_NETWORK.lock().unwrap().as_ref() {
+        Some(network) => {
+            if network.unban_peer(&params.address) {
+                Ok(ActionResult::ok(format!("Peer {} unbanned", peer_key(&params.address))))
+            } else {
+                Err(RpcError::not_found("Peer is not banned"))
+            }
+        }
+        None => Err(RpcError::network_unavailable("P2P network is not running")),
+    }
+}
+
+pub fn get_logs(params: GetLogsParams) -> RpcResult<Vec<LogEntry>> {
+    let min_level = params.level.as_deref().map(parse_log_level).transpose()?;
+    Ok(logging::query(&LogQuery {
+        min_level,
+        module: params.module,
+        since: params.since,
+        until: params.until,
+        limit: Some(params.limit.unwrap_or(100) as usize),
+    }))
+}
+
+pub fn get_log_levels(_: GetLogLevelsParams) -> RpcResult<LogLevels> {
+    logging::levels()
+        .map(log_levels)
+        .ok_or_else(|| RpcError::internal("Logger is not initialized"))
+}
+
+pub fn set_log_level(params: SetLogLevelParams) -> RpcResult<LogLevels> {
+    let level = parse_log_level(&params.level)?;
+    logging::set_level(params.module.as_deref(), level)
+        .map(log_levels)
+        .map_err(RpcError::internal)
+}
+
+// Debug
+
+pub fn get_all_wallets(_: GetAllWalletsParams) -> RpcResult<Vec<Wallet>> {
+    let wallet_manager = WALLET_MANAGER
+        .lock()
+        .map_err(|_| RpcError::internal("Failed to access wallet manager"))?;
+    Ok(wallet_manager.get_all_wallets().into_iter().cloned().collect())
+}
+
+pub fn get_all_balances(params: GetAllBalancesParams) -> RpcResult<HashMap<String, u64>> {
+    Ok(TOKEN_MANAGER.get_all_balances(&params.address))
+}
+
+fn parse_log_level(level: &str) -> RpcResult<LogLevel> {
+    LogLevel::parse(level).ok_or_else(|| {
+        RpcError::invalid_params(format!("Unknown log level '{}'; use trace, debug, info, warn or error", level))
+    })
+}
+
+fn log_levels(filter: LevelFilter) -> LogLevels {
+    LogLevels { default: filter.default, modules: filter.modules }
+}
+
+fn decode_hex(name: &str, value: &str) -> RpcResult<Vec<u8>> {
+    hex::decode(value.trim_start_matches("0x"))
+        .map_err(|_| RpcError::invalid_params(format!("{} is not valid hex", name)))
+}
+
+fn current_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
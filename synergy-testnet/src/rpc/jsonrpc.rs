@@ -0,0 +1,110 @@
+//! JSON-RPC 2.0 envelope handling: request validation, batches and
+//! notifications. Method dispatch itself lives in `rpc_server`.
+
+use serde_json::{json, Map, Value};
+use super::error::RpcError;
+
+/// Parses a raw request body and runs every call through `dispatch`.
+///
+/// Returns `None` when nothing must be sent back, i.e. the body held only
+/// notifications. Batches longer than `max_batch_size` are rejected whole.
+pub fn process_body<F>(body: &[u8], max_batch_size: usize, dispatch: F) -> Option<Value>
+where
+    F: Fn(&str, Value) -> Result<Value, RpcError>,
+{
+    let parsed: Value = match serde_json::from_slice(body) {
+        Ok(value) => value,
+        Err(e) => return Some(error_response(Value::Null, RpcError::parse_error(e.to_string()))),
+    };
+
+    match parsed {
+        Value::Array(calls) => {
+            if calls.is_empty() {
+                return Some(error_response(Value::Null, RpcError::invalid_request("Empty batch")));
+            }
+            if calls.len() > max_batch_size {
+                return Some(error_response(Value::Null, RpcError::invalid_request(format!(
+                    "Batch of {} calls exceeds the limit of {}", calls.len(), max_batch_size
+                ))));
+            }
+
+            let responses: Vec<Value> = calls
+                .into_iter()
+                .filter_map(|call| process_call(call, &dispatch))
+                .collect();
+
+            if responses.is_empty() {
+                None
+            } else {
+                Some(Value::Array(responses))
+            }
+        }
+        call => process_call(call, &dispatch),
+    }
+}
+
+/// Handles a single request object. Notifications (no `id` member) are
+/// executed but produce no response.
+pub fn process_call<F>(call: Value, dispatch: &F) -> Option<Value>
+where
+    F: Fn(&str, Value) -> Result<Value, RpcError>,
+{
+    let request = match call {
+        Value::Object(request) => request,
+        _ => return Some(error_response(Value::Null, RpcError::invalid_request("Request must be an object"))),
+    };
+
+    let is_notification = !request.contains_key("id");
+    let id = request.get("id").cloned().unwrap_or(Value::Null);
+
+    if !matches!(id, Value::Null | Value::String(_) | Value::Number(_)) {
+        return Some(error_response(Value::Null, RpcError::invalid_request("id must be a string, number or null")));
+    }
+
+    let (method, params) = match validate_request(&request) {
+        Ok(parts) => parts,
+        Err(error) => return Some(error_response(id, error)),
+    };
+
+    let result = dispatch(method, params);
+
+    if is_notification {
+        return None;
+    }
+
+    Some(match result {
+        Ok(value) => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "result": value
+        }),
+        Err(error) => error_response(id, error),
+    })
+}
+
+pub fn error_response(id: Value, error: RpcError) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": error
+    })
+}
+
+fn validate_request(request: &Map<String, Value>) -> Result<(&str, Value), RpcError> {
+    if request.get("jsonrpc").and_then(|v| v.as_str()) != Some("2.0") {
+        return Err(RpcError::invalid_request("jsonrpc must be \"2.0\""));
+    }
+
+    let method = request
+        .get("method")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| RpcError::invalid_request("method must be a string"))?;
+
+    let params = match request.get("params") {
+        None => json!([]),
+        Some(params @ Value::Array(_)) | Some(params @ Value::Object(_)) => params.clone(),
+        Some(_) => return Err(RpcError::invalid_request("params must be an array or object")),
+    };
+
+    Ok((method, params))
+}
@@ -0,0 +1,126 @@
+//! Per-client limits shared by the HTTP, WebSocket and gRPC endpoints.
+//!
+//! Every call is charged to a token bucket for the caller's IP, and to a
+//! second bucket when its method has its own rate. Expensive methods also
+//! share a node-wide cap on how many may run at once, and every call
+//! executed off the async workers needs one of `worker_threads` slots.
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::config::RpcLimitsConfig;
+use crate::rate_limit::RateLimiter;
+use super::error::RpcError;
+
+pub struct RpcLimiter {
+    config: RpcLimitsConfig,
+    per_ip: Mutex<RateLimiter<IpAddr>>,
+    per_method: Mutex<HashMap<String, RateLimiter<IpAddr>>>,
+    expensive_in_flight: Arc<AtomicUsize>,
+    workers: Arc<Semaphore>,
+}
+
+/// Held while an admitted call runs. Releases the expensive-call slot, if
+/// the call took one, when dropped.
+pub struct CallPermit {
+    slot: Option<Arc<AtomicUsize>>,
+}
+
+impl Drop for CallPermit {
+    fn drop(&mut self) {
+        if let Some(slot) = &self.slot {
+            slot.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+}
+
+impl RpcLimiter {
+    pub fn new(config: RpcLimitsConfig) -> Self {
+        let per_ip = RateLimiter::new(config.per_ip.capacity, config.per_ip.refill_per_sec);
+        let per_method = config
+            .per_method
+            .iter()
+            .map(|(method, rate)| (method.clone(), RateLimiter::new(rate.capacity, rate.refill_per_sec)))
+            .collect();
+        let workers = Arc::new(Semaphore::new(config.worker_threads.max(1)));
+
+        RpcLimiter {
+            config,
+            per_ip: Mutex::new(per_ip),
+            per_method: Mutex::new(per_method),
+            expensive_in_flight: Arc::new(AtomicUsize::new(0)),
+            workers,
+        }
+    }
+
+    pub fn config(&self) -> &RpcLimitsConfig {
+        &self.config
+    }
+
+    /// Charges one call to `method` by `ip` and takes an expensive-call slot
+    /// if the method needs one. Callers without a known address (in-process
+    /// use) skip the rate limits but not the concurrency cap.
+    pub fn admit(&self, ip: Option<IpAddr>, method: &str) -> Result<CallPermit, RpcError> {
+        if let Some(ip) = ip {
+            self.check_ip(ip)?;
+            self.check_method(ip, method)?;
+        }
+        self.acquire(method)
+    }
+
+    /// Charges one call to the caller's per-IP bucket.
+    pub fn check_ip(&self, ip: IpAddr) -> Result<(), RpcError> {
+        if self.per_ip.lock().unwrap().check(&ip) {
+            Ok(())
+        } else {
+            Err(RpcError::limit_exceeded(format!("Rate limit exceeded for {}", ip)))
+        }
+    }
+
+    /// Charges one call to the method's own bucket, if it has one.
+    pub fn check_method(&self, ip: IpAddr, method: &str) -> Result<(), RpcError> {
+        if let Some(limiter) = self.per_method.lock().unwrap().get_mut(method) {
+            if !limiter.check(&ip) {
+                return Err(RpcError::limit_exceeded(format!("Rate limit exceeded for {}", method)));
+            }
+        }
+        Ok(())
+    }
+
+    /// Takes an expensive-call slot for `method`; other methods get an
+    /// empty permit.
+    pub fn acquire(&self, method: &str) -> Result<CallPermit, RpcError> {
+        if !self.config.expensive_methods.iter().any(|expensive| expensive == method) {
+            return Ok(CallPermit { slot: None });
+        }
+
+        if self.expensive_in_flight.fetch_add(1, Ordering::SeqCst) >= self.config.max_concurrent_expensive {
+            self.expensive_in_flight.fetch_sub(1, Ordering::SeqCst);
+            return Err(RpcError::limit_exceeded(format!(
+                "Too many concurrent {} calls, try again later", method
+            )));
+        }
+        Ok(CallPermit { slot: Some(Arc::clone(&self.expensive_in_flight)) })
+    }
+
+    /// Reserves a worker thread for one request until the permit is
+    /// dropped. Fails at once when all `worker_threads` are busy, so
+    /// requests cannot pile up behind slow calls.
+    pub fn reserve_worker(&self) -> Result<OwnedSemaphorePermit, RpcError> {
+        Arc::clone(&self.workers)
+            .try_acquire_owned()
+            .map_err(|_| RpcError::limit_exceeded("All RPC workers are busy, try again later"))
+    }
+
+    /// Drops the buckets of clients that have been idle long enough to refill.
+    pub fn prune_idle(&self) {
+        self.per_ip.lock().unwrap().prune_idle();
+        for limiter in self.per_method.lock().unwrap().values_mut() {
+            limiter.prune_idle();
+        }
+    }
+}
@@ -0,0 +1,438 @@
+//! The JSON-RPC method registry.
+//!
+//! Every `synergy_*` method is declared exactly once below with its
+//! namespace, typed parameters, result type and handler. Dispatch, access
+//! control and the OpenRPC document served at `rpc.discover` are all driven
+//! from this table.
+//!
+//! Parameters are accepted either by position (`[a, b]`) or by name
+//! (`{"a": .., "b": ..}`); trailing optional parameters may be omitted.
+
+use std::collections::HashMap;
+
+use schemars::gen::SchemaGenerator;
+use schemars::schema::Schema;
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::aivm::distributed_ai::AIComputationTask;
+use crate::aivm::runtime::{AIVMContract, AIVMExecutionContext, AIVMExecutionResult};
+use crate::block::Block;
+use crate::key_rotation::KeyRotation;
+use crate::logging::{LogEntry, LogLevel};
+use crate::name_service::NameRecord;
+use crate::p2p::peer_scoring::BanEntry;
+use crate::token::{StakingInfo, Token, TokenTransfer};
+use crate::transaction::Transaction;
+use crate::validator::Validator;
+use crate::wallet::Wallet;
+use super::auth::Namespace;
+use super::error::RpcError;
+use super::handlers;
+
+/// A registered method.
+pub struct MethodInfo {
+    pub name: &'static str,
+    pub namespace: Namespace,
+    pub summary: &'static str,
+    /// Parameter names in positional order.
+    pub params: &'static [&'static str],
+    pub invoke: fn(Value) -> Result<Value, RpcError>,
+    /// Object schema of the parameters, one property per parameter.
+    pub params_schema: fn(&mut SchemaGenerator) -> Schema,
+    pub result_schema: fn(&mut SchemaGenerator) -> Schema,
+}
+
+macro_rules! rpc_methods {
+    ($(
+        $(#[doc = $doc:literal])*
+        $namespace:ident $name:literal $params:ident { $($field:ident: $ty:ty),* $(,)? } -> $result:ty = $handler:path;
+    )*) => {
+        $(
+            #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+            #[serde(deny_unknown_fields)]
+            pub struct $params {
+                $(pub $field: $ty,)*
+            }
+        )*
+
+        pub static METHODS: &[MethodInfo] = &[
+            $(
+                MethodInfo {
+                    name: $name,
+                    namespace: Namespace::$namespace,
+                    summary: concat!($($doc),*),
+                    params: &[$(stringify!($field)),*],
+                    invoke: |params| invoke::<$params, $result>(params, &[$(stringify!($field)),*], $handler),
+                    params_schema: |generator| <$params as JsonSchema>::json_schema(generator),
+                    result_schema: |generator| generator.subschema_for::<$result>(),
+                },
+            )*
+        ];
+    };
+}
+
+pub fn lookup(method: &str) -> Option<&'static MethodInfo> {
+    METHODS.iter().find(|info| info.name == method)
+}
+
+fn invoke<P, R>(params: Value, names: &[&str], handler: fn(P) -> Result<R, RpcError>) -> Result<Value, RpcError>
+where
+    P: DeserializeOwned,
+    R: Serialize,
+{
+    let params: P = serde_json::from_value(named_params(params, names)?)
+        .map_err(|e| RpcError::invalid_params(format!("Invalid params: {}", e)))?;
+    let result = handler(params)?;
+    serde_json::to_value(result).map_err(|e| RpcError::internal(e.to_string()))
+}
+
+/// Turns positional params into an object keyed by parameter name.
+fn named_params(params: Value, names: &[&str]) -> Result<Value, RpcError> {
+    match params {
+        Value::Null => Ok(Value::Object(Map::new())),
+        Value::Object(_) => Ok(params),
+        Value::Array(values) => {
+            if values.len() > names.len() {
+                return Err(RpcError::invalid_params(format!(
+                    "Expected at most {} params, got {}", names.len(), values.len()
+                )));
+            }
+            Ok(Value::Object(
+                names.iter().map(|name| name.to_string()).zip(values).collect(),
+            ))
+        }
+        _ => Err(RpcError::invalid_params("Params must be an array or an object")),
+    }
+}
+
+/// Outcome of a state-changing call.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ActionResult {
+    pub success: bool,
+    pub message: String,
+}
+
+impl ActionResult {
+    pub fn ok(message: impl Into<String>) -> Self {
+        ActionResult { success: true, message: message.into() }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeInfo {
+    pub name: String,
+    pub version: String,
+    pub protocol_version: u32,
+    pub network_id: u64,
+    pub chain_id: u64,
+    /// Hash of the genesis the node's chain was started from.
+    pub genesis_hash: Option<String>,
+    pub consensus: String,
+    pub syncing: bool,
+    pub current_block: u64,
+    pub timestamp: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct NetworkStats {
+    pub block_height: u64,
+    pub total_transactions: usize,
+    pub active_validators: usize,
+    pub total_supply: u64,
+    pub tokens: usize,
+    pub network_uptime: String,
+    pub current_epoch: usize,
+    pub total_staked: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PeerList {
+    pub peer_count: usize,
+    pub peers: Vec<Value>,
+    pub banned: Vec<BanEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PeerBanned {
+    pub success: bool,
+    pub message: String,
+    pub permanent: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ValidatorStats {
+    pub total_validators: usize,
+    pub active_validators: Vec<Validator>,
+    pub top_validators: Vec<Validator>,
+    pub epoch_rewards: HashMap<String, u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TokenStats {
+    pub symbol: String,
+    pub name: String,
+    pub total_supply: u64,
+    pub total_staked: u64,
+    pub holders: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct StakedBalance {
+    pub balance: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct WalletCreated {
+    pub success: bool,
+    pub address: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SignedTransaction {
+    pub success: bool,
+    pub message: String,
+    pub transaction: Transaction,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TransactionCreated {
+    pub success: bool,
+    pub transaction: Transaction,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ContractDeployed {
+    pub success: bool,
+    pub contract_address: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ContractExecuted {
+    pub success: bool,
+    pub result: AIVMExecutionResult,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AIVMStats {
+    pub total_contracts: usize,
+    pub supported_features: Vec<String>,
+    pub ai_models: Vec<String>,
+    pub supported_chains: Vec<String>,
+    pub distributed_computations: u64,
+    pub completed_computations: u64,
+    pub active_validators: u64,
+    pub total_ai_rewards_distributed: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ChatResponse {
+    pub success: bool,
+    pub message: String,
+    pub context: AIVMExecutionContext,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ComputationStarted {
+    pub success: bool,
+    pub computation_id: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ComputationStatusInfo {
+    /// Pending, InProgress, Aggregating, Completed, Failed or Timeout.
+    pub status: String,
+    pub computation_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ComputationResult {
+    pub success: bool,
+    /// Hex-encoded aggregated output.
+    pub result: String,
+    pub computation_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ValidatorAIRewards {
+    pub validator_address: String,
+    pub total_rewards: u64,
+}
+
+/// Log levels in effect, per module and otherwise.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct LogLevels {
+    pub default: LogLevel,
+    pub modules: HashMap<String, LogLevel>,
+}
+
+rpc_methods! {
+    // Discovery
+
+    /// Returns the OpenRPC document describing this API.
+    Public "rpc.discover" DiscoverParams {} -> Value = handlers::discover;
+
+    // Blockchain queries
+
+    /// Height of the latest block.
+    Public "synergy_blockNumber" BlockNumberParams {} -> u64 = handlers::block_number;
+    /// Block at the given height, or null.
+    Public "synergy_getBlockByNumber" GetBlockByNumberParams { block_number: u64 } -> Option<Block> = handlers::get_block_by_number;
+    /// Block with the given hash, or null.
+    Public "synergy_getBlockByHash" GetBlockByHashParams { hash: String } -> Option<Block> = handlers::get_block_by_hash;
+    /// Most recent block, or null before genesis.
+    Public "synergy_getLatestBlock" GetLatestBlockParams {} -> Option<Block> = handlers::get_latest_block;
+    /// Blocks with a height between `start` and `end`, inclusive.
+    Public "synergy_getBlockRange" GetBlockRangeParams { start: u64, end: u64 } -> Vec<Block> = handlers::get_block_range;
+
+    // Transactions
+
+    /// Validates a signed transaction and adds it to the pool.
+    Public "synergy_sendTransaction" SendTransactionParams { transaction: Transaction } -> String = handlers::send_transaction;
+    /// Transactions waiting to be included in a block.
+    Public "synergy_getTransactionPool" GetTransactionPoolParams {} -> Vec<Transaction> = handlers::get_transaction_pool;
+    /// Nonce the next transaction from an address should carry, counting its pending transactions.
+    Public "synergy_getNonce" GetNonceParams { address: String } -> u64 = handlers::get_nonce;
+    /// Included transaction with the given hash, or null.
+    Public "synergy_getTransactionByHash" GetTransactionByHashParams { transaction_hash: String } -> Option<Transaction> = handlers::get_transaction_by_hash;
+    /// Transactions in the block at the given height.
+    Public "synergy_getTransactionsInBlock" GetTransactionsInBlockParams { block_number: u64 } -> Vec<Transaction> = handlers::get_transactions_in_block;
+
+    // Network information
+
+    /// Node name, version and chain identifiers.
+    Public "synergy_nodeInfo" NodeInfoParams {} -> NodeInfo = handlers::node_info;
+    /// Chain-wide totals.
+    Public "synergy_getNetworkStats" GetNetworkStatsParams {} -> NetworkStats = handlers::get_network_stats;
+    /// Liveness check; always "ok".
+    Public "synergy_status" StatusParams {} -> String = handlers::status;
+
+    // Validators
+
+    /// All active validators.
+    Public "synergy_getValidators" GetValidatorsParams {} -> Vec<Validator> = handlers::get_validators;
+    /// Validator with the given address, or null.
+    Public "synergy_getValidator" GetValidatorParams { address: String } -> Option<Validator> = handlers::get_validator;
+    /// Highest-scoring validators; `count` defaults to 10.
+    Public "synergy_getTopValidators" GetTopValidatorsParams { count: Option<u64> } -> Vec<Validator> = handlers::get_top_validators;
+    /// Submits a validator registration for approval.
+    Public "synergy_registerValidator" RegisterValidatorParams { address: String, public_key: String, name: String, stake_amount: u64 } -> ActionResult = handlers::register_validator;
+    /// Validator counts, rankings and epoch rewards.
+    Public "synergy_getValidatorStats" GetValidatorStatsParams {} -> ValidatorStats = handlers::get_validator_stats;
+    /// Account and consensus key rotations of an address, oldest first, including pending and superseded ones.
+    Public "synergy_getKeyHistory" GetKeyHistoryParams { address: String } -> Vec<KeyRotation> = handlers::get_key_history;
+
+    // Name service
+
+    /// Record of a `.syn` name, or null if it is not registered or has expired.
+    Public "synergy_resolveName" ResolveNameParams { name: String } -> Option<NameRecord> = handlers::resolve_name;
+    /// Record of the primary name of an address, or null if it has none that resolves to it.
+    Public "synergy_lookupAddress" LookupAddressParams { address: String } -> Option<NameRecord> = handlers::lookup_address;
+
+    // Tokens and staking
+
+    /// Balance of one token held by an address.
+    Public "synergy_getTokenBalance" GetTokenBalanceParams { address: String, token_symbol: String } -> u64 = handlers::get_token_balance;
+    /// All registered tokens.
+    Public "synergy_getTokens" GetTokensParams {} -> Vec<Token> = handlers::get_tokens;
+    /// Supply, staking and holder counts per token.
+    Public "synergy_getTokenStats" GetTokenStatsParams {} -> Vec<TokenStats> = handlers::get_token_stats;
+    /// Amount of a token an address has staked.
+    Public "synergy_getStakedBalance" GetStakedBalanceParams { address: String, token_symbol: String } -> StakedBalance = handlers::get_staked_balance;
+    /// Stakes held by an address.
+    Public "synergy_getStakingInfo" GetStakingInfoParams { address: String } -> Vec<StakingInfo> = handlers::get_staking_info;
+    /// Recent transfers to or from an address; `limit` defaults to 50.
+    Public "synergy_getTransferHistory" GetTransferHistoryParams { address: String, limit: Option<u64> } -> Vec<TokenTransfer> = handlers::get_transfer_history;
+
+    // AIVM
+
+    /// Runs a deployed contract with hex-encoded input, outside of a block.
+    Public "synergy_executeAIVMContract" ExecuteAIVMContractParams { contract_address: String, input_data: String } -> ContractExecuted = handlers::execute_aivm_contract;
+    /// All deployed contracts.
+    Public "synergy_getAIVMContracts" GetAIVMContractsParams {} -> Vec<AIVMContract> = handlers::get_aivm_contracts;
+    /// Contract at the given address, or null.
+    Public "synergy_getAIVMContract" GetAIVMContractParams { address: String } -> Option<AIVMContract> = handlers::get_aivm_contract;
+    /// Contract and distributed AI totals.
+    Public "synergy_getAIVMStats" GetAIVMStatsParams {} -> AIVMStats = handlers::get_aivm_stats;
+    /// Builds the execution context for a chat message.
+    Public "synergy_chatWithAIVM" ChatWithAIVMParams { message: String } -> ChatResponse = handlers::chat_with_aivm;
+    /// Starts a distributed AI computation on hex-encoded input.
+    Public "synergy_initiateDistributedAI" InitiateDistributedAIParams { model_id: String, input_data: String, cluster_id: Option<u64> } -> ComputationStarted = handlers::initiate_distributed_ai;
+    /// Current status of a distributed computation.
+    Public "synergy_getDistributedAIStatus" GetDistributedAIStatusParams { computation_id: String } -> ComputationStatusInfo = handlers::get_distributed_ai_status;
+    /// Aggregated result of a completed computation.
+    Public "synergy_getDistributedAIResult" GetDistributedAIResultParams { computation_id: String } -> ComputationResult = handlers::get_distributed_ai_result;
+    /// Submits a validator's hex-encoded partial result for a task.
+    Public "synergy_submitAIPartialResult" SubmitAIPartialResultParams { task_id: String, validator_address: String, partial_result: String } -> ActionResult = handlers::submit_ai_partial_result;
+    /// Tasks assigned to a validator and not yet completed.
+    Public "synergy_getValidatorAITasks" GetValidatorAITasksParams { validator_address: String } -> Vec<AIComputationTask> = handlers::get_validator_ai_tasks;
+    /// AI rewards earned by a validator.
+    Public "synergy_getValidatorAIRewards" GetValidatorAIRewardsParams { validator_address: String } -> ValidatorAIRewards = handlers::get_validator_ai_rewards;
+    /// Distributed AI network counters.
+    Public "synergy_getAIDistributedStats" GetAIDistributedStatsParams {} -> HashMap<String, String> = handlers::get_ai_distributed_stats;
+
+    // Wallet
+
+    /// Creates a node-held wallet whose key is stored in the node's keystore, encrypted with `password`. The wallet starts locked.
+    Wallet "synergy_createWallet" CreateWalletParams { password: String, hybrid: Option<bool> } -> WalletCreated = handlers::create_wallet;
+    /// Decrypts a node-held wallet's key so it can sign, for `duration_secs` or until locked.
+    Wallet "synergy_unlockWallet" UnlockWalletParams { address: String, password: String, duration_secs: Option<u64> } -> ActionResult = handlers::unlock_wallet;
+    /// Discards a node-held wallet's decrypted key.
+    Wallet "synergy_lockWallet" LockWalletParams { address: String } -> ActionResult = handlers::lock_wallet;
+    /// Node-held wallet with the given address, or null.
+    Wallet "synergy_getWallet" GetWalletParams { address: String } -> Option<Wallet> = handlers::get_wallet;
+    /// Signs a transaction with an unlocked node-held wallet.
+    Wallet "synergy_signTransaction" SignTransactionParams { address: String, transaction: Transaction } -> SignedTransaction = handlers::sign_transaction;
+    /// Creates a signed transfer from a node-held wallet.
+    Wallet "synergy_sendTokens" SendTokensParams { from: String, to: String, token_symbol: String, amount: u64 } -> TransactionCreated = handlers::send_tokens;
+    /// Creates a signed staking transaction from a node-held wallet.
+    Wallet "synergy_stakeTokens" StakeTokensParams { staker: String, validator: String, token_symbol: String, amount: u64 } -> TransactionCreated = handlers::stake_tokens;
+
+    // Admin
+
+    /// Activates a pending validator.
+    Admin "synergy_approveValidator" ApproveValidatorParams { address: String } -> ActionResult = handlers::approve_validator;
+    /// Slashes a validator's stake.
+    Admin "synergy_slashValidator" SlashValidatorParams { address: String, reason: String } -> ActionResult = handlers::slash_validator;
+    /// Registers a new token with a maximum supply of twice `total_supply`.
+    Admin "synergy_createToken" CreateTokenParams { symbol: String, name: String, decimals: u8, total_supply: u64, creator: String } -> ActionResult = handlers::create_token;
+    /// Mints tokens to an address.
+    Admin "synergy_mintTokens" MintTokensParams { to: String, token_symbol: String, amount: u64 } -> ActionResult = handlers::mint_tokens;
+    /// Burns tokens held by an address.
+    Admin "synergy_burnTokens" BurnTokensParams { from: String, token_symbol: String, amount: u64 } -> ActionResult = handlers::burn_tokens;
+    /// Moves tokens between addresses without a signature.
+    Admin "synergy_transferTokens" TransferTokensParams { from: String, to: String, token_symbol: String, amount: u64 } -> ActionResult = handlers::transfer_tokens;
+    /// Stakes tokens without a signature.
+    Admin "synergy_stakeTokensDirect" StakeTokensDirectParams { staker: String, validator: String, token_symbol: String, amount: u64 } -> ActionResult = handlers::stake_tokens_direct;
+    /// Unstakes tokens without a signature.
+    Admin "synergy_unstakeTokens" UnstakeTokensParams { staker: String, validator: String, token_symbol: String, amount: u64 } -> ActionResult = handlers::unstake_tokens;
+    /// Deploys hex-encoded bytecode as the node. `contract_type` is ai, cross_chain, oracle or standard.
+    Admin "synergy_deployAIVMContract" DeployAIVMContractParams { bytecode: String, abi: String, contract_type: String } -> ContractDeployed = handlers::deploy_aivm_contract;
+    /// Connected peers with their scores, and active bans.
+    Admin "synergy_peers" PeersParams {} -> PeerList = handlers::peers;
+    /// Bans a peer for `duration_secs`, or permanently when omitted.
+    Admin "synergy_banPeer" BanPeerParams { address: String, duration_secs: Option<u64>, reason: Option<String> } -> PeerBanned = handlers::ban_peer;
+    /// Lifts a ban.
+    Admin "synergy_unbanPeer" UnbanPeerParams { address: String } -> ActionResult = handlers::unban_peer;
+    /// Recent log entries, oldest first. `level` is the minimum level, `module` includes its submodules, `since` and `until` are Unix times, and `limit` (default 100) keeps the newest.
+    Admin "synergy_getLogs" GetLogsParams { level: Option<String>, module: Option<String>, since: Option<u64>, until: Option<u64>, limit: Option<u64> } -> Vec<LogEntry> = handlers::get_logs;
+    /// Log levels currently in effect.
+    Admin "synergy_getLogLevels" GetLogLevelsParams {} -> LogLevels = handlers::get_log_levels;
+    /// Sets the level of `module`, or the default level when omitted, until the node restarts.
+    Admin "synergy_setLogLevel" SetLogLevelParams { level: String, module: Option<String> } -> LogLevels = handlers::set_log_level;
+
+    // Debug
+
+    /// Every node-held wallet.
+    Debug "synergy_getAllWallets" GetAllWalletsParams {} -> Vec<Wallet> = handlers::get_all_wallets;
+    /// Every token balance held by an address.
+    Debug "synergy_getAllBalances" GetAllBalancesParams { address: String } -> HashMap<String, u64> = handlers::get_all_balances;
+}
@@ -1,31 +1,15 @@
-use std::io::{Read, Write};
-use std::net::TcpListener;
+pub mod api;
+pub mod auth;
+pub mod error;
+pub mod eth;
+pub mod grpc_server;
+pub mod handlers;
+pub mod jsonrpc;
+pub mod limits;
+pub mod methods;
+pub mod openrpc;
 pub mod rpc_server;
+pub mod subscriptions;
+pub mod ws_server;
 
-
-// Temporarily disabled to avoid tokio runtime issues
-// pub fn start_rpc_server() {
-//     let listener =
-//         TcpListener::bind("0.0.0.0:8545").expect("Failed to bind RPC server to port 8545");
-
-//     println!("RPC server is running...");
-
-//     for stream in listener.incoming() {
-//         match stream {
-//             Ok(mut stream) => {
-//                 let mut buffer = [0; 1024];
-//                 stream.read(&mut buffer).unwrap();
-//                 println!(
-//                     "Received RPC request: {:?}",
-//                     String::from_utf8_lossy(&buffer)
-//                 );
-//                 stream
-//                     .write(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nOK")
-//                     .unwrap();
-//             }
-//             Err(e) => {
-//                 println!("Connection failed: {}", e);
-//             }
-//         }
-//     }
-// }
+pub use error::RpcError;
@@ -0,0 +1,66 @@
+//! OpenRPC document for the methods in `rpc::methods`, served by
+//! `rpc.discover` and used to generate the `synergy-client` crate.
+
+use lazy_static::lazy_static;
+use schemars::gen::SchemaSettings;
+use schemars::schema::Schema;
+use serde_json::{json, Value};
+
+use super::methods::METHODS;
+
+pub const OPENRPC_VERSION: &str = "1.2.6";
+
+lazy_static! {
+    static ref DOCUMENT: Value = build_document();
+}
+
+pub fn document() -> &'static Value {
+    &DOCUMENT
+}
+
+fn build_document() -> Value {
+    let mut generator = SchemaSettings::draft07()
+        .with(|settings| settings.definitions_path = "#/components/schemas/".to_string())
+        .into_generator();
+
+    let methods: Vec<Value> = METHODS
+        .iter()
+        .map(|method| {
+            let params = match (method.params_schema)(&mut generator) {
+                Schema::Object(schema) => {
+                    let object = schema.object.unwrap_or_default();
+                    object
+                        .properties
+                        .iter()
+                        .map(|(name, schema)| json!({
+                            "name": name,
+                            "required": object.required.contains(name),
+                            "schema": schema,
+                        }))
+                        .collect()
+                }
+                Schema::Bool(_) => Vec::new(),
+            };
+            let result = (method.result_schema)(&mut generator);
+
+            json!({
+                "name": method.name,
+                "summary": method.summary.trim(),
+                "tags": [{"name": method.namespace.as_str()}],
+                "paramStructure": "either",
+                "params": params,
+                "result": {"name": "result", "schema": result},
+            })
+        })
+        .collect();
+
+    json!({
+        "openrpc": OPENRPC_VERSION,
+        "info": {
+            "title": "Synergy Network JSON-RPC API",
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+        "methods": methods,
+        "components": {"schemas": generator.take_definitions()},
+    })
+}
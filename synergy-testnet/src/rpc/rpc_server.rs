@@ -1,17 +1,44 @@
-use std::net::TcpListener;
-use std::io::{Read, Write};
+use std::future::Future;
+use std::io;
+use std::net::SocketAddr;
+use std::pin::{pin, Pin};
 use std::sync::{Arc, Mutex};
-use std::thread;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
 
 use crate::transaction::Transaction;
 use crate::block::BlockChain;
 use crate::validator::ValidatorManager;
-use crate::token::TOKEN_MANAGER;
-use crate::wallet::{WALLET_MANAGER, WalletManager};
 use crate::aivm::AIVMRuntime;
+use crate::config::{HealthConfig, RPCConfig, RpcLimitsConfig};
+use crate::health::{self, HealthStatus};
+use crate::metrics;
+use crate::shutdown;
+use super::auth::{self, CallerContext, Namespace, RpcAuth};
+use super::error::RpcError;
+use super::eth;
+use super::grpc_server;
+use super::jsonrpc;
+use super::limits::RpcLimiter;
+use super::methods;
+use super::ws_server;
+use axum::{Router, routing::{get, post}};
+use axum::body::Body;
+use axum::extract::{ConnectInfo, DefaultBodyLimit, Request, State};
+use axum::http::{header, HeaderMap, HeaderValue, Method, StatusCode};
+use axum::response::{IntoResponse, Response};
+use hyper::body::Incoming;
+use hyper::server::conn::http1;
+use hyper_util::rt::{TokioIo, TokioTimer};
+use hyper_util::service::TowerToHyperService;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinSet;
+use tokio::time::Sleep;
+use tower::ServiceExt;
+use tower_http::cors::{AllowOrigin, CorsLayer};
 use lazy_static::lazy_static;
-use serde_json::{Value, json};
+use serde_json::Value;
 
 lazy_static! {
     pub static ref TX_POOL: Arc<Mutex<Vec<Transaction>>> = Arc::new(Mutex::new(Vec::new()));
@@ -29,812 +56,386 @@ lazy_static! {
     pub static ref AIVM_RUNTIME: Arc<AIVMRuntime> = Arc::new(AIVMRuntime::new());
 }
 
-pub fn start_rpc_server() {
-    println!("📡 RPC server running on 0.0.0.0:8545");
-
-    for stream in TcpListener::bind("0.0.0.0:8545").expect("Failed to bind RPC server").incoming() {
-        let tx_pool = Arc::clone(&TX_POOL);
-        let chain = Arc::clone(&CHAIN);
-        let validator_manager = Arc::clone(&VALIDATOR_MANAGER);
-        let aivm_runtime = Arc::clone(&AIVM_RUNTIME);
-
-        thread::spawn(move || {
-            if let Ok(mut stream) = stream {
-                let mut buffer = [0; 16384];
-                if let Ok(bytes_read) = stream.read(&mut buffer) {
-                    let request_str = String::from_utf8_lossy(&buffer[..bytes_read]);
-
-                    // Split headers and body
-                    let parts: Vec<&str> = request_str.split("\r\n\r\n").collect();
-                    if parts.len() < 2 {
-                        send_error(&mut stream, "Malformed HTTP request");
-                        return;
-                    }
-
-                    let body = parts[1];
-
-                    if request_str.starts_with("POST") {
-                        match serde_json::from_str::<Value>(body) {
-                            Ok(parsed) => {
-                                let method = parsed.get("method").and_then(|m| m.as_str()).unwrap_or("");
-                                let params = parsed.get("params").cloned().unwrap_or(json!([]));
-                                let id = parsed.get("id").cloned().unwrap_or(json!(null));
-
-                                let result = handle_json_rpc(method, params, &tx_pool, &chain, &validator_manager, &aivm_runtime);
-
-                                let response = json!({
-                                    "jsonrpc": "2.0",
-                                    "id": id,
-                                    "result": result
-                                });
-
-                                let response_str = format_response(&response.to_string());
-                                let _ = stream.write(response_str.as_bytes());
-                            }
-                            Err(_) => send_error(&mut stream, "Malformed JSON in body"),
-                        }
-                    }
-                }
-            }
-        });
-    }
-}
-
-fn handle_json_rpc(
-    method: &str,
-    params: Value,
-    tx_pool: &Arc<Mutex<Vec<Transaction>>>,
-    chain: &Arc<Mutex<BlockChain>>,
-    validator_manager: &Arc<ValidatorManager>,
-    aivm_runtime: &Arc<AIVMRuntime>,
-) -> Value {
-    match method {
-        // Blockchain queries
-        "synergy_blockNumber" => {
-            let chain = chain.lock().unwrap();
-            json!(chain.last().map_or(0, |b| b.block_index))
-        }
-
-        "synergy_getBlockByNumber" => {
-            if let Some(block_num) = params.get(0).and_then(|v| v.as_u64()) {
-                let chain = chain.lock().unwrap();
-                if let Some(block) = chain.chain.iter().find(|b| b.block_index == block_num) {
-                    json!(block)
-                } else {
-                    json!(null)
-                }
-            } else {
-                json!("Invalid block number")
-            }
-        }
-
-        "synergy_getLatestBlock" => {
-            let chain = chain.lock().unwrap();
-            if let Some(block) = chain.last() {
-                json!(block)
-            } else {
-                json!(null)
-            }
-        }
-
-        // Transaction methods
-        "synergy_sendTransaction" => {
-            if let Some(tx_data) = params.get(0) {
-                match serde_json::from_value::<Transaction>(tx_data.clone()) {
-                    Ok(tx) => {
-                        match tx.validate() {
-                            crate::transaction::TransactionValidationResult { is_valid: true, .. } => {
-                                let mut pool = tx_pool.lock().unwrap();
-                                pool.push(tx);
-                                json!("Transaction submitted successfully")
-                            }
-                            crate::transaction::TransactionValidationResult { error_message: Some(msg), .. } => {
-                                json!({"error": msg})
-                            }
-                            _ => {
-                                json!("Invalid transaction")
-                            }
-                        }
-                    }
-                    Err(_) => json!("Invalid transaction format"),
-                }
-            } else {
-                json!("Missing transaction data")
-            }
-        }
-
-        "synergy_getTransactionPool" => {
-            let pool = tx_pool.lock().unwrap();
-            json!(*pool)
-        }
+/// How often idle rate-limit buckets are dropped.
+const LIMITER_PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+/// Pause after a failed accept, e.g. when out of file descriptors.
+const ACCEPT_RETRY_DELAY: Duration = Duration::from_millis(100);
 
-        // Node status
-        "synergy_nodeInfo" => {
-            json!({
-                "name": "Synergy Testnet Node",
-                "version": "1.0.0",
-                "protocolVersion": 1,
-                "networkId": 7963749,
-                "chainId": 7963749,
-                "consensus": "Proof of Synergy",
-                "syncing": false,
-                "currentBlock": chain.lock().unwrap().last().map_or(0, |b| b.block_index),
-                "timestamp": current_timestamp()
-            })
-        }
-
-        // Validator management
-        "synergy_getValidators" => {
-            let validators = validator_manager.get_active_validators();
-            json!(validators)
-        }
-
-        "synergy_getValidator" => {
-            if let Some(address) = params.get(0).and_then(|v| v.as_str()) {
-                match validator_manager.get_validator(address) {
-                    Some(validator) => json!(validator),
-                    None => json!(null),
-                }
-            } else {
-                json!("Missing validator address")
-            }
-        }
-
-        // Token methods
-        "synergy_getTokenBalance" => {
-            if let (Some(address), Some(token)) = (
-                params.get(0).and_then(|v| v.as_str()),
-                params.get(1).and_then(|v| v.as_str()),
-            ) {
-                let token_manager = TOKEN_MANAGER.clone();
-                json!(token_manager.get_balance(address, token))
-            } else {
-                json!("Missing address or token symbol")
-            }
-        }
-
-        "synergy_getTokens" => {
-            let token_manager = TOKEN_MANAGER.clone();
-            json!(token_manager.get_all_tokens())
-        }
-
-        "synergy_createWallet" => {
-            if let Ok(mut wallet_manager) = WALLET_MANAGER.lock() {
-                let address = wallet_manager.create_wallet();
-                json!({"address": address, "message": "Wallet created successfully"})
-            } else {
-                json!({"error": "Failed to create wallet"})
-            }
-        }
-
-        "synergy_getWallet" => {
-            if let Some(address) = params.get(0).and_then(|v| v.as_str()) {
-                if let Ok(wallet_manager) = WALLET_MANAGER.lock() {
-                    match wallet_manager.get_wallet(address) {
-                        Some(wallet) => json!(wallet),
-                        None => json!(null),
-                    }
-                } else {
-                    json!({"error": "Failed to access wallet"})
-                }
-            } else {
-                json!("Missing address")
-            }
-        }
-
-        "synergy_createWalletFromKeypair" => {
-            if let (Some(public_key), Some(private_key)) = (
-                params.get(0).and_then(|v| v.as_str()),
-                params.get(1).and_then(|v| v.as_str()),
-            ) {
-                if let Ok(mut wallet_manager) = WALLET_MANAGER.lock() {
-                    let address = wallet_manager.create_wallet_from_keypair(public_key.to_string(), private_key.to_string());
-                    json!({"success": true, "address": address, "message": "Wallet created successfully"})
-                } else {
-                    json!({"success": false, "error": "Failed to access wallet manager"})
-                }
-            } else {
-                json!({"success": false, "error": "Missing required parameters: public_key, private_key"})
-            }
-        }
-
-        "synergy_getAllWallets" => {
-            if let Ok(wallet_manager) = WALLET_MANAGER.lock() {
-                json!(wallet_manager.get_all_wallets())
-            } else {
-                json!({"error": "Failed to access wallet manager"})
-            }
-        }
-
-        "synergy_signTransaction" => {
-            if let (Some(address), Some(tx_data)) = (
-                params.get(0).and_then(|v| v.as_str()),
-                params.get(1),
-            ) {
-                if let Ok(mut transaction) = serde_json::from_value::<Transaction>(tx_data.clone()) {
-                    if let Ok(mut wallet_manager) = WALLET_MANAGER.lock() {
-                        match wallet_manager.sign_transaction(address, &mut transaction) {
-                            Ok(result) => json!({"success": true, "message": result, "transaction": transaction}),
-                            Err(error) => json!({"success": false, "error": error}),
-                        }
-                    } else {
-                        json!({"success": false, "error": "Failed to access wallet manager"})
-                    }
-                } else {
-                    json!({"success": false, "error": "Invalid transaction format"})
-                }
-            } else {
-                json!({"success": false, "error": "Missing required parameters: address, transaction"})
-            }
-        }
-
-        "synergy_sendTokens" => {
-            if let (Some(from), Some(to), Some(token_symbol), Some(amount)) = (
-                params.get(0).and_then(|v| v.as_str()),
-                params.get(1).and_then(|v| v.as_str()),
-                params.get(2).and_then(|v| v.as_str()),
-                params.get(3).and_then(|v| v.as_u64()),
-            ) {
-                if let Ok(mut wallet_manager) = WALLET_MANAGER.lock() {
-                    let token_manager = TOKEN_MANAGER.clone();
-                    match wallet_manager.send_tokens(from, to, token_symbol, amount, &token_manager) {
-                        Ok(transaction) => json!({"success": true, "transaction": transaction, "message": "Transaction created successfully"}),
-                        Err(error) => json!({"success": false, "error": error}),
-                    }
-                } else {
-                    json!({"success": false, "error": "Failed to access wallet manager"})
-                }
-            } else {
-                json!({"success": false, "error": "Missing required parameters: from, to, token_symbol, amount"})
-            }
-        }
-
-        "synergy_stakeTokens" => {
-            if let (Some(staker), Some(validator), Some(token_symbol), Some(amount)) = (
-                params.get(0).and_then(|v| v.as_str()),
-                params.get(1).and_then(|v| v.as_str()),
-                params.get(2).and_then(|v| v.as_str()),
-                params.get(3).and_then(|v| v.as_u64()),
-            ) {
-                if let Ok(mut wallet_manager) = WALLET_MANAGER.lock() {
-                    let token_manager = TOKEN_MANAGER.clone();
-                    match wallet_manager.stake_tokens(staker, validator, token_symbol, amount, &token_manager) {
-                        Ok(transaction) => json!({"success": true, "transaction": transaction, "message": "Staking transaction created successfully"}),
-                        Err(error) => json!({"success": false, "error": error}),
-                    }
-                } else {
-                    json!({"success": false, "error": "Failed to access wallet manager"})
-                }
-            } else {
-                json!({"success": false, "error": "Missing required parameters: staker, validator, token_symbol, amount"})
-            }
-        }
-
-        "synergy_stakeTokensDirect" => {
-            if let (Some(staker), Some(validator), Some(token_symbol), Some(amount)) = (
-                params.get(0).and_then(|v| v.as_str()),
-                params.get(1).and_then(|v| v.as_str()),
-                params.get(2).and_then(|v| v.as_str()),
-                params.get(3).and_then(|v| v.as_u64()),
-            ) {
-                let token_manager = TOKEN_MANAGER.clone();
-                match token_manager.stake_tokens(staker, validator, token_symbol, amount) {
-                    Ok(result) => json!({"success": true, "message": result}),
-                    Err(error) => json!({"success": false, "error": error}),
-                }
-            } else {
-                json!({"success": false, "error": "Missing required parameters: staker, validator, token_symbol, amount"})
-            }
-        }
+#[derive(Clone)]
+struct HttpState {
+    auth: Arc<RpcAuth>,
+    limiter: Arc<RpcLimiter>,
+    health: Arc<HealthConfig>,
+}
 
-        "synergy_unstakeTokens" => {
-            if let (Some(staker), Some(validator), Some(token_symbol), Some(amount)) = (
-                params.get(0).and_then(|v| v.as_str()),
-                params.get(1).and_then(|v| v.as_str()),
-                params.get(2).and_then(|v| v.as_str()),
-                params.get(3).and_then(|v| v.as_u64()),
-            ) {
-                let token_manager = TOKEN_MANAGER.clone();
-                match token_manager.unstake_tokens(staker, validator, token_symbol, amount) {
-                    Ok(result) => json!({"success": true, "message": result}),
-                    Err(error) => json!({"success": false, "error": error}),
-                }
-            } else {
-                json!({"success": false, "error": "Missing required parameters: staker, validator, token_symbol, amount"})
-            }
-        }
+/// Starts the HTTP JSON-RPC server on `RPCConfig.http_port` and, when enabled,
+/// the WebSocket endpoint on `RPCConfig.ws_port` and the gRPC service on
+/// `RPCConfig.grpc_port`. Blocks until all of them exit, which they do
+/// once `shutdown::request` is called and in-flight requests finish.
+///
+/// Calls run on a blocking pool of `RPCConfig.limits.worker_threads`
+/// threads. A request that finds every thread busy is rejected with 503
+/// instead of waiting for one.
+pub fn start_rpc_server(config: &RPCConfig) {
+    if !config.enable_http && !config.enable_ws && !config.enable_grpc {
+        crate::info!("rpc", "HTTP, WebSocket and gRPC RPC disabled in configuration");
+        return;
+    }
 
-        "synergy_getStakedBalance" => {
-            if let (Some(address), Some(token_symbol)) = (
-                params.get(0).and_then(|v| v.as_str()),
-                params.get(1).and_then(|v| v.as_str()),
-            ) {
-                let token_manager = TOKEN_MANAGER.clone();
-                json!({"balance": token_manager.get_staked_balance(address, token_symbol)})
-            } else {
-                json!("Missing address or token_symbol parameter")
-            }
+    let auth = match RpcAuth::from_config(&config.auth) {
+        Ok(auth) => Arc::new(auth),
+        Err(e) => {
+            crate::error!("rpc", "RPC auth configuration error", "error" => e.to_string());
+            return;
         }
+    };
 
-        "synergy_getStakingInfo" => {
-            if let Some(address) = params.get(0).and_then(|v| v.as_str()) {
-                let token_manager = TOKEN_MANAGER.clone();
-                json!(token_manager.get_staking_info(address))
-            } else {
-                json!("Missing address parameter")
-            }
-        }
+    let limiter = Arc::new(RpcLimiter::new(config.limits.clone()));
 
-        "synergy_registerValidator" => {
-            if let (Some(address), Some(public_key), Some(name), Some(stake_amount)) = (
-                params.get(0).and_then(|v| v.as_str()),
-                params.get(1).and_then(|v| v.as_str()),
-                params.get(2).and_then(|v| v.as_str()),
-                params.get(3).and_then(|v| v.as_u64()),
-            ) {
-                let registration = crate::validator::ValidatorRegistration {
-                    address: address.to_string(),
-                    public_key: public_key.to_string(),
-                    name: name.to_string(),
-                    stake_amount,
-                    submitted_at: current_timestamp(),
-                    registration_tx_hash: format!("reg_{}", current_timestamp()),
-                };
-
-                match validator_manager.register_validator(registration) {
-                    Ok(result) => json!({"success": true, "message": result}),
-                    Err(error) => json!({"success": false, "error": error}),
-                }
-            } else {
-                json!({"success": false, "error": "Missing required parameters: address, public_key, name, stake_amount"})
-            }
-        }
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .thread_name("synergy-rpc")
+        .max_blocking_threads(config.limits.worker_threads.max(1))
+        .build()
+        .expect("Failed to create RPC runtime");
 
-        "synergy_approveValidator" => {
-            if let Some(address) = params.get(0).and_then(|v| v.as_str()) {
-                match validator_manager.approve_validator(address) {
-                    Ok(_) => json!({"success": true, "message": "Validator approved successfully"}),
-                    Err(error) => json!({"success": false, "error": error}),
-                }
-            } else {
-                json!("Missing address parameter")
+    let config = config.clone();
+    runtime.block_on(async move {
+        let pruner = Arc::clone(&limiter);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(LIMITER_PRUNE_INTERVAL);
+            loop {
+                interval.tick().await;
+                pruner.prune_idle();
             }
-        }
-
-        "synergy_getTopValidators" => {
-            let count = params.get(0).and_then(|v| v.as_u64()).unwrap_or(10) as usize;
-            json!(validator_manager.get_top_validators(count))
-        }
+        });
 
-        "synergy_slashValidator" => {
-            if let (Some(address), Some(reason)) = (
-                params.get(0).and_then(|v| v.as_str()),
-                params.get(1).and_then(|v| v.as_str()),
-            ) {
-                match validator_manager.slash_validator(address, reason) {
-                    Ok(_) => json!({"success": true, "message": "Validator slashed successfully"}),
-                    Err(error) => json!({"success": false, "error": error}),
+        let http = async {
+            if config.enable_http {
+                if let Err(e) = serve_http(&config, Arc::clone(&auth), Arc::clone(&limiter)).await {
+                    crate::error!("rpc", "RPC server error", "error" => e.to_string());
                 }
-            } else {
-                json!({"success": false, "error": "Missing required parameters: address, reason"})
-            }
-        }
-
-        "synergy_getBlockRange" => {
-            if let (Some(start), Some(end)) = (
-                params.get(0).and_then(|v| v.as_u64()),
-                params.get(1).and_then(|v| v.as_u64()),
-            ) {
-                let chain = chain.lock().unwrap();
-                let blocks: Vec<_> = chain.chain.iter()
-                    .filter(|block| block.block_index >= start && block.block_index <= end)
-                    .collect();
-
-                json!(blocks)
-            } else {
-                json!("Missing start or end parameter")
             }
-        }
-
-        "synergy_getTransactionByHash" => {
-            if let Some(tx_hash) = params.get(0).and_then(|v| v.as_str()) {
-                let chain = chain.lock().unwrap();
-                for block in &chain.chain {
-                    for tx in &block.transactions {
-                        if tx.hash() == tx_hash {
-                            return json!(tx);
-                        }
-                    }
+        };
+        let ws = async {
+            if config.enable_ws {
+                if let Err(e) = ws_server::serve_ws(&config, Arc::clone(&auth), Arc::clone(&limiter)).await {
+                    crate::error!("rpc", "WebSocket server error", "error" => e.to_string());
                 }
-                json!(null)
-            } else {
-                json!("Missing transaction hash parameter")
             }
-        }
-
-        "synergy_getTransactionsInBlock" => {
-            if let Some(block_number) = params.get(0).and_then(|v| v.as_u64()) {
-                let chain = chain.lock().unwrap();
-                if let Some(block) = chain.chain.iter().find(|b| b.block_index == block_number) {
-                    json!(block.transactions.clone())
-                } else {
-                    json!([])
+        };
+        let grpc = async {
+            if config.enable_grpc {
+                if let Err(e) = grpc_server::serve_grpc(&config, Arc::clone(&auth), Arc::clone(&limiter)).await {
+                    crate::error!("rpc", "gRPC server error", "error" => e.to_string());
                 }
-            } else {
-                json!("Missing block number parameter")
-            }
-        }
-
-        "synergy_getValidatorStats" => {
-            let active_validators = validator_manager.get_active_validators();
-            let top_validators = validator_manager.get_top_validators(20);
-
-            json!({
-                "total_validators": active_validators.len(),
-                "active_validators": active_validators,
-                "top_validators": top_validators,
-                "epoch_rewards": validator_manager.calculate_epoch_rewards(0)
-            })
-        }
-
-        "synergy_getTokenStats" => {
-            let token_manager = TOKEN_MANAGER.clone();
-            let tokens = token_manager.get_all_tokens();
-
-            let mut token_stats = Vec::new();
-            for token in tokens {
-                let total_staked = token_manager.get_staked_balance("*", &token.symbol);
-                token_stats.push(json!({
-                    "symbol": token.symbol,
-                    "name": token.name,
-                    "total_supply": token.total_supply,
-                    "total_staked": total_staked,
-                    "holders": token_manager.balances.lock().unwrap().keys()
-                        .filter(|addr| token_manager.get_balance(addr, &token.symbol) > 0)
-                        .count()
-                }));
             }
+        };
+        tokio::join!(http, ws, grpc);
+    });
+}
 
-            json!(token_stats)
-        }
+async fn serve_http(config: &RPCConfig, auth: Arc<RpcAuth>, limiter: Arc<RpcLimiter>) -> Result<(), Box<dyn std::error::Error>> {
+    // The body size is enforced while reading, so the client gets a JSON-RPC error
+    let mut app = Router::new()
+        .route("/", post(handle_http_request))
+        .route("/health", get(handle_health))
+        .route("/ready", get(handle_ready))
+        .route("/live", get(handle_live));
+    if config.enable_metrics {
+        app = app.route("/metrics", get(handle_metrics));
+    }
+    let app = app
+        .layer(DefaultBodyLimit::disable())
+        .layer(cors_layer(config))
+        .with_state(HttpState { auth, limiter, health: Arc::new(config.health.clone()) });
 
-        // AIVM - Artificial Intelligence Virtual Machine Methods
-        "synergy_deployAIVMContract" => {
-            if let (Some(bytecode), Some(abi), Some(contract_type)) = (
-                params.get(0).and_then(|v| v.as_str()),
-                params.get(1).and_then(|v| v.as_str()),
-                params.get(2).and_then(|v| v.as_str()),
-            ) {
-                let bytecode_vec = hex::decode(bytecode).unwrap_or_default();
-                let contract_type_enum = match contract_type {
-                    "ai" => crate::aivm::ContractType::AIEnhanced,
-                    "cross_chain" => crate::aivm::ContractType::CrossChain,
-                    "oracle" => crate::aivm::ContractType::Oracle,
-                    _ => crate::aivm::ContractType::Standard,
-                };
-
-                match aivm_runtime.deploy_contract(
-                    bytecode_vec,
-                    abi.to_string(),
-                    "system".to_string(),
-                    contract_type_enum,
-                ) {
-                    Ok(address) => json!({"success": true, "contract_address": address, "message": "AIVM contract deployed successfully"}),
-                    Err(error) => json!({"success": false, "error": error}),
-                }
-            } else {
-                json!({"success": false, "error": "Missing required parameters: bytecode, abi, contract_type"})
-            }
-        }
+    let address = format!("0.0.0.0:{}", config.http_port);
+    let listener = TcpListener::bind(&address).await?;
+    crate::info!("rpc", "RPC server running", "address" => address.to_string());
 
-        "synergy_executeAIVMContract" => {
-            if let (Some(contract_address), Some(input_data)) = (
-                params.get(0).and_then(|v| v.as_str()),
-                params.get(1).and_then(|v| v.as_str()),
-            ) {
-                let input_bytes = hex::decode(input_data).unwrap_or_default();
-                let context = crate::aivm::AIVMExecutionContext {
-                    transaction_hash: "manual_execution".to_string(),
-                    block_height: 0,
-                    timestamp: current_timestamp(),
-                    sender: "manual".to_string(),
-                    contract_address: Some(contract_address.to_string()),
-                    input_data: input_bytes,
-                    gas_limit: 1000000,
-                    gas_price: 1000,
-                };
-
-                match aivm_runtime.execute_contract(contract_address, context) {
-                    Ok(result) => json!({"success": true, "result": result, "message": "AIVM contract executed successfully"}),
-                    Err(error) => json!({"success": false, "error": error}),
-                }
-            } else {
-                json!({"success": false, "error": "Missing required parameters: contract_address, input_data"})
-            }
-        }
+    serve_connections(listener, app, &config.limits).await;
+    Ok(())
+}
 
-        "synergy_initiateDistributedAI" => {
-            if let (Some(model_id), Some(input_data)) = (
-                params.get(0).and_then(|v| v.as_str()),
-                params.get(1).and_then(|v| v.as_str()),
-            ) {
-                let input_bytes = hex::decode(input_data).unwrap_or_default();
-                let cluster_id = params.get(2).and_then(|v| v.as_u64());
-
-                match aivm_runtime.distributed_ai.initiate_distributed_computation(
-                    model_id.to_string(),
-                    input_bytes,
-                    cluster_id,
-                ) {
-                    Ok(computation_id) => json!({"success": true, "computation_id": computation_id, "message": "Distributed AI computation initiated"}),
-                    Err(error) => json!({"success": false, "error": error}),
+/// Serves HTTP/1.1 connections until a shutdown is requested, then lets
+/// in-flight requests finish. Unlike `axum::serve`, this bounds how long a
+/// client may take to send request headers (`read_timeout_secs`) and to
+/// accept response data (`write_timeout_secs`), so slow clients cannot
+/// hold connections open indefinitely.
+async fn serve_connections(listener: TcpListener, app: Router, limits: &RpcLimitsConfig) {
+    let header_timeout = Duration::from_secs(limits.read_timeout_secs);
+    let write_timeout = Duration::from_secs(limits.write_timeout_secs);
+    let mut connections = JoinSet::new();
+
+    loop {
+        let (stream, remote) = tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    crate::warn!("rpc", "Failed to accept RPC connection", "error" => e.to_string());
+                    tokio::time::sleep(ACCEPT_RETRY_DELAY).await;
+                    continue;
                 }
-            } else {
-                json!({"success": false, "error": "Missing required parameters: model_id, input_data"})
-            }
-        }
+            },
+            Some(_) = connections.join_next() => continue,
+            _ = shutdown::wait() => break,
+        };
+
+        let service = TowerToHyperService::new(app.clone().map_request(move |mut request: Request<Incoming>| {
+            request.extensions_mut().insert(ConnectInfo(remote));
+            request
+        }));
+        connections.spawn(async move {
+            let mut builder = http1::Builder::new();
+            builder.timer(TokioTimer::new()).header_read_timeout(header_timeout);
+            let mut connection = pin!(builder.serve_connection(TokioIo::new(WriteTimeout::new(stream, write_timeout)), service));
+            tokio::select! {
+                _ = connection.as_mut() => return,
+                _ = shutdown::wait() => connection.as_mut().graceful_shutdown(),
+            }
+            let _ = connection.await;
+        });
+    }
 
-        "synergy_getDistributedAIStatus" => {
-            if let Some(computation_id) = params.get(0).and_then(|v| v.as_str()) {
-                match aivm_runtime.distributed_ai.get_computation_status(computation_id) {
-                    Some(status) => json!({"status": format!("{:?}", status), "computation_id": computation_id}),
-                    None => json!({"error": "Computation not found"}),
-                }
-            } else {
-                json!("Missing computation_id parameter")
-            }
-        }
+    // In-flight requests are allowed to finish once a shutdown is requested
+    while connections.join_next().await.is_some() {}
+}
 
-        "synergy_getDistributedAIResult" => {
-            if let Some(computation_id) = params.get(0).and_then(|v| v.as_str()) {
-                match aivm_runtime.distributed_ai.get_computation_result(computation_id) {
-                    Some(result) => json!({"success": true, "result": hex::encode(result), "computation_id": computation_id}),
-                    None => json!({"error": "Result not available or computation not completed"}),
-                }
-            } else {
-                json!("Missing computation_id parameter")
-            }
-        }
+/// TCP stream whose writes fail once the client has not accepted any data
+/// for `timeout`, so a client that stops reading releases its connection.
+struct WriteTimeout {
+    stream: TcpStream,
+    timeout: Duration,
+    deadline: Option<Pin<Box<Sleep>>>,
+}
 
-        "synergy_submitAIPartialResult" => {
-            if let (Some(task_id), Some(validator_address), Some(partial_result)) = (
-                params.get(0).and_then(|v| v.as_str()),
-                params.get(1).and_then(|v| v.as_str()),
-                params.get(2).and_then(|v| v.as_str()),
-            ) {
-                let result_bytes = hex::decode(partial_result).unwrap_or_default();
-
-                match aivm_runtime.distributed_ai.submit_partial_result(
-                    task_id,
-                    validator_address,
-                    result_bytes,
-                ) {
-                    Ok(_) => json!({"success": true, "message": "Partial result submitted successfully"}),
-                    Err(error) => json!({"success": false, "error": error}),
-                }
-            } else {
-                json!({"success": false, "error": "Missing required parameters: task_id, validator_address, partial_result"})
-            }
-        }
+impl WriteTimeout {
+    fn new(stream: TcpStream, timeout: Duration) -> Self {
+        WriteTimeout { stream, timeout, deadline: None }
+    }
 
-        "synergy_getValidatorAITasks" => {
-            if let Some(validator_address) = params.get(0).and_then(|v| v.as_str()) {
-                let tasks = aivm_runtime.distributed_ai.get_pending_tasks_for_validator(validator_address);
-                json!(tasks)
-            } else {
-                json!("Missing validator_address parameter")
-            }
+    /// Passes a finished write through; a pending one arms the deadline.
+    fn check<T>(&mut self, cx: &mut Context<'_>, result: Poll<io::Result<T>>) -> Poll<io::Result<T>> {
+        if result.is_ready() {
+            self.deadline = None;
+            return result;
         }
-
-        "synergy_getValidatorAIRewards" => {
-            if let Some(validator_address) = params.get(0).and_then(|v| v.as_str()) {
-                let rewards = aivm_runtime.distributed_ai.get_validator_ai_rewards(validator_address);
-                json!({"validator_address": validator_address, "total_rewards": rewards})
-            } else {
-                json!("Missing validator_address parameter")
+        let timeout = self.timeout;
+        let deadline = self.deadline.get_or_insert_with(|| Box::pin(tokio::time::sleep(timeout)));
+        match deadline.as_mut().poll(cx) {
+            Poll::Ready(()) => {
+                self.deadline = None;
+                Poll::Ready(Err(io::Error::new(io::ErrorKind::TimedOut, "client stopped reading the response")))
             }
+            Poll::Pending => Poll::Pending,
         }
+    }
+}
 
-        "synergy_getAIDistributedStats" => {
-            json!(aivm_runtime.distributed_ai.get_ai_network_stats())
-        }
+impl AsyncRead for WriteTimeout {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().stream).poll_read(cx, buf)
+    }
+}
 
-        "synergy_chatWithAIVM" => {
-            if let Some(message) = params.get(0).and_then(|v| v.as_str()) {
-                let context = crate::aivm::AIVMExecutionContext {
-                    transaction_hash: "chat_interaction".to_string(),
-                    block_height: 0,
-                    timestamp: current_timestamp(),
-                    sender: "user".to_string(),
-                    contract_address: None,
-                    input_data: message.as_bytes().to_vec(),
-                    gas_limit: 10000,
-                    gas_price: 100,
-                };
-
-                // This would need async support in the RPC handler
-                json!({"success": true, "message": "Chat functionality requires async support - use direct AIVM runtime calls", "context": context})
-            } else {
-                json!({"success": false, "error": "Missing message parameter"})
-            }
-        }
+impl AsyncWrite for WriteTimeout {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let result = Pin::new(&mut this.stream).poll_write(cx, buf);
+        this.check(cx, result)
+    }
 
-        "synergy_getAIVMContracts" => {
-            json!(aivm_runtime.get_all_contracts())
-        }
+    fn poll_write_vectored(self: Pin<&mut Self>, cx: &mut Context<'_>, bufs: &[io::IoSlice<'_>]) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let result = Pin::new(&mut this.stream).poll_write_vectored(cx, bufs);
+        this.check(cx, result)
+    }
 
-        "synergy_getAIVMContract" => {
-            if let Some(address) = params.get(0).and_then(|v| v.as_str()) {
-                match aivm_runtime.get_contract(address) {
-                    Some(contract) => json!(contract),
-                    None => json!(null),
-                }
-            } else {
-                json!("Missing contract address parameter")
-            }
-        }
+    fn is_write_vectored(&self) -> bool {
+        self.stream.is_write_vectored()
+    }
 
-        "synergy_getAIVMStats" => {
-            let distributed_stats = aivm_runtime.distributed_ai.get_ai_network_stats();
-            json!({
-                "total_contracts": aivm_runtime.get_all_contracts().len(),
-                "supported_features": ["ai_enhanced", "cross_chain", "oracle", "standard", "distributed_ai"],
-                "ai_models": ["distributed_ai_model"],
-                "supported_chains": ["ethereum", "polygon", "solana"],
-                "distributed_computations": distributed_stats.get("total_computations").and_then(|v| v.parse::<u64>().ok()).unwrap_or(0),
-                "completed_computations": distributed_stats.get("completed_computations").and_then(|v| v.parse::<u64>().ok()).unwrap_or(0),
-                "active_validators": distributed_stats.get("active_validators").unwrap_or(&"0".to_string()).parse::<u64>().unwrap_or(0),
-                "total_ai_rewards_distributed": distributed_stats.get("total_ai_rewards_distributed").and_then(|v| v.parse::<u64>().ok()).unwrap_or(0)
-            })
-        }
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        let result = Pin::new(&mut this.stream).poll_flush(cx);
+        this.check(cx, result)
+    }
 
-        "synergy_getNetworkStats" => {
-            let chain = chain.lock().unwrap();
-            let token_manager = TOKEN_MANAGER.clone();
-
-            let total_supply = token_manager.get_all_tokens().iter()
-                .map(|token| token.total_supply)
-                .sum::<u64>();
-
-            json!({
-                "block_height": chain.last().map_or(0, |b| b.block_index),
-                "total_transactions": chain.chain.iter().map(|b| b.transactions.len()).sum::<usize>(),
-                "active_validators": validator_manager.get_active_validators().len(),
-                "total_supply": total_supply,
-                "tokens": token_manager.get_all_tokens().len(),
-                "network_uptime": "99.9%",
-                "current_epoch": validator_manager.calculate_epoch_rewards(0).len(),
-                "total_staked": token_manager.get_all_tokens().iter().map(|t| t.symbol.clone()).collect::<Vec<_>>()
-                    .iter().map(|symbol| token_manager.get_staked_balance("*", symbol)).sum::<u64>()
-            })
-        }
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().stream).poll_shutdown(cx)
+    }
+}
 
-        // Enhanced Token Operations
-        "synergy_createToken" => {
-            if let (Some(symbol), Some(name), Some(decimals), Some(total_supply), Some(creator)) = (
-                params.get(0).and_then(|v| v.as_str()),
-                params.get(1).and_then(|v| v.as_str()),
-                params.get(2).and_then(|v| v.as_u64()),
-                params.get(3).and_then(|v| v.as_u64()),
-                params.get(4).and_then(|v| v.as_str()),
-            ) {
-                let token_manager = TOKEN_MANAGER.clone();
-                match token_manager.create_token(
-                    symbol.to_string(),
-                    name.to_string(),
-                    decimals as u8,
-                    total_supply,
-                    Some(total_supply * 2), // max_supply = 2x total_supply
-                    true, // mintable
-                    true, // burnable
-                    creator.to_string(),
-                ) {
-                    Ok(result) => json!({"success": true, "message": result}),
-                    Err(error) => json!({"success": false, "error": error}),
-                }
-            } else {
-                json!({"success": false, "error": "Missing required parameters: symbol, name, decimals, total_supply, creator"})
-            }
-        }
+fn cors_layer(config: &RPCConfig) -> CorsLayer {
+    if !config.cors_enabled {
+        return CorsLayer::new();
+    }
 
-        "synergy_mintTokens" => {
-            if let (Some(to), Some(token_symbol), Some(amount)) = (
-                params.get(0).and_then(|v| v.as_str()),
-                params.get(1).and_then(|v| v.as_str()),
-                params.get(2).and_then(|v| v.as_u64()),
-            ) {
-                let token_manager = TOKEN_MANAGER.clone();
-                match token_manager.mint_tokens(to, token_symbol, amount) {
-                    Ok(result) => json!({"success": true, "message": result}),
-                    Err(error) => json!({"success": false, "error": error}),
-                }
-            } else {
-                json!({"success": false, "error": "Missing required parameters: to, token_symbol, amount"})
-            }
-        }
+    let origins = if config.cors_origins.iter().any(|origin| origin == "*") {
+        AllowOrigin::any()
+    } else {
+        AllowOrigin::list(
+            config.cors_origins.iter().filter_map(|origin| HeaderValue::from_str(origin).ok())
+        )
+    };
+
+    CorsLayer::new()
+        .allow_origin(origins)
+        .allow_methods([Method::POST, Method::OPTIONS])
+        .allow_headers([header::CONTENT_TYPE, header::AUTHORIZATION])
+}
 
-        "synergy_burnTokens" => {
-            if let (Some(from), Some(token_symbol), Some(amount)) = (
-                params.get(0).and_then(|v| v.as_str()),
-                params.get(1).and_then(|v| v.as_str()),
-                params.get(2).and_then(|v| v.as_u64()),
-            ) {
-                let token_manager = TOKEN_MANAGER.clone();
-                match token_manager.burn_tokens(from, token_symbol, amount) {
-                    Ok(result) => json!({"success": true, "message": result}),
-                    Err(error) => json!({"success": false, "error": error}),
-                }
-            } else {
-                json!({"success": false, "error": "Missing required parameters: from, token_symbol, amount"})
-            }
-        }
+async fn handle_http_request(
+    State(state): State<HttpState>,
+    ConnectInfo(remote): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    body: Body,
+) -> Response {
+    let limits = state.limiter.config();
+    let read = tokio::time::timeout(
+        Duration::from_secs(limits.read_timeout_secs),
+        axum::body::to_bytes(body, limits.max_body_size),
+    ).await;
+
+    let body = match read {
+        Ok(Ok(body)) => body,
+        Ok(Err(_)) => {
+            return error_reply(StatusCode::PAYLOAD_TOO_LARGE, RpcError::invalid_request(format!(
+                "Request body exceeds {} bytes", limits.max_body_size
+            )));
+        }
+        Err(_) => return error_reply(StatusCode::REQUEST_TIMEOUT, RpcError::timeout("Timed out reading request")),
+    };
+
+    let caller = CallerContext {
+        ip: Some(remote.ip()),
+        authorization: headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_string()),
+    };
+
+    // The worker is held until the call returns, even past the request timeout
+    let worker = match state.limiter.reserve_worker() {
+        Ok(worker) => worker,
+        Err(e) => return error_reply(StatusCode::SERVICE_UNAVAILABLE, e),
+    };
+
+    let request_timeout = Duration::from_secs(limits.request_timeout_secs);
+    let max_batch_size = limits.max_batch_size;
+    let task = tokio::task::spawn_blocking(move || {
+        let _worker = worker;
+        jsonrpc::process_body(&body, max_batch_size, |method, params| {
+            dispatch_limited(&state.limiter, &state.auth, &caller, method, params)
+        })
+    });
+
+    // A call that overruns keeps its worker thread until it finishes, but the
+    // client is answered and the connection released
+    match tokio::time::timeout(request_timeout, task).await {
+        Ok(Ok(Some(value))) => (
+            StatusCode::OK,
+            [(header::CONTENT_TYPE, "application/json")],
+            value.to_string(),
+        ).into_response(),
+        Ok(Ok(None)) => StatusCode::NO_CONTENT.into_response(),
+        Ok(Err(e)) => error_reply(StatusCode::INTERNAL_SERVER_ERROR, RpcError::internal(e.to_string())),
+        Err(_) => error_reply(StatusCode::SERVICE_UNAVAILABLE, RpcError::timeout(format!(
+            "Request did not complete within {}s", request_timeout.as_secs()
+        ))),
+    }
+}
 
-        "synergy_transferTokens" => {
-            if let (Some(from), Some(to), Some(token_symbol), Some(amount)) = (
-                params.get(0).and_then(|v| v.as_str()),
-                params.get(1).and_then(|v| v.as_str()),
-                params.get(2).and_then(|v| v.as_str()),
-                params.get(3).and_then(|v| v.as_u64()),
-            ) {
-                let token_manager = TOKEN_MANAGER.clone();
-                match token_manager.transfer_tokens(from, to, token_symbol, amount, 1000) {
-                    Ok(result) => json!({"success": true, "message": result}),
-                    Err(error) => json!({"success": false, "error": error}),
-                }
-            } else {
-                json!({"success": false, "error": "Missing required parameters: from, to, token_symbol, amount"})
-            }
-        }
+async fn handle_metrics() -> Response {
+    match tokio::task::spawn_blocking(metrics::render).await {
+        Ok(body) => (StatusCode::OK, [(header::CONTENT_TYPE, metrics::CONTENT_TYPE)], body).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
 
-        "synergy_getAllBalances" => {
-            if let Some(address) = params.get(0).and_then(|v| v.as_str()) {
-                let token_manager = TOKEN_MANAGER.clone();
-                json!(token_manager.get_all_balances(address))
-            } else {
-                json!("Missing address parameter")
-            }
-        }
+/// Full health report. Answers 503 only when the node is not live, so
+/// monitoring can tell a degraded node from a dead one.
+async fn handle_health(State(state): State<HttpState>) -> Response {
+    health_reply(state.health, |report| report.status != HealthStatus::Unhealthy).await
+}
 
-        "synergy_getTransferHistory" => {
-            if let (Some(address), Some(limit)) = (
-                params.get(0).and_then(|v| v.as_str()),
-                params.get(1).and_then(|v| v.as_u64()).unwrap_or(50),
-            ) {
-                let token_manager = TOKEN_MANAGER.clone();
-                json!(token_manager.get_transfer_history(address, limit as usize))
-            } else {
-                json!("Missing address parameter")
-            }
-        }
+/// 200 once every check passes, for load balancers and orchestrators.
+async fn handle_ready(State(state): State<HttpState>) -> Response {
+    health_reply(state.health, |report| report.ready).await
+}
 
-        // Legacy support
-        "synergy_status" => {
-            json!("ok")
-        }
+async fn handle_live(State(state): State<HttpState>) -> Response {
+    let live = health::is_live(&state.health);
+    let status = if live { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    let body = serde_json::json!({
+        "live": live,
+        "consensus_heartbeat_age_secs": health::consensus_heartbeat_age(),
+    });
+    (status, [(header::CONTENT_TYPE, "application/json")], body.to_string()).into_response()
+}
 
-        _ => {
-            json!("Unknown method")
-        }
+async fn health_reply<F>(config: Arc<HealthConfig>, passed: F) -> Response
+where
+    F: Fn(&health::HealthReport) -> bool,
+{
+    match tokio::task::spawn_blocking(move || health::check(&config)).await {
+        Ok(report) => {
+            let status = if passed(&report) { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+            let body = serde_json::to_string(&report).unwrap_or_default();
+            (status, [(header::CONTENT_TYPE, "application/json")], body).into_response()
+        }
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
     }
 }
 
-fn current_timestamp() -> u64 {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_secs()
+fn error_reply(status: StatusCode, error: RpcError) -> Response {
+    (
+        status,
+        [(header::CONTENT_TYPE, "application/json")],
+        jsonrpc::error_response(Value::Null, error).to_string(),
+    ).into_response()
+}
+
+/// Applies the caller's rate limits and, for expensive methods, the
+/// concurrency cap before `dispatch_as`. Limits are charged before
+/// authentication so they also slow down token guessing.
+pub fn dispatch_limited(
+    limiter: &RpcLimiter,
+    auth: &RpcAuth,
+    caller: &CallerContext,
+    method: &str,
+    params: Value,
+) -> Result<Value, RpcError> {
+    let _permit = limiter.admit(caller.ip, method)?;
+    dispatch_as(auth, caller, method, params)
 }
 
-fn format_response(body: &str) -> String {
-    format!(
-        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
-        body.len(),
-        body
-    )
+/// Checks `caller` against the method's namespace before dispatching it.
+/// Admin and debug calls are audit-logged, whether or not they are allowed.
+pub fn dispatch_as(auth: &RpcAuth, caller: &CallerContext, method: &str, params: Value) -> Result<Value, RpcError> {
+    let namespace = Namespace::of(method);
+    let started = Instant::now();
+    let result = auth
+        .authorize(method, caller)
+        .and_then(|_| dispatch(method, params));
+    metrics::record_rpc_call(method, started.elapsed().as_secs_f64(), result.as_ref().err().map(|e| e.code));
+
+    if namespace.is_privileged() {
+        auth::audit(method, namespace, caller, &result);
+    }
+    result
 }
 
-fn send_error(stream: &mut std::net::TcpStream, msg: &str) {
-    let body = format!("{{\"error\": \"{}\"}}", msg);
-    let response = format_response(&body);
-    let _ = stream.write(response.as_bytes());
+/// Routes a single JSON-RPC call to its handler using the node's shared state,
+/// without any access checks.
+pub fn dispatch(method: &str, params: Value) -> Result<Value, RpcError> {
+    match methods::lookup(method) {
+        Some(info) => (info.invoke)(params),
+        // Ethereum-compatible facade
+        None if method.starts_with("eth_") || method.starts_with("net_") || method.starts_with("web3_") => {
+            eth::handle(method, params, &TX_POOL, &CHAIN)
+        }
+        None => Err(RpcError::method_not_found(method)),
+    }
 }
@@ -0,0 +1,156 @@
+//! Subscription channels and their filters for the WebSocket API.
+//!
+//! A subscription is created from `synergy_subscribe` params of the form
+//! `[channel, filter?]` and decides which `NodeEvent`s are forwarded to the
+//! client and how they are rendered.
+
+use serde_json::{json, Value};
+
+use crate::events::{LogEntry, NodeEvent};
+use super::error::RpcError;
+
+pub const CHANNEL_NEW_HEADS: &str = "newHeads";
+pub const CHANNEL_PENDING_TRANSACTIONS: &str = "pendingTransactions";
+pub const CHANNEL_LOGS: &str = "logs";
+pub const CHANNEL_VALIDATOR_STATUS: &str = "validatorStatus";
+pub const CHANNEL_AI_COMPUTATIONS: &str = "aiComputations";
+
+/// Log filter in the usual address/topics shape. Each topic position holds
+/// either `null` (match anything) or a list of accepted values.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LogFilter {
+    pub addresses: Vec<String>,
+    pub topics: Vec<Option<Vec<String>>>,
+}
+
+impl LogFilter {
+    pub fn from_value(filter: Option<&Value>) -> Result<Self, RpcError> {
+        let filter = match filter {
+            None | Some(Value::Null) => return Ok(LogFilter::default()),
+            Some(Value::Object(filter)) => filter,
+            Some(_) => return Err(RpcError::invalid_params("Log filter must be an object")),
+        };
+
+        let addresses = match filter.get("address") {
+            None | Some(Value::Null) => Vec::new(),
+            Some(value) => string_or_list(value)
+                .ok_or_else(|| RpcError::invalid_params("address must be a string or a list of strings"))?,
+        };
+
+        let topics = match filter.get("topics") {
+            None | Some(Value::Null) => Vec::new(),
+            Some(Value::Array(positions)) => positions
+                .iter()
+                .map(|position| match position {
+                    Value::Null => Ok(None),
+                    value => string_or_list(value)
+                        .map(Some)
+                        .ok_or_else(|| RpcError::invalid_params("topics entries must be null, a string or a list of strings")),
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+            Some(_) => return Err(RpcError::invalid_params("topics must be a list")),
+        };
+
+        Ok(LogFilter { addresses, topics })
+    }
+
+    pub fn matches(&self, log: &LogEntry) -> bool {
+        if !self.addresses.is_empty() && !self.addresses.contains(&log.address) {
+            return false;
+        }
+
+        self.topics.iter().enumerate().all(|(position, accepted)| match accepted {
+            None => true,
+            Some(accepted) => log
+                .topics
+                .get(position)
+                .is_some_and(|topic| accepted.contains(topic)),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SubscriptionKind {
+    NewHeads,
+    PendingTransactions,
+    Logs(LogFilter),
+    ValidatorStatus { address: Option<String> },
+    AIComputations { computation_id: Option<String> },
+}
+
+impl SubscriptionKind {
+    /// Parses `synergy_subscribe` params: `[channel, filter?]`.
+    pub fn from_params(params: &Value) -> Result<Self, RpcError> {
+        let channel = params
+            .get(0)
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| RpcError::invalid_params("Missing subscription channel"))?;
+        let filter = params.get(1);
+
+        match channel {
+            CHANNEL_NEW_HEADS => Ok(SubscriptionKind::NewHeads),
+            CHANNEL_PENDING_TRANSACTIONS => Ok(SubscriptionKind::PendingTransactions),
+            CHANNEL_LOGS => Ok(SubscriptionKind::Logs(LogFilter::from_value(filter)?)),
+            CHANNEL_VALIDATOR_STATUS => Ok(SubscriptionKind::ValidatorStatus {
+                address: optional_field(filter, "address")?,
+            }),
+            CHANNEL_AI_COMPUTATIONS => Ok(SubscriptionKind::AIComputations {
+                computation_id: optional_field(filter, "computationId")?,
+            }),
+            other => Err(RpcError::invalid_params(format!("Unknown subscription channel: {}", other))),
+        }
+    }
+
+    /// Returns the notification payload for `event`, or `None` when this
+    /// subscription is not interested in it.
+    pub fn render(&self, event: &NodeEvent) -> Option<Value> {
+        match (self, event) {
+            (SubscriptionKind::NewHeads, NodeEvent::NewHead(block)) => Some(json!({
+                "number": block.block_index,
+                "hash": block.hash,
+                "parentHash": block.previous_hash,
+                "validator": block.validator_id,
+                "nonce": block.nonce,
+                "transactionCount": block.transactions.len(),
+            })),
+            (SubscriptionKind::PendingTransactions, NodeEvent::PendingTransaction(tx)) => Some(json!({
+                "hash": tx.hash(),
+                "transaction": tx,
+            })),
+            (SubscriptionKind::Logs(filter), NodeEvent::Log(log)) if filter.matches(log) => Some(json!(log)),
+            (SubscriptionKind::ValidatorStatus { address }, NodeEvent::ValidatorStatus(change))
+                if address.as_ref().is_none_or(|a| *a == change.address) => Some(json!(change)),
+            (SubscriptionKind::AIComputations { computation_id }, NodeEvent::ComputationStatus(change))
+                if computation_id.as_ref().is_none_or(|id| *id == change.computation_id) => Some(json!(change)),
+            _ => None,
+        }
+    }
+}
+
+/// Builds the server-to-client notification for a subscription.
+pub fn notification(subscription_id: &str, result: Value) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "synergy_subscription",
+        "params": {
+            "subscription": subscription_id,
+            "result": result
+        }
+    })
+}
+
+fn string_or_list(value: &Value) -> Option<Vec<String>> {
+    match value {
+        Value::String(s) => Some(vec![s.clone()]),
+        Value::Array(items) => items.iter().map(|v| v.as_str().map(str::to_string)).collect(),
+        _ => None,
+    }
+}
+
+fn optional_field(filter: Option<&Value>, field: &str) -> Result<Option<String>, RpcError> {
+    match filter.and_then(|f| f.get(field)) {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::String(value)) => Ok(Some(value.clone())),
+        Some(_) => Err(RpcError::invalid_params(format!("{} must be a string", field))),
+    }
+}
@@ -0,0 +1,277 @@
+//! WebSocket JSON-RPC endpoint with `synergy_subscribe`/`synergy_unsubscribe`.
+//!
+//! Every connection owns a bounded notification queue. Subscriptions push
+//! into it without waiting; a client that stops reading fills the queue and
+//! is disconnected instead of holding back the rest of the node.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::Duration;
+
+use axum::{Router, routing::get};
+use axum::extract::{ConnectInfo, State, WebSocketUpgrade};
+use axum::extract::ws::{close_code, CloseFrame, Message, WebSocket};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use serde_json::{json, Value};
+use tokio::runtime::Handle;
+use tokio::sync::{broadcast, mpsc, Notify};
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::task::JoinHandle;
+
+use crate::config::{RPCConfig, WsLimitsConfig};
+use crate::events::EVENT_BUS;
+use crate::shutdown;
+use super::auth::{CallerContext, RpcAuth};
+use super::error::RpcError;
+use super::jsonrpc;
+use super::limits::RpcLimiter;
+use super::rpc_server;
+use super::subscriptions::{self, SubscriptionKind};
+
+static NEXT_SUBSCRIPTION_ID: AtomicU64 = AtomicU64::new(1);
+
+#[derive(Clone)]
+struct WsState {
+    limits: WsLimitsConfig,
+    connections: Arc<AtomicUsize>,
+    auth: Arc<RpcAuth>,
+    limiter: Arc<RpcLimiter>,
+}
+
+/// Releases a connection slot when the socket (or a failed upgrade) goes away.
+struct ConnectionSlot(Arc<AtomicUsize>);
+
+impl Drop for ConnectionSlot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+pub async fn serve_ws(config: &RPCConfig, auth: Arc<RpcAuth>, limiter: Arc<RpcLimiter>) -> Result<(), Box<dyn std::error::Error>> {
+    let state = WsState {
+        limits: config.ws_limits.clone(),
+        connections: Arc::new(AtomicUsize::new(0)),
+        auth,
+        limiter,
+    };
+
+    let app = Router::new()
+        .route("/", get(handle_upgrade))
+        .with_state(state);
+
+    let address = format!("0.0.0.0:{}", config.ws_port);
+    let listener = tokio::net::TcpListener::bind(&address).await?;
+    crate::info!("rpc", "WebSocket RPC server running", "address" => address.clone());
+
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+        .with_graceful_shutdown(shutdown::wait())
+        .await?;
+    Ok(())
+}
+
+async fn handle_upgrade(
+    ws: WebSocketUpgrade,
+    State(state): State<WsState>,
+    ConnectInfo(remote): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+) -> Response {
+    if state.connections.fetch_add(1, Ordering::SeqCst) >= state.limits.max_connections {
+        state.connections.fetch_sub(1, Ordering::SeqCst);
+        return (StatusCode::SERVICE_UNAVAILABLE, "Too many WebSocket connections").into_response();
+    }
+
+    // Credentials are fixed for the lifetime of the connection
+    let caller = CallerContext {
+        ip: Some(remote.ip()),
+        authorization: headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_string()),
+    };
+
+    let slot = ConnectionSlot(Arc::clone(&state.connections));
+    let limits = state.limits.clone();
+    let auth = Arc::clone(&state.auth);
+    let limiter = Arc::clone(&state.limiter);
+    ws.max_message_size(state.limiter.config().max_body_size)
+        .on_upgrade(move |socket| async move {
+            handle_socket(socket, limits, auth, limiter, caller).await;
+            drop(slot);
+        })
+}
+
+async fn handle_socket(
+    mut socket: WebSocket,
+    limits: WsLimitsConfig,
+    auth: Arc<RpcAuth>,
+    limiter: Arc<RpcLimiter>,
+    caller: CallerContext,
+) {
+    let max_batch_size = limiter.config().max_batch_size;
+    let request_timeout = Duration::from_secs(limiter.config().request_timeout_secs);
+    let (queue, mut notifications) = mpsc::channel::<Value>(limits.send_queue_size.max(1));
+    let slow_consumer = Arc::new(Notify::new());
+    let connection = Arc::new(Mutex::new(Connection {
+        subscriptions: HashMap::new(),
+        max_subscriptions: limits.max_subscriptions_per_connection,
+        queue,
+        slow_consumer: Arc::clone(&slow_consumer),
+        runtime: Handle::current(),
+    }));
+
+    loop {
+        tokio::select! {
+            incoming = socket.recv() => {
+                let body = match incoming {
+                    Some(Ok(Message::Text(text))) => text.as_str().as_bytes().to_vec(),
+                    Some(Ok(Message::Binary(bytes))) => bytes.to_vec(),
+                    Some(Ok(Message::Ping(_))) | Some(Ok(Message::Pong(_))) => continue,
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                };
+
+                // Calls run on a worker under the request timeout, as over HTTP
+                let response = match limiter.reserve_worker() {
+                    Ok(worker) => {
+                        let connection = Arc::clone(&connection);
+                        let (auth, limiter, caller) = (Arc::clone(&auth), Arc::clone(&limiter), caller.clone());
+                        let task = tokio::task::spawn_blocking(move || {
+                            let _worker = worker;
+                            jsonrpc::process_body(&body, max_batch_size, |method, params| match method {
+                                "synergy_subscribe" => connection.lock().unwrap().subscribe(params),
+                                "synergy_unsubscribe" => connection.lock().unwrap().unsubscribe(params),
+                                _ => rpc_server::dispatch_limited(&limiter, &auth, &caller, method, params),
+                            })
+                        });
+                        match tokio::time::timeout(request_timeout, task).await {
+                            Ok(Ok(response)) => response,
+                            Ok(Err(e)) => Some(jsonrpc::error_response(Value::Null, RpcError::internal(e.to_string()))),
+                            Err(_) => Some(jsonrpc::error_response(Value::Null, RpcError::timeout(format!(
+                                "Request did not complete within {}s", request_timeout.as_secs()
+                            )))),
+                        }
+                    }
+                    Err(e) => Some(jsonrpc::error_response(Value::Null, e)),
+                };
+
+                if let Some(response) = response {
+                    if socket.send(Message::Text(response.to_string().into())).await.is_err() {
+                        break;
+                    }
+                }
+            }
+            Some(notification) = notifications.recv() => {
+                if socket.send(Message::Text(notification.to_string().into())).await.is_err() {
+                    break;
+                }
+            }
+            _ = shutdown::wait() => {
+                let _ = socket.send(Message::Close(Some(CloseFrame {
+                    code: close_code::AWAY,
+                    reason: "Node shutting down".into(),
+                }))).await;
+                break;
+            }
+            _ = slow_consumer.notified() => {
+                crate::warn!("rpc", "Dropping slow WebSocket subscriber");
+                let _ = socket.send(Message::Close(Some(CloseFrame {
+                    code: close_code::AGAIN,
+                    reason: "Subscriber too slow, notifications dropped".into(),
+                }))).await;
+                break;
+            }
+        }
+    }
+}
+
+struct Connection {
+    subscriptions: HashMap<String, JoinHandle<()>>,
+    max_subscriptions: usize,
+    queue: mpsc::Sender<Value>,
+    slow_consumer: Arc<Notify>,
+    /// Runtime of the socket; calls run on blocking threads outside it.
+    runtime: Handle,
+}
+
+impl Connection {
+    fn subscribe(&mut self, params: Value) -> Result<Value, RpcError> {
+        if self.subscriptions.len() >= self.max_subscriptions {
+            return Err(RpcError::limit_exceeded(format!(
+                "Subscription limit of {} per connection reached", self.max_subscriptions
+            )));
+        }
+
+        let kind = SubscriptionKind::from_params(&params)?;
+        let subscription_id = format!("0x{:016x}", NEXT_SUBSCRIPTION_ID.fetch_add(1, Ordering::Relaxed));
+
+        let task = self.runtime.spawn(forward_events(
+            subscription_id.clone(),
+            kind,
+            EVENT_BUS.subscribe(),
+            self.queue.clone(),
+            Arc::clone(&self.slow_consumer),
+        ));
+        self.subscriptions.insert(subscription_id.clone(), task);
+
+        Ok(json!(subscription_id))
+    }
+
+    fn unsubscribe(&mut self, params: Value) -> Result<Value, RpcError> {
+        let subscription_id = params
+            .get(0)
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| RpcError::invalid_params("Missing subscription id"))?;
+
+        match self.subscriptions.remove(subscription_id) {
+            Some(task) => {
+                task.abort();
+                Ok(json!(true))
+            }
+            None => Ok(json!(false)),
+        }
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        for (_, task) in self.subscriptions.drain() {
+            task.abort();
+        }
+    }
+}
+
+async fn forward_events(
+    subscription_id: String,
+    kind: SubscriptionKind,
+    mut events: broadcast::Receiver<crate::events::NodeEvent>,
+    queue: mpsc::Sender<Value>,
+    slow_consumer: Arc<Notify>,
+) {
+    loop {
+        match events.recv().await {
+            Ok(event) => {
+                let result = match kind.render(&event) {
+                    Some(result) => result,
+                    None => continue,
+                };
+
+                match queue.try_send(subscriptions::notification(&subscription_id, result)) {
+                    Ok(()) => {}
+                    Err(TrySendError::Full(_)) => {
+                        slow_consumer.notify_one();
+                        return;
+                    }
+                    Err(TrySendError::Closed(_)) => return,
+                }
+            }
+            // Missed events on the bus mean this connection cannot keep up either
+            Err(broadcast::error::RecvError::Lagged(_)) => {
+                slow_consumer.notify_one();
+                return;
+            }
+            Err(broadcast::error::RecvError::Closed) => return,
+        }
+    }
+}
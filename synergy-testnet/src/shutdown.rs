@@ -0,0 +1,69 @@
+//! Node-wide shutdown signal.
+//!
+//! SIGINT or SIGTERM requests a shutdown. The consensus loop checks for it
+//! between blocks and then flushes its state, after which the node closes
+//! its peer connections; the RPC servers stop accepting connections and
+//! finish the requests already in flight. A second signal exits
+//! immediately.
+
+use lazy_static::lazy_static;
+use tokio::sync::watch;
+
+lazy_static! {
+    static ref SHUTDOWN: watch::Sender<bool> = watch::channel(false).0;
+}
+
+/// Asks every subsystem to stop. Safe to call more than once.
+pub fn request() {
+    if !SHUTDOWN.send_replace(true) {
+        crate::info!("shutdown", "Shutdown requested, finishing current work");
+    }
+}
+
+pub fn is_requested() -> bool {
+    *SHUTDOWN.borrow()
+}
+
+/// Resolves once a shutdown has been requested.
+pub async fn wait() {
+    let mut receiver = SHUTDOWN.subscribe();
+    let _ = receiver.wait_for(|requested| *requested).await;
+}
+
+/// Listens for SIGINT and SIGTERM on a background thread.
+pub fn install_signal_handler() {
+    std::thread::Builder::new()
+        .name("synergy-signals".to_string())
+        .spawn(|| {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("Failed to create signal runtime");
+
+            runtime.block_on(async {
+                next_signal().await;
+                request();
+
+                next_signal().await;
+                crate::warn!("shutdown", "Second signal received, exiting without cleanup");
+                std::process::exit(130);
+            });
+        })
+        .expect("Failed to spawn signal handler");
+}
+
+#[cfg(unix)]
+async fn next_signal() {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut terminate = signal(SignalKind::terminate()).expect("Failed to listen for SIGTERM");
+    tokio::select! {
+        _ = tokio::signal::ctrl_c() => {}
+        _ = terminate.recv() => {}
+    }
+}
+
+#[cfg(not(unix))]
+async fn next_signal() {
+    let _ = tokio::signal::ctrl_c().await;
+}
@@ -0,0 +1,96 @@
+//! Crash-safe persistence for the node's JSON state files.
+//!
+//! Files are never rewritten in place: the new contents go to a temporary
+//! file next to the target, which is synced and then renamed over it. A
+//! crash at any point leaves either the old file or the new one.
+
+use std::error::Error;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+
+/// Atomically replaces `path` with `contents`, creating parent directories
+/// as needed.
+pub fn write_atomic(path: impl AsRef<Path>, contents: &[u8]) -> io::Result<()> {
+    replace_file(path.as_ref(), contents, false)
+}
+
+/// Like `write_atomic`, but the file is created readable only by its owner.
+/// Used for key material.
+pub fn write_private_atomic(path: impl AsRef<Path>, contents: &[u8]) -> io::Result<()> {
+    replace_file(path.as_ref(), contents, true)
+}
+
+fn replace_file(path: &Path, contents: &[u8], private: bool) -> io::Result<()> {
+    let parent = parent_dir(path);
+    fs::create_dir_all(&parent)?;
+
+    let temp = temp_path(path);
+    {
+        let mut options = OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        if private {
+            // A leftover temp file would keep its old permissions
+            let _ = fs::remove_file(&temp);
+            #[cfg(unix)]
+            std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        }
+        let mut file = options.open(&temp)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+    }
+    fs::rename(&temp, path)?;
+
+    // Make the rename itself durable. Directories cannot be opened for
+    // syncing on every platform, so this is best effort.
+    if let Ok(dir) = File::open(&parent) {
+        let _ = dir.sync_all();
+    }
+    Ok(())
+}
+
+/// Serializes `value` as pretty JSON and writes it with `write_atomic`.
+pub fn write_json_atomic<T: Serialize>(path: impl AsRef<Path>, value: &T) -> Result<(), Box<dyn Error>> {
+    let json = serde_json::to_string_pretty(value)?;
+    write_atomic(path, json.as_bytes())?;
+    Ok(())
+}
+
+/// Moves an unreadable state file aside as `<name>.corrupt-<unix time>`, so
+/// the node can start over without destroying it.
+pub fn quarantine(path: impl AsRef<Path>) -> io::Result<PathBuf> {
+    let path = path.as_ref();
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let target = path.with_file_name(format!("{}.corrupt-{}", file_name(path), timestamp));
+    fs::rename(path, &target)?;
+    Ok(target)
+}
+
+/// Deletes the temporary file an interrupted `write_atomic` may have left
+/// next to `path`. Returns whether there was one.
+pub fn remove_stale_temp(path: impl AsRef<Path>) -> bool {
+    fs::remove_file(temp_path(path.as_ref())).is_ok()
+}
+
+fn temp_path(path: &Path) -> PathBuf {
+    path.with_file_name(format!("{}.tmp", file_name(path)))
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+fn parent_dir(path: &Path) -> PathBuf {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    }
+}
@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 use serde::{Deserialize, Serialize};
-use crate::crypto::pqc::{PQCManager, PQCAlgorithm};
+use crate::crypto::pqc::{PQCSignature, SignatureAlgorithm, Signer};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SynQContract {
@@ -9,7 +9,7 @@ pub struct SynQContract {
     pub code: String,
     pub bytecode: Vec<u8>,
     pub abi: String,
-    pub pqc_algorithm: PQCAlgorithm,
+    pub pqc_algorithm: SignatureAlgorithm,
     pub cross_chain_enabled: bool,
     pub created_at: u64,
     pub author: String,
@@ -23,7 +23,7 @@ pub struct CompilationResult {
     pub synq_code: String,
     pub warnings: Vec<String>,
     pub errors: Vec<String>,
-    pub pqc_signatures: Vec<String>,
+    pub pqc_signatures: Vec<PQCSignature>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -64,7 +64,6 @@ pub struct SynQVariable {
 
 #[derive(Debug)]
 pub struct SynQCompiler {
-    pqc_manager: PQCManager,
     contracts: HashMap<String, SynQContract>,
     compiled_results: HashMap<String, CompilationResult>,
 }
@@ -72,7 +71,6 @@ pub struct SynQCompiler {
 impl SynQCompiler {
     pub fn new() -> Self {
         SynQCompiler {
-            pqc_manager: PQCManager::new(),
             contracts: HashMap::new(),
             compiled_results: HashMap::new(),
         }
@@ -114,7 +112,7 @@ impl SynQCompiler {
             code: code.to_string(),
             bytecode: vec![], // Will be generated
             abi: self.generate_abi(code)?,
-            pqc_algorithm: PQCAlgorithm::Dilithium, // Default PQC algorithm
+            pqc_algorithm: SignatureAlgorithm::MlDsa87, // Default PQC algorithm
             cross_chain_enabled: true,
             created_at: std::time::SystemTime::now()
                 .duration_since(std::time::UNIX_EPOCH)
@@ -135,14 +133,9 @@ impl SynQCompiler {
         // Add PQC signature header
         bytecode.extend_from_slice(&[0x53, 0x79, 0x6E, 0x51]); // "SynQ" magic bytes
 
-        // Add PQC algorithm identifier
-        match contract.pqc_algorithm {
-            PQCAlgorithm::Dilithium => bytecode.push(0x01),
-            PQCAlgorithm::Kyber => bytecode.push(0x02),
-            PQCAlgorithm::Falcon => bytecode.push(0x03),
-            PQCAlgorithm::Sphincs => bytecode.push(0x04),
-            PQCAlgorithm::ClassicMcEliece => bytecode.push(0x05),
-        }
+        // Add PQC parameter set identifier: its position in SignatureAlgorithm::ALL, from 1
+        let algorithm_id = SignatureAlgorithm::ALL.iter().position(|a| *a == contract.pqc_algorithm).unwrap_or(0) + 1;
+        bytecode.push(algorithm_id as u8);
 
         // Add contract metadata
         bytecode.extend_from_slice(&(contract.name.len() as u32).to_le_bytes());
@@ -161,7 +154,7 @@ impl SynQCompiler {
             r#"// Auto-generated Solidity contract from SynQ with PQC integration
 // Original SynQ contract: {}
 // Generated at: {}
-// PQC Algorithm: {:?}
+// PQC Algorithm: {}
 
 pragma solidity ^0.8.0;
 
@@ -173,7 +166,7 @@ contract {} {{
     bytes32 public pqcPublicKeyHash;
 
     constructor() {{
-        pqcAlgorithm = "{:?}";
+        pqcAlgorithm = "{}";
         synergyContract = address(this);
         // Initialize PQC key hash (would be set during deployment)
         pqcPublicKeyHash = bytes32(0);
@@ -219,32 +212,30 @@ contract {} {{
         // In a real implementation, this would parse the SynQ AST
 
         let abi = format!(
-            r#"[{{ "name": "{}", "type": "contract", "version": "1.0.0", "pqc_algorithm": "{:?}" }}]"#,
-            "SynQContract", PQCAlgorithm::Dilithium
+            r#"[{{ "name": "{}", "type": "contract", "version": "1.0.0", "pqc_algorithm": "{}" }}]"#,
+            "SynQContract", SignatureAlgorithm::MlDsa87
         );
 
         Ok(abi)
     }
 
-    fn generate_pqc_signatures(&self, contract: &SynQContract) -> Result<Vec<String>, String> {
+    fn generate_pqc_signatures(&self, contract: &SynQContract) -> Result<Vec<PQCSignature>, String> {
         let mut signatures = Vec::new();
 
         // Generate signatures for different PQC algorithms
-        for algorithm in self.pqc_manager.get_supported_algorithms() {
-            let (public_key, private_key) = self.pqc_manager.generate_keypair(algorithm.clone())?;
+        for algorithm in [contract.pqc_algorithm, SignatureAlgorithm::Falcon1024, SignatureAlgorithm::SlhDsaSha2_256f] {
+            let (public_key, private_key) = algorithm.generate_keypair();
 
             // Sign the contract bytecode
-            let signature = self.pqc_manager.sign_message(&private_key.public_key_id, &contract.bytecode)?;
-
-            signatures.push(format!("{:?}_{}", algorithm, signature.public_key_id));
+            signatures.push(PQCSignature::sign(algorithm, &public_key, &private_key, &contract.bytecode)?);
         }
 
         Ok(signatures)
     }
 
-    pub fn verify_contract_signature(&self, contract_hash: &str, signature_id: &str) -> Result<bool, String> {
+    pub fn verify_contract_signature(&self, contract_hash: &str, signature: &PQCSignature) -> Result<bool, String> {
         // Verify that the contract signature is valid
-        self.pqc_manager.verify_signature(signature_id, contract_hash.as_bytes())
+        Ok(signature.verify(contract_hash.as_bytes()))
     }
 
     pub fn get_contract_info(&self, contract_name: &str) -> Option<&SynQContract> {
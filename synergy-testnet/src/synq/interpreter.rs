@@ -1,6 +1,5 @@
 use std::collections::HashMap;
 use serde::{Deserialize, Serialize};
-use crate::crypto::pqc::{PQCManager, PQCAlgorithm};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SynQExecutionContext {
@@ -32,15 +31,11 @@ pub enum SecurityLevel {
 }
 
 #[derive(Debug)]
-pub struct SynQInterpreter {
-    pqc_manager: crate::crypto::pqc::PQCManager,
-}
+pub struct SynQInterpreter;
 
 impl SynQInterpreter {
     pub fn new() -> Self {
-        SynQInterpreter {
-            pqc_manager: crate::crypto::pqc::PQCManager::new(),
-        }
+        SynQInterpreter
     }
 
     pub fn execute_contract(
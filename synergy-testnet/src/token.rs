@@ -1,11 +1,14 @@
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use sha3::{Sha3_256, Digest};
 use hex;
+use crate::genesis::Genesis;
+use crate::storage;
 use crate::transaction::Transaction;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Token {
     pub symbol: String,
     pub name: String,
@@ -28,7 +31,7 @@ pub struct TokenBalance {
     pub last_updated: u64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct TokenTransfer {
     pub from: String,
     pub to: String,
@@ -40,7 +43,7 @@ pub struct TokenTransfer {
     pub block_height: u64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct StakingInfo {
     pub validator_address: String,
     pub staker_address: String,
@@ -63,6 +66,7 @@ pub struct TokenManager {
 }
 
 impl Token {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         symbol: String,
         name: String,
@@ -102,9 +106,15 @@ impl Token {
     }
 }
 
+impl Default for TokenManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl TokenManager {
     pub fn new() -> Self {
-        let mut manager = TokenManager {
+        TokenManager {
             tokens: Arc::new(Mutex::new(HashMap::new())),
             balances: Arc::new(Mutex::new(HashMap::new())),
             locked_balances: Arc::new(Mutex::new(HashMap::new())),
@@ -112,49 +122,49 @@ impl TokenManager {
             transfers: Arc::new(Mutex::new(Vec::new())),
             stakes: Arc::new(Mutex::new(HashMap::new())),
             total_supply: Arc::new(Mutex::new(HashMap::new())),
-        };
-
-        // Initialize with SNRG token
-        manager.initialize_snrg_token();
-        manager
+        }
     }
 
-    fn initialize_snrg_token(&mut self) {
-        let snrg_token = Token::new(
-            "SNRG".to_string(),
-            "SynergyCoin".to_string(),
-            9, // 9 decimals for better usability
-            12_000_000_000 * 10u64.pow(9), // 12 billion SNRG with 9 decimals
-            Some(21_000_000_000 * 10u64.pow(9)), // 21 billion max supply
-            true, // mintable
-            true, // burnable
-            "genesis".to_string(),
-        );
-
-        if let Ok(mut tokens) = self.tokens.lock() {
-            tokens.insert("SNRG".to_string(), snrg_token.clone());
+    /// Registers the genesis tokens and mints the genesis allocations.
+    /// Fails if any of the tokens already exists.
+    pub fn apply_genesis(&self, genesis: &Genesis) -> Result<(), String> {
+        {
+            let mut tokens = self.tokens.lock().map_err(|_| "Failed to acquire lock".to_string())?;
+            if let Some(token) = genesis.tokens.iter().find(|token| tokens.contains_key(&token.symbol)) {
+                return Err(format!("Token {} already exists", token.symbol));
+            }
+            for token in &genesis.tokens {
+                let registered = Token::new(
+                    token.symbol.clone(),
+                    token.name.clone(),
+                    token.decimals,
+                    genesis.allocated_supply(&token.symbol),
+                    token.max_supply,
+                    token.mintable,
+                    token.burnable,
+                    "genesis".to_string(),
+                );
+                tokens.insert(token.symbol.clone(), registered);
+            }
         }
 
         if let Ok(mut supply) = self.total_supply.lock() {
-            supply.insert("SNRG".to_string(), snrg_token.total_supply);
+            for token in &genesis.tokens {
+                supply.insert(token.symbol.clone(), genesis.allocated_supply(&token.symbol));
+            }
         }
-
-        // Distribute initial supply to genesis accounts
-        self.distribute_genesis_supply();
-    }
-
-    fn distribute_genesis_supply(&self) {
-        let genesis_allocations = [
-            ("sYnQ1zxy8qhj4j59xp5lwkwpd5qws9aygz8pl9m3kmjx3", 6_000_000_000 * 10u64.pow(9)), // 6B SNRG
-            ("sYnQ1ffzcyq7l0sw7v9fhrx2wdvxxzv9q5mj3ehd6yl3e", 3_000_000_000 * 10u64.pow(9)), // 3B SNRG
-            ("sYnU1v3smghwdd2zj7vpgkx0fn3cf0k57eq7hqufup0tp", 3_000_000_000 * 10u64.pow(9)), // 3B SNRG
-        ];
-
-        for (address, amount) in genesis_allocations {
-            self.mint_tokens(address, "SNRG", amount);
+        if let Ok(mut balances) = self.balances.lock() {
+            for (address, allocations) in &genesis.alloc {
+                let address_balances = balances.entry(address.clone()).or_insert_with(HashMap::new);
+                for (symbol, amount) in allocations {
+                    *address_balances.entry(symbol.clone()).or_insert(0) += amount;
+                }
+            }
         }
+        Ok(())
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn create_token(
         &self,
         symbol: String,
@@ -188,8 +198,10 @@ impl TokenManager {
                 supply.insert(symbol.clone(), total_supply);
             }
 
-            // Mint initial supply to creator
-            self.mint_tokens(&creator, &symbol, total_supply);
+            // Credit initial supply to creator; minting here would take the tokens lock again
+            if let Ok(mut balances) = self.balances.lock() {
+                *balances.entry(creator).or_default().entry(symbol.clone()).or_insert(0) += total_supply;
+            }
 
             Ok(format!("Token {} created successfully", symbol))
         } else {
@@ -198,7 +210,7 @@ impl TokenManager {
     }
 
     pub fn mint_tokens(&self, to: &str, token_symbol: &str, amount: u64) -> Result<String, String> {
-        if let Ok(mut tokens) = self.tokens.lock() {
+        if let Ok(tokens) = self.tokens.lock() {
             if let Some(token) = tokens.get(token_symbol) {
                 if !token.mintable {
                     return Err("Token is not mintable".to_string());
@@ -215,7 +227,7 @@ impl TokenManager {
 
                 // Update total supply
                 if let Ok(mut supply) = self.total_supply.lock() {
-                    let current = supply.get(token_symbol).unwrap_or(&0);
+                    let current = *supply.get(token_symbol).unwrap_or(&0);
                     supply.insert(token_symbol.to_string(), current + amount);
                 }
 
@@ -236,7 +248,7 @@ impl TokenManager {
     }
 
     pub fn burn_tokens(&self, from: &str, token_symbol: &str, amount: u64) -> Result<String, String> {
-        if let Ok(mut tokens) = self.tokens.lock() {
+        if let Ok(tokens) = self.tokens.lock() {
             if let Some(token) = tokens.get(token_symbol) {
                 if !token.burnable {
                     return Err("Token is not burnable".to_string());
@@ -258,7 +270,7 @@ impl TokenManager {
 
                 // Update total supply
                 if let Ok(mut supply) = self.total_supply.lock() {
-                    let current = supply.get(token_symbol).unwrap_or(&0);
+                    let current = *supply.get(token_symbol).unwrap_or(&0);
                     supply.insert(token_symbol.to_string(), current - amount);
                 }
 
@@ -323,12 +335,32 @@ impl TokenManager {
     pub fn get_balance(&self, address: &str, token_symbol: &str) -> u64 {
         if let Ok(balances) = self.balances.lock() {
             if let Some(address_balances) = balances.get(address) {
-                return address_balances.get(token_symbol).unwrap_or(&0).clone();
+                return *address_balances.get(token_symbol).unwrap_or(&0);
             }
         }
         0
     }
 
+    /// Number of addresses holding a non-zero balance of the token.
+    pub fn holder_count(&self, token_symbol: &str) -> usize {
+        if let Ok(balances) = self.balances.lock() {
+            balances
+                .values()
+                .filter(|address_balances| address_balances.get(token_symbol).is_some_and(|balance| *balance > 0))
+                .count()
+        } else {
+            0
+        }
+    }
+
+    pub fn total_supply(&self, token_symbol: &str) -> u64 {
+        if let Ok(supply) = self.total_supply.lock() {
+            *supply.get(token_symbol).unwrap_or(&0)
+        } else {
+            0
+        }
+    }
+
     pub fn get_all_balances(&self, address: &str) -> HashMap<String, u64> {
         if let Ok(balances) = self.balances.lock() {
             balances.get(address).cloned().unwrap_or_default()
@@ -399,14 +431,12 @@ impl TokenManager {
         if let Ok(mut stakes) = self.stakes.lock() {
             if let Some(validator_stakes) = stakes.get_mut(validator) {
                 for stake in validator_stakes.iter_mut() {
-                    if stake.staker_address == staker && stake.is_active {
-                        if stake.amount >= amount {
-                            stake.amount -= amount;
-                            if stake.amount == 0 {
-                                stake.is_active = false;
-                            }
-                            break;
+                    if stake.staker_address == staker && stake.is_active && stake.amount >= amount {
+                        stake.amount -= amount;
+                        if stake.amount == 0 {
+                            stake.is_active = false;
                         }
+                        break;
                     }
                 }
             }
@@ -433,7 +463,7 @@ impl TokenManager {
     pub fn get_staked_balance(&self, address: &str, token_symbol: &str) -> u64 {
         if let Ok(staked) = self.staked_balances.lock() {
             if let Some(address_staked) = staked.get(address) {
-                return address_staked.get(token_symbol).unwrap_or(&0).clone();
+                return *address_staked.get(token_symbol).unwrap_or(&0);
             }
         }
         0
@@ -455,7 +485,7 @@ impl TokenManager {
                     return Ok("No staked tokens".to_string());
                 }
 
-                for stake in active_stakes {
+                for stake in &active_stakes {
                     let reward_portion = (stake.amount * reward_amount) / total_staked;
 
                     // Add rewards to staker's balance
@@ -488,7 +518,7 @@ impl TokenManager {
 
     pub fn process_transaction(&self, tx: &Transaction) -> Result<String, String> {
         // Handle token transfers
-        if tx.data.as_ref().map_or(false, |data| data.starts_with("token_transfer:")) {
+        if tx.data.as_ref().is_some_and(|data| data.starts_with("token_transfer:")) {
             if let Some(data_str) = &tx.data {
                 if let Some(transfer_data) = data_str.strip_prefix("token_transfer:") {
                     if let Ok(transfer_info) = serde_json::from_str::<serde_json::Value>(transfer_data) {
@@ -505,7 +535,7 @@ impl TokenManager {
         }
 
         // Handle staking transactions
-        if tx.data.as_ref().map_or(false, |data| data.starts_with("stake:")) {
+        if tx.data.as_ref().is_some_and(|data| data.starts_with("stake:")) {
             if let Some(data_str) = &tx.data {
                 if let Some(stake_data) = data_str.strip_prefix("stake:") {
                     if let Ok(stake_info) = serde_json::from_str::<serde_json::Value>(stake_data) {
@@ -569,9 +599,9 @@ impl TokenManager {
         hasher.update(from.as_bytes());
         hasher.update(to.as_bytes());
         hasher.update(token.as_bytes());
-        hasher.update(&amount.to_le_bytes());
-        hasher.update(&fee.to_le_bytes());
-        hasher.update(&Token::current_timestamp().to_le_bytes());
+        hasher.update(amount.to_le_bytes());
+        hasher.update(fee.to_le_bytes());
+        hasher.update(Token::current_timestamp().to_le_bytes());
         hex::encode(hasher.finalize())
     }
 
@@ -583,9 +613,28 @@ impl TokenManager {
             stakes: self.stakes.lock().unwrap().clone(),
         };
 
-        let json = serde_json::to_string_pretty(&state)?;
-        std::fs::write(path, json)?;
-        Ok(())
+        storage::write_json_atomic(path, &state)
+    }
+
+    pub fn snapshot(&self) -> TokenSnapshot {
+        TokenSnapshot {
+            balances: self.balances.lock().unwrap().clone(),
+            locked_balances: self.locked_balances.lock().unwrap().clone(),
+            staked_balances: self.staked_balances.lock().unwrap().clone(),
+            transfers: self.transfers.lock().unwrap().clone(),
+            stakes: self.stakes.lock().unwrap().clone(),
+            total_supply: self.total_supply.lock().unwrap().clone(),
+        }
+    }
+
+    /// Puts back the state recorded by `snapshot`.
+    pub fn restore(&self, snapshot: TokenSnapshot) {
+        *self.balances.lock().unwrap() = snapshot.balances;
+        *self.locked_balances.lock().unwrap() = snapshot.locked_balances;
+        *self.staked_balances.lock().unwrap() = snapshot.staked_balances;
+        *self.transfers.lock().unwrap() = snapshot.transfers;
+        *self.stakes.lock().unwrap() = snapshot.stakes;
+        *self.total_supply.lock().unwrap() = snapshot.total_supply;
     }
 
     pub fn load_state(&self, path: &str) -> Result<(), Box<dyn std::error::Error>> {
@@ -616,6 +665,17 @@ impl TokenManager {
     }
 }
 
+/// Copy of every balance, stake and transfer, taken before a block is
+/// applied so a block that fails halfway can be undone.
+pub struct TokenSnapshot {
+    balances: HashMap<String, HashMap<String, u64>>,
+    locked_balances: HashMap<String, HashMap<String, u64>>,
+    staked_balances: HashMap<String, HashMap<String, u64>>,
+    transfers: Vec<TokenTransfer>,
+    stakes: HashMap<String, Vec<StakingInfo>>,
+    total_supply: HashMap<String, u64>,
+}
+
 #[derive(Serialize, Deserialize)]
 struct TokenState {
     tokens: Vec<Token>,
@@ -1,18 +1,38 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use blake3::Hasher;
 use bincode::{encode_to_vec, decode_from_slice};
 use bincode::config::standard;
 use bincode::{Decode, Encode};
-use sha3::{Sha3_256, Digest};
 use hex;
+use rayon::prelude::*;
 
-#[derive(Debug, Clone, Serialize, Deserialize, Encode, Decode)]
+use crate::crypto::hybrid::{self, HybridSignature};
+use crate::crypto::keys::KeyPair;
+use crate::crypto::multisig::{MultisigAuth, MultisigPolicy};
+use crate::crypto::pqc::{SignatureAlgorithm, Signer, VerifyRequest, SIGNATURE_CACHE};
+use crate::key_rotation::{KeyRegistry, RotateKey, KEY_REGISTRY};
+use crate::name_service::NameOp;
+use crate::wallet::WalletManager;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Encode, Decode, JsonSchema)]
 pub struct Transaction {
     pub sender: String,
     pub receiver: String,
     pub amount: u64,
     pub nonce: u64,
     pub signature: String,
+    /// Hex ML-DSA-87 public key of the sender; empty until signed.
+    #[serde(default)]
+    pub public_key: String,
+    /// Policy and signatures of a multisig sender, which leaves
+    /// `signature` and `public_key` empty; `None` for single-key senders.
+    #[serde(default)]
+    pub multisig: Option<MultisigAuth>,
+    /// secp256k1 half of a hybrid sender's signature, next to the ML-DSA
+    /// half in `signature`; `None` for other senders.
+    #[serde(default)]
+    pub hybrid: Option<HybridSignature>,
     pub timestamp: u64,
     pub gas_price: u64,
     pub gas_limit: u64,
@@ -26,6 +46,7 @@ pub struct TransactionValidationResult {
 }
 
 impl Transaction {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         sender: String,
         receiver: String,
@@ -42,6 +63,9 @@ impl Transaction {
             amount,
             nonce,
             signature,
+            public_key: String::new(),
+            multisig: None,
+            hybrid: None,
             timestamp: std::time::SystemTime::now()
                 .duration_since(std::time::UNIX_EPOCH)
                 .unwrap()
@@ -66,11 +90,98 @@ impl Transaction {
             hasher.update(data.as_bytes());
         }
 
-        // Note: signature is NOT included in the hash for verification
+        // Note: signature, public key, multisig and hybrid are NOT included
+        // in the hash; keys and policies are bound through the sender address
         hasher.finalize().to_hex().to_string()
     }
 
+    /// `data` of a token transfer, as `TokenManager::process_transaction` reads it.
+    pub fn token_transfer_data(to: &str, token_symbol: &str, amount: u64) -> String {
+        format!("token_transfer:{{\"to\":\"{}\",\"token\":\"{}\",\"amount\":{}}}", to, token_symbol, amount)
+    }
+
+    /// `data` of a staking transaction, as `TokenManager::process_transaction` reads it.
+    pub fn stake_data(validator: &str, token_symbol: &str, amount: u64) -> String {
+        format!("stake:{{\"validator\":\"{}\",\"token\":\"{}\",\"amount\":{}}}", validator, token_symbol, amount)
+    }
+
+    /// Signs the transaction as the owner of `keypair`: sets the sender,
+    /// public key and an ML-DSA signature over `hash()`, and for a hybrid
+    /// key pair also the secp256k1 signature.
+    pub fn sign(&mut self, keypair: &KeyPair) {
+        self.sender = keypair.address();
+        self.public_key = keypair.public_key_hex();
+        let hash = self.hash();
+        self.signature = hex::encode(keypair.sign(hash.as_bytes()));
+        self.hybrid = keypair.secp256k1_key().map(|key| HybridSignature {
+            public_key: hybrid::public_key_hex(key),
+            signature: hybrid::sign(key, &hex::decode(&hash).expect("hex transaction hash")),
+        });
+    }
+
+    /// Signs the transaction for its current sender with a key bound to it
+    /// by a key rotation, which may be of any signature algorithm.
+    pub fn sign_with(&mut self, algorithm: SignatureAlgorithm, public_key: &[u8], private_key: &[u8]) -> Result<(), String> {
+        self.public_key = hex::encode(public_key);
+        self.hybrid = None;
+        self.signature = hex::encode(algorithm.sign(private_key, self.hash().as_bytes())?);
+        Ok(())
+    }
+
+    /// Whether the transaction rotates a key of its sender (`crate::key_rotation`).
+    pub fn is_key_rotation(&self) -> bool {
+        RotateKey::from_transaction(self).is_some()
+    }
+
+    /// Whether the transaction is a name service operation (`crate::name_service`).
+    pub fn is_name_operation(&self) -> bool {
+        NameOp::from_transaction(self).is_some()
+    }
+
+    /// Makes the transaction a spend from the multisig account of `policy`,
+    /// with every signature slot empty.
+    pub fn set_multisig(&mut self, policy: MultisigPolicy) {
+        self.sender = policy.address();
+        self.public_key.clear();
+        self.signature.clear();
+        self.hybrid = None;
+        self.multisig = Some(MultisigAuth::new(policy));
+    }
+
+    /// Adds the signature of `keypair`, one of the signers of the multisig
+    /// sender.
+    pub fn sign_multisig(&mut self, keypair: &KeyPair) -> Result<(), String> {
+        let hash = self.hash();
+        match self.multisig.as_mut() {
+            Some(auth) => auth.sign(keypair, hash.as_bytes()),
+            None => Err("Transaction is not from a multisig account".to_string()),
+        }
+    }
+
+    pub fn is_signed(&self) -> bool {
+        !self.signature.is_empty() || self.multisig.as_ref().is_some_and(|auth| auth.signed_count() > 0)
+    }
+
     pub fn validate(&self) -> TransactionValidationResult {
+        let result = self.validate_fields();
+        if !result.is_valid {
+            return result;
+        }
+
+        // Signature validation
+        if let Err(e) = self.check_signature() {
+            return TransactionValidationResult {
+                is_valid: false,
+                error_message: Some(e),
+            };
+        }
+
+        result
+    }
+
+    /// `validate` without the signature check, for transactions that are
+    /// still to be signed.
+    pub fn validate_fields(&self) -> TransactionValidationResult {
         // Basic field validation
         if self.sender.is_empty() {
             return TransactionValidationResult {
@@ -86,7 +197,7 @@ impl Transaction {
             };
         }
 
-        if self.amount == 0 {
+        if self.amount == 0 && !self.is_key_rotation() && !self.is_name_operation() {
             return TransactionValidationResult {
                 is_valid: false,
                 error_message: Some("Transaction amount must be greater than 0".to_string()),
@@ -114,82 +225,99 @@ impl Transaction {
             };
         }
 
-        // Address format validation (Bech32m format)
-        if !self.is_valid_address(&self.sender) {
+        // Address format validation
+        if !Self::is_valid_address(&self.sender) {
             return TransactionValidationResult {
                 is_valid: false,
                 error_message: Some("Invalid sender address format".to_string()),
             };
         }
 
-        if !self.is_valid_address(&self.receiver) {
+        if !Self::is_valid_address(&self.receiver) {
             return TransactionValidationResult {
                 is_valid: false,
                 error_message: Some("Invalid receiver address format".to_string()),
             };
         }
 
-        // Signature validation
-        if !self.verify_signature() {
-            return TransactionValidationResult {
-                is_valid: false,
-                error_message: Some("Invalid transaction signature".to_string()),
-            };
-        }
-
         TransactionValidationResult {
             is_valid: true,
             error_message: None,
         }
     }
 
+    /// Whether the signature is a valid ML-DSA signature over `hash()` by
+    /// the public key, and the public key belongs to the sender. A multisig
+    /// sender needs `threshold` valid signatures from its policy instead, and
+    /// a hybrid sender a valid secp256k1 signature as well. A sender that
+    /// rotated its key must sign with the key now bound to it.
     pub fn verify_signature(&self) -> bool {
-        // For now, we'll implement a basic signature verification
-        // In production, this would verify Dilithium-3 signatures
-
-        if self.signature.is_empty() {
-            return false;
-        }
-
-        // Basic check: signature should be at least 64 characters
-        if self.signature.len() < 64 {
-            return false;
-        }
-
-        // Verify signature against transaction hash
-        let message_hash = self.hash();
-        let signature_hash = self.generate_signature_hash();
-
-        // This is a simplified verification - in production you'd use proper crypto
-        message_hash.starts_with(&signature_hash[..8])
+        self.check_signature().is_ok()
     }
 
-    fn generate_signature_hash(&self) -> String {
-        let mut hasher = Sha3_256::new();
-        hasher.update(self.sender.as_bytes());
-        hasher.update(&self.nonce.to_le_bytes());
-        hex::encode(hasher.finalize())
+    /// `verify_signature` with the reason a signature is rejected, against
+    /// the keys in force for the next block.
+    pub fn check_signature(&self) -> Result<(), String> {
+        let keys = KEY_REGISTRY.lock().unwrap();
+        self.check_signature_at(&keys, keys.height() + 1)
     }
 
-    fn is_valid_address(&self, address: &str) -> bool {
-        // Basic Bech32m validation for Synergy addresses
-        if address.len() != 41 {
-            return false;
+    /// `check_signature` against the keys in force at block `height`.
+    pub fn check_signature_at(&self, keys: &KeyRegistry, height: u64) -> Result<(), String> {
+        if let Some(auth) = &self.multisig {
+            if !self.public_key.is_empty() || !self.signature.is_empty() || self.hybrid.is_some() {
+                return Err("Multisig transactions carry their signatures in multisig".to_string());
+            }
+            if auth.policy.address() != self.sender {
+                return Err("Multisig policy does not belong to the sender".to_string());
+            }
+            return auth.verify(self.hash().as_bytes());
         }
 
-        if !address.starts_with("sYn") {
-            return false;
+        let (Ok(public_key), Ok(signature)) = (hex::decode(&self.public_key), hex::decode(&self.signature)) else {
+            return Err("Invalid transaction signature".to_string());
+        };
+        let algorithm = match keys.account_key(&self.sender, height) {
+            Some(rotation) => {
+                if self.hybrid.is_some() || !self.public_key.eq_ignore_ascii_case(&rotation.public_key) {
+                    return Err(format!(
+                        "Sender's key was rotated at block {}; sign with the {} key bound to it",
+                        rotation.effective_height, rotation.algorithm
+                    ));
+                }
+                rotation.algorithm
+            }
+            None => {
+                let address = match &self.hybrid {
+                    Some(hybrid) => hybrid::address(&self.public_key, &hybrid.public_key),
+                    None => WalletManager::generate_address(&self.public_key),
+                };
+                if address != self.sender {
+                    return Err("Invalid transaction signature".to_string());
+                }
+                SignatureAlgorithm::MlDsa87
+            }
+        };
+        let hash = self.hash();
+        let request = VerifyRequest { algorithm, public_key: &public_key, message: hash.as_bytes(), signature: &signature };
+        if !SIGNATURE_CACHE.verify(&request) {
+            return Err("Invalid transaction signature".to_string());
         }
-
-        // Check if it contains only valid Bech32m characters
-        let valid_chars = "023456789acdefghjklmnpqrstuvwxyz";
-        for c in address.chars().skip(3) {
-            if !valid_chars.contains(c) {
-                return false;
+        if let Some(hybrid) = &self.hybrid {
+            let digest = hex::decode(&hash).expect("hex transaction hash");
+            if !hybrid::verify(&hybrid.public_key, &digest, &hybrid.signature) {
+                return Err("Invalid secp256k1 signature".to_string());
             }
         }
+        Ok(())
+    }
 
-        true
+    /// Whether `address` is `sYn` followed by the 38 lowercase hex digits
+    /// `WalletManager::generate_address` produces.
+    pub fn is_valid_address(address: &str) -> bool {
+        address.len() == 41
+            && address.starts_with("sYn")
+            && address[3..].chars().all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c))
     }
 
     pub fn calculate_fee(&self) -> u64 {
@@ -230,7 +358,20 @@ impl Transaction {
     }
 
     pub fn from_bytes(data: &[u8]) -> Self {
-        let config = standard();
-        decode_from_slice(data, config).unwrap().0
+        Self::try_from_bytes(data).unwrap()
     }
+
+    /// Decodes `to_bytes` output, rejecting malformed or trailing bytes.
+    pub fn try_from_bytes(data: &[u8]) -> Result<Self, String> {
+        let (tx, read) = decode_from_slice(data, standard()).map_err(|e| format!("Invalid transaction encoding: {}", e))?;
+        if read != data.len() {
+            return Err(format!("Invalid transaction encoding: {} trailing bytes", data.len() - read));
+        }
+        Ok(tx)
+    }
+}
+
+/// `check_signature_at` of each of `transactions`, spread over the rayon pool.
+pub fn check_signatures(transactions: &[Transaction], keys: &KeyRegistry, height: u64) -> Vec<Result<(), String>> {
+    transactions.par_iter().map(|tx| tx.check_signature_at(keys, height)).collect()
 }
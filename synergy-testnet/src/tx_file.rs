@@ -0,0 +1,315 @@
+//! Transaction files for offline signing.
+//!
+//! `synergy-wallet tx build` writes an unsigned transaction, `tx sign`
+//! signs it with a keystore key on a machine that may be offline, and
+//! `tx broadcast` submits it to a node. A file holds one `Transaction`,
+//! either as JSON (`Transaction::to_json`) or in the canonical bincode
+//! encoding (`Transaction::to_bytes`); readers accept both.
+//!
+//! A transaction from a multisig account is passed from signer to signer,
+//! each adding a signature with `tx sign`, or signed in parallel and
+//! merged with `tx combine`. Its policy is kept in a JSON file written by
+//! `synergy-wallet multisig create`.
+//!
+//! Once an account has rotated its key (`crate::key_rotation`), its
+//! transactions are signed with `--signer` set to the new key's keystore
+//! address. Only a node knows the rotation, so such signatures are checked
+//! on submission rather than offline.
+//!
+//! The wallet takes a `.syn` name anywhere it takes an address and
+//! resolves it with `resolve`, which needs a node.
+
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+
+use serde_json::{json, Value};
+
+use crate::crypto::multisig::MultisigPolicy;
+use crate::crypto::pqc::SignatureAlgorithm;
+use crate::key_rotation::RotateKey;
+use crate::keystore::Keystore;
+use crate::name_service::{self, NameOp};
+use crate::storage;
+use crate::transaction::Transaction;
+use crate::wallet::WalletManager;
+
+pub const DEFAULT_GAS_PRICE: u64 = 1000;
+pub const DEFAULT_GAS_LIMIT: u64 = 21000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TxFormat {
+    Json,
+    Bincode,
+}
+
+impl FromStr for TxFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(TxFormat::Json),
+            "bincode" => Ok(TxFormat::Bincode),
+            _ => Err(format!("Unknown transaction format {} (expected json or bincode)", s)),
+        }
+    }
+}
+
+impl fmt::Display for TxFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            TxFormat::Json => "json",
+            TxFormat::Bincode => "bincode",
+        })
+    }
+}
+
+/// What a built transaction does.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TxOp {
+    /// Moves native SNRG to `to`.
+    Transfer { to: String },
+    /// Moves `token` to `to`.
+    TokenTransfer { to: String, token: String },
+    /// Stakes `token` with `validator`.
+    Stake { validator: String, token: String },
+    /// Binds a new key to the sender.
+    RotateKey(RotateKey),
+    /// Registers or edits a `.syn` name.
+    Name(NameOp),
+}
+
+/// An unsigned transaction from `from`, encoded the same way the node's
+/// wallet encodes it.
+pub fn build(from: &str, op: &TxOp, amount: u64, nonce: u64, gas_price: u64, gas_limit: u64) -> Transaction {
+    let (receiver, data) = match op {
+        TxOp::Transfer { to } => (to.clone(), None),
+        TxOp::TokenTransfer { to, token } => (to.clone(), Some(Transaction::token_transfer_data(to, token, amount))),
+        TxOp::Stake { validator, token } => (validator.clone(), Some(Transaction::stake_data(validator, token, amount))),
+        TxOp::RotateKey(rotation) => (from.to_string(), Some(rotation.to_data())),
+        TxOp::Name(op) => (from.to_string(), Some(op.to_data())),
+    };
+    Transaction::new(from.to_string(), receiver, amount, nonce, String::new(), gas_price, gas_limit, data)
+}
+
+pub fn encode(tx: &Transaction, format: TxFormat) -> Vec<u8> {
+    match format {
+        TxFormat::Json => format!("{}\n", tx.to_json()).into_bytes(),
+        TxFormat::Bincode => tx.to_bytes(),
+    }
+}
+
+/// Decodes a transaction file, detecting its format.
+pub fn decode(data: &[u8]) -> Result<(Transaction, TxFormat), String> {
+    if data.trim_ascii_start().starts_with(b"{") {
+        let text = std::str::from_utf8(data).map_err(|e| format!("Invalid transaction JSON: {}", e))?;
+        let tx = Transaction::from_json(text).map_err(|e| format!("Invalid transaction JSON: {}", e))?;
+        return Ok((tx, TxFormat::Json));
+    }
+    Ok((Transaction::try_from_bytes(data)?, TxFormat::Bincode))
+}
+
+pub fn read(path: impl AsRef<Path>) -> Result<(Transaction, TxFormat), Box<dyn Error>> {
+    let path = path.as_ref();
+    let data = fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    Ok(decode(&data).map_err(|e| format!("{}: {}", path.display(), e))?)
+}
+
+pub fn write(path: impl AsRef<Path>, tx: &Transaction, format: TxFormat) -> Result<(), Box<dyn Error>> {
+    let path = path.as_ref();
+    storage::write_atomic(path, &encode(tx, format)).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(())
+}
+
+/// Signs `tx` with the keystore key of its sender, or for a multisig
+/// sender with the one key in the keystore that has still to sign.
+pub fn sign(tx: &mut Transaction, keystore: &Keystore, password: &str) -> Result<(), Box<dyn Error>> {
+    let signer = signer(tx, keystore, None)?;
+    sign_as(tx, keystore, &signer, password)
+}
+
+/// The keystore address that signs `tx`: its sender, or `requested` for
+/// a sender that rotated to that key, or a signer of its multisig policy
+/// (`requested` if given) that has not signed yet.
+pub fn signer(tx: &Transaction, keystore: &Keystore, requested: Option<&str>) -> Result<String, Box<dyn Error>> {
+    if tx.sender.is_empty() {
+        return Err("Transaction has no sender".into());
+    }
+    let Some(auth) = &tx.multisig else {
+        return Ok(requested.unwrap_or(&tx.sender).to_string());
+    };
+
+    let signers = auth.policy.signer_addresses();
+    if let Some(address) = requested {
+        if !signers.iter().any(|signer| signer == address) {
+            return Err(format!("{} is not a signer of {}", address, tx.sender).into());
+        }
+        return Ok(address.to_string());
+    }
+    let mut pending: Vec<String> = signers
+        .into_iter()
+        .zip(&auth.signatures)
+        .filter(|(address, signature)| signature.is_empty() && keystore.find(address).is_ok())
+        .map(|(address, _)| address)
+        .collect();
+    match pending.len() {
+        0 => Err(format!("No key in {} still has to sign for {}", keystore.dir().display(), tx.sender).into()),
+        1 => Ok(pending.remove(0)),
+        _ => Err(format!("Several keys in {} can sign for {}; choose one: {}", keystore.dir().display(), tx.sender, pending.join(", ")).into()),
+    }
+}
+
+/// Signs `tx` with the keystore key of `signer`, as returned by `signer`.
+pub fn sign_as(tx: &mut Transaction, keystore: &Keystore, signer: &str, password: &str) -> Result<(), Box<dyn Error>> {
+    let keypair = keystore.unlock(signer, password)?;
+    if tx.multisig.is_some() {
+        tx.sign_multisig(&keypair)?;
+    } else if keypair.address() == tx.sender {
+        tx.sign(&keypair);
+    } else {
+        // A key the sender rotated to; the sender stays as it is
+        tx.sign_with(SignatureAlgorithm::MlDsa87, keypair.public_key(), keypair.private_key())?;
+    }
+    Ok(())
+}
+
+/// Whether `tx` is signed by a key other than the one its sender address
+/// was derived from, which only a node that knows the rotation can check.
+fn signed_by_rotated_key(tx: &Transaction) -> bool {
+    tx.multisig.is_none()
+        && tx.hybrid.is_none()
+        && !tx.signature.is_empty()
+        && WalletManager::generate_address(&tx.public_key) != tx.sender
+}
+
+/// Merges copies of one multisig transaction signed by different signers.
+pub fn combine(txs: &[Transaction]) -> Result<Transaction, String> {
+    let (first, rest) = txs.split_first().ok_or("Nothing to combine")?;
+    let mut combined = first.clone();
+    let hash = combined.hash();
+    let auth = combined.multisig.as_mut().ok_or("Only multisig transactions can be combined")?;
+    for tx in rest {
+        if tx.hash() != hash {
+            return Err(format!("Transaction {} is not {}", tx.hash(), hash));
+        }
+        auth.merge(tx.multisig.as_ref().ok_or("Only multisig transactions can be combined")?)?;
+    }
+    Ok(combined)
+}
+
+/// Reads a policy written by `write_policy`.
+pub fn read_policy(path: impl AsRef<Path>) -> Result<MultisigPolicy, Box<dyn Error>> {
+    let path = path.as_ref();
+    let data = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let policy: MultisigPolicy = serde_json::from_str(&data).map_err(|e| format!("{}: invalid multisig policy: {}", path.display(), e))?;
+    policy.check().map_err(|e| format!("{}: {}", path.display(), e))?;
+    Ok(policy)
+}
+
+pub fn write_policy(path: impl AsRef<Path>, policy: &MultisigPolicy) -> Result<(), Box<dyn Error>> {
+    let path = path.as_ref();
+    let data = format!("{}\n", serde_json::to_string_pretty(policy)?);
+    storage::write_atomic(path, data.as_bytes()).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(())
+}
+
+/// Field / value pairs `tx inspect` prints.
+pub fn describe(tx: &Transaction) -> Vec<(&'static str, String)> {
+    let signature = if !tx.is_signed() {
+        "unsigned".to_string()
+    } else if signed_by_rotated_key(tx) {
+        "by a rotated key, checked by the node".to_string()
+    } else if tx.verify_signature() {
+        "valid".to_string()
+    } else {
+        match &tx.multisig {
+            Some(auth) if auth.policy.address() == tx.sender && auth.check_signatures(tx.hash().as_bytes()).is_ok() => {
+                format!("incomplete, {} of {} required", auth.signed_count(), auth.policy.threshold)
+            }
+            _ => "INVALID".to_string(),
+        }
+    };
+    let mut fields = vec![
+        ("Hash", tx.hash()),
+        ("From", tx.sender.clone()),
+        ("To", tx.receiver.clone()),
+        ("Amount", tx.amount.to_string()),
+        ("Nonce", tx.nonce.to_string()),
+        ("Gas", format!("{} x {}", tx.gas_limit, tx.gas_price)),
+        ("Fee", tx.calculate_fee().to_string()),
+        ("Total cost", tx.total_cost().to_string()),
+        ("Timestamp", tx.timestamp.to_string()),
+        ("Data", tx.data.clone().unwrap_or_else(|| "-".to_string())),
+        ("Signature", signature),
+    ];
+    if let Some(Ok(op)) = NameOp::from_transaction(tx) {
+        if op.fee() > 0 {
+            fields.push(("Name fee", format!("{} SNRG, burned on inclusion", op.fee())));
+        }
+    }
+    if let Some(hybrid) = &tx.hybrid {
+        fields.push(("secp256k1", hybrid.public_key.clone()));
+    }
+    if let Some(auth) = &tx.multisig {
+        fields.push(("Multisig", format!("{} of {}", auth.policy.threshold, auth.policy.public_keys.len())));
+        for (address, signature) in auth.policy.signer_addresses().into_iter().zip(&auth.signatures) {
+            fields.push(("Signer", format!("{} {}", address, if signature.is_empty() { "pending" } else { "signed" })));
+        }
+    }
+    fields
+}
+
+/// Submits a signed transaction with `synergy_sendTransaction`. Returns
+/// its hash.
+pub fn broadcast(rpc_url: &str, tx: &Transaction) -> Result<String, Box<dyn Error>> {
+    if !signed_by_rotated_key(tx) && !tx.verify_signature() {
+        return Err("Transaction is not signed by its sender; run `tx sign` first".into());
+    }
+    let response = call(rpc_url, "synergy_sendTransaction", json!({ "transaction": tx }))?;
+    match response.get("error") {
+        Some(error) => Err(format!("Node rejected the transaction: {}", error["message"].as_str().unwrap_or("unknown error")).into()),
+        None => Ok(tx.hash()),
+    }
+}
+
+/// The address `value` stands for: `value` itself, or for a `.syn` name
+/// the address it resolves to on the node at `rpc_url`.
+pub fn resolve(rpc_url: &str, value: &str) -> Result<String, Box<dyn Error>> {
+    if !name_service::is_name(value) {
+        return Ok(value.to_string());
+    }
+    let name = name_service::normalize(value)?;
+    let response = call(rpc_url, "synergy_resolveName", json!({ "name": name }))?;
+    if let Some(error) = response.get("error") {
+        return Err(format!("Failed to resolve {}: {}", name, error["message"].as_str().unwrap_or("unknown error")).into());
+    }
+    match response["result"]["address"].as_str() {
+        Some(address) => Ok(address.to_string()),
+        None => Err(format!("{} is not registered", name).into()),
+    }
+}
+
+/// The primary name of `address` on the node at `rpc_url`, if it has one
+/// that resolves to it.
+pub fn lookup(rpc_url: &str, address: &str) -> Result<Option<String>, Box<dyn Error>> {
+    let response = call(rpc_url, "synergy_lookupAddress", json!({ "address": address }))?;
+    if let Some(error) = response.get("error") {
+        return Err(format!("Failed to look up {}: {}", address, error["message"].as_str().unwrap_or("unknown error")).into());
+    }
+    Ok(response["result"]["name"].as_str().map(str::to_string))
+}
+
+/// Sends one JSON-RPC request and returns the response object.
+fn call(rpc_url: &str, method: &str, params: Value) -> Result<Value, Box<dyn Error>> {
+    let response = reqwest::blocking::Client::new()
+        .post(rpc_url)
+        .timeout(Duration::from_secs(10))
+        .json(&json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params }))
+        .send()
+        .and_then(|response| response.json())
+        .map_err(|e| format!("Node is not reachable at {}: {}", rpc_url, e))?;
+    Ok(response)
+}
@@ -1,10 +1,13 @@
 use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, Mutex};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 // SHA3 is not currently used in this module
 use std::time::{SystemTime, UNIX_EPOCH};
+use crate::events::{NodeEvent, ValidatorStatusChange, EVENT_BUS};
+use crate::storage;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Validator {
     pub address: String,
     pub public_key: String,
@@ -41,7 +44,7 @@ pub struct Validator {
     pub version: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 pub enum ValidatorStatus {
     Active,
     Inactive,
@@ -179,6 +182,12 @@ impl Validator {
     }
 }
 
+impl Default for ValidatorRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ValidatorRegistry {
     pub fn new() -> Self {
         ValidatorRegistry {
@@ -273,6 +282,14 @@ impl ValidatorRegistry {
         }
     }
 
+    /// Switches a validator to a rotated consensus key.
+    pub fn set_public_key(&mut self, address: &str, public_key: &str) -> Result<(), String> {
+        let validator = self.validators.get_mut(address).ok_or("Validator not found")?;
+        validator.public_key = public_key.to_string();
+        validator.update_activity();
+        Ok(())
+    }
+
     pub fn get_active_validators(&self) -> Vec<&Validator> {
         self.validators
             .values()
@@ -380,7 +397,7 @@ impl ValidatorRegistry {
         validators.into_iter().take(count).collect()
     }
 
-    pub fn calculate_epoch_rewards(&self, epoch: u64) -> HashMap<String, u64> {
+    pub fn calculate_epoch_rewards(&self, _epoch: u64) -> HashMap<String, u64> {
         let mut rewards = HashMap::new();
 
         for validator in self.validators.values() {
@@ -399,9 +416,7 @@ impl ValidatorRegistry {
     }
 
     pub fn save_to_file(&self, path: &str) -> Result<(), Box<dyn std::error::Error>> {
-        let json = serde_json::to_string_pretty(self)?;
-        std::fs::write(path, json)?;
-        Ok(())
+        storage::write_json_atomic(path, self)
     }
 
     pub fn load_from_file(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
@@ -419,6 +434,12 @@ pub struct ValidatorPerformanceUpdate {
     pub timestamp: u64,
 }
 
+impl Default for ValidatorManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ValidatorManager {
     pub fn new() -> Self {
         ValidatorManager {
@@ -436,15 +457,30 @@ impl ValidatorManager {
 
     pub fn approve_validator(&self, address: &str) -> Result<(), String> {
         if let Ok(mut registry) = self.registry.lock() {
-            registry.approve_registration(address)
+            let result = registry.approve_registration(address);
+            Self::publish_status_change(&registry, address, Some(ValidatorStatus::Pending));
+            result
         } else {
             Err("Failed to acquire registry lock".to_string())
         }
     }
 
     pub fn update_performance(&self, update: ValidatorPerformanceUpdate) {
+        match update.update_type.as_str() {
+            "block_produced" => crate::metrics::VALIDATOR_PROPOSALS_TOTAL
+                .with_label_values(&[&update.validator_address])
+                .inc(),
+            "block_missed" => crate::metrics::VALIDATOR_MISSED_BLOCKS_TOTAL
+                .with_label_values(&[&update.validator_address])
+                .inc(),
+            _ => {}
+        }
+
         if let Ok(mut registry) = self.registry.lock() {
-            registry.update_validator_performance(&update.validator_address.clone(), update);
+            let address = update.validator_address.clone();
+            let previous = registry.get_validator_by_address(&address).map(|v| v.status.clone());
+            registry.update_validator_performance(&address, update);
+            Self::publish_status_change(&registry, &address, previous);
         }
     }
 
@@ -466,12 +502,31 @@ impl ValidatorManager {
 
     pub fn slash_validator(&self, address: &str, reason: &str) -> Result<(), String> {
         if let Ok(mut registry) = self.registry.lock() {
-            registry.slash_validator(address, reason)
+            let previous = registry.get_validator_by_address(address).map(|v| v.status.clone());
+            let result = registry.slash_validator(address, reason);
+            Self::publish_status_change(&registry, address, previous);
+            result
+        } else {
+            Err("Failed to acquire registry lock".to_string())
+        }
+    }
+
+    pub fn set_public_key(&self, address: &str, public_key: &str) -> Result<(), String> {
+        if let Ok(mut registry) = self.registry.lock() {
+            registry.set_public_key(address, public_key)
         } else {
             Err("Failed to acquire registry lock".to_string())
         }
     }
 
+    pub fn get_validator_clusters(&self) -> HashMap<u64, ValidatorCluster> {
+        if let Ok(registry) = self.registry.lock() {
+            registry.clusters.clone()
+        } else {
+            HashMap::new()
+        }
+    }
+
     pub fn get_top_validators(&self, count: usize) -> Vec<Validator> {
         if let Ok(registry) = self.registry.lock() {
             registry.get_top_validators(count).into_iter().cloned().collect()
@@ -503,6 +558,19 @@ impl ValidatorManager {
         }
         Ok(())
     }
+
+    fn publish_status_change(registry: &ValidatorRegistry, address: &str, previous: Option<ValidatorStatus>) {
+        if let Some(validator) = registry.get_validator_by_address(address) {
+            if previous.as_ref() != Some(&validator.status) {
+                EVENT_BUS.publish(NodeEvent::ValidatorStatus(ValidatorStatusChange {
+                    address: address.to_string(),
+                    previous_status: previous,
+                    status: validator.status.clone(),
+                    timestamp: Validator::current_timestamp(),
+                }));
+            }
+        }
+    }
 }
 
 // Global validator manager instance
@@ -1,24 +1,45 @@
 use std::collections::HashMap;
+use std::error::Error;
+use std::time::{Duration, Instant};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use sha3::{Sha3_256, Digest};
 use hex;
+use crate::crypto::hd::{self, HdSeed};
+use crate::crypto::hybrid;
+use crate::crypto::keys::KeyPair;
+use crate::keystore::{Keystore, DEFAULT_KEYSTORE_DIR};
 use crate::transaction::Transaction;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Wallet {
     pub address: String,
     pub public_key: String,
-    pub private_key: Option<String>, // Only stored for testing, never in production
     pub balance: HashMap<String, u64>, // token_symbol -> balance
     pub staked_balance: HashMap<String, u64>, // token_symbol -> staked amount
-    pub nonce: u64,
     pub created_at: u64,
 }
 
+/// Node-held wallets. Their keys live encrypted in the keystore; a wallet
+/// can sign only while it is unlocked, and the decrypted key never leaves
+/// this struct.
 #[derive(Debug, Clone)]
 pub struct WalletManager {
     wallets: HashMap<String, Wallet>,
-    keypairs: HashMap<String, (String, String)>, // address -> (public_key, private_key)
+    keystore: Keystore,
+    unlocked: HashMap<String, UnlockedKey>,
+}
+
+#[derive(Debug, Clone)]
+struct UnlockedKey {
+    keypair: KeyPair,
+    expires_at: Option<Instant>,
+}
+
+impl UnlockedKey {
+    fn is_expired(&self) -> bool {
+        self.expires_at.is_some_and(|expires_at| Instant::now() >= expires_at)
+    }
 }
 
 impl Wallet {
@@ -26,20 +47,12 @@ impl Wallet {
         Wallet {
             address,
             public_key,
-            private_key: None,
             balance: HashMap::new(),
             staked_balance: HashMap::new(),
-            nonce: 0,
             created_at: Self::current_timestamp(),
         }
     }
 
-    pub fn with_private_key(address: String, public_key: String, private_key: String) -> Self {
-        let mut wallet = Self::new(address, public_key);
-        wallet.private_key = Some(private_key);
-        wallet
-    }
-
     pub fn update_balance(&mut self, token_symbol: String, amount: u64) {
         *self.balance.entry(token_symbol).or_insert(0) = amount;
     }
@@ -48,10 +61,6 @@ impl Wallet {
         self.balance.get(token_symbol).copied().unwrap_or(0)
     }
 
-    pub fn increment_nonce(&mut self) {
-        self.nonce += 1;
-    }
-
     fn current_timestamp() -> u64 {
         std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
@@ -60,30 +69,36 @@ impl Wallet {
     }
 }
 
+impl Default for WalletManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl WalletManager {
     pub fn new() -> Self {
         WalletManager {
             wallets: HashMap::new(),
-            keypairs: HashMap::new(),
+            keystore: Keystore::new(DEFAULT_KEYSTORE_DIR),
+            unlocked: HashMap::new(),
         }
     }
 
-    pub fn generate_keypair() -> (String, String, String) {
-        // Generate a deterministic keypair for testing
-        // In production, this would use proper cryptographic key generation
-        let timestamp = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
-
-        let private_key_seed = format!("synergy_private_key_{}", timestamp);
-        let public_key_seed = format!("synergy_public_key_{}", timestamp);
-
-        let private_key = hex::encode(private_key_seed.as_bytes());
-        let public_key = hex::encode(public_key_seed.as_bytes());
-        let address = Self::generate_address(&public_key);
+    /// Switches to `keystore` and registers every key in it as a locked
+    /// wallet. Returns how many keys were found.
+    pub fn open_keystore(&mut self, keystore: Keystore) -> Result<usize, Box<dyn Error>> {
+        let entries = keystore.list()?;
+        for entry in &entries {
+            self.wallets
+                .entry(entry.address.clone())
+                .or_insert_with(|| Wallet::new(entry.address.clone(), entry.public_key.clone()));
+        }
+        self.keystore = keystore;
+        Ok(entries.len())
+    }
 
-        (address, public_key, private_key)
+    pub fn keystore(&self) -> &Keystore {
+        &self.keystore
     }
 
     pub fn generate_address(public_key: &str) -> String {
@@ -100,34 +115,73 @@ impl WalletManager {
         format!("sYn{}", &address_hex[..38]) // 38 chars to make 41 total with prefix
     }
 
-    pub fn create_wallet(&mut self) -> String {
-        let (address, public_key, private_key) = Self::generate_keypair();
-
-        let wallet = Wallet::with_private_key(
-            address.clone(),
-            public_key,
-            private_key,
-        );
+    /// Generates a key, stores it in the keystore under `password` and
+    /// registers it as a locked wallet.
+    pub fn create_wallet(&mut self, password: &str) -> Result<String, String> {
+        let entry = self.keystore.create(password).map_err(|e| e.to_string())?;
+        let wallet = Wallet::new(entry.address.clone(), entry.public_key);
+        self.wallets.insert(entry.address.clone(), wallet);
+        Ok(entry.address)
+    }
 
-        self.wallets.insert(address.clone(), wallet);
-        self.keypairs.insert(address.clone(), (public_key, private_key));
+    /// Like `create_wallet`, but for a hybrid account that also needs a
+    /// secp256k1 signature on every transaction.
+    pub fn create_hybrid_wallet(&mut self, password: &str) -> Result<String, String> {
+        let keypair = KeyPair::generate().with_secp256k1(hybrid::generate_key());
+        let entry = self.keystore.add(&keypair, password).map_err(|e| e.to_string())?;
+        let wallet = Wallet::new(entry.address.clone(), entry.public_key);
+        self.wallets.insert(entry.address.clone(), wallet);
+        Ok(entry.address)
+    }
 
-        address
+    /// Restores accounts `0..accounts` of a BIP-39 phrase into the keystore
+    /// under `password` and registers them as locked wallets. Accounts that
+    /// are already there are left alone, so recovery can be repeated.
+    /// Returns the addresses in account order.
+    pub fn create_from_mnemonic(
+        &mut self,
+        phrase: &str,
+        passphrase: &str,
+        password: &str,
+        accounts: u32,
+    ) -> Result<Vec<String>, String> {
+        let seed = HdSeed::from_mnemonic(&hd::parse_mnemonic(phrase)?, passphrase);
+        (0..accounts).map(|account| self.derive_account(&seed, account, password)).collect()
     }
 
-    pub fn create_wallet_from_keypair(&mut self, public_key: String, private_key: String) -> String {
-        let address = Self::generate_address(&public_key);
+    /// Derives account `index` of a mnemonic seed, storing and registering
+    /// it like `create_from_mnemonic` does. Returns its address.
+    pub fn derive_account(&mut self, seed: &HdSeed, index: u32, password: &str) -> Result<String, String> {
+        let keypair = seed.derive_account(index)?;
+        let address = keypair.address();
+        if self.keystore.find(&address).is_err() {
+            self.keystore.add(&keypair, password).map_err(|e| e.to_string())?;
+        }
+        self.wallets
+            .entry(address.clone())
+            .or_insert_with(|| Wallet::new(address.clone(), keypair.public_key_hex()));
+        Ok(address)
+    }
 
-        let wallet = Wallet::with_private_key(
-            address.clone(),
-            public_key,
-            private_key,
-        );
+    /// Decrypts a wallet's key so it can sign, until `lock_wallet` or, when
+    /// given, until `duration` has passed.
+    pub fn unlock_wallet(&mut self, address: &str, password: &str, duration: Option<Duration>) -> Result<(), String> {
+        if !self.wallets.contains_key(address) {
+            return Err(format!("Wallet {} not found", address));
+        }
+        let keypair = self.keystore.unlock(address, password).map_err(|e| e.to_string())?;
+        let expires_at = duration.map(|duration| Instant::now() + duration);
+        self.unlocked.insert(address.to_string(), UnlockedKey { keypair, expires_at });
+        Ok(())
+    }
 
-        self.wallets.insert(address.clone(), wallet);
-        self.keypairs.insert(address.clone(), (public_key, private_key));
+    /// Forgets a wallet's decrypted key. Returns whether it was unlocked.
+    pub fn lock_wallet(&mut self, address: &str) -> bool {
+        self.unlocked.remove(address).is_some_and(|key| !key.is_expired())
+    }
 
-        address
+    pub fn is_unlocked(&self, address: &str) -> bool {
+        self.unlocked.get(address).is_some_and(|key| !key.is_expired())
     }
 
     pub fn get_wallet(&self, address: &str) -> Option<&Wallet> {
@@ -139,39 +193,40 @@ impl WalletManager {
     }
 
     pub fn sign_transaction(&self, address: &str, tx: &mut Transaction) -> Result<String, String> {
-        if let Some(keypair) = self.keypairs.get(address) {
-            let (_, private_key) = keypair;
-
-            // Create signature using private key
-            // In production, this would use proper ECDSA or Dilithium signatures
-            let message = tx.hash();
-            let signature = Self::sign_message(&message, private_key);
-
-            tx.signature = signature;
-            tx.sender = address.to_string();
-
-            Ok("Transaction signed successfully".to_string())
+        if !self.wallets.contains_key(address) {
+            return Err(format!("Wallet {} not found", address));
+        }
+        let key = self
+            .unlocked
+            .get(address)
+            .filter(|key| !key.is_expired())
+            .ok_or_else(|| format!("Wallet {} is locked; unlock it first", address))?;
+
+        if tx.multisig.is_some() {
+            tx.sign_multisig(&key.keypair)?;
         } else {
-            Err("Wallet not found or no private key available".to_string())
+            tx.sign(&key.keypair);
         }
+
+        Ok("Transaction signed successfully".to_string())
     }
 
+    /// Checks a transaction's signatures against the sender's wallet.
     pub fn verify_signature(&self, tx: &Transaction) -> bool {
-        if let Some(keypair) = self.keypairs.get(&tx.sender) {
-            let (public_key, _) = keypair;
-            let message = tx.hash();
-            Self::verify_message(&message, &tx.signature, public_key)
-        } else {
-            false
-        }
+        self.wallets
+            .get(&tx.sender)
+            .is_some_and(|wallet| wallet.public_key == tx.public_key && tx.verify_signature())
     }
 
+    /// Creates a token transfer from the unlocked wallet `from`, signed
+    /// with `nonce`. The transaction is returned, not submitted.
     pub fn send_tokens(
-        &mut self,
+        &self,
         from: &str,
         to: &str,
         token_symbol: &str,
         amount: u64,
+        nonce: u64,
         token_manager: &crate::token::TokenManager,
     ) -> Result<Transaction, String> {
         // Check balance
@@ -185,30 +240,28 @@ impl WalletManager {
             from.to_string(),
             to.to_string(),
             amount,
-            self.get_wallet(from).map_or(0, |w| w.nonce),
+            nonce,
             "".to_string(), // signature will be added
             1000, // gas_price
             21000, // gas_limit
-            Some(format!("token_transfer:{{\"to\":\"{}\",\"token\":\"{}\",\"amount\":{}}}", to, token_symbol, amount)),
+            Some(Transaction::token_transfer_data(to, token_symbol, amount)),
         );
 
         // Sign transaction
         self.sign_transaction(from, &mut tx)?;
 
-        // Update nonce
-        if let Some(wallet) = self.wallets.get_mut(from) {
-            wallet.increment_nonce();
-        }
-
         Ok(tx)
     }
 
+    /// Creates a staking transaction from the unlocked wallet `staker`,
+    /// signed with `nonce`. The transaction is returned, not submitted.
     pub fn stake_tokens(
-        &mut self,
+        &self,
         staker: &str,
         validator: &str,
         token_symbol: &str,
         amount: u64,
+        nonce: u64,
         token_manager: &crate::token::TokenManager,
     ) -> Result<Transaction, String> {
         // Check balance
@@ -222,41 +275,22 @@ impl WalletManager {
             staker.to_string(),
             validator.to_string(),
             amount,
-            self.get_wallet(staker).map_or(0, |w| w.nonce),
+            nonce,
             "".to_string(),
             1000,
             21000,
-            Some(format!("stake:{{\"validator\":\"{}\",\"token\":\"{}\",\"amount\":{}}}", validator, token_symbol, amount)),
+            Some(Transaction::stake_data(validator, token_symbol, amount)),
         );
 
         // Sign transaction
         self.sign_transaction(staker, &mut tx)?;
 
-        // Update nonce
-        if let Some(wallet) = self.wallets.get_mut(staker) {
-            wallet.increment_nonce();
-        }
-
         Ok(tx)
     }
 
     pub fn get_all_wallets(&self) -> Vec<&Wallet> {
         self.wallets.values().collect()
     }
-
-    fn sign_message(message: &str, private_key: &str) -> String {
-        // Simple signature for testing - in production use proper crypto
-        let mut hasher = Sha3_256::new();
-        hasher.update(message.as_bytes());
-        hasher.update(private_key.as_bytes());
-        hex::encode(hasher.finalize())
-    }
-
-    fn verify_message(message: &str, signature: &str, public_key: &str) -> bool {
-        // Simple verification for testing - in production use proper crypto
-        let expected_signature = Self::sign_message(message, &format!("priv_{}", public_key));
-        signature == expected_signature
-    }
 }
 
 // Global wallet manager instance
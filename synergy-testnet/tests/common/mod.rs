@@ -0,0 +1,31 @@
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::thread;
+use std::time::{Duration, Instant};
+use synergy_testnet::config::RPCConfig;
+use synergy_testnet::rpc::rpc_server;
+
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Starts the servers `config` enables, with HTTP (or gRPC when HTTP is off)
+/// on `port`, and returns that address once it accepts connections. Port 0
+/// picks a free port.
+pub fn start_test_server(mut config: RPCConfig, port: u16) -> SocketAddr {
+    let port = match port {
+        0 => TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port(),
+        port => port,
+    };
+    if config.enable_http {
+        config.http_port = port;
+    } else {
+        config.grpc_port = port;
+    }
+    thread::spawn(move || rpc_server::start_rpc_server(&config));
+
+    let address = SocketAddr::from(([127, 0, 0, 1], port));
+    let started = Instant::now();
+    while TcpStream::connect(address).is_err() {
+        assert!(started.elapsed() < STARTUP_TIMEOUT, "Server on {} did not start", address);
+        thread::sleep(Duration::from_millis(20));
+    }
+    address
+}
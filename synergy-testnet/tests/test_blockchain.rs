@@ -1,15 +1,40 @@
-use synergy_testnet::node::Blockchain;
+use synergy_testnet::block::{Block, BlockChain};
+use synergy_testnet::crypto::keys::KeyPair;
+use synergy_testnet::transaction::Transaction;
 
 #[test]
 fn test_block_creation() {
-    let mut blockchain = Blockchain::new();
+    let mut blockchain = BlockChain::new();
+    blockchain.genesis();
     assert_eq!(blockchain.chain.len(), 1); // Genesis block should exist
 
-    blockchain.add_transaction("Alice".to_string(), "Bob".to_string(), 100);
-    blockchain.mine_block();
+    let tx = Transaction::new("Alice".to_string(), "Bob".to_string(), 100, 0, String::new(), 1, 21_000, None);
+    let previous_hash = blockchain.last().unwrap().hash.clone();
+    blockchain.add_block(Block::new(1, vec![tx], previous_hash.clone(), "validator".to_string(), 0));
 
     assert_eq!(blockchain.chain.len(), 2); // New block should be added
     assert_eq!(blockchain.chain[1].transactions.len(), 1); // Block should contain one transaction
+    assert_eq!(blockchain.chain[1].previous_hash, previous_hash);
+    assert_eq!(blockchain.chain[1].hash, blockchain.chain[1].calculate_hash());
+}
+
+#[test]
+fn test_blocks_are_signed_over_a_hash_that_covers_the_timestamp() {
+    let mut first = BlockChain::new();
+    first.genesis_for("signed-genesis");
+    let mut second = BlockChain::new();
+    second.genesis_for("signed-genesis");
+    // Block 0 is the same on every node
+    assert_eq!(first.chain[0].hash, second.chain[0].hash);
+
+    let proposer = KeyPair::generate();
+    let mut block = Block::new(1, vec![], first.chain[0].hash.clone(), proposer.address(), 7);
+    assert!(!block.verify_signature(&proposer.public_key_hex()));
+    block.sign(&proposer);
+    assert!(block.verify_signature(&proposer.public_key_hex()));
+    assert!(!block.verify_signature(&KeyPair::generate().public_key_hex()));
 
-    println!("Blockchain State: {:?}", blockchain.chain);
+    let mut backdated = block.clone();
+    backdated.timestamp -= 1;
+    assert_ne!(backdated.calculate_hash(), block.hash);
 }
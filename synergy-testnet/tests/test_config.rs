@@ -0,0 +1,113 @@
+use std::path::PathBuf;
+
+use synergy_testnet::config::{load_layered, ConfigSources, NodeConfig};
+use synergy_testnet::genesis::GenesisSpec;
+
+fn temp_file(name: &str, contents: &str) -> String {
+    let path: PathBuf = std::env::temp_dir().join(format!("synergy-config-{}-{}", std::process::id(), name));
+    std::fs::write(&path, contents).unwrap();
+    path.to_string_lossy().into_owned()
+}
+
+fn genesis(name: &str, chain_id: u64, block_time: u64) -> String {
+    let mut spec = GenesisSpec::load(concat!(env!("CARGO_MANIFEST_DIR"), "/../config/genesis-spec.toml")).unwrap();
+    spec.chain_id = chain_id;
+    spec.consensus.parameters.block_time = block_time;
+    spec.consensus.parameters.reward_weighting.task_accuracy = 0.6;
+    spec.consensus.parameters.reward_weighting.collaboration = 0.1;
+    let path = temp_file(name, "");
+    spec.build().unwrap().save(&path).unwrap();
+    path
+}
+
+fn args(args: &[&str]) -> Vec<String> {
+    args.iter().map(|arg| arg.to_string()).collect()
+}
+
+#[test]
+fn test_validation_reports_every_problem() {
+    assert!(NodeConfig::default().validate().is_ok());
+
+    let mut config = NodeConfig::default();
+    config.consensus.reward_weighting.uptime = 0.5;
+    config.rpc.ws_port = config.rpc.http_port;
+    config.logging.log_level = "loud".to_string();
+    config.p2p.listen_address = "localhost".to_string();
+
+    let errors = config.validate().unwrap_err();
+    assert_eq!(errors.len(), 4, "{:?}", errors);
+    assert!(errors.iter().any(|e| e.starts_with("consensus.reward_weighting must sum to 1")));
+    assert!(errors.iter().any(|e| e.contains("rpc.http_port and rpc.ws_port")));
+    assert!(errors.iter().any(|e| e.starts_with("logging.log_level")));
+    assert!(errors.iter().any(|e| e.starts_with("p2p.listen_address")));
+}
+
+#[test]
+fn test_layering_and_flags() {
+    let genesis = genesis("layering.json", 7963749, 5);
+    let file = temp_file(
+        "layering.toml",
+        &format!(
+            "[blockchain]\ngenesis_file = \"{}\"\n\n[rpc]\nhttp_port = 9000\nws_port = 9001\n\n[bogus]\nkey = 1\n",
+            genesis
+        ),
+    );
+
+    let (sources, rest) = ConfigSources::from_args(&args(&[
+        "--config",
+        &file,
+        "--rpc-port=9100",
+        "--set",
+        "logging.log_level=debug",
+        "http://node:8545",
+    ]))
+    .unwrap();
+    assert_eq!(rest, vec!["http://node:8545"]);
+
+    let loaded = load_layered(&sources).unwrap();
+    let config = loaded.config;
+    // The flag beats the file, which beats the defaults
+    assert_eq!(config.rpc.http_port, 9100);
+    assert_eq!(config.rpc.ws_port, 9001);
+    assert_eq!(config.rpc.grpc_port, NodeConfig::default().rpc.grpc_port);
+    assert_eq!(config.logging.log_level, "debug");
+    assert!(loaded.warnings.iter().any(|w| w.contains("Unknown key 'bogus'")));
+
+    assert!(ConfigSources::from_args(&args(&["--set", "rpc"])).is_err());
+    assert!(ConfigSources::from_args(&args(&["--rpc-port"])).is_err());
+    let (sources, _) = ConfigSources::from_args(&args(&["--config", &file, "--set", "rpc.nope=1"])).unwrap();
+    assert!(load_layered(&sources).is_err());
+}
+
+#[test]
+fn test_genesis_overrides_consensus() {
+    let mut config = NodeConfig::default();
+    config.blockchain.genesis_file = genesis("override.json", 7963749, 2);
+
+    let overridden = config.apply_genesis().unwrap();
+    assert_eq!(config.consensus.block_time_secs, 2);
+    assert_eq!(config.consensus.reward_weighting.task_accuracy, 0.6);
+    assert!(overridden.iter().any(|w| w.starts_with("consensus.block_time_secs = 5")));
+    assert!(config.validate().is_ok());
+
+    let mut config = NodeConfig::default();
+    config.blockchain.genesis_file = genesis("mismatch.json", 1, 5);
+    let error = config.apply_genesis().unwrap_err().to_string();
+    assert!(error.contains("does not match chainId 1"), "{}", error);
+}
+
+#[test]
+fn test_presets() {
+    let devnet = NodeConfig::preset("devnet").unwrap();
+    assert!(devnet.network.bootnodes.is_empty());
+    assert!(devnet.rpc.auth.enable_admin);
+    assert!(devnet.validate().is_ok());
+    assert!(NodeConfig::preset("mainnet").is_err());
+
+    // What `config init` writes loads back unchanged
+    let file = temp_file("devnet.toml", &devnet.to_toml().unwrap());
+    let sources = ConfigSources { path: Some(file), network: Some("testnet".to_string()), ..ConfigSources::default() };
+    let loaded = load_layered(&sources).unwrap();
+    assert_eq!(loaded.config.network.name, "Synergy Devnet");
+    assert!(loaded.warnings.iter().all(|w| !w.starts_with("Unknown key")));
+}
@@ -1,18 +1,27 @@
+use synergy_testnet::config::NodeConfig;
 use synergy_testnet::consensus;
-use std::sync::{Arc, Mutex};
-use std::thread;
-use std::time::Duration;
 
-#[test]
-fn test_consensus_algorithm() {
-    let blockchain = Arc::new(Mutex::new(Vec::<String>::new()));
+const GENESIS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../config/genesis.json");
 
-    let handle = thread::spawn(move || {
-        crate::consensus::run_consensus();
-    });
+fn temp_path(name: &str) -> String {
+    let path = std::env::temp_dir().join(format!("synergy-consensus-{}-{}", std::process::id(), name));
+    path.to_string_lossy().into_owned()
+}
 
-    thread::sleep(Duration::from_secs(2));
+#[test]
+fn test_consensus_algorithm() {
+    // Consensus refuses to start without its genesis, before touching the chain on disk
+    let mut config = NodeConfig::default();
+    config.blockchain.genesis_file = temp_path("missing.json");
+    assert!(consensus::start_consensus(&config).is_err());
 
-    assert!(blockchain.lock().unwrap().is_empty());
-    handle.join().unwrap();
+    // ...and from a genesis file edited after its hash was computed
+    let mut genesis: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(GENESIS).unwrap()).unwrap();
+    genesis["config"]["chainId"] = serde_json::json!(1);
+    let path = temp_path("edited.json");
+    std::fs::write(&path, serde_json::to_string_pretty(&genesis).unwrap()).unwrap();
+    config.blockchain.genesis_file = path.clone();
+    let error = consensus::start_consensus(&config).unwrap_err();
+    assert!(error.contains("was modified"), "{}", error);
+    std::fs::remove_file(path).unwrap();
 }
@@ -0,0 +1,75 @@
+//! Multi-node tests against a local devnet. The block test launches three
+//! real node processes from the built binary with a one-second block time.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use synergy_testnet::devnet::{Devnet, DevnetOptions, DEVNET_PASSWORD};
+use synergy_testnet::genesis::Genesis;
+use synergy_testnet::keystore::KeystoreFile;
+
+fn options(name: &str, nodes: usize, rpc_port: u16, p2p_port: u16) -> DevnetOptions {
+    DevnetOptions {
+        nodes,
+        dir: std::env::temp_dir().join(format!("synergy-devnet-{}-{}", name, std::process::id())),
+        rpc_port,
+        p2p_port,
+        block_time_secs: 1,
+        binary: PathBuf::from(env!("CARGO_BIN_EXE_synergy-testnet")),
+    }
+}
+
+#[test]
+fn test_generate_layout() {
+    let devnet = Devnet::generate(options("layout", 4, 28545, 41303)).unwrap();
+
+    let genesis = Genesis::load(&devnet.options.dir.join("genesis.json").to_string_lossy()).unwrap();
+    assert_eq!(genesis.genesis_hash, devnet.genesis_hash);
+    assert_eq!(genesis.validators.initial_validators.len(), 4);
+
+    let mut ports = Vec::new();
+    for node in &devnet.nodes {
+        assert!(node.dir.join("config.toml").exists());
+        let key = KeystoreFile::load(node.dir.join("keys/validator.json")).unwrap();
+        assert_eq!(key.decrypt(DEVNET_PASSWORD).unwrap().address(), node.address);
+        let copy = Genesis::load(&node.dir.join("genesis.json").to_string_lossy()).unwrap();
+        assert_eq!(copy.genesis_hash, devnet.genesis_hash);
+        ports.extend([node.rpc_port, node.ws_port, node.grpc_port, node.p2p_port]);
+    }
+    let count = ports.len();
+    ports.sort_unstable();
+    ports.dedup();
+    assert_eq!(ports.len(), count, "ports must not collide");
+
+    assert!(Devnet::generate(options("too-many", 17, 28545, 41303)).is_err());
+}
+
+#[test]
+fn test_blocks_are_produced_and_finalized_on_every_node() {
+    let mut devnet = Devnet::generate(options("blocks", 3, 29545, 42303)).unwrap();
+    devnet.start().unwrap();
+
+    let result = devnet
+        .wait_until_ready(Duration::from_secs(60))
+        .and_then(|()| devnet.wait_for_height(2, Duration::from_secs(60)));
+    if let Err(e) = result {
+        panic!("{}\n{}", e, devnet.tail_logs(20).join("\n"));
+    }
+
+    for height in devnet.finalized_heights() {
+        assert!(height.unwrap() >= 2);
+    }
+
+    // The nodes share one chain of signed blocks: the same genesis block and the same blocks after it
+    for height in 0..=2 {
+        let hashes: Vec<Option<String>> = devnet.nodes.iter().map(|node| node.block_hash(height).unwrap()).collect();
+        assert!(hashes[0].is_some(), "node-0 has no block {}", height);
+        assert!(hashes.iter().all(|hash| hash == &hashes[0]), "nodes disagree on block {}: {:?}", height, hashes);
+    }
+
+    devnet.stop();
+    for node in &devnet.nodes {
+        let log = std::fs::read_to_string(node.log_path()).unwrap();
+        assert!(log.contains("Consensus state flushed"), "{} did not shut down cleanly", node.name);
+    }
+}
@@ -0,0 +1,148 @@
+use std::sync::{Arc, Mutex};
+use serde_json::{json, Value};
+use synergy_testnet::block::{Block, BlockChain};
+use synergy_testnet::nonce::{NonceRegistry, NONCE_REGISTRY};
+use synergy_testnet::rpc::eth;
+use synergy_testnet::transaction::Transaction;
+
+const VALIDATOR: &str = "sYn0f43dd43e8a3ffa378899067449320cb15b085";
+
+fn sample_transaction(nonce: u64) -> Transaction {
+    Transaction::new(
+        "sYn4b45e8d59f59f51377d2a6847b92f242e73527".to_string(),
+        "sYn5c7ae6ccf388391abafd1b6521c2904a8e2fb3".to_string(),
+        500,
+        nonce,
+        "signature".to_string(),
+        2000,
+        21000,
+        None,
+    )
+}
+
+/// The pool, the chain and the transaction included in block 1.
+type SampleState = (Arc<Mutex<Vec<Transaction>>>, Arc<Mutex<BlockChain>>, Transaction);
+
+fn sample_state() -> SampleState {
+    let mut chain = BlockChain::new();
+    chain.genesis();
+    let tx = sample_transaction(1);
+    let genesis_hash = chain.last().unwrap().hash.clone();
+    chain.add_block(Block::new(1, vec![tx.clone()], genesis_hash, VALIDATOR.to_string(), 42));
+
+    let pool = vec![sample_transaction(2)];
+    (Arc::new(Mutex::new(pool)), Arc::new(Mutex::new(chain)), tx)
+}
+
+fn call(method: &str, params: Value) -> Value {
+    let (pool, chain, _) = sample_state();
+    eth::handle(method, params, &pool, &chain).expect("call should succeed")
+}
+
+#[test]
+fn test_quantity_encoding() {
+    assert_eq!(eth::quantity(0), "0x0");
+    assert_eq!(eth::quantity(1024), "0x400");
+    assert_eq!(eth::parse_quantity(&json!("0x400")), Some(1024));
+    assert_eq!(eth::parse_quantity(&json!("0x0")), Some(0));
+    // Leading zeros and missing prefixes are not valid quantities
+    assert_eq!(eth::parse_quantity(&json!("0x0400")), None);
+    assert_eq!(eth::parse_quantity(&json!("400")), None);
+    assert_eq!(eth::hash_data("0").len(), 66);
+}
+
+#[test]
+fn test_chain_identity() {
+    assert_eq!(call("eth_chainId", json!([])), json!("0x798465"));
+    assert_eq!(call("net_version", json!([])), json!("7963749"));
+    assert!(call("web3_clientVersion", json!([])).as_str().unwrap().starts_with("synergy-testnet/"));
+}
+
+#[test]
+fn test_blocks_use_hex_fields() {
+    assert_eq!(call("eth_blockNumber", json!([])), json!("0x1"));
+
+    // Same shape the explorer indexer reads
+    let block = call("eth_getBlockByNumber", json!(["0x1", false]));
+    assert_eq!(block["number"], json!("0x1"));
+    assert!(block["hash"].as_str().unwrap().starts_with("0x"));
+    assert!(block["timestamp"].as_str().unwrap().starts_with("0x"));
+    assert_eq!(block["transactions"].as_array().unwrap().len(), 1);
+    assert!(block["transactions"][0].is_string());
+
+    let by_hash = call("eth_getBlockByHash", json!([block["hash"], true]));
+    assert_eq!(by_hash["number"], json!("0x1"));
+    assert_eq!(by_hash["transactions"][0]["value"], json!("0x1f4"));
+
+    assert_eq!(call("eth_getBlockByNumber", json!(["earliest", false]))["number"], json!("0x0"));
+    assert_eq!(call("eth_getBlockByNumber", json!(["0x9", false])), Value::Null);
+}
+
+#[test]
+fn test_transactions_and_receipts() {
+    let (pool, chain, tx) = sample_state();
+    let hash = eth::hash_data(&tx.hash());
+
+    let found = eth::handle("eth_getTransactionByHash", json!([hash]), &pool, &chain).unwrap();
+    assert_eq!(found["blockNumber"], json!("0x1"));
+    assert_eq!(found["gasPrice"], json!("0x7d0"));
+
+    let receipt = eth::handle("eth_getTransactionReceipt", json!([hash]), &pool, &chain).unwrap();
+    assert_eq!(receipt["status"], json!("0x1"));
+    assert_eq!(receipt["gasUsed"], json!("0x5208"));
+
+    let pending_hash = eth::hash_data(&pool.lock().unwrap()[0].hash());
+    let pending = eth::handle("eth_getTransactionByHash", json!([pending_hash]), &pool, &chain).unwrap();
+    assert_eq!(pending["blockHash"], Value::Null);
+    assert_eq!(eth::handle("eth_getTransactionReceipt", json!([pending_hash]), &pool, &chain).unwrap(), Value::Null);
+
+    // Counts come from the account nonces, not from the transactions in blocks
+    let sender = tx.sender.clone();
+    *NONCE_REGISTRY.lock().unwrap() = NonceRegistry::from_chain(&chain.lock().unwrap());
+    pool.lock().unwrap().push(sample_transaction(4));
+    assert_eq!(eth::handle("eth_getTransactionCount", json!([sender, "latest"]), &pool, &chain).unwrap(), json!("0x1"));
+    assert_eq!(eth::handle("eth_getTransactionCount", json!([sender, "pending"]), &pool, &chain).unwrap(), json!("0x2"), "nonce 4 waits for 3");
+    assert_eq!(eth::handle("eth_getTransactionCount", json!([sender, "earliest"]), &pool, &chain).unwrap(), json!("0x0"));
+    assert_eq!(eth::handle("eth_getTransactionCount", json!([sender, "0x1"]), &pool, &chain).unwrap(), json!("0x1"));
+    assert_eq!(eth::handle("eth_gasPrice", json!([]), &pool, &chain).unwrap(), json!("0x7d0"));
+}
+
+#[test]
+fn test_addresses_use_the_ethereum_form() {
+    let (pool, chain, tx) = sample_state();
+    let block = call("eth_getBlockByNumber", json!(["0x1", true]));
+    assert_eq!(block["miner"], json!("0x000f43dd43e8a3ffa378899067449320cb15b085"));
+    assert_eq!(block["transactions"][0]["from"], json!("0x004b45e8d59f59f51377d2a6847b92f242e73527"));
+    assert_eq!(block["transactions"][0]["to"], json!("0x005c7ae6ccf388391abafd1b6521c2904a8e2fb3"));
+
+    // Names that are not addresses are left out
+    assert!(call("eth_getBlockByNumber", json!(["earliest", false])).get("miner").is_none());
+
+    assert_eq!(eth::synergy_address("0x004B45E8D59F59F51377D2A6847B92F242E73527").as_deref(), Some(tx.sender.as_str()));
+    assert_eq!(eth::synergy_address(&tx.sender).as_deref(), Some(tx.sender.as_str()));
+    assert_eq!(eth::synergy_address("0x114b45e8d59f59f51377d2a6847b92f242e73527"), None);
+    assert_eq!(eth::synergy_address("0x004b45e8"), None);
+
+    *NONCE_REGISTRY.lock().unwrap() = NonceRegistry::from_chain(&chain.lock().unwrap());
+    let count = |address: &str| eth::handle("eth_getTransactionCount", json!([address, "latest"]), &pool, &chain);
+    assert_eq!(count(&eth::eth_address(&tx.sender).unwrap()).unwrap(), count(&tx.sender).unwrap());
+    assert_eq!(count("validator-1").unwrap_err().code, -32602);
+}
+
+#[test]
+fn test_balance_is_only_kept_for_the_latest_block() {
+    let (pool, chain, tx) = sample_state();
+    let balance = |tag: &str| eth::handle("eth_getBalance", json!([tx.sender, tag]), &pool, &chain);
+    assert!(balance("latest").is_ok());
+    assert!(balance("pending").is_ok());
+    for tag in ["earliest", "finalized", "0x0"] {
+        assert_eq!(balance(tag).unwrap_err().code, -32602, "{}", tag);
+    }
+}
+
+#[test]
+fn test_invalid_block_tag() {
+    let (pool, chain, _) = sample_state();
+    let error = eth::handle("eth_getBlockByNumber", json!(["tomorrow", false]), &pool, &chain).unwrap_err();
+    assert_eq!(error.code, -32602);
+}
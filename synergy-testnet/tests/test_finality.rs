@@ -0,0 +1,95 @@
+use synergy_testnet::block::{Block, BlockChain};
+use synergy_testnet::consensus::consensus_algorithm::quorum_height;
+use synergy_testnet::crypto::keys::KeyPair;
+use synergy_testnet::p2p::networking::PeerHeads;
+use synergy_testnet::p2p::protocol::HeadVote;
+use synergy_testnet::validator::Validator;
+
+fn chain(height: u64) -> BlockChain {
+    let mut chain = BlockChain::new();
+    chain.genesis_for("finality-genesis");
+    for index in 1..=height {
+        let parent = chain.last().unwrap().hash.clone();
+        chain.add_block(Block::new(index, vec![], parent, "validator".to_string(), index));
+    }
+    chain
+}
+
+fn validators(count: usize) -> (Vec<KeyPair>, Vec<Validator>) {
+    let keys: Vec<KeyPair> = (0..count).map(|_| KeyPair::generate()).collect();
+    let validators = keys
+        .iter()
+        .enumerate()
+        .map(|(index, key)| Validator::new(key.address(), key.public_key_hex(), format!("validator-{}", index), 1000))
+        .collect();
+    (keys, validators)
+}
+
+fn addresses(validators: &[Validator]) -> Vec<String> {
+    validators.iter().map(|validator| validator.address.clone()).collect()
+}
+
+/// A head vote by `key` for our block at `height`.
+fn vote(chain: &BlockChain, key: &KeyPair, height: u64) -> HeadVote {
+    HeadVote::sign(key, &key.address(), height, &chain.chain[height as usize].hash)
+}
+
+fn peer(votes: Vec<HeadVote>) -> PeerHeads {
+    PeerHeads { address: "127.0.0.1:30303".to_string(), votes }
+}
+
+#[test]
+fn test_a_node_holding_every_key_finalizes_its_head() {
+    let chain = chain(5);
+    let (_, all) = validators(1);
+    assert_eq!(quorum_height(&chain, &all, &addresses(&all), &[]), 5);
+}
+
+#[test]
+fn test_finality_needs_more_than_two_thirds() {
+    let chain = chain(5);
+    let (keys, all) = validators(4);
+    let local = &addresses(&all)[..1];
+
+    // Us and one peer are only half of the validators
+    let peers = vec![peer(vec![vote(&chain, &keys[1], 5)])];
+    assert_eq!(quorum_height(&chain, &all, local, &peers), 0);
+
+    // A third validator at height 3 makes three of four at 3 or above
+    let peers = vec![
+        peer(vec![vote(&chain, &keys[1], 5)]),
+        peer(vec![vote(&chain, &keys[2], 2), vote(&chain, &keys[2], 3)]),
+    ];
+    assert_eq!(quorum_height(&chain, &all, local, &peers), 3);
+}
+
+#[test]
+fn test_heads_off_our_chain_do_not_count() {
+    let chain = chain(5);
+    let (keys, all) = validators(3);
+    let local = &addresses(&all)[..1];
+
+    let fork = HeadVote::sign(&keys[1], &all[1].address, 4, "fork");
+    let ahead = HeadVote::sign(&keys[2], &all[2].address, 9, "ahead");
+    let peers = vec![peer(vec![fork]), peer(vec![vote(&chain, &keys[2], 4), ahead])];
+    assert_eq!(quorum_height(&chain, &all, local, &peers), 0);
+
+    // One peer may pass on the votes of several validators
+    let peers = vec![peer(vec![vote(&chain, &keys[1], 4), vote(&chain, &keys[2], 4)])];
+    assert_eq!(quorum_height(&chain, &all, local, &peers), 4);
+}
+
+#[test]
+fn test_votes_need_the_validators_signature() {
+    let chain = chain(5);
+    let (keys, all) = validators(3);
+    let local = &addresses(&all)[..1];
+
+    // A peer cannot vote for validators whose keys it does not hold
+    let forged = HeadVote::sign(&keys[0], &all[1].address, 5, &chain.chain[5].hash);
+    let mut tampered = vote(&chain, &keys[2], 3);
+    tampered.height = 5;
+    tampered.hash = chain.chain[5].hash.clone();
+    let peers = vec![peer(vec![forged, tampered])];
+    assert_eq!(quorum_height(&chain, &all, local, &peers), 0);
+}
@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use synergy_testnet::block::BlockChain;
+use synergy_testnet::config::NodeConfig;
+use synergy_testnet::genesis::{AllocationSpec, Genesis, GenesisSpec};
+use synergy_testnet::p2p::networking::P2PNetwork;
+use synergy_testnet::token::TokenManager;
+
+const SPEC: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../config/genesis-spec.toml");
+const GENESIS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../config/genesis.json");
+
+fn temp_path(name: &str) -> String {
+    let path = std::env::temp_dir().join(format!("synergy-genesis-{}-{}", std::process::id(), name));
+    path.to_string_lossy().into_owned()
+}
+
+#[test]
+fn test_checked_in_genesis_matches_spec() {
+    let built = GenesisSpec::load(SPEC).unwrap().build().unwrap();
+    let stored = Genesis::load(GENESIS).unwrap();
+    assert_eq!(built, stored);
+    assert_eq!(built.allocated_supply("SNRG"), 12_000_000_000 * 10u64.pow(9));
+}
+
+#[test]
+fn test_hash_is_canonical_and_detects_edits() {
+    let genesis = GenesisSpec::load(SPEC).unwrap().build().unwrap();
+
+    // Formatting does not matter
+    let compact = temp_path("compact.json");
+    std::fs::write(&compact, serde_json::to_string(&genesis).unwrap()).unwrap();
+    assert_eq!(Genesis::load(&compact).unwrap().genesis_hash, genesis.genesis_hash);
+
+    // Contents do
+    let mut edited = genesis.clone();
+    edited.config.chain_id += 1;
+    let path = temp_path("edited.json");
+    std::fs::write(&path, serde_json::to_string_pretty(&edited).unwrap()).unwrap();
+    let error = Genesis::load(&path).unwrap_err().to_string();
+    assert!(error.contains("was modified"), "{}", error);
+    assert_ne!(edited.compute_hash(), genesis.genesis_hash);
+}
+
+#[test]
+fn test_spec_errors() {
+    let mut spec = GenesisSpec::load(SPEC).unwrap();
+    let holder = spec.allocations[0].address.clone();
+    spec.allocations.push(AllocationSpec { address: holder.clone(), token: "USDX".to_string(), amount: 1 });
+    spec.allocations.push(AllocationSpec { address: holder, token: "SNRG".to_string(), amount: u64::MAX });
+    spec.allocations.push(AllocationSpec { address: "sYnQ1abc".to_string(), token: "SNRG".to_string(), amount: 1 });
+    spec.validators[1].public_key = "8a3f7a9e5c3b2d1c".to_string();
+    spec.validators[2].public_key = spec.validators[0].public_key.clone();
+    spec.consensus.parameters.reward_weighting.uptime = 0.9;
+
+    let errors = spec.build().unwrap_err();
+    assert_eq!(errors.len(), 6, "{:?}", errors);
+    assert!(errors.iter().any(|e| e.contains("unknown token USDX")));
+    assert!(errors.iter().any(|e| e.contains("does not fit in 64 bits")));
+    assert!(errors.iter().any(|e| e.contains("'sYnQ1abc' is not a Synergy address")));
+    assert!(errors.iter().any(|e| e.contains("public_key must be a hex ML-DSA-87 public key")));
+    assert!(errors.iter().any(|e| e.contains("address does not belong to public_key")));
+    assert!(errors.iter().any(|e| e.contains("rewardWeighting must sum to 1")));
+}
+
+#[test]
+fn test_tokens_come_from_genesis() {
+    let genesis = Genesis::load(GENESIS).unwrap();
+    let tokens = TokenManager::new();
+    tokens.apply_genesis(&genesis).unwrap();
+
+    assert_eq!(tokens.total_supply("SNRG"), genesis.allocated_supply("SNRG"));
+    for (address, balances) in &genesis.alloc {
+        assert_eq!(tokens.get_balance(address, "SNRG"), balances["SNRG"]);
+    }
+    assert!(tokens.apply_genesis(&genesis).is_err());
+}
+
+#[test]
+fn test_peers_on_another_genesis_are_banned() {
+    let genesis = Genesis::load(GENESIS).unwrap();
+    let mut chain = BlockChain::new();
+    chain.genesis_for(&genesis.genesis_hash);
+    assert_eq!(chain.genesis_hash(), Some(genesis.genesis_hash.as_str()));
+    assert!(chain.verify().is_ok());
+
+    let config = NodeConfig::default();
+    let network = P2PNetwork::new(Arc::new(Mutex::new(chain)), &config);
+    let chain_id = config.blockchain.chain_id;
+
+    assert!(network.accept_handshake("203.0.113.1:30303", chain_id, &genesis.genesis_hash).is_ok());
+
+    assert!(network.accept_handshake("203.0.113.2:30303", chain_id, "00ff").is_err());
+    assert!(network.accept_handshake("203.0.113.3:30303", chain_id + 1, &genesis.genesis_hash).is_err());
+
+    // The ban covers the IP, so another port does not get around it
+    assert_eq!(network.accept_handshake("203.0.113.2:30304", chain_id, &genesis.genesis_hash), Err("banned".to_string()));
+
+    // Nodes on a private network may share an IP, so only the banned one is refused
+    assert!(network.accept_handshake("10.0.0.2:30303", chain_id, "00ff").is_err());
+    assert!(network.accept_handshake("10.0.0.2:30304", chain_id, &genesis.genesis_hash).is_ok());
+
+    let banned: HashMap<String, String> =
+        network.get_banned_peers().into_iter().map(|ban| (ban.address, ban.reason)).collect();
+    assert!(!banned.contains_key("203.0.113.1"));
+    assert!(banned["203.0.113.2"].contains("genesis 00ff"));
+    assert!(banned["203.0.113.3"].contains("chain id"));
+    assert!(banned.contains_key("10.0.0.2:30303"));
+    for peer in ["203.0.113.2", "203.0.113.3", "10.0.0.2:30303"] {
+        network.unban_peer(peer);
+    }
+}
@@ -0,0 +1,79 @@
+mod common;
+
+use std::time::Duration;
+use synergy_testnet::config::NodeConfig;
+use synergy_testnet::events::{NodeEvent, EVENT_BUS};
+use synergy_testnet::genesis::Genesis;
+use synergy_testnet::rpc::grpc_server::proto;
+use synergy_testnet::rpc::grpc_server::proto::synergy_node_client::SynergyNodeClient;
+use synergy_testnet::token::TOKEN_MANAGER;
+use synergy_testnet::transaction::Transaction;
+use tonic::Code;
+
+const GENESIS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../config/genesis.json");
+fn start_test_server() -> String {
+    let mut config = NodeConfig::default().rpc;
+    config.enable_http = false;
+    config.enable_ws = false;
+    config.enable_grpc = true;
+
+    // SNRG and its allocations come from the genesis file, as on a node
+    TOKEN_MANAGER.apply_genesis(&Genesis::load(GENESIS).unwrap()).unwrap();
+    format!("http://{}", common::start_test_server(config, 0))
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_grpc_server() {
+    let endpoint = start_test_server();
+    let mut client = SynergyNodeClient::connect(endpoint).await.expect("Failed to reach gRPC server");
+
+    // Unary queries share the JSON-RPC handlers
+    let tokens = client.list_tokens(proto::ListTokensRequest {}).await.unwrap().into_inner();
+    assert!(tokens.tokens.iter().any(|t| t.symbol == "SNRG"));
+
+    let missing = client
+        .get_block_by_number(proto::GetBlockByNumberRequest { number: 1_000_000 })
+        .await
+        .unwrap_err();
+    assert_eq!(missing.code(), Code::NotFound);
+
+    // JSON-RPC error categories map onto gRPC status codes
+    let rejected = client.send_transaction(proto::Transaction::default()).await.unwrap_err();
+    assert_eq!(rejected.code(), Code::FailedPrecondition);
+
+    let unknown_channel = client
+        .stream_events(proto::StreamEventsRequest { channels: vec!["everything".to_string()] })
+        .await
+        .unwrap_err();
+    assert_eq!(unknown_channel.code(), Code::InvalidArgument);
+
+    // Server-streaming feed forwards node events
+    let mut events = client
+        .stream_events(proto::StreamEventsRequest { channels: vec!["pendingTransactions".to_string()] })
+        .await
+        .unwrap()
+        .into_inner();
+
+    let tx = Transaction::new(
+        "sYn4b45e8d59f59f51377d2a6847b92f242e73527".to_string(),
+        "sYn5c7ae6ccf388391abafd1b6521c2904a8e2fb3".to_string(),
+        10,
+        1,
+        "signature".to_string(),
+        1000,
+        21000,
+        None,
+    );
+    // The server subscribes before it answers the stream call
+    EVENT_BUS.publish(NodeEvent::PendingTransaction(tx.clone()));
+
+    let event = tokio::time::timeout(Duration::from_secs(5), events.message())
+        .await
+        .expect("No event received")
+        .unwrap()
+        .expect("Stream ended");
+    match event.event {
+        Some(proto::event::Event::PendingTransaction(pending)) => assert_eq!(pending.hash, tx.hash()),
+        other => panic!("unexpected event: {:?}", other),
+    }
+}
@@ -0,0 +1,53 @@
+mod common;
+
+use synergy_testnet::config::{HealthConfig, NodeConfig};
+use synergy_testnet::health::{self, HealthReport, HealthStatus};
+
+fn start_test_server() -> String {
+    let mut config = NodeConfig::default().rpc;
+    config.enable_ws = false;
+    config.enable_grpc = false;
+    format!("http://{}", common::start_test_server(config, 0))
+}
+
+#[test]
+fn test_health_checks() {
+    std::fs::create_dir_all("data").unwrap();
+    health::consensus_heartbeat();
+
+    let report = health::check(&HealthConfig::default());
+    assert!(report.live);
+    assert!(report.storage_writable);
+    assert_eq!(report.consensus_heartbeat_age_secs, Some(0));
+    // No P2P network runs here, so nothing is known about peers
+    assert_eq!(report.sync_lag, None);
+    assert_eq!(report.clock_drift_secs, None);
+
+    // A missing data directory cannot be written to
+    let config = HealthConfig { data_dir: "data/does/not/exist".to_string(), ..HealthConfig::default() };
+    let report = health::check(&config);
+    assert!(!report.storage_writable);
+    assert!(!report.ready);
+    assert_eq!(report.status, HealthStatus::Degraded);
+    assert!(report.failures.iter().any(|failure| failure.contains("not writable")));
+}
+
+#[test]
+fn test_health_endpoints() {
+    let url = start_test_server();
+    let client = reqwest::blocking::Client::new();
+    health::consensus_heartbeat();
+
+    let response = client.get(format!("{}/live", url)).send().unwrap();
+    assert_eq!(response.status(), 200);
+
+    let response = client.get(format!("{}/health", url)).send().unwrap();
+    assert_eq!(response.status(), 200);
+    let report: HealthReport = response.json().unwrap();
+    assert!(report.live);
+
+    // Readiness follows the report
+    let response = client.get(format!("{}/ready", url)).send().unwrap();
+    let expected = if report.ready { 200 } else { 503 };
+    assert_eq!(response.status(), expected);
+}
@@ -0,0 +1,119 @@
+use std::path::PathBuf;
+
+use k256::ecdsa::Signature;
+use synergy_testnet::crypto::hybrid;
+use synergy_testnet::crypto::keys::KeyPair;
+use synergy_testnet::keystore::{KdfParams, Keystore, KeystoreFile};
+use synergy_testnet::transaction::Transaction;
+use synergy_testnet::wallet::WalletManager;
+
+const TEST_KDF: KdfParams = KdfParams { memory_kib: 1024, iterations: 1, parallelism: 1 };
+
+// Private key and address from the web3.js account documentation
+const ETH_PRIVATE_KEY: &str = "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+const ETH_ADDRESS: &str = "0x2c7536e3605d9c16a7a3d7b1898e529396a65c23";
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("synergy-hybrid-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn transfer(sender: &str) -> Transaction {
+    Transaction::new(sender.to_string(), WalletManager::generate_address("receiver"), 10, 1, String::new(), 1000, 21000, None)
+}
+
+#[test]
+fn test_hybrid_address() {
+    let secp256k1 = hybrid::parse_private_key(ETH_PRIVATE_KEY).unwrap();
+    assert_eq!(hybrid::eth_address(&secp256k1), ETH_ADDRESS);
+    assert!(hybrid::parse_private_key("0x1234").is_err());
+
+    let mldsa = KeyPair::generate();
+    let keypair = mldsa.clone().with_secp256k1(secp256k1);
+    assert!(keypair.is_hybrid() && !mldsa.is_hybrid());
+    let secp256k1_public_key = keypair.secp256k1_public_key_hex().unwrap();
+    assert_eq!(secp256k1_public_key.len(), 2 * hybrid::SECP256K1_PUBLIC_KEY_BYTES);
+
+    // The address binds both keys
+    assert_eq!(keypair.address(), hybrid::address(&mldsa.public_key_hex(), &secp256k1_public_key));
+    assert_ne!(keypair.address(), mldsa.address());
+    assert_ne!(keypair.address(), mldsa.clone().with_secp256k1(hybrid::generate_key()).address());
+    assert_ne!(keypair.address(), KeyPair::generate().with_secp256k1(keypair.secp256k1_key().unwrap().clone()).address());
+}
+
+#[test]
+fn test_hybrid_signatures() {
+    let keypair = KeyPair::generate().with_secp256k1(hybrid::generate_key());
+    let mut tx = transfer(&keypair.address());
+    tx.sign(&keypair);
+    assert_eq!(tx.sender, keypair.address());
+    assert!(tx.validate().is_valid, "{:?}", tx.validate().error_message);
+    let digest = hex::decode(tx.hash()).unwrap();
+    let secp256k1 = tx.hybrid.clone().unwrap();
+    assert!(hybrid::verify(&secp256k1.public_key, &digest, &secp256k1.signature));
+
+    // Neither signature suffices alone
+    let mut mldsa_only = tx.clone();
+    mldsa_only.hybrid = None;
+    assert!(!mldsa_only.verify_signature());
+    let mut no_mldsa = tx.clone();
+    no_mldsa.signature.clear();
+    assert!(!no_mldsa.verify_signature());
+
+    // The secp256k1 signature must be by the account's key and over this transaction
+    let other = KeyPair::generate().with_secp256k1(hybrid::generate_key());
+    let mut other_tx = transfer(&other.address());
+    other_tx.sign(&other);
+    let mut swapped = tx.clone();
+    swapped.hybrid.as_mut().unwrap().signature = other_tx.hybrid.unwrap().signature;
+    assert_eq!(swapped.validate().error_message.as_deref(), Some("Invalid secp256k1 signature"));
+    let mut wrong_key = tx.clone();
+    wrong_key.hybrid.as_mut().unwrap().public_key = other.secp256k1_public_key_hex().unwrap();
+    assert!(!wrong_key.verify_signature());
+
+    // The malleated high-s twin of the signature is rejected
+    let signature = Signature::from_slice(&hex::decode(&secp256k1.signature).unwrap()).unwrap();
+    let (r, s) = signature.split_scalars();
+    let high_s = Signature::from_scalars(r, -s).unwrap();
+    assert!(!hybrid::verify(&secp256k1.public_key, &digest, &hex::encode(high_s.to_bytes())));
+}
+
+#[test]
+fn test_hybrid_keystore() {
+    let keystore = Keystore::new(temp_dir("keystore")).with_kdf(TEST_KDF);
+    let keypair = KeyPair::generate().with_secp256k1(hybrid::parse_private_key(ETH_PRIVATE_KEY).unwrap());
+    let entry = keystore.add(&keypair, "correct horse").unwrap();
+    assert_eq!(entry.address, keypair.address());
+
+    let (path, file) = keystore.find(&entry.address).unwrap();
+    assert_eq!(file.algorithm, hybrid::ALGORITHM);
+    assert_eq!(file.secp256k1_public_key, keypair.secp256k1_public_key_hex());
+    let unlocked = keystore.unlock(&entry.address, "correct horse").unwrap();
+    assert_eq!(unlocked.address(), keypair.address());
+    assert_eq!(hybrid::eth_address(unlocked.secp256k1_key().unwrap()), ETH_ADDRESS);
+
+    // Swapping in another secp256k1 key breaks the address binding
+    let mut tampered = file.clone();
+    tampered.secp256k1_public_key = Some(hybrid::public_key_hex(&hybrid::generate_key()));
+    tampered.save(&path).unwrap();
+    assert!(KeystoreFile::load(&path).is_err());
+    file.save(&path).unwrap();
+
+    let export = keystore.export(&entry.address, "correct horse").unwrap();
+    assert!(export.secp256k1_private_key.is_some());
+    let other = Keystore::new(temp_dir("import")).with_kdf(TEST_KDF);
+    assert_eq!(other.import(&export, "battery staple").unwrap().address, entry.address);
+
+    // Node wallets sign with both keys
+    let mut manager = WalletManager::new();
+    manager.open_keystore(Keystore::new(temp_dir("wallet")).with_kdf(TEST_KDF)).unwrap();
+    let address = manager.create_hybrid_wallet("correct horse").unwrap();
+    manager.unlock_wallet(&address, "correct horse", None).unwrap();
+    let mut tx = transfer(&address);
+    manager.sign_transaction(&address, &mut tx).unwrap();
+    assert!(tx.hybrid.is_some());
+    assert!(tx.validate().is_valid);
+    assert!(manager.verify_signature(&tx));
+}
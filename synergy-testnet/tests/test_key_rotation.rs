@@ -0,0 +1,187 @@
+use std::path::PathBuf;
+
+use synergy_testnet::block::{Block, BlockChain};
+use synergy_testnet::crypto::keys::KeyPair;
+use synergy_testnet::crypto::multisig::MultisigPolicy;
+use synergy_testnet::crypto::{SignatureAlgorithm, Signer};
+use synergy_testnet::key_rotation::{KeyRegistry, KeyScope, RotateKey, ACCOUNT_ROTATION_DELAY};
+use synergy_testnet::keystore::{KdfParams, Keystore};
+use synergy_testnet::transaction::Transaction;
+use synergy_testnet::tx_file::{self, TxOp};
+use synergy_testnet::wallet::WalletManager;
+
+const EPOCH_LENGTH: u64 = 10;
+const TEST_KDF: KdfParams = KdfParams { memory_kib: 1024, iterations: 1, parallelism: 1 };
+
+fn receiver() -> String {
+    WalletManager::generate_address("receiver")
+}
+
+fn transfer(sender: &str, nonce: u64) -> Transaction {
+    Transaction::new(sender.to_string(), receiver(), 10, nonce, String::new(), 1, 1, None)
+}
+
+fn rotation(keypair: &KeyPair, scope: KeyScope, algorithm: SignatureAlgorithm, public_key: &[u8], nonce: u64) -> Transaction {
+    let data = RotateKey { scope, algorithm, public_key: hex::encode(public_key) }.to_data();
+    let mut tx = Transaction::new(keypair.address(), keypair.address(), 0, nonce, String::new(), 1, 1, Some(data));
+    tx.sign(keypair);
+    tx
+}
+
+/// A chain whose block `height` holds `transactions` for each entry, with
+/// empty blocks in between.
+fn build_chain(length: u64, blocks: Vec<(u64, Vec<Transaction>)>) -> BlockChain {
+    let mut chain = BlockChain::new();
+    chain.genesis();
+    for index in 1..length {
+        let transactions = blocks.iter().find(|(height, _)| *height == index).map_or(vec![], |(_, txs)| txs.clone());
+        let previous_hash = chain.last().unwrap().hash.clone();
+        chain.add_block(Block::new(index, transactions, previous_hash, "validator".to_string(), index));
+    }
+    chain
+}
+
+#[test]
+fn test_account_rotation_takes_effect_after_delay() {
+    let owner = KeyPair::generate();
+    let (falcon_public, falcon_private) = SignatureAlgorithm::Falcon512.generate_keypair();
+    let rotate = rotation(&owner, KeyScope::Account, SignatureAlgorithm::Falcon512, &falcon_public, 1);
+    assert!(rotate.is_key_rotation());
+    assert!(rotate.validate().is_valid, "a rotation moves no funds but is a valid transaction");
+
+    let keys = KeyRegistry::from_chain(&build_chain(3, vec![(1, vec![rotate.clone()])]), EPOCH_LENGTH);
+    let effective = 1 + ACCOUNT_ROTATION_DELAY;
+    let history = keys.history(&owner.address());
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].algorithm, SignatureAlgorithm::Falcon512);
+    assert_eq!(history[0].previous_algorithm, SignatureAlgorithm::MlDsa87);
+    assert_eq!(history[0].previous_public_key, owner.public_key_hex());
+    assert_eq!(history[0].transaction_hash, rotate.hash());
+    assert_eq!((history[0].block_height, history[0].effective_height), (1, effective));
+    assert_eq!(keys.activated_at(effective).len(), 1);
+
+    let mut old = transfer(&owner.address(), 2);
+    old.sign(&owner);
+    let mut new = transfer(&owner.address(), 2);
+    new.sign_with(SignatureAlgorithm::Falcon512, &falcon_public, &falcon_private).unwrap();
+    assert_eq!(new.sender, owner.address(), "signing with a rotated key keeps the sender");
+
+    // The old key signs until the delay has passed, the new one from then on
+    assert!(old.check_signature_at(&keys, effective - 1).is_ok());
+    assert!(new.check_signature_at(&keys, effective - 1).is_err());
+    assert!(old.check_signature_at(&keys, effective).is_err());
+    assert!(new.check_signature_at(&keys, effective).is_ok());
+
+    // Without the rotation on chain the new key does not belong to the sender
+    assert!(!new.verify_signature());
+}
+
+#[test]
+fn test_pending_rotation_can_be_superseded() {
+    let owner = KeyPair::generate();
+    let stolen = KeyPair::generate();
+    let recovery = KeyPair::generate();
+    let hijack = rotation(&owner, KeyScope::Account, SignatureAlgorithm::MlDsa87, stolen.public_key(), 1);
+    let recover = rotation(&owner, KeyScope::Account, SignatureAlgorithm::MlDsa87, recovery.public_key(), 2);
+
+    let keys = KeyRegistry::from_chain(&build_chain(6, vec![(2, vec![hijack]), (5, vec![recover])]), EPOCH_LENGTH);
+    let history = keys.history(&owner.address());
+    assert_eq!(history.len(), 2);
+    assert!(history[0].superseded && !history[1].superseded);
+
+    // The superseded key never comes into force
+    assert!(keys.account_key(&owner.address(), 2 + ACCOUNT_ROTATION_DELAY).is_none());
+    let current = keys.account_key(&owner.address(), 5 + ACCOUNT_ROTATION_DELAY).unwrap();
+    assert_eq!(current.public_key, recovery.public_key_hex());
+}
+
+#[test]
+fn test_consensus_rotation_waits_for_next_epoch() {
+    let validator = KeyPair::generate();
+    let new_key = KeyPair::generate();
+    let rotate = rotation(&validator, KeyScope::Consensus, SignatureAlgorithm::MlDsa87, new_key.public_key(), 1);
+
+    let keys = KeyRegistry::from_chain(&build_chain(5, vec![(3, vec![rotate])]), EPOCH_LENGTH);
+    assert_eq!(keys.effective_height(KeyScope::Consensus, 3), EPOCH_LENGTH);
+    assert_eq!(keys.effective_height(KeyScope::Consensus, EPOCH_LENGTH), 2 * EPOCH_LENGTH);
+    assert!(keys.consensus_key(&validator.address(), EPOCH_LENGTH - 1).is_none());
+    let current = keys.consensus_key(&validator.address(), EPOCH_LENGTH).unwrap();
+    assert_eq!(current.public_key, new_key.public_key_hex());
+    assert_eq!(keys.activated_at(EPOCH_LENGTH).len(), 1);
+
+    // Blocks from the new epoch are signed with the new key
+    let mut block = Block::new(EPOCH_LENGTH, vec![], "parent".to_string(), validator.address(), 1);
+    block.sign(&new_key);
+    assert!(block.verify_signature(&current.public_key));
+    assert!(!block.verify_signature(&current.previous_public_key));
+
+    // The account key is untouched
+    assert!(keys.account_key(&validator.address(), 1000).is_none());
+}
+
+#[test]
+fn test_invalid_rotations_are_rejected() {
+    let owner = KeyPair::generate();
+    let keys = KeyRegistry::new(EPOCH_LENGTH);
+
+    let same = rotation(&owner, KeyScope::Account, SignatureAlgorithm::MlDsa87, owner.public_key(), 1);
+    assert!(keys.check_rotation(&same, 1).is_err());
+
+    let short = rotation(&owner, KeyScope::Account, SignatureAlgorithm::Falcon512, owner.public_key(), 1);
+    assert!(keys.check_rotation(&short, 1).unwrap_err().contains("bytes"));
+
+    // Blocks and head votes are ML-DSA-87 signatures
+    let (falcon_public, _) = SignatureAlgorithm::Falcon512.generate_keypair();
+    let falcon = rotation(&owner, KeyScope::Consensus, SignatureAlgorithm::Falcon512, &falcon_public, 1);
+    assert!(keys.check_rotation(&falcon, 1).unwrap_err().contains("ML-DSA-87"));
+
+    let mut multisig = rotation(&owner, KeyScope::Account, SignatureAlgorithm::MlDsa87, KeyPair::generate().public_key(), 1);
+    multisig.set_multisig(MultisigPolicy::new(1, vec![owner.public_key_hex()]).unwrap());
+    assert!(keys.check_rotation(&multisig, 1).is_err());
+
+    assert!(keys.check_rotation(&transfer(&owner.address(), 1), 1).is_err());
+    assert!(!transfer(&owner.address(), 1).is_key_rotation());
+}
+
+#[test]
+fn test_chain_verification_follows_rotations() {
+    let owner = KeyPair::generate();
+    let new_key = KeyPair::generate();
+    let rotate = rotation(&owner, KeyScope::Account, SignatureAlgorithm::MlDsa87, new_key.public_key(), 1);
+    let height = 1 + ACCOUNT_ROTATION_DELAY;
+
+    let mut rotated = transfer(&owner.address(), 2);
+    rotated.sign_with(SignatureAlgorithm::MlDsa87, new_key.public_key(), new_key.private_key()).unwrap();
+    let chain = build_chain(height + 1, vec![(1, vec![rotate.clone()]), (height, vec![rotated])]);
+    assert!(chain.verify().is_ok());
+
+    // The old key no longer signs once the rotation is in force
+    let mut stale = transfer(&owner.address(), 2);
+    stale.sign(&owner);
+    let chain = build_chain(height + 1, vec![(1, vec![rotate]), (height, vec![stale])]);
+    assert_eq!(chain.valid_prefix_len(), height as usize);
+}
+
+#[test]
+fn test_offline_rotation_and_signing() {
+    let dir = std::env::temp_dir().join(format!("synergy-rotation-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let keystore = Keystore::new(PathBuf::from(&dir)).with_kdf(TEST_KDF);
+    let owner = keystore.create("correct horse").unwrap();
+    let new_key = keystore.create("correct horse").unwrap();
+    let (_, new_file) = keystore.find(&new_key.address).unwrap();
+
+    let op = TxOp::RotateKey(RotateKey { scope: KeyScope::Account, algorithm: SignatureAlgorithm::MlDsa87, public_key: new_file.public_key });
+    let mut tx = tx_file::build(&owner.address, &op, 0, 1, 1, 1);
+    assert_eq!(tx.receiver, owner.address);
+    tx_file::sign(&mut tx, &keystore, "correct horse").unwrap();
+    assert!(tx.verify_signature(), "the rotation is signed by the old key");
+
+    // Later transactions are signed by the new key for the old address
+    let mut later = tx_file::build(&owner.address, &TxOp::Transfer { to: receiver() }, 5, 2, 1, 1);
+    let signer = tx_file::signer(&later, &keystore, Some(&new_key.address)).unwrap();
+    tx_file::sign_as(&mut later, &keystore, &signer, "correct horse").unwrap();
+    assert_eq!(later.sender, owner.address);
+    assert!(tx_file::describe(&later).contains(&("Signature", "by a rotated key, checked by the node".to_string())));
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,123 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use synergy_testnet::crypto::keys::{self, KeyPair};
+use synergy_testnet::keystore::{KdfParams, KeyExport, Keystore, KeystoreFile};
+use synergy_testnet::transaction::Transaction;
+use synergy_testnet::wallet::WalletManager;
+
+// Cheap parameters keep the tests fast; the format is the same
+const TEST_KDF: KdfParams = KdfParams { memory_kib: 1024, iterations: 1, parallelism: 1 };
+
+fn keystore(name: &str) -> Keystore {
+    let dir: PathBuf = std::env::temp_dir().join(format!("synergy-keystore-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_dir_all(&dir);
+    Keystore::new(dir).with_kdf(TEST_KDF)
+}
+
+#[test]
+fn test_create_unlock_and_change_password() {
+    let keystore = keystore("create");
+    assert!(keystore.create("short").is_err());
+
+    let first = keystore.create("correct horse").unwrap();
+    let second = keystore.create("correct horse").unwrap();
+    assert_ne!(first.address, second.address, "keys must not collide");
+    assert_eq!(keystore.list().unwrap().len(), 2);
+
+    let keypair = keystore.unlock(&first.address, "correct horse").unwrap();
+    assert_eq!(keypair.address(), first.address);
+    assert!(!format!("{:?}", keypair).contains(&hex::encode(keypair.private_key())));
+    let error = keystore.unlock(&first.address, "wrong horse").unwrap_err().to_string();
+    assert!(error.contains("Wrong password"), "{}", error);
+
+    keystore.change_password(&first.address, "correct horse", "battery staple").unwrap();
+    assert!(keystore.unlock(&first.address, "correct horse").is_err());
+    assert!(keystore.unlock(&first.address, "battery staple").is_ok());
+}
+
+#[test]
+fn test_file_format_and_tampering() {
+    let keystore = keystore("format");
+    let entry = keystore.create("correct horse").unwrap();
+
+    let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&entry.path).unwrap()).unwrap();
+    assert_eq!(json["version"], 1);
+    assert_eq!(json["algorithm"], "ML-DSA-87");
+    assert_eq!(json["crypto"]["kdf"], "argon2id");
+    assert_eq!(json["crypto"]["cipher"], "xchacha20-poly1305");
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        assert_eq!(std::fs::metadata(&entry.path).unwrap().permissions().mode() & 0o777, 0o600);
+    }
+
+    // The public half is bound to the ciphertext
+    let mut file = KeystoreFile::load(&entry.path).unwrap();
+    let other = KeyPair::generate();
+    file.public_key = other.public_key_hex();
+    file.address = other.address();
+    let error = file.decrypt("correct horse").unwrap_err();
+    assert!(error.contains("corrupted"), "{}", error);
+
+    let mut file = KeystoreFile::load(&entry.path).unwrap();
+    file.version = 2;
+    assert!(file.decrypt("correct horse").unwrap_err().contains("Unsupported keystore version"));
+}
+
+#[test]
+fn test_export_and_import() {
+    let source = keystore("export");
+    let entry = source.create("correct horse").unwrap();
+    let export = source.export(&entry.address, "correct horse").unwrap();
+    assert_eq!(export.address, entry.address);
+
+    // Round trip through the JSON `keystore export` writes
+    let export: KeyExport = serde_json::from_str(&serde_json::to_string(&export).unwrap()).unwrap();
+    let target = keystore("import");
+    assert_eq!(target.import(&export, "new password").unwrap().address, entry.address);
+    assert!(target.import(&export, "new password").is_err(), "duplicates are refused");
+
+    let mut mismatched = export.clone();
+    mismatched.private_key = KeyExport::from_keypair(&KeyPair::generate()).private_key.clone();
+    assert!(keystore("mismatch").import(&mismatched, "new password").is_err());
+}
+
+#[test]
+fn test_wallet_signs_only_while_unlocked() {
+    let keystore = keystore("wallet");
+    let mut manager = WalletManager::new();
+    assert_eq!(manager.open_keystore(keystore).unwrap(), 0);
+
+    let address = manager.create_wallet("correct horse").unwrap();
+    let wallet = serde_json::to_value(manager.get_wallet(&address).unwrap()).unwrap();
+    assert!(wallet.get("private_key").is_none());
+
+    let receiver = WalletManager::generate_address("receiver");
+    let mut tx = Transaction::new(address.clone(), receiver, 10, 0, String::new(), 1000, 21000, None);
+    let error = manager.sign_transaction(&address, &mut tx).unwrap_err();
+    assert!(error.contains("locked"), "{}", error);
+
+    assert!(manager.unlock_wallet(&address, "wrong horse", None).is_err());
+    manager.unlock_wallet(&address, "correct horse", None).unwrap();
+    manager.sign_transaction(&address, &mut tx).unwrap();
+    assert!(manager.verify_signature(&tx));
+    let public_key = hex::decode(&manager.get_wallet(&address).unwrap().public_key).unwrap();
+    assert!(keys::verify(&public_key, tx.hash().as_bytes(), &hex::decode(&tx.signature).unwrap()));
+
+    tx.amount += 1;
+    assert!(!manager.verify_signature(&tx));
+
+    assert!(manager.lock_wallet(&address));
+    assert!(!manager.is_unlocked(&address));
+
+    // An expired unlock behaves like a locked wallet
+    manager.unlock_wallet(&address, "correct horse", Some(Duration::ZERO)).unwrap();
+    assert!(manager.sign_transaction(&address, &mut tx).is_err());
+
+    // Reopening the keystore finds the wallet again, locked
+    let mut reopened = WalletManager::new();
+    assert_eq!(reopened.open_keystore(manager.keystore().clone()).unwrap(), 1);
+    assert!(reopened.get_wallet(&address).is_some());
+    assert!(!reopened.is_unlocked(&address));
+}
@@ -0,0 +1,112 @@
+use serde_json::{json, Value};
+use synergy_testnet::config::NodeConfig;
+use synergy_testnet::logging::{self, LevelFilter, LogEntry, LogLevel, LogQuery, Logger};
+use synergy_testnet::rpc::methods;
+
+fn log_file(name: &str) -> String {
+    let path = std::env::temp_dir().join(format!("synergy-logging-{}-{}.log", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path.to_string_lossy().into_owned()
+}
+
+fn quiet_config(name: &str) -> synergy_testnet::config::LoggingConfig {
+    let mut config = NodeConfig::default().logging;
+    config.enable_console = false;
+    config.log_file = log_file(name);
+    config
+}
+
+#[test]
+fn test_module_filters() {
+    let filter = LevelFilter::parse("consensus=debug, p2p=warn", LogLevel::Info).unwrap();
+    assert_eq!(filter.level_for("consensus"), LogLevel::Debug);
+    assert_eq!(filter.level_for("consensus::vrf"), LogLevel::Debug);
+    assert_eq!(filter.level_for("p2p"), LogLevel::Warn);
+    assert_eq!(filter.level_for("rpc"), LogLevel::Info);
+    assert!(filter.enabled(LogLevel::Debug, "consensus"));
+    assert!(!filter.enabled(LogLevel::Info, "p2p"));
+
+    // A bare level replaces the default
+    let filter = LevelFilter::parse("error,rpc=trace", LogLevel::Info).unwrap();
+    assert_eq!(filter.default, LogLevel::Error);
+    assert_eq!(filter.level_for("rpc::audit"), LogLevel::Trace);
+
+    assert!(LevelFilter::parse("consensus=loud", LogLevel::Info).is_err());
+    assert!(LevelFilter::parse("=debug", LogLevel::Info).is_err());
+}
+
+#[test]
+fn test_ring_buffer_and_query() {
+    let mut config = quiet_config("buffer");
+    config.buffer_size = 3;
+    config.module_levels = "p2p=warn".to_string();
+    let logger = Logger::from_config(&config).unwrap();
+
+    logger.log(LogLevel::Info, "p2p", "filtered out");
+    for height in 1..=4 {
+        logger.log(LogLevel::Info, "consensus", &format!("block {}", height));
+    }
+    logger.log(LogLevel::Error, "rpc::audit", "denied");
+
+    // Only the newest three entries are kept
+    let messages: Vec<String> = logger.get_entries().into_iter().map(|entry| entry.message).collect();
+    assert_eq!(messages, vec!["block 3", "block 4", "denied"]);
+
+    let errors = logger.query(&LogQuery { min_level: Some(LogLevel::Warn), ..LogQuery::default() });
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].module, "rpc::audit");
+
+    let rpc = logger.query(&LogQuery { module: Some("rpc".to_string()), ..LogQuery::default() });
+    assert_eq!(rpc.len(), 1);
+
+    let newest = logger.query(&LogQuery { module: Some("consensus".to_string()), limit: Some(1), ..LogQuery::default() });
+    assert_eq!(newest[0].message, "block 4");
+
+    let future = logger.query(&LogQuery { since: Some(u64::MAX), ..LogQuery::default() });
+    assert!(future.is_empty());
+}
+
+#[test]
+fn test_json_format() {
+    let mut config = quiet_config("json");
+    config.log_format = "json".to_string();
+    let logger = Logger::from_config(&config).unwrap();
+
+    logger.log_with_metadata(LogLevel::Warn, "p2p", "Peer banned", json!({ "peer": "10.0.0.1:5622" }));
+
+    let contents = std::fs::read_to_string(&config.log_file).unwrap();
+    let line: Value = serde_json::from_str(contents.lines().next().unwrap()).unwrap();
+    assert_eq!(line["level"], "WARN");
+    assert_eq!(line["module"], "p2p");
+    assert_eq!(line["message"], "Peer banned");
+    assert_eq!(line["metadata"]["peer"], "10.0.0.1:5622");
+    assert!(line["timestamp"].is_u64());
+
+    config.log_format = "xml".to_string();
+    assert!(Logger::from_config(&config).is_err());
+}
+
+#[test]
+fn test_log_rpc_methods() {
+    logging::init_logger_from_config(&quiet_config("rpc")).unwrap();
+    synergy_testnet::debug!("consensus", "hidden at the default level");
+
+    let set_level = methods::lookup("synergy_setLogLevel").unwrap();
+    let levels = (set_level.invoke)(json!({ "level": "debug", "module": "consensus" })).unwrap();
+    assert_eq!(levels["modules"]["consensus"], "DEBUG");
+    assert!((set_level.invoke)(json!(["loud"])).is_err());
+
+    synergy_testnet::debug!("consensus", "Vote counted", "round" => 7);
+    synergy_testnet::info!("p2p", "Peer connected");
+
+    let get_logs = methods::lookup("synergy_getLogs").unwrap();
+    let entries: Vec<LogEntry> =
+        serde_json::from_value((get_logs.invoke)(json!({ "module": "consensus" })).unwrap()).unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].message, "Vote counted");
+    assert_eq!(entries[0].metadata.as_ref().unwrap()["round"], 7);
+
+    let entries: Vec<LogEntry> =
+        serde_json::from_value((get_logs.invoke)(json!({ "level": "info" })).unwrap()).unwrap();
+    assert!(entries.iter().all(|entry| entry.level >= LogLevel::Info));
+}
@@ -0,0 +1,40 @@
+use synergy_testnet::metrics;
+use synergy_testnet::rpc::error::{INVALID_PARAMS, METHOD_NOT_FOUND};
+
+#[test]
+fn test_render_exports_all_areas() {
+    let output = metrics::render();
+
+    for name in [
+        "synergy_block_height",
+        "synergy_block_interval_seconds",
+        "synergy_block_transactions",
+        "synergy_mempool_transactions",
+        "synergy_p2p_peers",
+        "synergy_ai_computations",
+        "synergy_ai_computation_duration_seconds",
+        "synergy_ai_provider_queue_size",
+    ] {
+        assert!(output.contains(&format!("# TYPE {} ", name)), "missing {}", name);
+    }
+}
+
+#[test]
+fn test_rpc_calls_by_method() {
+    metrics::record_rpc_call("synergy_getValidator", 0.01, Some(INVALID_PARAMS));
+    metrics::record_rpc_call("synergy_madeUpMethod", 0.01, Some(METHOD_NOT_FOUND));
+
+    let output = metrics::render();
+    assert!(output.contains(r#"synergy_rpc_request_duration_seconds_count{method="synergy_getValidator"}"#));
+    assert!(output.contains(r#"synergy_rpc_errors_total{code="-32602",method="synergy_getValidator"} 1"#));
+    assert!(output.contains(r#"method="unknown""#));
+    assert!(!output.contains("synergy_madeUpMethod"));
+}
+
+#[test]
+fn test_block_metrics() {
+    metrics::record_block(42, 3, Some(5.0));
+
+    assert!(metrics::BLOCK_TRANSACTIONS.get_sample_count() >= 1);
+    assert!(metrics::BLOCK_INTERVAL_SECONDS.get_sample_sum() >= 5.0);
+}
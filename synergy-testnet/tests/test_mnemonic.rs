@@ -0,0 +1,102 @@
+use serde_json::Value;
+use synergy_testnet::crypto::hd::{self, HdSeed};
+use synergy_testnet::crypto::mldsa;
+use synergy_testnet::keystore::{KdfParams, Keystore};
+use synergy_testnet::wallet::WalletManager;
+
+// The BIP-39 test phrase and the accounts it derives
+const PHRASE: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+const ACCOUNT_0_SEED: &str = "a832b399e70bf7a708abf16141aa314680c6ed14c9d4feca8f61678ffc348c12";
+const ACCOUNT_0_PUBLIC_KEY_PREFIX: &str = "3e49fbee6296ae4f82afa13f521ccda24ae104eeac4f2829b7a4b3817b764c13";
+const ADDRESSES: [&str; 3] = [
+    "sYn796f449a845250c3d42c2b5c815150992b0751",
+    "sYn0ff2b4285bef20ad499fdafa6639b6ea20e765",
+    "sYn04582149267401fdf2ff48f547d551e8e40d8a",
+];
+const TREZOR_ADDRESS: &str = "sYnb3adf744cd5e864d89309f38ade61751c06363";
+
+// Count 0 of the ML-DSA-87 NIST KAT built from PQClean's nistkat harness, whose
+// output matches the SHA-256 PQClean records; the seed is the 32 bytes the KAT's
+// DRBG hands to key generation
+const KEYGEN_VECTOR: &str = include_str!("data/mldsa87_keygen.json");
+
+const TEST_KDF: KdfParams = KdfParams { memory_kib: 1024, iterations: 1, parallelism: 1 };
+
+#[test]
+fn test_derivation_vectors() {
+    let mnemonic = hd::parse_mnemonic(PHRASE).unwrap();
+    // BIP-39 reference vector
+    assert_eq!(
+        hex::encode(mnemonic.to_seed("TREZOR")),
+        "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04"
+    );
+
+    let seed = HdSeed::from_mnemonic(&mnemonic, "");
+    assert_eq!(hd::account_path(0), "m/44'/7963749'/0'/0'/0'");
+    assert_eq!(hex::encode(&seed.account_seed(0).unwrap()[..]), ACCOUNT_0_SEED);
+    let account = seed.derive_account(0).unwrap();
+    assert!(account.public_key_hex().starts_with(ACCOUNT_0_PUBLIC_KEY_PREFIX));
+
+    for (index, address) in ADDRESSES.iter().enumerate() {
+        assert_eq!(seed.derive_account(index as u32).unwrap().address(), *address);
+    }
+    let with_passphrase = HdSeed::from_mnemonic(&mnemonic, "TREZOR");
+    assert_eq!(with_passphrase.derive_account(0).unwrap().address(), TREZOR_ADDRESS);
+    assert!(seed.derive_account(1 << 31).is_err());
+}
+
+#[test]
+fn test_keygen_vector() {
+    let vector: Value = serde_json::from_str(KEYGEN_VECTOR).unwrap();
+    let field = |name: &str| vector[name].as_str().unwrap().to_string();
+    let seed: [u8; mldsa::SEED_BYTES] = hex::decode(field("seed")).unwrap().try_into().unwrap();
+
+    let (public_key, private_key) = mldsa::keypair_from_seed(&seed);
+    assert_eq!(hex::encode(&public_key), field("public_key"));
+    assert_eq!(hex::encode(&private_key[..]), field("private_key"));
+}
+
+#[test]
+fn test_mnemonic_parsing_and_generation() {
+    let messy = format!("  {}\n", PHRASE.to_uppercase().replace(' ', "   "));
+    let seed = HdSeed::from_mnemonic(&hd::parse_mnemonic(&messy).unwrap(), "");
+    assert_eq!(seed.derive_account(0).unwrap().address(), ADDRESSES[0]);
+
+    let bad_checksum = PHRASE.replace("about", "abandon");
+    assert!(hd::parse_mnemonic(&bad_checksum).unwrap_err().contains("checksum"));
+    assert!(hd::parse_mnemonic("abandon synergy").is_err());
+
+    for words in hd::WORD_COUNTS {
+        let mnemonic = hd::generate_mnemonic(words).unwrap();
+        assert_eq!(mnemonic.word_count(), words);
+        assert!(hd::parse_mnemonic(&mnemonic.to_string()).is_ok());
+    }
+    assert_ne!(hd::generate_mnemonic(24).unwrap().to_string(), hd::generate_mnemonic(24).unwrap().to_string());
+    assert!(hd::generate_mnemonic(15).is_err());
+}
+
+#[test]
+fn test_wallet_manager_restores_accounts() {
+    let dir = std::env::temp_dir().join(format!("synergy-mnemonic-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let mut manager = WalletManager::new();
+    manager.open_keystore(Keystore::new(&dir).with_kdf(TEST_KDF)).unwrap();
+
+    let addresses = manager.create_from_mnemonic(PHRASE, "", "correct horse", 2).unwrap();
+    assert_eq!(addresses, ADDRESSES[..2]);
+    assert_eq!(manager.keystore().list().unwrap().len(), 2);
+
+    // Recovering again keeps the stored keys; one more account is added
+    let addresses = manager.create_from_mnemonic(PHRASE, "", "other password", 3).unwrap();
+    assert_eq!(addresses, ADDRESSES);
+    assert_eq!(manager.keystore().list().unwrap().len(), 3);
+    assert!(manager.unlock_wallet(ADDRESSES[0], "correct horse", None).is_ok());
+    assert!(manager.unlock_wallet(ADDRESSES[2], "other password", None).is_ok());
+
+    let seed = HdSeed::from_mnemonic(&hd::parse_mnemonic(PHRASE).unwrap(), "");
+    let address = manager.derive_account(&seed, 7, "correct horse").unwrap();
+    assert_eq!(address, seed.derive_account(7).unwrap().address());
+    assert!(manager.get_wallet(&address).is_some());
+
+    assert!(manager.create_from_mnemonic("abandon about", "", "correct horse", 1).is_err());
+}
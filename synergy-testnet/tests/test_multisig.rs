@@ -0,0 +1,152 @@
+use std::path::PathBuf;
+
+use synergy_testnet::crypto::keys::KeyPair;
+use synergy_testnet::crypto::multisig::MultisigPolicy;
+use synergy_testnet::keystore::{KdfParams, Keystore};
+use synergy_testnet::transaction::Transaction;
+use synergy_testnet::tx_file::{self, TxFormat, TxOp, DEFAULT_GAS_LIMIT, DEFAULT_GAS_PRICE};
+use synergy_testnet::wallet::WalletManager;
+
+const TEST_KDF: KdfParams = KdfParams { memory_kib: 1024, iterations: 1, parallelism: 1 };
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("synergy-multisig-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn transfer(policy: &MultisigPolicy) -> Transaction {
+    let to = TxOp::Transfer { to: WalletManager::generate_address("receiver") };
+    let mut tx = tx_file::build(&policy.address(), &to, 500, 1, DEFAULT_GAS_PRICE, DEFAULT_GAS_LIMIT);
+    tx.set_multisig(policy.clone());
+    tx
+}
+
+#[test]
+fn test_policy_address() {
+    let signers: Vec<KeyPair> = (0..3).map(|_| KeyPair::generate()).collect();
+    let keys: Vec<String> = signers.iter().map(KeyPair::public_key_hex).collect();
+    let policy = MultisigPolicy::new(2, keys.clone()).unwrap();
+
+    // Signer order does not matter; threshold and signers do
+    let reversed = MultisigPolicy::new(2, keys.iter().rev().cloned().collect()).unwrap();
+    assert_eq!(policy, reversed);
+    assert_eq!(policy.address(), reversed.address());
+    assert_ne!(policy.address(), MultisigPolicy::new(3, keys.clone()).unwrap().address());
+    assert_ne!(policy.address(), MultisigPolicy::new(2, keys[..2].to_vec()).unwrap().address());
+
+    // A 1-of-1 account is not the single-key account of the same key
+    let single = MultisigPolicy::new(1, vec![keys[0].clone()]).unwrap();
+    assert_ne!(single.address(), signers[0].address());
+    assert!(Transaction::new(policy.address(), signers[0].address(), 1, 1, String::new(), 1, 1, None).validate_fields().is_valid);
+
+    assert!(MultisigPolicy::new(0, keys.clone()).is_err());
+    assert!(MultisigPolicy::new(4, keys.clone()).is_err());
+    assert!(MultisigPolicy::new(1, vec![keys[0].clone(), keys[0].to_uppercase()]).is_err(), "duplicate keys");
+    assert!(MultisigPolicy::new(1, vec!["abcd".to_string()]).is_err());
+    assert!(MultisigPolicy::new(1, Vec::new()).is_err());
+}
+
+#[test]
+fn test_threshold_signatures() {
+    let signers: Vec<KeyPair> = (0..3).map(|_| KeyPair::generate()).collect();
+    let policy = MultisigPolicy::new(2, signers.iter().map(KeyPair::public_key_hex).collect()).unwrap();
+    let mut tx = transfer(&policy);
+    assert_eq!(tx.sender, policy.address());
+    assert!(!tx.is_signed());
+
+    tx.sign_multisig(&signers[2]).unwrap();
+    let result = tx.validate();
+    assert!(!result.is_valid);
+    assert_eq!(result.error_message.as_deref(), Some("Multisig transaction has 1 of 2 required signatures"));
+    assert!(tx.sign_multisig(&KeyPair::generate()).is_err(), "only policy keys can sign");
+
+    tx.sign_multisig(&signers[0]).unwrap();
+    assert!(tx.validate().is_valid, "{:?}", tx.validate().error_message);
+
+    // Signatures cover the transaction and sit in their signer's slot
+    let mut tampered = tx.clone();
+    tampered.amount += 1;
+    assert!(!tampered.verify_signature());
+    let mut swapped = tx.clone();
+    swapped.multisig.as_mut().unwrap().signatures.swap(0, 2);
+    assert!(!swapped.verify_signature());
+
+    // The policy must be the sender's and leaves the single-key fields empty
+    let mut stolen = tx.clone();
+    stolen.sender = signers[0].address();
+    assert!(!stolen.verify_signature());
+    let mut mixed = tx.clone();
+    mixed.public_key = signers[0].public_key_hex();
+    assert!(!mixed.verify_signature());
+    let mut lowered = tx.clone();
+    lowered.multisig.as_mut().unwrap().policy.threshold = 1;
+    assert!(!lowered.verify_signature());
+
+    let (decoded, _) = tx_file::decode(&tx_file::encode(&tx, TxFormat::Bincode)).unwrap();
+    assert!(decoded.verify_signature());
+
+    // The node's wallet cosigns with an unlocked signer key
+    let mut manager = WalletManager::new();
+    manager.open_keystore(Keystore::new(temp_dir("wallet")).with_kdf(TEST_KDF)).unwrap();
+    let address = manager.create_wallet("correct horse").unwrap();
+    manager.unlock_wallet(&address, "correct horse", None).unwrap();
+    let (_, file) = manager.keystore().find(&address).unwrap();
+    let policy = MultisigPolicy::new(1, vec![file.public_key, signers[0].public_key_hex()]).unwrap();
+    let mut tx = transfer(&policy);
+    manager.sign_transaction(&address, &mut tx).unwrap();
+    assert_eq!(tx.sender, policy.address());
+    assert!(tx.validate().is_valid);
+}
+
+#[test]
+fn test_partially_signed_files() {
+    let dir = temp_dir("files");
+    let alice = Keystore::new(dir.join("alice")).with_kdf(TEST_KDF);
+    let bob = Keystore::new(dir.join("bob")).with_kdf(TEST_KDF);
+    let alice_key = alice.create("alice password").unwrap();
+    let bob_key = bob.create("bob password").unwrap();
+    let carol = KeyPair::generate();
+
+    let policy = MultisigPolicy::new(2, vec![alice_key.public_key.clone(), bob_key.public_key.clone(), carol.public_key_hex()]).unwrap();
+    let policy_path = dir.join("policy.json");
+    tx_file::write_policy(&policy_path, &policy).unwrap();
+    assert_eq!(tx_file::read_policy(&policy_path).unwrap(), policy);
+
+    let path = dir.join("tx.json");
+    tx_file::write(&path, &transfer(&policy), TxFormat::Json).unwrap();
+
+    // Signers pass the file along, each signing with the key they hold
+    let (mut tx, format) = tx_file::read(&path).unwrap();
+    assert_eq!(tx_file::signer(&tx, &alice, None).unwrap(), alice_key.address);
+    assert!(tx_file::signer(&tx, &alice, Some(&carol.address())).is_ok());
+    assert!(tx_file::signer(&tx, &alice, Some(&KeyPair::generate().address())).is_err());
+    tx_file::sign(&mut tx, &alice, "alice password").unwrap();
+    tx_file::write(&path, &tx, format).unwrap();
+    assert!(tx_file::signer(&tx, &alice, None).is_err(), "alice has already signed");
+
+    let (partial, _) = tx_file::read(&path).unwrap();
+    assert!(!partial.validate().is_valid);
+    let summary = tx_file::describe(&partial);
+    assert!(summary.contains(&("Signature", "incomplete, 1 of 2 required".to_string())));
+    assert!(summary.contains(&("Signer", format!("{} signed", alice_key.address))));
+    assert!(summary.contains(&("Signer", format!("{} pending", bob_key.address))));
+
+    let mut serial = partial.clone();
+    tx_file::sign(&mut serial, &bob, "bob password").unwrap();
+    assert!(serial.validate().is_valid);
+    assert!(tx_file::describe(&serial).contains(&("Signature", "valid".to_string())));
+
+    // Or they sign copies in parallel and combine them
+    let mut copy = transfer(&policy);
+    copy.timestamp = partial.timestamp;
+    tx_file::sign(&mut copy, &bob, "bob password").unwrap();
+    let combined = tx_file::combine(&[partial.clone(), copy]).unwrap();
+    assert!(combined.validate().is_valid, "{:?}", combined.validate().error_message);
+
+    let mut other = transfer(&policy);
+    other.timestamp = partial.timestamp + 1;
+    assert!(tx_file::combine(&[partial.clone(), other]).is_err());
+    assert!(tx_file::combine(&[partial]).is_ok());
+}
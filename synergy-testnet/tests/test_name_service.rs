@@ -0,0 +1,180 @@
+use synergy_testnet::block::{Block, BlockChain};
+use synergy_testnet::crypto::keys::KeyPair;
+use synergy_testnet::name_service::{self, NameOp, NameRegistry, BLOCKS_PER_YEAR, GRACE_PERIOD};
+use synergy_testnet::transaction::Transaction;
+use synergy_testnet::tx_file::{self, TxOp};
+
+fn name_tx(keypair: &KeyPair, op: NameOp, nonce: u64) -> Transaction {
+    let mut tx = tx_file::build(&keypair.address(), &TxOp::Name(op), 0, nonce, 1, 1);
+    tx.sign(keypair);
+    tx
+}
+
+fn register(name: &str, years: u64) -> NameOp {
+    NameOp::Register { name: name.to_string(), years, owner: None }
+}
+
+fn set_text(name: &str, key: &str, value: Option<&str>) -> NameOp {
+    NameOp::SetText { name: name.to_string(), key: key.to_string(), value: value.map(str::to_string) }
+}
+
+#[test]
+fn test_names_are_normalized_and_priced_by_length() {
+    assert_eq!(name_service::normalize(" Alice.SYN ").unwrap(), "alice.syn");
+    for invalid in ["alice", "al.syn", "-alice.syn", "alice-.syn", "al_ice.syn", "a.b.syn"] {
+        assert!(name_service::normalize(invalid).is_err(), "{} is not a valid name", invalid);
+    }
+    assert!(name_service::is_name("Bob.Syn"));
+    assert!(!name_service::is_name(&KeyPair::generate().address()));
+
+    assert_eq!(register("bob.syn", 2).fee(), 2 * 640);
+    assert_eq!(register("dave.syn", 1).fee(), 160);
+    assert_eq!(NameOp::Renew { name: "alice.syn".to_string(), years: 3 }.fee(), 3 * 5);
+    assert_eq!(set_text("alice.syn", "url", Some("https://example.org")).fee(), 0);
+}
+
+#[test]
+fn test_register_and_resolve() {
+    let alice = KeyPair::generate();
+    let bob = KeyPair::generate();
+    let mut names = NameRegistry::new();
+
+    let tx = name_tx(&alice, register("Alice.syn", 1), 1);
+    assert!(tx.is_name_operation());
+    assert!(tx.validate().is_valid, "a name operation moves no funds but is a valid transaction");
+    names.apply(&tx, 10).unwrap();
+
+    let record = names.resolve("alice.syn", 11).unwrap();
+    assert_eq!((record.owner.as_str(), record.address.as_str()), (alice.address().as_str(), alice.address().as_str()));
+    assert_eq!((record.registered_height, record.expiry_height), (10, 10 + BLOCKS_PER_YEAR));
+
+    // Taken names and bad terms are refused
+    assert!(names.check(&name_tx(&bob, register("alice.syn", 1), 1), 11).unwrap_err().contains("registered"));
+    assert!(names.check(&name_tx(&bob, register("bobby.syn", 0), 1), 11).is_err());
+    assert!(names.check(&name_tx(&bob, register("bobby.syn", 11), 1), 11).is_err());
+
+    // A name can be registered for someone else
+    let gift = NameOp::Register { name: "carol.syn".to_string(), years: 1, owner: Some(bob.address()) };
+    names.apply(&name_tx(&alice, gift, 2), 12).unwrap();
+    assert_eq!(names.resolve("carol.syn", 13).unwrap().owner, bob.address());
+}
+
+#[test]
+fn test_expiry_grace_period_and_renewal() {
+    let alice = KeyPair::generate();
+    let bob = KeyPair::generate();
+    let mut names = NameRegistry::new();
+    names.apply(&name_tx(&alice, register("alice.syn", 1), 1), 0).unwrap();
+    let expiry = BLOCKS_PER_YEAR;
+
+    // Anyone may pay for a renewal while the name resolves
+    let renew = NameOp::Renew { name: "alice.syn".to_string(), years: 1 };
+    assert!(names.check(&name_tx(&bob, renew.clone(), 1), expiry - 1).is_ok());
+    assert!(names.check(&name_tx(&bob, NameOp::Renew { name: "alice.syn".to_string(), years: 10 }, 1), 1).is_err());
+
+    // Expired names stop resolving; during the grace period only the owner renews
+    assert!(names.resolve("alice.syn", expiry).is_none());
+    assert!(names.check(&name_tx(&bob, renew.clone(), 1), expiry).unwrap_err().contains("grace"));
+    assert!(names.check(&name_tx(&bob, register("alice.syn", 1), 1), expiry + GRACE_PERIOD - 1).is_err());
+    assert!(names.check(&name_tx(&alice, set_text("alice.syn", "url", Some("x")), 2), expiry).is_err());
+
+    let mut renewed = names.clone();
+    renewed.apply(&name_tx(&alice, renew, 2), expiry).unwrap();
+    assert_eq!(renewed.resolve("alice.syn", expiry).unwrap().expiry_height, 2 * BLOCKS_PER_YEAR);
+
+    // After the grace period anyone may take the name, which starts afresh
+    names.apply(&name_tx(&bob, register("alice.syn", 2), 1), expiry + GRACE_PERIOD).unwrap();
+    let record = names.resolve("alice.syn", expiry + GRACE_PERIOD).unwrap();
+    assert_eq!(record.owner, bob.address());
+    assert_eq!(record.registered_height, expiry + GRACE_PERIOD);
+}
+
+#[test]
+fn test_only_the_owner_edits_a_name() {
+    let alice = KeyPair::generate();
+    let bob = KeyPair::generate();
+    let vault = KeyPair::generate();
+    let mut names = NameRegistry::new();
+    names.apply(&name_tx(&alice, register("alice.syn", 1), 1), 1).unwrap();
+
+    assert!(names.check(&name_tx(&bob, set_text("alice.syn", "avatar", Some("ipfs://x")), 1), 2).unwrap_err().contains("owned"));
+    assert!(names.check(&name_tx(&bob, set_text("nobody.syn", "avatar", Some("ipfs://x")), 1), 2).unwrap_err().contains("not registered"));
+
+    names.apply(&name_tx(&alice, set_text("alice.syn", "avatar", Some("ipfs://x")), 2), 2).unwrap();
+    names.apply(&name_tx(&alice, set_text("alice.syn", "url", Some("https://alice.example")), 3), 2).unwrap();
+    names.apply(&name_tx(&alice, set_text("alice.syn", "url", None), 4), 3).unwrap();
+    let set_address = NameOp::SetAddress { name: "alice.syn".to_string(), address: Some(vault.address()) };
+    names.apply(&name_tx(&alice, set_address, 5), 3).unwrap();
+    let record = names.resolve("alice.syn", 4).unwrap();
+    assert_eq!(record.text.get("avatar").map(String::as_str), Some("ipfs://x"));
+    assert!(!record.text.contains_key("url"));
+    assert_eq!((record.owner.clone(), record.address.clone()), (alice.address(), vault.address()));
+
+    // A transfer hands over control, points the name at the new owner and clears its records
+    let transfer = NameOp::Transfer { name: "alice.syn".to_string(), to: bob.address() };
+    names.apply(&name_tx(&alice, transfer, 6), 4).unwrap();
+    let record = names.resolve("alice.syn", 5).unwrap();
+    assert_eq!((record.owner.clone(), record.address.clone()), (bob.address(), bob.address()));
+    assert!(record.text.is_empty());
+    assert!(names.check(&name_tx(&alice, set_text("alice.syn", "avatar", Some("ipfs://y")), 7), 5).is_err());
+}
+
+#[test]
+fn test_reverse_resolution() {
+    let alice = KeyPair::generate();
+    let bob = KeyPair::generate();
+    let mut names = NameRegistry::new();
+    names.apply(&name_tx(&alice, register("alice.syn", 1), 1), 1).unwrap();
+    assert!(names.lookup(&alice.address(), 2).is_none());
+
+    // Only a name that resolves to the sender can be its primary name
+    let primary = NameOp::SetPrimary { name: Some("alice.syn".to_string()) };
+    assert!(names.check(&name_tx(&bob, primary.clone(), 1), 2).is_err());
+    names.apply(&name_tx(&alice, primary, 2), 2).unwrap();
+    assert_eq!(names.lookup(&alice.address(), 3).unwrap().name, "alice.syn");
+
+    // Reverse resolution follows the forward record
+    let repoint = NameOp::SetAddress { name: "alice.syn".to_string(), address: Some(bob.address()) };
+    names.apply(&name_tx(&alice, repoint, 3), 3).unwrap();
+    assert!(names.lookup(&alice.address(), 4).is_none());
+    assert!(names.lookup(&bob.address(), 4).is_none(), "bob never chose the name");
+    assert!(names.lookup(&alice.address(), BLOCKS_PER_YEAR + 1).is_none());
+
+    names.apply(&name_tx(&alice, NameOp::SetPrimary { name: None }, 4), 4).unwrap();
+}
+
+#[test]
+fn test_registry_is_rebuilt_from_the_chain() {
+    let alice = KeyPair::generate();
+    let register_tx = name_tx(&alice, register("alice.syn", 1), 1);
+    let text_tx = name_tx(&alice, set_text("alice.syn", "email", Some("alice@example.org")), 2);
+    let renew_tx = name_tx(&alice, NameOp::Renew { name: "alice.syn".to_string(), years: 2 }, 3);
+
+    let mut chain = BlockChain::new();
+    chain.genesis();
+    for (index, transactions) in [(1, vec![register_tx, text_tx]), (2, vec![renew_tx])] {
+        let previous_hash = chain.last().unwrap().hash.clone();
+        chain.add_block(Block::new(index, transactions, previous_hash, "validator".to_string(), index));
+    }
+    assert!(chain.verify().is_ok());
+
+    let names = NameRegistry::from_chain(&chain);
+    assert_eq!(names.height(), 2);
+    let record = names.resolve("alice.syn", 3).unwrap();
+    assert_eq!(record.expiry_height, 1 + 3 * BLOCKS_PER_YEAR);
+    assert_eq!(record.text.get("email").map(String::as_str), Some("alice@example.org"));
+    assert_eq!(names.records().count(), 1);
+}
+
+#[test]
+fn test_wallet_builds_name_operations() {
+    let alice = KeyPair::generate();
+    let tx = name_tx(&alice, register("alice.syn", 3), 1);
+    assert_eq!(tx.receiver, alice.address());
+    assert_eq!(tx.amount, 0);
+    assert!(tx_file::describe(&tx).contains(&("Name fee", "15 SNRG, burned on inclusion".to_string())));
+
+    // Addresses pass through without a node
+    assert_eq!(tx_file::resolve("http://127.0.0.1:1", &alice.address()).unwrap(), alice.address());
+    assert!(tx_file::resolve("http://127.0.0.1:1", "not a name.syn").is_err());
+}
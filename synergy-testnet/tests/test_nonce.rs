@@ -0,0 +1,127 @@
+use std::sync::{Arc, Mutex};
+
+use synergy_testnet::block::{Block, BlockChain};
+use synergy_testnet::crypto::keys::KeyPair;
+use synergy_testnet::nonce::{NonceRegistry, QueuedTransactions, MAX_QUEUED_NONCES, NONCE_REGISTRY, QUEUED_TX_LIFETIME};
+use synergy_testnet::rpc::api;
+use synergy_testnet::transaction::Transaction;
+use synergy_testnet::wallet::WalletManager;
+
+fn transfer(sender: &KeyPair, nonce: u64) -> Transaction {
+    let receiver = WalletManager::generate_address("receiver");
+    let mut tx = Transaction::new(sender.address(), receiver, 10, nonce, String::new(), 1, 1, None);
+    tx.sign(sender);
+    tx
+}
+
+fn chain_with(blocks: Vec<Vec<Transaction>>) -> BlockChain {
+    let mut chain = BlockChain::new();
+    chain.genesis();
+    for (index, transactions) in (1..).zip(blocks) {
+        let previous_hash = chain.last().unwrap().hash.clone();
+        chain.add_block(Block::new(index, transactions, previous_hash, "validator".to_string(), index));
+    }
+    chain
+}
+
+#[test]
+fn test_inclusion_needs_exactly_the_next_nonce() {
+    let alice = KeyPair::generate();
+    let mut nonces = NonceRegistry::new();
+    assert_eq!(nonces.nonce(&alice.address()), 0);
+    assert_eq!(nonces.next_nonce(&alice.address()), 1);
+
+    assert!(nonces.check(&transfer(&alice, 2)).unwrap_err().contains("ahead"));
+    let first = transfer(&alice, 1);
+    nonces.check(&first).unwrap();
+    nonces.record(&first);
+    assert_eq!(nonces.next_nonce(&alice.address()), 2);
+
+    // The same transaction cannot be included twice
+    assert!(nonces.check(&first).unwrap_err().contains("already used"));
+    assert!(nonces.check(&transfer(&alice, 2)).is_ok());
+
+    // Other addresses keep their own nonces
+    assert_eq!(nonces.next_nonce(&KeyPair::generate().address()), 1);
+}
+
+#[test]
+fn test_pool_admission_and_pending_nonce() {
+    let alice = KeyPair::generate();
+    let mut nonces = NonceRegistry::new();
+    nonces.record(&transfer(&alice, 1));
+
+    let pending = vec![transfer(&alice, 2), transfer(&alice, 3), transfer(&alice, 5)];
+    assert_eq!(nonces.pending_nonce(&alice.address(), &pending), 4, "nonce 5 waits for 4");
+    assert_eq!(nonces.pending_nonce(&alice.address(), &[]), 2);
+
+    assert!(nonces.check_pending(&transfer(&alice, 4), &pending).is_ok());
+    assert!(nonces.check_pending(&transfer(&alice, 1), &pending).unwrap_err().contains("already used"));
+    assert!(nonces.check_pending(&transfer(&alice, 3), &pending).unwrap_err().contains("already pending"));
+    assert!(nonces.check_pending(&transfer(&alice, 2 + MAX_QUEUED_NONCES - 1), &pending).is_ok());
+    assert!(nonces.check_pending(&transfer(&alice, 2 + MAX_QUEUED_NONCES), &pending).is_err());
+}
+
+#[test]
+fn test_nonces_are_rebuilt_from_the_chain() {
+    let alice = KeyPair::generate();
+    let bob = KeyPair::generate();
+    let chain = chain_with(vec![
+        vec![transfer(&alice, 1), transfer(&bob, 1)],
+        vec![transfer(&alice, 2), transfer(&alice, 3)],
+        // Blocks from before nonces were enforced may repeat one
+        vec![transfer(&bob, 1)],
+    ]);
+
+    let nonces = NonceRegistry::from_chain(&chain);
+    assert_eq!(nonces.height(), 3);
+    assert_eq!(nonces.nonce(&alice.address()), 3);
+    assert_eq!(nonces.nonce(&bob.address()), 1);
+    assert!(nonces.check(&transfer(&bob, 1)).is_err());
+}
+
+#[test]
+fn test_submission_rejects_replays() {
+    let alice = KeyPair::generate();
+    let mut nonces = NonceRegistry::new();
+    nonces.record(&transfer(&alice, 1));
+    *NONCE_REGISTRY.lock().unwrap() = nonces;
+    let pool = Arc::new(Mutex::new(Vec::new()));
+
+    assert!(api::submit_transaction(&pool, transfer(&alice, 1)).is_err(), "nonce 1 is already included");
+    let second = transfer(&alice, 2);
+    api::submit_transaction(&pool, second.clone()).unwrap();
+    assert!(api::submit_transaction(&pool, second).is_err(), "nonce 2 is already pending");
+    api::submit_transaction(&pool, transfer(&alice, 4)).unwrap();
+
+    assert_eq!(pool.lock().unwrap().len(), 2);
+    assert_eq!(api::next_nonce(&pool, &alice.address()), 3);
+}
+
+#[test]
+fn test_transactions_behind_a_failed_nonce_expire() {
+    let alice = KeyPair::generate();
+    let bob = KeyPair::generate();
+    let mut nonces = NonceRegistry::new();
+    nonces.record(&transfer(&bob, 1));
+    nonces.end_block(1);
+
+    // Alice's nonce 1 failed to apply at height 1, so 2 and 3 wait for a nonce that may never come
+    let mut pool = vec![transfer(&alice, 2), transfer(&alice, 3), transfer(&bob, 1), transfer(&bob, 2)];
+    let mut queued = QueuedTransactions::new();
+    assert!(queued.prune(&mut pool, &nonces, 1).is_empty());
+    assert_eq!(pool.len(), 3, "bob's nonce 1 was used");
+
+    // Joining the queue later does not shorten a transaction's wait
+    pool.push(transfer(&alice, 4));
+    assert!(queued.prune(&mut pool, &nonces, QUEUED_TX_LIFETIME).is_empty());
+    let expired = queued.prune(&mut pool, &nonces, 1 + QUEUED_TX_LIFETIME);
+    let expired: Vec<u64> = expired.iter().map(|tx| tx.nonce).collect();
+    assert_eq!(expired, vec![2, 3]);
+    let left: Vec<(String, u64)> = pool.iter().map(|tx| (tx.sender.clone(), tx.nonce)).collect();
+    assert_eq!(left, vec![(bob.address(), 2), (alice.address(), 4)]);
+
+    // A transaction with the next nonce is never expired
+    assert!(queued.prune(&mut pool, &nonces, 10 * QUEUED_TX_LIFETIME).iter().all(|tx| tx.sender == alice.address()));
+    assert_eq!(pool.len(), 1);
+}
@@ -0,0 +1,63 @@
+use serde_json::{json, Value};
+use synergy_testnet::rpc::error::INVALID_PARAMS;
+use synergy_testnet::rpc::methods::METHODS;
+use synergy_testnet::rpc::{openrpc, rpc_server};
+
+#[test]
+fn test_client_spec_is_up_to_date() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../crates/synergy-client/openrpc.json");
+    let committed: Value = serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+    assert!(
+        &committed == openrpc::document(),
+        "openrpc.json is stale; run `synergy-testnet openrpc > crates/synergy-client/openrpc.json`"
+    );
+}
+
+#[test]
+fn test_every_method_is_described() {
+    let document = rpc_server::dispatch("rpc.discover", json!([])).unwrap();
+    let methods = document["methods"].as_array().unwrap();
+    assert_eq!(methods.len(), METHODS.len());
+
+    for method in methods {
+        assert!(!method["summary"].as_str().unwrap().is_empty(), "{} has no summary", method["name"]);
+    }
+
+    let ban_peer = methods.iter().find(|m| m["name"] == "synergy_banPeer").unwrap();
+    assert_eq!(ban_peer["tags"][0]["name"], json!("admin"));
+    assert_eq!(ban_peer["params"][0]["name"], json!("address"));
+    assert_eq!(ban_peer["params"][0]["required"], json!(true));
+    assert_eq!(ban_peer["params"][1]["required"], json!(false));
+    assert!(document["components"]["schemas"]["Block"].is_object());
+}
+
+#[test]
+fn test_params_by_position_or_name() {
+    let by_position = rpc_server::dispatch("synergy_getTokenBalance", json!(["sYn4b45e8d59f59f51377d2a6847b92f242e73527", "SNRG"]));
+    let by_name = rpc_server::dispatch(
+        "synergy_getTokenBalance",
+        json!({"address": "sYn4b45e8d59f59f51377d2a6847b92f242e73527", "token_symbol": "SNRG"}),
+    );
+    assert_eq!(by_position.unwrap(), by_name.unwrap());
+
+    // Trailing optional params may be left out
+    assert!(rpc_server::dispatch("synergy_getTopValidators", json!([])).is_ok());
+    assert!(rpc_server::dispatch("synergy_blockNumber", Value::Null).is_ok());
+}
+
+#[test]
+fn test_params_are_validated() {
+    let invalid = |method: &str, params: Value| {
+        let error = rpc_server::dispatch(method, params).unwrap_err();
+        assert_eq!(error.code, INVALID_PARAMS, "{}", error.message);
+        error.message
+    };
+
+    assert!(invalid("synergy_getBlockByNumber", json!([])).contains("block_number"));
+    assert!(invalid("synergy_getBlockByNumber", json!(["ten"])).contains("invalid type"));
+    assert!(invalid("synergy_getBlockByNumber", json!({"number": 1})).contains("unknown field"));
+    invalid("synergy_getBlockByNumber", json!([1, 2]));
+    invalid("synergy_getBlockByNumber", json!("1"));
+    invalid("synergy_getBlockByNumber", json!([-1]));
+    invalid("synergy_executeAIVMContract", json!(["contract", "not hex"]));
+}
@@ -1,19 +1,267 @@
+use std::io::{BufReader, Write};
+use std::net::{SocketAddr, TcpStream};
 use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::Duration;
-use synergy_testnet::p2p;
-use tokio;
+use std::time::{Duration, Instant};
+use synergy_testnet::block::{Block, BlockChain};
+use synergy_testnet::config::NodeConfig;
+use synergy_testnet::crypto::keys::KeyPair;
+use synergy_testnet::p2p::networking::P2PNetwork;
+use synergy_testnet::p2p::protocol::{read_message, write_message, HeadVote, P2PMessage};
 
-#[tokio::test]
-async fn test_p2p_networking() {
-    let blockchain = Arc::new(Mutex::new(Vec::<String>::new()));
+const GENESIS_HASH: &str = "p2p-test-genesis";
+/// Score and ban key of the peers `handshake` connects, which listen on
+/// port 1 of a loopback address.
+const PEER_KEY: &str = "127.0.0.1:1";
 
-    let handle = tokio::spawn(async move {
-        crate::p2p::start_p2p_network("0.0.0.0:8080").await.unwrap();
+fn temp_ban_list(name: &str) -> String {
+    let path = std::env::temp_dir().join(format!("synergy_p2p_bans_{}_{}.json", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path.to_string_lossy().to_string()
+}
+
+fn start_network(name: &str, config: NodeConfig) -> (Arc<P2PNetwork>, SocketAddr) {
+    let mut chain = BlockChain::new();
+    chain.genesis_for(GENESIS_HASH);
+    let network = Arc::new(P2PNetwork::with_ban_list(Arc::new(Mutex::new(chain)), &config, &temp_ban_list(name)));
+    let address = network.start("127.0.0.1:0").unwrap();
+    (network, address)
+}
+
+fn wait_for(what: &str, mut condition: impl FnMut() -> bool) {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while !condition() {
+        assert!(Instant::now() < deadline, "timed out waiting for {}", what);
+        thread::sleep(Duration::from_millis(50));
+    }
+}
+
+/// Connects a hand-driven peer and completes the handshake with it.
+fn handshake(address: SocketAddr, genesis_hash: &str) -> (TcpStream, BufReader<TcpStream>, P2PMessage) {
+    let stream = TcpStream::connect(address).unwrap();
+    stream.set_read_timeout(Some(Duration::from_secs(10))).unwrap();
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let hello = P2PMessage::Hello {
+        node_id: "f".repeat(64),
+        chain_id: NodeConfig::default().blockchain.chain_id,
+        genesis_hash: genesis_hash.to_string(),
+        listen_port: 1,
+    };
+    write_message(&mut &stream, &hello).unwrap();
+    let (reply, _) = read_message(&mut reader, usize::MAX).unwrap().unwrap();
+    (stream, reader, reply)
+}
+
+/// Asserts the node closes the connection, skipping its status messages.
+fn assert_closed(reader: &mut BufReader<TcpStream>) {
+    loop {
+        match read_message(reader, usize::MAX) {
+            Ok(Some((P2PMessage::Status { .. }, _))) => {}
+            Ok(Some((other, _))) => panic!("expected the connection to close, got {:?}", other),
+            Ok(None) | Err(_) => return,
+        }
+    }
+}
+
+#[test]
+fn test_blocks_gossip_between_peers() {
+    let (first, first_address) = start_network("gossip_first", NodeConfig::default());
+    let mut config = NodeConfig::default();
+    config.network.bootnodes = vec![format!("enode://{}@{}", "a".repeat(64), first_address)];
+    let (second, _) = start_network("gossip_second", config);
+
+    wait_for("the bootnode connection", || first.get_peer_count() == 1 && second.get_peer_count() == 1);
+
+    let block = Block::new(1, vec![], "parent".to_string(), "validator".to_string(), 7);
+    first.broadcast_block(&block);
+
+    let mut received = Vec::new();
+    wait_for("the gossiped block", || {
+        received.extend(second.take_blocks());
+        !received.is_empty()
+    });
+    assert_eq!(received[0].1.hash, block.hash);
+
+    // The same block again is not queued twice
+    first.broadcast_block(&block);
+    thread::sleep(Duration::from_millis(300));
+    assert!(second.take_blocks().is_empty());
+
+    first.stop();
+    second.stop();
+}
+
+#[test]
+fn test_missing_blocks_are_requested_from_peers() {
+    let mut chain = BlockChain::new();
+    chain.genesis_for(GENESIS_HASH);
+    for height in 1..=5 {
+        let parent = chain.last().unwrap().hash.clone();
+        chain.add_block(Block::new(height, vec![], parent, "validator".to_string(), height));
+    }
+    let expected: Vec<String> = chain.chain[1..].iter().map(|block| block.hash.clone()).collect();
+    let ahead = Arc::new(P2PNetwork::with_ban_list(Arc::new(Mutex::new(chain)), &NodeConfig::default(), &temp_ban_list("sync_ahead")));
+    let ahead_address = ahead.start("127.0.0.1:0").unwrap();
+
+    let mut config = NodeConfig::default();
+    config.network.bootnodes = vec![ahead_address.to_string()];
+    let (behind, _) = start_network("sync_behind", config);
+
+    // The status after the handshake shows the peer ahead; the blocks arrive in height order
+    let mut received = Vec::new();
+    wait_for("the missing blocks", || {
+        received.extend(behind.take_blocks().into_iter().map(|(_, block)| block.hash));
+        received.len() >= expected.len()
     });
+    assert_eq!(received[..expected.len()], expected[..]);
+    assert_eq!(behind.best_peer_height(), Some(5));
+
+    ahead.stop();
+    behind.stop();
+}
+
+#[test]
+fn test_unrequested_blocks_are_a_protocol_violation() {
+    let (network, address) = start_network("unrequested", NodeConfig::default());
+    let (stream, _reader, _) = handshake(address, GENESIS_HASH);
+    wait_for("the handshake", || network.get_peer_count() == 1);
+
+    write_message(&mut &stream, &P2PMessage::Blocks { blocks: vec![] }).unwrap();
+
+    wait_for("the penalty", || network.peer_scores().get_score(PEER_KEY) < 0.0);
+    assert!(network.take_blocks().is_empty());
+
+    network.stop();
+}
+
+#[test]
+fn test_late_answers_to_block_requests_are_ignored() {
+    let (network, address) = start_network("late_answer", NodeConfig::default());
+    let (stream, mut reader, _) = handshake(address, GENESIS_HASH);
+    let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs();
+    let status = P2PMessage::Status { height: 3, head_hash: "head".to_string(), timestamp: now, votes: vec![] };
+    write_message(&mut &stream, &status).unwrap();
+
+    // Unanswered, the request is sent again once it times out
+    let mut requests = 0;
+    while requests < 2 {
+        if let Some((P2PMessage::GetBlocks { from }, _)) = read_message(&mut reader, usize::MAX).unwrap() {
+            assert_eq!(from, 1);
+            requests += 1;
+        }
+    }
+
+    // One answer is for the current request and the other is late
+    write_message(&mut &stream, &P2PMessage::Blocks { blocks: vec![] }).unwrap();
+    write_message(&mut &stream, &P2PMessage::Blocks { blocks: vec![] }).unwrap();
+    thread::sleep(Duration::from_millis(300));
+    assert_eq!(network.peer_scores().get_score(PEER_KEY), 0.0);
+
+    write_message(&mut &stream, &P2PMessage::Blocks { blocks: vec![] }).unwrap();
+    wait_for("the penalty", || network.peer_scores().get_score(PEER_KEY) < 0.0);
+
+    network.stop();
+}
+
+#[test]
+fn test_peer_status_is_recorded_after_handshake() {
+    let (network, address) = start_network("status", NodeConfig::default());
+    assert_eq!(network.best_peer_height(), None);
+    assert_eq!(network.median_clock_offset(), None);
+
+    let (stream, mut reader, _) = handshake(address, GENESIS_HASH);
+    match read_message(&mut reader, usize::MAX).unwrap() {
+        Some((P2PMessage::Status { height, .. }, _)) => assert_eq!(height, 0),
+        other => panic!("expected the node's status, got {:?}", other),
+    }
+
+    // A peer 42 blocks ahead whose clock runs 5 seconds fast, holding one validator key
+    let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs();
+    let key = KeyPair::generate();
+    let vote = HeadVote::sign(&key, &key.address(), 42, "head");
+    let status = P2PMessage::Status { height: 42, head_hash: "head".to_string(), timestamp: now + 5, votes: vec![vote.clone()] };
+    write_message(&mut &stream, &status).unwrap();
+
+    wait_for("the peer status", || network.best_peer_height() == Some(42));
+    assert_eq!(network.peer_heads()[0].votes, vec![vote.clone()]);
+    assert!(vote.verify(&key.public_key_hex()));
+    let offset = network.median_clock_offset().unwrap();
+    assert!((4..=5).contains(&offset), "offset {}", offset);
+
+    network.stop();
+}
+
+#[test]
+fn test_malformed_messages_get_peer_banned() {
+    let (network, address) = start_network("malformed", NodeConfig::default());
+    let (mut stream, mut reader, reply) = handshake(address, GENESIS_HASH);
+    assert!(matches!(reply, P2PMessage::Hello { .. }));
+    wait_for("the handshake", || network.get_peer_count() == 1);
+
+    stream.write_all(b"not json\n{\"type\":\"unknown\"}\n").unwrap();
+
+    // Two protocol violations cross the ban threshold and close the connection
+    assert_closed(&mut reader);
+    wait_for("the ban", || network.get_peer_count() == 0);
+    let bans = network.get_banned_peers();
+    assert_eq!(bans.len(), 1);
+    assert_eq!(bans[0].address, PEER_KEY);
+
+    network.stop();
+}
+
+#[test]
+fn test_oversized_messages_close_the_connection() {
+    let mut config = NodeConfig::default();
+    config.p2p.peer_scoring.max_message_size = 1024;
+    let (network, address) = start_network("oversized", config);
+    let (mut stream, mut reader, _) = handshake(address, GENESIS_HASH);
+    wait_for("the handshake", || network.get_peer_count() == 1);
+
+    let oversized = format!("{{\"type\":\"disconnect\",\"reason\":\"{}\"}}\n", "x".repeat(4096));
+    stream.write_all(oversized.as_bytes()).unwrap();
+
+    assert_closed(&mut reader);
+    wait_for("the disconnect", || network.get_peer_count() == 0);
+    assert!(network.peer_scores().get_score(PEER_KEY) < 0.0);
+
+    network.stop();
+}
+
+#[test]
+fn test_peers_on_another_network_are_rejected_at_handshake() {
+    let (network, address) = start_network("mismatch", NodeConfig::default());
+
+    let (_stream, mut reader, reply) = handshake(address, "another-genesis");
+    assert!(matches!(reply, P2PMessage::Hello { .. }));
+    match read_message(&mut reader, usize::MAX).unwrap() {
+        Some((P2PMessage::Disconnect { reason }, _)) => assert!(reason.contains("genesis another-genesis"), "{}", reason),
+        other => panic!("expected a disconnect, got {:?}", other),
+    }
+    assert_eq!(network.get_peer_count(), 0);
+
+    // The ban follows the port the peer listens on, so reconnecting from another port does not escape it
+    let bans = network.get_banned_peers();
+    assert_eq!(bans.len(), 1);
+    assert_eq!(bans[0].address, PEER_KEY);
+    let (_stream, mut reader, _) = handshake(address, GENESIS_HASH);
+    match read_message(&mut reader, usize::MAX).unwrap() {
+        Some((P2PMessage::Disconnect { reason }, _)) => assert_eq!(reason, "banned"),
+        other => panic!("expected a disconnect, got {:?}", other),
+    }
+
+    network.stop();
+}
+
+#[test]
+fn test_stop_disconnects_peers() {
+    let (network, address) = start_network("stop", NodeConfig::default());
+    let (_stream, mut reader, _) = handshake(address, GENESIS_HASH);
+    wait_for("the handshake", || network.get_peer_count() == 1);
 
-    thread::sleep(Duration::from_secs(2));
+    network.stop();
 
-    assert!(blockchain.lock().unwrap().is_empty());
-    handle.await.unwrap();
+    assert!(!network.is_running());
+    assert_eq!(network.get_peer_count(), 0);
+    assert_closed(&mut reader);
 }
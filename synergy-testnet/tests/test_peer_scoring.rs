@@ -0,0 +1,78 @@
+use synergy_testnet::p2p::peer_scoring::{MessageType, PeerAction, PeerEvent, PeerScoreManager, PeerScoringConfig};
+use synergy_testnet::rate_limit::TokenBucket;
+
+fn temp_ban_list(name: &str) -> String {
+    let path = std::env::temp_dir().join(format!("synergy_peer_bans_{}_{}.json", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path.to_string_lossy().to_string()
+}
+
+#[test]
+fn test_bad_blocks_lead_to_temporary_ban() {
+    let manager = PeerScoreManager::new(PeerScoringConfig::default(), &temp_ban_list("temp"));
+    let peer = "203.0.113.5:30303";
+
+    assert_eq!(manager.record_event(peer, PeerEvent::BadBlock), PeerAction::Drop);
+    assert_eq!(manager.record_event(peer, PeerEvent::BadBlock), PeerAction::Ban);
+    assert!(manager.is_banned(peer));
+
+    let bans = manager.get_banned_peers();
+    assert_eq!(bans.len(), 1);
+    assert!(bans[0].expires_at.is_some());
+
+    assert!(manager.unban_peer(peer));
+    assert!(!manager.is_banned(peer));
+}
+
+#[test]
+fn test_repeat_offenders_are_banned_permanently() {
+    let config = PeerScoringConfig { max_temp_bans: 1, ..PeerScoringConfig::default() };
+    let manager = PeerScoreManager::new(config, &temp_ban_list("permanent"));
+    let peer = "198.51.100.7:30303";
+
+    for _ in 0..2 {
+        manager.record_event(peer, PeerEvent::BadBlock);
+        manager.record_event(peer, PeerEvent::BadBlock);
+    }
+
+    let bans = manager.get_banned_peers();
+    assert_eq!(bans.len(), 1);
+    assert!(bans[0].expires_at.is_none());
+}
+
+#[test]
+fn test_bans_persist_across_restarts() {
+    let path = temp_ban_list("persist");
+    let peer = "192.0.2.1:30303";
+
+    PeerScoreManager::new(PeerScoringConfig::default(), &path).ban_peer(peer, None, "test");
+
+    let reloaded = PeerScoreManager::new(PeerScoringConfig::default(), &path);
+    assert!(reloaded.is_banned(peer));
+}
+
+#[test]
+fn test_useful_data_raises_score() {
+    let manager = PeerScoreManager::new(PeerScoringConfig::default(), &temp_ban_list("reward"));
+    let peer = "192.0.2.2:30303";
+
+    assert_eq!(manager.record_event(peer, PeerEvent::UsefulBlock), PeerAction::Keep);
+    assert!(manager.get_score(peer) > 0.0);
+}
+
+#[test]
+fn test_rate_limit_per_message_type() {
+    let manager = PeerScoreManager::new(PeerScoringConfig::default(), &temp_ban_list("rate"));
+    let peer = "192.0.2.3:30303";
+    let capacity = manager.config().block_rate.capacity;
+
+    for _ in 0..capacity {
+        assert!(manager.check_rate_limit(peer, MessageType::Block));
+    }
+    assert!(!manager.check_rate_limit(peer, MessageType::Block));
+    assert!(manager.check_rate_limit(peer, MessageType::Transaction));
+
+    let mut bucket = TokenBucket::new(1, 0.0);
+    assert!(bucket.try_consume(1));
+    assert!(!bucket.try_consume(1));
+}
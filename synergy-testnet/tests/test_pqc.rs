@@ -0,0 +1,220 @@
+use serde_json::Value;
+use synergy_testnet::crypto::keys::{self, KeyPair};
+use synergy_testnet::crypto::pqc::{
+    self, Falcon512, Kem, KemAlgorithm, MlDsa87, MlKem768, PQCAlgorithm, PQCSignature, SignatureAlgorithm, SignatureCache,
+    Signer, Verifier, VerifyRequest,
+};
+
+// Recorded with the PQClean implementations; any change to an encoding or
+// to verification and decapsulation results fails these
+const KNOWN_ANSWERS: &str = include_str!("data/pqc_kat.json");
+
+fn bytes(vector: &Value, field: &str) -> Vec<u8> {
+    hex::decode(vector[field].as_str().unwrap()).unwrap()
+}
+
+#[test]
+fn test_parameter_set_sizes() {
+    // (public key, private key, maximum signature) from FIPS 204, FIPS 205 and the Falcon specification
+    let signatures = [
+        (SignatureAlgorithm::MlDsa44, 1312, 2560, 2420),
+        (SignatureAlgorithm::MlDsa65, 1952, 4032, 3309),
+        (SignatureAlgorithm::MlDsa87, 2592, 4896, 4627),
+        (SignatureAlgorithm::Falcon512, 897, 1281, 752),
+        (SignatureAlgorithm::Falcon1024, 1793, 2305, 1462),
+        (SignatureAlgorithm::SlhDsaSha2_128s, 32, 64, 7856),
+        (SignatureAlgorithm::SlhDsaSha2_128f, 32, 64, 17088),
+        (SignatureAlgorithm::SlhDsaSha2_192s, 48, 96, 16224),
+        (SignatureAlgorithm::SlhDsaSha2_192f, 48, 96, 35664),
+        (SignatureAlgorithm::SlhDsaSha2_256s, 64, 128, 29792),
+        (SignatureAlgorithm::SlhDsaSha2_256f, 64, 128, 49856),
+        (SignatureAlgorithm::SlhDsaShake128s, 32, 64, 7856),
+        (SignatureAlgorithm::SlhDsaShake128f, 32, 64, 17088),
+        (SignatureAlgorithm::SlhDsaShake192s, 48, 96, 16224),
+        (SignatureAlgorithm::SlhDsaShake192f, 48, 96, 35664),
+        (SignatureAlgorithm::SlhDsaShake256s, 64, 128, 29792),
+        (SignatureAlgorithm::SlhDsaShake256f, 64, 128, 49856),
+    ];
+    assert_eq!(signatures.len(), SignatureAlgorithm::ALL.len());
+    for (algorithm, public_key, private_key, signature) in signatures {
+        let sizes = (algorithm.public_key_bytes(), algorithm.private_key_bytes(), algorithm.signature_bytes());
+        assert_eq!(sizes, (public_key, private_key, signature), "{}", algorithm);
+        assert_eq!(algorithm.scheme().algorithm(), algorithm);
+    }
+
+    // (public key, private key, ciphertext) from FIPS 203 and the Classic McEliece specification
+    let kems = [
+        (KemAlgorithm::MlKem512, 800, 1632, 768),
+        (KemAlgorithm::MlKem768, 1184, 2400, 1088),
+        (KemAlgorithm::MlKem1024, 1568, 3168, 1568),
+        (KemAlgorithm::McEliece348864, 261120, 6492, 96),
+        (KemAlgorithm::McEliece348864f, 261120, 6492, 96),
+        (KemAlgorithm::McEliece460896, 524160, 13608, 156),
+        (KemAlgorithm::McEliece460896f, 524160, 13608, 156),
+        (KemAlgorithm::McEliece6688128, 1044992, 13932, 208),
+        (KemAlgorithm::McEliece6688128f, 1044992, 13932, 208),
+        (KemAlgorithm::McEliece6960119, 1047319, 13948, 194),
+        (KemAlgorithm::McEliece6960119f, 1047319, 13948, 194),
+        (KemAlgorithm::McEliece8192128, 1357824, 14120, 208),
+        (KemAlgorithm::McEliece8192128f, 1357824, 14120, 208),
+    ];
+    assert_eq!(kems.len(), KemAlgorithm::ALL.len());
+    for (algorithm, public_key, private_key, ciphertext) in kems {
+        let sizes = (algorithm.public_key_bytes(), algorithm.private_key_bytes(), algorithm.ciphertext_bytes());
+        assert_eq!(sizes, (public_key, private_key, ciphertext), "{}", algorithm);
+        assert_eq!(algorithm.shared_secret_bytes(), 32);
+    }
+}
+
+#[test]
+fn test_algorithm_names() {
+    for &algorithm in SignatureAlgorithm::ALL {
+        let json = serde_json::to_string(&algorithm).unwrap();
+        assert_eq!(json, format!("\"{}\"", algorithm));
+        assert_eq!(serde_json::from_str::<SignatureAlgorithm>(&json).unwrap(), algorithm);
+        assert_eq!(algorithm.name().to_lowercase().parse::<SignatureAlgorithm>().unwrap(), algorithm);
+    }
+    for &algorithm in KemAlgorithm::ALL {
+        assert_eq!(serde_json::to_string(&algorithm).unwrap(), format!("\"{}\"", algorithm));
+        assert_eq!(algorithm.name().parse::<KemAlgorithm>().unwrap(), algorithm);
+    }
+    assert_eq!(serde_json::to_string(&SignatureAlgorithm::SlhDsaShake192f).unwrap(), "\"SLH-DSA-SHAKE-192f\"");
+    assert_eq!(serde_json::to_string(&KemAlgorithm::McEliece6960119).unwrap(), "\"Classic-McEliece-6960119\"");
+    assert!("Dilithium".parse::<SignatureAlgorithm>().is_err());
+    assert!("ML-DSA-87".parse::<KemAlgorithm>().is_err());
+
+    let either: Vec<PQCAlgorithm> = serde_json::from_str(r#"["ML-DSA-65", "ML-KEM-1024"]"#).unwrap();
+    assert_eq!(either, vec![SignatureAlgorithm::MlDsa65.into(), KemAlgorithm::MlKem1024.into()]);
+}
+
+#[test]
+fn test_sign_and_verify() {
+    let message = b"synergy block 42";
+    // The "s" SLH-DSA sets are the same code as "f" with slower parameters
+    for &algorithm in SignatureAlgorithm::ALL.iter().filter(|a| !a.name().starts_with("SLH-DSA") || a.name().ends_with('f')) {
+        let (public_key, private_key) = algorithm.generate_keypair();
+        let signature = algorithm.sign(&private_key, message).unwrap();
+        assert!(signature.len() <= algorithm.signature_bytes());
+        assert!(algorithm.verify(&public_key, message, &signature), "{}", algorithm);
+
+        assert!(!algorithm.verify(&public_key, b"synergy block 43", &signature));
+        assert!(!algorithm.verify(&algorithm.generate_keypair().0, message, &signature));
+        assert!(!algorithm.verify(&public_key[1..], message, &signature));
+        assert!(algorithm.sign(&private_key[1..], message).is_err());
+    }
+
+    // Keys only verify with their own parameter set
+    let (public_key, private_key) = MlDsa87.generate_keypair();
+    let signature = MlDsa87.sign(&private_key, message).unwrap();
+    assert!(!SignatureAlgorithm::MlDsa65.verify(&public_key, message, &signature));
+    assert!(!Falcon512.verify(&public_key, message, &signature));
+
+    // Account keys are ML-DSA-87 keys
+    let keypair = KeyPair::generate();
+    assert!(MlDsa87.verify(keypair.public_key(), message, &keypair.sign(message)));
+    assert!(keys::verify(&public_key, message, &signature));
+    assert!(KeyPair::from_bytes(&public_key, &private_key).is_ok());
+    assert!(KeyPair::from_bytes(&public_key, &private_key[1..]).is_err());
+}
+
+#[test]
+fn test_signature_envelope() {
+    let message = b"bridge message";
+    let algorithm = SignatureAlgorithm::Falcon1024;
+    let (public_key, private_key) = algorithm.generate_keypair();
+    let signature = PQCSignature::sign(algorithm, &public_key, &private_key, message).unwrap();
+    assert!(signature.verify(message));
+    assert!(!signature.verify(b"other message"));
+
+    // It verifies wherever it is sent, with nothing but the message
+    let json = serde_json::to_value(&signature).unwrap();
+    assert_eq!(json["algorithm"], "Falcon-1024");
+    let received: PQCSignature = serde_json::from_value(json).unwrap();
+    assert!(received.verify(message));
+
+    let mut relabelled = received.clone();
+    relabelled.algorithm = SignatureAlgorithm::Falcon512;
+    assert!(!relabelled.verify(message));
+    assert!(PQCSignature::sign(algorithm, &public_key[1..], &private_key, message).is_err());
+}
+
+#[test]
+fn test_batch_verification_and_cache() {
+    let algorithms = [SignatureAlgorithm::MlDsa44, SignatureAlgorithm::MlDsa87, SignatureAlgorithm::Falcon512];
+    let signed: Vec<_> = (0..12)
+        .map(|i| {
+            let algorithm = algorithms[i % algorithms.len()];
+            let (public_key, private_key) = algorithm.generate_keypair();
+            let message = format!("vote {}", i).into_bytes();
+            let mut signature = algorithm.sign(&private_key, &message).unwrap();
+            if i % 5 == 0 {
+                signature[0] ^= 1;
+            }
+            (algorithm, public_key, message, signature)
+        })
+        .collect();
+    let requests: Vec<VerifyRequest> = signed
+        .iter()
+        .map(|(algorithm, public_key, message, signature)| VerifyRequest { algorithm: *algorithm, public_key, message, signature })
+        .collect();
+    let expected: Vec<bool> = (0..12).map(|i| i % 5 != 0).collect();
+    assert_eq!(pqc::verify_batch(&requests), expected);
+    assert_eq!(requests.iter().map(VerifyRequest::verify).collect::<Vec<_>>(), expected);
+
+    // Only signatures that verified are remembered, up to the capacity
+    let cache = SignatureCache::new(4);
+    assert_eq!(cache.verify_batch(&requests), expected);
+    assert_eq!(cache.len(), 4);
+    assert_eq!(cache.verify_batch(&requests), expected);
+    let small = SignatureCache::new(100);
+    assert_eq!(small.verify_batch(&requests), expected);
+    assert_eq!(small.len(), 9);
+
+    // The cache key covers every part of the request
+    let mut other_message = requests[1];
+    other_message.message = b"vote 2";
+    assert!(!small.verify(&other_message));
+    let mut other_algorithm = requests[1];
+    other_algorithm.algorithm = SignatureAlgorithm::MlDsa65;
+    assert!(!small.verify(&other_algorithm));
+    small.clear();
+    assert!(small.is_empty());
+}
+
+#[test]
+fn test_key_encapsulation() {
+    for algorithm in [KemAlgorithm::MlKem512, KemAlgorithm::MlKem768, KemAlgorithm::MlKem1024, KemAlgorithm::McEliece348864] {
+        let (public_key, private_key) = algorithm.generate_keypair();
+        let (ciphertext, shared_secret) = algorithm.encapsulate(&public_key).unwrap();
+        assert_eq!(ciphertext.len(), algorithm.ciphertext_bytes());
+        assert_eq!(algorithm.decapsulate(&private_key, &ciphertext).unwrap(), shared_secret, "{}", algorithm);
+
+        // Every encapsulation is a fresh secret
+        assert_ne!(algorithm.encapsulate(&public_key).unwrap().1, shared_secret);
+        assert!(algorithm.encapsulate(&public_key[1..]).is_err());
+        assert!(algorithm.decapsulate(&private_key, &ciphertext[1..]).is_err());
+    }
+
+    // ML-KEM rejects a tampered ciphertext implicitly, with an unrelated secret
+    let (public_key, private_key) = MlKem768.generate_keypair();
+    let (mut ciphertext, shared_secret) = MlKem768.encapsulate(&public_key).unwrap();
+    ciphertext[0] ^= 1;
+    assert_ne!(MlKem768.decapsulate(&private_key, &ciphertext).unwrap(), shared_secret);
+}
+
+#[test]
+fn test_known_answers() {
+    let vectors: Value = serde_json::from_str(KNOWN_ANSWERS).unwrap();
+    for vector in vectors["signatures"].as_array().unwrap() {
+        let algorithm: SignatureAlgorithm = serde_json::from_value(vector["algorithm"].clone()).unwrap();
+        let (public_key, message, mut signature) = (bytes(vector, "public_key"), bytes(vector, "message"), bytes(vector, "signature"));
+        assert!(algorithm.verify(&public_key, &message, &signature), "{}", algorithm);
+        signature[0] ^= 1;
+        assert!(!algorithm.verify(&public_key, &message, &signature), "{}", algorithm);
+    }
+    for vector in vectors["kems"].as_array().unwrap() {
+        let algorithm: KemAlgorithm = serde_json::from_value(vector["algorithm"].clone()).unwrap();
+        let shared_secret = algorithm.decapsulate(&bytes(vector, "private_key"), &bytes(vector, "ciphertext")).unwrap();
+        assert_eq!(hex::encode(&shared_secret[..]), vector["shared_secret"].as_str().unwrap(), "{}", algorithm);
+    }
+}
@@ -0,0 +1,161 @@
+use std::net::IpAddr;
+use jsonwebtoken::{encode, EncodingKey, Header};
+use serde_json::json;
+use synergy_testnet::config::RpcAuthConfig;
+use synergy_testnet::rpc::auth::{CallerContext, IpRange, Namespace, RpcAuth};
+use synergy_testnet::rpc::error::{METHOD_NOT_FOUND, UNAUTHORIZED};
+use synergy_testnet::rpc::rpc_server;
+
+const SECRET: &str = "test-secret";
+
+fn admin_auth(allowlist: Vec<&str>) -> RpcAuth {
+    let config = RpcAuthConfig {
+        enable_admin: true,
+        enable_debug: true,
+        ip_allowlist: allowlist.into_iter().map(String::from).collect(),
+        ..RpcAuthConfig::default()
+    };
+    RpcAuth::new(config, Some(SECRET.as_bytes().to_vec())).unwrap()
+}
+
+fn caller(ip: &str, authorization: Option<&str>) -> CallerContext {
+    CallerContext {
+        ip: Some(ip.parse::<IpAddr>().unwrap()),
+        authorization: authorization.map(String::from),
+    }
+}
+
+fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+#[test]
+fn test_method_namespaces() {
+    assert_eq!(Namespace::of("synergy_blockNumber"), Namespace::Public);
+    assert_eq!(Namespace::of("synergy_sendTransaction"), Namespace::Public);
+    assert_eq!(Namespace::of("eth_chainId"), Namespace::Public);
+    assert_eq!(Namespace::of("synergy_createWallet"), Namespace::Wallet);
+    assert_eq!(Namespace::of("synergy_slashValidator"), Namespace::Admin);
+    assert_eq!(Namespace::of("synergy_mintTokens"), Namespace::Admin);
+    assert_eq!(Namespace::of("synergy_stakeTokensDirect"), Namespace::Admin);
+    assert_eq!(Namespace::of("synergy_getAllWallets"), Namespace::Debug);
+}
+
+#[test]
+fn test_admin_disabled_by_default() {
+    let auth = RpcAuth::new(RpcAuthConfig::default(), Some(SECRET.as_bytes().to_vec())).unwrap();
+    let bearer = format!("Bearer {}", SECRET);
+
+    // Even a valid token cannot reach a namespace that is switched off
+    let error = auth.authorize("synergy_approveValidator", &caller("127.0.0.1", Some(&bearer))).unwrap_err();
+    assert_eq!(error.code, METHOD_NOT_FOUND);
+    assert!(auth.authorize("synergy_blockNumber", &caller("203.0.113.9", None)).is_ok());
+}
+
+#[test]
+fn test_admin_requires_token() {
+    let auth = admin_auth(vec![]);
+
+    let error = auth.authorize("synergy_mintTokens", &caller("127.0.0.1", None)).unwrap_err();
+    assert_eq!(error.code, UNAUTHORIZED);
+    let error = auth.authorize("synergy_mintTokens", &caller("127.0.0.1", Some("Bearer wrong"))).unwrap_err();
+    assert_eq!(error.code, UNAUTHORIZED);
+
+    let bearer = format!("Bearer {}", SECRET);
+    assert_eq!(auth.authorize("synergy_mintTokens", &caller("203.0.113.9", Some(&bearer))).unwrap(), Namespace::Admin);
+    assert_eq!(auth.authorize("synergy_getAllBalances", &caller("203.0.113.9", Some(&bearer))).unwrap(), Namespace::Debug);
+}
+
+#[test]
+fn test_admin_accepts_jwt() {
+    let auth = admin_auth(vec![]);
+    let key = EncodingKey::from_secret(SECRET.as_bytes());
+
+    let valid = encode(&Header::default(), &json!({"sub": "operator", "exp": now() + 600}), &key).unwrap();
+    assert!(auth.authorize("synergy_slashValidator", &caller("10.0.0.5", Some(&format!("Bearer {}", valid)))).is_ok());
+
+    let expired = encode(&Header::default(), &json!({"sub": "operator", "exp": now() - 600}), &key).unwrap();
+    let error = auth.authorize("synergy_slashValidator", &caller("10.0.0.5", Some(&format!("Bearer {}", expired)))).unwrap_err();
+    assert_eq!(error.code, UNAUTHORIZED);
+
+    let forged = encode(&Header::default(), &json!({"exp": now() + 600}), &EncodingKey::from_secret(b"other")).unwrap();
+    assert!(auth.authorize("synergy_slashValidator", &caller("10.0.0.5", Some(&format!("Bearer {}", forged)))).is_err());
+}
+
+#[test]
+fn test_ip_allowlist() {
+    let auth = admin_auth(vec!["10.0.0.0/8", "::1"]);
+    let bearer = format!("Bearer {}", SECRET);
+
+    assert!(auth.authorize("synergy_banPeer", &caller("10.20.30.40", Some(&bearer))).is_ok());
+    assert!(auth.authorize("synergy_banPeer", &caller("::1", Some(&bearer))).is_ok());
+    let error = auth.authorize("synergy_banPeer", &caller("192.168.1.1", Some(&bearer))).unwrap_err();
+    assert_eq!(error.code, UNAUTHORIZED);
+
+    // The allowlist only guards privileged namespaces
+    assert!(auth.authorize("synergy_blockNumber", &caller("192.168.1.1", None)).is_ok());
+
+    assert!(IpRange::parse("10.0.0.0/33").is_err());
+    assert!(IpRange::parse("not-an-ip").is_err());
+    assert!(RpcAuth::new(
+        RpcAuthConfig { ip_allowlist: vec!["bogus".to_string()], ..RpcAuthConfig::default() },
+        None,
+    ).is_err());
+}
+
+#[test]
+fn test_wallet_methods_need_a_token() {
+    let auth = admin_auth(vec![]);
+    let bearer = format!("Bearer {}", SECRET);
+
+    // Loopback callers are not trusted by default
+    let error = auth.authorize("synergy_createWallet", &caller("127.0.0.1", None)).unwrap_err();
+    assert_eq!(error.code, UNAUTHORIZED);
+    assert!(auth.authorize("synergy_createWallet", &caller("127.0.0.1", Some(&bearer))).is_ok());
+    let error = auth.authorize("synergy_createWallet", &caller("203.0.113.9", None)).unwrap_err();
+    assert_eq!(error.code, UNAUTHORIZED);
+    assert!(auth.authorize("synergy_createWallet", &caller("203.0.113.9", Some(&bearer))).is_ok());
+}
+
+#[test]
+fn test_loopback_wallet_access_is_opt_in() {
+    let config = RpcAuthConfig { allow_loopback_wallet: true, ..RpcAuthConfig::default() };
+    let auth = RpcAuth::new(config, Some(SECRET.as_bytes().to_vec())).unwrap();
+
+    assert!(auth.authorize("synergy_createWallet", &caller("127.0.0.1", None)).is_ok());
+    assert!(auth.authorize("synergy_createWallet", &caller("::1", None)).is_ok());
+    let error = auth.authorize("synergy_createWallet", &caller("203.0.113.9", None)).unwrap_err();
+    assert_eq!(error.code, UNAUTHORIZED);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_generated_token_file_is_private() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let path = std::env::temp_dir().join(format!("synergy_rpc_token_{}", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let config = RpcAuthConfig { token_file: path.to_string_lossy().to_string(), ..RpcAuthConfig::default() };
+    RpcAuth::from_config(&config).unwrap();
+
+    let metadata = std::fs::metadata(&path).unwrap();
+    assert_eq!(metadata.permissions().mode() & 0o777, 0o600);
+    assert_eq!(std::fs::read_to_string(&path).unwrap().len(), 64);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_dispatch_rejects_before_running() {
+    let auth = admin_auth(vec![]);
+
+    let error = rpc_server::dispatch_as(
+        &auth,
+        &caller("203.0.113.9", None),
+        "synergy_slashValidator",
+        json!(["sYn4b45e8d59f59f51377d2a6847b92f242e73527", "test"]),
+    ).unwrap_err();
+    assert_eq!(error.code, UNAUTHORIZED);
+}
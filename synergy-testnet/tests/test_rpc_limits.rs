@@ -0,0 +1,116 @@
+mod common;
+
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::net::{IpAddr, TcpStream};
+use std::time::{Duration, Instant};
+use serde_json::{json, Value};
+use synergy_testnet::config::{NodeConfig, RpcLimitsConfig};
+use synergy_testnet::rate_limit::RateLimitConfig;
+use synergy_testnet::rpc::error::{RpcError, INVALID_REQUEST, LIMIT_EXCEEDED};
+use synergy_testnet::rpc::jsonrpc;
+use synergy_testnet::rpc::limits::RpcLimiter;
+
+fn ip(address: &str) -> Option<IpAddr> {
+    Some(address.parse().unwrap())
+}
+
+fn limits() -> RpcLimitsConfig {
+    let mut per_method = HashMap::new();
+    per_method.insert("synergy_getBlockRange".to_string(), RateLimitConfig { capacity: 2, refill_per_sec: 0.0 });
+
+    RpcLimitsConfig {
+        per_ip: RateLimitConfig { capacity: 5, refill_per_sec: 0.0 },
+        per_method,
+        expensive_methods: vec!["synergy_executeAIVMContract".to_string()],
+        max_concurrent_expensive: 1,
+        max_batch_size: 3,
+        ..RpcLimitsConfig::default()
+    }
+}
+
+#[test]
+fn test_per_ip_limit() {
+    let limiter = RpcLimiter::new(limits());
+
+    for _ in 0..5 {
+        assert!(limiter.admit(ip("203.0.113.9"), "synergy_status").is_ok());
+    }
+    let error = limiter.admit(ip("203.0.113.9"), "synergy_status").err().unwrap();
+    assert_eq!(error.code, LIMIT_EXCEEDED);
+
+    // Other clients have their own bucket, in-process callers are not limited
+    assert!(limiter.admit(ip("198.51.100.1"), "synergy_status").is_ok());
+    assert!(limiter.admit(None, "synergy_status").is_ok());
+}
+
+#[test]
+fn test_per_method_limit() {
+    let limiter = RpcLimiter::new(limits());
+
+    assert!(limiter.admit(ip("203.0.113.9"), "synergy_getBlockRange").is_ok());
+    assert!(limiter.admit(ip("203.0.113.9"), "synergy_getBlockRange").is_ok());
+    let error = limiter.admit(ip("203.0.113.9"), "synergy_getBlockRange").err().unwrap();
+    assert_eq!(error.code, LIMIT_EXCEEDED);
+
+    // The same client can still call other methods
+    assert!(limiter.admit(ip("203.0.113.9"), "synergy_status").is_ok());
+}
+
+#[test]
+fn test_expensive_concurrency_cap() {
+    let limiter = RpcLimiter::new(limits());
+
+    let permit = limiter.admit(None, "synergy_executeAIVMContract").ok().unwrap();
+    let error = limiter.admit(None, "synergy_executeAIVMContract").err().unwrap();
+    assert_eq!(error.code, LIMIT_EXCEEDED);
+
+    // The slot is released once the running call finishes
+    drop(permit);
+    assert!(limiter.admit(None, "synergy_executeAIVMContract").is_ok());
+}
+
+#[test]
+fn test_worker_threads_cap_admission() {
+    let limiter = RpcLimiter::new(RpcLimitsConfig { worker_threads: 2, ..limits() });
+
+    let first = limiter.reserve_worker().unwrap();
+    let _second = limiter.reserve_worker().unwrap();
+    assert_eq!(limiter.reserve_worker().err().unwrap().code, LIMIT_EXCEEDED);
+
+    drop(first);
+    assert!(limiter.reserve_worker().is_ok());
+}
+
+#[test]
+fn test_slow_headers_close_the_connection() {
+    let mut config = NodeConfig::default().rpc;
+    config.enable_ws = false;
+    config.enable_grpc = false;
+    config.limits.read_timeout_secs = 1;
+    let address = common::start_test_server(config, 0);
+
+    let mut stream = TcpStream::connect(address).unwrap();
+    stream.set_read_timeout(Some(Duration::from_secs(10))).unwrap();
+    let started = Instant::now();
+    stream.write_all(b"POST / HTTP/1.1\r\nHost: localhost\r\n").unwrap();
+
+    // The node gives up on the missing end of the headers and hangs up
+    let mut reply = Vec::new();
+    let _ = stream.read_to_end(&mut reply);
+    assert!(started.elapsed() < Duration::from_secs(5), "connection held for {:?}", started.elapsed());
+}
+
+#[test]
+fn test_batch_size_limit() {
+    let call = json!({"jsonrpc": "2.0", "method": "synergy_status", "id": 1});
+    let dispatch = |_: &str, _: Value| -> Result<Value, RpcError> { Ok(json!("ok")) };
+
+    let body = serde_json::to_vec(&json!([call, call, call])).unwrap();
+    let response = jsonrpc::process_body(&body, 3, dispatch).unwrap();
+    assert_eq!(response.as_array().unwrap().len(), 3);
+
+    let body = serde_json::to_vec(&json!([call, call, call, call])).unwrap();
+    let response = jsonrpc::process_body(&body, 3, dispatch).unwrap();
+    assert_eq!(response["error"]["code"], json!(INVALID_REQUEST));
+}
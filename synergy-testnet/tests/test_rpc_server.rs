@@ -1,38 +1,68 @@
-use std::io::{Read, Write};
-use std::net::{TcpListener, TcpStream};
-use std::thread;
-use std::time::Duration;
-use synergy_testnet::rpc;
+mod common;
+
+use serde_json::{json, Value};
+use synergy_testnet::config::NodeConfig;
+
+fn start_test_server() -> String {
+    let mut config = NodeConfig::default().rpc;
+    config.enable_ws = false;
+    config.enable_grpc = false;
+    format!("http://{}/", common::start_test_server(config, 0))
+}
 
 #[test]
 fn test_rpc_server() {
-    // Check if port 8545 is already in use
-    if TcpListener::bind("0.0.0.0:8545").is_ok() {
-        // If the port is free, start the RPC server in a separate thread
-        thread::spawn(|| {
-            rpc::start_rpc_server();
-        });
-
-        // Wait a few seconds for the server to fully start
-        thread::sleep(Duration::from_secs(3));
-    } else {
-        println!("RPC server is already running, skipping server startup in test.");
-    }
-
-    // Attempt to connect to the RPC server
-    let mut stream = TcpStream::connect("0.0.0.0:8545").expect("Failed to connect to RPC server");
-
-    // Send a dummy request to test response handling
-    let request = b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n";
-    stream.write_all(request).expect("Failed to send request");
-
-    // Read the response
-    let mut buffer = [0; 1024];
-    let bytes_read = stream.read(&mut buffer).expect("Failed to read response");
-
-    // Ensure we got a response and check for HTTP 200 OK
-    assert!(bytes_read > 0);
-    assert!(String::from_utf8_lossy(&buffer).contains("200 OK"));
-
-    println!("RPC server test successfully completed.");
+    let url = start_test_server();
+    let client = reqwest::blocking::Client::new();
+
+    // Plain request
+    let response: Value = client.post(&url)
+        .json(&json!({"jsonrpc": "2.0", "method": "synergy_status", "params": [], "id": 1}))
+        .send().expect("Failed to reach RPC server")
+        .json().expect("Response is not JSON");
+    assert_eq!(response["result"], json!("ok"));
+    assert_eq!(response["id"], json!(1));
+
+    // Unknown method and bad params map onto standard error codes
+    let response: Value = client.post(&url)
+        .json(&json!({"jsonrpc": "2.0", "method": "synergy_doesNotExist", "id": 2}))
+        .send().unwrap().json().unwrap();
+    assert_eq!(response["error"]["code"], json!(-32601));
+
+    let response: Value = client.post(&url)
+        .json(&json!({"jsonrpc": "2.0", "method": "synergy_getValidator", "params": [], "id": 3}))
+        .send().unwrap().json().unwrap();
+    assert_eq!(response["error"]["code"], json!(-32602));
+
+    // Malformed JSON
+    let response: Value = client.post(&url)
+        .header("Content-Type", "application/json")
+        .body("{\"jsonrpc\": \"2.0\", \"method\"")
+        .send().unwrap().json().unwrap();
+    assert_eq!(response["error"]["code"], json!(-32700));
+
+    // Batches answer every call that has an id, and skip notifications
+    let response: Value = client.post(&url)
+        .json(&json!([
+            {"jsonrpc": "2.0", "method": "synergy_status", "id": "a"},
+            {"jsonrpc": "2.0", "method": "synergy_status"},
+            {"jsonrpc": "2.0", "method": "synergy_doesNotExist", "id": "b"}
+        ]))
+        .send().unwrap().json().unwrap();
+    let responses = response.as_array().expect("Batch response must be an array");
+    assert_eq!(responses.len(), 2);
+
+    // A notification-only request gets no body
+    let response = client.post(&url)
+        .json(&json!({"jsonrpc": "2.0", "method": "synergy_status"}))
+        .send().unwrap();
+    assert_eq!(response.status().as_u16(), 204);
+
+    // Large bodies are read completely; synergy_status takes no params, so the id carries the padding
+    let padding = "x".repeat(64 * 1024);
+    let response: Value = client.post(&url)
+        .json(&json!({"jsonrpc": "2.0", "method": "synergy_status", "params": [], "id": padding}))
+        .send().unwrap().json().unwrap();
+    assert_eq!(response["result"], json!("ok"));
+    assert_eq!(response["id"], json!(padding));
 }
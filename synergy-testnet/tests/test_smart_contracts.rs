@@ -1,14 +1,21 @@
-use synergy_testnet::node::Blockchain;
+use synergy_testnet::contracts::{ContractExecutor, ContractMetadata};
 
 #[test]
 fn test_smart_contract_deployment() {
-    let mut blockchain = Blockchain::new();
+    let mut executor = ContractExecutor::new();
 
     let contract_address = "0xABC123".to_string();
     let contract_code = vec![0x00, 0x61, 0x73, 0x6D]; // Sample WASM header
+    let metadata = ContractMetadata {
+        name: "sample".to_string(),
+        version: "1.0.0".to_string(),
+        abi_hash: String::new(),
+    };
 
-    blockchain.deploy_smart_contract(contract_address.clone(), contract_code);
-    blockchain.execute_smart_contract(&contract_address);
+    executor.deploy_contract(contract_address.clone(), contract_code, metadata.clone()).unwrap();
+    assert!(executor.deploy_contract(contract_address.clone(), vec![], metadata).is_err());
 
-    println!("Smart Contract Test Passed!");
+    let output = executor.execute_contract(&contract_address, &[]).unwrap();
+    assert!(output.contains("sample"));
+    assert!(executor.execute_contract("0xDEF456", &[]).is_err());
 }
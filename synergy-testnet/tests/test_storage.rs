@@ -0,0 +1,106 @@
+use std::fs;
+use std::path::PathBuf;
+use synergy_testnet::block::{Block, BlockChain};
+use synergy_testnet::crypto::keys::KeyPair;
+use synergy_testnet::transaction::Transaction;
+use synergy_testnet::wallet::WalletManager;
+use synergy_testnet::{shutdown, storage};
+
+fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("synergy-storage-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+fn build_chain(length: u64) -> BlockChain {
+    let mut chain = BlockChain::new();
+    chain.genesis();
+    for index in 1..length {
+        let previous_hash = chain.last().unwrap().hash.clone();
+        chain.add_block(Block::new(index, vec![], previous_hash, "validator".to_string(), index));
+    }
+    chain
+}
+
+#[test]
+fn test_atomic_write_replaces_file() {
+    let dir = test_dir("atomic");
+    let path = dir.join("nested").join("state.json");
+
+    storage::write_atomic(&path, b"first").unwrap();
+    storage::write_atomic(&path, b"second").unwrap();
+
+    assert_eq!(fs::read_to_string(&path).unwrap(), "second");
+    // The temporary file has been renamed into place
+    assert!(!storage::remove_stale_temp(&path));
+}
+
+#[test]
+fn test_stale_temp_and_quarantine() {
+    let dir = test_dir("quarantine");
+    let path = dir.join("chain.json");
+    storage::write_atomic(&path, b"{ truncated").unwrap();
+
+    // A crash between writing and renaming leaves the temporary file behind
+    fs::write(dir.join("chain.json.tmp"), b"partial").unwrap();
+    assert!(storage::remove_stale_temp(&path));
+
+    let moved = storage::quarantine(&path).unwrap();
+    assert!(!path.exists());
+    assert_eq!(fs::read_to_string(&moved).unwrap(), "{ truncated");
+    assert!(moved.file_name().unwrap().to_string_lossy().starts_with("chain.json.corrupt-"));
+}
+
+#[test]
+fn test_chain_verification() {
+    let chain = build_chain(5);
+    assert!(chain.verify().is_ok());
+    assert_eq!(chain.valid_prefix_len(), 5);
+
+    // Tampering with a block breaks its hash
+    let mut tampered = chain.clone();
+    tampered.chain[3].validator_id = "someone-else".to_string();
+    assert!(tampered.verify().is_err());
+    assert_eq!(tampered.truncate_to_valid(), 2);
+    assert_eq!(tampered.last().unwrap().block_index, 2);
+
+    // A block that does not link to its predecessor is dropped with everything after it
+    let mut unlinked = chain.clone();
+    unlinked.chain[2] = Block::new(2, vec![], "0".to_string(), "validator".to_string(), 2);
+    assert_eq!(unlinked.valid_prefix_len(), 2);
+
+    // So is a block with a transaction that is not signed by its sender
+    let keypair = KeyPair::generate();
+    let mut tx = Transaction::new(keypair.address(), WalletManager::generate_address("receiver"), 5, 1, String::new(), 1000, 21000, None);
+    tx.sign(&keypair);
+    let mut forged = tx.clone();
+    forged.amount = 500;
+    for (transactions, valid) in [(vec![tx.clone()], 5), (vec![tx, forged], 4)] {
+        let mut signed = chain.clone();
+        signed.chain[4] = Block::new(4, transactions, chain.chain[3].hash.clone(), "validator".to_string(), 4);
+        assert_eq!(signed.valid_prefix_len(), valid);
+    }
+}
+
+#[test]
+fn test_chain_round_trip() {
+    let dir = test_dir("chain");
+    let path = dir.join("chain.json");
+    let chain = build_chain(3);
+
+    chain.save_to_file(path.to_str().unwrap());
+    let loaded = BlockChain::load_from_file(path.to_str().unwrap()).unwrap();
+
+    assert_eq!(loaded.chain.len(), 3);
+    assert!(loaded.verify().is_ok());
+}
+
+#[test]
+fn test_shutdown_request() {
+    assert!(!shutdown::is_requested());
+    shutdown::request();
+    assert!(shutdown::is_requested());
+
+    // Waiters that arrive after the request resolve immediately
+    tokio::runtime::Runtime::new().unwrap().block_on(shutdown::wait());
+}
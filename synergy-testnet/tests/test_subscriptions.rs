@@ -0,0 +1,85 @@
+use serde_json::json;
+use synergy_testnet::aivm::distributed_ai::ComputationStatus;
+use synergy_testnet::events::{ComputationStatusChange, EventBus, LogEntry, NodeEvent};
+use synergy_testnet::rpc::subscriptions::{LogFilter, SubscriptionKind};
+
+fn transfer_log(address: &str, from: &str, to: &str) -> LogEntry {
+    LogEntry {
+        address: address.to_string(),
+        topics: vec!["Transfer".to_string(), from.to_string(), to.to_string()],
+        data: "100".to_string(),
+        block_number: Some(7),
+        transaction_hash: Some("abc".to_string()),
+    }
+}
+
+#[test]
+fn test_log_filter_matching() {
+    let log = transfer_log("sYnQalice", "sYnQalice", "sYnQbob");
+
+    assert!(LogFilter::default().matches(&log));
+
+    let by_address = LogFilter::from_value(Some(&json!({"address": ["sYnQcarol", "sYnQalice"]}))).unwrap();
+    assert!(by_address.matches(&log));
+
+    let wrong_address = LogFilter::from_value(Some(&json!({"address": "sYnQcarol"}))).unwrap();
+    assert!(!wrong_address.matches(&log));
+
+    // null positions are wildcards, lists are alternatives
+    let by_topic = LogFilter::from_value(Some(&json!({"topics": ["Transfer", null, ["sYnQbob", "sYnQdave"]]}))).unwrap();
+    assert!(by_topic.matches(&log));
+
+    let too_many_topics = LogFilter::from_value(Some(&json!({"topics": [null, null, null, "extra"]}))).unwrap();
+    assert!(!too_many_topics.matches(&log));
+
+    assert!(LogFilter::from_value(Some(&json!({"topics": "Transfer"}))).is_err());
+}
+
+#[test]
+fn test_subscription_params() {
+    assert_eq!(SubscriptionKind::from_params(&json!(["newHeads"])).unwrap(), SubscriptionKind::NewHeads);
+    assert_eq!(
+        SubscriptionKind::from_params(&json!(["validatorStatus", {"address": "sYnQval"}])).unwrap(),
+        SubscriptionKind::ValidatorStatus { address: Some("sYnQval".to_string()) }
+    );
+
+    let unknown = SubscriptionKind::from_params(&json!(["everything"])).unwrap_err();
+    assert_eq!(unknown.code, -32602);
+    assert!(SubscriptionKind::from_params(&json!([])).is_err());
+}
+
+#[test]
+fn test_subscription_renders_only_matching_events() {
+    let kind = SubscriptionKind::from_params(&json!(["aiComputations", {"computationId": "comp-1"}])).unwrap();
+    let change = |id: &str| NodeEvent::ComputationStatus(ComputationStatusChange {
+        computation_id: id.to_string(),
+        model_id: "model".to_string(),
+        cluster_id: 0,
+        status: ComputationStatus::Completed,
+        timestamp: 0,
+    });
+
+    let rendered = kind.render(&change("comp-1")).expect("matching computation should be forwarded");
+    assert_eq!(rendered["status"], json!("Completed"));
+    assert!(kind.render(&change("comp-2")).is_none());
+    assert!(kind.render(&NodeEvent::Log(transfer_log("a", "b", "c"))).is_none());
+}
+
+#[test]
+fn test_event_bus_fans_out() {
+    let bus = EventBus::new(4);
+    // Publishing without subscribers is a no-op
+    bus.publish(NodeEvent::Log(transfer_log("a", "b", "c")));
+
+    let mut first = bus.subscribe();
+    let mut second = bus.subscribe();
+    assert_eq!(bus.subscriber_count(), 2);
+
+    bus.publish(NodeEvent::Log(transfer_log("sYnQalice", "sYnQalice", "sYnQbob")));
+    for receiver in [&mut first, &mut second] {
+        match receiver.try_recv().unwrap() {
+            NodeEvent::Log(log) => assert_eq!(log.address, "sYnQalice"),
+            other => panic!("unexpected event: {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,40 @@
+mod common;
+
+use serde_json::{json, Value};
+use synergy_client::{Client, Error};
+use synergy_testnet::config::NodeConfig;
+use synergy_testnet::genesis::Genesis;
+use synergy_testnet::token::TOKEN_MANAGER;
+
+const GENESIS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../config/genesis.json");
+fn start_test_server() -> String {
+    let mut config = NodeConfig::default().rpc;
+    config.enable_ws = false;
+    config.enable_grpc = false;
+
+    // SNRG and its allocations come from the genesis file, as on a node
+    TOKEN_MANAGER.apply_genesis(&Genesis::load(GENESIS).unwrap()).unwrap();
+    format!("http://{}/", common::start_test_server(config, 0))
+}
+
+#[tokio::test]
+async fn test_generated_client() {
+    let client = Client::new(start_test_server());
+
+    assert_eq!(client.status().await.unwrap(), "ok");
+    assert!(client.get_tokens().await.unwrap().iter().any(|t| t.symbol == "SNRG"));
+    assert!(client.get_block_by_number(1_000_000).await.unwrap().is_none());
+
+    let spec = client.rpc_discover().await.unwrap();
+    assert_eq!(spec["openrpc"], json!("1.2.6"));
+
+    // Node errors come back as typed RPC errors
+    match client.request::<Value>("synergy_getValidator", json!([])).await {
+        Err(Error::Rpc(error)) => assert_eq!(error.code, -32602),
+        other => panic!("unexpected response: {:?}", other),
+    }
+    match client.mint_tokens("sYn4b45e8d59f59f51377d2a6847b92f242e73527", "SNRG", 1).await {
+        Err(Error::Rpc(error)) => assert_eq!(error.code, -32601),
+        other => panic!("unexpected response: {:?}", other),
+    }
+}
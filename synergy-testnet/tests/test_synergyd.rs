@@ -1,18 +1,28 @@
-use synergy_testnet::consensus;
-use std::sync::{Arc, Mutex};
-use std::thread;
+use std::path::PathBuf;
 use std::time::Duration;
 
+use synergy_testnet::devnet::{Devnet, DevnetOptions};
+
 #[test]
 fn test_synergy_node_initialization() {
-    let blockchain = Arc::new(Mutex::new(Vec::<String>::new()));
-
-    let handle = thread::spawn(move || {
-        crate::consensus::run_consensus();
-    });
-
-    thread::sleep(Duration::from_secs(2));
+    let mut devnet = Devnet::generate(DevnetOptions {
+        nodes: 1,
+        dir: std::env::temp_dir().join(format!("synergy-node-init-{}", std::process::id())),
+        rpc_port: 30545,
+        p2p_port: 43303,
+        block_time_secs: 1,
+        binary: PathBuf::from(env!("CARGO_BIN_EXE_synergy-testnet")),
+    })
+    .unwrap();
+    devnet.start().unwrap();
 
-    assert!(blockchain.lock().unwrap().is_empty());
-    handle.join().unwrap();
+    // A single node finalizes its own blocks
+    let result = devnet
+        .wait_until_ready(Duration::from_secs(60))
+        .and_then(|()| devnet.wait_for_height(1, Duration::from_secs(30)));
+    let logs = devnet.tail_logs(20).join("\n");
+    devnet.stop();
+    if let Err(e) = result {
+        panic!("{}\n{}", e, logs);
+    }
 }
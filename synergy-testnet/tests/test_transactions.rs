@@ -1,15 +1,19 @@
-use synergy_testnet::node::Blockchain;
+use synergy_testnet::block::{Block, BlockChain};
+use synergy_testnet::transaction::Transaction;
 
 #[test]
 fn test_transaction_processing() {
-    let mut blockchain = Blockchain::new();
+    let mut blockchain = BlockChain::new();
+    blockchain.genesis();
     assert_eq!(blockchain.chain.len(), 1); // Genesis block should exist
 
-    blockchain.add_transaction("Alice".to_string(), "Bob".to_string(), 100);
-    blockchain.mine_block();
+    let tx = Transaction::new("Alice".to_string(), "Bob".to_string(), 100, 0, String::new(), 1, 21_000, None);
+    let hash = tx.hash();
+    let previous_hash = blockchain.last().unwrap().hash.clone();
+    blockchain.add_block(Block::new(1, vec![tx], previous_hash, "validator".to_string(), 0));
 
     assert_eq!(blockchain.chain.len(), 2); // New block should be added
     assert_eq!(blockchain.chain[1].transactions.len(), 1); // Block should contain one transaction
-
-    println!("Blockchain State: {:?}", blockchain.chain);
+    assert_eq!(blockchain.chain[1].transactions[0].hash(), hash);
+    assert_eq!(blockchain.chain[1].transactions[0].amount, 100);
 }
@@ -0,0 +1,104 @@
+use std::path::PathBuf;
+
+use synergy_testnet::crypto::keys::KeyPair;
+use synergy_testnet::keystore::{KdfParams, Keystore};
+use synergy_testnet::transaction::Transaction;
+use synergy_testnet::tx_file::{self, TxFormat, TxOp, DEFAULT_GAS_LIMIT, DEFAULT_GAS_PRICE};
+use synergy_testnet::wallet::WalletManager;
+
+const TEST_KDF: KdfParams = KdfParams { memory_kib: 1024, iterations: 1, parallelism: 1 };
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("synergy-txfile-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn receiver() -> String {
+    WalletManager::generate_address("receiver")
+}
+
+#[test]
+fn test_build_and_encodings() {
+    let sender = KeyPair::generate().address();
+    let tx = tx_file::build(&sender, &TxOp::Transfer { to: receiver() }, 500, 1, DEFAULT_GAS_PRICE, DEFAULT_GAS_LIMIT);
+    assert!(tx.validate_fields().is_valid);
+    assert!(!tx.validate().is_valid, "unsigned transactions are not admitted");
+    assert_eq!(tx.data, None);
+
+    for format in [TxFormat::Json, TxFormat::Bincode] {
+        let (decoded, detected) = tx_file::decode(&tx_file::encode(&tx, format)).unwrap();
+        assert_eq!(detected, format);
+        assert_eq!(decoded.hash(), tx.hash());
+    }
+    assert!(tx_file::decode(b"not a transaction").is_err());
+    let mut trailing = tx.to_bytes();
+    trailing.push(0);
+    assert!(tx_file::decode(&trailing).is_err());
+
+    // Token operations use the encoding the node's wallet uses
+    let stake = TxOp::Stake { validator: receiver(), token: "SNRG".to_string() };
+    let tx = tx_file::build(&sender, &stake, 100, 2, DEFAULT_GAS_PRICE, DEFAULT_GAS_LIMIT);
+    assert_eq!(tx.receiver, receiver());
+    assert_eq!(tx.data, Some(Transaction::stake_data(&receiver(), "SNRG", 100)));
+    let transfer = TxOp::TokenTransfer { to: receiver(), token: "SNRG".to_string() };
+    let tx = tx_file::build(&sender, &transfer, 100, 3, DEFAULT_GAS_PRICE, DEFAULT_GAS_LIMIT);
+    assert!(tx.data.unwrap().starts_with("token_transfer:"));
+}
+
+#[test]
+fn test_sign_with_keystore() {
+    let dir = temp_dir("sign");
+    let keystore = Keystore::new(dir.join("keystore")).with_kdf(TEST_KDF);
+    let entry = keystore.create("correct horse").unwrap();
+
+    let tx = tx_file::build(&entry.address, &TxOp::Transfer { to: receiver() }, 500, 1, DEFAULT_GAS_PRICE, DEFAULT_GAS_LIMIT);
+    let path = dir.join("tx.bin");
+    tx_file::write(&path, &tx, TxFormat::Bincode).unwrap();
+
+    let (mut tx, format) = tx_file::read(&path).unwrap();
+    assert!(tx_file::sign(&mut tx, &keystore, "wrong horse").is_err());
+    tx_file::sign(&mut tx, &keystore, "correct horse").unwrap();
+    tx_file::write(&path, &tx, format).unwrap();
+
+    let (signed, _) = tx_file::read(&path).unwrap();
+    assert!(signed.verify_signature());
+    assert!(signed.validate().is_valid, "{:?}", signed.validate().error_message);
+    assert_eq!(signed.public_key, keystore.find(&entry.address).unwrap().1.public_key);
+    let summary = tx_file::describe(&signed);
+    assert!(summary.contains(&("Signature", "valid".to_string())));
+    assert!(summary.contains(&("Fee", (DEFAULT_GAS_PRICE * DEFAULT_GAS_LIMIT).to_string())));
+
+    let mut tampered = signed.clone();
+    tampered.amount += 1;
+    assert!(!tampered.verify_signature());
+    assert!(tx_file::describe(&tampered).contains(&("Signature", "INVALID".to_string())));
+
+    // A key that does not belong to the sender is rejected
+    let mut forged = signed.clone();
+    forged.public_key = KeyPair::generate().public_key_hex();
+    assert!(!forged.verify_signature());
+
+    // Only the sender's key can sign
+    let mut foreign = tx_file::build(&receiver(), &TxOp::Transfer { to: entry.address.clone() }, 1, 1, 1, 1);
+    assert!(tx_file::sign(&mut foreign, &keystore, "correct horse").is_err());
+}
+
+#[test]
+fn test_node_wallet_and_offline_signatures_match() {
+    let keystore = Keystore::new(temp_dir("wallet")).with_kdf(TEST_KDF);
+    let mut manager = WalletManager::new();
+    manager.open_keystore(keystore.clone()).unwrap();
+    let address = manager.create_wallet("correct horse").unwrap();
+    manager.unlock_wallet(&address, "correct horse", None).unwrap();
+
+    let mut tx = tx_file::build(&address, &TxOp::Transfer { to: receiver() }, 10, 1, DEFAULT_GAS_PRICE, DEFAULT_GAS_LIMIT);
+    let mut offline = tx.clone();
+    manager.sign_transaction(&address, &mut tx).unwrap();
+    tx_file::sign(&mut offline, &keystore, "correct horse").unwrap();
+
+    assert!(tx.verify_signature() && offline.verify_signature());
+    assert!(manager.verify_signature(&offline));
+    assert_eq!(tx.public_key, offline.public_key);
+}
@@ -9,16 +9,19 @@ class RPCService {
   constructor() {
     this.endpoints = RPC_ENDPOINTS;
     this.currentEndpoint = RPC_ENDPOINTS.testnet;
+    this.authToken = null;
   }
 
   async call(method, params = []) {
     try {
+      // Admin methods such as the log calls need the node's RPC token
+      const headers = this.authToken ? { Authorization: `Bearer ${this.authToken}` } : {};
       const response = await axios.post(this.currentEndpoint, {
         jsonrpc: '2.0',
         method: method,
         params: params,
         id: Date.now(),
-      });
+      }, { headers });
 
       if (response.data.error) {
         throw new Error(response.data.error.message);
@@ -67,6 +70,20 @@ class RPCService {
     return this.call('synergy_updateValidatorPerformance', [update]);
   }
 
+  // Node logs (admin namespace)
+  // filters: { level, module, since, until, limit }
+  async getLogs(filters = {}) {
+    return this.call('synergy_getLogs', filters);
+  }
+
+  async getLogLevels() {
+    return this.call('synergy_getLogLevels');
+  }
+
+  async setLogLevel(level, module = null) {
+    return this.call('synergy_setLogLevel', module ? { level, module } : { level });
+  }
+
   // Utility methods
   setEndpoint(endpoint) {
     if (this.endpoints[endpoint]) {
@@ -80,6 +97,11 @@ class RPCService {
     return this.currentEndpoint;
   }
 
+  // Contents of the node's token_file, or a JWT signed with it; null to stop sending one
+  setAuthToken(token) {
+    this.authToken = token || null;
+  }
+
   isConnected() {
     return this.call('eth_blockNumber').then(() => true).catch(() => false);
   }